@@ -21,6 +21,8 @@ pub mod functions;
 pub mod methods;
 pub mod predicates;
 pub mod purification;
+pub mod slicing;
+pub mod splitting;
 
 fn log_method(
     source_file_name: &str,
@@ -0,0 +1,115 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Slicing of a program to the definitions needed to verify its methods.
+//!
+//! The unused predicates are removed by `delete_unused_predicates`; this
+//! module removes the functions and the built-in methods that are not
+//! (transitively) used by the methods, the predicates, or the domain axioms
+//! of the program.
+
+use crate::vir::polymorphic_vir::{
+    ast::*,
+    utils::{walk_functions, walk_methods},
+    Program,
+};
+use fxhash::FxHashSet;
+use log::debug;
+use vir::common::identifier::WithIdentifier;
+
+/// Removes the functions and built-in methods that are not used by the
+/// methods of the program.
+pub fn slice_program(mut program: Program) -> Program {
+    let mut collector = UsedDefinitionCollector::default();
+    walk_methods(&program.methods, &mut collector);
+    for predicate in &program.viper_predicates {
+        if let Some(body) = predicate.body() {
+            ExprWalker::walk(&mut collector, &body);
+        }
+    }
+    for domain in &program.domains {
+        for axiom in &domain.axioms {
+            ExprWalker::walk(&mut collector, &axiom.expr);
+        }
+    }
+    for method in &program.builtin_methods {
+        if collector.used_methods.contains(&method.name) {
+            for expr in method.pres.iter().chain(&method.posts) {
+                ExprWalker::walk(&mut collector, expr);
+            }
+        }
+    }
+    // Functions can call each other, so we walk the newly used functions
+    // until no new function is found.
+    let mut visited_functions = FxHashSet::default();
+    loop {
+        let new_functions: Vec<_> = program
+            .functions
+            .iter()
+            .filter(|function| {
+                let identifier = function.get_identifier();
+                collector.used_functions.contains(&identifier)
+                    && !visited_functions.contains(&identifier)
+            })
+            .cloned()
+            .collect();
+        if new_functions.is_empty() {
+            break;
+        }
+        visited_functions.extend(new_functions.iter().map(|function| function.get_identifier()));
+        walk_functions(&new_functions, &mut collector);
+    }
+    program.functions.retain(|function| {
+        let is_used = visited_functions.contains(&function.get_identifier());
+        if !is_used {
+            debug!("Removed unused function {}", function.get_identifier());
+        }
+        is_used
+    });
+    program.builtin_methods.retain(|method| {
+        let is_used = collector.used_methods.contains(&method.name);
+        if !is_used {
+            debug!("Removed unused method {}", method.name);
+        }
+        is_used
+    });
+    program
+}
+
+#[derive(Default)]
+struct UsedDefinitionCollector {
+    /// The identifiers of the called functions.
+    used_functions: FxHashSet<String>,
+    /// The names of the called methods.
+    used_methods: FxHashSet<String>,
+}
+
+impl ExprWalker for UsedDefinitionCollector {
+    fn walk_func_app(&mut self, func_app: &FuncApp) {
+        self.used_functions.insert(compute_identifier(
+            &func_app.function_name,
+            &func_app.type_arguments,
+            &func_app.formal_arguments,
+            &func_app.return_type,
+        ));
+        for argument in &func_app.arguments {
+            ExprWalker::walk(self, argument);
+        }
+    }
+}
+
+impl StmtWalker for UsedDefinitionCollector {
+    fn walk_expr(&mut self, expr: &Expr) {
+        ExprWalker::walk(self, expr);
+    }
+
+    fn walk_method_call(&mut self, method_call: &MethodCall) {
+        self.used_methods.insert(method_call.method_name.clone());
+        for argument in &method_call.arguments {
+            ExprWalker::walk(self, argument);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Splits the methods whose encoding is too large for the verifier into
+//! several methods that are verified independently.
+//!
+//! The blocks of a method are partitioned in topological order into parts of
+//! roughly `max_size` statements. The method generated for a part checks only
+//! the blocks of that part:
+//!
+//! * the blocks of the earlier parts are kept, but their pure assertions are
+//!   turned into assumptions, so that they only establish the state in which
+//!   the part is verified;
+//! * the blocks of the later parts are cut off with `inhale false`.
+//!
+//! Since the assertions of each block are checked by exactly one of the
+//! generated methods, the split program is verified if and only if the
+//! original one is. However, a failing permission check (for example, of an
+//! `exhale` or a `fold`) in a block is reported by all methods whose parts
+//! come after the block.
+
+use crate::vir::polymorphic_vir::{
+    ast::{Assert, Exhale, Expr, Inhale, Stmt},
+    cfg::{CfgMethod, Successor},
+};
+use log::debug;
+use std::cmp::Ordering;
+
+pub fn split_methods(methods: Vec<CfgMethod>, max_size: usize) -> Vec<CfgMethod> {
+    methods
+        .into_iter()
+        .flat_map(|method| split_method(method, max_size))
+        .collect()
+}
+
+fn split_method(method: CfgMethod, max_size: usize) -> Vec<CfgMethod> {
+    let size: usize = method
+        .basic_blocks
+        .iter()
+        .map(|block| block.stmts.len())
+        .sum();
+    if size <= max_size || method.has_loops() {
+        return vec![method];
+    }
+    let mut block_parts = vec![0; method.basic_blocks.len()];
+    let mut part = 0;
+    let mut part_size = 0;
+    for index in method.get_topological_sort() {
+        let block_size = method.basic_blocks[index.index()].stmts.len();
+        if part_size > 0 && part_size + block_size > max_size {
+            part += 1;
+            part_size = 0;
+        }
+        block_parts[index.index()] = part;
+        part_size += block_size;
+    }
+    let part_count = part + 1;
+    if part_count == 1 {
+        return vec![method];
+    }
+    debug!(
+        "Splitting method {} with {} statements into {} parts",
+        method.name(),
+        size,
+        part_count
+    );
+    (0..part_count)
+        .map(|part| {
+            let mut part_method = method.clone();
+            part_method.set_name(format!("{}$part{}", method.name(), part));
+            for (block, block_part) in part_method.basic_blocks.iter_mut().zip(&block_parts) {
+                match block_part.cmp(&part) {
+                    Ordering::Less => {
+                        for stmt in &mut block.stmts {
+                            assume_check(stmt);
+                        }
+                    }
+                    Ordering::Equal => {}
+                    Ordering::Greater => {
+                        block.stmts = vec![Stmt::Inhale(Inhale { expr: false.into() })];
+                        block.successor = Successor::Return;
+                    }
+                }
+            }
+            part_method
+        })
+        .collect()
+}
+
+/// Replaces an assertion that does not remove permissions by an assumption.
+fn assume_check(stmt: &mut Stmt) {
+    let expr: Expr = match stmt {
+        Stmt::Assert(Assert { expr, .. }) => expr.clone(),
+        Stmt::Exhale(Exhale { expr, .. }) if expr.is_pure() => expr.clone(),
+        _ => return,
+    };
+    *stmt = Stmt::Inhale(Inhale { expr });
+}
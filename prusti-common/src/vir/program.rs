@@ -1,4 +1,8 @@
 use super::low_to_viper::{Context, ToViper};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
 use viper::{self, AstFactory};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Hash, Eq, PartialEq)]
@@ -29,6 +33,78 @@ impl Program {
     pub fn get_name_with_check_mode(&self) -> String {
         format!("{}-{}", self.get_name(), self.get_check_mode())
     }
+    pub fn get_domains(&self) -> Vec<ProgramDomain> {
+        match self {
+            Program::Legacy(program) => program
+                .domains
+                .iter()
+                .cloned()
+                .map(ProgramDomain::Legacy)
+                .collect(),
+            Program::Low(program) => program
+                .domains
+                .iter()
+                .cloned()
+                .map(ProgramDomain::Low)
+                .collect(),
+        }
+    }
+    /// Removes the domains for whose hash `is_removed` holds and returns the
+    /// hashes of the removed domains.
+    pub fn remove_domains(&mut self, mut is_removed: impl FnMut(u64) -> bool) -> Vec<u64> {
+        let mut removed = Vec::new();
+        let mut keep = |hash| {
+            if is_removed(hash) {
+                removed.push(hash);
+                false
+            } else {
+                true
+            }
+        };
+        match self {
+            Program::Legacy(program) => program.domains.retain(|domain| keep(hash_of(domain))),
+            Program::Low(program) => program.domains.retain(|domain| keep(hash_of(domain))),
+        }
+        removed
+    }
+    /// Adds the domain to the program. Returns `false` if the domain is of
+    /// the encoding of another kind of program.
+    pub fn add_domain(&mut self, domain: ProgramDomain) -> bool {
+        match (self, domain) {
+            (Program::Legacy(program), ProgramDomain::Legacy(domain)) => {
+                program.domains.push(domain)
+            }
+            (Program::Low(program), ProgramDomain::Low(domain)) => program.domains.push(domain),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// A domain of a program. The domains that encode common types, such as the
+/// ones of the standard library, are shared between the programs of
+/// different crates.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Hash, Eq, PartialEq)]
+pub enum ProgramDomain {
+    Legacy(vir::legacy::Domain),
+    Low(vir::low::DomainDecl),
+}
+
+impl ProgramDomain {
+    /// The hash that identifies the domain, which is the same as the one used
+    /// by `Program::remove_domains`.
+    pub fn get_hash(&self) -> u64 {
+        match self {
+            ProgramDomain::Legacy(domain) => hash_of(domain),
+            ProgramDomain::Low(domain) => hash_of(domain),
+        }
+    }
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
 }
 
 impl<'v> ToViper<'v, viper::Program<'v>> for Program {
@@ -0,0 +1,152 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::vir::{program::Program, ExprWalker};
+use vir::{
+    legacy,
+    low::{
+        self as vir_low,
+        ast::statement::visitors::{default_walk_statement, StatementWalker},
+        expression::visitors::{default_walk_expression, ExpressionWalker},
+    },
+};
+
+/// Size and complexity metrics of a program sent to the verifier. They help
+/// to correlate slow verification with the size of the encoding.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ProgramMetrics {
+    pub name: String,
+    /// The number of methods and procedures that have a body.
+    pub methods: usize,
+    pub statements: usize,
+    /// The number of expression nodes, including sub-expressions.
+    pub expressions: usize,
+    pub quantifiers: usize,
+    pub predicates: usize,
+    /// The number of predicate access predicates, unfoldings, folds, and
+    /// unfolds.
+    pub predicate_instances: usize,
+    pub functions: usize,
+    pub domains: usize,
+    pub domain_functions: usize,
+    pub domain_axioms: usize,
+}
+
+impl ProgramMetrics {
+    pub fn compute(program: &Program) -> Self {
+        let mut metrics = ProgramMetrics {
+            name: program.get_name_with_check_mode(),
+            ..Default::default()
+        };
+        match program {
+            Program::Legacy(program) => metrics.add_legacy_program(program),
+            Program::Low(program) => metrics.add_low_program(program),
+        }
+        metrics
+    }
+
+    fn add_legacy_program(&mut self, program: &legacy::Program) {
+        self.methods = program.methods.len();
+        self.predicates = program.viper_predicates.len();
+        self.functions = program.functions.len();
+        self.domains = program.domains.len();
+        for domain in &program.domains {
+            self.domain_functions += domain.functions.len();
+            self.domain_axioms += domain.axioms.len();
+            for axiom in &domain.axioms {
+                ExprWalker::walk(self, &axiom.expr);
+            }
+        }
+        program.visit_statements(|statement| {
+            self.statements += 1;
+            if matches!(statement, legacy::Stmt::Fold(..) | legacy::Stmt::Unfold(..)) {
+                self.predicate_instances += 1;
+            }
+        });
+        program.visit_expressions(|expression| ExprWalker::walk(self, expression));
+    }
+
+    fn add_low_program(&mut self, program: &vir_low::Program) {
+        self.predicates = program.predicates.len();
+        self.functions = program.functions.len();
+        self.domains = program.domains.len();
+        for domain in &program.domains {
+            self.domain_functions += domain.functions.len();
+            self.domain_axioms += domain.axioms.len();
+            for axiom in &domain.axioms {
+                ExpressionWalker::walk_expression(self, &axiom.body);
+            }
+        }
+        for predicate in &program.predicates {
+            if let Some(body) = &predicate.body {
+                ExpressionWalker::walk_expression(self, body);
+            }
+        }
+        for function in &program.functions {
+            let body = function.body.iter();
+            for expression in function.pres.iter().chain(&function.posts).chain(body) {
+                ExpressionWalker::walk_expression(self, expression);
+            }
+        }
+        for method in &program.methods {
+            for expression in method.pres.iter().chain(&method.posts) {
+                ExpressionWalker::walk_expression(self, expression);
+            }
+            if let Some(body) = &method.body {
+                self.methods += 1;
+                for statement in body {
+                    StatementWalker::walk_statement(self, statement);
+                }
+            }
+        }
+        for procedure in &program.procedures {
+            self.methods += 1;
+            for block in &procedure.basic_blocks {
+                for statement in &block.statements {
+                    StatementWalker::walk_statement(self, statement);
+                }
+            }
+        }
+    }
+}
+
+impl ExprWalker for ProgramMetrics {
+    fn walk(&mut self, expression: &legacy::Expr) {
+        self.expressions += 1;
+        match expression {
+            legacy::Expr::ForAll(..) | legacy::Expr::Exists(..) => self.quantifiers += 1,
+            legacy::Expr::PredicateAccessPredicate(..) | legacy::Expr::Unfolding(..) => {
+                self.predicate_instances += 1;
+            }
+            _ => {}
+        }
+        legacy::default_walk_expr(self, expression);
+    }
+}
+
+impl ExpressionWalker for ProgramMetrics {
+    fn walk_expression(&mut self, expression: &vir_low::Expression) {
+        self.expressions += 1;
+        match expression {
+            vir_low::Expression::Quantifier(_) => self.quantifiers += 1,
+            vir_low::Expression::PredicateAccessPredicate(_)
+            | vir_low::Expression::Unfolding(_) => self.predicate_instances += 1,
+            _ => {}
+        }
+        default_walk_expression(self, expression);
+    }
+}
+
+impl StatementWalker for ProgramMetrics {
+    fn walk_statement(&mut self, statement: &vir_low::Statement) {
+        self.statements += 1;
+        default_walk_statement(self, statement);
+    }
+
+    fn walk_expression(&mut self, expression: &vir_low::Expression) {
+        ExpressionWalker::walk_expression(self, expression);
+    }
+}
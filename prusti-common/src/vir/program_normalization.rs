@@ -105,18 +105,28 @@ impl NormalizationInfo {
 
     /// Denormalize a verification result.
     pub fn denormalize_result(&self, result: &mut VerificationResult) {
-        if let VerificationResult::Failure(ref mut ver_errors) = result {
-            ver_errors.iter_mut().for_each(|ver_error| {
-                if let Some(pos) = ver_error.pos_id.as_mut() {
-                    self.denormalize_position_string(pos);
-                }
-                if let Some(pos) = ver_error.offending_pos_id.as_mut() {
-                    self.denormalize_position_string(pos);
-                }
-                if let Some(pos) = ver_error.reason_pos_id.as_mut() {
-                    self.denormalize_position_string(pos);
-                }
-            });
+        match result {
+            VerificationResult::Failure(ref mut ver_errors) => {
+                ver_errors.iter_mut().for_each(|ver_error| {
+                    if let Some(pos) = ver_error.pos_id.as_mut() {
+                        self.denormalize_position_string(pos);
+                    }
+                    if let Some(pos) = ver_error.offending_pos_id.as_mut() {
+                        self.denormalize_position_string(pos);
+                    }
+                    if let Some(pos) = ver_error.reason_pos_id.as_mut() {
+                        self.denormalize_position_string(pos);
+                    }
+                });
+            }
+            VerificationResult::ConsistencyErrors(ref mut errors) => {
+                errors.iter_mut().for_each(|error| {
+                    if let Some(pos) = error.pos_id.as_mut() {
+                        self.denormalize_position_string(pos);
+                    }
+                });
+            }
+            _ => {}
         }
     }
 }
@@ -100,6 +100,13 @@ pub fn ghost(_tokens: TokenStream) -> TokenStream {
     TokenStream::new()
 }
 
+#[cfg(not(feature = "prusti"))]
+#[proc_macro]
+pub fn trusted_block(tokens: TokenStream) -> TokenStream {
+    // The block follows its specifications.
+    tokens.into_iter().last().into_iter().collect()
+}
+
 #[cfg(not(feature = "prusti"))]
 #[proc_macro_attribute]
 pub fn print_counterexample(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
@@ -118,6 +125,30 @@ pub fn body_variant(_tokens: TokenStream) -> TokenStream {
     TokenStream::new()
 }
 
+#[cfg(not(feature = "prusti"))]
+#[proc_macro_attribute]
+pub fn verification_timeout(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[cfg(not(feature = "prusti"))]
+#[proc_macro_attribute]
+pub fn verification_budget(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[cfg(not(feature = "prusti"))]
+#[proc_macro_attribute]
+pub fn backend(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[cfg(not(feature = "prusti"))]
+#[proc_macro_attribute]
+pub fn nonlinear(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
 // ----------------------
 // --- PRUSTI ENABLED ---
 
@@ -236,6 +267,12 @@ pub fn ghost(tokens: TokenStream) -> TokenStream {
     prusti_specs::ghost(tokens.into()).into()
 }
 
+#[cfg(feature = "prusti")]
+#[proc_macro]
+pub fn trusted_block(tokens: TokenStream) -> TokenStream {
+    prusti_specs::trusted_block(tokens.into()).into()
+}
+
 #[cfg(feature = "prusti")]
 #[proc_macro_attribute]
 pub fn print_counterexample(attr: TokenStream, tokens: TokenStream) -> TokenStream {
@@ -254,5 +291,29 @@ pub fn body_variant(tokens: TokenStream) -> TokenStream {
     prusti_specs::body_variant(tokens.into()).into()
 }
 
+#[cfg(feature = "prusti")]
+#[proc_macro_attribute]
+pub fn verification_timeout(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::verification_timeout(attr.into(), tokens.into()).into()
+}
+
+#[cfg(feature = "prusti")]
+#[proc_macro_attribute]
+pub fn verification_budget(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::verification_budget(attr.into(), tokens.into()).into()
+}
+
+#[cfg(feature = "prusti")]
+#[proc_macro_attribute]
+pub fn backend(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::backend(attr.into(), tokens.into()).into()
+}
+
+#[cfg(feature = "prusti")]
+#[proc_macro_attribute]
+pub fn nonlinear(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::nonlinear(attr.into(), tokens.into()).into()
+}
+
 // Ensure that you've also crated a transparent `#[cfg(not(feature = "prusti"))]`
 // version of your new macro above!
@@ -16,3 +16,168 @@ impl<T, E: ::core::fmt::Debug> ::core::result::Result<T, E> {
     #[requires(matches!(self, Ok(_)))]
     fn unwrap(self) -> T;
 }
+
+// The invariant of the `NonZero*` types: their value is never zero.
+macro_rules! nonzero_specs {
+    ($($nonzero:ident($int:ident);)*) => {
+        $(
+            #[extern_spec]
+            impl ::core::num::$nonzero {
+                #[ensures(match result {
+                    Some(nonzero) => n != 0 && nonzero.get() == n,
+                    None => n == 0,
+                })]
+                fn new(n: $int) -> Option<Self>;
+
+                #[pure]
+                #[ensures(result != 0)]
+                fn get(self) -> $int;
+            }
+        )*
+    };
+}
+
+nonzero_specs! {
+    NonZeroU8(u8);
+    NonZeroU16(u16);
+    NonZeroU32(u32);
+    NonZeroU64(u64);
+    NonZeroU128(u128);
+    NonZeroUsize(usize);
+    NonZeroI8(i8);
+    NonZeroI16(i16);
+    NonZeroI32(i32);
+    NonZeroI64(i64);
+    NonZeroI128(i128);
+    NonZeroIsize(isize);
+}
+
+// The byte representations of the integer types. A byte array is related to
+// the integer value through the weighted sum of its bytes; the weights are
+// listed per byte with its index in the little- and big-endian order. The
+// native-endian functions and the pointer-sized types are not covered because
+// they depend on the target.
+macro_rules! unsigned_bytes_specs {
+    ($($int:ident: [$($le:literal / $be:literal => $weight:literal),*];)*) => {
+        $(
+            #[extern_spec]
+            impl $int {
+                #[pure]
+                #[ensures($(result[$le] as $int == self / $weight % 256)&&*)]
+                fn to_le_bytes(self) -> [u8; ::core::mem::size_of::<$int>()];
+
+                #[pure]
+                #[ensures($(result[$be] as $int == self / $weight % 256)&&*)]
+                fn to_be_bytes(self) -> [u8; ::core::mem::size_of::<$int>()];
+
+                #[pure]
+                #[ensures(result == 0 $(+ bytes[$le] as $int * $weight)*)]
+                fn from_le_bytes(bytes: [u8; ::core::mem::size_of::<$int>()]) -> $int;
+
+                #[pure]
+                #[ensures(result == 0 $(+ bytes[$be] as $int * $weight)*)]
+                fn from_be_bytes(bytes: [u8; ::core::mem::size_of::<$int>()]) -> $int;
+            }
+        )*
+    };
+}
+
+unsigned_bytes_specs! {
+    u16: [
+        0 / 1 => 1,
+        1 / 0 => 256
+    ];
+    u32: [
+        0 / 3 => 1,
+        1 / 2 => 256,
+        2 / 1 => 65536,
+        3 / 0 => 16777216
+    ];
+    u64: [
+        0 / 7 => 1,
+        1 / 6 => 256,
+        2 / 5 => 65536,
+        3 / 4 => 16777216,
+        4 / 3 => 4294967296,
+        5 / 2 => 1099511627776,
+        6 / 1 => 281474976710656,
+        7 / 0 => 72057594037927936
+    ];
+    u128: [
+        0 / 15 => 1,
+        1 / 14 => 256,
+        2 / 13 => 65536,
+        3 / 12 => 16777216,
+        4 / 11 => 4294967296,
+        5 / 10 => 1099511627776,
+        6 / 9 => 281474976710656,
+        7 / 8 => 72057594037927936,
+        8 / 7 => 18446744073709551616,
+        9 / 6 => 4722366482869645213696,
+        10 / 5 => 1208925819614629174706176,
+        11 / 4 => 309485009821345068724781056,
+        12 / 3 => 79228162514264337593543950336,
+        13 / 2 => 20282409603651670423947251286016,
+        14 / 1 => 5192296858534827628530496329220096,
+        15 / 0 => 1329227995784915872903807060280344576
+    ];
+}
+
+// A signed integer is represented by the bytes of its two's complement, which
+// is the value itself if it is not negative, and the value plus `$modulus`
+// otherwise. The arithmetic is done in `i128`, so `i128` itself is not covered.
+macro_rules! signed_bytes_specs {
+    ($($int:ident($modulus:literal): [$($le:literal / $be:literal => $weight:literal),*];)*) => {
+        $(
+            #[extern_spec]
+            impl $int {
+                #[pure]
+                #[ensures($(
+                    result[$le] as i128
+                        == (if self >= 0 { self as i128 } else { self as i128 + $modulus }) / $weight % 256
+                )&&*)]
+                fn to_le_bytes(self) -> [u8; ::core::mem::size_of::<$int>()];
+
+                #[pure]
+                #[ensures($(
+                    result[$be] as i128
+                        == (if self >= 0 { self as i128 } else { self as i128 + $modulus }) / $weight % 256
+                )&&*)]
+                fn to_be_bytes(self) -> [u8; ::core::mem::size_of::<$int>()];
+
+                #[pure]
+                #[ensures(result as i128 == 0 $(+ bytes[$le] as i128 * $weight)*
+                    - if result >= 0 { 0 } else { $modulus })]
+                fn from_le_bytes(bytes: [u8; ::core::mem::size_of::<$int>()]) -> $int;
+
+                #[pure]
+                #[ensures(result as i128 == 0 $(+ bytes[$be] as i128 * $weight)*
+                    - if result >= 0 { 0 } else { $modulus })]
+                fn from_be_bytes(bytes: [u8; ::core::mem::size_of::<$int>()]) -> $int;
+            }
+        )*
+    };
+}
+
+signed_bytes_specs! {
+    i16(65536): [
+        0 / 1 => 1,
+        1 / 0 => 256
+    ];
+    i32(4294967296): [
+        0 / 3 => 1,
+        1 / 2 => 256,
+        2 / 1 => 65536,
+        3 / 0 => 16777216
+    ];
+    i64(18446744073709551616): [
+        0 / 7 => 1,
+        1 / 6 => 256,
+        2 / 5 => 65536,
+        3 / 4 => 16777216,
+        4 / 3 => 4294967296,
+        5 / 2 => 1099511627776,
+        6 / 1 => 281474976710656,
+        7 / 0 => 72057594037927936
+    ];
+}
@@ -0,0 +1,98 @@
+//! Lemmas about multiplication, division and remainder of the unsigned
+//! integer types.
+//!
+//! The SMT solver does not reason about nonlinear arithmetic by default, so
+//! goals such as `a * c <= b * c` are not proven even if `a <= b` is known.
+//! Calling a lemma adds its postcondition as a fact at the call site, where
+//! its precondition has to hold. The lemmas are trusted: the facts are
+//! standard properties of the integers and do not depend on the arithmetic of
+//! the Rust types, because the arithmetic in specifications does not overflow.
+//! At run time, the lemmas do nothing.
+//!
+//! A function whose own nonlinear goals are beyond these lemmas can be
+//! verified with the nonlinear reasoning of the solver by annotating it with
+//! `#[nonlinear]`.
+
+macro_rules! lemmas {
+    ($($int:ident),*) => {
+        $(
+            #[doc = concat!("Lemmas about the arithmetic of `", stringify!($int), "`.")]
+            #[allow(unused_variables)]
+            pub mod $int {
+                use crate::*;
+
+                /// Multiplication is monotonic.
+                #[trusted]
+                #[requires(a <= b)]
+                #[ensures(a * c <= b * c)]
+                pub fn mul_monotonic(a: $int, b: $int, c: $int) {}
+
+                /// Multiplication by a positive factor is strictly monotonic.
+                #[trusted]
+                #[requires(a < b && c > 0)]
+                #[ensures(a * c < b * c)]
+                pub fn mul_strictly_monotonic(a: $int, b: $int, c: $int) {}
+
+                /// The product of smaller factors is smaller.
+                #[trusted]
+                #[requires(a <= b && c <= d)]
+                #[ensures(a * c <= b * d)]
+                pub fn mul_le_mul(a: $int, b: $int, c: $int, d: $int) {}
+
+                /// Multiplication distributes over addition.
+                #[trusted]
+                #[ensures(a * (b + c) == a * b + a * c)]
+                pub fn mul_distributes(a: $int, b: $int, c: $int) {}
+
+                /// Multiplication is associative.
+                #[trusted]
+                #[ensures(a * (b * c) == (a * b) * c)]
+                pub fn mul_associative(a: $int, b: $int, c: $int) {}
+
+                /// The quotient and the remainder of a division recompose
+                /// the dividend, and the remainder is smaller than the divisor.
+                #[trusted]
+                #[requires(b > 0)]
+                #[ensures(a == (a / b) * b + a % b)]
+                #[ensures(a % b < b)]
+                pub fn div_mod(a: $int, b: $int) {}
+
+                /// The quotient times the divisor is at most the dividend, and
+                /// less than a divisor away from it.
+                #[trusted]
+                #[requires(b > 0)]
+                #[ensures((a / b) * b <= a)]
+                #[ensures(a < (a / b) * b + b)]
+                pub fn div_bounds(a: $int, b: $int) {}
+
+                /// Division is monotonic in the dividend.
+                #[trusted]
+                #[requires(a <= b && c > 0)]
+                #[ensures(a / c <= b / c)]
+                pub fn div_monotonic(a: $int, b: $int, c: $int) {}
+
+                /// Dividing by a larger divisor gives a smaller quotient.
+                #[trusted]
+                #[requires(0 < b && b <= c)]
+                #[ensures(a / c <= a / b)]
+                pub fn div_antitonic(a: $int, b: $int, c: $int) {}
+
+                /// Division cancels multiplication.
+                #[trusted]
+                #[requires(b > 0)]
+                #[ensures((a * b) / b == a)]
+                #[ensures((a * b) % b == 0)]
+                pub fn mul_div_cancel(a: $int, b: $int) {}
+
+                /// The remainder of a sum is the remainder of the sum of the
+                /// remainders.
+                #[trusted]
+                #[requires(c > 0)]
+                #[ensures((a + b) % c == (a % c + b % c) % c)]
+                pub fn mod_add(a: $int, b: $int, c: $int) {}
+            }
+        )*
+    };
+}
+
+lemmas!(u8, u16, u32, u64, u128, usize);
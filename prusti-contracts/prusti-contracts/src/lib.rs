@@ -51,6 +51,11 @@ pub use prusti_contracts_proc_macros::ghost_constraint;
 /// but omitted during compilation.
 pub use prusti_contracts_proc_macros::ghost;
 
+/// A macro for blocks whose bodies are trusted to satisfy their
+/// specifications, which are written as `#[requires(..)]` and `#[ensures(..)]`
+/// attributes before the block.
+pub use prusti_contracts_proc_macros::trusted_block;
+
 /// A macro to customize how a struct or enum should be printed in a counterexample
 pub use prusti_contracts_proc_macros::print_counterexample;
 
@@ -60,6 +65,18 @@ pub use prusti_contracts_proc_macros::terminates;
 /// A macro to annotate body variant of a loop to prove termination
 pub use prusti_contracts_proc_macros::body_variant;
 
+/// A macro to set the time budget (in milliseconds) for verifying a function
+pub use prusti_contracts_proc_macros::verification_timeout;
+
+/// A macro to set the time and quantifier instantiation budgets for verifying a function
+pub use prusti_contracts_proc_macros::verification_budget;
+
+/// A macro to select the Viper backend (`silicon` or `carbon`) that verifies a function
+pub use prusti_contracts_proc_macros::backend;
+
+/// A macro to verify a function with the nonlinear arithmetic reasoning of the SMT solver
+pub use prusti_contracts_proc_macros::nonlinear;
+
 #[cfg(not(feature = "prusti"))]
 mod private {
     use core::marker::PhantomData;
@@ -108,6 +125,10 @@ mod private {
 #[cfg(feature = "prusti")]
 pub mod core_spec;
 
+pub mod lemmas;
+
+pub mod time_credits;
+
 #[cfg(feature = "prusti")]
 mod private {
     use core::{marker::PhantomData, ops::*};
@@ -119,6 +140,18 @@ mod private {
         unreachable!();
     }
 
+    /// Havocs a place that a trusted block modifies.
+    #[trusted]
+    pub fn prusti_trusted_block_havoc<T>(_place: &mut T) {
+        unreachable!();
+    }
+
+    /// An unknown value of a trusted block.
+    #[trusted]
+    pub fn prusti_trusted_block_result<T>() -> T {
+        unreachable!();
+    }
+
     #[pure]
     pub fn prusti_terminates_trusted() -> Int {
         Int::new(1)
@@ -338,4 +371,21 @@ pub fn snapshot_equality<T>(_l: T, _r: T) -> bool {
     true
 }
 
+/// Unfolding of a predicate.
+///
+/// This is a Prusti-internal representation of the `unfolding!` syntax.
+pub fn unfolding(_predicate: bool, _body: bool) -> bool {
+    true
+}
+
+/// Evaluates the assertion `$body` with the definition of the predicate call
+/// `$predicate` unfolded. Like the unfolding of a Viper predicate, the
+/// predicate has to hold.
+#[macro_export]
+macro_rules! unfolding {
+    ($predicate:expr, $body:expr) => {
+        $crate::unfolding($predicate, $body)
+    };
+}
+
 pub use private::*;
@@ -6,6 +6,8 @@ use proc_macro2::Ident;
 use syn::{GenericParam, parse_quote, TypeParam};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
 use uuid::Uuid;
 pub(crate) use syn_extensions::*;
 pub(crate) use self_type_rewriter::*;
@@ -405,6 +407,146 @@ pub(crate) fn merge_generics<T: HasGenerics>(target: &mut T, source: &T) {
     }
 }
 
+/// Makes the elided lifetimes in the return type of a function explicit,
+/// following the lifetime elision rules: they refer to the lifetime of the
+/// `&self` or `&mut self` receiver or, if there is no such receiver, to the
+/// only lifetime of the inputs. An elided lifetime of the inputs from which
+/// the output lifetimes are taken is made explicit as well.
+///
+/// This is needed when the signature is copied to a function in which the
+/// output becomes an input, such as the `result` argument of a postcondition,
+/// because elided lifetimes in inputs are independent of each other. For
+/// example, the postcondition of
+/// ```ignore
+/// fn next(&mut self) -> Option<Self::Item<'_>>
+/// ```
+/// uses the signature
+/// ```ignore
+/// fn next<'prusti_elided_lifetime>(&'prusti_elided_lifetime mut self)
+///     -> Option<Self::Item<'prusti_elided_lifetime>>
+/// ```
+/// because the generic associated type `Item<'a>` may require `Self: 'a`.
+pub(crate) fn with_explicit_output_lifetimes(sig: &syn::Signature) -> syn::Signature {
+    let mut new_sig = sig.clone();
+    let mut output_lifetimes = LifetimeCollector::default();
+    output_lifetimes.visit_return_type(&sig.output);
+    if output_lifetimes.elided == 0 {
+        return new_sig;
+    }
+
+    let new_lifetime: syn::Lifetime = parse_quote!('prusti_elided_lifetime);
+    let receiver_lifetime = match new_sig.inputs.first_mut() {
+        Some(syn::FnArg::Receiver(syn::Receiver {
+            reference: Some((_, lifetime)),
+            ..
+        })) => Some(lifetime),
+        _ => None,
+    };
+    let lifetime = match receiver_lifetime {
+        Some(Some(lifetime)) if lifetime.ident != "_" => lifetime.clone(),
+        Some(lifetime) => {
+            *lifetime = Some(new_lifetime.clone());
+            add_lifetime_param(&mut new_sig.generics, &new_lifetime);
+            new_lifetime
+        }
+        None => {
+            let mut input_lifetimes = LifetimeCollector::default();
+            for input in &sig.inputs {
+                input_lifetimes.visit_fn_arg(input);
+            }
+            input_lifetimes.explicit.dedup_by(|left, right| left.ident == right.ident);
+            match (input_lifetimes.elided, input_lifetimes.explicit.as_slice()) {
+                (0, [lifetime]) => lifetime.clone(),
+                (1, []) => {
+                    let mut replacer = ElidedLifetimeReplacer {
+                        lifetime: &new_lifetime,
+                    };
+                    for input in new_sig.inputs.iter_mut() {
+                        replacer.visit_fn_arg_mut(input);
+                    }
+                    add_lifetime_param(&mut new_sig.generics, &new_lifetime);
+                    new_lifetime
+                }
+                // The elision rules do not apply, so the compiler rejects the signature.
+                _ => return new_sig,
+            }
+        }
+    };
+    ElidedLifetimeReplacer {
+        lifetime: &lifetime,
+    }
+    .visit_return_type_mut(&mut new_sig.output);
+    new_sig
+}
+
+fn add_lifetime_param(generics: &mut syn::Generics, lifetime: &syn::Lifetime) {
+    generics.lt_token.get_or_insert_with(Default::default);
+    generics.gt_token.get_or_insert_with(Default::default);
+    generics.params.insert(0, parse_quote!(#lifetime));
+}
+
+/// Collects the lifetimes of a type, except those of function pointers and
+/// `Fn` traits, which have their own elision rules.
+#[derive(Default)]
+struct LifetimeCollector {
+    /// The number of elided lifetimes, either omitted or written as `'_`.
+    elided: usize,
+    explicit: Vec<syn::Lifetime>,
+}
+
+impl<'ast> Visit<'ast> for LifetimeCollector {
+    fn visit_type_reference(&mut self, reference: &'ast syn::TypeReference) {
+        if reference.lifetime.is_none() {
+            self.elided += 1;
+        }
+        syn::visit::visit_type_reference(self, reference);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+        if lifetime.ident == "_" {
+            self.elided += 1;
+        } else {
+            self.explicit.push(lifetime.clone());
+        }
+    }
+
+    fn visit_type_bare_fn(&mut self, _: &'ast syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments(
+        &mut self,
+        _: &'ast syn::ParenthesizedGenericArguments,
+    ) {
+    }
+}
+
+/// Replaces the elided lifetimes of a type with the given lifetime.
+struct ElidedLifetimeReplacer<'a> {
+    lifetime: &'a syn::Lifetime,
+}
+
+impl<'a> VisitMut for ElidedLifetimeReplacer<'a> {
+    fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.lifetime.clone());
+        }
+        syn::visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.lifetime.clone();
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, _: &mut syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _: &mut syn::ParenthesizedGenericArguments,
+    ) {
+    }
+}
+
 /// Add `PhantomData` markers for each type parameter to silence errors
 /// about unused type parameters. Works for structs with named or unnamed fields
 /// Given
@@ -660,4 +802,64 @@ mod tests {
             }
         }
     }
+
+    mod with_explicit_output_lifetimes {
+        use syn::parse_quote;
+        use crate::common::with_explicit_output_lifetimes;
+
+        macro_rules! test_explicit {
+            ([$($sig:tt)+] gives [$($expected:tt)+]) => {
+                let item: syn::TraitItemMethod = parse_quote! { $($sig)+; };
+                let expected: syn::TraitItemMethod = parse_quote! { $($expected)+; };
+                assert_eq!(expected.sig, with_explicit_output_lifetimes(&item.sig));
+            }
+        }
+
+        #[test]
+        fn test_receiver() {
+            test_explicit! {
+                [fn next(&mut self) -> Option<Self::Item<'_>>] gives
+                [fn next<'prusti_elided_lifetime>(&'prusti_elided_lifetime mut self)
+                    -> Option<Self::Item<'prusti_elided_lifetime>>]
+            }
+            test_explicit! {
+                [fn get<K>(&self, key: &K) -> &Self::Value<'_>] gives
+                [fn get<'prusti_elided_lifetime, K>(&'prusti_elided_lifetime self, key: &K)
+                    -> &'prusti_elided_lifetime Self::Value<'prusti_elided_lifetime>]
+            }
+            test_explicit! {
+                [fn next<'a>(&'a mut self) -> Option<Self::Item<'_>>] gives
+                [fn next<'a>(&'a mut self) -> Option<Self::Item<'a>>]
+            }
+        }
+
+        #[test]
+        fn test_single_input_lifetime() {
+            test_explicit! {
+                [fn first(items: &[u32]) -> &u32] gives
+                [fn first<'prusti_elided_lifetime>(items: &'prusti_elided_lifetime [u32])
+                    -> &'prusti_elided_lifetime u32]
+            }
+            test_explicit! {
+                [fn first<'a>(items: &'a [u32], index: usize) -> Item<'_>] gives
+                [fn first<'a>(items: &'a [u32], index: usize) -> Item<'a>]
+            }
+        }
+
+        #[test]
+        fn test_unchanged() {
+            test_explicit! {
+                [fn next<'a>(&'a mut self) -> Option<Self::Item<'a>>] gives
+                [fn next<'a>(&'a mut self) -> Option<Self::Item<'a>>]
+            }
+            test_explicit! {
+                [fn call(self, f: &dyn Fn(&u32) -> &u32) -> u32] gives
+                [fn call(self, f: &dyn Fn(&u32) -> &u32) -> u32]
+            }
+            test_explicit! {
+                [fn pick(left: &u32, right: &u32) -> &u32] gives
+                [fn pick(left: &u32, right: &u32) -> &u32]
+            }
+        }
+    }
 }
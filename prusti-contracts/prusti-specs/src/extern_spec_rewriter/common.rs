@@ -3,11 +3,10 @@ use quote::{quote, ToTokens};
 use syn::{Expr, FnArg, GenericParam, GenericArgument, parse_quote_spanned, Pat, PatType, Token};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use crate::common::{HasAttributes, HasSignature};
+use crate::common::{with_explicit_output_lifetimes, HasAttributes, HasSignature};
 use crate::span_overrider::SpanOverrider;
 use crate::untyped::AnyFnItem;
 use syn::visit::Visit;
-use syn::visit_mut::VisitMut;
 
 /// Counts the number of elided lifetimes in receivers and types.
 /// For details see the function `with_explicit_lifetimes`.
@@ -33,6 +32,14 @@ impl <'ast> syn::visit::Visit<'ast> for ElidedLifetimeCounter {
         if reference.lifetime.is_none() {
             self.num_elided_lifetimes += 1;
         }
+        syn::visit::visit_type_reference(self, reference);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &syn::Lifetime) {
+        // The anonymous lifetime, as in `Self::Item<'_>`
+        if lifetime.ident == "_" {
+            self.num_elided_lifetimes += 1;
+        }
     }
 }
 
@@ -60,40 +67,12 @@ fn returns_reference_with_elided_lifetime(return_type: &syn::ReturnType) -> bool
 /// annotations. The explicit lifetime annotations correspond to what Rust would assign
 /// for the elided lifetimes in the original signature.
 fn with_explicit_lifetimes(sig: &syn::Signature) -> Option<syn::Signature> {
-
-    // This struct is responsible for inserting an explicit lifetime for elided
-    // lifetimes in the receiver and output type.
-    struct SelfLifetimeInserter {}
-
-    impl syn::visit_mut::VisitMut for SelfLifetimeInserter {
-        fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
-            reference.lifetime = parse_quote_spanned!{reference.span() => 'prusti_self_lifetime };
-        }
-
-        fn visit_receiver_mut(&mut self, receiver: &mut syn::Receiver) {
-            receiver.reference.as_mut().unwrap().1 = parse_quote_spanned!{receiver.span() => 'prusti_self_lifetime };
-        }
-    }
-
     if !returns_reference_with_elided_lifetime(&sig.output) ||
        !has_multiple_elided_lifetimes(&sig.inputs)
     {
         return None;
     }
-    let mut new_sig = sig.clone();
-    let mut inserter = SelfLifetimeInserter{};
-
-    // Insert explicit lifetime parameter to method signature
-    new_sig.generics.params.insert(0, parse_quote_spanned!{new_sig.generics.params.span() => 'prusti_self_lifetime });
-
-    // Assign the explicit lifetime to the reference to self
-    if let Some(syn::FnArg::Receiver(r)) = new_sig.inputs.first_mut() {
-        inserter.visit_receiver_mut(r)
-    }
-
-    // Assign the explicit lifetime to references in the output
-    inserter.visit_return_type_mut(&mut new_sig.output);
-    Some(new_sig)
+    Some(with_explicit_output_lifetimes(sig))
 }
 
 /// Generates a method stub and spec functions for an externally specified function.
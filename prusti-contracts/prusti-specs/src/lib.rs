@@ -12,6 +12,7 @@
 mod common;
 mod extern_spec_rewriter;
 mod ghost_constraints;
+mod ord_laws;
 mod parse_closure_macro;
 mod parse_quote_spanned;
 mod predicate;
@@ -19,6 +20,7 @@ mod rewriter;
 mod span_overrider;
 mod spec_attribute_kind;
 pub mod specifications;
+mod trusted_block;
 mod type_model;
 mod user_provided_type_params;
 mod print_counterexample;
@@ -31,9 +33,11 @@ use std::convert::TryInto;
 use syn::{spanned::Spanned, visit::Visit};
 
 use crate::{
-    common::{merge_generics, RewritableReceiver, SelfTypeRewriter},
+    common::{merge_generics, HasSignature, RewritableReceiver, SelfTypeRewriter},
     predicate::{is_predicate_macro, ParsedPredicate},
-    specifications::preparser::{parse_ghost_constraint, parse_prusti, NestedSpec},
+    specifications::preparser::{
+        desugar_prophecies, extract_read_accesses, parse_ghost_constraint, parse_prusti, NestedSpec,
+    },
 };
 pub use extern_spec_rewriter::ExternSpecKind;
 use parse_closure_macro::ClosureWithSpec;
@@ -175,22 +179,47 @@ fn generate_spec_and_assertions(
 }
 
 /// Generate spec items and attributes to typecheck the and later retrieve "requires" annotations.
+/// A read-only access `read(place)` in the precondition becomes a postcondition that the place
+/// is not modified, so that the function is checked not to write the place and callers can rely
+/// on its value being preserved.
 fn generate_for_requires(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let (attr, read_places) = extract_read_accesses(attr)?;
     let mut rewriter = rewriter::AstRewriter::new();
     let spec_id = rewriter.generate_spec_id();
     let spec_id_str = spec_id.to_string();
     let spec_item =
         rewriter.process_assertion(rewriter::SpecItemType::Precondition, spec_id, attr, item)?;
-    Ok((
-        vec![spec_item],
-        vec![parse_quote_spanned! {item.span()=>
-            #[prusti::pre_spec_id_ref = #spec_id_str]
-        }],
-    ))
+    let mut generated_items = vec![spec_item];
+    let mut generated_attributes = vec![parse_quote_spanned! {item.span()=>
+        #[prusti::pre_spec_id_ref = #spec_id_str]
+    }];
+    for place in read_places {
+        let (items, attributes) = generate_for_ensures(
+            quote_spanned! {place.span()=> (#place) === *old(&(#place)) },
+            item,
+        )?;
+        generated_items.extend(items);
+        generated_attributes.extend(attributes);
+    }
+    Ok((generated_items, generated_attributes))
 }
 
 /// Generate spec items and attributes to typecheck the and later retrieve "ensures" annotations.
+/// A postcondition with prophecies (`final(place)` or `^x`) of a function that returns a mutable
+/// reference is a pledge, because the prophecies refer to the expiry of the returned borrow.
 fn generate_for_ensures(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let returns_mut_ref = matches!(
+        &item.sig().output,
+        syn::ReturnType::Type(_, box syn::Type::Reference(syn::TypeReference {
+            mutability: Some(_),
+            ..
+        }))
+    );
+    let attr = match desugar_prophecies(attr.clone(), returns_mut_ref)? {
+        Some(pledge) if returns_mut_ref => return generate_for_after_expiry(pledge, item),
+        Some(desugared) => desugared,
+        None => attr,
+    };
     let mut rewriter = rewriter::AstRewriter::new();
     let spec_id = rewriter.generate_spec_id();
     let spec_id_str = spec_id.to_string();
@@ -461,6 +490,45 @@ pub fn closure(tokens: TokenStream) -> TokenStream {
         });
     }
 
+    // The `after_expiry` assertion is checked at the end of each call of the
+    // closure and assumed in the creator once the closure's borrow expires.
+    let mut expiry_spec = TokenStream::new();
+    let mut expiry_annotation = TokenStream::new();
+    let mut expiry_check = TokenStream::new();
+    if let Some(pledge) = cl_spec.pledges.get(1) {
+        return syn::Error::new(
+            pledge.span(),
+            "a closure can have at most one `after_expiry` specification",
+        )
+        .to_compile_error();
+    }
+    if let Some(pledge) = cl_spec.pledges.into_iter().next() {
+        let spec_id = rewriter.generate_spec_id();
+        let spec_id_str = spec_id.to_string();
+        let closure =
+            handle_result!(rewriter.process_closure_expiry(spec_id, pledge.to_token_stream()));
+        expiry_spec = quote_spanned! {callsite_span=>
+            #[allow(unused_must_use, unused_variables, unused_braces, unused_parens)]
+            #[prusti::specs_version = #SPECS_VERSION]
+            if false {
+                #closure
+            }
+        };
+        expiry_annotation = quote_spanned! {callsite_span=>
+            #[prusti::closure_expiry_spec_id_ref = #spec_id_str]
+        };
+        let check_spec_id = rewriter.generate_spec_id();
+        let check = handle_result!(
+            rewriter.process_prusti_assertion(check_spec_id, pledge.to_token_stream())
+        );
+        expiry_check = quote_spanned! {callsite_span=>
+            #[allow(unused_must_use, unused_variables, unused_braces, unused_parens)]
+            if false {
+                #check
+            }
+        };
+    }
+
     let syn::ExprClosure {
         attrs,
         asyncness,
@@ -491,11 +559,13 @@ pub fn closure(tokens: TokenStream) -> TokenStream {
 
     quote_spanned! {callsite_span=>
         {
+            #expiry_spec
             #[allow(unused_variables, unused_braces, unused_parens)]
             #[prusti::closure]
             #[prusti::specs_version = #SPECS_VERSION]
             #cl_annotations #attrs_ts
             let _prusti_closure =
+                #expiry_annotation
                 #asyncness #movability #capture
                 #or1_token #inputs #or2_token #output
                 {
@@ -504,6 +574,7 @@ pub fn closure(tokens: TokenStream) -> TokenStream {
                         #spec_toks_pre
                     }
                     let result = #body ;
+                    #expiry_check
                     #[allow(unused_must_use, unused_braces, unused_parens)]
                     if false {
                         #spec_toks_post
@@ -529,8 +600,15 @@ pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream
 
     let self_type: &syn::Type = &impl_block.self_ty;
 
+    let is_ord_impl = trait_path
+        .path
+        .segments
+        .last()
+        .map_or(false, |segment| segment.ident == "Ord");
+
     let mut new_items = Vec::new();
     let mut generated_spec_items = Vec::new();
+    let mut pure_cmp_span = None;
     for item in impl_block.items {
         match item {
             syn::ImplItem::Method(method) => {
@@ -550,6 +628,15 @@ pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream
                     handle_result!(err);
                 }
 
+                if is_ord_impl
+                    && method_item.sig().ident == "cmp"
+                    && prusti_attributes
+                        .iter()
+                        .any(|(kind, _)| kind == &SpecAttributeKind::Pure)
+                {
+                    pure_cmp_span = Some(method_item.span());
+                }
+
                 let (spec_items, generated_attributes) = handle_result!(
                     generate_spec_and_assertions(prusti_attributes, &method_item)
                 );
@@ -593,10 +680,12 @@ pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream
     }
 
     impl_block.items = new_items;
+    let ord_laws = pure_cmp_span.map(|span| ord_laws::generate(span, &impl_block));
     quote_spanned! {impl_block.span()=>
         #(#generated_spec_items)*
         #[prusti::specs_version = #SPECS_VERSION]
         #impl_block
+        #ord_laws
     }
 }
 
@@ -958,6 +1047,121 @@ pub fn print_counterexample(attr: TokenStream, tokens: TokenStream) -> TokenStre
         .to_compile_error()
     }
 }
+
+/// Records the time budget of the verification of a function in the
+/// `prusti::verification_timeout` attribute.
+pub fn verification_timeout(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    let timeout: syn::LitInt = handle_result!(syn::parse2(attr));
+    let timeout: u64 = handle_result!(timeout.base10_parse());
+    let timeout = timeout.to_string();
+    quote! {
+        #[prusti::verification_timeout = #timeout]
+        #tokens
+    }
+}
+
+/// Records the Viper backend that verifies a function in the `prusti::backend`
+/// attribute.
+pub fn backend(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    let backend: syn::Ident = handle_result!(syn::parse2(attr));
+    let backend_name = backend.to_string().to_lowercase();
+    if backend_name != "silicon" && backend_name != "carbon" {
+        return syn::Error::new(
+            backend.span(),
+            "unknown backend; expected `silicon` or `carbon`",
+        )
+        .to_compile_error();
+    }
+    quote! {
+        #[prusti::backend = #backend_name]
+        #tokens
+    }
+}
+
+/// Records in the `prusti::nonlinear` attribute that a function is verified
+/// with the nonlinear arithmetic reasoning of the SMT solver.
+pub fn nonlinear(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(attr.span(), "the `nonlinear` attribute takes no arguments")
+            .to_compile_error();
+    }
+    quote! {
+        #[prusti::nonlinear]
+        #tokens
+    }
+}
+
+/// Records the resource budget of the verification of a function, given as
+/// `time = "30s", qi = 100_000`, in the `prusti::verification_timeout` (in
+/// milliseconds) and `prusti::verification_qi_bound` attributes.
+pub fn verification_budget(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    let budget = handle_result!(syn::parse::Parser::parse2(
+        syn::punctuated::Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated,
+        attr,
+    ));
+    let mut attributes = TokenStream::new();
+    for item in budget {
+        let value = &item.lit;
+        if item.path.is_ident("time") {
+            let timeout = match value {
+                syn::Lit::Str(time) => handle_result!(parse_duration_ms(time)),
+                _ => {
+                    return syn::Error::new(value.span(), "expected a duration such as \"30s\"")
+                        .to_compile_error()
+                }
+            };
+            let timeout = timeout.to_string();
+            attributes.extend(quote! { #[prusti::verification_timeout = #timeout] });
+        } else if item.path.is_ident("qi") {
+            let bound: u64 = match value {
+                syn::Lit::Int(bound) => handle_result!(bound.base10_parse()),
+                _ => {
+                    return syn::Error::new(value.span(), "expected a number of instantiations")
+                        .to_compile_error()
+                }
+            };
+            let bound = bound.to_string();
+            attributes.extend(quote! { #[prusti::verification_qi_bound = #bound] });
+        } else {
+            return syn::Error::new(
+                item.path.span(),
+                "unknown verification budget; expected `time` or `qi`",
+            )
+            .to_compile_error();
+        }
+    }
+    quote! {
+        #attributes
+        #tokens
+    }
+}
+
+/// Parses a duration such as `"500ms"`, `"30s"` or `"2min"` into milliseconds.
+fn parse_duration_ms(time: &syn::LitStr) -> syn::Result<u64> {
+    let value = time.value();
+    let digits_end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits_end);
+    let factor = match unit.trim() {
+        "ms" => Some(1),
+        "s" => Some(1000),
+        "min" => Some(60_000),
+        _ => None,
+    };
+    match (number.parse::<u64>(), factor) {
+        (Ok(number), Some(factor)) => Ok(number * factor),
+        _ => Err(syn::Error::new(
+            time.span(),
+            "expected a duration in `ms`, `s` or `min`, such as \"30s\"",
+        )),
+    }
+}
+
+pub fn trusted_block(tokens: TokenStream) -> TokenStream {
+    handle_result!(trusted_block::generate(tokens))
+}
+
 pub fn ghost(tokens: TokenStream) -> TokenStream {
     let mut rewriter = rewriter::AstRewriter::new();
     let callsite_span = Span::call_site();
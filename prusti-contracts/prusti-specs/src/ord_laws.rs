@@ -0,0 +1,56 @@
+//! The proof obligations that an `Ord` implementation is lawful.
+//!
+//! When `#[refine_trait_spec]` is applied to an implementation of `Ord` whose
+//! `cmp` is `#[pure]`, we generate a method whose postconditions state that
+//! `cmp` is reflexive, antisymmetric and transitive. Totality follows from
+//! `cmp` being a pure function. The verifier checks the method only with the
+//! `check_ord_laws` flag and only if the type is used by a function that
+//! requires `Ord`, for example a sorting function or a `BTreeMap` operation.
+
+use crate::rewriter::AstRewriter;
+use proc_macro2::Span;
+use quote::quote_spanned;
+use syn::parse_quote_spanned;
+
+/// Generates an inherent implementation for the self type of `impl_block`
+/// with the method that checks the laws. The specifications point to `span`,
+/// the span of the `cmp` method.
+pub fn generate(span: Span, impl_block: &syn::ItemImpl) -> syn::ItemImpl {
+    let spec_id = AstRewriter::new().generate_spec_id();
+    let method_name = syn::Ident::new(&format!("prusti_ord_laws_{}", spec_id), span);
+    let (impl_generics, _, where_clause) = impl_block.generics.split_for_impl();
+    let self_type = &impl_block.self_ty;
+    let cmp = |lhs: &str, rhs: &str| {
+        let lhs = syn::Ident::new(lhs, span);
+        let rhs = syn::Ident::new(rhs, span);
+        quote_spanned! {span=> ::core::cmp::Ord::cmp(#lhs, #rhs) }
+    };
+    let (cmp_aa, cmp_ab, cmp_ba, cmp_bc, cmp_ac) = (
+        cmp("a", "a"),
+        cmp("a", "b"),
+        cmp("b", "a"),
+        cmp("b", "c"),
+        cmp("a", "c"),
+    );
+    parse_quote_spanned! {span=>
+        impl #impl_generics #self_type #where_clause {
+            #[allow(unused_variables, dead_code, non_snake_case)]
+            #[prusti::ord_laws]
+            #[prusti_contracts::ensures(
+                matches!(#cmp_aa, ::core::cmp::Ordering::Equal)
+            )]
+            #[prusti_contracts::ensures(match (#cmp_ab, #cmp_ba) {
+                (::core::cmp::Ordering::Equal, ::core::cmp::Ordering::Equal)
+                | (::core::cmp::Ordering::Less, ::core::cmp::Ordering::Greater)
+                | (::core::cmp::Ordering::Greater, ::core::cmp::Ordering::Less) => true,
+                _ => false,
+            })]
+            #[prusti_contracts::ensures(
+                (!matches!(#cmp_ab, ::core::cmp::Ordering::Greater)
+                    && !matches!(#cmp_bc, ::core::cmp::Ordering::Greater))
+                ==> !matches!(#cmp_ac, ::core::cmp::Ordering::Greater)
+            )]
+            fn #method_name(a: &Self, b: &Self, c: &Self) {}
+        }
+    }
+}
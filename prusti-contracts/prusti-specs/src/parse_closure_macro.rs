@@ -3,6 +3,9 @@ use syn::parse::{Parse, ParseStream};
 pub(crate) struct ClosureWithSpec {
     pub pres: Vec<syn::Expr>,
     pub posts: Vec<syn::Expr>,
+    /// The `after_expiry` assertions about the places that the closure
+    /// captures by mutable reference.
+    pub pledges: Vec<syn::Expr>,
     pub cl: syn::ExprClosure
 }
 
@@ -13,6 +16,7 @@ impl Parse for ClosureWithSpec {
 
         let mut pres: Vec<syn::Result<syn::Expr>> = vec![];
         let mut posts: Vec<syn::Result<syn::Expr>> = vec![];
+        let mut pledges: Vec<syn::Result<syn::Expr>> = vec![];
 
         // collect and remove any specification attributes
         // leave other attributes intact
@@ -21,6 +25,7 @@ impl Parse for ClosureWithSpec {
                 match id.to_string().as_ref() {
                     "requires" => pres.push(syn::parse2(attr.tokens.clone())),
                     "ensures" => posts.push(syn::parse2(attr.tokens.clone())),
+                    "after_expiry" => pledges.push(syn::parse2(attr.tokens.clone())),
                     _ => return false
                 }
                 true
@@ -33,6 +38,7 @@ impl Parse for ClosureWithSpec {
         Ok(Self {
             pres: pres.into_iter().collect::<syn::Result<Vec<_>>>()?,
             posts: posts.into_iter().collect::<syn::Result<Vec<_>>>()?,
+            pledges: pledges.into_iter().collect::<syn::Result<Vec<_>>>()?,
             cl,
         })
     }
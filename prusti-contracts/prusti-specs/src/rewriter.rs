@@ -1,13 +1,13 @@
 use crate::{
-    common::HasSignature,
+    common::{with_explicit_output_lifetimes, HasSignature},
     specifications::{
         common::{SpecificationId, SpecificationIdGenerator},
         preparser::{parse_prusti, parse_prusti_assert_pledge, parse_prusti_pledge},
         untyped,
     },
 };
-use proc_macro2::{Span, TokenStream};
-use quote::{format_ident, quote, quote_spanned};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{parse_quote_spanned, punctuated::Punctuated, spanned::Spanned, Pat, Token, Type};
 
 pub(crate) struct AstRewriter {
@@ -65,16 +65,20 @@ impl AstRewriter {
         None
     }
 
-    fn generate_result_arg<T: HasSignature + Spanned>(&self, item: &T) -> syn::FnArg {
+    fn generate_result_arg<T: HasSignature + Spanned>(
+        &self,
+        item: &T,
+        output: &syn::ReturnType,
+    ) -> syn::FnArg {
         let item_span = item.span();
-        let output_ty = match &item.sig().output {
+        let output_ty = match output {
             syn::ReturnType::Default => parse_quote_spanned!(item_span=> ()),
             syn::ReturnType::Type(_, ty) => ty.clone(),
         };
         let fn_arg = syn::FnArg::Typed(syn::PatType {
             attrs: Vec::new(),
             pat: Box::new(parse_quote_spanned!(item_span=> result)),
-            colon_token: syn::Token![:](item.sig().output.span()),
+            colon_token: syn::Token![:](output.span()),
             ty: output_ty,
         });
         fn_arg
@@ -126,19 +130,29 @@ impl AstRewriter {
             }
         };
 
-        spec_item.sig.generics = item.sig().generics.clone();
-        spec_item.sig.inputs = item.sig().inputs.clone();
         match spec_type {
             SpecItemType::Postcondition | SpecItemType::Pledge => {
-                let fn_arg = self.generate_result_arg(item);
+                // The result becomes an argument, so its elided lifetimes
+                // must be tied to the inputs explicitly, as for example
+                // generic associated types can require `Self: 'a`.
+                let sig = with_explicit_output_lifetimes(item.sig());
+                let fn_arg = self.generate_result_arg(item, &sig.output);
+                spec_item.sig.generics = sig.generics;
+                spec_item.sig.inputs = sig.inputs;
                 spec_item.sig.inputs.push(fn_arg);
             }
-            _ => (),
+            _ => {
+                spec_item.sig.generics = item.sig().generics.clone();
+                spec_item.sig.inputs = item.sig().inputs.clone();
+            }
         }
         Ok(syn::Item::Fn(spec_item))
     }
 
-    /// Parse an assertion into a Rust expression
+    /// Parse an assertion into a Rust expression. Preconditions and
+    /// postconditions can end with a custom failure message, as in
+    /// `#[requires(x > 0, "x must be positive")]`, which is attached to the
+    /// spec item and reported when the assertion fails.
     pub fn process_assertion<T: HasSignature + Spanned>(
         &mut self,
         spec_type: SpecItemType,
@@ -146,7 +160,20 @@ impl AstRewriter {
         tokens: TokenStream,
         item: &T,
     ) -> syn::Result<syn::Item> {
-        self.generate_spec_item_fn(spec_type, spec_id, parse_prusti(tokens)?, item)
+        let (tokens, failure_message) = match spec_type {
+            SpecItemType::Precondition | SpecItemType::Postcondition => {
+                split_failure_message(tokens)
+            }
+            _ => (tokens, None),
+        };
+        let mut spec_item =
+            self.generate_spec_item_fn(spec_type, spec_id, parse_prusti(tokens)?, item)?;
+        if let (Some(message), syn::Item::Fn(spec_fn)) = (failure_message, &mut spec_item) {
+            spec_fn.attrs.push(parse_quote_spanned! {message.span()=>
+                #[prusti::failure_message = #message]
+            });
+        }
+        Ok(spec_item)
     }
 
     /// Parse a pledge with lhs into a Rust expression
@@ -225,6 +252,15 @@ impl AstRewriter {
         self.process_prusti_expression(quote! {prusti_assumption}, spec_id, tokens)
     }
 
+    /// Parse the `after_expiry` assertion of a closure into a Rust expression
+    pub fn process_closure_expiry(
+        &mut self,
+        spec_id: SpecificationId,
+        tokens: TokenStream,
+    ) -> syn::Result<TokenStream> {
+        self.process_prusti_expression(quote! {closure_expiry}, spec_id, tokens)
+    }
+
     fn process_prusti_expression(
         &mut self,
         kind: TokenStream,
@@ -313,3 +349,19 @@ impl AstRewriter {
         })
     }
 }
+
+/// Splits the trailing `, "message"` off an assertion. Only the last two
+/// tokens are inspected, so commas nested in groups or closures are not
+/// affected.
+fn split_failure_message(tokens: TokenStream) -> (TokenStream, Option<syn::LitStr>) {
+    let mut token_trees: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    if let [.., TokenTree::Punct(comma), TokenTree::Literal(literal)] = token_trees.as_slice() {
+        if comma.as_char() == ',' {
+            if let Ok(message) = syn::parse2::<syn::LitStr>(literal.to_token_stream()) {
+                token_trees.truncate(token_trees.len() - 2);
+                return (token_trees.into_iter().collect(), Some(message));
+            }
+        }
+    }
+    (tokens, None)
+}
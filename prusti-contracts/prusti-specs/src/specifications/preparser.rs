@@ -50,6 +50,180 @@ pub fn parse_prusti_assert_pledge(tokens: TokenStream) -> syn::Result<(TokenStre
     Ok((lhs, rhs))
 }
 
+/// Desugars the prophecies `final(place)` and `^x` of a postcondition, which
+/// refer to the value of a place when the mutable borrow of the place expires.
+/// `^x` is a shorthand for `final(*x)`, where `x` is a variable or a
+/// parenthesized expression. Returns `None` if there is no prophecy.
+///
+/// The mutable borrows of the arguments expire when the function returns, so
+/// in a postcondition `final(place)` is just `place`. If the function returns
+/// a mutable reference, the postcondition is checked as a pledge when the
+/// returned borrow expires (`at_expiry`): `final(place)` then refers to the
+/// value before the expiry if the place is reachable from `result`, and to
+/// the value after the expiry otherwise. Outside of prophecies, such a
+/// postcondition cannot refer to `result`.
+pub fn desugar_prophecies(tokens: TokenStream, at_expiry: bool) -> syn::Result<Option<TokenStream>> {
+    let mut found = false;
+    let desugared = desugar_prophecy_tokens(tokens.clone(), false, false, &mut found)?;
+    if !found {
+        Ok(None)
+    } else if at_expiry {
+        desugar_prophecy_tokens(tokens, true, false, &mut found).map(Some)
+    } else {
+        Ok(Some(desugared))
+    }
+}
+
+fn desugar_prophecy_tokens(
+    tokens: TokenStream,
+    at_expiry: bool,
+    in_prophecy: bool,
+    found: &mut bool,
+) -> syn::Result<TokenStream> {
+    let source = tokens.into_iter().collect::<Vec<_>>();
+    let mut desugared = TokenStream::new();
+    let mut pos = 0;
+    while pos < source.len() {
+        let token = &source[pos];
+        pos += 1;
+        let place = match (token, source.get(pos)) {
+            (TokenTree::Ident(ident), Some(TokenTree::Group(group)))
+                if ident == "final" && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                pos += 1;
+                group.stream()
+            }
+            (TokenTree::Punct(punct), Some(operand @ (TokenTree::Ident(_) | TokenTree::Group(_))))
+                if punct.as_char() == '^'
+                    && punct.spacing() == Alone
+                    && matches!(
+                        pos.checked_sub(2).and_then(|prev| source.get(prev)),
+                        None | Some(TokenTree::Punct(_))
+                    ) =>
+            {
+                // after an operator, `^` cannot be the binary exclusive or
+                pos += 1;
+                quote_spanned! {punct.span()=> *#operand }
+            }
+            (TokenTree::Ident(ident), _)
+                if ident == "result" && at_expiry && !in_prophecy =>
+            {
+                return err(
+                    ident.span(),
+                    "a postcondition with `final` can only refer to `result` within `final`",
+                );
+            }
+            (TokenTree::Group(group), _) => {
+                let mut new_group = proc_macro2::Group::new(
+                    group.delimiter(),
+                    desugar_prophecy_tokens(group.stream(), at_expiry, in_prophecy, found)?,
+                );
+                new_group.set_span(group.span());
+                desugared.extend(TokenTree::Group(new_group).to_token_stream());
+                continue;
+            }
+            (token, _) => {
+                desugared.extend(token.to_token_stream());
+                continue;
+            }
+        };
+        *found = true;
+        let span = token.span();
+        let mentions_result = mentions_ident(place.clone(), "result");
+        let place = desugar_prophecy_tokens(place, at_expiry, true, found)?;
+        desugared.extend(if at_expiry && mentions_result {
+            quote_spanned! {span=> ::prusti_contracts::before_expiry(#place) }
+        } else {
+            quote_spanned! {span=> (#place) }
+        });
+    }
+    Ok(desugared)
+}
+
+/// Extracts the read-only accesses `read(place)` of a precondition, which
+/// must be conjuncts of the precondition, and replaces them with `true`.
+/// Returns the precondition and the places.
+pub fn extract_read_accesses(tokens: TokenStream) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
+    let source = tokens.into_iter().collect::<Vec<_>>();
+    let mut precondition = TokenStream::new();
+    let mut places = vec![];
+    let mut pos = 0;
+    while pos < source.len() {
+        let token = &source[pos];
+        pos += 1;
+        match (token, source.get(pos)) {
+            // `x.read(..)` and `io::read(..)` are calls of other functions
+            (TokenTree::Ident(ident), Some(TokenTree::Group(group)))
+                if ident == "read"
+                    && group.delimiter() == Delimiter::Parenthesis
+                    && !matches!(
+                        pos.checked_sub(2).and_then(|prev| source.get(prev)),
+                        Some(TokenTree::Punct(punct)) if matches!(punct.as_char(), '.' | ':')
+                    ) =>
+            {
+                pos += 1;
+                if group.stream().is_empty() {
+                    return err(group.span(), "expected a place in `read`");
+                }
+                places.push(group.stream());
+                precondition.extend(quote_spanned! {ident.span()=> true });
+            }
+            (TokenTree::Group(group), _) if mentions_read_access(group.stream()) => {
+                return err(
+                    group.span(),
+                    "`read` can only be used as a conjunct of a precondition",
+                );
+            }
+            (token, _) => precondition.extend(token.to_token_stream()),
+        }
+    }
+    if !places.is_empty() && !is_conjunction(&source) {
+        return err(
+            join_spans(source[0].span(), source[source.len() - 1].span()),
+            "`read` can only be used as a conjunct of a precondition",
+        );
+    }
+    Ok((precondition, places))
+}
+
+/// Whether the top-level operators of the tokens are not disjunctions,
+/// implications or negations.
+fn is_conjunction(source: &[TokenTree]) -> bool {
+    !source.iter().enumerate().any(|(pos, token)| match token {
+        TokenTree::Punct(punct) => match punct.as_char() {
+            '|' => true,
+            '!' => punct.spacing() == Alone,
+            '>' => pos >= 2 && matches!(
+                (&source[pos - 2], &source[pos - 1]),
+                (TokenTree::Punct(first), TokenTree::Punct(second))
+                    if first.as_char() == '=' && second.as_char() == '='
+            ),
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+fn mentions_read_access(tokens: TokenStream) -> bool {
+    let source = tokens.into_iter().collect::<Vec<_>>();
+    source.iter().enumerate().any(|(pos, token)| match (token, source.get(pos + 1)) {
+        (TokenTree::Ident(ident), Some(TokenTree::Group(_))) if ident == "read" => !matches!(
+            pos.checked_sub(1).and_then(|prev| source.get(prev)),
+            Some(TokenTree::Punct(punct)) if matches!(punct.as_char(), '.' | ':')
+        ),
+        (TokenTree::Group(group), _) => mentions_read_access(group.stream()),
+        _ => false,
+    })
+}
+
+fn mentions_ident(tokens: TokenStream, name: &str) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == name,
+        TokenTree::Group(group) => mentions_ident(group.stream(), name),
+        _ => false,
+    })
+}
+
 pub fn parse_ghost_constraint(tokens: TokenStream) -> syn::Result<GhostConstraint> {
     syn::parse2(tokens)
 }
@@ -0,0 +1,271 @@
+//! The `trusted_block!` macro, which marks a block of an otherwise verified
+//! function as trusted. The verifier skips the body of the block: it checks
+//! the preconditions of the block, havocs the places that the block modifies,
+//! and assumes its postconditions, in which `result` is the value of the block.
+
+use crate::{rewriter::AstRewriter, SPECS_VERSION};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use std::collections::HashSet;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    visit::Visit,
+    Token,
+};
+
+struct TrustedBlock {
+    pres: Vec<TokenStream>,
+    posts: Vec<TokenStream>,
+    /// The places listed in `#[modifies(...)]` attributes.
+    modified: Vec<syn::Expr>,
+    block: syn::Block,
+}
+
+impl Parse for TrustedBlock {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let block = input.parse()?;
+        let mut pres = vec![];
+        let mut posts = vec![];
+        let mut modified = vec![];
+        for attr in attrs {
+            match attr.path.get_ident().map(|ident| ident.to_string()).as_deref() {
+                Some("requires") => pres.push(attr.parse_args()?),
+                Some("ensures") => posts.push(attr.parse_args()?),
+                Some("modifies") => modified.extend(
+                    attr.parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)?,
+                ),
+                _ => {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "expected `requires`, `ensures` or `modifies`",
+                    ))
+                }
+            }
+        }
+        Ok(Self {
+            pres,
+            posts,
+            modified,
+            block,
+        })
+    }
+}
+
+pub(crate) fn generate(tokens: TokenStream) -> syn::Result<TokenStream> {
+    let TrustedBlock {
+        pres,
+        posts,
+        modified,
+        block,
+    } = syn::parse2(tokens)?;
+
+    let mut effects = EffectCollector::default();
+    effects.visit_block(&block);
+    if let Some(span) = effects.exits.first() {
+        return Err(syn::Error::new(
+            *span,
+            "control flow cannot leave a trusted block early",
+        ));
+    }
+
+    let mut rewriter = AstRewriter::new();
+    let mut pre_checks = TokenStream::new();
+    for pre in pres {
+        let spec_id = rewriter.generate_spec_id();
+        let assertion = rewriter.process_prusti_assertion(spec_id, pre)?;
+        pre_checks.extend(quote! {
+            #[allow(unused_must_use, unused_variables, unused_braces, unused_parens)]
+            if false {
+                #assertion
+            }
+        });
+    }
+    let mut post_assumptions = TokenStream::new();
+    for post in posts {
+        let spec_id = rewriter.generate_spec_id();
+        let assumption = rewriter.process_prusti_assumption(spec_id, post)?;
+        post_assumptions.extend(quote! {
+            #[allow(unused_must_use, unused_variables, unused_braces, unused_parens)]
+            if false {
+                #assumption
+            }
+        });
+    }
+    let havocs = modified
+        .iter()
+        .chain(effects.places.iter())
+        .map(|place| {
+            quote_spanned! {place.span()=>
+                ::prusti_contracts::prusti_trusted_block_havoc(&mut #place);
+            }
+        })
+        .collect::<TokenStream>();
+    let marker_id = rewriter.generate_spec_id().to_string();
+    let callsite_span = Span::call_site();
+
+    // The marker closure makes the body a specification block, which the
+    // encoder does not verify.
+    Ok(quote_spanned! {callsite_span=>
+        {
+            #pre_checks
+            #[prusti::specs_version = #SPECS_VERSION]
+            let result = if false {
+                #[allow(unused_must_use, unused_variables)]
+                {
+                    #[prusti::spec_only]
+                    #[prusti::trusted_block]
+                    #[prusti::spec_id = #marker_id]
+                    || -> () {};
+                }
+                #block
+            } else {
+                #havocs
+                ::prusti_contracts::prusti_trusted_block_result()
+            };
+            #post_assumptions
+            result
+        }
+    })
+}
+
+/// Collects the places that a block assigns or borrows mutably, and the
+/// expressions that leave the block early.
+#[derive(Default)]
+struct EffectCollector {
+    places: Vec<syn::Expr>,
+    /// The variables declared in the block, whose modifications are not
+    /// visible outside of it.
+    locals: HashSet<syn::Ident>,
+    exits: Vec<Span>,
+    /// The labels of the loops of the block that enclose the visited
+    /// expression, with `None` for unlabeled loops.
+    loops: Vec<Option<syn::Ident>>,
+}
+
+impl EffectCollector {
+    fn add_place(&mut self, place: &syn::Expr) {
+        if let Some(root) = place_root(place) {
+            if !self.locals.contains(root) {
+                self.places.push(place.clone());
+            }
+        }
+    }
+
+    fn visit_loop_body(&mut self, label: &Option<syn::Label>, body: &syn::Block) {
+        self.loops.push(label.as_ref().map(|label| label.name.ident.clone()));
+        self.visit_block(body);
+        self.loops.pop();
+    }
+
+    /// Whether a `break` or `continue` with the given label leaves the block.
+    fn is_exit(&self, label: &Option<syn::Lifetime>) -> bool {
+        match label {
+            Some(label) => !self
+                .loops
+                .iter()
+                .any(|loop_label| loop_label.as_ref() == Some(&label.ident)),
+            None => self.loops.is_empty(),
+        }
+    }
+}
+
+/// The variable of a place expression, or `None` if the expression is not a
+/// place.
+fn place_root(place: &syn::Expr) -> Option<&syn::Ident> {
+    match place {
+        syn::Expr::Path(path) => path.path.get_ident(),
+        syn::Expr::Field(field) => place_root(&field.base),
+        syn::Expr::Index(index) => place_root(&index.expr),
+        syn::Expr::Paren(paren) => place_root(&paren.expr),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Deref(_),
+            expr,
+            ..
+        }) => place_root(expr),
+        _ => None,
+    }
+}
+
+impl<'ast> Visit<'ast> for EffectCollector {
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        syn::visit::visit_local(self, local);
+        let mut bindings = BindingCollector::default();
+        bindings.visit_pat(&local.pat);
+        self.locals.extend(bindings.0);
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast syn::ExprAssign) {
+        syn::visit::visit_expr_assign(self, assign);
+        self.add_place(&assign.left);
+    }
+
+    fn visit_expr_assign_op(&mut self, assign: &'ast syn::ExprAssignOp) {
+        syn::visit::visit_expr_assign_op(self, assign);
+        self.add_place(&assign.left);
+    }
+
+    fn visit_expr_reference(&mut self, reference: &'ast syn::ExprReference) {
+        syn::visit::visit_expr_reference(self, reference);
+        if reference.mutability.is_some() {
+            self.add_place(&reference.expr);
+        }
+    }
+
+    fn visit_expr_return(&mut self, expr: &'ast syn::ExprReturn) {
+        self.exits.push(expr.span());
+    }
+
+    fn visit_expr_try(&mut self, expr: &'ast syn::ExprTry) {
+        syn::visit::visit_expr_try(self, expr);
+        self.exits.push(expr.question_token.span());
+    }
+
+    fn visit_expr_break(&mut self, expr: &'ast syn::ExprBreak) {
+        syn::visit::visit_expr_break(self, expr);
+        if self.is_exit(&expr.label) {
+            self.exits.push(expr.span());
+        }
+    }
+
+    fn visit_expr_continue(&mut self, expr: &'ast syn::ExprContinue) {
+        if self.is_exit(&expr.label) {
+            self.exits.push(expr.span());
+        }
+    }
+
+    fn visit_expr_loop(&mut self, expr: &'ast syn::ExprLoop) {
+        self.visit_loop_body(&expr.label, &expr.body);
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        self.visit_expr(&expr.cond);
+        self.visit_loop_body(&expr.label, &expr.body);
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        self.visit_expr(&expr.expr);
+        let mut bindings = BindingCollector::default();
+        bindings.visit_pat(&expr.pat);
+        self.locals.extend(bindings.0);
+        self.visit_loop_body(&expr.label, &expr.body);
+    }
+
+    // Closures and nested items are separate bodies: their returns do not
+    // leave the block.
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+#[derive(Default)]
+struct BindingCollector(Vec<syn::Ident>);
+
+impl<'ast> Visit<'ast> for BindingCollector {
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        syn::visit::visit_pat_ident(self, pat);
+        self.0.push(pat.ident.clone());
+    }
+}
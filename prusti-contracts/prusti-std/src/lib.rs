@@ -1,3 +1,5 @@
+#![cfg_attr(feature = "prusti", feature(allocator_api))]
+
 use prusti_contracts::*;
 
 #[extern_spec]
@@ -12,3 +14,229 @@ where
         K: ::core::borrow::Borrow<Q>,
         Q: ::core::hash::Hash + Eq;
 }
+
+#[extern_spec]
+impl<T> ::std::vec::Vec<T> {
+    #[ensures(result.len() == 0)]
+    pub fn new() -> ::std::vec::Vec<T>;
+
+    #[ensures(result.len() == 0)]
+    #[ensures(result.capacity() >= capacity)]
+    pub fn with_capacity(capacity: usize) -> ::std::vec::Vec<T>;
+}
+
+// A vector never reallocates when its capacity suffices and never shrinks by
+// itself, so the capacity changes only in `push`, `insert` and `reserve`. The
+// elements are specified with `vec_elem`: the operations that remove or
+// insert an element shift the following elements, except for `swap_remove`,
+// which moves the last element into the gap.
+#[extern_spec]
+impl<T, A: ::std::alloc::Allocator> ::std::vec::Vec<T, A> {
+    #[pure]
+    pub fn len(&self) -> usize;
+
+    #[pure]
+    #[ensures(result >= self.len())]
+    pub fn capacity(&self) -> usize;
+
+    #[pure]
+    #[ensures(result == (self.len() == 0))]
+    pub fn is_empty(&self) -> bool;
+
+    #[ensures(self.len() == old(self.len()))]
+    #[ensures(self.capacity() - self.len() >= additional)]
+    pub fn reserve(&mut self, additional: usize);
+
+    #[ensures(self.len() == old(self.len()) + 1)]
+    #[ensures(old(self.len()) < old(self.capacity()) ==> self.capacity() == old(self.capacity()))]
+    #[ensures(snapshot_equality(vec_elem(self, old(self.len())), &value))]
+    #[ensures(forall(|i: usize| i < old(self.len()) ==>
+        snapshot_equality(vec_elem(self, i), old(vec_elem(self, i)))))]
+    pub fn push(&mut self, value: T);
+
+    #[ensures(old(self.len()) == 0 ==> self.len() == 0)]
+    #[ensures(old(self.len()) > 0 ==> self.len() == old(self.len()) - 1)]
+    #[ensures(self.capacity() == old(self.capacity()))]
+    pub fn pop(&mut self) -> Option<T>;
+
+    #[requires(index <= self.len())]
+    #[ensures(self.len() == old(self.len()) + 1)]
+    #[ensures(old(self.len()) < old(self.capacity()) ==> self.capacity() == old(self.capacity()))]
+    #[ensures(snapshot_equality(vec_elem(self, index), &element))]
+    #[ensures(forall(|i: usize| i < index ==>
+        snapshot_equality(vec_elem(self, i), old(vec_elem(self, i)))))]
+    #[ensures(forall(|i: usize| index < i && i < self.len() ==>
+        snapshot_equality(vec_elem(self, i), old(vec_elem(self, i - 1)))))]
+    pub fn insert(&mut self, index: usize, element: T);
+
+    #[requires(index < self.len())]
+    #[ensures(self.len() == old(self.len()) - 1)]
+    #[ensures(self.capacity() == old(self.capacity()))]
+    #[ensures(snapshot_equality(&result, old(vec_elem(self, index))))]
+    #[ensures(forall(|i: usize| i < index ==>
+        snapshot_equality(vec_elem(self, i), old(vec_elem(self, i)))))]
+    #[ensures(forall(|i: usize| index <= i && i < self.len() ==>
+        snapshot_equality(vec_elem(self, i), old(vec_elem(self, i + 1)))))]
+    pub fn remove(&mut self, index: usize) -> T;
+
+    #[requires(index < self.len())]
+    #[ensures(self.len() == old(self.len()) - 1)]
+    #[ensures(self.capacity() == old(self.capacity()))]
+    #[ensures(snapshot_equality(&result, old(vec_elem(self, index))))]
+    #[ensures(index < self.len() ==>
+        snapshot_equality(vec_elem(self, index), old(vec_elem(self, self.len() - 1))))]
+    #[ensures(forall(|i: usize| i < self.len() && i != index ==>
+        snapshot_equality(vec_elem(self, i), old(vec_elem(self, i)))))]
+    pub fn swap_remove(&mut self, index: usize) -> T;
+
+    #[ensures(old(self.len()) > len ==> self.len() == len)]
+    #[ensures(old(self.len()) <= len ==> self.len() == old(self.len()))]
+    #[ensures(self.capacity() == old(self.capacity()))]
+    #[ensures(forall(|i: usize| i < self.len() ==>
+        snapshot_equality(vec_elem(self, i), old(vec_elem(self, i)))))]
+    pub fn truncate(&mut self, len: usize);
+
+    #[ensures(self.len() == 0)]
+    #[ensures(self.capacity() == old(self.capacity()))]
+    pub fn clear(&mut self);
+}
+
+/// The element of the vector at `index`. The elements of a vector are not
+/// modeled in general, so this function is related to the vector only by the
+/// contracts that mention it, such as those of `push`, `insert`, `remove` and
+/// `vec![elem; n]`.
+#[pure]
+#[trusted]
+#[requires(index < vec.len())]
+pub fn vec_elem<T, A: ::std::alloc::Allocator>(vec: &::std::vec::Vec<T, A>, index: usize) -> &T {
+    &vec[index]
+}
+
+// `vec![elem; n]` expands to a call of `from_elem`, which fills the vector
+// with clones of `elem`. The contract assumes that cloning the element gives
+// an equal value, as the implementations of `Clone` for the primitive types
+// and the derived ones do.
+#[extern_spec]
+mod std {
+    mod vec {
+        use crate::vec_elem;
+        use prusti_contracts::*;
+
+        #[ensures(result.len() == n)]
+        #[ensures(forall(|i: usize| i < n ==> snapshot_equality(vec_elem(&result, i), &elem)))]
+        pub fn from_elem<T: ::core::clone::Clone>(elem: T, n: usize) -> ::std::vec::Vec<T>;
+    }
+}
+
+/// The order of `T` used by the specifications of sorting and searching.
+/// Comparisons of `T` are not pure in general, so the order is uninterpreted:
+/// the verifier only knows the facts about it that the contracts state.
+#[pure]
+#[trusted]
+pub fn ord_le<T: Ord>(a: &T, b: &T) -> bool {
+    a <= b
+}
+
+/// Whether `a` and `b` are equal in the order of `T`.
+#[pure]
+pub fn ord_eq<T: Ord>(a: &T, b: &T) -> bool {
+    ord_le(a, b) && ord_le(b, a)
+}
+
+// Whether the elements of the slice are in non-decreasing order.
+predicate! {
+    pub fn sorted<T: Ord>(s: &[T]) -> bool {
+        forall(|i: usize, j: usize| i < j && j < s.len() ==> ord_le(&s[i], &s[j]))
+    }
+}
+
+#[extern_spec]
+impl<T: Ord> [T] {
+    #[ensures(self.len() == old(self.len()))]
+    #[ensures(sorted(self))]
+    pub fn sort(&mut self);
+
+    #[ensures(self.len() == old(self.len()))]
+    #[ensures(sorted(self))]
+    pub fn sort_unstable(&mut self);
+
+    #[requires(sorted(self))]
+    #[ensures(match result {
+        Ok(index) => index < self.len() && ord_eq(&self[index], x),
+        Err(index) => index <= self.len()
+            && forall(|i: usize| i < self.len() ==> !ord_eq(&self[i], x)),
+    })]
+    pub fn binary_search(&self, x: &T) -> Result<usize, usize>;
+}
+
+#[extern_spec]
+impl<T> [T] {
+    #[ensures(self.len() == old(self.len()))]
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> ::core::cmp::Ordering;
+}
+
+/// Whether `value` holds an initialized value. This is a ghost flag: it is set
+/// by the functions that initialize a `MaybeUninit` and required by those that
+/// read its value. Unions, and thus `MaybeUninit`, are supported only with
+/// the `unsafe_core_proof` flag. Like the quantifiers of `prusti_contracts`,
+/// the function is only meaningful in specifications and returns `true` when
+/// executed.
+#[pure]
+#[trusted]
+pub fn is_initialized<T>(_value: &::core::mem::MaybeUninit<T>) -> bool {
+    true
+}
+
+#[extern_spec]
+impl<T> ::core::mem::MaybeUninit<T> {
+    #[ensures(is_initialized(&result))]
+    pub fn new(val: T) -> ::core::mem::MaybeUninit<T>;
+
+    #[ensures(!is_initialized(&result))]
+    pub fn uninit() -> ::core::mem::MaybeUninit<T>;
+
+    #[ensures(is_initialized(self))]
+    pub fn write(&mut self, val: T) -> &mut T;
+
+    #[requires(is_initialized(&self))]
+    pub unsafe fn assume_init(self) -> T;
+
+    #[requires(is_initialized(self))]
+    pub unsafe fn assume_init_ref(&self) -> &T;
+
+    #[requires(is_initialized(self))]
+    pub unsafe fn assume_init_mut(&mut self) -> &mut T;
+
+    #[requires(is_initialized(self))]
+    #[ensures(!is_initialized(self))]
+    pub unsafe fn assume_init_drop(&mut self);
+}
+
+// The value behind a reference-counted pointer is shared, so dereferencing the
+// pointer has no side effects. Marking `deref` as pure lets the methods of the
+// value be called through the pointer in specifications.
+#[extern_spec]
+impl<T: ?Sized> ::core::ops::Deref for ::std::rc::Rc<T> {
+    #[pure]
+    fn deref(&self) -> &T;
+}
+
+#[extern_spec]
+impl<T> ::std::rc::Rc<T> {
+    #[ensures(snapshot_equality(&*result, &value))]
+    pub fn new(value: T) -> ::std::rc::Rc<T>;
+}
+
+#[extern_spec]
+impl<T: ?Sized> ::core::ops::Deref for ::std::sync::Arc<T> {
+    #[pure]
+    fn deref(&self) -> &T;
+}
+
+#[extern_spec]
+impl<T> ::std::sync::Arc<T> {
+    #[ensures(snapshot_equality(&*result, &value))]
+    pub fn new(value: T) -> ::std::sync::Arc<T>;
+}
@@ -1,12 +1,18 @@
+use prusti_common::exit_code::ExitCode;
 use prusti_rustc_interface::{
-    errors::{DiagnosticBuilder, EmissionGuarantee, MultiSpan},
+    errors::{Applicability, DiagnosticBuilder, EmissionGuarantee, MultiSpan},
     middle::ty::TyCtxt,
+    span::Span,
 };
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 pub struct EnvDiagnostic<'tcx> {
     tcx: TyCtxt<'tcx>,
     warn_buffer: RefCell<Vec<prusti_rustc_interface::errors::Diagnostic>>,
+    /// The most severe exit code of the errors emitted so far.
+    exit_code: Cell<ExitCode>,
+    /// The primary spans of the errors emitted so far.
+    error_spans: RefCell<Vec<Span>>,
 }
 
 impl<'tcx> EnvDiagnostic<'tcx> {
@@ -14,6 +20,8 @@ impl<'tcx> EnvDiagnostic<'tcx> {
         EnvDiagnostic {
             tcx,
             warn_buffer: RefCell::new(Vec::new()),
+            exit_code: Cell::new(ExitCode::Success),
+            error_spans: RefCell::new(Vec::new()),
         }
     }
 
@@ -22,6 +30,7 @@ impl<'tcx> EnvDiagnostic<'tcx> {
         sp: S,
         help: &Option<String>,
         notes: &[(String, Option<S>)],
+        suggestion: &Option<(String, Span, String)>,
     ) {
         diagnostic.set_span(sp);
         if let Some(help_msg) = help {
@@ -34,6 +43,14 @@ impl<'tcx> EnvDiagnostic<'tcx> {
                 diagnostic.note(note_msg);
             }
         }
+        if let Some((suggestion_msg, suggestion_sp, replacement)) = suggestion {
+            diagnostic.span_suggestion(
+                *suggestion_sp,
+                suggestion_msg,
+                replacement,
+                Applicability::MachineApplicable,
+            );
+        }
     }
 
     /// Emits an error message.
@@ -43,9 +60,14 @@ impl<'tcx> EnvDiagnostic<'tcx> {
         msg: &str,
         help: &Option<String>,
         notes: &[(String, Option<S>)],
+        suggestion: &Option<(String, Span, String)>,
     ) {
         let mut diagnostic = self.tcx.sess.struct_err(msg);
-        Self::configure_diagnostic(&mut diagnostic, sp, help, notes);
+        let multi_span: MultiSpan = sp.clone().into();
+        self.error_spans
+            .borrow_mut()
+            .extend_from_slice(multi_span.primary_spans());
+        Self::configure_diagnostic(&mut diagnostic, sp, help, notes, suggestion);
         for warn in self.warn_buffer.borrow_mut().iter_mut() {
             self.tcx.sess.diagnostic().emit_diagnostic(warn);
         }
@@ -59,9 +81,10 @@ impl<'tcx> EnvDiagnostic<'tcx> {
         msg: &str,
         help: &Option<String>,
         notes: &[(String, Option<S>)],
+        suggestion: &Option<(String, Span, String)>,
     ) {
         let mut diagnostic = self.tcx.sess.struct_warn(msg);
-        Self::configure_diagnostic(&mut diagnostic, sp, help, notes);
+        Self::configure_diagnostic(&mut diagnostic, sp, help, notes, suggestion);
         diagnostic.emit();
     }
 
@@ -72,9 +95,10 @@ impl<'tcx> EnvDiagnostic<'tcx> {
         msg: &str,
         help: &Option<String>,
         notes: &[(String, Option<S>)],
+        suggestion: &Option<(String, Span, String)>,
     ) {
         let mut diagnostic = self.tcx.sess.struct_warn(msg);
-        Self::configure_diagnostic(&mut diagnostic, sp, help, notes);
+        Self::configure_diagnostic(&mut diagnostic, sp, help, notes, suggestion);
         diagnostic.buffer(&mut self.warn_buffer.borrow_mut());
     }
 
@@ -82,4 +106,20 @@ impl<'tcx> EnvDiagnostic<'tcx> {
     pub fn has_errors(&self) -> bool {
         self.tcx.sess.has_errors().is_some()
     }
+
+    /// Records that an error corresponding to the given exit code has been
+    /// emitted.
+    pub fn record_exit_code(&self, exit_code: ExitCode) {
+        self.exit_code.set(self.exit_code.get().max(exit_code));
+    }
+
+    /// Returns the most severe exit code of the errors emitted so far.
+    pub fn exit_code(&self) -> ExitCode {
+        self.exit_code.get()
+    }
+
+    /// Returns the primary spans of the errors emitted so far.
+    pub fn error_spans(&self) -> Vec<Span> {
+        self.error_spans.borrow().clone()
+    }
 }
@@ -6,7 +6,7 @@
 
 use crate::environment::EnvDiagnostic;
 use ::log::warn;
-use prusti_common::config;
+use prusti_common::{config, exit_code::ExitCode};
 use prusti_rustc_interface::{errors::MultiSpan, span::Span};
 
 /// The Prusti message that will be reported to the user.
@@ -21,6 +21,8 @@ use prusti_rustc_interface::{errors::MultiSpan, span::Span};
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PrustiError {
     kind: PrustiErrorKind,
+    /// The exit code of Prusti if this error is reported to the user.
+    exit_code: ExitCode,
     /// If `true`, it should not be reported to the user. We need this in cases
     /// when the same error could be reported twice.
     ///
@@ -32,6 +34,9 @@ pub struct PrustiError {
     span: Box<MultiSpan>,
     help: Option<String>,
     notes: Vec<(String, Option<MultiSpan>)>,
+    /// A machine-applicable change that fixes the error: the message, the
+    /// span to replace, and the replacement.
+    suggestion: Option<(String, Span, String)>,
 }
 /// Determines how a `PrustiError` is reported.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -58,14 +63,16 @@ impl Ord for PrustiError {
 
 impl PrustiError {
     /// Private constructor. Use one of the following methods.
-    fn new(message: String, span: MultiSpan) -> Self {
+    fn new(message: String, span: MultiSpan, exit_code: ExitCode) -> Self {
         PrustiError {
             kind: PrustiErrorKind::Error,
+            exit_code,
             is_disabled: false,
             message,
             span: Box::new(span),
             help: None,
             notes: vec![],
+            suggestion: None,
         }
     }
 
@@ -75,6 +82,7 @@ impl PrustiError {
         PrustiError::new(
             format!("[Prusti: verification error] {}", message.to_string()),
             span,
+            ExitCode::VerificationFailure,
         )
     }
 
@@ -83,6 +91,7 @@ impl PrustiError {
         let mut error = PrustiError::new(
             format!("[Prusti: verification error] {}", message.to_string()),
             span,
+            ExitCode::VerificationFailure,
         );
         error.is_disabled = true;
         error
@@ -94,6 +103,7 @@ impl PrustiError {
         let mut error = PrustiError::new(
             format!("[Prusti: unsupported feature] {}", message.to_string()),
             span,
+            ExitCode::UnsupportedFeature,
         );
         if config::skip_unsupported_features() {
             error.set_warning();
@@ -107,13 +117,18 @@ impl PrustiError {
         PrustiError::new(
             format!("[Prusti: invalid specification] {}", message.to_string()),
             span,
+            ExitCode::InvalidSpecification,
         )
     }
 
     /// Report a non-fatal issue
     pub fn warning<S: ToString>(message: S, span: MultiSpan) -> Self {
         check_message(message.to_string());
-        let mut err = PrustiError::new(format!("[Prusti: warning] {}", message.to_string()), span);
+        let mut err = PrustiError::new(
+            format!("[Prusti: warning] {}", message.to_string()),
+            span,
+            ExitCode::Success,
+        );
         err.kind = PrustiErrorKind::Warning;
         err
     }
@@ -122,7 +137,11 @@ impl PrustiError {
     /// (e.g. cannot automatically include loop guard as an invariant)
     pub fn warning_on_error<S: ToString>(message: S, span: MultiSpan) -> Self {
         check_message(message.to_string());
-        let mut err = PrustiError::new(format!("[Prusti: warning] {}", message.to_string()), span);
+        let mut err = PrustiError::new(
+            format!("[Prusti: warning] {}", message.to_string()),
+            span,
+            ExitCode::Success,
+        );
         err.kind = PrustiErrorKind::WarningOnError;
         err
     }
@@ -132,7 +151,8 @@ impl PrustiError {
         check_message(message.to_string());
         let mut error = PrustiError::new(
             "[Prusti internal error] Prusti encountered an unexpected internal error".to_string(),
-            span
+            span,
+            ExitCode::InternalError,
         ).add_note(
             "We would appreciate a bug report: https://github.com/viperproject/prusti-dev/issues/new",
             None
@@ -175,6 +195,17 @@ impl PrustiError {
         self
     }
 
+    #[must_use]
+    pub fn set_suggestion<S: ToString, R: ToString>(
+        mut self,
+        message: S,
+        span: Span,
+        replacement: R,
+    ) -> Self {
+        self.suggestion = Some((message.to_string(), span, replacement.to_string()));
+        self
+    }
+
     pub fn add_note_mut<S: ToString>(&mut self, message: S, opt_span: Option<MultiSpan>) {
         self.notes.push((message.to_string(), opt_span));
     }
@@ -185,23 +216,29 @@ impl PrustiError {
     pub fn emit(self, env_diagnostic: &EnvDiagnostic) {
         assert!(!self.is_disabled);
         match self.kind {
-            PrustiErrorKind::Error => env_diagnostic.span_err_with_help_and_notes(
-                *self.span,
-                &self.message,
-                &self.help,
-                &self.notes,
-            ),
+            PrustiErrorKind::Error => {
+                env_diagnostic.record_exit_code(self.exit_code);
+                env_diagnostic.span_err_with_help_and_notes(
+                    *self.span,
+                    &self.message,
+                    &self.help,
+                    &self.notes,
+                    &self.suggestion,
+                )
+            }
             PrustiErrorKind::Warning => env_diagnostic.span_warn_with_help_and_notes(
                 *self.span,
                 &self.message,
                 &self.help,
                 &self.notes,
+                &self.suggestion,
             ),
             PrustiErrorKind::WarningOnError => env_diagnostic.span_warn_on_err_with_help_and_notes(
                 *self.span,
                 &self.message,
                 &self.help,
                 &self.notes,
+                &self.suggestion,
             ),
         };
     }
@@ -211,6 +248,24 @@ impl PrustiError {
         assert!(self.is_disabled);
     }
 
+    /// Whether the errors are reported with the same message at the same location. The locations
+    /// are compared ignoring the macro expansions they come from, so that the errors in different
+    /// expansions of the same macro are duplicates.
+    fn is_duplicate_of(&self, other: &Self) -> bool {
+        let location = |error: &Self| -> Vec<_> {
+            error
+                .span
+                .primary_spans()
+                .iter()
+                .map(|span| (span.lo(), span.hi()))
+                .collect()
+        };
+        self.kind == other.kind
+            && self.message == other.message
+            && self.help == other.help
+            && location(self) == location(other)
+    }
+
     /// Set the span of the failing assertion expression.
     ///
     /// Note: this is a noop if `opt_span` is None
@@ -237,6 +292,59 @@ impl PrustiError {
     }
 }
 
+/// Merges the errors that are reported with the same message at the same location, e.g. for several
+/// instantiations of a generic function or for several expansions of a macro. Each error is paired
+/// with a description of the item whose verification reported it. The first error of each group is
+/// kept and lists the other items and macro call sites as notes, of which at most `max_notes` are
+/// shown.
+pub fn deduplicate_errors(
+    errors: Vec<(String, PrustiError)>,
+    max_notes: usize,
+) -> Vec<PrustiError> {
+    let mut groups: Vec<(String, PrustiError, Vec<(String, Option<MultiSpan>)>)> = vec![];
+    for (origin, error) in errors {
+        let group = groups.iter_mut().find(|(_, first, _)| first.is_duplicate_of(&error));
+        let Some((first_origin, first, duplicates)) = group else {
+            groups.push((origin, error, vec![]));
+            continue;
+        };
+        let callsite = error
+            .span
+            .primary_span()
+            .filter(|span| span.from_expansion())
+            .map(|span| span.source_callsite())
+            .filter(|callsite| {
+                first.span.primary_span().map(|span| span.source_callsite()) != Some(*callsite)
+            });
+        if origin == *first_origin && callsite.is_none() {
+            continue;
+        }
+        let note = if callsite.is_some() {
+            format!("the same error is reported for this macro expansion in {}", origin)
+        } else {
+            format!("the same error is reported for {}", origin)
+        };
+        let note = (note, callsite.map(MultiSpan::from));
+        if !duplicates.contains(&note) {
+            duplicates.push(note);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(_, mut error, duplicates)| {
+            let hidden = duplicates.len().saturating_sub(max_notes);
+            error.notes.extend(duplicates.into_iter().take(max_notes));
+            if hidden > 0 {
+                error.notes.push((
+                    format!("the same error is reported in {} more places", hidden),
+                    None,
+                ));
+            }
+            error
+        })
+        .collect()
+}
+
 fn check_message(message: String) {
     debug_assert!(message.len() >= 3, "Message {:?} is too short", message);
     if message.get(0..1).unwrap() != message.get(0..1).unwrap().to_lowercase() {
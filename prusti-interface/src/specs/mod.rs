@@ -117,6 +117,7 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
     }
 
     fn determine_procedure_specs(&self, def_spec: &mut typed::DefSpecificationMap) {
+        let memory_safety_only = prusti_common::config::memory_safety_only();
         for (local_id, refs) in self.procedure_specs.iter() {
             let mut spec = SpecGraph::new(ProcedureSpecification::empty(local_id.to_def_id()));
 
@@ -124,6 +125,11 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
 
             for spec_id_ref in &refs.spec_id_refs {
                 match spec_id_ref {
+                    // Only the core proof obligations are checked.
+                    SpecIdRef::Precondition(_)
+                    | SpecIdRef::Postcondition(_)
+                    | SpecIdRef::Pledge { .. }
+                        if memory_safety_only => {}
                     SpecIdRef::Precondition(spec_id) => {
                         spec.add_precondition(*self.spec_functions.get(spec_id).unwrap(), self.env);
                     }
@@ -6,8 +6,16 @@
 #![feature(let_chains)]
 #![feature(option_result_contains)]
 
-use prusti_utils::{config, launch};
-use std::{env, fs, io, path::PathBuf, process::Command};
+use flate2::{write::GzEncoder, Compression};
+use prusti_utils::{config, exit_code::ExitCode, launch};
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+};
 
 fn main() {
     if let Err(code) = process(env::args().skip(1)) {
@@ -31,7 +39,13 @@ where
     let args = args.skip_while(|arg| arg == "prusti");
     // Remove the "-- -Pflag" arguments since these won't apply to `cargo check`.
     // They have already been loaded (and the Category B flags are used below).
-    let args = args.take_while(|arg| arg != "--");
+    let mut args: Vec<_> = args.take_while(|arg| arg != "--").collect();
+    // The profile has to be selected before the configuration is loaded.
+    if let Some(profile) = extract_profile_arg(&mut args) {
+        env::set_var("PRUSTI_PROFILE", profile);
+    }
+    let emit_repro = extract_flag_arg(&mut args, "--emit-repro");
+    let session = extract_flag_arg(&mut args, "--session");
 
     // Category B flags (see dev-guide flags table):
     let cargo_path = config::cargo_path();
@@ -44,8 +58,45 @@ where
     };
     let cargo_target = env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string());
     let cargo_target: PathBuf = [cargo_target, "verify".to_string()].into_iter().collect();
-    let exit_status = Command::new(cargo_path)
-        .arg(&command)
+    let exit_code_file = cargo_target.join("exit-codes");
+    // Exit codes of a previous run are not relevant.
+    fs::remove_file(&exit_code_file).ok();
+    let mut cmd = Command::new(cargo_path);
+    cmd.arg(&command);
+    if let Some(profile) = config::profile() {
+        // Use the same profile for all crates.
+        cmd.env("PRUSTI_PROFILE", profile);
+    }
+    let repro_dir = cargo_target.join("repro");
+    let viper_program_dir = cargo_target.join("log").join("viper_program");
+    if emit_repro {
+        // Only collect the data of this run.
+        fs::remove_dir_all(&repro_dir).ok();
+        fs::remove_dir_all(&viper_program_dir).ok();
+        cmd.env("PRUSTI_REPRO_DIR", &repro_dir)
+            .env("PRUSTI_DUMP_VIPER_PROGRAM", "true");
+    }
+    let session_server = if session {
+        let mut prusti_server_path = prusti_rustc_path.with_file_name("prusti-server");
+        if cfg!(windows) {
+            prusti_server_path.set_extension("exe");
+        }
+        let server = SessionServer::start(&prusti_server_path, &cargo_target).map_err(|error| {
+            eprintln!("Failed to start the Prusti server of the session: {}", error);
+            -1
+        })?;
+        cmd.env("PRUSTI_SERVER_ADDRESS", &server.address)
+            .env("PRUSTI_SERVER_SESSION", "true");
+        // The crates are verified one at a time, so that each crate is verified after its
+        // dependencies and can reuse the domains that the server received from them.
+        if !args.iter().any(|arg| arg.starts_with("-j") || arg.starts_with("--jobs")) {
+            cmd.arg("--jobs=1");
+        }
+        Some(server)
+    } else {
+        None
+    };
+    let exit_status = cmd
         .args(features)
         .args(args)
         .env("RUST_TOOLCHAIN", launch::get_rust_toolchain_channel())
@@ -63,19 +114,144 @@ where
         .env("DEFAULT_PRUSTI_FULL_COMPILATION", "true")
         .env("DEFAULT_PRUSTI_LOG_DIR", cargo_target.join("log"))
         .env("DEFAULT_PRUSTI_CACHE_PATH", cargo_target.join("cache.bin"))
+        .env("DEFAULT_PRUSTI_EXIT_CODE_FILE", &exit_code_file)
         .status()
         .expect("could not run cargo");
 
+    if let Some(server) = session_server {
+        server.stop();
+    }
+
+    if emit_repro {
+        let archive = cargo_target.join("prusti-repro.tar.gz");
+        match write_repro_archive(&archive, &repro_dir, &viper_program_dir) {
+            Ok(()) => eprintln!("Reproduction bundle written to {}", archive.display()),
+            Err(error) => eprintln!("Failed to write the reproduction bundle: {}", error),
+        }
+    }
+
     if exit_status.success() {
         if command == "build" {
             copy_exported_specs(cargo_target).ok();
         }
         Ok(())
     } else {
-        Err(exit_status.code().unwrap_or(-1))
+        // Cargo uses the same exit code for any failing crate, so we report
+        // the most severe exit code of the Prusti invocations instead.
+        let prusti_exit_code = fs::read_to_string(&exit_code_file)
+            .ok()
+            .filter(|_| config::differentiated_exit_codes())
+            .and_then(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| line.parse::<ExitCode>().ok())
+                    .max()
+            })
+            .filter(|&exit_code| exit_code != ExitCode::Success);
+        Err(prusti_exit_code
+            .map(ExitCode::code)
+            .unwrap_or_else(|| exit_status.code().unwrap_or(-1)))
+    }
+}
+
+/// Remove the `--prusti-profile <name>` (or `--prusti-profile=<name>`) argument, which selects a
+/// profile of the `Prusti.toml` file, and return the name of the profile. Cargo's own `--profile`
+/// argument is passed on to Cargo.
+fn extract_profile_arg(args: &mut Vec<String>) -> Option<String> {
+    let position = args
+        .iter()
+        .position(|arg| arg == "--prusti-profile" || arg.starts_with("--prusti-profile="))?;
+    let arg = args.remove(position);
+    if let Some(profile) = arg.strip_prefix("--prusti-profile=") {
+        Some(profile.to_string())
+    } else if position < args.len() {
+        Some(args.remove(position))
+    } else {
+        panic!("The `--prusti-profile` argument requires the name of a profile");
     }
 }
 
+/// A Prusti server that verifies all crates of a `cargo prusti --session` run.
+struct SessionServer {
+    process: Child,
+    address: String,
+}
+
+impl SessionServer {
+    /// Starts the server on a free port and waits until it listens.
+    fn start(prusti_server_path: &Path, cargo_target: &Path) -> io::Result<Self> {
+        let mut process = Command::new(prusti_server_path)
+            .args(["--port", "0"])
+            // The same defaults as for the crates that are verified without a server.
+            .env("DEFAULT_PRUSTI_LOG_DIR", cargo_target.join("log"))
+            .env("DEFAULT_PRUSTI_CACHE_PATH", cargo_target.join("cache.bin"))
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let mut stdout = BufReader::new(process.stdout.take().unwrap());
+        let mut line = String::new();
+        let port = loop {
+            line.clear();
+            if stdout.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the server stopped before listening on a port",
+                ));
+            }
+            if let Some(port) = line.trim().strip_prefix("port: ") {
+                break port.to_string();
+            }
+        };
+        // Keep reading the output, so that the server does not fail to write it.
+        thread::spawn(move || io::copy(&mut stdout, &mut io::sink()));
+        Ok(Self {
+            process,
+            address: format!("127.0.0.1:{}", port),
+        })
+    }
+
+    /// Saves the verification cache of the server and stops it.
+    fn stop(mut self) {
+        let saved = TcpStream::connect(&self.address).and_then(|mut stream| {
+            stream.write_all(
+                b"POST /save HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\n\
+                Connection: close\r\n\r\n",
+            )?;
+            io::copy(&mut stream, &mut io::sink())
+        });
+        if let Err(error) = saved {
+            eprintln!("Failed to save the verification cache of the session: {}", error);
+        }
+        self.process.kill().ok();
+        self.process.wait().ok();
+    }
+}
+
+/// Remove the given flag from the arguments and return whether it was present.
+fn extract_flag_arg(args: &mut Vec<String>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != len
+}
+
+/// Pack the reproduction bundles written by the Prusti invocations, together
+/// with the dumped Viper programs, into a `.tar.gz` archive.
+fn write_repro_archive(
+    archive: &Path,
+    repro_dir: &Path,
+    viper_program_dir: &Path,
+) -> io::Result<()> {
+    let encoder = GzEncoder::new(fs::File::create(archive)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    if repro_dir.is_dir() {
+        builder.append_dir_all("prusti-repro", repro_dir)?;
+    }
+    if viper_program_dir.is_dir() {
+        builder.append_dir_all("prusti-repro/viper_program", viper_program_dir)?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
 /// Copy specs from '{cargo_target}/*/deps/*.specs' to '{cargo_target}/*/*.specs'
 fn copy_exported_specs(cargo_target: PathBuf) -> io::Result<()> {
     for de in fs::read_dir(cargo_target)? {
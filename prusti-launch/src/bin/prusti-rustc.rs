@@ -6,6 +6,8 @@
 
 #[cfg(target_family = "unix")]
 use nix::unistd::{setpgid, Pid};
+#[cfg(target_family = "unix")]
+use prusti_utils::config;
 use prusti_utils::launch;
 use std::{
     env,
@@ -21,6 +23,7 @@ fn main() {
 }
 
 fn process(mut args: Vec<String>) -> Result<(), i32> {
+    let bisect = extract_flag_arg(&mut args, "--bisect");
     let prusti_home = launch::get_current_executable_dir();
 
     let mut prusti_driver_path = prusti_home.join("prusti-driver");
@@ -48,6 +51,19 @@ fn process(mut args: Vec<String>) -> Result<(), i32> {
 
     launch::set_environment_settings(&mut cmd, &prusti_home, &java_home);
 
+    // Connect to the server started with `prusti-server --daemon`, starting it if necessary.
+    #[cfg(target_family = "unix")]
+    if config::server_address().as_deref() == Some("DAEMON") {
+        let socket_path = config::server_socket_path();
+        if let Err(error) = launch::daemon::ensure_daemon_server(&prusti_home, &socket_path) {
+            panic!("Failed to start the Prusti server daemon: {}", error);
+        }
+        cmd.env(
+            "PRUSTI_SERVER_ADDRESS",
+            format!("unix:{}", socket_path.display()),
+        );
+    }
+
     // Setting RUSTC_WRAPPER causes Cargo to pass 'rustc' as the first argument.
     // We're invoking the compiler programmatically, so we ignore this
     let rustc_pos = args
@@ -145,6 +161,10 @@ fn process(mut args: Vec<String>) -> Result<(), i32> {
         }
     }
 
+    if bisect {
+        return bisect_failure(&cmd, &args);
+    }
+
     let exit_status = cmd
         .status()
         .unwrap_or_else(|_| panic!("failed to execute prusti-driver ({:?})", prusti_driver_path));
@@ -155,3 +175,33 @@ fn process(mut args: Vec<String>) -> Result<(), i32> {
         Err(exit_status.code().unwrap_or(-1))
     }
 }
+
+/// Remove the given flag from the arguments and return whether it was present.
+fn extract_flag_arg(args: &mut Vec<String>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != len
+}
+
+/// Minimize the source file while Prusti keeps failing on it in the same way.
+fn bisect_failure(cmd: &Command, args: &[String]) -> Result<(), i32> {
+    let source_file = args
+        .iter()
+        .map(Path::new)
+        .find(|arg| arg.extension() == Some("rs".as_ref()))
+        .expect("The `--bisect` argument requires the path of a Rust source file");
+    match launch::bisect::bisect(cmd, source_file) {
+        Ok(Some(minimized_file)) => {
+            eprintln!("Minimized input written to {}", minimized_file.display());
+            Ok(())
+        }
+        Ok(None) => {
+            eprintln!("Prusti does not fail on {}", source_file.display());
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("Failed to minimize {}: {}", source_file.display(), error);
+            Err(1)
+        }
+    }
+}
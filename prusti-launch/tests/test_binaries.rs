@@ -211,3 +211,73 @@ fn test_prusti_rustc_with_server() {
         cmd
     });
 }
+
+#[test]
+fn test_prusti_rustc_bisect() {
+    let prusti_rustc = find_executable_path("prusti-rustc");
+    let dir = std::env::temp_dir().join(format!("prusti-bisect-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("wrong_ensures.rs");
+    std::fs::copy("tests/fail/wrong_ensures.rs", &program).unwrap();
+
+    let output = Command::new(&prusti_rustc)
+        .arg("--edition=2018")
+        .arg("--bisect")
+        .arg(&program)
+        .env("RUST_BACKTRACE", "1")
+        .output()
+        .expect("failed to execute prusti-rustc");
+    assert!(
+        output.status.success(),
+        "prusti-rustc --bisect failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let original = std::fs::read_to_string(&program).unwrap();
+    let minimized = std::fs::read_to_string(dir.join("wrong_ensures.min.rs")).unwrap();
+    assert!(minimized.lines().count() < original.lines().count());
+    assert!(minimized.contains("#[ensures(result == 40)]"));
+    assert!(!minimized.contains("fn id("));
+    assert!(!dir.join("wrong_ensures_bisect.rs").exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_prusti_rustc_dump_source_locations() {
+    let prusti_rustc = find_executable_path("prusti-rustc");
+    let dir = std::env::temp_dir().join(format!("prusti-locations-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("with_specs.rs");
+    std::fs::copy("tests/pass/with_specs.rs", &program).unwrap();
+
+    let output = Command::new(&prusti_rustc)
+        .arg("--edition=2018")
+        .arg(&program)
+        .current_dir(&dir)
+        .env("PRUSTI_DUMP_VIPER_PROGRAM", "true")
+        .env("PRUSTI_LOG_DIR", dir.join("log"))
+        .env("RUST_BACKTRACE", "1")
+        .output()
+        .expect("failed to execute prusti-rustc");
+    assert!(
+        output.status.success(),
+        "prusti-rustc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The `assert!(x == 42)` of `test` is on line 14, column 5.
+    let pattern = format!("{}/log/viper_program/*.vpr.json", dir.display());
+    let locations: Vec<String> = glob(&pattern)
+        .unwrap()
+        .map(|path| std::fs::read_to_string(path.unwrap()).unwrap())
+        .collect();
+    assert!(!locations.is_empty(), "no source locations were dumped");
+    assert!(locations.iter().any(|locations| {
+        locations.split('}').any(|location| {
+            location.contains("with_specs.rs")
+                && location.contains("\"line\": 14,")
+                && location.contains("\"column\": 5,")
+        })
+    }));
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -4,58 +4,314 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::VerificationRequest;
+use crate::{
+    compression::{compress, ChunkDecoder, GZIP},
+    termination_result, MessageDecoder, MessageEncoding, ServerMessage, VerificationRequest,
+};
+use log::warn;
 use prusti_common::config;
-use reqwest::Client;
+use reqwest::{
+    header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE},
+    Client,
+};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{fmt, io, time::Duration};
 use url::{ParseError, Url};
 use viper::VerificationResult;
 
+/// The header with the `server_client_token` that identifies the client to the server.
+pub const CLIENT_TOKEN_HEADER: &str = "x-prusti-client";
+
 pub struct PrustiClient {
     client: Client,
-    server_url: Url,
+    server: ServerLocation,
+}
+
+enum ServerLocation {
+    Url(Url),
+    /// A server started with `prusti-server --daemon`.
+    #[cfg(unix)]
+    UnixSocket(PathBuf),
+}
+
+/// The error of a verification request that did not return a result.
+#[derive(Debug)]
+pub enum ClientError {
+    Http(reqwest::Error),
+    /// The response was compressed with an unsupported encoding or is corrupted.
+    Encoding(io::Error),
+    #[cfg(unix)]
+    Socket(io::Error),
+    #[cfg(unix)]
+    Hyper(hyper::Error),
+    #[cfg(unix)]
+    Status(hyper::StatusCode),
+}
+
+impl ClientError {
+    /// Whether the error is caused by the network rather than by the request, so that
+    /// resubmitting the request might succeed.
+    fn is_transient(&self) -> bool {
+        match self {
+            ClientError::Http(error) => {
+                error.is_connect()
+                    || error.is_timeout()
+                    || error.is_request()
+                    || error.is_body()
+                    || error.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS)
+            }
+            ClientError::Encoding(_) => false,
+            #[cfg(unix)]
+            ClientError::Socket(error) => matches!(
+                error.kind(),
+                io::ErrorKind::NotFound
+                    | io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::BrokenPipe
+            ),
+            #[cfg(unix)]
+            ClientError::Hyper(error) => {
+                error.is_closed() || error.is_incomplete_message() || error.is_canceled()
+            }
+            #[cfg(unix)]
+            ClientError::Status(status) => *status == hyper::StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(error) => write!(f, "{}", error),
+            ClientError::Encoding(error) => write!(f, "{}", error),
+            #[cfg(unix)]
+            ClientError::Socket(error) => write!(f, "{}", error),
+            #[cfg(unix)]
+            ClientError::Hyper(error) => write!(f, "{}", error),
+            #[cfg(unix)]
+            ClientError::Status(status) => write!(f, "the server responded with {}", status),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        ClientError::Http(error)
+    }
+}
+
+#[cfg(unix)]
+impl From<io::Error> for ClientError {
+    fn from(error: io::Error) -> Self {
+        ClientError::Socket(error)
+    }
+}
+
+#[cfg(unix)]
+impl From<hyper::Error> for ClientError {
+    fn from(error: hyper::Error) -> Self {
+        ClientError::Hyper(error)
+    }
 }
 
 impl PrustiClient {
+    /// Creates a client of the server at the given address, which is either a URL, an address
+    /// and port, or `unix:` followed by the path of a Unix socket.
     pub fn new<S: ToString>(server_address: S) -> Result<Self, ParseError> {
         let mut address = server_address.to_string();
+        #[cfg(unix)]
+        if let Some(socket_path) = address.strip_prefix("unix:") {
+            return Ok(Self {
+                client: Client::new(),
+                server: ServerLocation::UnixSocket(PathBuf::from(socket_path)),
+            });
+        }
         if !address.starts_with("http") {
             address = format!("http://{}", address);
         }
         Ok(Self {
             client: Client::new(),
-            server_url: Url::parse(address.as_str())?,
+            server: ServerLocation::Url(Url::parse(address.as_str())?),
         })
     }
 
+    /// Sends the request to the server and returns the result of its verification.
     pub async fn verify(
         &self,
         request: VerificationRequest,
-    ) -> reqwest::Result<VerificationResult> {
+    ) -> Result<VerificationResult, ClientError> {
+        let messages = self.process_request(request).await?;
+        Ok(termination_result(messages.into_iter()))
+    }
+
+    /// Sends the request to the server and returns the messages about its verification, which
+    /// end with a `ServerMessage::Termination`.
+    pub async fn process_request(
+        &self,
+        request: VerificationRequest,
+    ) -> Result<Vec<ServerMessage>, ClientError> {
+        let mut messages = Vec::new();
+        self.process_request_with(request, &mut |message| messages.push(message))
+            .await?;
+        Ok(messages)
+    }
+
+    /// Sends the request to the server and passes each message about its verification to
+    /// `on_message` as soon as the server sends it. The last message is a
+    /// `ServerMessage::Termination`. After a network failure, or if the server rejected the
+    /// request because this client has too many requests in progress, the request is
+    /// resubmitted up to `server_max_retries` times, with an exponentially increasing delay, but
+    /// only if no message was received yet. Resubmitting a request is idempotent, because the
+    /// server caches the results of requests by their hash.
+    pub async fn process_request_with(
+        &self,
+        request: VerificationRequest,
+        on_message: &mut (dyn FnMut(ServerMessage) + Send),
+    ) -> Result<(), ClientError> {
+        let mut delay = Duration::from_millis(config::server_retry_delay());
+        let mut retries = 0;
+        loop {
+            let mut received = false;
+            let mut receive = |message| {
+                received = true;
+                on_message(message);
+            };
+            let result = match &self.server {
+                ServerLocation::Url(server_url) => {
+                    self.send(server_url, &request, &mut receive).await
+                }
+                #[cfg(unix)]
+                ServerLocation::UnixSocket(socket_path) => {
+                    send_to_socket(socket_path, &request, &mut receive).await
+                }
+            };
+            match result {
+                Err(error)
+                    if error.is_transient()
+                        && !received
+                        && retries < config::server_max_retries() =>
+                {
+                    retries += 1;
+                    warn!(
+                        "Verification request {} for program {} failed ({}), retrying in {:?} \
+                        (retry {} of {})",
+                        request.get_hash(),
+                        request.program.get_name(),
+                        error,
+                        delay,
+                        retries,
+                        config::server_max_retries()
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// The hashes of the domains that the requests of the current verification session can omit
+    /// (see `SessionDefinitions`). Servers on a Unix socket are local, so their requests do not
+    /// omit any domain.
+    pub async fn session_domains(&self) -> Result<Vec<u64>, ClientError> {
+        match &self.server {
+            ServerLocation::Url(server_url) => Ok(self
+                .client
+                .get(server_url.join("session/domains").unwrap())
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?),
+            #[cfg(unix)]
+            ServerLocation::UnixSocket(_) => Ok(Vec::new()),
+        }
+    }
+
+    async fn send(
+        &self,
+        server_url: &Url,
+        request: &VerificationRequest,
+        on_message: &mut (dyn FnMut(ServerMessage) + Send),
+    ) -> Result<(), ClientError> {
         let use_json = config::json_communication();
-        let base = self.client.post(
-            self.server_url
+        let mut base = self.client.post(
+            server_url
                 .join(if use_json { "json/" } else { "bincode/" })
                 .unwrap()
                 .join("verify/")
                 .unwrap(),
         );
-        let response = if use_json {
-            base.json(&request)
-                .send()
-                .await?
-                .error_for_status()?
-                .json()
-                .await?
+        if let Some(token) = config::server_client_token() {
+            base = base.header(CLIENT_TOKEN_HEADER, token);
+        }
+        let body = if use_json {
+            base = base.header(CONTENT_TYPE, "application/json");
+            serde_json::to_vec(request).expect("error encoding verification request")
         } else {
-            let bytes = base
-                .body(bincode::serialize(&request).expect("error encoding verification request"))
-                .send()
-                .await?
-                .error_for_status()?
-                .bytes()
-                .await?;
-            bincode::deserialize(&bytes).expect("error decoding verification result")
+            bincode::serialize(request).expect("error encoding verification request")
+        };
+        let body = if config::server_compression() {
+            base = base.header(CONTENT_ENCODING, GZIP).header(ACCEPT_ENCODING, GZIP);
+            compress(&body)
+        } else {
+            body
         };
-        Ok(response)
+        let mut response = base.body(body).send().await?.error_for_status()?;
+        let encoding = response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|encoding| encoding.to_str().ok())
+            .map(str::to_string);
+        let mut decoder = ChunkDecoder::new(encoding.as_deref()).map_err(ClientError::Encoding)?;
+        let mut messages = MessageDecoder::new(if use_json {
+            MessageEncoding::Json
+        } else {
+            MessageEncoding::Bincode
+        });
+        while let Some(chunk) = response.chunk().await? {
+            let chunk = decoder.decode(&chunk).map_err(ClientError::Encoding)?;
+            for message in messages.decode(&chunk) {
+                on_message(message);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sends the request to the server listening on the Unix socket. The request is always encoded
+/// with bincode, because the server is local.
+#[cfg(unix)]
+async fn send_to_socket(
+    socket_path: &std::path::Path,
+    request: &VerificationRequest,
+    on_message: &mut (dyn FnMut(ServerMessage) + Send),
+) -> Result<(), ClientError> {
+    use hyper::body::HttpBody;
+    let stream = tokio::net::UnixStream::connect(socket_path).await?;
+    let (mut sender, connection) = hyper::client::conn::handshake(stream).await?;
+    tokio::spawn(connection);
+    let mut http_request = hyper::Request::post("/bincode/verify/")
+        .header(hyper::header::HOST, "localhost");
+    if let Some(token) = config::server_client_token() {
+        http_request = http_request.header(CLIENT_TOKEN_HEADER, token);
+    }
+    let http_request = http_request
+        .body(hyper::Body::from(
+            bincode::serialize(request).expect("error encoding verification request"),
+        ))
+        .expect("error building verification request");
+    let response = sender.send_request(http_request).await?;
+    if !response.status().is_success() {
+        return Err(ClientError::Status(response.status()));
+    }
+    let mut body = response.into_body();
+    let mut messages = MessageDecoder::new(MessageEncoding::Bincode);
+    while let Some(chunk) = body.data().await {
+        for message in messages.decode(&chunk?) {
+            on_message(message);
+        }
     }
+    Ok(())
 }
@@ -0,0 +1,157 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The compression of the bodies of the requests and responses exchanged with
+//! the server, negotiated with the `Content-Encoding` and `Accept-Encoding`
+//! headers. Only `gzip` is supported.
+
+use flate2::{read::GzDecoder, write, write::GzEncoder, Compression};
+use std::{
+    io::{self, Read, Write},
+    mem,
+};
+
+/// The name of the supported encoding in the `Content-Encoding` and `Accept-Encoding` headers.
+pub(crate) const GZIP: &str = "gzip";
+
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    // Programs are large and sent once, so a fast compression pays off more than a small one.
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    encoder
+        .write_all(data)
+        .and_then(|()| encoder.finish())
+        .expect("compressing into memory cannot fail")
+}
+
+pub(crate) fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Decodes a body with the encoding given by its `Content-Encoding` header.
+pub(crate) fn decode(content_encoding: Option<&str>, body: &[u8]) -> io::Result<Vec<u8>> {
+    match content_encoding.map(str::trim) {
+        None | Some("identity") => Ok(body.to_vec()),
+        Some(GZIP) => decompress(body),
+        Some(encoding) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported content encoding {}", encoding),
+        )),
+    }
+}
+
+/// Compresses a body that is sent in chunks, such that each chunk can be
+/// decompressed as soon as it arrives.
+pub(crate) struct ChunkCompressor(GzEncoder<Vec<u8>>);
+
+impl ChunkCompressor {
+    pub(crate) fn new() -> Self {
+        Self(GzEncoder::new(Vec::new(), Compression::fast()))
+    }
+
+    pub(crate) fn compress(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.0
+            .write_all(chunk)
+            .and_then(|()| self.0.flush())
+            .expect("compressing into memory cannot fail");
+        mem::take(self.0.get_mut())
+    }
+
+    /// The end of the compressed body.
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.0.finish().expect("compressing into memory cannot fail")
+    }
+}
+
+/// Decodes a body that arrives in chunks, with the encoding given by its
+/// `Content-Encoding` header.
+pub(crate) enum ChunkDecoder {
+    Identity,
+    Gzip(write::GzDecoder<Vec<u8>>),
+}
+
+impl ChunkDecoder {
+    pub(crate) fn new(content_encoding: Option<&str>) -> io::Result<Self> {
+        match content_encoding.map(str::trim) {
+            None | Some("identity") => Ok(ChunkDecoder::Identity),
+            Some(GZIP) => Ok(ChunkDecoder::Gzip(write::GzDecoder::new(Vec::new()))),
+            Some(encoding) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported content encoding {}", encoding),
+            )),
+        }
+    }
+
+    /// Returns the decoded part of the body that the chunk completes.
+    pub(crate) fn decode(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            ChunkDecoder::Identity => Ok(chunk.to_vec()),
+            ChunkDecoder::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                Ok(mem::take(decoder.get_mut()))
+            }
+        }
+    }
+}
+
+/// Whether the `Accept-Encoding` header allows responses compressed with `gzip`.
+pub(crate) fn accepts_gzip(accept_encoding: Option<&str>) -> bool {
+    accept_encoding.map_or(false, |accept_encoding| {
+        accept_encoding.split(',').any(|encoding| {
+            let mut parts = encoding.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let rejected = parts.any(|parameter| {
+                parameter
+                    .strip_prefix("q=")
+                    .and_then(|quality| quality.parse::<f32>().ok())
+                    .map_or(false, |quality| quality == 0.0)
+            });
+            (name == GZIP || name == "*") && !rejected
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let data = b"method m() { assert true }".repeat(100);
+        let compressed = compress(&data);
+        assert!(compressed.len() < data.len());
+        assert_eq!(decode(Some(GZIP), &compressed).unwrap(), data);
+        assert_eq!(decode(None, &data).unwrap(), data);
+        assert!(decode(Some("br"), &data).is_err());
+    }
+
+    #[test]
+    fn chunked_roundtrip() {
+        let data = b"method m() { assert true }".repeat(100);
+        let mut compressor = ChunkCompressor::new();
+        let mut decoder = ChunkDecoder::new(Some(GZIP)).unwrap();
+        let mut decompressed = Vec::new();
+        for chunk in data.chunks(100) {
+            // Each chunk can be decompressed before the next one is compressed.
+            decompressed.extend(decoder.decode(&compressor.compress(chunk)).unwrap());
+            assert_eq!(decompressed, data[..decompressed.len()]);
+        }
+        decompressed.extend(decoder.decode(&compressor.finish()).unwrap());
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn accept_encoding() {
+        assert!(accepts_gzip(Some("gzip")));
+        assert!(accepts_gzip(Some("deflate, gzip;q=0.5")));
+        assert!(accepts_gzip(Some("*")));
+        assert!(!accepts_gzip(Some("gzip;q=0")));
+        assert!(!accepts_gzip(Some("identity")));
+        assert!(!accepts_gzip(None));
+    }
+}
@@ -0,0 +1,97 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The files dumped with the `dump_debug_session` flag and loaded by the
+//! verification debugger (`prusti-server debug`). A session file contains a
+//! verification request together with its result; the fold-unfold states of
+//! the methods are dumped to separate files next to it while the methods are
+//! encoded.
+
+use crate::VerificationRequest;
+use prusti_common::report::log;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use viper::VerificationResult;
+
+/// The namespace of the log directory in which the sessions are dumped.
+pub const DEBUG_SESSION_NAMESPACE: &str = "debug_session";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DebugSession {
+    pub request: VerificationRequest,
+    pub result: VerificationResult,
+}
+
+/// The fold-unfold states of a method, by the labels of its basic blocks.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct MethodStates {
+    pub method: String,
+    pub blocks: BTreeMap<String, BlockStates>,
+}
+
+/// The fold-unfold states at the entry and at the exit of a basic block.
+/// They are missing for the blocks that the algorithm did not reach.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct BlockStates {
+    pub entry: Option<FoldUnfoldState>,
+    pub exit: Option<FoldUnfoldState>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct FoldUnfoldState {
+    pub acc: Vec<String>,
+    pub pred: Vec<String>,
+}
+
+impl DebugSession {
+    pub fn dump(&self) {
+        log::report_with_writer(
+            DEBUG_SESSION_NAMESPACE,
+            format!("{}.session.json", self.request.program.get_name_with_check_mode()),
+            |writer| serde_json::to_writer(writer, self).unwrap(),
+        );
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        serde_json::from_reader(io::BufReader::new(file)).map_err(io::Error::from)
+    }
+}
+
+impl MethodStates {
+    pub fn dump(&self, source_filename: &str) {
+        log::report_with_writer(
+            DEBUG_SESSION_NAMESPACE,
+            format!("{}.{}.states.json", source_filename, self.method),
+            |writer| serde_json::to_writer(writer, self).unwrap(),
+        );
+    }
+
+    /// Loads the fold-unfold states dumped in the directory of the session
+    /// file, by the names of their methods. The files that cannot be read are
+    /// skipped.
+    pub fn load_all(session_file: &Path) -> BTreeMap<String, MethodStates> {
+        let dir = session_file
+            .parent()
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        let Ok(entries) = fs::read_dir(dir) else {
+            return BTreeMap::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.to_string_lossy().ends_with(".states.json"))
+            .filter_map(|path| {
+                let file = fs::File::open(path).ok()?;
+                serde_json::from_reader::<_, MethodStates>(io::BufReader::new(file)).ok()
+            })
+            .map(|states| (states.method.clone(), states))
+            .collect()
+    }
+}
@@ -0,0 +1,620 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! An interactive debugger for failing verifications (`prusti-server debug`).
+//! It loads a session dumped with the `dump_debug_session` flag and lets the
+//! user walk through the basic blocks of the encoded methods, inspect the
+//! fold-unfold states, see which statements failed, and re-run a single
+//! assertion against the backend with all other assertions assumed.
+
+use crate::{
+    debug_session::{DebugSession, FoldUnfoldState, MethodStates},
+    jvm_memory, process_verification_request, ReusableVerifier, VerificationRequest,
+};
+use prusti_common::{
+    config,
+    vir::{self, program::Program},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+use viper::{PersistentCache, VerificationError, VerificationResult, Viper};
+
+const HELP: &str = "\
+Commands:
+  methods                 list the methods of the program
+  method <name|index>     select a method
+  blocks                  list the basic blocks of the selected method
+  block [<label|index>]   show (and select) a basic block
+  step [<n>]              go to the (n-th) successor of the selected block
+  state                   show the fold-unfold states of the selected block
+  errors                  list the verification errors
+  check <index>           re-run the assert or exhale with the given index in the selected
+                          block, assuming all other assertions of the method
+  help                    show this message
+  quit                    exit the debugger
+Statements marked with `!` failed in the dumped verification.";
+
+/// Runs the debugger on the session dumped to `session_file`, reading the
+/// commands from the standard input.
+pub fn run_debugger(session_file: &Path) -> io::Result<()> {
+    let session = DebugSession::load(session_file)?;
+    let states = MethodStates::load_all(session_file);
+    let mut debugger = Debugger::new(session, states)?;
+    println!(
+        "Loaded program {} with {} methods. Type `help` for the list of commands.",
+        debugger.program().name,
+        debugger.program().methods.len()
+    );
+    debugger.print_errors();
+    let stdin = io::stdin();
+    loop {
+        print!("(prusti-debug) ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let result = match words.as_slice() {
+            [] => Ok(()),
+            ["quit" | "exit" | "q"] => return Ok(()),
+            ["help" | "h"] => {
+                println!("{}", HELP);
+                Ok(())
+            }
+            ["methods"] => {
+                debugger.print_methods();
+                Ok(())
+            }
+            ["method", name] => debugger.select_method(name),
+            ["blocks"] => {
+                debugger.print_blocks();
+                Ok(())
+            }
+            ["block" | "b"] => {
+                debugger.print_block();
+                Ok(())
+            }
+            ["block" | "b", name] => debugger.select_block(name).map(|()| debugger.print_block()),
+            ["step" | "s"] => debugger.step(None),
+            ["step" | "s", n] => parse_index(n).and_then(|n| debugger.step(Some(n))),
+            ["state"] => {
+                debugger.print_state();
+                Ok(())
+            }
+            ["errors"] => {
+                debugger.print_errors();
+                Ok(())
+            }
+            ["check", index] => parse_index(index).and_then(|index| debugger.check(index)),
+            _ => Err(format!("Unknown command `{}`, type `help`", line.trim())),
+        };
+        if let Err(message) = result {
+            println!("{}", message);
+        }
+    }
+}
+
+fn parse_index(index: &str) -> Result<usize, String> {
+    index
+        .parse()
+        .map_err(|_| format!("`{}` is not an index", index))
+}
+
+struct Debugger {
+    session: DebugSession,
+    states: BTreeMap<String, MethodStates>,
+    /// The position ids of the statements that failed in the session.
+    failing_positions: BTreeSet<String>,
+    /// The index of the selected method.
+    method: usize,
+    /// The index of the selected block in the selected method.
+    block: usize,
+    /// Started on the first `check` command.
+    viper: Option<Viper>,
+}
+
+impl Debugger {
+    fn new(session: DebugSession, states: BTreeMap<String, MethodStates>) -> io::Result<Self> {
+        if !matches!(session.request.program, Program::Legacy(_)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "only the programs of the default encoding can be debugged",
+            ));
+        }
+        let failing_positions = match &session.result {
+            VerificationResult::Failure(errors) => {
+                errors.iter().filter_map(error_position).collect()
+            }
+            _ => BTreeSet::new(),
+        };
+        Ok(Self {
+            session,
+            states,
+            failing_positions,
+            method: 0,
+            block: 0,
+            viper: None,
+        })
+    }
+
+    fn program(&self) -> &vir::Program {
+        match &self.session.request.program {
+            Program::Legacy(program) => program,
+            Program::Low(_) => unreachable!(),
+        }
+    }
+
+    fn method(&self) -> Result<&vir::CfgMethod, String> {
+        self.program()
+            .methods
+            .get(self.method)
+            .ok_or_else(|| "The program has no methods".to_string())
+    }
+
+    fn is_failing(&self, stmt: &vir::Stmt) -> bool {
+        stmt.pos()
+            .map_or(false, |pos| self.failing_positions.contains(&pos.id().to_string()))
+    }
+
+    fn print_methods(&self) {
+        for (index, method) in self.program().methods.iter().enumerate() {
+            let selected = if index == self.method { "*" } else { " " };
+            println!("{}{:>3} {}", selected, index, method.name());
+        }
+    }
+
+    fn select_method(&mut self, name: &str) -> Result<(), String> {
+        let methods = &self.program().methods;
+        let index = name
+            .parse::<usize>()
+            .ok()
+            .filter(|index| *index < methods.len())
+            .or_else(|| methods.iter().position(|method| method.name() == name))
+            .ok_or_else(|| format!("No method `{}`", name))?;
+        self.method = index;
+        self.block = 0;
+        self.print_blocks();
+        Ok(())
+    }
+
+    fn print_blocks(&self) {
+        let Ok(method) = self.method() else {
+            return;
+        };
+        for (index, block) in method.basic_blocks.iter().enumerate() {
+            let selected = if index == self.block { "*" } else { " " };
+            let failing = if block.stmts.iter().any(|stmt| self.is_failing(stmt)) {
+                "!"
+            } else {
+                " "
+            };
+            println!(
+                "{}{}{:>3} {} -> {}",
+                selected,
+                failing,
+                index,
+                method.basic_blocks_labels[index],
+                self.successor_labels(method, &block.successor).join(", ")
+            );
+        }
+    }
+
+    fn successor_labels(&self, method: &vir::CfgMethod, successor: &vir::Successor) -> Vec<String> {
+        match successor {
+            vir::Successor::Undefined => vec!["undefined".to_string()],
+            vir::Successor::Return => vec!["return".to_string()],
+            _ => successor
+                .get_following()
+                .iter()
+                .map(|target| method.basic_blocks_labels[target.index()].clone())
+                .collect(),
+        }
+    }
+
+    fn select_block(&mut self, name: &str) -> Result<(), String> {
+        let method = self.method()?;
+        let index = name
+            .parse::<usize>()
+            .ok()
+            .filter(|index| *index < method.basic_blocks.len())
+            .or_else(|| {
+                method
+                    .basic_blocks_labels
+                    .iter()
+                    .position(|label| label == name)
+            })
+            .ok_or_else(|| format!("No block `{}` in {}", name, method.name()))?;
+        self.block = index;
+        Ok(())
+    }
+
+    fn print_block(&self) {
+        let Ok(method) = self.method() else {
+            return;
+        };
+        let Some(block) = method.basic_blocks.get(self.block) else {
+            return;
+        };
+        println!("label {} // {}", method.basic_blocks_labels[self.block], self.block);
+        for (index, stmt) in block.stmts.iter().enumerate() {
+            let failing = if self.is_failing(stmt) { "!" } else { " " };
+            println!("{}{:>4}: {}", failing, index, stmt);
+        }
+        match &block.successor {
+            vir::Successor::GotoSwitch(guarded_targets, default_target) => {
+                for (guard, target) in guarded_targets {
+                    let label = &method.basic_blocks_labels[target.index()];
+                    println!("       if {} goto {}", guard, label);
+                }
+                let label = &method.basic_blocks_labels[default_target.index()];
+                println!("       else goto {}", label);
+            }
+            successor => {
+                let labels = self.successor_labels(method, successor);
+                println!("       goto {}", labels.join(", "));
+            }
+        }
+    }
+
+    fn step(&mut self, successor: Option<usize>) -> Result<(), String> {
+        let method = self.method()?;
+        let targets = method.basic_blocks[self.block].successor.get_following();
+        let target = match (successor, targets.as_slice()) {
+            (_, []) => return Err("The block has no successors".to_string()),
+            (None, [target]) => *target,
+            (None, _) => {
+                let successor = &method.basic_blocks[self.block].successor;
+                let labels = self.successor_labels(method, successor);
+                return Err(format!(
+                    "The block has several successors, choose one with `step <n>`: {}",
+                    labels.join(", ")
+                ));
+            }
+            (Some(n), targets) => *targets
+                .get(n)
+                .ok_or_else(|| format!("The block has only {} successors", targets.len()))?,
+        };
+        self.block = target.index();
+        self.print_block();
+        Ok(())
+    }
+
+    fn print_state(&self) {
+        let Ok(method) = self.method() else {
+            return;
+        };
+        let label = &method.basic_blocks_labels[self.block];
+        let block_states = self
+            .states
+            .get(&method.name())
+            .and_then(|states| states.blocks.get(label));
+        let Some(block_states) = block_states else {
+            println!(
+                "No fold-unfold state of block {} was dumped (was the session dumped with \
+                `dump_debug_session` and the default encoding?)",
+                label
+            );
+            return;
+        };
+        print_fold_unfold_state("entry", &block_states.entry);
+        print_fold_unfold_state("exit", &block_states.exit);
+    }
+
+    fn print_errors(&self) {
+        match &self.session.result {
+            VerificationResult::Success => println!("The program verified."),
+            VerificationResult::Failure(errors) => {
+                println!("The verification failed with {} errors:", errors.len());
+                for error in errors {
+                    let location = error_position(error)
+                        .and_then(|pos_id| self.locate(&pos_id))
+                        .map(|(method, block, stmt)| {
+                            let method = &self.program().methods[method];
+                            format!(
+                                " (method {}, block {}, statement {})",
+                                method.name(),
+                                method.basic_blocks_labels[block],
+                                stmt
+                            )
+                        })
+                        .unwrap_or_default();
+                    println!("  [{}] {}{}", error.full_id, error.message, location);
+                }
+            }
+            VerificationResult::ConsistencyErrors(errors) => {
+                println!("The program has {} consistency errors:", errors.len());
+                for error in errors {
+                    println!("  {}", error.message);
+                }
+            }
+            VerificationResult::JavaException(exception) => {
+                println!("The verification raised an exception: {}", exception);
+            }
+            VerificationResult::Timeout => println!("The verification timed out."),
+        }
+    }
+
+    /// Finds the method, block, and statement with the given position id.
+    fn locate(&self, pos_id: &str) -> Option<(usize, usize, usize)> {
+        self.program()
+            .methods
+            .iter()
+            .enumerate()
+            .find_map(|(method_index, method)| {
+                method
+                    .basic_blocks
+                    .iter()
+                    .enumerate()
+                    .find_map(|(block_index, block)| {
+                        block
+                            .stmts
+                            .iter()
+                            .position(|stmt| {
+                                stmt.pos().map_or(false, |pos| pos.id().to_string() == pos_id)
+                            })
+                            .map(|stmt_index| (method_index, block_index, stmt_index))
+                    })
+            })
+    }
+
+    /// Verifies the selected method with only the given statement checked.
+    fn check(&mut self, stmt_index: usize) -> Result<(), String> {
+        let method = self.method()?;
+        let stmt = method.basic_blocks[self.block]
+            .stmts
+            .get(stmt_index)
+            .ok_or_else(|| format!("No statement {} in the block", stmt_index))?;
+        let pos = match stmt {
+            vir::Stmt::Assert(_, pos) | vir::Stmt::Exhale(_, pos) => pos,
+            _ => return Err("Only assert and exhale statements can be checked".to_string()),
+        };
+        let pos_id = pos.id().to_string();
+        let mut program = self.program().clone();
+        program.methods = vec![self.isolate_statement(method, stmt_index)];
+        let request = VerificationRequest {
+            program: Program::Legacy(program),
+            backend_config: self.session.request.backend_config.clone(),
+            webhook_url: None,
+            return_artifacts: false,
+            report_progress: false,
+            shared_domains: vec![],
+        };
+        let viper = self.viper.get_or_insert_with(|| {
+            println!("Starting the verifier...");
+            Viper::new_with_memory(
+                &config::viper_home(),
+                config::extra_jvm_args(),
+                jvm_memory(None),
+            )
+        });
+        let verification_context = viper.attach_current_thread();
+        let mut cache = PersistentCache::load_cache(PathBuf::new());
+        let result = process_verification_request(
+            &verification_context,
+            request,
+            &mut cache,
+            &mut ReusableVerifier::default(),
+        );
+        match result {
+            VerificationResult::Success => println!("The statement holds."),
+            VerificationResult::Failure(errors) => {
+                let (at_stmt, elsewhere): (Vec<_>, Vec<_>) = errors
+                    .iter()
+                    .partition(|error| error_position(error).as_ref() == Some(&pos_id));
+                if at_stmt.is_empty() {
+                    println!("The statement holds.");
+                }
+                for error in at_stmt {
+                    println!("The statement fails: [{}] {}", error.full_id, error.message);
+                }
+                for error in elsewhere {
+                    println!("Another statement fails: [{}] {}", error.full_id, error.message);
+                }
+            }
+            result => println!("The verification did not complete: {:?}", result),
+        }
+        Ok(())
+    }
+
+    /// Turns all assertions of the method but the given statement of the
+    /// selected block into assumptions.
+    fn isolate_statement(&self, method: &vir::CfgMethod, stmt_index: usize) -> vir::CfgMethod {
+        let mut method = method.clone();
+        for (block_index, block) in method.basic_blocks.iter_mut().enumerate() {
+            for (index, stmt) in block.stmts.iter_mut().enumerate() {
+                if (block_index, index) == (self.block, stmt_index) {
+                    continue;
+                }
+                if let vir::Stmt::Assert(expr, _) = stmt {
+                    *stmt = vir::Stmt::Inhale(expr.clone());
+                }
+            }
+        }
+        method
+    }
+}
+
+/// The position of the statement that failed.
+fn error_position(error: &VerificationError) -> Option<String> {
+    error
+        .offending_pos_id
+        .clone()
+        .or_else(|| error.pos_id.clone())
+}
+
+fn print_fold_unfold_state(name: &str, state: &Option<FoldUnfoldState>) {
+    match state {
+        Some(state) => {
+            println!("At the {} of the block:", name);
+            println!("  Acc:");
+            for acc in &state.acc {
+                println!("    {}", acc);
+            }
+            println!("  Pred:");
+            for pred in &state.pred {
+                println!("    {}", pred);
+            }
+        }
+        None => println!("The {} of the block was not reached.", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockStates, ViperBackendConfig};
+    use viper::VerificationBackend;
+
+    /// A method whose `start` block branches to `then` and `else`. The
+    /// assertion with the position id 2 failed.
+    fn debugger() -> Debugger {
+        let mut method = vir::CfgMethod::new("test".to_string(), vec![], vec![]);
+        let start = method.add_block(
+            "start",
+            vec![
+                vir::Stmt::Assert(true.into(), vir::Position::new(1, 1, 1)),
+                vir::Stmt::Assert(false.into(), vir::Position::new(2, 1, 2)),
+            ],
+        );
+        let then_block = method.add_block("then", vec![]);
+        let else_block = method.add_block(
+            "else",
+            vec![vir::Stmt::Exhale(true.into(), vir::Position::new(3, 1, 3))],
+        );
+        method.set_successor(
+            start,
+            vir::Successor::GotoSwitch(vec![(true.into(), then_block)], else_block),
+        );
+        method.set_successor(then_block, vir::Successor::Return);
+        method.set_successor(else_block, vir::Successor::Return);
+        let program = vir::Program {
+            name: "program".to_string(),
+            domains: vec![],
+            fields: vec![],
+            builtin_methods: vec![],
+            methods: vec![method],
+            functions: vec![],
+            viper_predicates: vec![],
+        };
+        let error = VerificationError::new(
+            "assert.failed:assertion.false".to_string(),
+            Some("2".to_string()),
+            Some("2".to_string()),
+            None,
+            "Assert might fail.".to_string(),
+            None,
+            vec![],
+        );
+        let session = DebugSession {
+            request: VerificationRequest {
+                program: Program::Legacy(program),
+                backend_config: ViperBackendConfig {
+                    backend: VerificationBackend::Silicon,
+                    verifier_args: vec![],
+                    qi_bound: None,
+                },
+                webhook_url: None,
+                return_artifacts: false,
+                report_progress: false,
+                shared_domains: vec![],
+            },
+            result: VerificationResult::Failure(vec![error]),
+        };
+        Debugger::new(session, BTreeMap::new()).unwrap()
+    }
+
+    #[test]
+    fn failing_statements() {
+        let debugger = debugger();
+        let stmts = &debugger.method().unwrap().basic_blocks[0].stmts;
+        assert!(!debugger.is_failing(&stmts[0]));
+        assert!(debugger.is_failing(&stmts[1]));
+        assert_eq!(debugger.locate("2"), Some((0, 0, 1)));
+        assert_eq!(debugger.locate("3"), Some((0, 2, 0)));
+        assert_eq!(debugger.locate("4"), None);
+    }
+
+    #[test]
+    fn select_methods_and_blocks() {
+        let mut debugger = debugger();
+        assert!(debugger.select_method("test").is_ok());
+        assert!(debugger.select_method("0").is_ok());
+        assert!(debugger.select_method("1").is_err());
+        assert!(debugger.select_block("else").is_ok());
+        assert_eq!(debugger.block, 2);
+        assert!(debugger.select_block("1").is_ok());
+        assert_eq!(debugger.block, 1);
+        assert!(debugger.select_block("missing").is_err());
+        assert_eq!(debugger.block, 1);
+    }
+
+    #[test]
+    fn step_to_successors() {
+        let mut debugger = debugger();
+        assert!(debugger.step(None).is_err());
+        assert!(debugger.step(Some(2)).is_err());
+        assert_eq!(debugger.block, 0);
+        assert!(debugger.step(Some(1)).is_ok());
+        assert_eq!(debugger.block, 2);
+        assert!(debugger.step(None).is_err());
+    }
+
+    #[test]
+    fn isolate_statement() {
+        let debugger = debugger();
+        let method = debugger.isolate_statement(debugger.method().unwrap(), 1);
+        let stmts = &method.basic_blocks[0].stmts;
+        assert!(matches!(stmts[0], vir::Stmt::Inhale(_)));
+        assert!(matches!(stmts[1], vir::Stmt::Assert(..)));
+        assert!(matches!(
+            method.basic_blocks[2].stmts[0],
+            vir::Stmt::Exhale(..)
+        ));
+    }
+
+    #[test]
+    fn load_session_and_states() {
+        let dir = std::env::temp_dir().join(format!("prusti-debug-session-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let session_file = dir.join("program.session.json");
+        let session = debugger().session;
+        serde_json::to_writer(std::fs::File::create(&session_file).unwrap(), &session).unwrap();
+        let states = MethodStates {
+            method: "test".to_string(),
+            blocks: [(
+                "start".to_string(),
+                BlockStates {
+                    entry: Some(FoldUnfoldState {
+                        acc: vec!["_1.val_int".to_string()],
+                        pred: vec![],
+                    }),
+                    exit: None,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        };
+        serde_json::to_writer(
+            std::fs::File::create(dir.join("file.test.states.json")).unwrap(),
+            &states,
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.states.json"), "{").unwrap();
+
+        let loaded = DebugSession::load(&session_file).unwrap();
+        assert_eq!(loaded.result, session.result);
+        let loaded_states = MethodStates::load_all(&session_file);
+        assert_eq!(loaded_states.len(), 1);
+        let start = &loaded_states["test"].blocks["start"];
+        assert_eq!(start.entry.as_ref().unwrap().acc, vec!["_1.val_int"]);
+        assert!(start.exit.is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
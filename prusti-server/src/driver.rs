@@ -4,8 +4,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use prusti_utils::config;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use viper::PersistentCache;
 
 /// A verification server to handle Prusti verification requests.
 #[derive(Parser, Debug)]
@@ -15,16 +21,113 @@ struct Args {
     /// Pass 0 to get a free one assigned by the OS.
     #[clap(short, long, value_name = "PORT", default_value_t = 0)]
     port: u16,
+    /// Listens on a Unix socket instead of a port, and shuts down after
+    /// `server_idle_timeout` seconds without verification requests.
+    #[clap(long)]
+    daemon: bool,
+    /// Sets the Unix socket on which to listen in daemon mode. Defaults to the
+    /// `server_socket_path` flag.
+    #[clap(long, value_name = "PATH", requires = "daemon")]
+    socket: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Interactively debugs a failing verification dumped with the
+    /// `dump_debug_session` flag, instead of starting the server.
+    Debug {
+        /// The `.session.json` file in the `debug_session` log directory.
+        #[clap(value_name = "SESSION")]
+        session_file: PathBuf,
+    },
+    /// Transfers the results of the verification cache at the `cache_path`
+    /// flag, e.g. to keep them between CI runs.
+    Cache {
+        #[clap(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Writes all results of the cache to a file.
+    Export {
+        #[clap(value_name = "FILE")]
+        file: PathBuf,
+    },
+    /// Adds the results of a file written by `cache export` to the cache.
+    Import {
+        #[clap(value_name = "FILE")]
+        file: PathBuf,
+    },
 }
 
 fn main() {
-    env_logger::init_from_env(
-        env_logger::Env::new()
-            .filter_or("PRUSTI_LOG", config::log())
-            .write_style_or("PRUSTI_LOG_STYLE", config::log_style()),
-    );
+    prusti_server::init_reloadable_logger();
 
     let args = Args::parse();
 
-    prusti_server::start_server_on_port(args.port);
+    match args.command {
+        Some(Command::Debug { session_file }) => {
+            if let Err(error) = prusti_server::run_debugger(&session_file) {
+                eprintln!("Could not debug {}: {}", session_file.display(), error);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Cache { command }) => {
+            if let Err(error) = transfer_cache(command) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        None if args.daemon => start_daemon(args.socket),
+        None => prusti_server::start_server_on_port(args.port),
+    }
+}
+
+fn transfer_cache(command: CacheCommand) -> Result<(), String> {
+    let cache_path = config::cache_path();
+    if cache_path.as_os_str().is_empty() {
+        return Err("The cache_path flag is not set".to_string());
+    }
+    let mut cache = PersistentCache::load_cache(cache_path);
+    match command {
+        CacheCommand::Export { file } => {
+            let mut writer = BufWriter::new(create_file(&file)?);
+            cache.export(&mut writer)?;
+            writer
+                .flush()
+                .map_err(|error| format!("Could not write {}: {}", file.display(), error))?;
+            println!("Exported the cache to {}", file.display());
+        }
+        CacheCommand::Import { file } => {
+            let reader = BufReader::new(open_file(&file)?);
+            let imported = cache.import(reader)?;
+            cache.save();
+            println!("Imported {} results from {}", imported, file.display());
+        }
+    }
+    Ok(())
+}
+
+fn create_file(file: &Path) -> Result<File, String> {
+    File::create(file).map_err(|error| format!("Could not create {}: {}", file.display(), error))
+}
+
+fn open_file(file: &Path) -> Result<File, String> {
+    File::open(file).map_err(|error| format!("Could not open {}: {}", file.display(), error))
+}
+
+#[cfg(unix)]
+fn start_daemon(socket: Option<PathBuf>) {
+    let socket_path = socket.unwrap_or_else(config::server_socket_path);
+    prusti_server::start_daemon_on_socket(&socket_path);
+}
+
+#[cfg(not(unix))]
+fn start_daemon(_socket: Option<PathBuf>) {
+    eprintln!("The daemon mode requires Unix sockets, which are not supported on this platform");
+    std::process::exit(1);
 }
@@ -5,14 +5,29 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 mod client;
+mod compression;
+mod debug_session;
+mod debugger;
 mod process_verification;
+mod reloadable_logger;
 mod server;
+mod server_message;
+mod session;
 mod verification_request;
+mod verification_request_processing;
+mod webhook;
 
 pub use client::*;
+pub use debug_session::*;
+pub use debugger::*;
 pub use process_verification::*;
+pub use reloadable_logger::*;
 pub use server::*;
+pub use server_message::*;
+pub use session::*;
 pub use verification_request::*;
+pub use verification_request_processing::*;
+pub use webhook::*;
 
 // Futures returned by `Client` need to be executed in a compatible tokio runtime.
 pub use tokio;
@@ -4,7 +4,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{VerificationRequest, ViperBackendConfig};
+use crate::{Artifact, MethodResult, ServerMessage, VerificationRequest, ViperBackendConfig};
 use log::info;
 use prusti_common::{
     config,
@@ -12,16 +12,119 @@ use prusti_common::{
     vir::{program_normalization::NormalizationInfo, ToViper},
     Stopwatch,
 };
-use std::{fs::create_dir_all, path::PathBuf};
+use std::{
+    cell::RefCell,
+    fs::create_dir_all,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
 use viper::{
-    smt_manager::SmtManager, Cache, VerificationBackend, VerificationContext, VerificationResult,
+    smt_manager::SmtManager, Cache, JavaException, VerificationBackend, VerificationContext,
+    VerificationResult,
 };
 
+/// Whether the result of a verification request was taken from the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheStatus {
+    /// The result was taken from the cache.
+    Hit,
+    /// The program was verified because the cache had no result for it, and
+    /// the result was stored in the cache.
+    Miss,
+    /// The program was verified because the cache had no result for it, but
+    /// the result was not stored in the cache because it might depend on the
+    /// machine (i.e. it is a Java exception or a timeout).
+    Uncacheable,
+    /// The cache was not used.
+    Disabled,
+}
+
+/// How often the cache was used for a set of verification requests.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CacheStatistics {
+    /// The number of requests whose result was taken from the cache.
+    pub hits: u64,
+    /// The number of requests that were verified because the cache had no
+    /// result for them.
+    pub misses: u64,
+    /// The number of results that were stored in the cache.
+    pub insertions: u64,
+}
+
+impl CacheStatistics {
+    pub fn record(&mut self, status: CacheStatus) {
+        match status {
+            CacheStatus::Hit => self.hits += 1,
+            CacheStatus::Miss => {
+                self.misses += 1;
+                self.insertions += 1;
+            }
+            CacheStatus::Uncacheable => self.misses += 1,
+            CacheStatus::Disabled => {}
+        }
+    }
+
+    /// The fraction of the requests that used the cache whose result was
+    /// taken from the cache, if any request used the cache.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+impl std::ops::AddAssign for CacheStatistics {
+    fn add_assign(&mut self, other: Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.insertions += other.insertions;
+    }
+}
+
+impl std::fmt::Display for CacheStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses, {} insertions",
+            self.hits, self.misses, self.insertions
+        )?;
+        if let Some(hit_rate) = self.hit_rate() {
+            write!(f, " (hit rate {:.0}%)", hit_rate * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
 pub fn process_verification_request<'v, 't: 'v>(
     verification_context: &'v VerificationContext<'t>,
-    mut request: VerificationRequest,
+    request: VerificationRequest,
     cache: impl Cache,
+    reusable_verifier: &mut ReusableVerifier<'v>,
 ) -> viper::VerificationResult {
+    process_verification_request_with_cache_status(
+        verification_context,
+        request,
+        cache,
+        reusable_verifier,
+        &mut |_| {},
+    )
+    .0
+}
+
+/// Like `process_verification_request`, but also tells whether the result was taken from the
+/// cache. The other messages about the verification, i.e. the artifacts requested with
+/// `return_artifacts` and, with `report_progress`, the methods verified so far, are passed to
+/// `send_message`. The verified methods are sent from another thread while the verifier is
+/// running.
+pub fn process_verification_request_with_cache_status<'v, 't: 'v>(
+    verification_context: &'v VerificationContext<'t>,
+    mut request: VerificationRequest,
+    cache: impl Cache,
+    reusable_verifier: &mut ReusableVerifier<'v>,
+    send_message: &mut (dyn FnMut(ServerMessage) + Send),
+) -> (viper::VerificationResult, CacheStatus) {
     let ast_utils = verification_context.new_ast_utils();
 
     // Only for testing: Check that the normalization is reversible.
@@ -44,6 +147,9 @@ pub fn process_verification_request<'v, 't: 'v>(
         request.program.get_name()
     );
 
+    let dump_program = config::dump_viper_program() || request.return_artifacts;
+    // The pretty-printed program, if it has to be returned to the client.
+    let returned_program = RefCell::new(None);
     let build_or_dump_viper_program = || {
         let mut stopwatch = Stopwatch::start("prusti-server", "construction of JVM objects");
         let ast_factory = verification_context.new_ast_factory();
@@ -51,13 +157,19 @@ pub fn process_verification_request<'v, 't: 'v>(
             .program
             .to_viper(prusti_common::vir::LoweringContext::default(), &ast_factory);
 
-        if config::dump_viper_program() {
+        if dump_program {
             stopwatch.start_next("dumping viper program");
-            dump_viper_program(
-                &ast_utils,
-                viper_program,
-                &request.program.get_name_with_check_mode(),
-            );
+            let program_text = ast_utils.pretty_print(viper_program);
+            let file_name = format!("{}.vpr", request.program.get_name_with_check_mode());
+            if config::dump_viper_program() {
+                dump_viper_program(&file_name, &program_text);
+            }
+            if request.return_artifacts {
+                *returned_program.borrow_mut() = Some(Artifact::ViperProgram {
+                    file_name,
+                    program: program_text,
+                });
+            }
         }
 
         viper_program
@@ -71,12 +183,13 @@ pub fn process_verification_request<'v, 't: 'v>(
         );
         println!("Hash of the request is: {}", hash);
         // Some tests need the dump to report a diff of the Viper programs.
-        if config::dump_viper_program() {
+        if dump_program {
             ast_utils.with_local_frame(16, || {
                 let _ = build_or_dump_viper_program();
             });
         }
-        return viper::VerificationResult::Success;
+        send_returned_program(&returned_program, send_message);
+        return (viper::VerificationResult::Success, CacheStatus::Disabled);
     }
 
     // Early return in case of cache hit
@@ -87,55 +200,282 @@ pub fn process_verification_request<'v, 't: 'v>(
                 &result,
                 request.program.get_name()
             );
-            if config::dump_viper_program() {
+            if dump_program {
                 ast_utils.with_local_frame(16, || {
                     let _ = build_or_dump_viper_program();
                 });
             }
+            send_returned_program(&returned_program, send_message);
             normalization_info.denormalize_result(&mut result);
-            return result;
+            return (result, CacheStatus::Hit);
         }
     };
 
-    ast_utils.with_local_frame(16, || {
-        let viper_program = build_or_dump_viper_program();
-        let program_name = request.program.get_name();
+    let program_name = request.program.get_name().to_string();
+    if request.return_artifacts && config::use_smt_wrapper() {
+        send_message(ServerMessage::Artifact(Artifact::SmtLogDirectory(
+            smt_log_dir(&program_name),
+        )));
+    }
+    let mut result = if reusable_verifier.is_enabled() {
+        let mut result = verify_with_reusable_verifier(
+            verification_context,
+            reusable_verifier,
+            &request.backend_config,
+            request.report_progress,
+            &program_name,
+            &build_or_dump_viper_program,
+            send_message,
+        );
+        if let Some(java_exception) = result.1 {
+            // The exception might be caused by the state left by the previous verification.
+            // Retry with a fresh verifier.
+            info!(
+                "The reused verifier failed with {:?}, retrying program {} with a fresh verifier",
+                java_exception, program_name
+            );
+            reusable_verifier.discard();
+            result = verify_with_reusable_verifier(
+                verification_context,
+                reusable_verifier,
+                &request.backend_config,
+                request.report_progress,
+                &program_name,
+                &build_or_dump_viper_program,
+                send_message,
+            );
+        }
+        result.0
+    } else {
+        ast_utils.with_local_frame(16, || {
+            let viper_program = build_or_dump_viper_program();
 
-        // Create a new verifier each time.
-        // Workaround for https://github.com/viperproject/prusti-dev/issues/744
-        let mut stopwatch = Stopwatch::start("prusti-server", "verifier startup");
-        let mut verifier =
-            new_viper_verifier(program_name, verification_context, request.backend_config);
+            // Create a new verifier each time.
+            // Workaround for https://github.com/viperproject/prusti-dev/issues/744
+            let mut stopwatch = Stopwatch::start("prusti-server", "verifier startup");
+            let mut verifier = new_viper_verifier(
+                &program_name,
+                verification_context,
+                request.backend_config.clone(),
+                request.report_progress,
+            );
 
-        stopwatch.start_next("verification");
-        let mut result = verifier.verify(viper_program);
+            stopwatch.start_next("verification");
+            verify_reporting_methods(&mut verifier, viper_program, send_message)
+        })
+    };
 
-        // Don't cache Java exceptions, which might be due to misconfigured paths.
-        if config::enable_cache() && !matches!(result, VerificationResult::JavaException(_)) {
-            info!(
-                "Storing new cached result {:?} for program {}",
-                &result,
-                request.program.get_name()
+    // Don't cache Java exceptions, which might be due to misconfigured paths, and timeouts,
+    // which depend on the load of the machine.
+    let cache_status = if !config::enable_cache() {
+        CacheStatus::Disabled
+    } else if matches!(
+        result,
+        VerificationResult::JavaException(_) | VerificationResult::Timeout
+    ) {
+        CacheStatus::Uncacheable
+    } else {
+        info!(
+            "Storing new cached result {:?} for program {}",
+            &result,
+            request.program.get_name()
+        );
+        cache.insert(hash, result.clone());
+        CacheStatus::Miss
+    };
+
+    send_returned_program(&returned_program, send_message);
+    normalization_info.denormalize_result(&mut result);
+    (result, cache_status)
+}
+
+fn send_returned_program(
+    returned_program: &RefCell<Option<Artifact>>,
+    send_message: &mut (dyn FnMut(ServerMessage) + Send),
+) {
+    if let Some(artifact) = returned_program.borrow_mut().take() {
+        send_message(ServerMessage::Artifact(artifact));
+    }
+}
+
+/// Verifies the program with the verifier kept in `reusable_verifier`. Returns the result and,
+/// if the verifier was reused and failed with a Java exception, that exception.
+fn verify_with_reusable_verifier<'v, 't: 'v>(
+    verification_context: &'v VerificationContext<'t>,
+    reusable_verifier: &mut ReusableVerifier<'v>,
+    backend_config: &ViperBackendConfig,
+    report_progress: bool,
+    program_name: &str,
+    build_viper_program: &dyn Fn() -> viper::Program<'v>,
+    send_message: &mut (dyn FnMut(ServerMessage) + Send),
+) -> (VerificationResult, Option<JavaException>) {
+    let mut stopwatch = Stopwatch::start("prusti-server", "verifier startup");
+    // The verifier has to be created outside of the local frame of the program, because the
+    // frame releases all Java objects created in it.
+    let (verifier, reused) = reusable_verifier.get(
+        verification_context,
+        backend_config,
+        report_progress,
+        program_name,
+    );
+    stopwatch.start_next("verification");
+    let result = verification_context.new_ast_utils().with_local_frame(16, || {
+        verify_reporting_methods(verifier, build_viper_program(), send_message)
+    });
+    match result {
+        VerificationResult::JavaException(java_exception) if reused => {
+            (VerificationResult::JavaException(java_exception.clone()), Some(java_exception))
+        }
+        result => (result, None),
+    }
+}
+
+/// A verifier that is kept alive between the verification requests processed with the same
+/// verification context, which avoids the startup time of a new verifier for each program. It is
+/// used only if the `reuse_verifier` flag is set, and not when the verifier needs per-program
+/// configuration (i.e. with `dump_debug_info` or `use_smt_wrapper`).
+#[derive(Default)]
+pub struct ReusableVerifier<'v> {
+    /// The configuration of the verifier, whether it reports the verified methods, and the
+    /// verifier.
+    verifier: Option<(ViperBackendConfig, bool, viper::Verifier<'v>)>,
+    /// How many verifiers were started.
+    started: usize,
+    /// How many times the kept verifier was reset and reused.
+    reused: usize,
+}
+
+impl<'v> ReusableVerifier<'v> {
+    pub fn started_verifiers(&self) -> usize {
+        self.started
+    }
+
+    pub fn reused_verifiers(&self) -> usize {
+        self.reused
+    }
+
+    fn is_enabled(&self) -> bool {
+        config::reuse_verifier() && !config::dump_debug_info() && !config::use_smt_wrapper()
+    }
+
+    /// Returns the kept verifier after resetting it, if it has the given configuration and
+    /// reports the verified methods only if `report_progress` is set, and a new verifier
+    /// otherwise. The returned flag tells whether the verifier was reused.
+    fn get<'t: 'v>(
+        &mut self,
+        verification_context: &'v VerificationContext<'t>,
+        backend_config: &ViperBackendConfig,
+        report_progress: bool,
+        program_name: &str,
+    ) -> (&mut viper::Verifier<'v>, bool) {
+        let reused = match &mut self.verifier {
+            Some((verifier_config, reports_progress, verifier))
+                if verifier_config == backend_config && *reports_progress == report_progress =>
+            {
+                match verifier.reset() {
+                    Ok(()) => true,
+                    Err(java_exception) => {
+                        info!("Failed to reset the verifier: {:?}", java_exception);
+                        false
+                    }
+                }
+            }
+            _ => false,
+        };
+        if reused {
+            self.reused += 1;
+        } else {
+            // Stop the old verifier before starting the new one.
+            self.discard();
+            let verifier = new_viper_verifier(
+                program_name,
+                verification_context,
+                backend_config.clone(),
+                report_progress,
             );
-            cache.insert(hash, result.clone());
+            self.verifier = Some((backend_config.clone(), report_progress, verifier));
+            self.started += 1;
         }
+        let (_, _, verifier) = self.verifier.as_mut().unwrap();
+        (verifier, reused)
+    }
 
-        normalization_info.denormalize_result(&mut result);
-        result
+    fn discard(&mut self) {
+        self.verifier = None;
+    }
+}
+
+/// How often the methods reported by a running verifier are taken from its reporter.
+const PROGRESS_POLLING_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Verifies the program, while another thread sends a `ServerMessage::MethodVerified` to
+/// `send_message` for each method that the reporter of the verifier receives an
+/// `EntitySuccessMessage` or `EntityFailureMessage` about. The reporter keeps the messages in
+/// memory until they are taken. Only Silicon reports the verified methods.
+fn verify_reporting_methods<'v>(
+    verifier: &mut viper::Verifier<'v>,
+    program: viper::Program<'v>,
+    send_message: &mut (dyn FnMut(ServerMessage) + Send),
+) -> VerificationResult {
+    let Some(progress) = verifier.progress() else {
+        return verifier.verify(program);
+    };
+    // A reused verifier might have reported methods of the previous program after they were
+    // last taken.
+    progress.take_verified_members();
+    let finished = AtomicBool::new(false);
+    thread::scope(|scope| {
+        scope.spawn(|| loop {
+            // Take the methods once more after the verification finished, to catch the last
+            // ones.
+            let last_take = finished.load(Ordering::Acquire);
+            for member in progress.take_verified_members() {
+                send_message(ServerMessage::MethodVerified {
+                    name: member.name,
+                    result: if member.success {
+                        MethodResult::Success
+                    } else {
+                        MethodResult::Failure
+                    },
+                    time_ms: member.time_ms,
+                });
+            }
+            if last_take {
+                break;
+            }
+            thread::sleep(PROGRESS_POLLING_INTERVAL);
+        });
+        // Stop taking the methods also if the verifier panics, otherwise the scope never ends.
+        let _finished = SetOnDrop(&finished);
+        verifier.verify(program)
     })
 }
 
-fn dump_viper_program(ast_utils: &viper::AstUtils, program: viper::Program, program_name: &str) {
+/// Sets the flag when dropped.
+struct SetOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for SetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+fn dump_viper_program(file_name: &str, program: &str) {
     let namespace = "viper_program";
-    let filename = format!("{}.vpr", program_name);
-    info!("Dumping Viper program to '{}/{}'", namespace, filename);
-    report(namespace, filename, ast_utils.pretty_print(program));
+    info!("Dumping Viper program to '{}/{}'", namespace, file_name);
+    report(namespace, file_name, program);
+}
+
+/// The directory in which the SMT wrapper writes the logs of the verification of the program.
+fn smt_log_dir(program_name: &str) -> PathBuf {
+    config::log_dir().join("smt").join(to_legal_file_name(program_name))
 }
 
 fn new_viper_verifier<'v, 't: 'v>(
     program_name: &str,
     verification_context: &'v viper::VerificationContext<'t>,
     backend_config: ViperBackendConfig,
+    report_progress: bool,
 ) -> viper::Verifier<'v> {
     let mut verifier_args: Vec<String> = backend_config.verifier_args;
     let report_path: Option<PathBuf>;
@@ -169,9 +509,7 @@ fn new_viper_verifier<'v, 't: 'v>(
     }
     let (smt_solver, smt_manager) = if config::use_smt_wrapper() {
         std::env::set_var("PRUSTI_ORIGINAL_SMT_SOLVER_PATH", config::smt_solver_path());
-        let log_path = config::log_dir()
-            .join("smt")
-            .join(to_legal_file_name(program_name));
+        let log_path = smt_log_dir(program_name);
         create_dir_all(&log_path).unwrap();
         let smt_manager = SmtManager::new(
             log_path,
@@ -196,17 +534,21 @@ fn new_viper_verifier<'v, 't: 'v>(
         (config::smt_solver_path(), SmtManager::default())
     };
     let boogie_path = config::boogie_path();
-    if let Some(bound) = config::smt_qi_bound_global() {
-        // We need to set the environment variable to reach our Z3 wrapper.
-        std::env::set_var("PRUSTI_SMT_QI_BOUND_GLOBAL", bound.to_string());
+    // We need to set the environment variable to reach our Z3 wrapper. The bound of a previous
+    // program must not apply to this one.
+    match backend_config.qi_bound.or_else(config::smt_qi_bound_global) {
+        Some(bound) => std::env::set_var("PRUSTI_SMT_QI_BOUND_GLOBAL", bound.to_string()),
+        None => std::env::remove_var("PRUSTI_SMT_QI_BOUND_GLOBAL"),
     }
 
     verification_context.new_verifier(
         backend_config.backend,
         verifier_args,
         report_path,
+        report_progress,
         smt_solver,
         boogie_path,
         smt_manager,
     )
 }
+
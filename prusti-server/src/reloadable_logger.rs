@@ -0,0 +1,58 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use lazy_static::lazy_static;
+use log::{Log, Metadata, Record};
+use prusti_common::config;
+use std::sync::RwLock;
+
+lazy_static! {
+    static ref LOGGER: ReloadableLogger = ReloadableLogger {
+        inner: RwLock::new(build_logger()),
+    };
+}
+
+/// A logger whose filter, given by the `PRUSTI_LOG` environment variable or
+/// the `log` flag, can be changed while the server is running.
+struct ReloadableLogger {
+    inner: RwLock<env_logger::Logger>,
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.read().unwrap().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.read().unwrap().log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.read().unwrap().flush()
+    }
+}
+
+fn build_logger() -> env_logger::Logger {
+    env_logger::Builder::from_env(
+        env_logger::Env::new()
+            .filter_or("PRUSTI_LOG", config::log())
+            .write_style_or("PRUSTI_LOG_STYLE", config::log_style()),
+    )
+    .build()
+}
+
+/// Installs the logger of the server.
+pub fn init_reloadable_logger() {
+    log::set_logger(&*LOGGER).expect("a logger is already installed");
+    log::set_max_level(LOGGER.inner.read().unwrap().filter());
+}
+
+/// Applies the current value of the `log` flag to the logger of the server.
+pub fn reload_logger() {
+    let logger = build_logger();
+    log::set_max_level(logger.filter());
+    *LOGGER.inner.write().unwrap() = logger;
+}
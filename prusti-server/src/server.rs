@@ -4,24 +4,64 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{process_verification_request, VerificationRequest};
+use crate::{
+    compression::{accepts_gzip, compress, decode, ChunkCompressor, GZIP},
+    reload_logger, MessageEncoding, ServerMessage, SessionDefinitions, TooManyRequests,
+    UnknownDomains, VerificationRequest, VerificationRequestProcessing, CLIENT_TOKEN_HEADER,
+};
 use log::info;
-use prusti_common::{config, Stopwatch};
+use prusti_common::config;
+#[cfg(unix)]
+use std::{path::Path, time::Duration};
 use std::{
+    collections::HashMap,
     net::{Ipv4Addr, SocketAddr},
     sync::{mpsc, Arc, Mutex},
     thread,
+    time::Instant,
 };
 use tokio::runtime::Builder;
-use viper::{PersistentCache, Viper};
-use warp::Filter;
+use viper::{JvmMemory, PersistentCache};
+use warp::{http::StatusCode, Filter};
+
+/// The memory limits of the JVM for verifying programs of `program_size` VIR
+/// nodes, overridden by the `jvm_max_heap_size` and `jvm_stack_size` flags.
+pub fn jvm_memory(program_size: Option<usize>) -> JvmMemory {
+    let mut memory = JvmMemory::for_program_size(program_size);
+    if let Some(max_heap_size) = config::jvm_max_heap_size() {
+        memory.max_heap_size = max_heap_size;
+    }
+    if let Some(stack_size) = config::jvm_stack_size() {
+        memory.stack_size = stack_size;
+    }
+    memory
+}
+
+/// The messages about a verification request, which end when the request is verified.
+type Messages = Box<dyn Iterator<Item = ServerMessage> + Send>;
 
 #[derive(Debug)]
 struct BincodeReject(bincode::Error);
 impl warp::reject::Reject for BincodeReject {}
 
+#[derive(Debug)]
+struct JsonReject(serde_json::Error);
+impl warp::reject::Reject for JsonReject {}
+
+#[derive(Debug)]
+struct EncodingReject(std::io::Error);
+impl warp::reject::Reject for EncodingReject {}
+
+#[derive(Debug)]
+struct TooManyRequestsReject(TooManyRequests);
+impl warp::reject::Reject for TooManyRequestsReject {}
+
+#[derive(Debug)]
+struct UnknownDomainsReject(UnknownDomains);
+impl warp::reject::Reject for UnknownDomainsReject {}
+
 pub fn start_server_on_port(port: u16) {
-    listen_on_port_with_address_callback(port, move |address| {
+    listen_on_port_with_address_callback(port, true, move |address| {
         if port == 0 {
             return;
         }
@@ -39,6 +79,7 @@ pub fn spawn_server_thread() -> SocketAddr {
     thread::spawn(move || {
         listen_on_port_with_address_callback(
             0, // ask system for port
+            false, // the process is not a server
             move |address| sender.send(address).unwrap(),
         );
     });
@@ -46,81 +87,396 @@ pub fn spawn_server_thread() -> SocketAddr {
     receiver.recv().unwrap()
 }
 
-fn listen_on_port_with_address_callback<F>(port: u16, address_callback: F) -> !
+fn listen_on_port_with_address_callback<F>(
+    port: u16,
+    reload_on_hangup: bool,
+    address_callback: F,
+) -> !
 where
     F: FnOnce(SocketAddr),
 {
-    let stopwatch = Stopwatch::start("prusti-server", "JVM startup");
-    let viper = Arc::new(Viper::new_with_args(
-        &config::viper_home(),
-        config::extra_jvm_args(),
-    ));
-    stopwatch.finish();
+    let cache_data = PersistentCache::load_cache(config::cache_path());
+    let cache = Arc::new(Mutex::new(cache_data));
+    let last_request = Arc::new(Mutex::new(Instant::now()));
+    let endpoints = build_endpoints(cache, last_request);
+
+    let runtime = build_runtime();
+    runtime.block_on(async {
+        #[cfg(unix)]
+        if reload_on_hangup {
+            tokio::spawn(reload_settings_on_hangup());
+        }
+        #[cfg(not(unix))]
+        let _ = reload_on_hangup;
+
+        info!("Prusti Server binding to port {}", port);
+        let (address, server_loop) =
+            warp::serve(endpoints).bind_ephemeral((Ipv4Addr::LOCALHOST, port));
+
+        println!("port: {}", address.port());
+        address_callback(address);
 
+        info!("Prusti Server listening on port {}", address.port());
+        server_loop.await
+    });
+
+    unreachable!("The server unexpectedly stopped.");
+}
+
+/// Runs the server on the Unix socket at `socket_path`, or on the socket passed by systemd's
+/// socket activation, until no request arrives for `server_idle_timeout` seconds.
+#[cfg(unix)]
+pub fn start_daemon_on_socket(socket_path: &Path) {
     let cache_data = PersistentCache::load_cache(config::cache_path());
     let cache = Arc::new(Mutex::new(cache_data));
-    let build_verification_request_handler = |viper_arc: Arc<Viper>, cache| {
-        move |request: VerificationRequest| {
-            let stopwatch = Stopwatch::start("prusti-server", "attach thread to JVM");
-            let viper_thread = viper_arc.attach_current_thread();
-            stopwatch.finish();
-            process_verification_request(&viper_thread, request, &cache)
+    let last_request = Arc::new(Mutex::new(Instant::now()));
+    let endpoints = build_endpoints(cache.clone(), last_request.clone());
+    let idle_timeout = Duration::from_secs(config::server_idle_timeout());
+
+    let runtime = build_runtime();
+    let activated_by_systemd = runtime.block_on(async {
+        tokio::spawn(reload_settings_on_hangup());
+        let (listener, activated_by_systemd) = match systemd_socket() {
+            Some(listener) => (listener, true),
+            None => {
+                info!("Prusti Server binding to socket {}", socket_path.display());
+                let listener = std::os::unix::net::UnixListener::bind(socket_path)
+                    .unwrap_or_else(|error| {
+                        panic!(
+                            "Server could not bind to socket {}: {}",
+                            socket_path.display(),
+                            error
+                        )
+                    });
+                (listener, false)
+            }
+        };
+        listener
+            .set_nonblocking(true)
+            .expect("could not make the socket non-blocking");
+        let listener = tokio::net::UnixListener::from_std(listener)
+            .expect("could not register the socket with the Tokio runtime");
+        info!("Prusti Server listening on socket {}", socket_path.display());
+        warp::serve(endpoints)
+            .serve_incoming_with_graceful_shutdown(
+                tokio_stream::wrappers::UnixListenerStream::new(listener),
+                wait_until_idle(last_request, idle_timeout),
+            )
+            .await;
+        activated_by_systemd
+    });
+
+    info!("Prusti Server shutting down after {:?} without requests", idle_timeout);
+    cache.lock().unwrap().save();
+    // The socket passed by systemd is owned by systemd.
+    if !activated_by_systemd {
+        let _ = std::fs::remove_file(socket_path);
+    }
+}
+
+/// The listening socket passed by systemd's socket activation, if any.
+#[cfg(unix)]
+fn systemd_socket() -> Option<std::os::unix::net::UnixListener> {
+    use std::os::unix::io::FromRawFd;
+    // See `sd_listen_fds(3)`: the passed sockets start at file descriptor 3.
+    const SD_LISTEN_FDS_START: i32 = 3;
+    let listen_pid: u32 = std::env::var("LISTEN_PID").ok()?.parse().ok()?;
+    let listen_fds: u32 = std::env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    if listen_pid != std::process::id() || listen_fds < 1 {
+        return None;
+    }
+    // Safety: systemd passes the ownership of the socket to this process.
+    Some(unsafe { std::os::unix::net::UnixListener::from_raw_fd(SD_LISTEN_FDS_START) })
+}
+
+/// Reloads the flags that can be changed at runtime from the `Prusti.toml` file whenever the
+/// process receives `SIGHUP`.
+#[cfg(unix)]
+async fn reload_settings_on_hangup() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangups = signal(SignalKind::hangup()).expect("could not register a SIGHUP handler");
+    while hangups.recv().await.is_some() {
+        match config::reload_runtime_settings() {
+            Ok(()) => {
+                reload_logger();
+                info!("Reloaded the configuration");
+            }
+            Err(error) => log::warn!("Could not reload the configuration: {}", error),
+        }
+    }
+}
+
+/// Completes once no request arrived for `idle_timeout`.
+#[cfg(unix)]
+async fn wait_until_idle(last_request: Arc<Mutex<Instant>>, idle_timeout: Duration) {
+    loop {
+        let idle_time = last_request.lock().unwrap().elapsed();
+        if idle_time >= idle_timeout {
+            return;
+        }
+        tokio::time::sleep(idle_timeout - idle_time).await;
+    }
+}
+
+fn build_endpoints(
+    cache: Arc<Mutex<PersistentCache>>,
+    last_request: Arc<Mutex<Instant>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let processing = Arc::new(VerificationRequestProcessing::with_cache(cache.clone()));
+    let definitions = Arc::new(Mutex::new(SessionDefinitions::default()));
+    let verify = {
+        let definitions = definitions.clone();
+        let last_request = last_request.clone();
+        move |client: String, mut request: VerificationRequest| {
+            let processing = processing.clone();
+            let last_request = last_request.clone();
+            let definitions = definitions.clone();
+            async move {
+                *last_request.lock().unwrap() = Instant::now();
+                definitions
+                    .lock()
+                    .unwrap()
+                    .complete_request(&mut request)
+                    .map_err(|error| {
+                        info!("Rejected verification request: {}", error);
+                        warp::reject::custom(UnknownDomainsReject(error))
+                    })?;
+                let messages = processing
+                    .process_client_request(client, request)
+                    .map_err(|error| {
+                        info!("Rejected verification request: {}", error);
+                        warp::reject::custom(TooManyRequestsReject(error))
+                    })?;
+                Ok::<_, warp::Rejection>(Box::new(messages) as Messages)
+            }
+        }
+    };
+    let respond_with = move |encoding: MessageEncoding| {
+        let last_request = last_request.clone();
+        move |messages: Messages, accept_encoding: Option<String>| {
+            streamed_response(
+                messages,
+                encoding,
+                accept_encoding.as_deref(),
+                last_request.clone(),
+            )
         }
     };
 
+    // Clients are identified by their token or, without one, by their IP address.
+    let client = warp::header::optional::<String>(CLIENT_TOKEN_HEADER)
+        .and(warp::addr::remote())
+        .map(|token: Option<String>, address: Option<SocketAddr>| {
+            token
+                .or_else(|| address.map(|address| address.ip().to_string()))
+                .unwrap_or_else(|| "local".to_string())
+        });
+
+    // The bodies of requests and responses are compressed if the client asks for it.
+    let request_body = warp::header::optional::<String>("content-encoding")
+        .and(warp::body::bytes())
+        .and_then(
+            |encoding: Option<String>, body: warp::hyper::body::Bytes| async move {
+                decode(encoding.as_deref(), &body).map_err(|err| {
+                    info!("request body encoding error: {}", err);
+                    warp::reject::custom(EncodingReject(err))
+                })
+            },
+        );
+    let accept_encoding = warp::header::optional::<String>("accept-encoding");
+
     let json_verify = warp::path!("json" / "verify")
-        .and(warp::body::json())
-        .map(build_verification_request_handler(
-            viper.clone(),
-            cache.clone(),
-        ))
-        .map(|response| warp::reply::json(&response));
+        .and(client.clone())
+        .and(request_body.clone().and_then(|body: Vec<u8>| async move {
+            serde_json::from_slice(&body).map_err(|err| {
+                info!("request json body error: {}", err);
+                warp::reject::custom(JsonReject(err))
+            })
+        }))
+        .and_then(verify.clone())
+        .and(accept_encoding.clone())
+        .map(respond_with(MessageEncoding::Json));
 
     let bincode_verify = warp::path!("bincode" / "verify")
-        .and(warp::body::bytes())
-        .and_then(|buf: warp::hyper::body::Bytes| async move {
-            bincode::deserialize(&buf).map_err(|err| {
+        .and(client)
+        .and(request_body.clone().and_then(|body: Vec<u8>| async move {
+            bincode::deserialize(&body).map_err(|err| {
                 info!("request bincode body error: {}", err);
                 warp::reject::custom(BincodeReject(err))
             })
-        })
-        .map(build_verification_request_handler(viper, cache.clone()))
-        .map(|result| {
-            warp::http::Response::new(
-                bincode::serialize(&result).expect("could not encode verification result"),
-            )
+        }))
+        .and_then(verify)
+        .and(accept_encoding.clone())
+        .map(respond_with(MessageEncoding::Bincode));
+
+    let save_cache = {
+        let cache = cache.clone();
+        warp::post()
+            .and(warp::path("save"))
+            .and(warp::path::end())
+            .map(move || {
+                cache.lock().unwrap().save();
+                warp::reply::html("Saved")
+            })
+    };
+
+    // The cache is exported and imported in the format of `prusti-server cache export`.
+    let export_cache = {
+        let cache = cache.clone();
+        warp::get()
+            .and(warp::path("cache"))
+            .and(warp::path::end())
+            .and(accept_encoding)
+            .map(move |accept_encoding: Option<String>| {
+                let mut exported = Vec::new();
+                match cache.lock().unwrap().export(&mut exported) {
+                    Ok(()) => encoded_response(
+                        exported,
+                        "application/octet-stream",
+                        accept_encoding.as_deref(),
+                    ),
+                    Err(error) => {
+                        let mut response = warp::http::Response::new(error.into_bytes());
+                        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                        response
+                    }
+                }
+            })
+    };
+
+    let import_cache = warp::post()
+        .and(warp::path("cache"))
+        .and(warp::path::end())
+        .and(request_body)
+        .map(move |body: Vec<u8>| {
+            let mut cache = cache.lock().unwrap();
+            match cache.import(body.as_slice()) {
+                Ok(imported) => {
+                    cache.save();
+                    warp::reply::with_status(format!("Imported {}", imported), StatusCode::OK)
+                }
+                Err(error) => warp::reply::with_status(error, StatusCode::BAD_REQUEST),
+            }
         });
 
-    let save_cache = warp::post()
-        .and(warp::path("save"))
+    // The domains that the requests of the current session can omit.
+    let session_domains = warp::get()
+        .and(warp::path!("session" / "domains"))
+        .map(move || warp::reply::json(&definitions.lock().unwrap().domain_hashes()));
+
+    let update_config = warp::post()
+        .and(warp::path("config"))
         .and(warp::path::end())
-        .map(move || {
-            cache.lock().unwrap().save();
-            warp::reply::html("Saved")
+        .and(warp::body::json())
+        .map(|values: HashMap<String, String>| {
+            match config::update_runtime_settings(&values) {
+                Ok(()) => {
+                    reload_logger();
+                    info!("Updated the configuration: {:?}", values);
+                    warp::reply::with_status("Updated".to_string(), StatusCode::OK)
+                }
+                Err(error) => warp::reply::with_status(error, StatusCode::BAD_REQUEST),
+            }
         });
 
-    let endpoints = json_verify.or(bincode_verify).or(save_cache);
+    json_verify
+        .or(bincode_verify)
+        .or(save_cache)
+        .or(export_cache)
+        .or(import_cache)
+        .or(session_domains)
+        .or(update_config)
+        .recover(reply_rejection)
+}
+
+/// A response with the given body, compressed if the `Accept-Encoding` header of the request
+/// allows it.
+fn encoded_response(
+    body: Vec<u8>,
+    content_type: &str,
+    accept_encoding: Option<&str>,
+) -> warp::http::Response<Vec<u8>> {
+    let response = warp::http::Response::builder().header("content-type", content_type);
+    let response = if accepts_gzip(accept_encoding) {
+        response
+            .header("content-encoding", GZIP)
+            .body(compress(&body))
+    } else {
+        response.body(body)
+    };
+    response.expect("could not build the response")
+}
+
+/// A response whose body streams the messages about a verification request, each as soon as
+/// the verification sends it, compressed if the `Accept-Encoding` header of the request allows
+/// it.
+fn streamed_response(
+    messages: Messages,
+    encoding: MessageEncoding,
+    accept_encoding: Option<&str>,
+    last_request: Arc<Mutex<Instant>>,
+) -> warp::http::Response<warp::hyper::Body> {
+    let content_type = match encoding {
+        MessageEncoding::Json => "application/x-ndjson",
+        MessageEncoding::Bincode => "application/octet-stream",
+    };
+    let mut response = warp::http::Response::builder().header("content-type", content_type);
+    let mut compressor = if accepts_gzip(accept_encoding) {
+        response = response.header("content-encoding", GZIP);
+        Some(ChunkCompressor::new())
+    } else {
+        None
+    };
+    let (mut body_sender, body) = warp::hyper::Body::channel();
+    let (message_sender, mut message_receiver) = tokio::sync::mpsc::unbounded_channel();
+    // Wait for the messages without blocking the server, which meanwhile queues the requests of
+    // other clients.
+    tokio::task::spawn_blocking(move || {
+        for message in messages {
+            if message_sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+    tokio::spawn(async move {
+        while let Some(message) = message_receiver.recv().await {
+            let mut chunk = encoding.encode(&message);
+            if let Some(compressor) = &mut compressor {
+                chunk = compressor.compress(&chunk);
+            }
+            if body_sender.send_data(chunk.into()).await.is_err() {
+                info!("The client disconnected before its request was verified");
+                return;
+            }
+        }
+        if let Some(compressor) = compressor {
+            let _ = body_sender.send_data(compressor.finish().into()).await;
+        }
+        *last_request.lock().unwrap() = Instant::now();
+    });
+    response.body(body).expect("could not build the response")
+}
+
+/// Replies to the rejections of verification requests that the client can handle.
+async fn reply_rejection(
+    rejection: warp::Rejection,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Some(TooManyRequestsReject(error)) = rejection.find() {
+        Ok(warp::reply::with_status(error.to_string(), StatusCode::TOO_MANY_REQUESTS))
+    } else if let Some(UnknownDomainsReject(error)) = rejection.find() {
+        Ok(warp::reply::with_status(error.to_string(), StatusCode::CONFLICT))
+    } else {
+        Err(rejection)
+    }
+}
 
+fn build_runtime() -> tokio::runtime::Runtime {
     // Here we use a single thread because
     // 1. Viper is not thread safe yet (Silicon issue #578), and
     // 2. By default Silicon already uses as many cores as possible.
-    let runtime = Builder::new_current_thread()
+    Builder::new_current_thread()
         .thread_name("prusti-server")
         .enable_all()
         .build()
-        .expect("failed to construct Tokio runtime");
-
-    runtime.block_on(async {
-        info!("Prusti Server binding to port {}", port);
-        let (address, server_loop) =
-            warp::serve(endpoints).bind_ephemeral((Ipv4Addr::LOCALHOST, port));
-
-        println!("port: {}", address.port());
-        address_callback(address);
-
-        info!("Prusti Server listening on port {}", address.port());
-        server_loop.await
-    });
-
-    unreachable!("The server unexpectedly stopped.");
+        .expect("failed to construct Tokio runtime")
 }
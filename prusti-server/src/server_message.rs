@@ -0,0 +1,171 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::CacheStatistics;
+use std::path::PathBuf;
+use viper::VerificationResult;
+
+/// A message about the verification of a request. The messages of a request
+/// end with a `Termination` message.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ServerMessage {
+    /// A file produced by the verification, sent if the request asked for
+    /// its artifacts with `return_artifacts`.
+    Artifact(Artifact),
+    /// How the cache was used for the request. Sent right before the
+    /// `Termination` message.
+    CacheStatistics(CacheStatistics),
+    /// A method of the Viper program was verified, which took `time_ms`
+    /// milliseconds. Sent while the program is being verified, as soon as the
+    /// verifier reports the method. Only Silicon reports the verified methods,
+    /// together with the functions and predicates that it checks, and nothing
+    /// is reported for a result taken from the cache.
+    MethodVerified {
+        name: String,
+        result: MethodResult,
+        time_ms: u64,
+    },
+    /// The verification of the request finished with the given result.
+    Termination(VerificationResult),
+}
+
+/// Whether a method of a Viper program was verified successfully.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MethodResult {
+    Success,
+    Failure,
+}
+
+/// A file produced by the verification of a request, which a remote client
+/// cannot read from the log directory of the server.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Artifact {
+    /// The pretty-printed Viper program of the request, which
+    /// `dump_viper_program` writes to `file_name` in the `viper_program` log
+    /// directory.
+    ViperProgram { file_name: String, program: String },
+    /// The directory of the server in which the SMT wrapper writes its logs
+    /// of the verification of the request.
+    SmtLogDirectory(PathBuf),
+}
+
+/// How the messages about a request are encoded in the body of the response,
+/// which the server streams while the request is verified. A JSON message is
+/// followed by a newline, and a bincode message is preceded by its length as
+/// a little-endian `u32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageEncoding {
+    Json,
+    Bincode,
+}
+
+impl MessageEncoding {
+    pub fn encode(self, message: &ServerMessage) -> Vec<u8> {
+        match self {
+            MessageEncoding::Json => {
+                let mut frame =
+                    serde_json::to_vec(message).expect("could not encode verification message");
+                frame.push(b'\n');
+                frame
+            }
+            MessageEncoding::Bincode => {
+                let message =
+                    bincode::serialize(message).expect("could not encode verification message");
+                let mut frame = (message.len() as u32).to_le_bytes().to_vec();
+                frame.extend(message);
+                frame
+            }
+        }
+    }
+}
+
+/// Decodes the messages of a response from the chunks of its body, which do
+/// not need to end at the end of a message.
+pub struct MessageDecoder {
+    encoding: MessageEncoding,
+    /// The received part of the next message.
+    buffer: Vec<u8>,
+}
+
+impl MessageDecoder {
+    pub fn new(encoding: MessageEncoding) -> Self {
+        Self {
+            encoding,
+            buffer: vec![],
+        }
+    }
+
+    /// Returns the messages that are completed by the chunk.
+    pub fn decode(&mut self, chunk: &[u8]) -> Vec<ServerMessage> {
+        self.buffer.extend_from_slice(chunk);
+        let mut messages = vec![];
+        loop {
+            let (message, length) = match self.encoding {
+                MessageEncoding::Json => {
+                    let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') else {
+                        break;
+                    };
+                    (&self.buffer[..end], end + 1)
+                }
+                MessageEncoding::Bincode => {
+                    let Some(header) = self.buffer.get(..4) else {
+                        break;
+                    };
+                    let end = 4 + u32::from_le_bytes(header.try_into().unwrap()) as usize;
+                    let Some(message) = self.buffer.get(4..end) else {
+                        break;
+                    };
+                    (message, end)
+                }
+            };
+            messages.push(match self.encoding {
+                MessageEncoding::Json => serde_json::from_slice(message)
+                    .expect("error decoding verification message"),
+                MessageEncoding::Bincode => {
+                    bincode::deserialize(message).expect("error decoding verification message")
+                }
+            });
+            self.buffer.drain(..length);
+        }
+        messages
+    }
+
+    /// Whether a part of a message was received without its end.
+    pub fn is_incomplete(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_split_messages() {
+        let messages = vec![
+            ServerMessage::MethodVerified {
+                name: "m_first".to_string(),
+                result: MethodResult::Success,
+                time_ms: 42,
+            },
+            ServerMessage::Termination(VerificationResult::Success),
+        ];
+        for encoding in [MessageEncoding::Json, MessageEncoding::Bincode] {
+            let body: Vec<u8> = messages
+                .iter()
+                .flat_map(|message| encoding.encode(message))
+                .collect();
+            let mut decoder = MessageDecoder::new(encoding);
+            let decoded: Vec<_> = body
+                .chunks(3)
+                .flat_map(|chunk| decoder.decode(chunk))
+                .collect();
+            assert_eq!(decoded, messages);
+            assert!(!decoder.is_incomplete());
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::VerificationRequest;
+use prusti_common::vir::program::ProgramDomain;
+use std::{collections::HashMap, fmt};
+
+/// The domains that the server received during a verification session, by
+/// their hash. When the crates of a dependency graph are verified with the
+/// same server, the clients omit the domains that the server already
+/// received from the requests of the previous crates, such as the encoding
+/// of the types of the standard library, and the server adds them back.
+#[derive(Default)]
+pub struct SessionDefinitions {
+    domains: HashMap<u64, ProgramDomain>,
+}
+
+/// The error of a request that omitted domains which the server did not
+/// receive, for example because it was restarted since.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownDomains {
+    pub hashes: Vec<u64>,
+}
+
+impl fmt::Display for UnknownDomains {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the request omitted {} domains that the server does not know",
+            self.hashes.len()
+        )
+    }
+}
+
+impl SessionDefinitions {
+    /// The hashes of the domains that requests can omit.
+    pub fn domain_hashes(&self) -> Vec<u64> {
+        self.domains.keys().copied().collect()
+    }
+
+    /// Adds the domains that the request omitted back to its program, and
+    /// records the domains of the program so that later requests can omit
+    /// them.
+    pub fn complete_request(
+        &mut self,
+        request: &mut VerificationRequest,
+    ) -> Result<(), UnknownDomains> {
+        let unknown: Vec<u64> = request
+            .shared_domains
+            .iter()
+            .copied()
+            .filter(|hash| !self.domains.contains_key(hash))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(UnknownDomains { hashes: unknown });
+        }
+        for hash in request.shared_domains.drain(..) {
+            let domain = self.domains[&hash].clone();
+            if !request.program.add_domain(domain) {
+                return Err(UnknownDomains { hashes: vec![hash] });
+            }
+        }
+        for domain in request.program.get_domains() {
+            self.domains.entry(domain.get_hash()).or_insert(domain);
+        }
+        Ok(())
+    }
+}
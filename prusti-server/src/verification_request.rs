@@ -11,16 +11,36 @@ use std::{
 };
 use viper::{self, VerificationBackend};
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Hash)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VerificationRequest {
     pub program: Program,
     pub backend_config: ViperBackendConfig,
+    /// The URL to which the server sends a summary of the verification.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Whether the server sends the dumped Viper program and the location of
+    /// the SMT logs back to the client as `ServerMessage::Artifact`s.
+    #[serde(default)]
+    pub return_artifacts: bool,
+    /// Whether the server sends a `ServerMessage::MethodVerified` for each
+    /// method of the program as soon as the verifier reports it.
+    #[serde(default)]
+    pub report_progress: bool,
+    /// The hashes of the domains that the client removed from the program
+    /// because the server already received them in the current verification
+    /// session (see `SessionDefinitions`).
+    #[serde(default)]
+    pub shared_domains: Vec<u64>,
 }
 
 impl VerificationRequest {
+    /// The hash of the request, which identifies its result in the cache. The webhook, the
+    /// returned artifacts and the reported progress do not affect the result and thus are not
+    /// part of the hash.
     pub(crate) fn get_hash(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
-        self.hash(&mut hasher);
+        self.program.hash(&mut hasher);
+        self.backend_config.hash(&mut hasher);
         hasher.finish()
     }
 }
@@ -32,6 +52,11 @@ impl VerificationRequest {
 pub struct ViperBackendConfig {
     pub backend: VerificationBackend,
     pub verifier_args: Vec<String>,
+    /// The bound on the quantifier instantiations of the verification, which
+    /// is enforced by the SMT wrapper. Overrides the `smt_qi_bound_global`
+    /// flag.
+    #[serde(default)]
+    pub qi_bound: Option<u64>,
 }
 
 impl ViperBackendConfig {
@@ -46,6 +71,9 @@ impl ViperBackendConfig {
                     verifier_args.push("--counterexample".to_string());
                     verifier_args.push("mapped".to_string());
                 }
+                if config::report_failing_paths() {
+                    verifier_args.push("--enableBranchconditionReporting".to_string());
+                }
                 if let Some(number) = config::number_of_parallel_verifiers() {
                     verifier_args.push("--numberOfParallelVerifiers".to_string());
                     verifier_args.push(number.to_string());
@@ -78,6 +106,20 @@ impl ViperBackendConfig {
         Self {
             backend,
             verifier_args,
+            qi_bound: None,
+        }
+    }
+
+    /// Enables the nonlinear arithmetic reasoning of Z3, which the default
+    /// configuration of Silicon turns off, by extending the prover
+    /// configuration.
+    pub fn enable_nonlinear_arithmetic(&mut self) {
+        if let Some(index) = self
+            .verifier_args
+            .iter()
+            .rposition(|arg| arg == "--proverConfigArgs")
+        {
+            self.verifier_args[index + 1].push_str(" smt.arith.nl=true");
         }
     }
 }
@@ -0,0 +1,381 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{
+    jvm_memory, notify_webhook, process_verification_request_with_cache_status, CacheStatistics,
+    ReusableVerifier, ServerMessage, VerificationRequest, VerificationSummary,
+};
+use log::{error, info};
+use prusti_common::{config, Stopwatch};
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Condvar, Mutex, PoisonError},
+    thread,
+    time::Instant,
+};
+use viper::{JavaException, PersistentCache, VerificationResult, Viper};
+
+type RequestWithReplies = (VerificationRequest, mpsc::Sender<ServerMessage>);
+
+/// Processes verification requests in-process, without the HTTP layer of the
+/// server. The requests are verified one at a time by a thread that keeps the
+/// JVM and, if the `reuse_verifier` flag is set, the verifier alive between
+/// requests. If a request has a webhook, a summary of its verification is
+/// sent to the webhook once the request is verified.
+///
+/// The requests of different clients are verified round-robin, so that a
+/// client that submits many requests does not starve the other clients.
+///
+/// A request whose verification panics terminates with an error, and the
+/// thread continues with the next request. If the thread stops, for example
+/// because the JVM could not be started, the queued and all later requests
+/// terminate with an error instead of waiting forever.
+pub struct VerificationRequestProcessing {
+    queue: Arc<(Mutex<RequestQueue>, Condvar)>,
+}
+
+/// The error of a request that was rejected because its client already has
+/// `server_max_requests_per_client` requests queued or being verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooManyRequests {
+    pub client: String,
+    pub limit: usize,
+}
+
+impl fmt::Display for TooManyRequests {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "client {} already has {} verification requests in progress",
+            self.client, self.limit
+        )
+    }
+}
+
+/// The queued requests, by client. Requests without a client form a queue of
+/// their own and are not limited.
+#[derive(Default)]
+struct RequestQueue {
+    /// The clients with queued requests, in the order in which they are served.
+    clients: VecDeque<(Option<String>, VecDeque<RequestWithReplies>)>,
+    /// The number of queued or running requests of each client.
+    active_requests: HashMap<Option<String>, usize>,
+    /// Whether the processing was dropped, which stops the verification
+    /// thread, or the verification thread stopped.
+    closed: bool,
+}
+
+impl RequestQueue {
+    fn push(&mut self, client: Option<String>, request: RequestWithReplies) {
+        if self.closed {
+            let (_, message_sender) = request;
+            let _ = message_sender.send(ServerMessage::Termination(stopped_thread_result()));
+            return;
+        }
+        *self.active_requests.entry(client.clone()).or_default() += 1;
+        match self.clients.iter_mut().find(|(queued, _)| *queued == client) {
+            Some((_, requests)) => requests.push_back(request),
+            None => self.clients.push_back((client, VecDeque::from([request]))),
+        }
+    }
+
+    /// Takes the next request of the client whose turn it is, and moves the
+    /// client to the end of the queue.
+    fn pop(&mut self) -> Option<(Option<String>, RequestWithReplies)> {
+        let (client, mut requests) = self.clients.pop_front()?;
+        let request = requests.pop_front().unwrap();
+        if !requests.is_empty() {
+            self.clients.push_back((client.clone(), requests));
+        }
+        Some((client, request))
+    }
+
+    fn finish(&mut self, client: &Option<String>) {
+        if let Some(count) = self.active_requests.get_mut(client) {
+            *count -= 1;
+            if *count == 0 {
+                self.active_requests.remove(client);
+            }
+        }
+    }
+
+    /// Stops accepting requests and terminates the queued ones with an error.
+    fn close(&mut self) {
+        self.closed = true;
+        for (_, requests) in self.clients.drain(..) {
+            for (_, message_sender) in requests {
+                let _ = message_sender.send(ServerMessage::Termination(stopped_thread_result()));
+            }
+        }
+        self.active_requests.clear();
+    }
+}
+
+/// Closes the queue when the verification thread stops, also if it stops by
+/// panicking.
+struct CloseOnExit(Arc<(Mutex<RequestQueue>, Condvar)>);
+
+impl Drop for CloseOnExit {
+    fn drop(&mut self) {
+        self.0
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .close();
+    }
+}
+
+/// The result of the requests that cannot be verified because the
+/// verification thread stopped.
+fn stopped_thread_result() -> VerificationResult {
+    VerificationResult::JavaException(JavaException::new(
+        "the verification thread of the server stopped".to_string(),
+        String::new(),
+    ))
+}
+
+/// The result of a request whose verification panicked.
+fn panicked_result(payload: Box<dyn Any + Send>) -> VerificationResult {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    error!("The verification of a request panicked: {}", message);
+    VerificationResult::JavaException(JavaException::new(
+        format!("the verification of the request panicked: {}", message),
+        String::new(),
+    ))
+}
+
+impl VerificationRequestProcessing {
+    /// Starts the thread that verifies the requests, using the cache that is
+    /// loaded from the `cache_path` flag.
+    pub fn new() -> Self {
+        let cache = PersistentCache::load_cache(config::cache_path());
+        Self::with_cache(Arc::new(Mutex::new(cache)))
+    }
+
+    /// Starts the thread that verifies the requests, using the given cache.
+    pub fn with_cache(cache: Arc<Mutex<PersistentCache>>) -> Self {
+        let queue = Arc::new((Mutex::new(RequestQueue::default()), Condvar::new()));
+        let thread_queue = queue.clone();
+        thread::Builder::new()
+            .name("prusti-verification".to_string())
+            .spawn(move || {
+                let _close_on_exit = CloseOnExit(thread_queue.clone());
+                let mut stopwatch = Stopwatch::start("prusti-server", "JVM startup");
+                let viper = Viper::new_with_memory(
+                    &config::viper_home(),
+                    config::extra_jvm_args(),
+                    jvm_memory(None),
+                );
+                stopwatch.start_next("attach thread to JVM");
+                let viper_thread = viper.attach_current_thread();
+                stopwatch.finish();
+                let mut reusable_verifier = ReusableVerifier::default();
+                let mut total_cache_statistics = CacheStatistics::default();
+                let (queue, request_available) = &*thread_queue;
+                loop {
+                    let (client, (request, message_sender)) = {
+                        let mut queue = queue.lock().unwrap();
+                        loop {
+                            if let Some(next) = queue.pop() {
+                                break next;
+                            }
+                            if queue.closed {
+                                return;
+                            }
+                            queue = request_available.wait(queue).unwrap();
+                        }
+                    };
+                    let webhook = request
+                        .webhook_url
+                        .clone()
+                        .map(|url| (url, request.program.get_name().to_string()));
+                    let start = Instant::now();
+                    // The verified methods are sent from another thread, which needs its own
+                    // sender.
+                    let progress_sender = message_sender.clone();
+                    let processing = panic::catch_unwind(AssertUnwindSafe(|| {
+                        process_verification_request_with_cache_status(
+                            &viper_thread,
+                            request,
+                            &cache,
+                            &mut reusable_verifier,
+                            &mut move |message| {
+                                let _ = progress_sender.send(message);
+                            },
+                        )
+                    }));
+                    let (result, cache_status) = match processing {
+                        Ok(processed) => processed,
+                        Err(payload) => {
+                            // The verifier might be in an inconsistent state.
+                            reusable_verifier = ReusableVerifier::default();
+                            queue.lock().unwrap().finish(&client);
+                            let _ = message_sender
+                                .send(ServerMessage::Termination(panicked_result(payload)));
+                            continue;
+                        }
+                    };
+                    if let Some((url, program_name)) = webhook {
+                        let summary = VerificationSummary::new(
+                            program_name,
+                            &result,
+                            start.elapsed(),
+                            cache_status,
+                        );
+                        notify_webhook(url, summary);
+                    }
+                    let mut cache_statistics = CacheStatistics::default();
+                    cache_statistics.record(cache_status);
+                    total_cache_statistics += cache_statistics;
+                    info!("Cache statistics of the server: {}", total_cache_statistics);
+                    queue.lock().unwrap().finish(&client);
+                    // The messages are dropped if the caller is no longer interested in them.
+                    let _ = message_sender.send(ServerMessage::CacheStatistics(cache_statistics));
+                    let _ = message_sender.send(ServerMessage::Termination(result));
+                }
+            })
+            .expect("failed to spawn the verification thread");
+        Self { queue }
+    }
+
+    /// Submits the request for verification and returns the messages about
+    /// its verification, which end with a `ServerMessage::Termination`.
+    pub fn process_request(
+        &self,
+        request: VerificationRequest,
+    ) -> impl Iterator<Item = ServerMessage> {
+        let (message_sender, message_receiver) = mpsc::channel();
+        self.queue.0.lock().unwrap().push(None, (request, message_sender));
+        self.queue.1.notify_one();
+        message_receiver.into_iter()
+    }
+
+    /// Like `process_request`, but the request is scheduled fairly with the
+    /// requests of the other clients, and is rejected if the client already
+    /// has `server_max_requests_per_client` requests in progress.
+    pub fn process_client_request(
+        &self,
+        client: String,
+        request: VerificationRequest,
+    ) -> Result<impl Iterator<Item = ServerMessage>, TooManyRequests> {
+        let (message_sender, message_receiver) = mpsc::channel();
+        {
+            let mut queue = self.queue.0.lock().unwrap();
+            let client = Some(client);
+            if let Some(limit) = config::server_max_requests_per_client() {
+                if queue.active_requests.get(&client).copied().unwrap_or_default() >= limit {
+                    return Err(TooManyRequests {
+                        client: client.unwrap(),
+                        limit,
+                    });
+                }
+            }
+            queue.push(client, (request, message_sender));
+        }
+        self.queue.1.notify_one();
+        Ok(message_receiver.into_iter())
+    }
+
+    /// Verifies the request and returns its result.
+    pub fn verify(&self, request: VerificationRequest) -> VerificationResult {
+        termination_result(self.process_request(request))
+    }
+}
+
+/// The result carried by the `ServerMessage::Termination` that ends the
+/// messages of a request.
+pub fn termination_result(messages: impl Iterator<Item = ServerMessage>) -> VerificationResult {
+    match messages.last() {
+        Some(ServerMessage::Termination(result)) => result,
+        Some(message) => panic!(
+            "the messages of a request ended with {:?} instead of a termination",
+            message
+        ),
+        None => panic!("the verification thread stopped before finishing the request"),
+    }
+}
+
+impl Default for VerificationRequestProcessing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VerificationRequestProcessing {
+    fn drop(&mut self) {
+        let (queue, request_available) = &*self.queue;
+        queue.lock().unwrap().closed = true;
+        request_available.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ViperBackendConfig;
+    use prusti_common::vir::{self, program::Program};
+    use viper::VerificationBackend;
+
+    fn request() -> VerificationRequest {
+        VerificationRequest {
+            program: Program::Legacy(vir::Program {
+                name: "queued".to_string(),
+                domains: vec![],
+                fields: vec![],
+                builtin_methods: vec![],
+                methods: vec![],
+                functions: vec![],
+                viper_predicates: vec![],
+            }),
+            backend_config: ViperBackendConfig::new(VerificationBackend::Silicon),
+            webhook_url: None,
+            return_artifacts: false,
+            report_progress: false,
+            shared_domains: vec![],
+        }
+    }
+
+    fn is_stopped_thread_termination(messages: mpsc::Receiver<ServerMessage>) -> bool {
+        matches!(
+            termination_result(messages.into_iter()),
+            VerificationResult::JavaException(_)
+        )
+    }
+
+    #[test]
+    fn close_terminates_queued_requests() {
+        let mut queue = RequestQueue::default();
+        let (first_sender, first_receiver) = mpsc::channel();
+        let (second_sender, second_receiver) = mpsc::channel();
+        queue.push(Some("client".to_string()), (request(), first_sender));
+        queue.push(None, (request(), second_sender));
+        queue.close();
+
+        assert!(queue.pop().is_none());
+        assert!(queue.active_requests.is_empty());
+        assert!(is_stopped_thread_termination(first_receiver));
+        assert!(is_stopped_thread_termination(second_receiver));
+    }
+
+    #[test]
+    fn closed_queue_rejects_requests() {
+        let mut queue = RequestQueue::default();
+        queue.close();
+        let (sender, receiver) = mpsc::channel();
+        queue.push(Some("client".to_string()), (request(), sender));
+
+        assert!(queue.pop().is_none());
+        assert!(queue.active_requests.is_empty());
+        assert!(is_stopped_thread_termination(receiver));
+    }
+}
@@ -0,0 +1,73 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::CacheStatus;
+use log::{info, warn};
+use std::{thread, time::Duration};
+use viper::VerificationResult;
+
+/// The summary of a verification that the server sends to the webhook of the request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VerificationSummary {
+    pub program_name: String,
+    /// One of `success`, `failure`, `consistency_errors`, `java_exception` and `timeout`.
+    pub result: String,
+    /// The number of verification or consistency errors.
+    pub errors: usize,
+    pub duration_ms: u64,
+    pub cache: CacheStatus,
+}
+
+impl VerificationSummary {
+    pub fn new(
+        program_name: String,
+        result: &VerificationResult,
+        duration: Duration,
+        cache: CacheStatus,
+    ) -> Self {
+        let (result, errors) = match result {
+            VerificationResult::Success => ("success", 0),
+            VerificationResult::Failure(errors) => ("failure", errors.len()),
+            VerificationResult::ConsistencyErrors(errors) => ("consistency_errors", errors.len()),
+            VerificationResult::JavaException(_) => ("java_exception", 0),
+            VerificationResult::Timeout => ("timeout", 0),
+        };
+        Self {
+            program_name,
+            result: result.to_string(),
+            errors,
+            duration_ms: duration.as_millis() as u64,
+            cache,
+        }
+    }
+}
+
+/// Sends the summary to the webhook on a separate thread, so that a slow or unreachable webhook
+/// does not delay the verification of the next request.
+pub(crate) fn notify_webhook(url: String, summary: VerificationSummary) {
+    let spawned = thread::Builder::new()
+        .name("prusti-webhook".to_string())
+        .spawn(move || {
+            let response = reqwest::blocking::Client::new()
+                .post(&url)
+                .json(&summary)
+                .send()
+                .and_then(|response| response.error_for_status());
+            match response {
+                Ok(_) => info!(
+                    "Sent the verification summary of program {} to {}",
+                    summary.program_name, url
+                ),
+                Err(error) => warn!(
+                    "Could not send the verification summary of program {} to {}: {}",
+                    summary.program_name, url, error
+                ),
+            }
+        });
+    if let Err(error) = spawned {
+        warn!("Could not spawn the thread of the webhook: {}", error);
+    }
+}
@@ -64,6 +64,10 @@ where
         backend_config: ViperBackendConfig::new(
             prusti_common::config::viper_backend().parse().unwrap(),
         ),
+        webhook_url: None,
+        return_artifacts: false,
+        report_progress: false,
+        shared_domains: vec![],
     };
 
     Builder::new_current_thread()
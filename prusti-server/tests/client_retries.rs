@@ -0,0 +1,139 @@
+use prusti_common::vir::*;
+use prusti_server::{
+    tokio::runtime::Builder, ClientError, MessageEncoding, PrustiClient, ServerMessage,
+    VerificationRequest, ViperBackendConfig,
+};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+use viper::{VerificationBackend, VerificationResult};
+
+/// How the fake server answers a connection.
+#[derive(Clone, Copy)]
+enum Answer {
+    /// Close the connection without responding.
+    Drop,
+    TooManyRequests,
+    Success,
+}
+
+/// Starts a server that answers the connections in the order of `answers`,
+/// repeating the last answer, and counts the connections.
+fn spawn_fake_server(answers: Vec<Answer>) -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let index = counter.fetch_add(1, Ordering::SeqCst);
+            let answer = answers[index.min(answers.len() - 1)];
+            answer_connection(stream.unwrap(), answer);
+        }
+    });
+    (address, connections)
+}
+
+fn answer_connection(mut stream: TcpStream, answer: Answer) {
+    let (status, body) = match answer {
+        Answer::Drop => return,
+        Answer::TooManyRequests => ("429 Too Many Requests", vec![]),
+        Answer::Success => (
+            "200 OK",
+            MessageEncoding::Bincode.encode(&ServerMessage::Termination(
+                VerificationResult::Success,
+            )),
+        ),
+    };
+    read_request(&mut stream);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+        status,
+        body.len()
+    )
+    .unwrap();
+    stream.write_all(&body).unwrap();
+}
+
+fn read_request(stream: &mut TcpStream) {
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line == "\r\n" || line.is_empty() {
+            break;
+        }
+        let line = line.to_lowercase();
+        if let Some(length) = line.strip_prefix("content-length:") {
+            content_length = length.trim().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+}
+
+fn verify(address: SocketAddr) -> Result<VerificationResult, ClientError> {
+    std::env::set_var("PRUSTI_SERVER_MAX_RETRIES", "2");
+    std::env::set_var("PRUSTI_SERVER_RETRY_DELAY", "1");
+    std::env::set_var("PRUSTI_SERVER_COMPRESSION", "false");
+    let client = PrustiClient::new(address).unwrap();
+    let program = Program {
+        name: "dummy".to_string(),
+        domains: vec![],
+        fields: vec![],
+        builtin_methods: vec![],
+        methods: vec![],
+        functions: vec![],
+        viper_predicates: vec![],
+    };
+    let request = VerificationRequest {
+        program: prusti_common::vir::program::Program::Legacy(program),
+        backend_config: ViperBackendConfig {
+            backend: VerificationBackend::Silicon,
+            verifier_args: vec![],
+            qi_bound: None,
+        },
+        webhook_url: None,
+        return_artifacts: false,
+        report_progress: false,
+        shared_domains: vec![],
+    };
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to construct Tokio runtime")
+        .block_on(client.verify(request))
+}
+
+#[test]
+fn retry_after_dropped_connections() {
+    let (address, connections) = spawn_fake_server(vec![Answer::Drop, Answer::Success]);
+    assert!(matches!(verify(address), Ok(VerificationResult::Success)));
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn retry_after_too_many_requests() {
+    let (address, connections) = spawn_fake_server(vec![
+        Answer::TooManyRequests,
+        Answer::TooManyRequests,
+        Answer::Success,
+    ]);
+    assert!(matches!(verify(address), Ok(VerificationResult::Success)));
+    assert_eq!(connections.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn give_up_after_max_retries() {
+    let (address, connections) = spawn_fake_server(vec![Answer::Drop]);
+    assert!(verify(address).is_err());
+    assert_eq!(connections.load(Ordering::SeqCst), 3);
+}
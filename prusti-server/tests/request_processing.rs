@@ -0,0 +1,263 @@
+use lazy_static::lazy_static;
+use prusti_common::vir::*;
+use prusti_server::{
+    tokio::runtime::Builder, Artifact, MethodResult, ServerMessage, SessionDefinitions,
+    UnknownDomains, VerificationRequest, VerificationRequestProcessing, VerificationSummary,
+    ViperBackendConfig,
+};
+use std::{net::Ipv4Addr, sync::mpsc, thread, time::Duration};
+use viper::{VerificationBackend, VerificationResult};
+use warp::Filter;
+
+lazy_static! {
+    // only start the jvm once
+    static ref PROCESSING: VerificationRequestProcessing = VerificationRequestProcessing::new();
+}
+
+#[test]
+fn empty_program_messages() {
+    let messages: Vec<_> = PROCESSING.process_request(dummy_request()).collect();
+
+    match messages.as_slice() {
+        [
+            ServerMessage::CacheStatistics(_),
+            ServerMessage::Termination(VerificationResult::Success),
+        ] => {}
+        other => panic!(
+            "empty program not verified successfully, instead found {:?}",
+            other
+        ),
+    }
+}
+
+#[test]
+fn empty_program_result() {
+    match PROCESSING.verify(dummy_request()) {
+        VerificationResult::Success => {}
+        other => panic!(
+            "empty program not verified successfully, instead found {:?}",
+            other
+        ),
+    }
+}
+
+#[test]
+fn empty_program_artifacts() {
+    let mut request = dummy_request();
+    request.return_artifacts = true;
+    let messages: Vec<_> = PROCESSING.process_request(request).collect();
+
+    assert!(messages.iter().any(|message| matches!(
+        message,
+        ServerMessage::Artifact(Artifact::ViperProgram { file_name, .. })
+            if file_name.starts_with("dummy")
+    )));
+    assert!(matches!(
+        messages.last(),
+        Some(ServerMessage::Termination(VerificationResult::Success))
+    ));
+}
+
+#[test]
+fn empty_program_client_requests() {
+    let messages: Vec<_> = ["first", "second", "first"]
+        .iter()
+        .map(|client| {
+            PROCESSING
+                .process_client_request(client.to_string(), dummy_request())
+                .expect("the number of requests of a client is not limited by default")
+        })
+        .collect();
+
+    for messages in messages {
+        match messages.last() {
+            Some(ServerMessage::Termination(VerificationResult::Success)) => {}
+            other => panic!(
+                "empty program not verified successfully, instead found {:?}",
+                other
+            ),
+        }
+    }
+}
+
+#[test]
+fn empty_program_webhook() {
+    let (summary_sender, summary_receiver) = mpsc::channel();
+    let (address_sender, address_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let webhook = warp::post()
+            .and(warp::body::json())
+            .map(move |summary: VerificationSummary| {
+                summary_sender.send(summary).unwrap();
+                warp::reply()
+            });
+        Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to construct Tokio runtime")
+            .block_on(async {
+                let (address, server) =
+                    warp::serve(webhook).bind_ephemeral((Ipv4Addr::LOCALHOST, 0));
+                address_sender.send(address).unwrap();
+                server.await
+            });
+    });
+    let address = address_receiver.recv().unwrap();
+
+    let mut request = dummy_request();
+    request.webhook_url = Some(format!("http://{}/", address));
+    assert!(PROCESSING.verify(request).is_success());
+
+    let summary = summary_receiver
+        .recv_timeout(Duration::from_secs(30))
+        .expect("the webhook did not receive a summary");
+    assert_eq!(summary.program_name, "dummy");
+    assert_eq!(summary.result, "success");
+    assert_eq!(summary.errors, 0);
+}
+
+#[test]
+fn method_verified_messages() {
+    // Only Silicon reports the verified methods.
+    if !reports_methods() {
+        return;
+    }
+    let mut request = request_with_methods(&["m_empty"]);
+    request.report_progress = true;
+    let messages: Vec<_> = PROCESSING.process_request(request).collect();
+
+    assert!(messages.iter().any(|message| matches!(
+        message,
+        ServerMessage::MethodVerified {
+            name,
+            result: MethodResult::Success,
+            ..
+        } if name == "m_empty"
+    )));
+    assert!(matches!(
+        messages.last(),
+        Some(ServerMessage::Termination(VerificationResult::Success))
+    ));
+}
+
+#[test]
+fn method_verified_messages_precede_termination() {
+    if !reports_methods() {
+        return;
+    }
+    let mut request = request_with_methods(&["m_first", "m_second", "m_third"]);
+    request.report_progress = true;
+    let messages: Vec<_> = PROCESSING.process_request(request).collect();
+
+    // All methods are reported before the statistics and the result, which are the last two
+    // messages.
+    let verified: Vec<_> = messages
+        .iter()
+        .take_while(|message| matches!(message, ServerMessage::MethodVerified { .. }))
+        .filter_map(|message| match message {
+            ServerMessage::MethodVerified { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    for name in ["m_first", "m_second", "m_third"] {
+        assert!(verified.contains(&name), "{} not reported in {:?}", name, messages);
+    }
+    match &messages[verified.len()..] {
+        [
+            ServerMessage::CacheStatistics(_),
+            ServerMessage::Termination(VerificationResult::Success),
+        ] => {}
+        other => panic!("unexpected messages after the verified methods: {:?}", other),
+    }
+}
+
+#[test]
+fn method_verified_messages_only_on_request() {
+    if !reports_methods() {
+        return;
+    }
+    let messages: Vec<_> = PROCESSING
+        .process_request(request_with_methods(&["m_unreported"]))
+        .collect();
+
+    assert!(!messages
+        .iter()
+        .any(|message| matches!(message, ServerMessage::MethodVerified { .. })));
+    assert!(matches!(
+        messages.last(),
+        Some(ServerMessage::Termination(VerificationResult::Success))
+    ));
+}
+
+#[test]
+fn session_shared_domains() {
+    let mut definitions = SessionDefinitions::default();
+    let mut first_request = dummy_request();
+    if let prusti_common::vir::program::Program::Legacy(program) = &mut first_request.program {
+        program.domains.push(Domain {
+            name: "Shared".to_string(),
+            functions: vec![],
+            axioms: vec![],
+            type_vars: vec![],
+        });
+    }
+    let mut second_request = first_request.clone();
+    definitions.complete_request(&mut first_request).unwrap();
+
+    let shared_domains = second_request
+        .program
+        .remove_domains(|hash| definitions.domain_hashes().contains(&hash));
+    assert_eq!(shared_domains.len(), 1);
+    assert!(second_request.program.get_domains().is_empty());
+    second_request.shared_domains = shared_domains;
+    definitions.complete_request(&mut second_request).unwrap();
+    assert_eq!(second_request.program, first_request.program);
+
+    let mut unknown_request = dummy_request();
+    unknown_request.shared_domains = vec![42];
+    assert_eq!(
+        SessionDefinitions::default().complete_request(&mut unknown_request),
+        Err(UnknownDomains { hashes: vec![42] })
+    );
+}
+
+fn reports_methods() -> bool {
+    dummy_request().backend_config.backend == VerificationBackend::Silicon
+}
+
+/// A request for the dummy program with an empty method of each given name. The names have to
+/// differ between the tests, so that the result is not taken from the cache.
+fn request_with_methods(names: &[&str]) -> VerificationRequest {
+    let mut request = dummy_request();
+    if let prusti_common::vir::program::Program::Legacy(program) = &mut request.program {
+        for name in names {
+            let mut method = CfgMethod::new(name.to_string(), vec![], vec![]);
+            let start = method.add_block("start", vec![]);
+            method.set_successor(start, Successor::Return);
+            program.methods.push(method);
+        }
+    }
+    request
+}
+
+fn dummy_request() -> VerificationRequest {
+    let program = Program {
+        name: "dummy".to_string(),
+        domains: vec![],
+        fields: vec![],
+        builtin_methods: vec![],
+        methods: vec![],
+        functions: vec![],
+        viper_predicates: vec![],
+    };
+    VerificationRequest {
+        program: prusti_common::vir::program::Program::Legacy(program),
+        backend_config: ViperBackendConfig::new(
+            prusti_common::config::viper_backend().parse().unwrap(),
+        ),
+        webhook_url: None,
+        return_artifacts: false,
+        report_progress: false,
+        shared_domains: vec![],
+    }
+}
@@ -0,0 +1,63 @@
+use prusti_common::{config, vir::*};
+use prusti_server::{
+    process_verification_request, ReusableVerifier, VerificationRequest, ViperBackendConfig,
+};
+use std::collections::HashMap;
+use viper::{PersistentCache, VerificationResult, Viper};
+
+#[test]
+fn verifier_reused_between_requests() {
+    // The results must not come from the cache, otherwise the verifier is not used.
+    config::update_runtime_settings(&HashMap::from([
+        ("reuse_verifier".to_string(), "true".to_string()),
+        ("enable_cache".to_string(), "false".to_string()),
+    ]))
+    .unwrap();
+    let viper = Viper::new_for_tests();
+    let verification_context = viper.attach_current_thread();
+    let mut cache = PersistentCache::load_cache(Default::default());
+    let mut reusable_verifier = ReusableVerifier::default();
+
+    for name in ["first", "second", "third"] {
+        let result = process_verification_request(
+            &verification_context,
+            request_with_method(name),
+            &mut cache,
+            &mut reusable_verifier,
+        );
+        assert!(
+            matches!(result, VerificationResult::Success),
+            "program {} not verified successfully, instead found {:?}",
+            name,
+            result
+        );
+    }
+
+    // The verifier started for the first request was reset for the others.
+    assert_eq!(reusable_verifier.started_verifiers(), 1);
+    assert_eq!(reusable_verifier.reused_verifiers(), 2);
+}
+
+/// A request for a program with an empty method of the given name.
+fn request_with_method(name: &str) -> VerificationRequest {
+    let mut method = CfgMethod::new(name.to_string(), vec![], vec![]);
+    let start = method.add_block("start", vec![]);
+    method.set_successor(start, Successor::Return);
+    let program = Program {
+        name: name.to_string(),
+        domains: vec![],
+        fields: vec![],
+        builtin_methods: vec![],
+        methods: vec![method],
+        functions: vec![],
+        viper_predicates: vec![],
+    };
+    VerificationRequest {
+        program: prusti_common::vir::program::Program::Legacy(program),
+        backend_config: ViperBackendConfig::new(config::viper_backend().parse().unwrap()),
+        webhook_url: None,
+        return_artifacts: false,
+        report_progress: false,
+        shared_domains: vec![],
+    }
+}
@@ -0,0 +1,36 @@
+use prusti_common::config;
+use prusti_server::spawn_server_thread;
+
+#[test]
+fn update_config_endpoint() {
+    let address = spawn_server_thread();
+    let client = reqwest::blocking::Client::new();
+    let url = format!("http://{}/config", address);
+
+    let response = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(r#"{"enable_cache": "false", "log": "warn"}"#)
+        .send()
+        .unwrap();
+    assert!(response.status().is_success());
+    assert!(!config::enable_cache());
+    assert_eq!(config::log(), "warn");
+
+    let response = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(r#"{"viper_backend": "Carbon"}"#)
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(r#"{"enable_cache": "sometimes"}"#)
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    assert!(!config::enable_cache());
+}
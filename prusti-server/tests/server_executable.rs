@@ -36,16 +36,91 @@ fn system_assigned_port() {
     );
 }
 
-/// runs the server for a short duration and returns its stdout as a string
-fn run_server_with_port(port: u16) -> String {
-    let server_path = env::current_exe()
+/// test that the daemon accepts requests on its socket and shuts down when idle
+#[cfg(unix)]
+#[test]
+fn daemon_shuts_down_when_idle() {
+    use prusti_common::vir::{program::Program, Program as LegacyProgram};
+    use prusti_server::{
+        tokio::runtime::Builder, PrustiClient, VerificationRequest, ViperBackendConfig,
+    };
+    use std::{os::unix::net::UnixStream, time::Instant};
+    use viper::VerificationResult;
+
+    let socket_path = env::temp_dir().join(format!("prusti-server-{}.sock", std::process::id()));
+    let mut server = Command::new(server_path())
+        .arg("--daemon")
+        .arg("--socket")
+        .arg(&socket_path)
+        .env("PRUSTI_SERVER_IDLE_TIMEOUT", "5")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run prusti-server");
+
+    let start = Instant::now();
+    while UnixStream::connect(&socket_path).is_err() {
+        assert!(
+            start.elapsed() < Duration::from_secs(30),
+            "the daemon did not listen on its socket"
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let client = PrustiClient::new(format!("unix:{}", socket_path.display())).unwrap();
+    let request = VerificationRequest {
+        program: Program::Legacy(LegacyProgram {
+            name: "dummy".to_string(),
+            domains: vec![],
+            fields: vec![],
+            builtin_methods: vec![],
+            methods: vec![],
+            functions: vec![],
+            viper_predicates: vec![],
+        }),
+        backend_config: ViperBackendConfig::new(
+            prusti_common::config::viper_backend().parse().unwrap(),
+        ),
+        webhook_url: None,
+        return_artifacts: false,
+        report_progress: false,
+        shared_domains: vec![],
+    };
+    let result = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to construct Tokio runtime")
+        .block_on(client.verify(request))
+        .expect("Verification request failed");
+    assert!(matches!(result, VerificationResult::Success));
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = server.try_wait().unwrap() {
+            break status;
+        }
+        if start.elapsed() > Duration::from_secs(60) {
+            server.kill().unwrap();
+            panic!("the daemon did not shut down when idle");
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    assert!(status.success());
+    assert!(!socket_path.exists(), "the daemon did not remove its socket");
+}
+
+fn server_path() -> std::path::PathBuf {
+    env::current_exe()
         .unwrap()
         .parent()
         .unwrap()
         .with_file_name("prusti-server-driver") // can't run prusti-server itself because we need to kill it later, and that wouldn't kill the driver
-        .with_extension(env::consts::EXE_EXTENSION);
+        .with_extension(env::consts::EXE_EXTENSION)
+}
 
-    let mut server = Command::new(server_path)
+/// runs the server for a short duration and returns its stdout as a string
+fn run_server_with_port(port: u16) -> String {
+    let mut server = Command::new(server_path())
         .arg("--port")
         .arg(port.to_string())
         .stdout(Stdio::piped())
@@ -21,3 +21,15 @@ impl<T> Opt<T> {
         }
     }
 }
+
+predicate! {
+    pub fn in_range(x: i32, lo: i32, hi: i32) -> bool {
+        lo <= x && x <= hi
+    }
+}
+
+predicate! {
+    pub fn is_byte(x: i32) -> bool {
+        in_range(x, 0, 255)
+    }
+}
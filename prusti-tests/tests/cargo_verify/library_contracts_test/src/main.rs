@@ -1,7 +1,7 @@
 // `extern crate` is required to force the crate to be used
 // otherwise Rust ignores it as it isn't used
 extern crate library_contracts_extern_specs;
-use library_contracts_lib::Opt;
+use library_contracts_lib::{in_range, is_byte, Opt};
 use prusti_contracts::*;
 
 // Demonstrating library contracts: specification is imported
@@ -11,6 +11,7 @@ fn main() {
     test_dependency_import();
     test_extern_specs();
     test_builtin_extern_specs();
+    test_dependency_predicates();
 }
 
 fn test_dependency_import() {
@@ -36,6 +37,17 @@ fn test_builtin_extern_specs() {
     let _ = divide(10).unwrap();
 }
 
+fn test_dependency_predicates() {
+    // Verifying predicates imported along with their bodies.
+    let _ = to_byte(42);
+}
+
+#[requires(in_range(x, 0, 255))]
+#[ensures(is_byte(result))]
+fn to_byte(x: i32) -> i32 {
+    x
+}
+
 #[ensures((arg % 2 == 0) == result.is_ok())]
 fn divide(arg: i32) -> Result<i32, ()> {
     if arg % 2 == 0 { Ok(arg/2) } else { Err(()) }
@@ -0,0 +1,14 @@
+mod legacy {
+    pub fn not_verified() {
+        assert!(false);
+    }
+}
+
+fn overflow_error(x: i32) -> i32 {
+    x + 1
+}
+
+fn main() {
+    let _ = overflow_error(123);
+    legacy::not_verified();
+}
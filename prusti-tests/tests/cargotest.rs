@@ -64,7 +64,7 @@ fn simple_assert_false() {
         .file("src/main.rs", "fn main() { assert!(false); }")
         .build();
     p.process(cargo_prusti_path())
-        .with_status(101)
+        .with_status(2)
         .with_stderr(
             "\
 [CHECKING] foo v0.0.1 ([..])
@@ -90,6 +90,8 @@ error: could not compile `foo` due to previous error; 1 warning emitted
 /// Special files and folders in the root of the test crate:
 /// * `output.stdout` and `output.stderr`: if present, they are used to check the output of
 ///   `cargo-prusti`.
+/// * `output.status`: if present, it is used to check the exit code of `cargo-prusti`. If only
+///   `output.stderr` is present, `cargo-prusti` is expected to report a verification failure.
 /// * `prusti-contracts` and related Prusti crates: during the test they will link to the
 ///   corresponding Prusti crate.
 ///
@@ -160,19 +162,307 @@ fn test_local_project<T: Into<PathBuf>>(project_name: T) {
     test_builder.arg("--quiet");
     let opt_expected_stdout = fs::read_to_string(project_path.join("output.stdout")).ok();
     let opt_expected_stderr = fs::read_to_string(project_path.join("output.stderr")).ok();
+    let opt_expected_status = fs::read_to_string(project_path.join("output.status"))
+        .ok()
+        .map(|status| {
+            status
+                .trim()
+                .parse::<i32>()
+                .expect("output.status should contain an exit code")
+        });
     if let Some(ref expected_stdout) = opt_expected_stdout {
         // In some cases, Prusti outputs more macro definitions than needed.
         // See: https://github.com/viperproject/prusti-dev/pull/762
         test_builder.with_stdout_contains(expected_stdout);
     }
     if let Some(ref expected_stderr) = opt_expected_stderr {
-        test_builder.with_status(101).with_stderr(expected_stderr);
+        test_builder
+            .with_status(opt_expected_status.unwrap_or(2))
+            .with_stderr(expected_stderr);
+    } else if let Some(expected_status) = opt_expected_status {
+        test_builder.with_status(expected_status);
     }
 
     // Run the test
     test_builder.run();
 }
 
+#[cargo_test]
+fn prusti_profile_arg() {
+    let p = project()
+        .file("Prusti.toml", "[profiles.lenient]\ncheck_overflows = false\n")
+        .file(
+            "src/main.rs",
+            "fn increment(x: u8) -> u8 { x + 1 }\nfn main() { increment(1); }",
+        )
+        .build();
+    // The overflow is reported without the profile, so the crate is checked again below.
+    p.process(cargo_prusti_path()).with_status(2).run();
+    p.process(cargo_prusti_path())
+        .arg("--prusti-profile")
+        .arg("lenient")
+        .run();
+    // Cargo's own `--profile` argument selects a Cargo profile, not a Prusti profile.
+    p.process(cargo_prusti_path())
+        .arg("--prusti-profile=lenient")
+        .arg("--profile")
+        .arg("release")
+        .run();
+}
+
+#[cargo_test]
+fn emit_repro_bundle() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+struct Counter {
+    value: u8,
+}
+
+fn unrelated() -> u32 {
+    42
+}
+
+fn bump(counter: &mut Counter) {
+    counter.value = counter.value + 1;
+}
+
+fn main() {
+    let mut counter = Counter { value: 1 };
+    bump(&mut counter);
+    unrelated();
+}
+",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .arg("--emit-repro")
+        .with_status(2)
+        .run();
+    assert!(p.root().join("target/verify/prusti-repro.tar.gz").is_file());
+
+    // Only the failing function and the items that it uses are kept.
+    let source_path = p
+        .root()
+        .join("target/verify/repro/foo/failing_functions.rs");
+    let source = fs::read_to_string(&source_path)
+        .unwrap_or_else(|_| panic!("Failed to read {}", source_path.display()));
+    assert!(source.contains("struct Counter"));
+    assert!(source.contains("fn bump"));
+    assert!(!source.contains("fn unrelated"));
+
+    // The minimized source still reproduces the failure.
+    let repro = project()
+        .at("repro")
+        .file("src/main.rs", &source)
+        .build();
+    repro.process(cargo_prusti_path()).with_status(2).run();
+}
+
+#[cargo_test]
+fn encoding_cache() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn increment(x: u8) -> u8 { x + 1 }\nfn main() { increment(1); }",
+        )
+        .build();
+    let cache_path = p.root().join("encoding-cache");
+    let run = || {
+        p.process(cargo_prusti_path())
+            .env("PRUSTI_UNSAFE_CORE_PROOF", "true")
+            .env("PRUSTI_ENCODING_CACHE_PATH", &cache_path)
+            .with_status(2)
+            .with_stderr_contains("[..]attempt to add with overflow[..]")
+            .run();
+    };
+    run();
+    let cached_entries = fs::read_dir(&cache_path)
+        .unwrap_or_else(|_| panic!("Failed to read {}", cache_path.display()))
+        .count();
+    assert!(cached_entries > 0);
+    // The second run takes the encodings from the cache and must report the same error.
+    run();
+}
+
+#[cargo_test]
+fn encoding_threads() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+fn inc(a: u8) -> u8 { a + 1 }
+
+fn dec(a: u8) -> u8 { a - 1 }
+
+fn check(a: u8) { assert!(a > 10); }
+
+fn fail(a: u8) { if a == 0 { unreachable!() } }
+
+fn main() {
+    inc(1);
+    dec(1);
+    check(1);
+    fail(1);
+}
+",
+        )
+        .build();
+    let run = |threads: &str| {
+        let output = p
+            .process(cargo_prusti_path())
+            .env("PRUSTI_ENCODING_THREADS", threads)
+            .build_command()
+            .output()
+            .unwrap();
+        (output.status.code(), String::from_utf8(output.stderr).unwrap())
+    };
+    let (sequential_status, sequential_errors) = run("1");
+    assert_eq!(sequential_status, Some(2));
+    assert_eq!(sequential_errors.matches("[Prusti: verification error]").count(), 4);
+    // The errors do not depend on the number of threads, not even in their order.
+    for _ in 0..3 {
+        assert_eq!(run("4"), (sequential_status, sequential_errors.clone()));
+    }
+}
+
+#[cargo_test]
+fn verification_timeout() {
+    // Silicon verifies each of the 2^32 paths through the branches separately.
+    let parameters: Vec<_> = (0..32).map(|i| format!("x{i}: bool")).collect();
+    let branches: String = (0..32).map(|i| format!("    if x{i} {{ r += 1; }}\n")).collect();
+    let p = project()
+        .file(
+            "src/main.rs",
+            &format!(
+                "fn branches({}) -> u32 {{\n    let mut r = 0;\n{}    r\n}}\n\nfn main() {{}}\n",
+                parameters.join(", "),
+                branches
+            ),
+        )
+        .build();
+    // The timed out function is reported, but does not make the verification fail.
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_VERIFICATION_TIMEOUT", "1000")
+        .with_stderr_contains("[..][Prusti: warning] verification timed out (unknown)")
+        .run();
+}
+
+#[cargo_test]
+fn emit_metadata() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.0.1"
+edition = "2021"
+
+[dependencies]
+bar = { path = "bar" }
+"#,
+        )
+        .file("src/main.rs", "fn main() { bar::half(4); }")
+        .file(
+            "bar/Cargo.toml",
+            "[package]\nname = \"bar\"\nversion = \"0.0.1\"\nedition = \"2021\"\n",
+        )
+        .file("bar/src/lib.rs", "pub fn half(x: u32) -> u32 { x / 2 }")
+        .build();
+    // The dependent crate can only be checked with the metadata of `bar`.
+    p.process(cargo_prusti_path()).run();
+    let deps = p.root().join("target/verify/debug/deps");
+    let has_metadata = |crate_name: &str| {
+        fs::read_dir(&deps)
+            .unwrap_or_else(|_| panic!("Failed to read {}", deps.display()))
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .any(|file_name| {
+                file_name.starts_with(&format!("lib{crate_name}-")) && file_name.ends_with(".rmeta")
+            })
+    };
+    assert!(has_metadata("bar"));
+    assert!(has_metadata("foo"));
+}
+
+#[cargo_test]
+fn telemetry() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn confidential_name(x: u8) -> u8 { x / 2 }\nfn main() { confidential_name(1); }",
+        )
+        .build();
+    let telemetry_dir = p.root().join("telemetry");
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_TELEMETRY", "true")
+        .env("PRUSTI_TELEMETRY_DIR", &telemetry_dir)
+        .run();
+    let reports: Vec<_> = fs::read_dir(&telemetry_dir)
+        .unwrap_or_else(|_| panic!("Failed to read {}", telemetry_dir.display()))
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(!reports.is_empty());
+    for report in reports {
+        assert!(report.contains("\"phases\""));
+        // The report is anonymized.
+        assert!(!report.contains("confidential_name"));
+        assert!(!report.contains(&*p.root().to_string_lossy()));
+    }
+}
+
+#[cargo_test]
+fn verification_record() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn half(x: u8) -> u8 { x / 2 }\nfn main() { half(1); }",
+        )
+        .build();
+    let record_dir = p.root().join("records");
+    let record_path = record_dir.join("foo.prusti.json");
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_VERIFICATION_RECORD_DIR", &record_dir)
+        .run();
+    let record = fs::read_to_string(&record_path)
+        .unwrap_or_else(|_| panic!("Failed to read {}", record_path.display()));
+    assert!(record.contains("\"crate_name\": \"foo\""));
+    assert!(record.contains("\"half\""));
+    assert!(record.contains("src/main.rs"));
+    assert!(record.contains("\"signature\": null"));
+
+    // With a key, the record is signed. The source is changed, so that the crate is verified
+    // again.
+    let key_path = p.root().join("record.key");
+    fs::write(&key_path, "secret").unwrap();
+    fs::write(
+        p.root().join("src/main.rs"),
+        "fn half(x: u8) -> u8 { x / 2 }\nfn main() { half(2); }",
+    )
+    .unwrap();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_VERIFICATION_RECORD_DIR", &record_dir)
+        .env("PRUSTI_VERIFICATION_RECORD_KEY", &key_path)
+        .run();
+    let record = fs::read_to_string(&record_path)
+        .unwrap_or_else(|_| panic!("Failed to read {}", record_path.display()));
+    assert!(record.contains("\"signature\": \""));
+
+    // A failing verification does not write a record.
+    fs::remove_file(&record_path).unwrap();
+    fs::write(
+        p.root().join("src/main.rs"),
+        "fn main() { assert!(false); }",
+    )
+    .unwrap();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_VERIFICATION_RECORD_DIR", &record_dir)
+        .with_status(2)
+        .run();
+    assert!(!record_path.exists());
+}
+
 #[cargo_test]
 fn test_symlinks() {
     // Required by `test_local_project`
@@ -212,6 +502,11 @@ fn test_prusti_toml() {
     test_local_project("prusti_toml");
 }
 
+#[cargo_test]
+fn test_prusti_toml_profile() {
+    test_local_project("prusti_toml_profile");
+}
+
 #[cargo_test]
 fn test_prusti_toml_fail() {
     let old_value = if let Ok(value) = std::env::var("RUST_BACKTRACE") {
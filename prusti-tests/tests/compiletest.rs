@@ -73,6 +73,12 @@ impl Drop for TemporaryEnvVar {
 }
 
 fn run_prusti_tests(group_name: &str, filter: &Option<String>, rustc_flags: Option<&str>) {
+    // `compiletest` expects failing tests to exit with the exit code of `rustc`.
+    let _temporary_env_vars = (TemporaryEnvVar::set(
+        "PRUSTI_DIFFERENTIATED_EXIT_CODES",
+        "false",
+    ),);
+
     let mut config = Config {
         rustc_path: find_prusti_rustc_path(),
         ..Config::default()
@@ -151,6 +157,18 @@ fn run_verification_core_proof(group_name: &str, filter: &Option<String>) {
     run_verification_base(group_name, filter);
 }
 
+/// Runs the core proof tests of the group again with the optimizations of the
+/// core proof enabled, which must not change the verification results.
+fn run_verification_core_proof_optimizations(group_name: &str, filter: &Option<String>) {
+    let _temporary_env_vars = (TemporaryEnvVar::set(
+        "PRUSTI_ENABLE_CORE_PROOF_OPTIMIZATIONS",
+        "true",
+    ),);
+    let filter = filter.clone().or_else(|| Some("core_proof/".to_string()));
+
+    run_verification_base(group_name, &filter);
+}
+
 fn run_lifetimes_dump(group_name: &str, filter: &Option<String>) {
     let _temporary_env_vars = (
         TemporaryEnvVar::set("PRUSTI_NO_VERIFY", "true"),
@@ -214,6 +232,11 @@ fn test_runner(_tests: &[&()]) {
     run_verification_core_proof("core_proof", &filter);
     save_verification_cache();
 
+    // Test the verifier with the optimizations of the core proof enabled.
+    println!("[verify_overflow core_proof optimizations]");
+    run_verification_core_proof_optimizations("verify_overflow", &filter);
+    save_verification_cache();
+
     // Test the verifier with panic checks disabled (i.e. verify only the core proof).
     println!("[lifetimes_dump]");
     run_lifetimes_dump("lifetimes_dump", &filter);
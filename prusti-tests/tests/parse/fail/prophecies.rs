@@ -0,0 +1,10 @@
+use prusti_contracts::*;
+
+#[ensures(*result == 0 && final(*result) == final(*x))] //~ ERROR: a postcondition with `final` can only refer to `result` within `final`
+fn borrow(x: &mut i32) -> &mut i32 {
+    *x = 0;
+    x
+}
+
+fn main() {
+}
@@ -0,0 +1,7 @@
+use prusti_contracts::*;
+
+#[requires(read(*x) || *x > 0)] //~ ERROR: `read` can only be used as a conjunct of a precondition
+fn inspect(x: &mut i32) {}
+
+fn main() {
+}
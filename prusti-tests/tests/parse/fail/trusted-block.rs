@@ -0,0 +1,24 @@
+use prusti_contracts::*;
+
+fn early_return(x: u32) -> u32 {
+    trusted_block! {
+        {
+            if x == 0 {
+                return 0; //~ ERROR: control flow cannot leave a trusted block early
+            }
+            x
+        }
+    }
+}
+
+fn wrong_attribute(x: u32) -> u32 {
+    trusted_block! {
+        #[pure] //~ ERROR: expected `requires`, `ensures` or `modifies`
+        {
+            x
+        }
+    }
+}
+
+fn main() {
+}
@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+
+#[trusted]
+fn call_twice<F: FnMut()>(mut f: F) {
+    f();
+    f();
+}
+
+fn weak_guarantee() {
+    let mut count = 0;
+    let cl = closure!(
+        #[after_expiry(count >= old(count))]
+        || -> () { if count < 100 { count += 1; } }
+    );
+    call_twice(cl);
+    // The closure might not have been called at all
+    assert!(count >= 1); //~ ERROR the asserted expression might not hold
+}
+
+fn not_reflexive() {
+    let mut count = 0;
+    let cl = closure!(#[after_expiry(count > old(count))] || -> () { if count < 100 { count += 1; } }); //~ ERROR the `after_expiry` specification of the closure might not hold when the closure is created
+    call_twice(cl);
+}
+
+fn main() {}
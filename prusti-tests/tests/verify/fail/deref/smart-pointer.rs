@@ -0,0 +1,57 @@
+use prusti_contracts::*;
+use std::ops::Deref;
+
+pub struct Counter {
+    value: u32,
+}
+
+impl Counter {
+    #[pure]
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+}
+
+pub struct Tagged {
+    tag: u32,
+    inner: Counter,
+}
+
+impl Deref for Tagged {
+    type Target = Counter;
+
+    fn deref(&self) -> &Counter {
+        &self.inner
+    }
+}
+
+pub struct Either {
+    left: bool,
+    first: Counter,
+    second: Counter,
+}
+
+// The body branches, so `deref` is pure only if it is marked as such.
+impl Deref for Either {
+    type Target = Counter;
+
+    fn deref(&self) -> &Counter {
+        if self.left {
+            &self.first
+        } else {
+            &self.second
+        }
+    }
+}
+
+#[requires(either.get() > 0)] //~ ERROR use of impure function
+fn client(either: &Either) {}
+
+fn main() {
+    let tagged = Tagged {
+        tag: 7,
+        inner: Counter { value: 1 },
+    };
+    assert!(tagged.get() == 1);
+    assert!(tagged.get() == 2); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,57 @@
+use prusti_contracts::*;
+use std::ops::{Index, IndexMut};
+
+pub struct Buffer {
+    v: Vec<i32>,
+}
+
+impl Buffer {
+    #[trusted]
+    #[pure]
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    #[trusted]
+    #[pure]
+    #[requires(index < self.len())]
+    pub fn get(&self, index: usize) -> i32 {
+        self.v[index]
+    }
+}
+
+impl Index<usize> for Buffer {
+    type Output = i32;
+
+    #[trusted]
+    #[requires(index < self.len())]
+    #[ensures(*result == self.get(index))]
+    fn index(&self, index: usize) -> &i32 {
+        &self.v[index]
+    }
+}
+
+impl IndexMut<usize> for Buffer {
+    #[trusted]
+    #[requires(index < self.len())]
+    #[after_expiry(self.len() == old(self.len()) && self.get(index) == before_expiry(*result))]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut self.v[index]
+    }
+}
+
+#[requires(buf.len() == 4)]
+fn out_of_bounds(buf: &Buffer) -> i32 {
+    buf[4] //~ ERROR precondition might not hold
+}
+
+#[requires(buf.len() == 4)]
+fn other_elements_unknown(buf: &mut Buffer) {
+    let before = buf[2];
+    buf[1] = 5;
+    assert!(buf[1] == 5);
+    // The pledge of `index_mut` does not say that the other elements are unchanged
+    assert!(buf[2] == before); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use prusti_contracts::*;
+
+#[ensures(result == value)] //~ ERROR postcondition might not hold
+fn mixed_endianness(value: u32) -> u32 {
+    u32::from_be_bytes(value.to_le_bytes())
+}
+
+fn main() {
+    let bytes = 0x1234u16.to_le_bytes();
+    assert!(bytes[0] == 0x12); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,14 @@
+// The errors in the two expansions of the macro are reported only once.
+
+macro_rules! my_assert {
+    ( $( $args:expr ),* ) => {
+        assert!( $( $args ),* )  //~ ERROR the asserted expression might not hold
+    };
+}
+
+fn foo(x: bool, y: bool) {
+    my_assert!(x);
+    my_assert!(y);
+}
+
+fn main() {}
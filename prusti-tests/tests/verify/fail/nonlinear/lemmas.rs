@@ -0,0 +1,11 @@
+use prusti_contracts::*;
+
+#[requires(a <= b)]
+#[ensures(a * a <= b * b)] //~ ERROR postcondition might not hold
+fn square_monotonic(a: u32, b: u32) {}
+
+fn divisor_might_be_zero(a: u32, b: u32) {
+    lemmas::u32::div_mod(a, b); //~ ERROR precondition might not hold
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use prusti_contracts::*;
+
+predicate! {
+    fn is_byte(x: i32) -> bool {
+        0 <= x && x <= 255
+    }
+}
+
+#[ensures(unfolding!(is_byte(result), result < 255))] //~ ERROR postcondition might not hold
+fn max_byte() -> i32 {
+    255
+}
+
+// The predicate has to hold, even if the assertion holds without it.
+#[ensures(unfolding!(is_byte(result), result > 0))] //~ ERROR postcondition might not hold
+fn large() -> i32 {
+    1000
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+use prusti_std::{ord_eq, sorted};
+
+fn search_unsorted(s: &[i32]) {
+    let _ = s.binary_search(&1); //~ ERROR precondition might not hold
+}
+
+#[requires(sorted(s))]
+fn not_found_is_in_bounds(s: &[i32], x: &i32) {
+    match s.binary_search(x) {
+        Ok(i) => {
+            prusti_assert!(ord_eq(&s[i], x));
+        }
+        Err(i) => {
+            prusti_assert!(i < s.len()); //~ ERROR the asserted expression might not hold
+        }
+    }
+}
+
+// The contract of `sort` states only that the result is sorted, not that it
+// is a permutation of the input.
+fn sort_keeps_the_elements(v: &mut [i32]) {
+    if v.len() > 0 && v[0] == 5 {
+        v.sort();
+        assert!(v[0] == 5); //~ ERROR the asserted expression might not hold
+    }
+}
+
+fn main() {}
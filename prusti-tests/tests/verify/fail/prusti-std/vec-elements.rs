@@ -0,0 +1,37 @@
+use prusti_contracts::*;
+use prusti_std::vec_elem;
+
+fn remove_shifts_elements() {
+    let mut v: Vec<i32> = Vec::new();
+    v.push(1);
+    v.push(2);
+    v.push(3);
+    v.remove(0);
+    prusti_assert!(*vec_elem(&v, 0) == 1); //~ ERROR the asserted expression might not hold
+}
+
+fn swap_remove_does_not_shift() {
+    let mut v: Vec<i32> = Vec::new();
+    v.push(1);
+    v.push(2);
+    v.push(3);
+    v.swap_remove(0);
+    prusti_assert!(*vec_elem(&v, 1) == 3); //~ ERROR the asserted expression might not hold
+}
+
+fn remove_out_of_bounds() {
+    let mut v: Vec<i32> = Vec::new();
+    v.push(1);
+    v.truncate(0);
+    v.remove(0); //~ ERROR precondition might not hold
+}
+
+#[requires(index < v.len())]
+#[ensures(forall(|i: usize| i < v.len() ==> //~ ERROR postcondition might not hold
+    snapshot_equality(vec_elem(v, i), old(vec_elem(v, i)))))]
+fn remove_and_push<T>(v: &mut Vec<T>, index: usize) {
+    let element = v.remove(index);
+    v.push(element);
+}
+
+fn main() {}
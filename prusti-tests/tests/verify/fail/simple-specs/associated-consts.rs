@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+trait Limited {
+    const LIMIT: u32;
+}
+
+struct Small;
+
+impl Limited for Small {
+    const LIMIT: u32 = 10;
+}
+
+#[requires(x <= T::LIMIT)]
+fn limit<T: Limited>(x: u32) -> u32 {
+    // The value of the constant is unknown for an arbitrary `T`.
+    assert!(T::LIMIT > 0); //~ ERROR the asserted expression might not hold
+    x
+}
+
+fn main() {
+    limit::<Small>(11); //~ ERROR precondition might not hold
+}
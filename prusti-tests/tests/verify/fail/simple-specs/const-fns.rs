@@ -0,0 +1,30 @@
+// compile-flags: -Pcheck_panics=false -Pcheck_const_fns=true
+#![feature(const_fn_floating_point_arithmetic)]
+use prusti_contracts::*;
+
+// A panic is a compilation error when the function is evaluated in a
+// constant context, so it is reported even though `check_panics` is disabled.
+const fn average(total: u32, count: u32) -> u32 {
+    total / count //~ ERROR assertion might fail with "attempt to divide by zero"
+}
+
+#[requires(count > 0)]
+const fn checked_average(total: u32, count: u32) -> u32 {
+    total / count
+}
+
+// The absence of panics in functions that are not `const` is not checked.
+fn runtime_average(total: u32, count: u32) -> u32 {
+    total / count
+}
+
+const fn half(x: f64) -> f64 {
+    x / 2.0 //~ ERROR floating-point arithmetic is not deterministic during const evaluation
+}
+
+// Comparisons of floating-point numbers are deterministic.
+const fn is_positive(x: f64) -> bool {
+    x > 0.0
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// compile-flags: -Preport_failing_paths=true
+
+use prusti_contracts::*;
+
+#[ensures(result > 0)] //~ ERROR postcondition might not hold
+fn clamp(x: i32) -> i32 {
+    if x > 10 {
+        10
+    } else if x > 0 {
+        x
+    } else {
+        x - 1
+    }
+}
+
+fn main() {}
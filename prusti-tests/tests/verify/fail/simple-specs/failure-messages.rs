@@ -0,0 +1,23 @@
+use prusti_contracts::*;
+
+#[requires(index > 0, "the index must be positive, because the first slot is reserved")]
+#[ensures(result < index, "the result must be smaller than the index")] //~ ERROR postcondition might not hold
+fn previous(index: u32) -> u32 {
+    index
+}
+
+#[requires(x != 0, "x must be non-zero, because it is used as a divisor")]
+fn divide(y: i32, x: i32) -> i32 {
+    if x == -1 { 0 } else { y / x }
+}
+
+fn test_previous() {
+    previous(1);
+    previous(0); //~ ERROR precondition might not hold
+}
+
+fn test_divide(x: i32) {
+    divide(10, x); //~ ERROR precondition might not hold
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+// compile-flags: -Pmemory_safety_only=true
+use prusti_contracts::*;
+
+// The wrong postcondition is ignored.
+#[ensures(result == a)]
+fn max(a: i32, b: i32) -> i32 {
+    if a > b { a } else { b }
+}
+
+// The precondition is ignored as well, so the panic that it rules out is reported.
+#[requires(index < slice.len())]
+fn get(slice: &[u32], index: usize) -> u32 {
+    slice[index] //~ ERROR the array or slice index may be out of bounds
+}
+
+fn main() {
+    let m = max(1, 2);
+    // The postcondition is not used at the call either.
+    assert!(m == 1); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,8 @@
+use prusti_contracts::*;
+
+#[requires(read(*x))] //~ ERROR postcondition might not hold
+fn reset(x: &mut i32) {
+    *x = 0;
+}
+
+fn main() {}
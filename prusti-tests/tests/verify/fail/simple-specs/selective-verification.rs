@@ -0,0 +1,22 @@
+// compile-flags: -Pselective_verification=crate::target
+//
+// Only the selected function is verified, so the failing postcondition of
+// `other` is not reported. The program is sliced to the definitions used by
+// `target`, which must keep the contract of the called function.
+
+use prusti_contracts::*;
+
+#[ensures(result == a + 1)]
+fn inc(a: i32) -> i32 {
+    a + 1
+}
+
+#[ensures(result == a + 3)] //~ ERROR postcondition might not hold
+fn target(a: i32) -> i32 {
+    inc(inc(a))
+}
+
+#[ensures(false)]
+fn other() {}
+
+fn main() {}
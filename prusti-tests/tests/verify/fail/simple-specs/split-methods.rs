@@ -0,0 +1,34 @@
+// compile-flags: -Pmax_method_size=10
+//
+// Each failing assertion of a split method is reported once, by the method
+// generated for the part that contains it.
+
+use prusti_contracts::*;
+
+#[requires(a > 0)]
+fn long(a: i32, b: bool) -> i32 {
+    let mut x = a;
+    if b {
+        x = x + 1;
+    } else {
+        x = x + 2;
+    }
+    assert!(x > 2); //~ ERROR the asserted expression might not hold
+    let mut y = x;
+    if x > 5 {
+        y = y + 3;
+    } else {
+        y = y + 4;
+    }
+    assert!(y > 4);
+    let mut z = y;
+    if b {
+        z = z + 5;
+    } else {
+        z = z + 6;
+    }
+    assert!(z > 100); //~ ERROR the asserted expression might not hold
+    z + 1
+}
+
+fn main() {}
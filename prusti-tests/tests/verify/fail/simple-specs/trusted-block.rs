@@ -0,0 +1,32 @@
+use prusti_contracts::*;
+
+fn checked_precondition(x: u32) -> u32 {
+    trusted_block! {
+        #[requires(x > 0)] //~ ERROR the asserted expression might not hold
+        #[ensures(result < x)]
+        {
+            x - 1
+        }
+    }
+}
+
+fn modified_places() {
+    let mut x = 0;
+    trusted_block! {
+        {
+            x += 1;
+        }
+    };
+    assert!(x == 0); //~ ERROR the asserted expression might not hold
+}
+
+fn unknown_result(x: u32) {
+    let y = trusted_block! {
+        {
+            x + 1
+        }
+    };
+    assert!(y > x); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
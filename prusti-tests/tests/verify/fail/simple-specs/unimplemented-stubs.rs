@@ -0,0 +1,24 @@
+// compile-flags: -Pstub_unimplemented_functions=true
+
+use prusti_contracts::*;
+
+#[requires(x > 0)]
+#[ensures(result < x)]
+fn predecessor(x: u32) -> u32 {
+    todo!()
+}
+
+fn caller() {
+    let y = predecessor(0); //~ ERROR precondition might not hold
+}
+
+#[ensures(result > 0)]
+fn partially_implemented(x: u32) -> u32 {
+    if x > 0 {
+        x
+    } else {
+        unimplemented!() //~ ERROR unimplemented!(..) statement might be reachable
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+use prusti_contracts::time_credits::{tick, TimeCredits};
+
+#[requires(credits.amount() >= 1)]
+fn two_steps(credits: &mut TimeCredits) {
+    tick(credits);
+    tick(credits); //~ ERROR precondition might not hold
+}
+
+#[requires(credits.amount() >= 2)]
+#[ensures(credits.amount() == old(credits.amount()) - 1)] //~ ERROR postcondition might not hold
+fn understated(credits: &mut TimeCredits) {
+    tick(credits);
+    tick(credits);
+}
+
+fn main() {}
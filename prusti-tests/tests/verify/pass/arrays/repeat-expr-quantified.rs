@@ -0,0 +1,19 @@
+// the elements of a repeat expression with a non-constant element are known
+// without restating them in a loop
+
+use prusti_contracts::*;
+
+#[ensures(forall(|i: usize| i < 64 ==> result[i] == x))]
+fn fill(x: u32) -> [u32; 64] {
+    [x; 64]
+}
+
+fn main() {
+    let a = fill(7);
+    assert!(a[0] == 7);
+    assert!(a[63] == 7);
+
+    let mut b = [a[5]; 16];
+    b[3] = 0;
+    assert!(b[2] == 7 && b[3] == 0 && b[15] == 7);
+}
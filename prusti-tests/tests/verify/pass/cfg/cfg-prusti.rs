@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+
+#[cfg(prusti)]
+mod spec_helpers {
+    use prusti_contracts::*;
+
+    #[pure]
+    pub fn is_even(x: u32) -> bool {
+        x % 2 == 0
+    }
+}
+
+#[cfg(prusti)]
+use spec_helpers::is_even;
+
+#[cfg_attr(prusti, ensures(is_even(result)))]
+fn double(x: u32) -> u32 {
+    x + x
+}
+
+// Excluded when verifying, so the failing assertion is not reported.
+#[cfg(not(prusti))]
+fn unverified() {
+    assert!(false);
+}
+
+fn main() {
+    let x = double(3);
+    assert!(x % 2 == 0);
+}
@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+#[trusted]
+fn call_twice<F: FnMut()>(mut f: F) {
+    f();
+    f();
+}
+
+fn main() {
+    let mut count = 0;
+    let cl = closure!(
+        #[after_expiry(count >= old(count))]
+        || -> () { if count < 100 { count += 1; } }
+    );
+    call_twice(cl);
+    assert!(count >= 0);
+
+    let mut max = 5;
+    let cl = closure!(
+        #[after_expiry(max >= old(max) && max >= 5)]
+        || -> () { if max < 10 { max = 10; } }
+    );
+    call_twice(cl);
+    assert!(max >= 5);
+}
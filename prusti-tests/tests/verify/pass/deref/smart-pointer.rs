@@ -0,0 +1,66 @@
+use prusti_contracts::*;
+use std::ops::{Deref, DerefMut};
+
+pub struct Counter {
+    value: u32,
+}
+
+impl Counter {
+    #[pure]
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+
+    #[requires(self.get() < 100)]
+    #[ensures(self.get() == old(self.get()) + 1)]
+    pub fn increment(&mut self) {
+        self.value += 1;
+    }
+}
+
+pub struct Tagged {
+    tag: u32,
+    inner: Counter,
+}
+
+// The body only borrows a field, so `deref` is pure without an annotation.
+impl Deref for Tagged {
+    type Target = Counter;
+
+    fn deref(&self) -> &Counter {
+        &self.inner
+    }
+}
+
+impl DerefMut for Tagged {
+    #[ensures(result.get() == old(self.get()))]
+    #[after_expiry(self.get() == before_expiry(result.get()))]
+    fn deref_mut(&mut self) -> &mut Counter {
+        &mut self.inner
+    }
+}
+
+#[ensures(result == tagged.inner.value)]
+fn read(tagged: &Tagged) -> u32 {
+    tagged.get()
+}
+
+#[requires(tagged.get() < 100)]
+#[ensures(tagged.get() == old(tagged.get()) + 1)]
+fn increment(tagged: &mut Tagged) {
+    tagged.increment();
+}
+
+#[requires(counter.get() == 3)]
+fn take(counter: &Counter) {}
+
+fn main() {
+    let mut tagged = Tagged {
+        tag: 7,
+        inner: Counter { value: 1 },
+    };
+    increment(&mut tagged);
+    assert!(tagged.get() == 2);
+    increment(&mut tagged);
+    take(&tagged);
+}
@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+struct Counter {
+    value: u32,
+}
+
+#[requires(c.value < 100)]
+#[ensures(c.value == old(c.value) + 1)]
+fn increment(c: &mut Counter) {
+    println!("incrementing {}", c.value);
+    c.value += 1;
+    eprintln!("incremented to {} (limit {})", c.value, 100);
+}
+
+fn main() {
+    let x = 42;
+    println!("x = {}", x);
+    println!("x = {x:?}, twice = {}", 2 * x);
+    assert!(x == 42);
+
+    let mut c = Counter { value: 0 };
+    increment(&mut c);
+    println!("done");
+    assert!(c.value == 1);
+}
@@ -0,0 +1,20 @@
+#![feature(generators)]
+
+use prusti_contracts::*;
+
+struct Counter {
+    value: u32,
+}
+
+#[requires(counter.value < 10)]
+#[ensures(counter.value == old(counter.value))]
+fn spawn(counter: &Counter) {
+    let start = counter.value;
+    let generator = move || {
+        yield start;
+        start + 1
+    };
+    drop(generator);
+}
+
+fn main() {}
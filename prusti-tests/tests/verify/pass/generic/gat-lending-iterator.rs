@@ -0,0 +1,53 @@
+use prusti_contracts::*;
+
+/// An iterator whose items can borrow from the iterator itself.
+trait LendingIterator {
+    type Item<'a>
+    where
+        Self: 'a;
+
+    #[pure]
+    fn remaining(&self) -> u32;
+
+    #[ensures(old(self.remaining()) > 0 ==> self.remaining() == old(self.remaining()) - 1)]
+    #[ensures(old(self.remaining()) == 0 ==> self.remaining() == 0)]
+    fn next(&mut self) -> Option<Self::Item<'_>>;
+}
+
+struct Countdown {
+    value: u32,
+}
+
+impl LendingIterator for Countdown {
+    type Item<'a> = u32;
+
+    #[pure]
+    fn remaining(&self) -> u32 {
+        self.value
+    }
+
+    fn next(&mut self) -> Option<Self::Item<'_>> {
+        if self.value == 0 {
+            None
+        } else {
+            self.value -= 1;
+            Some(self.value)
+        }
+    }
+}
+
+#[requires(iter.remaining() > 1)]
+#[ensures(iter.remaining() == old(iter.remaining()) - 2)]
+fn skip_two<I: LendingIterator>(iter: &mut I) {
+    iter.next();
+    iter.next();
+}
+
+fn main() {
+    let mut countdown = Countdown { value: 3 };
+    skip_two(&mut countdown);
+    assert!(countdown.remaining() == 1);
+    countdown.next();
+    countdown.next();
+    assert!(countdown.remaining() == 0);
+}
@@ -0,0 +1,37 @@
+use prusti_contracts::*;
+use std::marker::PhantomData;
+
+struct Open;
+struct Closed;
+
+struct Door<State> {
+    opened_times: u32,
+    state: PhantomData<fn() -> State>,
+}
+
+impl Door<Closed> {
+    #[ensures(result.opened_times == 0)]
+    fn new() -> Self {
+        Door { opened_times: 0, state: PhantomData }
+    }
+
+    #[requires(self.opened_times < 100)]
+    #[ensures(result.opened_times == old(self.opened_times) + 1)]
+    fn open(self) -> Door<Open> {
+        Door { opened_times: self.opened_times + 1, state: PhantomData }
+    }
+}
+
+impl Door<Open> {
+    #[ensures(result.opened_times == old(self.opened_times))]
+    fn close(self) -> Door<Closed> {
+        Door { opened_times: self.opened_times, state: PhantomData }
+    }
+}
+
+fn main() {
+    let door = Door::new();
+    let door = door.open();
+    let door = door.close();
+    assert!(door.opened_times == 1);
+}
@@ -0,0 +1,65 @@
+use prusti_contracts::*;
+use std::ops::{Index, IndexMut};
+
+pub struct Buffer {
+    v: Vec<i32>,
+}
+
+impl Buffer {
+    #[trusted]
+    #[pure]
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    #[trusted]
+    #[pure]
+    #[requires(index < self.len())]
+    pub fn get(&self, index: usize) -> i32 {
+        self.v[index]
+    }
+}
+
+impl Index<usize> for Buffer {
+    type Output = i32;
+
+    #[trusted]
+    #[requires(index < self.len())]
+    #[ensures(*result == self.get(index))]
+    fn index(&self, index: usize) -> &i32 {
+        &self.v[index]
+    }
+}
+
+impl IndexMut<usize> for Buffer {
+    #[trusted]
+    #[requires(index < self.len())]
+    #[ensures(*result == old(self.get(index)))]
+    #[after_expiry(
+        self.len() == old(self.len())
+        && self.get(index) == before_expiry(*result)
+        && forall(|i: usize| i < self.len() && i != index ==> self.get(i) == old(self.get(i)))
+    )]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut self.v[index]
+    }
+}
+
+#[requires(buf.len() == 4)]
+#[ensures(buf.len() == 4)]
+#[ensures(buf.get(1) == 5)]
+#[ensures(buf.get(2) == old(buf.get(2)))]
+fn update(buf: &mut Buffer) {
+    buf[1] = 5;
+    assert!(buf[1] == 5);
+    buf[3] = 0;
+    assert!(buf[1] == 5);
+}
+
+#[requires(buf.len() > 0)]
+#[ensures(result == buf.get(0))]
+fn first(buf: &Buffer) -> i32 {
+    buf[0]
+}
+
+fn main() {}
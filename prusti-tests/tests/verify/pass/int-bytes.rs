@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+#[ensures(result == value)]
+fn round_trip_le(value: u32) -> u32 {
+    u32::from_le_bytes(value.to_le_bytes())
+}
+
+#[ensures(result == value)]
+fn round_trip_be(value: u64) -> u64 {
+    u64::from_be_bytes(value.to_be_bytes())
+}
+
+#[ensures(result == value)]
+fn round_trip_signed(value: i32) -> i32 {
+    i32::from_le_bytes(value.to_le_bytes())
+}
+
+#[ensures(result == header[0] as u16 * 256 + header[1] as u16)]
+fn parse_length(header: [u8; 2]) -> u16 {
+    u16::from_be_bytes(header)
+}
+
+fn main() {
+    let bytes = 0x12345678u32.to_le_bytes();
+    assert!(bytes[0] == 0x78 && bytes[3] == 0x12);
+    let bytes = 0x1234u16.to_be_bytes();
+    assert!(bytes[0] == 0x12 && bytes[1] == 0x34);
+    let bytes = (-2i16).to_le_bytes();
+    assert!(bytes[0] == 0xfe && bytes[1] == 0xff);
+    assert!(i16::from_be_bytes([0xff, 0xff]) == -1);
+}
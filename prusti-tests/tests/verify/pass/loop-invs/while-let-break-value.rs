@@ -0,0 +1,55 @@
+use prusti_contracts::*;
+
+struct Countdown {
+    remaining: u32,
+}
+
+impl Countdown {
+    #[ensures(old(self.remaining) == 0 ==> matches!(result, None) && self.remaining == 0)]
+    #[ensures(old(self.remaining) > 0 ==> self.remaining == old(self.remaining) - 1)]
+    #[ensures(old(self.remaining) > 0 ==> matches!(result, Some(_)))]
+    fn next(&mut self) -> Option<u32> {
+        if self.remaining == 0 {
+            None
+        } else {
+            self.remaining -= 1;
+            Some(self.remaining)
+        }
+    }
+}
+
+#[ensures(counter.remaining == 0)]
+fn drain_while_let(counter: &mut Countdown) {
+    while let Some(_) = counter.next() {
+        body_invariant!(true);
+    }
+}
+
+#[requires(counter.remaining <= 100)]
+#[ensures(counter.remaining == 0)]
+#[ensures(result == old(counter.remaining))]
+fn drain_loop_break(counter: &mut Countdown) -> u32 {
+    let mut steps = 0;
+    loop {
+        body_invariant!(steps + counter.remaining == old(counter.remaining));
+        match counter.next() {
+            Some(_) => steps += 1,
+            None => break steps,
+        }
+    }
+}
+
+// The `match` lists all the variants of `Option`, so its switch has an additional
+// unreachable target, and it exits the loop before the invariant.
+#[ensures(counter.remaining == 0)]
+fn drain_match_before_invariant(counter: &mut Countdown) {
+    loop {
+        match counter.next() {
+            Some(_) => {}
+            None => break,
+        }
+        body_invariant!(true);
+    }
+}
+
+fn main() {}
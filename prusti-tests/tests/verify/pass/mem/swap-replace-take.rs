@@ -0,0 +1,36 @@
+use prusti_contracts::*;
+use std::mem;
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[ensures(a.x == old(b.x) && a.y == old(b.y))]
+#[ensures(b.x == old(a.x) && b.y == old(a.y))]
+fn swap_points(a: &mut Point, b: &mut Point) {
+    mem::swap(a, b);
+}
+
+#[ensures(result.x == old(p.x) && p.x == 0 && p.y == 0)]
+fn reset(p: &mut Point) -> Point {
+    mem::replace(p, Point { x: 0, y: 0 })
+}
+
+#[ensures(result == old(*n))]
+fn take_count(n: &mut u32) -> u32 {
+    mem::take(n)
+}
+
+fn main() {
+    let mut a = 1;
+    let mut b = 2;
+    mem::swap(&mut a, &mut b);
+    assert!(a == 2 && b == 1);
+
+    let old_a = mem::replace(&mut a, 5);
+    assert!(old_a == 2 && a == 5);
+
+    let mut count = 3;
+    assert!(take_count(&mut count) == 3);
+}
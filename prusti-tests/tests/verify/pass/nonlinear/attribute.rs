@@ -0,0 +1,15 @@
+use prusti_contracts::*;
+
+#[nonlinear]
+#[requires(a <= b)]
+#[ensures(a * a <= b * b)]
+fn square_monotonic(a: u32, b: u32) {}
+
+#[nonlinear]
+#[requires(x <= 1000 && y <= 1000)]
+#[ensures(result == x * y)]
+fn area(x: u32, y: u32) -> u32 {
+    x * y
+}
+
+fn main() {}
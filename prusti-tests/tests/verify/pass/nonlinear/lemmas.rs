@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+#[requires(a <= b)]
+#[ensures(result <= b as u64 * c as u64)]
+fn scale(a: u32, b: u32, c: u32) -> u64 {
+    let max = u32::MAX as u64;
+    lemmas::u64::mul_le_mul(a as u64, max, c as u64, max);
+    lemmas::u64::mul_monotonic(a as u64, b as u64, c as u64);
+    a as u64 * c as u64
+}
+
+#[requires(parts > 0)]
+#[ensures(result <= total)]
+#[ensures(result * parts <= total)]
+fn share(total: u32, parts: u32) -> u32 {
+    lemmas::u32::div_antitonic(total, 1, parts);
+    lemmas::u32::div_bounds(total, parts);
+    total / parts
+}
+
+#[requires(width > 0)]
+#[ensures(result.0 < width)]
+#[ensures(result.0 + result.1 * width == index)]
+fn coordinates(index: usize, width: usize) -> (usize, usize) {
+    lemmas::usize::div_mod(index, width);
+    (index % width, index / width)
+}
+
+fn main() {}
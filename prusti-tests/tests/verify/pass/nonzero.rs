@@ -0,0 +1,19 @@
+use prusti_contracts::*;
+use std::num::NonZeroU32;
+
+fn average(total: u32, count: NonZeroU32) -> u32 {
+    total / count.get()
+}
+
+fn main() {
+    let count = NonZeroU32::new(4);
+    if let Some(count) = count {
+        assert!(count.get() == 4);
+        average(12, count);
+    } else {
+        unreachable!();
+    }
+    if let Some(_) = NonZeroU32::new(0) {
+        unreachable!();
+    }
+}
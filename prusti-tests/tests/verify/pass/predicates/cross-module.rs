@@ -0,0 +1,57 @@
+use prusti_contracts::*;
+
+mod even {
+    use prusti_contracts::*;
+
+    predicate! {
+        pub fn is_even(n: u32) -> bool {
+            n == 0 || (n > 0 && super::odd::is_odd(n - 1))
+        }
+    }
+}
+
+mod odd {
+    use prusti_contracts::*;
+
+    predicate! {
+        pub fn is_odd(n: u32) -> bool {
+            n > 0 && super::even::is_even(n - 1)
+        }
+    }
+}
+
+mod ranges {
+    use prusti_contracts::*;
+
+    predicate! {
+        pub fn in_range(x: i32, lo: i32, hi: i32) -> bool {
+            lo <= x && x <= hi
+        }
+    }
+}
+
+predicate! {
+    fn is_byte(x: i32) -> bool {
+        ranges::in_range(x, 0, 255)
+    }
+}
+
+#[requires(even::is_even(n) && n > 0)]
+#[ensures(odd::is_odd(result))]
+fn predecessor_of_even(n: u32) -> u32 {
+    n - 1
+}
+
+#[requires(odd::is_odd(n))]
+#[ensures(even::is_even(result))]
+fn predecessor_of_odd(n: u32) -> u32 {
+    n - 1
+}
+
+#[requires(is_byte(x))]
+#[ensures(result <= 255)]
+fn to_byte(x: i32) -> i32 {
+    x
+}
+
+fn main() {}
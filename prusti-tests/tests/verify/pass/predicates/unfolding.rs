@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+predicate! {
+    fn is_byte(x: i32) -> bool {
+        0 <= x && x <= 255
+    }
+}
+
+predicate! {
+    fn is_ascii(x: i32) -> bool {
+        0 <= x && x < 128
+    }
+}
+
+#[requires(is_ascii(x))]
+#[ensures(unfolding!(is_byte(result), result <= 255))]
+fn to_byte(x: i32) -> i32 {
+    x
+}
+
+#[requires(unfolding!(is_ascii(x), x != 0 ==> x > 0))]
+#[ensures(result >= 0)]
+fn to_positive(x: i32) -> i32 {
+    x
+}
+
+fn main() {
+    let x = to_byte(65);
+    prusti_assert!(unfolding!(is_byte(x), x <= 255));
+    let _ = to_positive(0);
+}
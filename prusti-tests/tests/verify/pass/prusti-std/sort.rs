@@ -0,0 +1,41 @@
+use prusti_contracts::*;
+use prusti_std::{ord_eq, ord_le, sorted};
+
+fn sort_then_search(v: &mut [i32], x: i32) {
+    v.sort();
+    prusti_assert!(sorted(v));
+    match v.binary_search(&x) {
+        Ok(i) => {
+            prusti_assert!(i < v.len() && ord_eq(&v[i], &x));
+        }
+        Err(i) => {
+            prusti_assert!(i <= v.len());
+        }
+    }
+}
+
+fn sort_unstable_keeps_the_length(v: &mut [u8]) {
+    let len = v.len();
+    v.sort_unstable();
+    assert!(v.len() == len);
+    prusti_assert!(sorted(v));
+}
+
+#[requires(sorted(s))]
+#[requires(i < j && j < s.len())]
+#[ensures(ord_le(&s[i], &s[j]))]
+fn sorted_pair(s: &[i32], i: usize, j: usize) {}
+
+#[requires(sorted(s))]
+#[ensures(match result {
+    Some(i) => i < s.len() && ord_eq(&s[i], x),
+    None => forall(|i: usize| i < s.len() ==> !ord_eq(&s[i], x)),
+})]
+fn find(s: &[i32], x: &i32) -> Option<usize> {
+    match s.binary_search(x) {
+        Ok(i) => Some(i),
+        Err(_) => None,
+    }
+}
+
+fn main() {}
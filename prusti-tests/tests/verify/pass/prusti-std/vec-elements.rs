@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+use prusti_std::vec_elem;
+
+fn shift_elements() {
+    let mut v: Vec<i32> = Vec::new();
+    v.push(1);
+    v.push(3);
+    v.insert(1, 2);
+    assert!(v.len() == 3);
+    prusti_assert!(*vec_elem(&v, 0) == 1 && *vec_elem(&v, 1) == 2 && *vec_elem(&v, 2) == 3);
+
+    let first = v.remove(0);
+    assert!(first == 1);
+    prusti_assert!(*vec_elem(&v, 0) == 2 && *vec_elem(&v, 1) == 3);
+
+    v.push(4);
+    let second = v.swap_remove(0);
+    assert!(second == 2);
+    prusti_assert!(*vec_elem(&v, 0) == 4 && *vec_elem(&v, 1) == 3);
+
+    v.truncate(1);
+    assert!(v.len() == 1);
+    prusti_assert!(*vec_elem(&v, 0) == 4);
+}
+
+fn swap_remove_last() {
+    let mut v = vec![7; 2];
+    let last = v.swap_remove(1);
+    assert!(last == 7);
+    assert!(v.len() == 1);
+    prusti_assert!(*vec_elem(&v, 0) == 7);
+}
+
+#[requires(index < v.len())]
+#[ensures(v.len() == old(v.len()))]
+#[ensures(forall(|i: usize| i < v.len() ==>
+    snapshot_equality(vec_elem(v, i), old(vec_elem(v, i)))))]
+fn remove_and_insert<T>(v: &mut Vec<T>, index: usize) {
+    let element = v.remove(index);
+    v.insert(index, element);
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+use std::pin::Pin;
+
+struct Counter {
+    value: u32,
+    limit: u32,
+}
+
+impl Counter {
+    #[pure]
+    fn value(self: Pin<&Self>) -> u32 {
+        self.value
+    }
+
+    #[pure]
+    #[ensures(result == (self.value() < self.limit))]
+    fn can_increment(self: Pin<&Self>) -> bool {
+        self.value < self.limit
+    }
+
+    #[trusted]
+    #[requires(self.value < self.limit)]
+    #[ensures(self.value == old(self.value) + 1)]
+    #[ensures(self.limit == old(self.limit))]
+    fn increment(self: Pin<&mut Self>) {
+        self.get_mut().value += 1;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,60 @@
+use prusti_contracts::*;
+
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    #[pure]
+    pub fn x(&self) -> &i32 {
+        &self.x
+    }
+
+    #[pure]
+    pub fn y(&self) -> &i32 {
+        &self.y
+    }
+}
+
+pub struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+impl<T> Pair<T> {
+    #[pure]
+    pub fn first(&self) -> &T {
+        &self.first
+    }
+
+    #[pure]
+    pub fn second(&self) -> &T {
+        &self.second
+    }
+}
+
+#[requires(*p.x() < 100)]
+#[ensures(*p.x() == old(*p.x()) + 1)]
+#[ensures(*p.y() == old(*p.y()))]
+fn move_right(p: &mut Point) {
+    p.x += 1;
+}
+
+#[ensures(result == *pair.first().x() + *pair.second().x())]
+fn sum_x(pair: &Pair<Point>) -> i32 {
+    pair.first.x + pair.second.x
+}
+
+#[requires(*pair.first().x() < 100)]
+#[ensures(*pair.first().x() == old(*pair.first().x()) + 1)]
+#[ensures(*pair.second().x() == old(*pair.second().x()))]
+fn move_first_right(pair: &mut Pair<Point>) {
+    move_right(&mut pair.first);
+}
+
+fn main() {
+    let mut p = Point { x: 1, y: 2 };
+    move_right(&mut p);
+    assert!(*p.x() == 2 && *p.y() == 2);
+}
@@ -0,0 +1,39 @@
+// compile-flags: -Psuggest_annotations=true -Pskip_unsupported_features=true
+
+use prusti_contracts::*;
+
+// Suggested to be marked as `#[pure]`.
+fn max(a: i32, b: i32) -> i32 {
+    if a > b { a } else { b }
+}
+
+#[pure]
+fn min(a: i32, b: i32) -> i32 {
+    if a < b { a } else { b }
+}
+
+// Not suggested, because it calls a non-pure function.
+fn max_of_three(a: i32, b: i32, c: i32) -> i32 {
+    let m = max(a, b);
+    if m > c { m } else { c }
+}
+
+// Not suggested, because it writes through a mutable reference.
+fn reset(x: &mut i32) {
+    *x = 0;
+}
+
+// Suggested to be marked as `#[trusted]`, because it calls a function pointer.
+fn apply(f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+#[trusted]
+fn apply_trusted(f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn main() {
+    let mut x = min(max(1, 2), 3);
+    reset(&mut x);
+}
@@ -0,0 +1,51 @@
+use prusti_contracts::*;
+
+const LIMIT: u32 = 100;
+
+struct Buffer;
+
+impl Buffer {
+    const CAPACITY: usize = 16;
+}
+
+trait Limited {
+    const LIMIT: u32;
+}
+
+struct Small;
+
+impl Limited for Small {
+    const LIMIT: u32 = 10;
+}
+
+#[requires(x < LIMIT)]
+#[ensures(result <= LIMIT)]
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+#[requires(index < Buffer::CAPACITY)]
+#[ensures(result < Buffer::CAPACITY)]
+fn check_index(index: usize) -> usize {
+    index
+}
+
+#[ensures(result == u8::MAX)]
+fn max_byte() -> u8 {
+    255
+}
+
+#[requires(x <= T::LIMIT)]
+#[ensures(result <= T::LIMIT)]
+fn limit<T: Limited>(x: u32) -> u32 {
+    x
+}
+
+fn main() {
+    let x = increment(99);
+    assert!(x == 100);
+    check_index(15);
+    assert!(max_byte() == 255);
+    let y = limit::<Small>(10);
+    assert!(y <= 10);
+}
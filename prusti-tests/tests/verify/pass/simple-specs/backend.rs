@@ -0,0 +1,15 @@
+use prusti_contracts::*;
+
+#[backend(carbon)]
+#[ensures(result == a as u64 * b as u64 + c as u64)]
+fn multiply_add(a: u32, b: u32, c: u32) -> u64 {
+    a as u64 * b as u64 + c as u64
+}
+
+#[backend(silicon)]
+#[ensures(result == 42)]
+fn test() -> u64 {
+    multiply_add(4, 10, 2)
+}
+
+fn main() {}
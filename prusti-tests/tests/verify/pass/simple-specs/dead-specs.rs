@@ -0,0 +1,28 @@
+// compile-flags: -Pdetect_dead_specs=true
+
+use prusti_contracts::*;
+
+#[requires(x > 10)]
+#[requires(x > 5)]
+#[ensures(x < 5 ==> result == 0)]
+#[ensures(result == x + 1)]
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+#[requires(n < 100)]
+#[ensures(result == n)]
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        body_invariant!(i < n);
+        body_invariant!(i >= 0);
+        i += 1;
+    }
+    i
+}
+
+fn main() {
+    increment(11);
+    count(3);
+}
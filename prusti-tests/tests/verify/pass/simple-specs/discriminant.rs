@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+use std::mem::discriminant;
+
+enum State {
+    Idle,
+    Running(u32),
+    Done(u32),
+}
+
+#[pure]
+fn same_variant(a: &State, b: &State) -> bool {
+    discriminant(a) == discriminant(b)
+}
+
+#[requires(same_variant(a, b))]
+#[ensures(same_variant(b, a))]
+fn symmetric(a: &State, b: &State) {}
+
+#[requires(matches!(*a, State::Running(_)) && matches!(*b, State::Running(_)))]
+#[ensures(same_variant(a, b))]
+fn both_running(a: &State, b: &State) {}
+
+#[requires(matches!(*a, State::Idle) && matches!(*b, State::Done(_)))]
+#[ensures(discriminant(a) != discriminant(b))]
+fn different(a: &State, b: &State) {}
+
+#[requires(same_variant(state, other))]
+#[ensures(same_variant(state, other))]
+fn step(state: &mut State, other: &State) {
+    if let State::Running(steps) = state {
+        *steps = 0;
+    }
+}
+
+fn main() {}
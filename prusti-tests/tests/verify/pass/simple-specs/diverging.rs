@@ -0,0 +1,38 @@
+// compile-flags: -Pcheck_panics=false
+
+use prusti_contracts::*;
+
+fn diverge() -> ! {
+    loop {}
+}
+
+#[ensures(result > 0)]
+fn positive(x: i32) -> i32 {
+    if x > 0 {
+        x
+    } else {
+        panic!("not positive")
+    }
+}
+
+#[ensures(result == 1)]
+fn one(b: bool) -> i32 {
+    if b {
+        1
+    } else {
+        diverge()
+    }
+}
+
+#[ensures(match x {
+    Some(value) => result == value,
+    None => false,
+})]
+fn unwrap_or_unreachable(x: Option<u32>) -> u32 {
+    match x {
+        Some(value) => value,
+        None => unreachable!(),
+    }
+}
+
+fn main() {}
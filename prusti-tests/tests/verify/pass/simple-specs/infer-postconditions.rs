@@ -0,0 +1,45 @@
+// compile-flags: -Pinfer_postconditions=true
+
+use prusti_contracts::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Inferred: `result == Point { x: x, y: y }`.
+fn new_point(x: i32, y: i32) -> Point {
+    Point { x, y }
+}
+
+// Inferred: `result == p.x`.
+fn get_x(p: &Point) -> i32 {
+    p.x
+}
+
+// Inferred: `result == (a + b) * 2`.
+#[requires(a < 1000 && b < 1000)]
+fn double_sum(a: u32, b: u32) -> u32 {
+    let sum = a + b;
+    sum * 2
+}
+
+// Not inferred, because the function branches.
+fn max(a: i32, b: i32) -> i32 {
+    if a > b { a } else { b }
+}
+
+// Not inferred, because the function already has a postcondition.
+#[ensures(result == x)]
+fn identity(x: i32) -> i32 {
+    x
+}
+
+fn main() {
+    let p = new_point(1, 2);
+    let _ = get_x(&p);
+    let _ = max(1, 2);
+    let _ = identity(3);
+    let _ = double_sum(1, 2);
+}
@@ -0,0 +1,36 @@
+use prusti_contracts::*;
+
+enum Shape {
+    Circle(u32),
+    Square(u32),
+    Rectangle(u32, u32),
+    Empty,
+}
+
+#[ensures(result == match *shape {
+    Shape::Circle(size) | Shape::Square(size) => size,
+    Shape::Rectangle(width, height) => if width > height { width } else { height },
+    Shape::Empty => 0,
+})]
+fn extent(shape: &Shape) -> u32 {
+    match shape {
+        Shape::Circle(size) | Shape::Square(size) => *size,
+        Shape::Rectangle(width, height) if width > height => *width,
+        Shape::Rectangle(_, height) => *height,
+        Shape::Empty => 0,
+    }
+}
+
+#[ensures(result == match x {
+    Some(value) => value % 2 == 0 && value != 0,
+    None => false,
+})]
+fn is_positive_even(x: Option<u32>) -> bool {
+    match x {
+        Some(0) | None => false,
+        Some(value) if value % 2 == 0 => true,
+        Some(_) => false,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+pub struct Sensor {
+    value: i32,
+    reads: u32,
+}
+
+impl Sensor {
+    #[requires(read(self.value) && self.reads < u32::MAX)]
+    #[ensures(self.reads == old(self.reads) + 1)]
+    #[ensures(result == self.value)]
+    pub fn sample(&mut self) -> i32 {
+        self.reads += 1;
+        self.value
+    }
+}
+
+#[requires(read(*x))]
+fn inspect(x: &mut i32) -> bool {
+    *x > 0
+}
+
+fn main() {
+    let mut x = 5;
+    inspect(&mut x);
+    assert!(x == 5);
+
+    let mut sensor = Sensor { value: 3, reads: 0 };
+    sensor.sample();
+    assert!(sensor.value == 3 && sensor.reads == 1);
+}
@@ -0,0 +1,26 @@
+// compile-flags: -Pselective_verification=crate::target
+//
+// The program of the selected function is sliced to the definitions it uses,
+// which must keep the pure function and the contract of the called function.
+
+use prusti_contracts::*;
+
+#[pure]
+fn double(a: i32) -> i32 {
+    a + a
+}
+
+#[ensures(result == double(a))]
+fn twice(a: i32) -> i32 {
+    a + a
+}
+
+#[ensures(result == 2 * a)]
+fn target(a: i32) -> i32 {
+    twice(a)
+}
+
+#[ensures(false)]
+fn other() {}
+
+fn main() {}
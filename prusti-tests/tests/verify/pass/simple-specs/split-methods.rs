@@ -0,0 +1,36 @@
+// compile-flags: -Pmax_method_size=10
+//
+// The long method below is split into several Viper methods that are
+// verified independently; the facts established by the earlier parts must be
+// available in the later ones.
+
+use prusti_contracts::*;
+
+#[requires(a > 0)]
+#[ensures(result > 10)]
+fn long(a: i32, b: bool) -> i32 {
+    let mut x = a;
+    if b {
+        x = x + 1;
+    } else {
+        x = x + 2;
+    }
+    assert!(x > 1);
+    let mut y = x;
+    if x > 5 {
+        y = y + 3;
+    } else {
+        y = y + 4;
+    }
+    assert!(y > 4);
+    let mut z = y;
+    if b {
+        z = z + 5;
+    } else {
+        z = z + 6;
+    }
+    assert!(z > 9);
+    z + 1
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Pair(i32, i32);
+
+#[ensures(*p == Point { x: 0, ..old(*p) })]
+fn reset_x(p: &mut Point) {
+    p.x = 0;
+}
+
+#[ensures(result == Pair(b, a))]
+fn swapped(a: i32, b: i32) -> Pair {
+    Pair(b, a)
+}
+
+#[ensures(result == (p.y, p.x))]
+fn flip(p: &Point) -> (i32, i32) {
+    (p.y, p.x)
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// compile-flags: -Ptrace_encoding=true
+//
+// Recording the trace of the encoding does not change the verification
+// result.
+
+use prusti_contracts::*;
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[requires(p.x < 100)]
+#[ensures(p.x == old(p.x) + 1)]
+fn move_right(p: &mut Point) {
+    p.x += 1;
+}
+
+fn main() {
+    let mut p = Point { x: 0, y: 0 };
+    move_right(&mut p);
+    assert!(p.x == 1 && p.y == 0);
+    let q = p;
+    assert!(q.x == 1);
+}
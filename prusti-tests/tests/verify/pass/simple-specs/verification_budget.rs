@@ -0,0 +1,23 @@
+// compile-flags: -Puse_smt_wrapper=true
+use prusti_contracts::*;
+
+#[verification_budget(time = "60s", qi = 100_000)]
+#[requires(x < 1000)]
+#[ensures(result == x + 1)]
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+#[ensures(result == 6)]
+#[verification_budget(time = "1min")]
+fn test() -> u32 {
+    increment(5)
+}
+
+#[verification_budget(qi = 10_000)]
+#[ensures(result == 2 * x)]
+fn double(x: u8) -> u16 {
+    x as u16 + x as u16
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use prusti_contracts::*;
+
+#[verification_timeout(60000)]
+#[requires(x < 1000)]
+#[ensures(result == x + 1)]
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+#[ensures(result == 6)]
+#[verification_timeout(60000)]
+fn test() -> u32 {
+    increment(5)
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// compile-flags: -Pdump_vir_metrics=true
+//
+// Computing and dumping the metrics of the encoded programs must not disturb
+// the verification. The program below contains quantifiers, predicates, and
+// pure functions, so that all kinds of metrics are computed.
+
+use prusti_contracts::*;
+
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[pure]
+fn sum(p: &Point) -> u32 {
+    p.x / 2 + p.y / 2
+}
+
+#[requires(forall(|i: usize| i < 10 ==> i < 20))]
+#[ensures(sum(&result) == old(sum(&p)))]
+fn swap(p: Point) -> Point {
+    Point { x: p.y, y: p.x }
+}
+
+fn main() {}
@@ -0,0 +1,64 @@
+use prusti_contracts::*;
+use prusti_contracts::time_credits::{tick, TimeCredits};
+
+/// A counter whose increments are paid in advance: each `add` stores one
+/// credit, which pays for the `reset` later.
+struct Counter {
+    value: usize,
+    potential: TimeCredits,
+}
+
+impl Counter {
+    #[ensures(result.value() == 0)]
+    #[ensures(result.potential() == 0)]
+    fn new() -> Self {
+        Counter {
+            value: 0,
+            potential: TimeCredits::none(),
+        }
+    }
+
+    #[pure]
+    fn value(&self) -> usize {
+        self.value
+    }
+
+    #[pure]
+    fn potential(&self) -> usize {
+        self.potential.amount()
+    }
+
+    #[requires(self.value() < 1000 && self.potential() < 1000)]
+    #[requires(credits.amount() >= 2)]
+    #[ensures(credits.amount() == old(credits.amount()) - 2)]
+    #[ensures(self.value() == old(self.value()) + 1)]
+    #[ensures(self.potential() == old(self.potential()) + 1)]
+    fn add(&mut self, credits: &mut TimeCredits) {
+        tick(credits);
+        self.value += 1;
+        let saved = credits.split(1);
+        self.potential.join(saved);
+    }
+
+    #[requires(self.potential() >= 1)]
+    #[ensures(self.value() == 0)]
+    fn reset(&mut self) {
+        // The reset is paid by the stored credits.
+        tick(&mut self.potential);
+        self.value = 0;
+    }
+}
+
+#[requires(credits.amount() >= 4)]
+#[ensures(credits.amount() == old(credits.amount()) - 4)]
+fn add_twice_and_reset(credits: &mut TimeCredits) {
+    let mut counter = Counter::new();
+    counter.add(credits);
+    counter.add(credits);
+    counter.reset();
+}
+
+fn main() {
+    let mut credits = TimeCredits::acquire(4);
+    add_twice_and_reset(&mut credits);
+}
@@ -0,0 +1,35 @@
+// compile-flags: -Pcheck_ord_laws=true
+use prusti_contracts::*;
+use std::cmp::Ordering;
+
+#[derive(PartialEq, Eq, PartialOrd)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+
+#[refine_trait_spec]
+impl Ord for Version {
+    #[pure]
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.major < other.major {
+            Ordering::Less
+        } else if self.major > other.major {
+            Ordering::Greater
+        } else if self.minor < other.minor {
+            Ordering::Less
+        } else if self.minor > other.minor {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+fn first<T: Ord>(a: T, _b: T) -> T {
+    a
+}
+
+fn main() {
+    let v = first(Version { major: 1, minor: 2 }, Version { major: 1, minor: 3 });
+}
@@ -0,0 +1,35 @@
+// compile-flags: -Preport_trusted_calls=true
+
+use prusti_contracts::*;
+
+#[trusted]
+#[requires(x < 100)]
+#[ensures(result == x + 1)]
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+#[extern_spec]
+impl<T> std::option::Option<T> {
+    #[pure]
+    #[ensures(matches!(*self, Some(_)) == result)]
+    pub fn is_some(&self) -> bool;
+}
+
+#[trusted]
+fn trusted_caller(x: u32) -> u32 {
+    increment(increment(x))
+}
+
+#[requires(x < 10)]
+#[ensures(result == x + 2)]
+fn verified_caller(x: u32) -> u32 {
+    let y = increment(x);
+    increment(y)
+}
+
+fn main() {
+    let x = Some(verified_caller(1));
+    assert!(x.is_some());
+    trusted_caller(1);
+}
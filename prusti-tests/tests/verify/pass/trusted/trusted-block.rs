@@ -0,0 +1,68 @@
+use prusti_contracts::*;
+
+fn xor_swap(a: &mut i32, b: &mut i32) {
+    let (old_a, old_b) = (*a, *b);
+    trusted_block! {
+        #[ensures(*a == old_b && *b == old_a)]
+        {
+            *a ^= *b;
+            *b ^= *a;
+            *a ^= *b;
+        }
+    };
+    assert!(*a == old_b && *b == old_a);
+}
+
+#[requires(x < 1000)]
+#[ensures(result == x * x)]
+fn square(x: u32) -> u32 {
+    let y = trusted_block! {
+        #[requires(x < 1000)]
+        #[ensures(result == x * x)]
+        {
+            let mut acc = 0;
+            for _ in 0..x {
+                acc += x;
+            }
+            acc
+        }
+    };
+    y
+}
+
+struct Counter {
+    value: u32,
+}
+
+impl Counter {
+    fn increment(&mut self) {
+        if self.value < u32::MAX {
+            self.value += 1;
+        }
+    }
+}
+
+fn count_to(n: u32) -> u32 {
+    let mut counter = Counter { value: 0 };
+    let limit = n;
+    trusted_block! {
+        #[modifies(counter)]
+        #[ensures(counter.value == n)]
+        {
+            while counter.value < limit {
+                counter.increment();
+            }
+        }
+    };
+    assert!(limit == n);
+    counter.value
+}
+
+fn main() {
+    let mut a = 1;
+    let mut b = 2;
+    xor_swap(&mut a, &mut b);
+    assert!(square(3) == 9);
+    let n = count_to(5);
+    assert!(n == 5);
+}
@@ -0,0 +1,40 @@
+// compile-flags: -Pstub_unimplemented_functions=true -Preport_trusted_calls=true
+
+use prusti_contracts::*;
+
+struct Account {
+    balance: u64,
+}
+
+impl Account {
+    #[pure]
+    fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    #[requires(amount <= self.balance())]
+    #[ensures(self.balance() == old(self.balance()) - amount)]
+    fn withdraw(&mut self, amount: u64) {
+        todo!()
+    }
+}
+
+#[ensures(result > 0)]
+fn fetch_limit() -> u64 {
+    unimplemented!("the limits are not stored yet")
+}
+
+fn log_withdrawal(_amount: u64) {
+    todo!()
+}
+
+#[requires(account.balance() >= 100)]
+#[ensures(account.balance() == old(account.balance()) - 100)]
+fn withdraw_hundred(account: &mut Account) {
+    account.withdraw(100);
+    log_withdrawal(100);
+    let limit = fetch_limit();
+    assert!(limit > 0);
+}
+
+fn main() {}
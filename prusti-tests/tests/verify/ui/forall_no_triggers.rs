@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+#[pure]
+fn identity(x: u32) -> u32 { x }
+
+// The bound variable is mentioned only by built-in operations.
+#[requires(forall(|i: u32| i < 10 ==> i + 1 <= 10))]
+fn no_function_application() {}
+
+// `identity(i)` is a valid trigger.
+#[requires(forall(|i: u32| identity(i) == i))]
+fn inferable_trigger() {}
+
+// The bound variable does not occur in the body.
+#[requires(forall(|i: u32| true))]
+fn unused_variable() {}
+
+// The user-provided trigger is used.
+#[requires(forall(|i: u32| i < 10 ==> i + 1 <= 10, triggers=[(identity(i),)]))]
+fn user_trigger() {}
+
+fn main() {}
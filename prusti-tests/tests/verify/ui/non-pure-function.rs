@@ -16,8 +16,15 @@ fn pure_get_true() -> bool {
     true
 }
 
+fn get_true_indirectly() -> bool {
+    get_true()
+}
+
 #[requires(get_true())]
 fn foo() {}
 
+#[requires(get_true_indirectly())]
+fn bar() {}
+
 #[requires(pure_get_true() && !foo::get_false())]
 fn main() {}
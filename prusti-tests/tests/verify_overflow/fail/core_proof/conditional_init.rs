@@ -0,0 +1,42 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+
+struct T {
+    f: u32,
+}
+
+struct U {
+    t: T,
+    g: u32,
+}
+
+fn reinit(b: bool) {
+    let mut a = T { f: 4 };
+    if b {
+        let _c = a;
+    }
+    a = T { f: 5 };
+    assert!(a.f == 4);      //~ ERROR: the asserted expression might not hold
+}
+
+fn reinit_field(b: bool) {
+    let mut u = U { t: T { f: 1 }, g: 2 };
+    if b {
+        let _c = u.t;
+    }
+    u.t = T { f: 3 };
+    assert!(u.t.f == 1);    //~ ERROR: the asserted expression might not hold
+}
+
+fn init_in_branch(b: bool) {
+    let a;
+    if b {
+        a = T { f: 1 };
+    } else {
+        a = T { f: 2 };
+    }
+    assert!(a.f == 1);      //~ ERROR: the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+use std::alloc::{alloc, dealloc, Layout};
+
+fn use_after_free() {
+    unsafe {
+        let p = alloc(Layout::new::<u8>());
+        dealloc(p, Layout::new::<u8>());
+        std::ptr::write(p, 5);      //~ ERROR: the accessed heap memory might not be allocated
+    }
+}
+
+fn double_free() {
+    unsafe {
+        let p = alloc(Layout::new::<u8>());
+        dealloc(p, Layout::new::<u8>());
+        dealloc(p, Layout::new::<u8>());    //~ ERROR: the accessed heap memory might not be allocated
+    }
+}
+
+fn not_allocated(p: *mut u8) {
+    unsafe {
+        let _x = std::ptr::read(p);     //~ ERROR: the accessed heap memory might not be allocated
+    }
+}
+
+fn main() {}
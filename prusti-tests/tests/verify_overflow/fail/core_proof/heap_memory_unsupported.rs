@@ -0,0 +1,27 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+use std::alloc::{alloc, Layout};
+
+struct T {
+    f: u8,
+}
+
+fn read_into_field(p: *mut u8) {
+    let mut t = T { f: 0 };
+    unsafe {
+        t.f = std::ptr::read(p);    //~ ERROR: the result of a heap memory operation must be stored in a local variable
+    }
+}
+
+fn reassigned_layout(b: bool) {
+    let mut layout = Layout::new::<u8>();
+    if b {
+        layout = Layout::new::<u16>();
+    }
+    unsafe {
+        let _p = alloc(layout);     //~ ERROR: only layouts created by `Layout::new` directly in the call are supported when allocating memory
+    }
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+use prusti_std::is_initialized;
+use std::mem::MaybeUninit;
+
+fn read_uninit() -> u32 {
+    let value = MaybeUninit::<u32>::uninit();
+    unsafe { value.assume_init() } //~ ERROR: precondition might not hold
+}
+
+fn drop_twice() {
+    let mut value = MaybeUninit::new(1u32);
+    unsafe { value.assume_init_drop() };
+    unsafe { value.assume_init_drop() }; //~ ERROR: precondition might not hold
+}
+
+#[ensures(is_initialized(&result))] //~ ERROR: postcondition might not hold.
+fn forget_to_write() -> MaybeUninit<u32> {
+    MaybeUninit::uninit()
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+// compile-flags: -Punsafe_core_proof=true -Penable_core_proof_optimizations=true
+//
+// The optimizations of the core proof must not remove the checks that fail.
+
+use prusti_contracts::*;
+
+fn constant_assertion() {
+    let a = 1;
+    let b = 2;
+    assert!(a + b == 4);    //~ ERROR the asserted expression might not hold
+}
+
+fn overflow(a: u8) {
+    let _b = a + 100;   //~ ERROR assertion might fail with "attempt to add with overflow"
+}
+
+fn constant_branch() {
+    let a = 1;
+    if a > 0 {
+        assert!(a == 2);    //~ ERROR the asserted expression might not hold
+    }
+}
+
+fn main() {}
@@ -15,7 +15,7 @@ fn test1() {
 fn test2() {
     let a = MyUnion { f1: 1 };
     let _x = unsafe { a.f1 };
-    let _y = unsafe { a.f2 };   //~ ERROR: failed to obtain the required capability because a conflicting capability is present
+    let _y = unsafe { a.f2 };   //~ ERROR: failed to unpack the capability of union's field
 }
 
 fn test3() {
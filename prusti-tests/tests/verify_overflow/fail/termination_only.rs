@@ -0,0 +1,43 @@
+// compile-flags: -Punsafe_core_proof=true -Ptermination_only=true
+
+#![allow(unused)]
+
+use prusti_contracts::*;
+
+// The wrong postcondition and the possible overflow are assumed.
+#[terminates]
+#[ensures(result == 0)]
+fn sum(n: u32) -> u32 {
+    let mut i = 0;
+    let mut s = 0;
+    while i < n {
+        body_invariant!(i < n);
+        body_variant!(Int::new_usize((n - i) as usize));
+        s += i;
+        i += 1;
+    }
+    s
+}
+
+#[terminates]
+#[requires(x > 0)]
+fn callee(x: u32) {}
+
+#[terminates]
+fn precondition_is_assumed() {
+    callee(0);
+}
+
+#[terminates]
+fn diverges() {
+    loop { //~ ERROR: this loop might not terminate
+    }
+}
+
+#[terminates]
+fn recursion() {
+    recursion(); //~ ERROR
+}
+
+#[terminates]
+fn main() {}
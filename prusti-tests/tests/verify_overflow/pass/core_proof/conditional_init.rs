@@ -0,0 +1,43 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+
+struct T {
+    f: u32,
+}
+
+struct U {
+    t: T,
+    g: u32,
+}
+
+fn reinit(b: bool) {
+    let mut a = T { f: 4 };
+    if b {
+        let _c = a;
+    }
+    a = T { f: 5 };
+    assert!(a.f == 5);
+}
+
+fn reinit_field(b: bool) {
+    let mut u = U { t: T { f: 1 }, g: 2 };
+    if b {
+        let _c = u.t;
+    }
+    u.t = T { f: 3 };
+    assert!(u.g == 2);
+    assert!(u.t.f == 3);
+}
+
+fn init_in_branch(b: bool) {
+    let a;
+    if b {
+        a = T { f: 1 };
+    } else {
+        a = T { f: 2 };
+    }
+    assert!(a.f == 1 || a.f == 2);
+}
+
+fn main() {}
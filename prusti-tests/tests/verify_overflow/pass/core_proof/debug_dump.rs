@@ -0,0 +1,27 @@
+// compile-flags: -Punsafe_core_proof=true -Pdump_debug_info=true
+//
+// Dumping the debug information renders the fold-unfold state of each block
+// into a graphviz file, which must not disturb the verification.
+
+use prusti_contracts::*;
+
+struct T {
+    f: u32,
+}
+
+fn branch(b: bool) {
+    let mut a = T { f: 4 };
+    if b {
+        a.f = 5;
+    }
+    let _c = a;
+}
+
+fn moved(b: bool) {
+    let a = T { f: 4 };
+    if b {
+        let _c = a;
+    }
+}
+
+fn main() {}
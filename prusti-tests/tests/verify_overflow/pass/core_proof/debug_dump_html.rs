@@ -0,0 +1,20 @@
+// compile-flags: -Punsafe_core_proof=true -Pdump_debug_info=true -Pdump_graphs_as_html=true
+//
+// The procedure graphs can also be dumped as HTML pages, which must not
+// disturb the verification.
+
+use prusti_contracts::*;
+
+struct T {
+    f: u32,
+}
+
+fn branch(b: bool) {
+    let mut a = T { f: 4 };
+    if b {
+        a.f = 5;
+    }
+    let _c = a;
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+
+#[trusted]
+fn diverge() -> ! {
+    unimplemented!()
+}
+
+fn call_diverging(b: bool) -> u32 {
+    if b {
+        1
+    } else {
+        diverge()
+    }
+}
+
+fn unwrap_or_diverge(x: Option<u32>) -> u32 {
+    match x {
+        Some(value) => value,
+        None => diverge(),
+    }
+}
+
+fn main() {}
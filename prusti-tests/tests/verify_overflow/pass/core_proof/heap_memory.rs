@@ -0,0 +1,24 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+use std::alloc::{alloc, dealloc, Layout};
+
+fn write_read() {
+    unsafe {
+        let p = alloc(Layout::new::<u8>());
+        std::ptr::write(p, 5);
+        let _x = std::ptr::read(p);
+        dealloc(p, Layout::new::<u8>());
+    }
+}
+
+fn allocate_twice() {
+    unsafe {
+        let p = alloc(Layout::new::<u32>());
+        let q = alloc(Layout::new::<u32>());
+        dealloc(p, Layout::new::<u32>());
+        dealloc(q, Layout::new::<u32>());
+    }
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+use prusti_std::is_initialized;
+use std::mem::MaybeUninit;
+
+fn read_after_write() -> u32 {
+    let mut value = MaybeUninit::<u32>::uninit();
+    value.write(5);
+    unsafe { value.assume_init() }
+}
+
+fn read_new() -> u32 {
+    let value = MaybeUninit::new(3u32);
+    prusti_assert!(is_initialized(&value));
+    unsafe { value.assume_init() }
+}
+
+#[requires(is_initialized(value))]
+fn read_ref(value: &MaybeUninit<u32>) -> u32 {
+    unsafe { *value.assume_init_ref() }
+}
+
+fn write_drop_write() -> u32 {
+    let mut value = MaybeUninit::new(1u32);
+    unsafe { value.assume_init_drop() };
+    value.write(2);
+    read_ref(&value)
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+// compile-flags: -Punsafe_core_proof=true -Penable_core_proof_optimizations=true
+//
+// The optimizations of the core proof simplify the constant branches and
+// assertions below, which must not change the verification result.
+
+use prusti_contracts::*;
+
+fn constant_branch() -> u32 {
+    let a = 1;
+    let b = 2;
+    if a < b {
+        a + b
+    } else {
+        b - a
+    }
+}
+
+fn copies(x: u32) {
+    let a = x;
+    let b = a;
+    assert!(b == x);
+}
+
+fn small_arithmetic() {
+    let a: u8 = 100;
+    let b = a + 100;
+    assert!(b == 200);
+}
+
+fn main() {}
@@ -0,0 +1,51 @@
+// compile-flags: -Punsafe_core_proof=true
+//
+// The declarations of the types used by several procedures, and of types that
+// differ only in their lifetimes, are encoded once and reused.
+
+use prusti_contracts::*;
+
+struct Pair {
+    first: u32,
+    second: u32,
+}
+
+struct Wrapper<'a> {
+    pair: &'a mut Pair,
+}
+
+fn swap(pair: &mut Pair) {
+    let first = pair.first;
+    pair.first = pair.second;
+    pair.second = first;
+}
+
+fn swap_twice(pair: &mut Pair) {
+    swap(pair);
+    swap(pair);
+}
+
+fn new_pair() -> Pair {
+    Pair {
+        first: 1,
+        second: 2,
+    }
+}
+
+fn wrap<'a>(pair: &'a mut Pair) -> Wrapper<'a> {
+    Wrapper { pair }
+}
+
+fn use_wrappers<'a, 'b>(first: &'a mut Pair, second: &'b mut Pair) {
+    let first = wrap(first);
+    let second = wrap(second);
+    swap(first.pair);
+    swap(second.pair);
+}
+
+fn main() {
+    let mut pair = new_pair();
+    swap_twice(&mut pair);
+    let mut other = new_pair();
+    use_wrappers(&mut pair, &mut other);
+}
@@ -0,0 +1,18 @@
+// compile-flags: -Punsafe_core_proof=true -Penable_core_proof_optimizations=true -Pdump_vir_diff=fold_constants
+//
+// Dumping the difference of the procedures before and after a pass must not
+// disturb the verification.
+
+use prusti_contracts::*;
+
+fn constants() -> u32 {
+    let a = 1;
+    let b = 2;
+    if a < b {
+        a + b
+    } else {
+        b - a
+    }
+}
+
+fn main() {}
@@ -0,0 +1,50 @@
+use prusti_contracts::*;
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[ensures(^x == old(*x))]
+fn keep(x: &mut i32) {}
+
+#[requires(p.x < i32::MAX)]
+#[ensures(final(p.x) == old(p.x) + 1)]
+#[ensures(final(p.y) == old(p.y))]
+fn inc_x(p: &mut Point) {
+    p.x += 1;
+}
+
+#[ensures(final(p.x) == ^result)]
+#[ensures(final(p.y) == old(p.y))]
+fn borrow_x(p: &mut Point) -> &mut i32 {
+    &mut p.x
+}
+
+#[ensures(if old(*a >= *b) {
+        final(*a) == final(*result) && final(*b) == old(*b)
+    } else {
+        final(*a) == old(*a) && final(*b) == final(*result)
+    })]
+fn take_max<'a>(a: &'a mut i32, b: &'a mut i32) -> &'a mut i32 {
+    if *a >= *b { a } else { b }
+}
+
+fn main() {
+    let mut v = 3;
+    keep(&mut v);
+    assert!(v == 3);
+
+    let mut p = Point { x: 1, y: 2 };
+    inc_x(&mut p);
+    assert!(p.x == 2 && p.y == 2);
+    let r = borrow_x(&mut p);
+    *r = 5;
+    assert!(p.x == 5 && p.y == 2);
+
+    let mut a = 4;
+    let mut b = 7;
+    let m = take_max(&mut a, &mut b);
+    *m = 0;
+    assert!(a == 4 && b == 0);
+}
@@ -0,0 +1,20 @@
+// compile-flags: -Punsafe_core_proof=true -Penable_fold_unfold_join_optimization=true -Preport_fold_unfold_statistics=true
+
+use prusti_contracts::*;
+
+struct T {
+    f: u32,
+    g: u32,
+}
+
+fn test(n: u32) {
+    let mut a = T { f: 0, g: 0 };
+    let mut i = 0;
+    while i < n {
+        a.f = i;
+        i += 1;
+    }
+    let _b = a;
+}
+
+fn main() {}
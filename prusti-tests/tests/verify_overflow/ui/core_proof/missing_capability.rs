@@ -0,0 +1,16 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+
+struct T {
+    f: u32,
+}
+
+fn test1() {
+    let a = T { f: 1 };
+    let p = std::ptr::addr_of!(a);
+    let _b = a;
+    let _x = unsafe { (*p).f };
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+// compile-flags: -Punsafe_core_proof=true -Pdump_debug_info=true -Pdump_lifetimes_in_graphs=true
+//
+// When the fold-unfold inference fails, the graph of the procedure is dumped
+// with the lifetimes column, which must not change the reported error.
+
+use prusti_contracts::*;
+
+struct T {
+    f: u32,
+}
+
+fn test1() {
+    let a = T { f: 1 };
+    let p = std::ptr::addr_of!(a);
+    let _b = a;
+    let _x = unsafe { (*p).f };
+}
+
+fn main() {}
@@ -9,10 +9,15 @@ pub mod commandline;
 
 use self::commandline::CommandLine;
 use crate::launch::{find_viper_home, get_current_executable_dir};
-use ::config::{Config, Environment, File};
+use ::config::{Config, Environment, File, Source};
 use log::warn;
 use serde::Deserialize;
-use std::{collections::HashSet, env, path::PathBuf, sync::RwLock};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::PathBuf,
+    sync::RwLock,
+};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Optimizations {
@@ -88,6 +93,9 @@ lazy_static::lazy_static! {
         settings.set_default("cache_path", "").unwrap();
         settings.set_default("dump_debug_info", false).unwrap();
         settings.set_default("dump_debug_info_during_fold", false).unwrap();
+        settings.set_default("dump_debug_session", false).unwrap();
+        settings.set_default("dump_graphs_as_html", false).unwrap();
+        settings.set_default("dump_lifetimes_in_graphs", false).unwrap();
         settings.set_default("dump_nll_facts", false).unwrap();
         settings.set_default("ignore_regions", false).unwrap();
         settings.set_default("max_log_file_name_length", 60).unwrap();
@@ -95,9 +103,18 @@ lazy_static::lazy_static! {
         settings.set_default("dump_reborrowing_dag_in_debug_info", false).unwrap();
         settings.set_default("dump_borrowck_info", false).unwrap();
         settings.set_default("dump_viper_program", false).unwrap();
+        settings.set_default::<Option<String>>("dump_vir_diff", None).unwrap();
+        settings.set_default("trace_encoding", false).unwrap();
+        settings.set_default("dump_vir_metrics", false).unwrap();
+        settings.set_default("telemetry", false).unwrap();
+        settings.set_default::<Option<String>>("telemetry_dir", None).unwrap();
+        settings.set_default::<Option<String>>("telemetry_upload_command", None).unwrap();
+        settings.set_default("deduplicate_errors", true).unwrap();
         settings.set_default("foldunfold_state_filter", "").unwrap();
         settings.set_default::<Vec<String>>("extra_jvm_args", vec![]).unwrap();
         settings.set_default::<Vec<String>>("extra_verifier_args", vec![]).unwrap();
+        settings.set_default::<Option<i64>>("jvm_max_heap_size", None).unwrap();
+        settings.set_default::<Option<i64>>("jvm_stack_size", None).unwrap();
         settings.set_default("quiet", false).unwrap();
         settings.set_default("assert_timeout", 10_000).unwrap();
         settings.set_default("smt_qi_eager_threshold", 1000).unwrap();
@@ -107,13 +124,38 @@ lazy_static::lazy_static! {
         settings.set_default("allow_unreachable_unsupported_code", false).unwrap();
         settings.set_default("no_verify", false).unwrap();
         settings.set_default("no_verify_deps", false).unwrap();
+        settings.set_default::<Vec<String>>("no_verify_modules", vec![]).unwrap();
+        settings.set_default("memory_safety_only", false).unwrap();
+        settings.set_default::<Vec<String>>("backend_rules", vec![]).unwrap();
+        settings.set_default::<Option<String>>("selective_verification", None).unwrap();
+        settings.set_default::<Option<String>>("profile", None).unwrap();
         settings.set_default("full_compilation", false).unwrap();
         settings.set_default("json_communication", false).unwrap();
+        settings.set_default("server_max_retries", 5).unwrap();
+        settings.set_default("server_retry_delay", 100).unwrap();
+        settings.set_default::<Option<String>>("server_socket_path", None).unwrap();
+        settings.set_default("server_idle_timeout", 600).unwrap();
+        settings.set_default("server_session", false).unwrap();
+        settings.set_default::<Option<String>>("server_webhook_url", None).unwrap();
+        settings.set_default::<Option<String>>("server_client_token", None).unwrap();
+        settings.set_default("server_compression", true).unwrap();
+        settings.set_default("differentiated_exit_codes", true).unwrap();
+        settings.set_default::<Option<String>>("exit_code_file", None).unwrap();
+        settings.set_default::<Option<String>>("repro_dir", None).unwrap();
+        settings.set_default::<Option<String>>("verification_record_dir", None).unwrap();
+        settings.set_default::<Option<String>>("verification_record_key", None).unwrap();
         settings.set_default("optimizations", "all").unwrap();
         settings.set_default("intern_names", true).unwrap();
         settings.set_default("enable_purification_optimization", false).unwrap();
+        settings.set_default("enable_core_proof_optimizations", false).unwrap();
+        settings.set_default("enable_fold_unfold_join_optimization", true).unwrap();
+        settings.set_default("report_fold_unfold_statistics", false).unwrap();
+        settings.set_default("enable_fold_unfold_block_cache", true).unwrap();
+        settings.set_default("fold_unfold_cache_path", "").unwrap();
+        settings.set_default("encoding_cache_path", "").unwrap();
         // settings.set_default("enable_manual_axiomatization", false).unwrap();
         settings.set_default("unsafe_core_proof", false).unwrap();
+        settings.set_default("termination_only", false).unwrap();
         settings.set_default("verify_core_proof", true).unwrap();
         settings.set_default("verify_specifications", true).unwrap();
         settings.set_default("verify_types", false).unwrap();
@@ -121,15 +163,27 @@ lazy_static::lazy_static! {
         settings.set_default("verify_specifications_backend", "Silicon").unwrap();
         settings.set_default("use_eval_axioms", true).unwrap();
         settings.set_default("inline_caller_for", false).unwrap();
+        settings.set_default("remove_unused_definitions", false).unwrap();
         settings.set_default("check_no_drops", false).unwrap();
         settings.set_default("enable_type_invariants", false).unwrap();
         settings.set_default("use_new_encoder", true).unwrap();
         settings.set_default::<Option<u8>>("number_of_parallel_verifiers", None).unwrap();
+        settings.set_default("encoding_threads", 0).unwrap();
+        settings.set_default("max_method_size", 0).unwrap();
         settings.set_default::<Option<String>>("min_prusti_version", None).unwrap();
 
         settings.set_default("print_desugared_specs", false).unwrap();
         settings.set_default("print_typeckd_specs", false).unwrap();
         settings.set_default("print_collected_verification_items", false).unwrap();
+        settings.set_default("report_trusted_calls", false).unwrap();
+        settings.set_default("report_failing_paths", false).unwrap();
+        settings.set_default("report_verified_methods", false).unwrap();
+        settings.set_default("stub_unimplemented_functions", false).unwrap();
+        settings.set_default("check_const_fns", false).unwrap();
+        settings.set_default("suggest_annotations", false).unwrap();
+        settings.set_default("infer_postconditions", false).unwrap();
+        settings.set_default("detect_dead_specs", false).unwrap();
+        settings.set_default("check_ord_laws", false).unwrap();
         settings.set_default("hide_uuids", false).unwrap();
         settings.set_default("counterexample", false).unwrap();
         settings.set_default("print_counterexample_if_model_is_present", false).unwrap();
@@ -139,13 +193,17 @@ lazy_static::lazy_static! {
         settings.set_default("print_hash", false).unwrap();
         settings.set_default("enable_cache", true).unwrap();
         settings.set_default("enable_ghost_constraints", false).unwrap();
+        settings.set_default("fuzz_core_proof", 0).unwrap();
+        settings.set_default("fuzz_core_proof_seed", 0).unwrap();
 
         settings.set_default("cargo_path", "cargo").unwrap();
         settings.set_default("cargo_command", "check").unwrap();
 
         // Flags for testing.
         settings.set_default::<Option<i64>>("verification_deadline", None).unwrap();
+        settings.set_default::<Option<i64>>("verification_timeout", None).unwrap();
         settings.set_default("use_smt_wrapper", false).unwrap();
+        settings.set_default("reuse_verifier", false).unwrap();
         settings.set_default("smt_qi_ignore_builtin", true).unwrap();
         settings.set_default::<Option<u64>>("smt_qi_bound_global", None).unwrap();
         settings.set_default::<Option<u64>>("smt_qi_bound_global_kind", None).unwrap();
@@ -170,6 +228,7 @@ lazy_static::lazy_static! {
         let mut allowed_keys = get_keys(&settings);
         allowed_keys.insert("server_max_stored_verifiers".to_string());
         allowed_keys.insert("server_max_concurrency".to_string());
+        allowed_keys.insert("server_max_requests_per_client".to_string());
         allowed_keys.insert("server_address".to_string());
         allowed_keys.insert("config".to_string());
         allowed_keys.insert("log".to_string());
@@ -177,6 +236,7 @@ lazy_static::lazy_static! {
         allowed_keys.insert("rustc_log_args".to_string());
         allowed_keys.insert("rustc_log_env".to_string());
         allowed_keys.insert("original_smt_solver_path".to_string());
+        allowed_keys.insert("profiles".to_string());
 
         // TODO: reduce this to something more sensible:
         static MAX_CONFIG_LEN: usize = 40;
@@ -199,6 +259,15 @@ lazy_static::lazy_static! {
         settings.merge(File::from(file.as_path()).required(false)).unwrap();
         check_keys(&settings, &allowed_keys, &format!("{} file", file.to_string_lossy()));
 
+        // 2b. Override with the selected profile of the "Prusti.toml" file
+        if let Some(profile) = selected_profile(&settings) {
+            let source = format!("profile “{}” of {} file", profile, file.to_string_lossy());
+            if let Some(profile_settings) = get_profile(&settings, &profile, &source) {
+                check_keys(&profile_settings, &allowed_keys, &source);
+                settings.merge(profile_settings).unwrap();
+            }
+        }
+
         // 3. Override with env variables (`PRUSTI_VIPER_BACKEND`, ...)
         settings.merge(
             Environment::with_prefix("PRUSTI")
@@ -207,6 +276,7 @@ lazy_static::lazy_static! {
                 .with_list_parse_key("delete_basic_blocks")
                 .with_list_parse_key("extra_jvm_args")
                 .with_list_parse_key("extra_verifier_args")
+                .with_list_parse_key("no_verify_modules")
                 .with_list_parse_key("verify_only_basic_block_path")
                 .list_separator(" ")
         ).unwrap();
@@ -241,6 +311,45 @@ fn check_keys(settings: &Config, allowed_keys: &HashSet<String>, source: &str) {
     }
 }
 
+/// Determine the name of the selected profile. The profile can be selected
+/// with any of the usual ways to provide flags, but this has to be done before
+/// the environment variables and command-line arguments are merged, because
+/// they must have a higher priority than the settings of the profile.
+fn selected_profile(settings: &Config) -> Option<String> {
+    let from_command_line = CommandLine::with_prefix("-P")
+        .ignore_invalid(true)
+        .collect()
+        .ok()
+        .and_then(|args| args.get("profile").cloned())
+        .and_then(|value| value.into_string().ok());
+    let from_env = || env::var("PRUSTI_PROFILE").ok().filter(|value| !value.is_empty());
+    let from_settings = || settings.get::<Option<String>>("profile").ok().flatten();
+    from_command_line.or_else(from_env).or_else(from_settings)
+}
+
+/// Collect the settings of the given profile, which is defined in a
+/// `[profiles.<name>]` table of the `Prusti.toml` file. Returns `None` if the
+/// file doesn't define any profiles (e.g. in dependencies that don't use
+/// profiles), and panics if it defines profiles but not the selected one.
+fn get_profile(settings: &Config, profile: &str, source: &str) -> Option<Config> {
+    let profiles = settings.get_table("profiles").ok()?;
+    let profile_table = match profiles.get(profile) {
+        Some(value) => value.clone().into_table().unwrap_or_else(|error| {
+            panic!("The {source} is not a table: {error}")
+        }),
+        None => {
+            let mut available: Vec<_> = profiles.keys().collect();
+            available.sort();
+            panic!("Unknown {source}. Available profiles: {available:?}");
+        }
+    };
+    let mut profile_settings = Config::default();
+    for (key, value) in profile_table {
+        profile_settings.set(&key, value).unwrap();
+    }
+    Some(profile_settings)
+}
+
 /// Return vector of arguments filtered out by prefix
 pub fn get_filtered_args() -> Vec<String> {
     CommandLine::with_prefix("-P")
@@ -278,6 +387,59 @@ where
         .unwrap_or_else(|e| panic!("Failed to read setting {} due to {}", name, e))
 }
 
+/// The flags that a running `prusti-server` can change, with its `config`
+/// endpoint or by reloading its `Prusti.toml` file on `SIGHUP`.
+pub const RUNTIME_FLAGS: [&str; 4] = [
+    "dump_viper_program",
+    "enable_cache",
+    "log",
+    "reuse_verifier",
+];
+
+/// Changes the values of the given flags, which have to be in `RUNTIME_FLAGS`.
+pub fn update_runtime_settings(values: &HashMap<String, String>) -> Result<(), String> {
+    if let Some(key) = values.keys().find(|key| !RUNTIME_FLAGS.contains(&key.as_str())) {
+        return Err(format!(
+            "the configuration flag “{key}” cannot be changed at runtime"
+        ));
+    }
+    // The values are checked before any flag is changed, so that an invalid
+    // update leaves the settings unchanged. All runtime flags but `log` are
+    // booleans.
+    if let Some((key, value)) = values
+        .iter()
+        .find(|(key, value)| key.as_str() != "log" && value.parse::<bool>().is_err())
+    {
+        return Err(format!("invalid value “{value}” of flag “{key}”"));
+    }
+    let mut settings = SETTINGS.write().unwrap();
+    for (key, value) in values {
+        settings
+            .set(key, value.as_str())
+            .map_err(|error| format!("invalid value of flag “{key}”: {error}"))?;
+    }
+    Ok(())
+}
+
+/// Reloads the flags in `RUNTIME_FLAGS` from the `Prusti.toml` file.
+pub fn reload_runtime_settings() -> Result<(), String> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let file = PathBuf::from(manifest_dir).join("Prusti.toml");
+    let mut file_settings = Config::default();
+    file_settings
+        .merge(File::from(file.as_path()).required(false))
+        .map_err(|error| error.to_string())?;
+    let values = file_settings
+        .cache
+        .into_table()
+        .map_err(|error| error.to_string())?
+        .into_iter()
+        .filter(|(key, _)| RUNTIME_FLAGS.contains(&key.as_str()))
+        .map(|(key, value)| (key, value.to_string()))
+        .collect();
+    update_runtime_settings(&values)
+}
+
 fn write_setting<T: Into<config::Value>>(key: &'static str, value: T) {
     SETTINGS
         .write()
@@ -370,7 +532,8 @@ pub fn simplify_encoding() -> bool {
     read_setting("simplify_encoding")
 }
 
-/// When enabled, debug files will be created.
+/// When enabled, debug files will be created and the procedures encoded by
+/// the core proof are checked for well-formedness after each lowering stage.
 pub fn dump_debug_info() -> bool {
     read_setting("dump_debug_info")
 }
@@ -381,6 +544,78 @@ pub fn dump_debug_info_during_fold() -> bool {
     read_setting("dump_debug_info_during_fold")
 }
 
+/// When enabled, the verified programs, their verification results, and the
+/// fold-unfold states of their methods are dumped in the `debug_session` log
+/// directory, to be inspected with `prusti-server debug`.
+pub fn dump_debug_session() -> bool {
+    read_setting("dump_debug_session")
+}
+
+/// The name of a transformation pass on `vir_high` or `vir_mid` procedures
+/// (for example, `fold_constants`). If set, the structural difference
+/// between each procedure before and after the pass is written to the log
+/// directory.
+pub fn dump_vir_diff() -> Option<String> {
+    read_setting("dump_vir_diff")
+}
+
+/// When enabled, the encoding of each MIR statement of the verified
+/// procedures is recorded and written to the log directory: the produced VIR
+/// statements, the permissions they consume and produce, and the responsible
+/// part of the procedure encoder.
+pub fn trace_encoding() -> bool {
+    read_setting("trace_encoding")
+}
+
+/// When enabled, the size and complexity metrics (statements, quantifiers,
+/// predicate instances, domain axioms, ...) of each program sent to the
+/// verifier are logged and written as JSON to the log directory.
+pub fn dump_vir_metrics() -> bool {
+    read_setting("dump_vir_metrics")
+}
+
+/// When enabled, Prusti records the timings of its phases and the size
+/// metrics of the verified programs, and writes them to an anonymized JSON
+/// file at the end of each run.
+pub fn telemetry() -> bool {
+    read_setting("telemetry")
+}
+
+/// The directory to which the telemetry is written. If not set, the telemetry
+/// is written to the `telemetry` subdirectory of the log directory.
+pub fn telemetry_dir() -> Option<String> {
+    read_setting("telemetry_dir")
+}
+
+/// The command that is run with the path of each written telemetry file as
+/// its argument, for example to upload the file.
+pub fn telemetry_upload_command() -> Option<String> {
+    read_setting("telemetry_upload_command")
+}
+
+/// When enabled, the verification errors that are reported with the same
+/// message at the same location (e.g. for several instantiations of a generic
+/// function or several expansions of a macro) are reported only once, with
+/// notes listing where else they were reported.
+pub fn deduplicate_errors() -> bool {
+    read_setting("deduplicate_errors")
+}
+
+/// When enabled, the procedure graphs of the core proof encoding dumped by
+/// `dump_debug_info` are written as interactive HTML pages instead of `.dot`
+/// files.
+pub fn dump_graphs_as_html() -> bool {
+    read_setting("dump_graphs_as_html")
+}
+
+/// When enabled, the graphs dumped by the fold-unfold inference of the core
+/// proof show the lifetime and borrow information of each statement in an
+/// additional column and highlight the operations opening and closing
+/// borrow regions.
+pub fn dump_lifetimes_in_graphs() -> bool {
+    read_setting("dump_lifetimes_in_graphs")
+}
+
 /// When enabled, dumps Polonius nll-facts in the log directory.
 pub fn dump_nll_facts() -> bool {
     read_setting("dump_nll_facts")
@@ -413,7 +648,8 @@ pub fn dump_borrowck_info() -> bool {
     read_setting("dump_borrowck_info")
 }
 
-/// When enabled, the encoded Viper program will be output.
+/// When enabled, the encoded Viper program will be output together with a
+/// JSON file that maps its position ids to the Rust source code.
 pub fn dump_viper_program() -> bool {
     read_setting("dump_viper_program")
 }
@@ -470,6 +706,28 @@ pub fn extra_jvm_args() -> Vec<String> {
     read_setting("extra_jvm_args")
 }
 
+/// The maximum heap size (in MB) of the JVM that runs the verifiers. If not
+/// set, the heap size is chosen based on the available memory and on the size
+/// of the programs to verify.
+pub fn jvm_max_heap_size() -> Option<u64> {
+    read_setting::<Option<i64>>("jvm_max_heap_size").map(|value| {
+        value
+            .try_into()
+            .expect("jvm_max_heap_size must be a valid u64")
+    })
+}
+
+/// The stack size (in MB) of the threads of the JVM that runs the verifiers.
+/// If not set, the stack size is chosen based on the size of the programs to
+/// verify.
+pub fn jvm_stack_size() -> Option<u64> {
+    read_setting::<Option<i64>>("jvm_stack_size").map(|value| {
+        value
+            .try_into()
+            .expect("jvm_stack_size must be a valid u64")
+    })
+}
+
 /// Additional arguments to pass to the verifier backend.
 pub fn extra_verifier_args() -> Vec<String> {
     read_setting("extra_verifier_args")
@@ -515,6 +773,72 @@ pub fn print_collected_verification_items() -> bool {
     read_setting("print_collected_verification_items")
 }
 
+/// When enabled, each verification error reported by Silicon is annotated
+/// with the branches of the procedure that the failing path takes.
+pub fn report_failing_paths() -> bool {
+    read_setting("report_failing_paths")
+}
+
+/// When enabled, Prusti reports a warning for each call from a verified
+/// function into a `#[trusted]` function or a function with an external
+/// specification, pointing to the unverified contract that the call relies on.
+pub fn report_trusted_calls() -> bool {
+    read_setting("report_trusted_calls")
+}
+
+/// When enabled, Prusti prints a message as soon as the verifier finishes a
+/// method of a Viper program, also when the program is verified by a server.
+/// Only Silicon reports the verified methods.
+pub fn report_verified_methods() -> bool {
+    read_setting("report_verified_methods")
+}
+
+/// When enabled, the functions whose body is only a `todo!()` or
+/// `unimplemented!()` call are treated as trusted: their contracts are assumed
+/// at their call sites instead of being verified.
+pub fn stub_unimplemented_functions() -> bool {
+    read_setting("stub_unimplemented_functions")
+}
+
+/// When enabled, Prusti additionally verifies each `const fn` under the
+/// semantics of const evaluation: the absence of panics is checked even if
+/// `check_panics` is disabled, and floating-point arithmetic is rejected.
+pub fn check_const_fns() -> bool {
+    read_setting("check_const_fns")
+}
+
+/// When enabled, Prusti reports a warning for each verified function that has
+/// no side effects but is not marked as `#[pure]`, and for each function whose
+/// body uses features that Prusti does not support, suggesting to mark it as
+/// `#[trusted]`. The warnings are reported before the verification starts.
+pub fn suggest_annotations() -> bool {
+    read_setting("suggest_annotations")
+}
+
+/// When enabled, Prusti infers a postcondition for each small verified
+/// function without a contract whose result is computed from its arguments
+/// without branching or calling other functions, and reports it as a
+/// suggested `#[ensures]` clause.
+pub fn infer_postconditions() -> bool {
+    read_setting("infer_postconditions")
+}
+
+/// When enabled, Prusti checks that the `Ord` implementations used by verified
+/// functions through an `Ord` bound, for example when sorting, are total
+/// orders. Only the implementations annotated with `#[refine_trait_spec]`
+/// whose `cmp` is `#[pure]` can be checked.
+pub fn check_ord_laws() -> bool {
+    read_setting("check_ord_laws")
+}
+
+/// When enabled, Prusti warns about the preconditions implied by the other
+/// preconditions, the implications in postconditions whose guard is
+/// unsatisfiable, and the loop body invariants that hold trivially. Each of
+/// them is checked with a separate query to the verifier.
+pub fn detect_dead_specs() -> bool {
+    read_setting("detect_dead_specs")
+}
+
 /// When enabled, prints the AST with desugared specifications.
 pub fn print_desugared_specs() -> bool {
     read_setting("print_desugared_specs")
@@ -638,21 +962,95 @@ pub fn server_max_concurrency() -> Option<usize> {
     read_optional_setting("server_max_concurrency")
 }
 
+/// Maximum amount of verification requests of a single client that the
+/// server queues or verifies at the same time. Further requests of the client
+/// are rejected until one of its requests is verified. If not set, the
+/// requests of a client are not limited.
+pub fn server_max_requests_per_client() -> Option<usize> {
+    read_optional_setting("server_max_requests_per_client")
+}
+
+/// When enabled, verification requests are compressed with gzip before they
+/// are sent to the server, and the server is asked to compress its responses.
+pub fn server_compression() -> bool {
+    read_setting("server_compression")
+}
+
+/// The token that identifies this client to the server, which schedules the
+/// requests of different clients fairly. If not set, the server identifies
+/// the client by its IP address.
+pub fn server_client_token() -> Option<String> {
+    read_setting("server_client_token")
+}
+
 /// When set to an address and port (e.g. `"127.0.0.1:2468"`), Prusti will
 /// connect to the given server and use it for its verification backend.
+/// When set to `"unix:"` followed by a path, Prusti will connect to the
+/// server listening on that Unix socket.
 ///
 /// When set to `"MOCK"`, the server is run off-thread, effectively mocking
 /// connecting to a server without having to start it up separately.
+///
+/// When set to `"DAEMON"`, `prusti-rustc` connects to the server started
+/// with `prusti-server --daemon` on the Unix socket at `SERVER_SOCKET_PATH`,
+/// and starts that server if it is not running.
 pub fn server_address() -> Option<String> {
     read_optional_setting("server_address")
 }
 
+/// The path of the Unix socket on which `prusti-server --daemon` listens.
+/// Defaults to `prusti-server.sock` in the directory given by the
+/// `XDG_RUNTIME_DIR` environment variable, or in the temporary directory.
+pub fn server_socket_path() -> PathBuf {
+    read_setting::<Option<String>>("server_socket_path")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            env::var_os("XDG_RUNTIME_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(env::temp_dir)
+                .join("prusti-server.sock")
+        })
+}
+
+/// The time (in seconds) without verification requests after which a server
+/// started with `prusti-server --daemon` shuts down.
+pub fn server_idle_timeout() -> u64 {
+    read_setting("server_idle_timeout")
+}
+
+/// When enabled, the requests sent to the server omit the domains that the
+/// server already received during its session, such as those of the crates
+/// verified before by `cargo prusti --session`.
+pub fn server_session() -> bool {
+    read_setting("server_session")
+}
+
 /// When enabled, communication with the server will be encoded as JSON
 /// instead of the default bincode.
 pub fn json_communication() -> bool {
     read_setting("json_communication")
 }
 
+/// How many times a verification request is resubmitted to the server at
+/// `SERVER_ADDRESS` after a network failure, such as a refused or interrupted
+/// connection, before the verification fails.
+pub fn server_max_retries() -> u64 {
+    read_setting("server_max_retries")
+}
+
+/// The time (in milliseconds) to wait before resubmitting a verification
+/// request after the first network failure. The time is doubled after each
+/// further failure.
+pub fn server_retry_delay() -> u64 {
+    read_setting("server_retry_delay")
+}
+
+/// The URL to which the server sends a JSON summary of each verification
+/// request of this run once the request is verified.
+pub fn server_webhook_url() -> Option<String> {
+    read_setting("server_webhook_url")
+}
+
 /// When enabled, Viper name mangling will be disabled.
 ///
 /// **Note:** This is very likely to result in invalid programs being generated
@@ -734,6 +1132,49 @@ pub fn enable_purification_optimization() -> bool {
     read_setting("enable_purification_optimization")
 }
 
+/// When enabled, the `vir_mid` procedures of the core proof are simplified by
+/// constant folding, copy propagation, dead statement elimination, and branch
+/// simplification before they are lowered to `vir_low`.
+pub fn enable_core_proof_optimizations() -> bool {
+    read_setting("enable_core_proof_optimizations")
+}
+
+/// When enabled, the fold-unfold inference of the core proof tries to avoid
+/// conditional fold-unfold states by bringing the state of a block that jumps
+/// to a join point into the shape of the state already at the join point.
+/// Disabling this flag is useful for measuring the effect of the optimization
+/// on the size of the generated Viper program.
+pub fn enable_fold_unfold_join_optimization() -> bool {
+    read_setting("enable_fold_unfold_join_optimization")
+}
+
+/// When enabled, the fold-unfold inference of the core proof reuses the
+/// results of blocks that have the same statements and are entered with the
+/// same fold-unfold state as a block inferred earlier in the same run.
+pub fn enable_fold_unfold_block_cache() -> bool {
+    read_setting("enable_fold_unfold_block_cache")
+}
+
+/// When enabled, the core proof reports for each procedure the number of
+/// fold-unfold operations added by the fold-unfold inference.
+pub fn report_fold_unfold_statistics() -> bool {
+    read_setting("report_fold_unfold_statistics")
+}
+
+/// Path to a directory in which the results of the fold-unfold inference of
+/// the core proof are cached across runs. The default empty string disables
+/// the cache.
+pub fn fold_unfold_cache_path() -> PathBuf {
+    PathBuf::from(read_setting::<String>("fold_unfold_cache_path"))
+}
+
+/// Path to a directory in which the `vir_mid` encodings of the procedures
+/// verified with the core proof are cached across runs. The default empty
+/// string disables the cache.
+pub fn encoding_cache_path() -> PathBuf {
+    PathBuf::from(read_setting::<String>("encoding_cache_path"))
+}
+
 /// Deadline (in seconds) within which Prusti should encode and verify
 /// the program.
 ///
@@ -747,6 +1188,30 @@ pub fn verification_deadline() -> Option<u64> {
     })
 }
 
+/// Time budget (in milliseconds) for verifying the Viper program of a single
+/// procedure. Procedures whose verification exceeds the budget are reported as
+/// unknown, while the remaining procedures are still verified. The
+/// `#[verification_timeout(ms)]` attribute overrides this budget for a single
+/// procedure. Only supported by Silicon, which measures the budget in whole
+/// seconds.
+pub fn verification_timeout() -> Option<u64> {
+    read_setting::<Option<i64>>("verification_timeout").map(|value| {
+        value
+            .try_into()
+            .expect("verification_timeout must be a valid u64")
+    })
+}
+
+/// When enabled, the verifier backend is kept alive and reset between the
+/// verified programs instead of being created anew for each program. If the
+/// reused verifier fails with a Java exception, the program is verified again
+/// with a fresh verifier. Has no effect with `dump_debug_info` or
+/// `use_smt_wrapper`, which configure the verifier per program, and in the
+/// Prusti server.
+pub fn reuse_verifier() -> bool {
+    read_setting("reuse_verifier")
+}
+
 /// Instead of using Z3 directly, use our SMT wrapper that tracks important
 /// statistics. This must be set to `true` to use any of the
 /// `smt_qi_bound_*`.
@@ -865,6 +1330,21 @@ pub fn unsafe_core_proof() -> bool {
     read_setting("unsafe_core_proof")
 }
 
+/// When enabled, only the termination of loops and recursive calls is
+/// verified, while the functional specifications are assumed to hold. This
+/// must be used together with `unsafe_core_proof`, which encodes the
+/// termination checks.
+pub fn termination_only() -> bool {
+    let value = read_setting("termination_only");
+    if value {
+        assert!(
+            unsafe_core_proof(),
+            "unsafe_core_proof must be true to use termination_only"
+        );
+    }
+    value
+}
+
 /// Whether the core proof (memory safety) should be verified.
 ///
 /// **Note:** This option is taken into account only when `unsafe_core_proof` is
@@ -898,6 +1378,22 @@ pub fn verify_specifications_with_core_proof() -> bool {
     read_setting("verify_specifications_with_core_proof")
 }
 
+/// Rules of the form `path=backend` (e.g. `crate::hashing=carbon`) that select
+/// the verification backend of the functions at the path or in the module at
+/// the path, instead of `viper_backend`. The first matching rule applies. The
+/// `#[backend(..)]` attribute of a function takes precedence over the rules.
+pub fn backend_rules() -> Vec<(String, String)> {
+    read_setting::<Vec<String>>("backend_rules")
+        .into_iter()
+        .map(|rule| {
+            let (path, backend) = rule.split_once('=').unwrap_or_else(|| {
+                panic!("backend rule {:?} must have the form path=backend", rule)
+            });
+            (path.trim().to_string(), backend.trim().to_lowercase())
+        })
+        .collect()
+}
+
 /// Verification backend to use for functional specification only.
 pub fn verify_specifications_backend() -> String {
     read_setting::<String>("verify_specifications_backend")
@@ -919,6 +1415,13 @@ pub fn inline_caller_for() -> bool {
     read_setting("inline_caller_for")
 }
 
+/// When enabled, the functions, predicates, methods, and domains that are not
+/// transitively used by the verified procedure are removed from the programs
+/// generated by the core proof encoder, after they were encoded.
+pub fn remove_unused_definitions() -> bool {
+    read_setting("remove_unused_definitions")
+}
+
 /// When enabled, replaces calls to the drop function with `assert false`.
 ///
 /// **Note:** This option is used only for testing.
@@ -938,6 +1441,21 @@ pub fn use_new_encoder() -> bool {
     read_setting("use_new_encoder")
 }
 
+/// How many threads are used to post-process the encoded procedures (for
+/// example, to optimize them). `0` uses one thread per CPU core.
+pub fn encoding_threads() -> usize {
+    read_setting("encoding_threads")
+}
+
+/// The number of statements above which a Viper method is split into
+/// several methods that are verified independently. `0` disables splitting.
+///
+/// **Note:** a failing permission check may be reported several times when
+/// the method is split.
+pub fn max_method_size() -> usize {
+    read_setting("max_method_size")
+}
+
 /// How many parallel verifiers Silicon should use.
 pub fn number_of_parallel_verifiers() -> Option<u8> {
     read_setting("number_of_parallel_verifiers")
@@ -984,6 +1502,74 @@ pub fn no_verify_deps() -> bool {
     read_setting("no_verify_deps")
 }
 
+/// When enabled, Prusti exits with a different exit code for each kind of
+/// failure: `2` for verification errors, `3` for invalid specifications, `4`
+/// for unsupported features, `1` for other compilation errors and `101` for
+/// internal errors. Otherwise, it uses the exit codes of `rustc`.
+pub fn differentiated_exit_codes() -> bool {
+    read_setting("differentiated_exit_codes")
+}
+
+/// When set to a path, Prusti appends its exit code to the given file. This
+/// is used by `cargo prusti` to report the most severe exit code of all the
+/// verified crates, because `cargo` uses the same exit code for any failure.
+pub fn exit_code_file() -> Option<PathBuf> {
+    read_setting::<Option<String>>("exit_code_file").map(PathBuf::from)
+}
+
+/// When set, a reproduction bundle for bug reports (the source of the failing
+/// functions, the external specifications, the effective configuration and
+/// version information) is written to this directory. This flag is set by
+/// `cargo prusti --emit-repro`.
+pub fn repro_dir() -> Option<PathBuf> {
+    read_setting::<Option<String>>("repro_dir").map(PathBuf::from)
+}
+
+/// When set, Prusti writes a record of each successful verification of a
+/// crate to the file `<crate name>.prusti.json` in the given directory. The
+/// record contains the verified functions, the Prusti version, and hashes of
+/// the configuration and of the source files.
+pub fn verification_record_dir() -> Option<PathBuf> {
+    read_setting::<Option<String>>("verification_record_dir").map(PathBuf::from)
+}
+
+/// The path of a file with the secret key with which the records written to
+/// `verification_record_dir` are signed (HMAC-SHA256). Without a key, the
+/// records are not signed.
+pub fn verification_record_key() -> Option<PathBuf> {
+    read_setting::<Option<String>>("verification_record_key").map(PathBuf::from)
+}
+
+/// When enabled, the preconditions, postconditions and pledges written in the
+/// verified crate are ignored, and only the absence of panics (including overflows
+/// and out-of-bounds accesses) is verified. The Viper programs are much
+/// smaller, which makes this mode suitable for a quick first pass over a
+/// large crate.
+pub fn memory_safety_only() -> bool {
+    read_setting("memory_safety_only")
+}
+
+/// Paths of modules (e.g. `crate::legacy`) whose functions are not verified.
+/// Specifications of these functions are still used when verifying other
+/// functions.
+pub fn no_verify_modules() -> Vec<String> {
+    read_setting("no_verify_modules")
+}
+
+/// The path of the only function to verify (e.g. `crate::module::function`).
+/// When set, the programs sent to the verifier are also sliced to the
+/// functions and methods used by the verified function.
+pub fn selective_verification() -> Option<String> {
+    read_setting("selective_verification")
+}
+
+/// The name of the profile to use. A profile is a named set of flags defined
+/// in a `[profiles.<name>]` table of the `Prusti.toml` file, which overrides
+/// the flags defined at the top level of the file.
+pub fn profile() -> Option<String> {
+    read_setting("profile")
+}
+
 /// When enabled, compilation will continue and a binary will be generated
 /// after Prusti terminates.
 pub fn full_compilation() -> bool {
@@ -1007,6 +1593,20 @@ pub fn enable_ghost_constraints() -> bool {
     read_setting("enable_ghost_constraints")
 }
 
+/// The number of randomly generated procedures that are run through the
+/// lowerings of the core proof to find crashes of the encoder. The failing
+/// procedures are reported as internal errors and dumped to the log
+/// directory. 0 disables the fuzzing.
+pub fn fuzz_core_proof() -> u64 {
+    read_setting("fuzz_core_proof")
+}
+
+/// The seed of the first procedure generated by `fuzz_core_proof`; the
+/// following procedures use the subsequent seeds.
+pub fn fuzz_core_proof_seed() -> u64 {
+    read_setting("fuzz_core_proof_seed")
+}
+
 /// Determines which cargo `cargo-prusti` should run (e.g. if "cargo" isn't in
 /// the path can point to it directly). Not relevant when only running as `prusti=rustc`.
 pub fn cargo_path() -> String {
@@ -1024,3 +1624,35 @@ pub fn cargo_command() -> String {
 pub fn enable_type_invariants() -> bool {
     read_setting("enable_type_invariants")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn update_runtime_settings_checks_all_values_first() {
+        let original = dump_viper_program();
+        assert!(update_runtime_settings(&values(&[("viper_backend", "Carbon")])).is_err());
+        assert!(update_runtime_settings(&values(&[
+            ("dump_viper_program", if original { "false" } else { "true" }),
+            ("reuse_verifier", "maybe"),
+        ]))
+        .is_err());
+        assert_eq!(dump_viper_program(), original);
+
+        assert!(update_runtime_settings(&values(&[
+            ("dump_viper_program", if original { "false" } else { "true" }),
+            ("log", "prusti_server=debug"),
+        ]))
+        .is_ok());
+        assert_eq!(dump_viper_program(), !original);
+        assert_eq!(log(), "prusti_server=debug");
+    }
+}
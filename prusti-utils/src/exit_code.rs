@@ -0,0 +1,71 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Exit codes of the Prusti binaries, which allow scripts and IDEs to react
+//! to the outcome of a run without parsing the output.
+//!
+//! When a run reports several kinds of problems, the most severe one (i.e.
+//! the largest `ExitCode` variant) determines the exit code of the process.
+
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExitCode {
+    /// Everything was verified successfully.
+    #[default]
+    Success,
+    /// The verifier reported at least one verification error.
+    VerificationFailure,
+    /// The program uses a feature that is not supported by Prusti.
+    UnsupportedFeature,
+    /// A specification is invalid (e.g. it calls an impure function).
+    InvalidSpecification,
+    /// The Rust compiler reported an error.
+    CompilationError,
+    /// Prusti or the compiler crashed, or Prusti reported an internal error.
+    InternalError,
+}
+
+impl ExitCode {
+    /// The exit code of the process.
+    pub fn code(self) -> i32 {
+        match self {
+            ExitCode::Success => 0,
+            // The same exit code used by `rustc` for compilation errors.
+            ExitCode::CompilationError => 1,
+            ExitCode::VerificationFailure => 2,
+            ExitCode::InvalidSpecification => 3,
+            ExitCode::UnsupportedFeature => 4,
+            // The same exit code used by `rustc` for internal compiler errors.
+            ExitCode::InternalError => 101,
+        }
+    }
+
+    /// The inverse of `code`. Returns `None` for unknown exit codes.
+    pub fn from_code(code: i32) -> Option<Self> {
+        match code {
+            0 => Some(ExitCode::Success),
+            1 => Some(ExitCode::CompilationError),
+            2 => Some(ExitCode::VerificationFailure),
+            3 => Some(ExitCode::InvalidSpecification),
+            4 => Some(ExitCode::UnsupportedFeature),
+            101 => Some(ExitCode::InternalError),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ExitCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code: i32 = s
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid exit code: {s:?}"))?;
+        ExitCode::from_code(code).ok_or_else(|| format!("unknown exit code: {code}"))
+    }
+}
@@ -0,0 +1,187 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Minimization of a failing input of Prusti (`prusti-rustc --bisect`).
+//!
+//! The lines of the source file are removed with the delta debugging
+//! algorithm as long as Prusti still fails in the same way, i.e. with the same
+//! exit code and the same first error message. The minimized file is written
+//! next to the original one, so that the modules it declares are still found.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// How a run of Prusti failed.
+#[derive(Debug, PartialEq, Eq)]
+struct Failure {
+    exit_code: Option<i32>,
+    /// The first error message or panic, without its location.
+    message: Option<String>,
+}
+
+/// Minimizes `source_file` while `cmd`, which runs Prusti on it, keeps failing
+/// in the same way. Returns the path of the minimized file, or `None` if `cmd`
+/// does not fail.
+pub fn bisect(cmd: &Command, source_file: &Path) -> io::Result<Option<PathBuf>> {
+    let source = fs::read_to_string(source_file)?;
+    let stem = source_file
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("input");
+    // The candidate is named such that rustc derives a valid crate name.
+    let candidate_file = source_file.with_file_name(format!("{stem}_bisect.rs"));
+    let minimized_file = source_file.with_file_name(format!("{stem}.min.rs"));
+
+    let Some(expected) = run(cmd, source_file, source_file)? else {
+        return Ok(None);
+    };
+    eprintln!(
+        "Minimizing {} while Prusti fails with exit code {:?} and message {:?}",
+        source_file.display(),
+        expected.exit_code,
+        expected.message.as_deref().unwrap_or(""),
+    );
+    let lines: Vec<&str> = source.lines().collect();
+    let mut runs = 0;
+    let mut result = Ok(());
+    let minimized = ddmin(lines.clone(), |candidate| {
+        if result.is_err() {
+            return false;
+        }
+        runs += 1;
+        let outcome = fs::write(&candidate_file, candidate.join("\n"))
+            .and_then(|()| run(cmd, source_file, &candidate_file));
+        match outcome {
+            Ok(failure) => failure.as_ref() == Some(&expected),
+            Err(error) => {
+                result = Err(error);
+                false
+            }
+        }
+    });
+    fs::remove_file(&candidate_file).ok();
+    result?;
+    fs::write(&minimized_file, minimized.join("\n") + "\n")?;
+    eprintln!(
+        "Minimized {} from {} to {} lines in {} runs",
+        source_file.display(),
+        lines.len(),
+        minimized.len(),
+        runs,
+    );
+    Ok(Some(minimized_file))
+}
+
+/// Runs `cmd` with the `source_file` argument replaced by `input_file`.
+/// Returns `None` if the run succeeds.
+fn run(cmd: &Command, source_file: &Path, input_file: &Path) -> io::Result<Option<Failure>> {
+    let mut command = Command::new(cmd.get_program());
+    command.args(cmd.get_args().map(|arg| {
+        if Path::new(arg) == source_file {
+            input_file.as_os_str()
+        } else {
+            arg
+        }
+    }));
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => command.env(key, value),
+            None => command.env_remove(key),
+        };
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        command.current_dir(dir);
+    }
+    let output = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+    if output.status.success() {
+        return Ok(None);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr
+        .lines()
+        .find(|line| {
+            (line.starts_with("error") && !line.starts_with("error: aborting due to"))
+                || line.contains("panicked at")
+        })
+        .map(|line| line.trim().to_string());
+    Ok(Some(Failure {
+        exit_code: output.status.code(),
+        message,
+    }))
+}
+
+/// The delta debugging algorithm: removes chunks of `items`, halving their
+/// size when no chunk can be removed, as long as `is_failing` holds. The
+/// result is 1-minimal: removing any single item makes `is_failing` false.
+fn ddmin<T: Clone>(mut items: Vec<T>, mut is_failing: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut granularity = 2;
+    while items.len() >= 2 {
+        let chunk_size = (items.len() + granularity - 1) / granularity;
+        let mut reduced = false;
+        let mut start = 0;
+        while start < items.len() {
+            let end = (start + chunk_size).min(items.len());
+            let complement: Vec<T> = items[..start]
+                .iter()
+                .chain(&items[end..])
+                .cloned()
+                .collect();
+            if is_failing(&complement) {
+                items = complement;
+                granularity = (granularity - 1).max(2);
+                reduced = true;
+                break;
+            }
+            start = end;
+        }
+        if !reduced {
+            if granularity >= items.len() {
+                break;
+            }
+            granularity = (granularity * 2).min(items.len());
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ddmin;
+
+    #[test]
+    fn ddmin_finds_the_failing_items() {
+        let items: Vec<u32> = (0..20).collect();
+        let mut runs = 0;
+        let minimized = ddmin(items, |candidate| {
+            runs += 1;
+            candidate.contains(&3) && candidate.contains(&17)
+        });
+        assert_eq!(minimized, vec![3, 17]);
+        assert!(runs < 100);
+    }
+
+    #[test]
+    fn ddmin_keeps_the_order() {
+        let items = vec!["use", "fn a", "fn b", "fn main", "}"];
+        let minimized = ddmin(items, |candidate| {
+            let position = |item| candidate.iter().position(|x| *x == item);
+            matches!((position("fn b"), position("}")), (Some(b), Some(end)) if b < end)
+        });
+        assert_eq!(minimized, vec!["fn b", "}"]);
+    }
+
+    #[test]
+    fn ddmin_of_a_single_item() {
+        assert_eq!(ddmin(vec![1], |_| true), vec![1]);
+        assert_eq!(ddmin(vec![1, 2], |_| true), vec![2]);
+    }
+}
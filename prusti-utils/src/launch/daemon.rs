@@ -0,0 +1,56 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Automatic start of the server that `prusti-server --daemon` runs on a Unix
+//! socket, used when the `server_address` flag is `"DAEMON"`.
+
+use std::{
+    io,
+    os::unix::net::UnixStream,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long to wait for a started server to listen on its socket.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Starts `prusti-server --daemon` on `socket_path`, unless a server already
+/// listens on it, and waits until the server accepts connections.
+pub fn ensure_daemon_server(prusti_home: &Path, socket_path: &Path) -> io::Result<()> {
+    if UnixStream::connect(socket_path).is_ok() {
+        return Ok(());
+    }
+    // The socket of a server that did not shut down cleanly is left behind.
+    if socket_path.exists() {
+        std::fs::remove_file(socket_path)?;
+    }
+    // The server puts itself in its own process group, so it survives the
+    // termination of the process group of `prusti-rustc`.
+    Command::new(prusti_home.join("prusti-server"))
+        .arg("--daemon")
+        .arg("--socket")
+        .arg(socket_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let start = Instant::now();
+    while UnixStream::connect(socket_path).is_err() {
+        if start.elapsed() > STARTUP_TIMEOUT {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "the server did not start listening on {}",
+                    socket_path.display()
+                ),
+            ));
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
@@ -6,6 +6,10 @@
 
 #![deny(unused_must_use)]
 
+pub mod bisect;
+#[cfg(target_family = "unix")]
+pub mod daemon;
+
 #[cfg(target_family = "unix")]
 use nix::{
     sys::signal::{killpg, Signal},
@@ -9,9 +9,11 @@
 #![deny(unused_must_use)]
 
 pub mod config;
+pub mod exit_code;
 pub mod launch;
 pub mod report;
 mod stopwatch;
+pub mod telemetry;
 pub mod utils;
 
 pub use stopwatch::Stopwatch;
@@ -4,6 +4,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use crate::telemetry;
 use std::{
     fmt::Display,
     marker::PhantomData,
@@ -17,6 +18,7 @@ pub trait LogLevel {
 
 pub struct Stopwatch<Level: LogLevel> {
     start_time: Instant,
+    domain: String,
     prefix: String,
     section_name: String,
     is_finished: bool,
@@ -31,11 +33,13 @@ impl Stopwatch<log_level::Info> {
 }
 
 impl<Level: LogLevel> Stopwatch<Level> {
-    fn _start(prefix: String, section_name: String) -> Self {
+    fn _start(domain: String, section_name: String) -> Self {
+        let prefix = format!("[{}] ", domain);
         Level::log_start(&prefix, &section_name);
 
         Self {
             start_time: Instant::now(),
+            domain,
             prefix,
             section_name,
             is_finished: false,
@@ -46,11 +50,9 @@ impl<Level: LogLevel> Stopwatch<Level> {
     /// Finishes up the current section, logging the time taken, and starts timing the next one.
     pub fn start_next<S: ToString>(&mut self, section_name: S) {
         let now = Instant::now();
-        Level::log_finish(
-            &self.prefix,
-            &self.section_name,
-            now.duration_since(self.start_time),
-        );
+        let duration = now.duration_since(self.start_time);
+        Level::log_finish(&self.prefix, &self.section_name, duration);
+        telemetry::record_phase(&self.domain, &self.section_name, duration);
         self.section_name = section_name.to_string();
         Level::log_start(&self.prefix, &self.section_name);
         self.start_time = now;
@@ -66,6 +68,7 @@ impl<Level: LogLevel> Stopwatch<Level> {
     fn _finish(&mut self) -> Duration {
         let duration = self.start_time.elapsed();
         Level::log_finish(&self.prefix, &self.section_name, duration);
+        telemetry::record_phase(&self.domain, &self.section_name, duration);
         self.is_finished = true;
         duration
     }
@@ -107,7 +110,7 @@ pub mod log_level {
             impl Stopwatch<$name> {
                 /// Starts a stopwatch logging at this level, within the given domain, timing a section with the given name.
                 pub fn $start<D: Display, S: ToString>(domain: D, section_name: S) -> Self {
-                    Self::_start(format!("[{}] ", domain), section_name.to_string())
+                    Self::_start(domain.to_string(), section_name.to_string())
                 }
             }
         };
@@ -0,0 +1,143 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The opt-in collection of performance telemetry (the `telemetry` flag). The
+//! timings of the phases measured by stopwatches and the size metrics of the
+//! verified programs are collected during a run and written to a JSON file at
+//! its end. The report is anonymized: it contains neither the names of the
+//! verified items nor any paths.
+
+use crate::config;
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+lazy_static::lazy_static! {
+    static ref TELEMETRY: Mutex<Telemetry> = Mutex::new(Telemetry::default());
+}
+
+#[derive(Default)]
+struct Telemetry {
+    /// The timings of the phases, by the domain and the name of the phase.
+    phases: BTreeMap<(String, String), PhaseTimings>,
+    programs: Vec<serde_json::Value>,
+}
+
+/// The aggregated timings of all runs of a phase.
+#[derive(Debug, Default, Clone, Serialize)]
+struct PhaseTimings {
+    count: usize,
+    total_ms: u128,
+    max_ms: u128,
+}
+
+#[derive(Serialize)]
+struct PhaseReport<'a> {
+    domain: &'a str,
+    phase: &'a str,
+    #[serde(flatten)]
+    timings: &'a PhaseTimings,
+}
+
+#[derive(Serialize)]
+struct TelemetryReport<'a> {
+    /// The seconds since the Unix epoch at the time the report is written.
+    timestamp: u64,
+    viper_backend: String,
+    unsafe_core_proof: bool,
+    phases: Vec<PhaseReport<'a>>,
+    programs: &'a [serde_json::Value],
+}
+
+/// Records the duration of a phase measured by a stopwatch.
+pub fn record_phase(domain: &str, phase: &str, duration: Duration) {
+    if !config::telemetry() {
+        return;
+    }
+    let mut telemetry = TELEMETRY.lock().unwrap();
+    let timings = telemetry
+        .phases
+        .entry((domain.to_string(), phase.to_string()))
+        .or_default();
+    let duration_ms = duration.as_millis();
+    timings.count += 1;
+    timings.total_ms += duration_ms;
+    timings.max_ms = timings.max_ms.max(duration_ms);
+}
+
+/// Records the size metrics of a verified program. The metrics must not
+/// contain the name of the program.
+pub fn record_program<M: Serialize>(metrics: &M) {
+    if !config::telemetry() {
+        return;
+    }
+    match serde_json::to_value(metrics) {
+        Ok(metrics) => TELEMETRY.lock().unwrap().programs.push(metrics),
+        Err(error) => warn!("Failed to record the program metrics: {}", error),
+    }
+}
+
+/// Writes the collected telemetry of this run to a new file in the telemetry
+/// directory, and passes the file to the `telemetry_upload_command`, if any.
+/// Failures are logged, but do not affect the run.
+pub fn write_report() {
+    if !config::telemetry() {
+        return;
+    }
+    let telemetry = TELEMETRY.lock().unwrap();
+    let report = TelemetryReport {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+        viper_backend: config::viper_backend(),
+        unsafe_core_proof: config::unsafe_core_proof(),
+        phases: telemetry
+            .phases
+            .iter()
+            .map(|((domain, phase), timings)| PhaseReport {
+                domain,
+                phase,
+                timings,
+            })
+            .collect(),
+        programs: &telemetry.programs,
+    };
+    let directory = config::telemetry_dir()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config::log_dir().join("telemetry"));
+    let path = directory.join(format!("{}.json", uuid::Uuid::new_v4()));
+    let written = fs::create_dir_all(&directory)
+        .map_err(|error| error.to_string())
+        .and_then(|()| serde_json::to_string_pretty(&report).map_err(|error| error.to_string()))
+        .and_then(|json| fs::write(&path, json).map_err(|error| error.to_string()));
+    if let Err(error) = written {
+        warn!("Failed to write the telemetry to {:?}: {}", path, error);
+        return;
+    }
+    info!("Wrote the telemetry to {:?}", path);
+    if let Some(command) = config::telemetry_upload_command() {
+        upload_report(&command, &path);
+    }
+}
+
+/// Starts the upload command without waiting for it, so that a slow upload
+/// does not delay the run.
+fn upload_report(command: &str, path: &Path) {
+    if let Err(error) = Command::new(command).arg(path).spawn() {
+        warn!(
+            "Failed to start the telemetry upload command {:?}: {}",
+            command, error
+        );
+    }
+}
@@ -49,6 +49,14 @@ pub enum BuiltinFunctionKind {
         slice_pred_type: vir::Type,
         elem_pred_type: vir::Type,
     },
+    /// abstract value of a constant that depends on type parameters
+    Constant {
+        name: String,
+        type_arguments: Vec<vir::Type>,
+        return_ty: vir::Type,
+        /// bounds of the value, in terms of `__result`
+        posts: Vec<vir::Expr>,
+    },
 }
 
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
@@ -221,6 +229,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> BuiltinEncoder<'p, 'v, 'tcx> {
                     body: None,
                 }
             },
+            BuiltinFunctionKind::Constant { return_ty, posts, .. } => vir::Function {
+                name: fn_name,
+                type_arguments,
+                formal_args: vec![],
+                return_type: return_ty,
+                pres: vec![],
+                posts,
+                body: None,
+            },
         }
     }
 
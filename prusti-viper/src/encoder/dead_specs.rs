@@ -0,0 +1,187 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The detection of dead specifications (the `detect_dead_specs` flag).
+//!
+//! For each candidate specification, the procedure encoder builds a query: a
+//! Viper method that asserts a fact which holds only if the specification is
+//! dead. For example, the query of a precondition inhales the other
+//! preconditions and asserts this one. The queries of a procedure are
+//! verified in a separate program, and a specification is reported only if
+//! the verifier proves the assertion of its query.
+
+use prusti_interface::PrustiError;
+use prusti_rustc_interface::span::Span;
+use rustc_hash::FxHashSet;
+use viper::VerificationResult;
+use vir_crate::polymorphic::{self as vir, ExprWalker};
+
+/// The suffix of the names of the programs that contain the queries.
+pub const DEAD_SPECS_PROGRAM_SUFFIX: &str = "$dead_specs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadSpecKind {
+    /// A precondition implied by the other preconditions.
+    ImpliedPrecondition,
+    /// An implication in a postcondition whose guard cannot hold.
+    UnsatisfiableGuard,
+    /// A loop body invariant that holds without any assumption.
+    TrivialLoopInvariant,
+}
+
+/// The method that checks whether a specification is dead.
+pub(super) struct DeadSpecQuery {
+    pub(super) kind: DeadSpecKind,
+    pub(super) span: Span,
+    /// The position of the assertion that holds if the specification is dead.
+    pub(super) position: vir::Position,
+    pub(super) method: vir::CfgMethod,
+}
+
+/// The specifications checked by the queries of a program.
+pub struct DeadSpecProgram {
+    specs: Vec<(DeadSpecKind, Span, vir::Position)>,
+}
+
+impl DeadSpecKind {
+    pub fn to_warning(self, span: Span) -> PrustiError {
+        let (message, help) = match self {
+            DeadSpecKind::ImpliedPrecondition => (
+                "this precondition is implied by the other preconditions",
+                "the precondition can be removed without changing the contract",
+            ),
+            DeadSpecKind::UnsatisfiableGuard => (
+                "a branch of this postcondition is dead because its condition never holds",
+                "the condition contradicts the precondition, so the guarded \
+                    postcondition never constrains the result",
+            ),
+            DeadSpecKind::TrivialLoopInvariant => (
+                "this loop invariant holds trivially",
+                "the invariant does not depend on the state of the loop and can be removed",
+            ),
+        };
+        PrustiError::warning(message, span.into()).set_help(help)
+    }
+}
+
+impl DeadSpecProgram {
+    pub(super) fn new(queries: &[DeadSpecQuery]) -> Self {
+        DeadSpecProgram {
+            specs: queries
+                .iter()
+                .map(|query| (query.kind, query.span, query.position))
+                .collect(),
+        }
+    }
+
+    /// The specifications that the `result` of the program proves dead. If the
+    /// verifier reports an error that does not belong to a query, nothing is
+    /// reported because the result cannot be trusted.
+    pub fn dead_specs(&self, result: &VerificationResult) -> Vec<(DeadSpecKind, Span)> {
+        let failed_positions: FxHashSet<u64> = match result {
+            VerificationResult::Success => FxHashSet::default(),
+            VerificationResult::Failure(errors) => {
+                let positions: Option<FxHashSet<u64>> = errors
+                    .iter()
+                    .map(|error| error.offending_pos_id.as_ref()?.parse().ok())
+                    .collect();
+                match positions {
+                    Some(positions) => positions,
+                    None => return Vec::new(),
+                }
+            }
+            _ => return Vec::new(),
+        };
+        let query_positions: FxHashSet<u64> = self
+            .specs
+            .iter()
+            .map(|(_, _, position)| position.id())
+            .collect();
+        if !failed_positions.is_subset(&query_positions) {
+            return Vec::new();
+        }
+        self.specs
+            .iter()
+            .filter(|(_, _, position)| !failed_positions.contains(&position.id()))
+            .map(|&(kind, span, _)| (kind, span))
+            .collect()
+    }
+}
+
+/// The guards of the implications at the top level of a postcondition. The
+/// implication `a ==> b` is desugared into `!a || b`, which is encoded as a
+/// conditional expression with a `true` branch.
+pub(super) fn implication_guards(expr: &vir::Expr) -> Vec<vir::Expr> {
+    fn is_true(expr: &vir::Expr) -> bool {
+        matches!(
+            expr,
+            vir::Expr::Const(vir::ConstExpr {
+                value: vir::Const::Bool(true),
+                ..
+            })
+        )
+    }
+    match expr {
+        vir::Expr::BinOp(vir::BinOp {
+            op_kind: vir::BinaryOpKind::And,
+            box left,
+            box right,
+            ..
+        }) => {
+            let mut guards = implication_guards(left);
+            guards.extend(implication_guards(right));
+            guards
+        }
+        vir::Expr::BinOp(vir::BinOp {
+            op_kind: vir::BinaryOpKind::Implies,
+            box left,
+            ..
+        }) => vec![left.clone()],
+        vir::Expr::Cond(vir::Cond {
+            box guard,
+            box then_expr,
+            box else_expr,
+            ..
+        }) => {
+            if is_true(else_expr) && !is_true(then_expr) {
+                vec![guard.clone()]
+            } else if is_true(then_expr) && !is_true(else_expr) {
+                vec![vir::Expr::not(guard.clone())]
+            } else {
+                Vec::new()
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Whether all the old expressions in `expr` refer to one of the `labels`
+/// defined by its query.
+pub(super) fn uses_only_labels(expr: &vir::Expr, labels: &[&str]) -> bool {
+    struct LabelChecker<'a> {
+        labels: &'a [&'a str],
+        uses_other_labels: bool,
+    }
+    impl<'a> ExprWalker for LabelChecker<'a> {
+        fn walk_labelled_old(
+            &mut self,
+            vir::LabelledOld {
+                label, box base, ..
+            }: &vir::LabelledOld,
+        ) {
+            if !self.labels.contains(&label.as_str()) {
+                self.uses_other_labels = true;
+            }
+            self.walk(base);
+        }
+    }
+    let mut checker = LabelChecker {
+        labels,
+        uses_other_labels: false,
+    };
+    checker.walk(expr);
+    !checker.uses_other_labels
+}
@@ -9,6 +9,8 @@ use prusti_common::utils::identifiers::encode_identifier;
 use vir_crate::common::check_mode::CheckMode;
 use crate::encoder::builtin_encoder::BuiltinEncoder;
 use crate::encoder::builtin_encoder::BuiltinMethodKind;
+use crate::encoder::builtin_encoder::BuiltinFunctionKind;
+use crate::encoder::dead_specs::{DeadSpecProgram, DeadSpecQuery, DEAD_SPECS_PROGRAM_SUFFIX};
 use crate::encoder::errors::{ErrorManager, SpannedEncodingError, EncodingError};
 use crate::encoder::foldunfold;
 use crate::encoder::procedure_encoder::ProcedureEncoder;
@@ -25,6 +27,7 @@ use vir_crate::common::identifier::WithIdentifier;
 use prusti_rustc_interface::hir::def_id::DefId;
 use prusti_rustc_interface::middle::mir;
 use prusti_rustc_interface::middle::ty;
+use prusti_rustc_interface::span::Span;
 use std::cell::{Cell, RefCell, RefMut, Ref};
 use rustc_hash::{FxHashSet, FxHashMap};
 use std::io::Write;
@@ -39,7 +42,9 @@ use crate::encoder::mirror_function_encoder::MirrorEncoder;
 use crate::encoder::snapshot::interface::{SnapshotEncoderInterface, SnapshotEncoderState};
 use crate::encoder::purifier;
 use super::builtin_encoder::BuiltinDomainKind;
-use super::high::builtin_functions::HighBuiltinFunctionEncoderState;
+use super::high::builtin_functions::{HighBuiltinFunctionEncoderState, HighBuiltinFunctionEncoderInterface};
+use super::high::generics::HighGenericsEncoderInterface;
+use super::high::procedures::FoldUnfoldInferenceState;
 use super::middle::core_proof::{MidCoreProofEncoderState, MidCoreProofEncoderInterface};
 use super::mir::{
     sequences::{
@@ -75,10 +80,16 @@ pub struct Encoder<'v, 'tcx: 'v> {
     pub(super) high_builtin_function_encoder_state: HighBuiltinFunctionEncoderState,
     procedures: RefCell<FxHashMap<ProcedureDefId, vir::CfgMethod>>,
     programs: Vec<vir::Program>,
+    /// The queries of the `detect_dead_specs` flag, by the checked procedures.
+    dead_spec_queries: RefCell<FxHashMap<ProcedureDefId, Vec<DeadSpecQuery>>>,
+    /// The specifications checked by the programs of dead specification
+    /// queries, by the names of the programs.
+    dead_spec_programs: FxHashMap<String, DeadSpecProgram>,
     pub(super) mir_sequences_encoder_state: MirSequencesEncoderState<'tcx>,
     pub(super) contracts_encoder_state: ContractsEncoderState<'tcx>,
     pub(super) mir_procedure_encoder_state: MirProcedureEncoderState,
     pub(super) mid_core_proof_encoder_state: MidCoreProofEncoderState,
+    pub(super) fold_unfold_inference_state: FoldUnfoldInferenceState,
     pub(super) mir_type_encoder_state: MirTypeEncoderState<'tcx>,
     pub(super) type_invariant_encoder_state: TypeInvariantEncoderState<'tcx>,
     pub(super) high_type_encoder_state: HighTypeEncoderState<'tcx>,
@@ -103,6 +114,8 @@ pub struct Encoder<'v, 'tcx: 'v> {
     /// this requires special care when encoding array/slice accesses which may come with
     /// bound checks included in the MIR.
     pub(super) is_encoding_trigger: Cell<bool>,
+    /// The spans of the warnings emitted by `emit_warning_once`.
+    emitted_warnings: RefCell<FxHashSet<Span>>,
 }
 
 pub enum EncodingTask<'tcx> {
@@ -150,16 +163,22 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
 
         Encoder {
             env,
-            error_manager: RefCell::new(ErrorManager::new(env.query.codemap())),
+            error_manager: RefCell::new(ErrorManager::new(env.query.codemap(), {
+                let name = env.name;
+                move |def_id| name.get_unique_item_name(def_id)
+            })),
             functions: RefCell::new(FxHashMap::default()),
             builtin_domains: RefCell::new(FxHashMap::default()),
             builtin_domains_in_progress: RefCell::new(FxHashSet::default()),
             builtin_methods: RefCell::new(FxHashMap::default()),
             high_builtin_function_encoder_state: Default::default(),
             programs: Vec::new(),
+            dead_spec_queries: RefCell::new(FxHashMap::default()),
+            dead_spec_programs: FxHashMap::default(),
             mir_sequences_encoder_state: Default::default(),
             mir_procedure_encoder_state: Default::default(),
             mid_core_proof_encoder_state: Default::default(),
+            fold_unfold_inference_state: Default::default(),
             procedures: RefCell::new(FxHashMap::default()),
             contracts_encoder_state: Default::default(),
             mir_type_encoder_state: Default::default(),
@@ -178,6 +197,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             encoding_errors_counter: RefCell::new(0),
             name_interner: RefCell::new(NameInterner::new()),
             is_encoding_trigger: Cell::new(false),
+            emitted_warnings: Default::default(),
             specifications_state: SpecificationsState::new(def_spec),
             mir_procedure_mapping: Default::default(),
             discriminants_state: Default::default(),
@@ -239,6 +259,48 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         std::mem::take(&mut self.programs)
     }
 
+    pub(super) fn register_dead_spec_queries(
+        &self,
+        proc_def_id: ProcedureDefId,
+        queries: Vec<DeadSpecQuery>,
+    ) {
+        self.dead_spec_queries.borrow_mut().insert(proc_def_id, queries);
+    }
+
+    /// The specifications checked by the programs of dead specification
+    /// queries, by the names of the programs.
+    pub fn take_dead_spec_programs(&mut self) -> FxHashMap<String, DeadSpecProgram> {
+        std::mem::take(&mut self.dead_spec_programs)
+    }
+
+    /// Collects the dead specification queries of the procedure in a separate
+    /// program. The queries are optional, so they are dropped if the program
+    /// cannot be finalized.
+    fn finalize_dead_spec_program(
+        &mut self,
+        proc_name: &str,
+        proc_def_id: ProcedureDefId,
+    ) -> Option<vir::Program> {
+        let queries = self.dead_spec_queries.borrow_mut().remove(&proc_def_id)?;
+        if queries.is_empty() {
+            return None;
+        }
+        let name = format!("{}{}", proc_name, DEAD_SPECS_PROGRAM_SUFFIX);
+        let dead_spec_program = DeadSpecProgram::new(&queries);
+        let methods = queries.into_iter().map(|query| query.method).collect();
+        let error_span = self.env.query.get_def_span(proc_def_id);
+        match super::definition_collector::collect_definitions(error_span, self, name.clone(), methods) {
+            Ok(program) => {
+                self.dead_spec_programs.insert(name, dead_spec_program);
+                Some(program)
+            }
+            Err(error) => {
+                debug!("Error finalizing the dead specification queries of {:?}: {:?}", proc_def_id, error);
+                None
+            }
+        }
+    }
+
     pub fn get_core_proof_programs(&mut self) -> Vec<prusti_common::vir::program::Program> {
         if config::counterexample() && config::unsafe_core_proof(){
             self.take_core_proof_programs().into_iter().map(
@@ -251,6 +313,20 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         }
     }
 
+    /// Runs the fuzzing harness of the core proof encoder (see
+    /// `config::fuzz_core_proof`) and returns the seeds of the generated
+    /// procedures that made the encoder fail together with the failures. The
+    /// span of `proc_def_id` is used for the positions of the generated
+    /// procedures.
+    pub fn run_core_proof_fuzzing(&mut self, proc_def_id: ProcedureDefId) -> Vec<(u64, String)> {
+        let seed = config::fuzz_core_proof_seed();
+        let iterations = config::fuzz_core_proof();
+        self.fuzz_core_proof(proc_def_id, seed, iterations)
+            .into_iter()
+            .map(|failure| (failure.seed, failure.message))
+            .collect()
+    }
+
     pub(in crate::encoder) fn register_encoding_error(&self, encoding_error: SpannedEncodingError) {
         debug!("Encoding error: {:?}", encoding_error);
         let prusti_error: PrustiError = encoding_error.into();
@@ -260,6 +336,15 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         prusti_error.emit(&self.env.diagnostic);
     }
 
+    /// Emits the warning unless a warning was already emitted at the same
+    /// span, e.g. because the specification that contains the span is encoded
+    /// once for each caller.
+    pub(in crate::encoder) fn emit_warning_once(&self, span: Span, warning: PrustiError) {
+        if self.emitted_warnings.borrow_mut().insert(span) {
+            warning.emit(&self.env.diagnostic);
+        }
+    }
+
     pub fn count_encoding_errors(&self) -> usize {
         *self.encoding_errors_counter.borrow()
     }
@@ -606,7 +691,12 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         value: mir::ConstantKind<'tcx>
     ) -> EncodingResult<vir::Expr> {
         trace!("encode_const_expr {:?}", value);
-        let scalar_value = self.const_eval_intlike(value)?;
+        let scalar_value = match self.const_eval_intlike(value) {
+            Ok(scalar_value) => scalar_value,
+            Err(error) => {
+                return self.encode_abstract_const_expr(ty, value)?.ok_or(error);
+            }
+        };
 
         let expr = match ty.kind() {
             ty::TyKind::Bool => scalar_value.to_bool().unwrap().into(),
@@ -675,6 +765,51 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         expr
     }
 
+    /// Encodes a constant that cannot be evaluated because its value depends
+    /// on type parameters, such as `T::CAPACITY` in a generic function, as
+    /// an abstract function of the type arguments. Returns `None` if the
+    /// constant cannot be encoded in this way.
+    fn encode_abstract_const_expr(
+        &self,
+        ty: ty::Ty<'tcx>,
+        value: mir::ConstantKind<'tcx>,
+    ) -> EncodingResult<Option<vir::Expr>> {
+        let ct = match value {
+            mir::ConstantKind::Ty(value) => match value.kind() {
+                ty::ConstKind::Unevaluated(ct) => mir::UnevaluatedConst::new(ct.def, ct.substs),
+                _ => return Ok(None),
+            },
+            mir::ConstantKind::Unevaluated(ct, _) => ct,
+            mir::ConstantKind::Val(..) => return Ok(None),
+        };
+        if ct.promoted.is_some() {
+            return Ok(None);
+        }
+        let return_ty = match ty.kind() {
+            ty::TyKind::Bool => vir::Type::Bool,
+            ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => vir::Type::Int,
+            _ => return Ok(None),
+        };
+        let result = vir::Expr::local(vir_local!{ __result: {return_ty.clone()} });
+        let (function_name, type_arguments) = self.encode_builtin_function_use(
+            BuiltinFunctionKind::Constant {
+                name: self.encode_item_name(ct.def.did),
+                type_arguments: self.encode_generic_arguments(ct.def.did, ct.substs)?,
+                return_ty: return_ty.clone(),
+                posts: self.encode_type_bounds(&result, ty),
+            }
+        );
+        debug!("encode_abstract_const_expr {:?} --> {}", value, function_name);
+        Ok(Some(vir::Expr::func_app(
+            function_name,
+            type_arguments,
+            vec![],
+            vec![],
+            return_ty,
+            vir::Position::default(),
+        )))
+    }
+
     pub fn encode_item_name(&self, def_id: DefId) -> String {
         let full_name = format!("m_{}", encode_identifier(self.env.name.get_unique_item_name(def_id)));
         let short_name = format!("m_{}", encode_identifier(
@@ -779,8 +914,13 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                                 self.register_encoding_error(error);
                                 debug!("Error encoding function: {:?}", proc_def_id);
                             } else {
-                                match self.finalize_viper_program(proc_name, proc_def_id) {
-                                    Ok(program) => self.programs.push(program),
+                                match self.finalize_viper_program(proc_name.clone(), proc_def_id) {
+                                    Ok(program) => {
+                                        self.programs.push(program);
+                                        if let Some(program) = self.finalize_dead_spec_program(&proc_name, proc_def_id) {
+                                            self.programs.push(program);
+                                        }
+                                    }
                                     Err(error) => {
                                         self.register_encoding_error(error);
                                         debug!("Error finalizing program: {:?}", proc_def_id);
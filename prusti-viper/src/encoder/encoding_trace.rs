@@ -0,0 +1,236 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A structured trace of the encoding of a procedure (the `trace_encoding`
+//! flag). For each MIR statement and terminator, it records the VIR statements
+//! produced for it, the permissions that these statements consume and
+//! produce, and the part of the procedure encoder responsible for them. The
+//! trace is dumped as JSON lines, one entry per MIR location, in the
+//! `encoding_trace` log directory.
+
+use prusti_common::report::log;
+use prusti_rustc_interface::{middle::mir, span::Span};
+use std::io::Write;
+use vir_crate::polymorphic::{self as vir, ExprWalker};
+
+#[derive(Default)]
+pub(super) struct EncodingTrace {
+    entries: Vec<EncodingTraceEntry>,
+}
+
+#[derive(serde::Serialize)]
+struct EncodingTraceEntry {
+    /// The MIR location, e.g. `bb3[2]`.
+    location: String,
+    mir: String,
+    span: String,
+    /// The method of the procedure encoder responsible for the statements.
+    encoder: String,
+    vir: Vec<String>,
+    consumed: Vec<String>,
+    produced: Vec<String>,
+}
+
+impl EncodingTrace {
+    pub(super) fn record(
+        &mut self,
+        location: mir::Location,
+        mir: String,
+        span: Span,
+        encoder: &'static str,
+        stmts: &[vir::Stmt],
+    ) {
+        let mut permissions = PermissionEffects::default();
+        for stmt in stmts {
+            permissions.add_stmt(stmt);
+        }
+        self.entries.push(EncodingTraceEntry {
+            location: format!("{:?}", location),
+            mir,
+            span: format!("{:?}", span),
+            encoder: format!("procedure_encoder::{}", encoder),
+            vir: stmts
+                .iter()
+                .filter(|stmt| !matches!(stmt, vir::Stmt::Comment(_)))
+                .map(|stmt| stmt.to_string())
+                .collect(),
+            consumed: permissions.consumed,
+            produced: permissions.produced,
+        });
+    }
+
+    pub(super) fn dump(&self, source_filename: &str, method_name: &str) {
+        log::report_with_writer(
+            "encoding_trace",
+            format!("{}.{}.jsonl", source_filename, method_name),
+            |writer| {
+                for entry in &self.entries {
+                    serde_json::to_writer(&mut *writer, entry).unwrap();
+                    writeln!(writer).unwrap();
+                }
+            },
+        );
+    }
+}
+
+/// The permissions consumed and produced by a sequence of VIR statements,
+/// before the fold-unfold algorithm adds the statements that move permissions
+/// between predicates.
+#[derive(Default)]
+struct PermissionEffects {
+    consumed: Vec<String>,
+    produced: Vec<String>,
+}
+
+impl PermissionEffects {
+    fn add_stmt(&mut self, stmt: &vir::Stmt) {
+        match stmt {
+            vir::Stmt::Inhale(vir::Inhale { expr }) => {
+                self.produced.extend(access_predicates(expr));
+            }
+            vir::Stmt::Exhale(vir::Exhale { expr, .. }) => {
+                self.consumed.extend(access_predicates(expr));
+            }
+            vir::Stmt::Fold(vir::Fold {
+                predicate,
+                arguments,
+                ..
+            }) => {
+                self.produced.push(predicate_instance(predicate, arguments));
+            }
+            vir::Stmt::Unfold(vir::Unfold {
+                predicate,
+                arguments,
+                ..
+            }) => {
+                self.consumed.push(predicate_instance(predicate, arguments));
+            }
+            vir::Stmt::Assign(vir::Assign {
+                target,
+                source,
+                kind,
+            }) if *kind != vir::AssignKind::Copy => {
+                if matches!(kind, vir::AssignKind::Move | vir::AssignKind::MutableBorrow(_)) {
+                    self.consumed.push(source.to_string());
+                }
+                self.produced.push(target.to_string());
+            }
+            vir::Stmt::TransferPerm(vir::TransferPerm { left, right, .. }) => {
+                self.consumed.push(left.to_string());
+                self.produced.push(right.to_string());
+            }
+            vir::Stmt::If(vir::If {
+                then_stmts,
+                else_stmts,
+                ..
+            }) => {
+                for stmt in then_stmts.iter().chain(else_stmts) {
+                    self.add_stmt(stmt);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn predicate_instance(predicate: &vir::Type, arguments: &[vir::Expr]) -> String {
+    let arguments: Vec<_> = arguments.iter().map(|arg| arg.to_string()).collect();
+    format!("{}({})", predicate, arguments.join(", "))
+}
+
+/// The accessibility predicates of the expression.
+fn access_predicates(expr: &vir::Expr) -> Vec<String> {
+    struct Collector(Vec<String>);
+    impl ExprWalker for Collector {
+        fn walk(&mut self, expr: &vir::Expr) {
+            match expr {
+                vir::Expr::PredicateAccessPredicate(..) | vir::Expr::FieldAccessPredicate(..) => {
+                    self.0.push(expr.to_string());
+                }
+                _ => vir::default_walk_expr(self, expr),
+            }
+        }
+    }
+    let mut collector = Collector(Vec::new());
+    collector.walk(expr);
+    collector.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PermissionEffects;
+    use vir_crate::polymorphic as vir;
+
+    fn place(name: &str) -> vir::Expr {
+        vir::Expr::local(vir::LocalVar::new(name, vir::Type::typed_ref("i32")))
+            .field(vir::Field::new("val_int", vir::Type::Int))
+    }
+
+    fn acc(name: &str) -> vir::Expr {
+        vir::Expr::acc_permission(place(name), vir::PermAmount::Write)
+    }
+
+    fn effects(stmts: &[vir::Stmt]) -> PermissionEffects {
+        let mut effects = PermissionEffects::default();
+        for stmt in stmts {
+            effects.add_stmt(stmt);
+        }
+        effects
+    }
+
+    #[test]
+    fn inhale_and_exhale() {
+        let effects = effects(&[
+            vir::Stmt::Inhale(vir::Inhale {
+                expr: vir::Expr::bin_op(vir::BinaryOpKind::And, acc("x"), acc("y")),
+            }),
+            vir::Stmt::Exhale(vir::Exhale {
+                expr: acc("z"),
+                position: Default::default(),
+            }),
+        ]);
+        assert_eq!(
+            effects.produced,
+            vec![acc("x").to_string(), acc("y").to_string()]
+        );
+        assert_eq!(effects.consumed, vec![acc("z").to_string()]);
+    }
+
+    #[test]
+    fn assignments() {
+        let effects = effects(&[
+            vir::Stmt::Assign(vir::Assign {
+                target: place("x"),
+                source: place("y"),
+                kind: vir::AssignKind::Copy,
+            }),
+            vir::Stmt::Assign(vir::Assign {
+                target: place("a"),
+                source: place("b"),
+                kind: vir::AssignKind::Move,
+            }),
+        ]);
+        assert_eq!(effects.produced, vec![place("a").to_string()]);
+        assert_eq!(effects.consumed, vec![place("b").to_string()]);
+    }
+
+    #[test]
+    fn nested_statements() {
+        let effects = effects(&[vir::Stmt::If(vir::If {
+            guard: true.into(),
+            then_stmts: vec![
+                vir::Stmt::comment("ignored"),
+                vir::Stmt::Inhale(vir::Inhale { expr: acc("x") }),
+            ],
+            else_stmts: vec![vir::Stmt::Exhale(vir::Exhale {
+                expr: acc("y"),
+                position: Default::default(),
+            })],
+        })]);
+        assert_eq!(effects.produced, vec![acc("x").to_string()]);
+        assert_eq!(effects.consumed, vec![acc("y").to_string()]);
+    }
+}
@@ -0,0 +1,268 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Translation of the errors of the verifier that are not caused by a failing
+//! assertion of the verified program: consistency errors of the generated
+//! Viper program, Java exceptions, and internal errors of the verifier. These
+//! errors are always reported as internal errors, but they are located at the
+//! Rust code that caused them (when the verifier reports a position) and
+//! suggest a likely cause.
+
+use super::ErrorManager;
+use prusti_interface::PrustiError;
+use prusti_rustc_interface::errors::MultiSpan;
+use viper::{JavaException, ProgramConsistencyError, VerificationError};
+
+/// The help shown when no more specific cause is recognized.
+const DEFAULT_HELP: &str = "This could be caused by too small assertion timeout. \
+    Try increasing it by setting the configuration parameter \
+    ASSERT_TIMEOUT to a larger value.";
+
+/// A likely cause of an error of the verifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BackendErrorCause {
+    /// The verifier does not support a construct of the generated program.
+    UnsupportedFeature,
+    /// The generated program refers to a definition that was not encoded.
+    MissingDefinition,
+    /// The generated program is not well-typed.
+    IllTyped,
+    /// The generated program contains a recursive definition that Viper
+    /// rejects.
+    RecursiveDefinition,
+    /// The JVM ran out of heap memory.
+    OutOfMemory,
+    /// The JVM ran out of stack memory.
+    StackOverflow,
+    /// The SMT solver could not be started or crashed.
+    SolverFailure,
+    /// A permission is missing at a position that Prusti does not expect to
+    /// fail, which usually happens after a loop without an invariant.
+    MissingInvariant,
+}
+
+impl BackendErrorCause {
+    /// Recognizes the cause from the message or the identifier of the error.
+    fn classify(message: &str) -> Option<Self> {
+        let message_lowercase = message.to_lowercase();
+        let contains_any = |patterns: &[&str]| {
+            patterns
+                .iter()
+                .any(|pattern| message_lowercase.contains(pattern))
+        };
+        let cause = if message.contains("OutOfMemoryError") || contains_any(&["gc overhead"]) {
+            Self::OutOfMemory
+        } else if message.contains("StackOverflowError") {
+            Self::StackOverflow
+        } else if contains_any(&["z3", "smt solver", "prover"]) {
+            Self::SolverFailure
+        } else if contains_any(&["unsupported", "not supported", "notimplemented"]) {
+            Self::UnsupportedFeature
+        } else if contains_any(&["insufficient.permission", "insufficient permission"]) {
+            Self::MissingInvariant
+        } else if contains_any(&["recursive", "cyclic", "not well-founded"]) {
+            Self::RecursiveDefinition
+        } else if contains_any(&[
+            "undefined",
+            "not defined",
+            "not declared",
+            "could not find",
+            "cannot find",
+            "not found",
+        ]) {
+            Self::MissingDefinition
+        } else if contains_any(&["type error", "typecheck", "expected type", "type mismatch"]) {
+            Self::IllTyped
+        } else {
+            return None;
+        };
+        Some(cause)
+    }
+
+    fn help(self) -> &'static str {
+        match self {
+            Self::UnsupportedFeature => {
+                "The verifier does not support a construct that Prusti generated for this \
+                code. Try to simplify the code or to mark the function as `#[trusted]`."
+            }
+            Self::MissingDefinition => {
+                "The generated program refers to an item that Prusti did not encode. This is \
+                often caused by an unsupported feature in a called function or in a type; \
+                marking the called function as `#[trusted]` might work around it."
+            }
+            Self::IllTyped => {
+                "Prusti generated an ill-typed program, which is often caused by unsupported \
+                generic or associated types."
+            }
+            Self::RecursiveDefinition => {
+                "Prusti generated a recursive definition that the verifier rejects, which is \
+                often caused by recursive types or pure functions."
+            }
+            Self::OutOfMemory => {
+                "The verifier ran out of memory. Try increasing the heap size by setting the \
+                configuration parameter JVM_MAX_HEAP_SIZE, or splitting the function into \
+                smaller ones."
+            }
+            Self::StackOverflow => {
+                "The verifier ran out of stack. Try increasing the stack size by setting the \
+                configuration parameter JVM_STACK_SIZE, or simplifying deeply nested \
+                expressions."
+            }
+            Self::SolverFailure => {
+                "The SMT solver failed. Check that the configuration parameter SMT_SOLVER_PATH \
+                (or the Z3_EXE environment variable) points to a working Z3 executable."
+            }
+            Self::MissingInvariant => {
+                "This is often caused by a missing loop invariant: after a loop, only the \
+                properties stated in the `body_invariant!`s of the loop are known about the \
+                memory it modifies."
+            }
+        }
+    }
+}
+
+/// The help for an internal error with the given message or identifier.
+pub(super) fn backend_error_help(message: &str) -> &'static str {
+    BackendErrorCause::classify(message).map_or(DEFAULT_HELP, BackendErrorCause::help)
+}
+
+impl<'tcx> ErrorManager<'tcx> {
+    /// Translates a consistency error of the program generated for `method`.
+    /// If the inconsistent node has no registered position, the error is
+    /// reported at `fallback_span`.
+    pub fn translate_consistency_error(
+        &self,
+        method: &str,
+        error: &ProgramConsistencyError,
+        fallback_span: MultiSpan,
+    ) -> PrustiError {
+        let span = error
+            .pos_id
+            .as_ref()
+            .and_then(|pos_id| pos_id.parse::<u64>().ok())
+            .and_then(|pos_id| self.position_manager().source_span.get(&pos_id))
+            .cloned()
+            .unwrap_or(fallback_span);
+        let prusti_error = PrustiError::internal(
+            format!("consistency error in {}: {}", method, error.message),
+            span,
+        );
+        match BackendErrorCause::classify(&error.message) {
+            Some(cause) => prusti_error.set_help(cause.help()),
+            None => prusti_error,
+        }
+    }
+
+    /// Translates a Java exception raised while verifying the program
+    /// generated for `method`.
+    pub fn translate_java_exception(
+        &self,
+        method: &str,
+        exception: &JavaException,
+        span: MultiSpan,
+    ) -> PrustiError {
+        let prusti_error = PrustiError::internal(format!("in {}: {}", method, exception), span);
+        let cause = BackendErrorCause::classify(exception.get_message())
+            .or_else(|| BackendErrorCause::classify(exception.get_stack_trace()));
+        match cause {
+            Some(cause) => prusti_error.set_help(cause.help()),
+            None => prusti_error,
+        }
+    }
+
+    /// Translates the errors that the verifier reports as internal, e.g. for
+    /// unsupported features. Returns `None` for the other errors.
+    pub(super) fn translate_internal_verification_error(
+        &self,
+        ver_error: &VerificationError,
+        error_span: MultiSpan,
+    ) -> Option<PrustiError> {
+        if !ver_error.full_id.starts_with("internal") {
+            return None;
+        }
+        let cause = BackendErrorCause::classify(&ver_error.full_id)
+            .or_else(|| BackendErrorCause::classify(&ver_error.message));
+        let prusti_error = if cause == Some(BackendErrorCause::UnsupportedFeature) {
+            PrustiError::unsupported(
+                format!("the verifier does not support this code: {}", ver_error.message),
+                error_span,
+            )
+        } else {
+            PrustiError::internal(
+                format!("[{}] {}", ver_error.full_id, ver_error.message),
+                error_span,
+            )
+        };
+        Some(prusti_error.set_help(cause.map_or(DEFAULT_HELP, BackendErrorCause::help)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backend_error_help, BackendErrorCause, DEFAULT_HELP};
+
+    #[test]
+    fn classify_java_exceptions() {
+        assert_eq!(
+            BackendErrorCause::classify("java.lang.OutOfMemoryError: Java heap space"),
+            Some(BackendErrorCause::OutOfMemory)
+        );
+        assert_eq!(
+            BackendErrorCause::classify("java.lang.OutOfMemoryError: GC overhead limit exceeded"),
+            Some(BackendErrorCause::OutOfMemory)
+        );
+        assert_eq!(
+            BackendErrorCause::classify("java.lang.StackOverflowError"),
+            Some(BackendErrorCause::StackOverflow)
+        );
+        assert_eq!(
+            BackendErrorCause::classify("Could not start Z3 at /usr/bin/z3"),
+            Some(BackendErrorCause::SolverFailure)
+        );
+    }
+
+    #[test]
+    fn classify_consistency_errors() {
+        assert_eq!(
+            BackendErrorCause::classify("Function f$foo is not defined"),
+            Some(BackendErrorCause::MissingDefinition)
+        );
+        assert_eq!(
+            BackendErrorCause::classify("Type error in the expression x + true"),
+            Some(BackendErrorCause::IllTyped)
+        );
+        assert_eq!(
+            BackendErrorCause::classify("Recursive predicate P$T is not well-founded"),
+            Some(BackendErrorCause::RecursiveDefinition)
+        );
+        assert_eq!(
+            BackendErrorCause::classify("Wildcard permissions are not supported here"),
+            Some(BackendErrorCause::UnsupportedFeature)
+        );
+    }
+
+    #[test]
+    fn classify_internal_errors() {
+        assert_eq!(
+            BackendErrorCause::classify("internal:insufficient.permission"),
+            Some(BackendErrorCause::MissingInvariant)
+        );
+        assert_eq!(
+            BackendErrorCause::classify("internal:feature.unsupported"),
+            Some(BackendErrorCause::UnsupportedFeature)
+        );
+        assert_eq!(BackendErrorCause::classify("internal:unknown"), None);
+    }
+
+    #[test]
+    fn help_falls_back_to_the_default() {
+        assert_eq!(backend_error_help("something unexpected"), DEFAULT_HELP);
+        assert_eq!(
+            backend_error_help("java.lang.StackOverflowError"),
+            BackendErrorCause::StackOverflow.help()
+        );
+    }
+}
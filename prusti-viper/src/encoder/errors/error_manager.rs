@@ -6,17 +6,20 @@
 
 use vir_crate::polymorphic::Position;
 use rustc_hash::FxHashMap;
-use prusti_rustc_interface::span::source_map::SourceMap;
+use prusti_rustc_interface::span::{source_map::SourceMap, Span};
 use prusti_rustc_interface::errors::MultiSpan;
 use viper::VerificationError;
 use prusti_interface::PrustiError;
 use log::{debug, trace};
-use super::PositionManager;
+use super::{ExportedPosition, PositionManager, backend_errors::backend_error_help};
 use prusti_interface::data::ProcedureDefId;
+use std::{collections::hash_map::DefaultHasher, hash::{Hash, Hasher}};
 
 
 /// The cause of a panic!()
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum PanicCause {
     /// Generic cause
     Generic,
@@ -33,7 +36,9 @@ pub enum PanicCause {
 }
 
 /// The kind of the method whose proof failed.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum BuiltinMethodKind {
     WriteConstant,
     MovePlace,
@@ -48,7 +53,7 @@ pub enum BuiltinMethodKind {
 
 /// In case of verification error, this enum will contain additional information
 /// required to describe the error.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum ErrorCtxt {
     /// A Viper `assert false` that encodes a Rust panic
     Panic(PanicCause),
@@ -133,6 +138,9 @@ pub enum ErrorCtxt {
     PackageMagicWandForPostcondition,
     /// Apply a magic wand as a borrow expires, relevant for pledge conditions
     ApplyMagicWandOnExpiry,
+    /// A Viper `assert expr` that checks that the `after_expiry` assertion of a
+    /// closure holds when the closure is created.
+    AssertClosureExpiryOnCreation,
     /// A diverging function call performed in a pure function
     DivergingCallInPureFunction,
     /// A Viper pure function call with `false` precondition that encodes a Rust panic in a pure function
@@ -155,6 +163,8 @@ pub enum ErrorCtxt {
     ProcedureCall,
     /// Failed to call a drop handler.
     DropCall,
+    /// Failed to obtain the heap memory accessed, or deallocated, by a call.
+    HeapMemoryAccess,
     /// Failed to encode lifetimes
     LifetimeEncoding,
     /// Failed to encode LifetimeTake
@@ -182,20 +192,65 @@ pub enum ErrorCtxt {
     UnreachableFoldingState,
 }
 
+impl ErrorCtxt {
+    /// Whether the error context belongs to a termination check.
+    pub fn is_termination_check(&self) -> bool {
+        matches!(
+            self,
+            ErrorCtxt::LoopVariant
+                | ErrorCtxt::LoopVariantOnEntry
+                | ErrorCtxt::LoopVariantAfterIteration
+                | ErrorCtxt::LoopVariantNonDecreased
+                | ErrorCtxt::UnexpectedReachableLoop
+                | ErrorCtxt::UnexpectedReachableCall
+                | ErrorCtxt::CallTerminationMeasureLower
+                | ErrorCtxt::CallTerminationMeasureNonNegative
+        )
+    }
+}
+
+/// A position together with its error context, exported by
+/// `ErrorManager::export_recorded_positions`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExportedErrorPosition {
+    position: ExportedPosition,
+    error_ctxt: Option<ErrorCtxt>,
+    inner_position: Option<Position>,
+    #[serde(default)]
+    branch: Option<Branch>,
+}
+
+/// The guard of a branch, registered by `ErrorManager::register_branch`.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+struct Branch {
+    /// The position of the other target of a two-way branch, which is taken
+    /// when the guard does not hold.
+    alternative: Option<Position>,
+}
+
 /// The error manager
 #[derive(Clone)]
 pub struct ErrorManager<'tcx> {
     position_manager: PositionManager<'tcx>,
     error_contexts: FxHashMap<u64, ErrorCtxt>,
     inner_positions: FxHashMap<u64, Position>,
+    /// The custom failure messages of specifications, by the span of the
+    /// specification.
+    failure_messages: FxHashMap<Span, String>,
+    /// The guards of the branches, by their position id.
+    branches: FxHashMap<u64, Branch>,
 }
 
 impl<'tcx> ErrorManager<'tcx> {
-    pub fn new(codemap: &'tcx SourceMap) -> Self {
+    /// `def_path` computes the def path of a procedure, which does not depend on the compiler
+    /// session. It is used to derive the ids of the positions of the procedure.
+    pub fn new(codemap: &'tcx SourceMap, def_path: impl Fn(ProcedureDefId) -> String + 'tcx) -> Self {
         ErrorManager {
-            position_manager: PositionManager::new(codemap),
+            position_manager: PositionManager::new(codemap, def_path),
             error_contexts: FxHashMap::default(),
             inner_positions: FxHashMap::default(),
+            failure_messages: FxHashMap::default(),
+            branches: FxHashMap::default(),
         }
     }
 
@@ -213,6 +268,54 @@ impl<'tcx> ErrorManager<'tcx> {
         self.position_manager.duplicate(pos)
     }
 
+    /// Starts recording the positions registered from now on. See
+    /// `export_recorded_positions`.
+    pub fn start_recording_positions(&mut self) {
+        self.position_manager.start_recording();
+    }
+
+    /// Stops recording the positions without exporting them.
+    pub fn stop_recording_positions(&mut self) {
+        self.position_manager.stop_recording();
+    }
+
+    /// Stops recording and exports the positions registered since the last call of
+    /// `start_recording_positions` together with their error contexts. Returns `None` if some
+    /// position belongs to a procedure that is not in `def_ids`.
+    pub fn export_recorded_positions(&mut self, def_ids: &[ProcedureDefId]) -> Option<Vec<ExportedErrorPosition>> {
+        let recorded = self.position_manager.stop_recording();
+        recorded.into_iter().map(|(pos_id, occurrence_key)| {
+            Some(ExportedErrorPosition {
+                position: self.position_manager.export_position(pos_id, occurrence_key, def_ids)?,
+                error_ctxt: self.error_contexts.get(&pos_id).cloned(),
+                inner_position: self.inner_positions.get(&pos_id).copied(),
+                branch: self.branches.get(&pos_id).copied(),
+            })
+        }).collect()
+    }
+
+    /// Registers the exported positions with their original ids. Returns `false` and registers
+    /// nothing if some id is already used or a span refers to a file that is not loaded.
+    pub fn import_positions(&mut self, positions: &[ExportedErrorPosition], def_ids: &[ProcedureDefId]) -> bool {
+        let exported: Vec<_> = positions.iter().map(|position| position.position.clone()).collect();
+        if !self.position_manager.can_import_positions(&exported, def_ids) {
+            return false;
+        }
+        for position in positions {
+            self.position_manager.import_position(&position.position, def_ids);
+            if let Some(error_ctxt) = &position.error_ctxt {
+                self.error_contexts.insert(position.position.id, error_ctxt.clone());
+            }
+            if let Some(inner_position) = position.inner_position {
+                self.inner_positions.insert(position.position.id, inner_position);
+            }
+            if let Some(branch) = position.branch {
+                self.branches.insert(position.position.id, branch);
+            }
+        }
+        true
+    }
+
     /// Register the ErrorCtxt on an existing VIR position.
     pub fn set_error(&mut self, pos: Position, error_ctxt: ErrorCtxt) {
         trace!("Register error {:?} at position id {:?}", error_ctxt, pos.id());
@@ -245,11 +348,91 @@ impl<'tcx> ErrorManager<'tcx> {
     /// Register a new VIR position with the given ErrorCtxt.
     /// Equivalent to calling `set_error` on the output of `register_span`.
     pub fn register_error<T: Into<MultiSpan>>(&mut self, span: T, error_ctxt: ErrorCtxt, def_id: ProcedureDefId) -> Position {
-        let pos = self.register_span(def_id, span);
+        // Only the kind of the error context is part of the role, because formatting the whole
+        // context for each position is expensive.
+        let mut hasher = DefaultHasher::new();
+        std::mem::discriminant(&error_ctxt).hash(&mut hasher);
+        let role = format!("error {:x}", hasher.finish());
+        let pos = self.position_manager.register_span_with_role(def_id, span, &role);
         self.set_error(pos, error_ctxt);
         pos
     }
 
+    /// Register the custom message that is reported when an assertion within
+    /// the span of a specification fails.
+    pub fn register_failure_message(&mut self, span: Span, message: String) {
+        self.failure_messages.insert(span, message);
+    }
+
+    /// The custom message of the specification that contains the failing
+    /// assertion of the verification error, if any.
+    fn get_failure_message(&self, ver_error: &VerificationError) -> Option<&str> {
+        let reason_pos_id: u64 = ver_error.reason_pos_id.as_ref()?.parse().ok()?;
+        let cause_span = self.position_manager.source_span.get(&reason_pos_id)?.primary_span()?;
+        self.failure_messages
+            .iter()
+            .find(|(span, _)| span.contains(cause_span))
+            .map(|(_, message)| message.as_str())
+    }
+
+    /// Registers the position of the guard of a branch to the code at `span`.
+    /// For a two-way branch, `alternative_span` is the span of the code that
+    /// is executed when the guard does not hold.
+    pub fn register_branch(
+        &mut self,
+        def_id: ProcedureDefId,
+        span: Span,
+        alternative_span: Option<Span>,
+    ) -> Position {
+        let alternative = alternative_span.map(|alternative_span| {
+            self.position_manager.register_span_with_role(def_id, alternative_span, "BranchAlternative")
+        });
+        let pos = self.position_manager.register_span_with_role(def_id, span, "Branch");
+        self.branches.insert(pos.id(), Branch { alternative });
+        pos
+    }
+
+    /// Adds a note to the error for each branch that is taken by the path on
+    /// which the verification error occurs, in the order of the path.
+    pub fn annotate_failing_path(
+        &self,
+        ver_error: &VerificationError,
+        mut prusti_error: PrustiError,
+    ) -> PrustiError {
+        for condition in &ver_error.branch_conditions {
+            let Some(pos_id) = condition.pos_id.as_ref().and_then(|id| id.parse().ok()) else {
+                continue;
+            };
+            let Some(branch) = self.branches.get(&pos_id) else {
+                continue;
+            };
+            let (branch_pos_id, message) = match (condition.negated, branch.alternative) {
+                (false, _) => (pos_id, "the failing path takes this branch"),
+                (true, Some(alternative)) => (alternative.id(), "the failing path takes this branch"),
+                (true, None) => (pos_id, "the failing path does not take this branch"),
+            };
+            let span = self.position_manager.source_span
+                .get(&branch_pos_id)
+                .and_then(|span| span.primary_span());
+            if span.is_some() {
+                prusti_error = prusti_error.add_note(message, span);
+            }
+        }
+        prusti_error
+    }
+
+    /// The error context registered on the position with the given id.
+    pub fn get_position_error_ctxt(&self, pos_id: u64) -> Option<&ErrorCtxt> {
+        self.error_contexts.get(&pos_id)
+    }
+
+    /// The context of the position at which the verification error was reported.
+    pub fn get_error_ctxt(&self, ver_error: &VerificationError) -> Option<&ErrorCtxt> {
+        ver_error.offending_pos_id.as_ref()
+            .and_then(|id| id.parse().ok())
+            .and_then(|id| self.error_contexts.get(&id))
+    }
+
     pub fn get_def_id(&self, ver_error: &VerificationError) -> Option<ProcedureDefId> {
         ver_error.offending_pos_id.as_ref()
             .and_then(|id| id.parse().ok())
@@ -257,6 +440,14 @@ impl<'tcx> ErrorManager<'tcx> {
     }
 
     pub fn translate_verification_error(&self, ver_error: &VerificationError) -> PrustiError {
+        let prusti_error = self.translate_verification_error_without_message(ver_error);
+        match self.get_failure_message(ver_error) {
+            Some(message) => prusti_error.add_note(message, None),
+            None => prusti_error,
+        }
+    }
+
+    fn translate_verification_error_without_message(&self, ver_error: &VerificationError) -> PrustiError {
         debug!("Verification error: {:?}", ver_error);
         let opt_pos_id: Option<u64> = match ver_error.offending_pos_id {
             Some(ref viper_pos_id) => {
@@ -304,6 +495,16 @@ impl<'tcx> ErrorManager<'tcx> {
             res
         });
 
+        let internal_error_span = opt_error_span
+            .or(opt_cause_span)
+            .cloned()
+            .unwrap_or_else(MultiSpan::new);
+        if let Some(prusti_error) =
+            self.translate_internal_verification_error(ver_error, internal_error_span)
+        {
+            return prusti_error.set_failing_assertion(opt_cause_span);
+        }
+
         if let Some(error_ctxt) = opt_error_ctxts {
             debug_assert!(opt_error_span.is_some());
             let error_span = opt_error_span.cloned().unwrap_or_else(MultiSpan::new);
@@ -329,11 +530,7 @@ impl<'tcx> ErrorManager<'tcx> {
                             ver_error.full_id, pos_id, ver_error.message
                         ),
                         error_span
-                    ).set_help(
-                        "This could be caused by too small assertion timeout. \
-                        Try increasing it by setting the configuration parameter \
-                        ASSERT_TIMEOUT to a larger value."
-                    )
+                    ).set_help(backend_error_help(&ver_error.full_id))
                 }
                 None => {
                     PrustiError::internal(
@@ -342,11 +539,7 @@ impl<'tcx> ErrorManager<'tcx> {
                             ver_error.full_id, ver_error.message
                         ),
                         error_span
-                    ).set_help(
-                        "This could be caused by too small assertion timeout. \
-                        Try increasing it by setting the configuration parameter \
-                        ASSERT_TIMEOUT to a larger value."
-                    )
+                    ).set_help(backend_error_help(&ver_error.full_id))
                 }
             }
         }
@@ -438,6 +631,13 @@ impl<'tcx> ErrorManager<'tcx> {
                     .push_primary_span(opt_cause_span)
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::AssertClosureExpiryOnCreation) => {
+                PrustiError::verification(
+                    "the `after_expiry` specification of the closure might not hold when the closure is created.",
+                    error_span
+                ).push_primary_span(opt_cause_span)
+            }
+
             ("assert.failed:assertion.false", ErrorCtxt::ExhaleLoopInvariantAfterIteration) => {
                 PrustiError::verification(
                     "loop invariant might not hold after a loop iteration that preserves the loop condition.",
@@ -589,6 +789,14 @@ impl<'tcx> ErrorManager<'tcx> {
                 ).set_failing_assertion(opt_cause_span)
             }
 
+            ("exhale.failed:insufficient.permission", ErrorCtxt::HeapMemoryAccess) => {
+                PrustiError::verification(
+                    "the accessed heap memory might not be allocated.".to_string(),
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+                .set_help("the memory has to be allocated with `alloc` before it is accessed through a raw pointer, and it can be deallocated only once.")
+            }
+
             ("unfold.failed:insufficient.permission", ErrorCtxt::UnfoldUnionVariant) => {
                 PrustiError::verification(
                     "failed to unpack the capability of union's field.".to_string(),
@@ -708,11 +916,7 @@ impl<'tcx> ErrorManager<'tcx> {
                     error_span,
                 ).set_failing_assertion(
                     opt_cause_span
-                ).set_help(
-                    "This could be caused by too small assertion timeout. \
-                    Try increasing it by setting the configuration parameter \
-                    ASSERT_TIMEOUT to a larger value."
-                )
+                ).set_help(backend_error_help(&ver_error.full_id))
             },
 
             _ => {
@@ -728,11 +932,7 @@ impl<'tcx> ErrorManager<'tcx> {
                     error_span,
                 ).set_failing_assertion(
                     opt_cause_span
-                ).set_help(
-                    "This could be caused by too small assertion timeout. \
-                    Try increasing it by setting the configuration parameter \
-                    ASSERT_TIMEOUT to a larger value."
-                )
+                ).set_help(backend_error_help(&ver_error.full_id))
             }
         }
     }
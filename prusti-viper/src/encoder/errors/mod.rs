@@ -20,3 +20,4 @@ mod encoding_error;
 mod encoding_error_kind;
 mod with_span;
 mod position_manager;
+mod backend_errors;
@@ -5,40 +5,102 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use vir_crate::polymorphic::Position;
-use rustc_hash::FxHashMap;
-use prusti_rustc_interface::span::source_map::SourceMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{cell::RefCell, collections::hash_map::DefaultHasher, hash::{Hash, Hasher}, rc::Rc};
+use prusti_rustc_interface::span::{source_map::SourceMap, BytePos, Span};
 use prusti_rustc_interface::errors::MultiSpan;
 use log::{debug, trace};
 use prusti_interface::data::ProcedureDefId;
 
+/// The source location of a VIR position. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// A position registered by `PositionManager` in a form that does not depend on the compiler
+/// session, so that it can be stored on disk and registered again by a later run.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExportedPosition {
+    pub id: u64,
+    /// The key under which the occurrence of the position was counted.
+    occurrence_key: String,
+    /// The index of the def_id of the position in the list of def_ids passed to
+    /// `export_position`.
+    def_id_index: usize,
+    /// The primary spans as the file name and the byte offsets within the file.
+    spans: Vec<(String, u32, u32)>,
+}
+
+/// Position ids are truncated to this many bits so that they can be represented
+/// exactly by the JSON numbers of tools inspecting the Viper program.
+const POSITION_ID_BITS: u32 = 48;
+
 /// Mapping from VIR positions to the source code that generated them.
 /// One VIR position can be involved in multiple errors. If an error needs to refer to a special
 /// span, that should be done by adding the span to `ErrorCtxt`, not by registering a new span.
+///
+/// Position ids are derived from the procedure, the source location of the span, the role of the
+/// position (for example, the kind of its error context), and the number of positions that were
+/// already registered for the same procedure, location and role. Collisions are resolved among
+/// the positions of the same procedure. Therefore, the ids do not depend on the order in which
+/// the procedures are encoded and are stable across runs, which keeps the hashes of the
+/// verification requests (used for caching) and the generated names (for example, of
+/// quantifiers) stable. Only if an id collides with an id of another procedure, which is
+/// unlikely with 48-bit ids, the id that is chosen instead depends on the encoding order.
 #[derive(Clone)]
 pub struct PositionManager<'tcx> {
     codemap: &'tcx SourceMap,
-    next_pos_id: u64,
+    /// Computes the def path of a procedure, which does not depend on the compiler session.
+    def_path: Rc<dyn Fn(ProcedureDefId) -> String + 'tcx>,
+    /// The def paths computed so far.
+    def_paths: RefCell<FxHashMap<ProcedureDefId, Rc<str>>>,
+    /// The number of positions registered for each procedure, location and role.
+    occurrences: FxHashMap<String, u64>,
+    /// The ids of the positions registered for each procedure.
+    procedure_positions: FxHashMap<ProcedureDefId, FxHashSet<u64>>,
     /// The def_id of the procedure that generated the given VIR position.
     pub(crate) def_id: FxHashMap<u64, ProcedureDefId>,
     /// The span of the source code that generated the given VIR position.
     pub(crate) source_span: FxHashMap<u64, MultiSpan>,
+    /// If set, the ids and the occurrence keys of the registered positions are recorded.
+    recorded: Option<Vec<(u64, String)>>,
 }
 
 impl<'tcx> PositionManager<'tcx>
 {
-    pub fn new(codemap: &'tcx SourceMap) -> Self {
+    pub fn new(codemap: &'tcx SourceMap, def_path: impl Fn(ProcedureDefId) -> String + 'tcx) -> Self {
         PositionManager {
             codemap,
-            next_pos_id: 1,
+            def_path: Rc::new(def_path),
+            def_paths: RefCell::new(FxHashMap::default()),
+            occurrences: FxHashMap::default(),
+            procedure_positions: FxHashMap::default(),
             def_id: FxHashMap::default(),
             source_span: FxHashMap::default(),
+            recorded: None,
         }
     }
 
     pub fn register_span<T: Into<MultiSpan>>(&mut self, def_id: ProcedureDefId, span: T) -> Position {
+        self.register_span_with_role(def_id, span, "")
+    }
+
+    /// Registers a position whose id is derived from `span` and `role`. Positions that have the
+    /// same span, but are used for different purposes, should have different roles so that
+    /// their ids do not depend on the order in which they are registered.
+    pub fn register_span_with_role<T: Into<MultiSpan>>(
+        &mut self,
+        def_id: ProcedureDefId,
+        span: T,
+        role: &str,
+    ) -> Position {
         let span = span.into();
-        let pos_id = self.next_pos_id;
-        self.next_pos_id += 1;
+        let pos_id = self.compute_position_id(def_id, &span, role);
         trace!("Register position id {} for span {:?} in {:?}, ", pos_id, span, def_id);
 
         let pos = if let Some(primary_span) = span.primary_span() {
@@ -68,17 +130,181 @@ impl<'tcx> PositionManager<'tcx>
 
         self.def_id.insert(pos_id, def_id);
         self.source_span.insert(pos_id, span);
+        self.procedure_positions.entry(def_id).or_default().insert(pos_id);
         pos
     }
 
     pub fn duplicate(&mut self, pos: Position) -> Position {
         assert!(!pos.is_default());
-        self.register_span(
+        self.register_span_with_role(
             self.get_def_id(pos).unwrap(),
             self.get_span(pos).cloned().unwrap(),
+            &format!("duplicate of {}", pos.id()),
         )
     }
 
+    fn compute_position_id(&mut self, def_id: ProcedureDefId, span: &MultiSpan, role: &str) -> u64 {
+        let key = self.position_key(def_id, span, role);
+        let occurrence = self.occurrences.entry(key.clone()).or_insert(0);
+        *occurrence += 1;
+        let occurrence = *occurrence;
+        let pos_id = self.hash_position_id(&key, occurrence, def_id, |_| false);
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push((pos_id, key));
+        }
+        pos_id
+    }
+
+    fn position_key(&self, def_id: ProcedureDefId, span: &MultiSpan, role: &str) -> String {
+        let location = span
+            .primary_spans()
+            .iter()
+            .map(|span| self.codemap.span_to_diagnostic_string(*span))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}|{}|{}", self.get_def_path(def_id), location, role)
+    }
+
+    fn get_def_path(&self, def_id: ProcedureDefId) -> Rc<str> {
+        self.def_paths
+            .borrow_mut()
+            .entry(def_id)
+            .or_insert_with(|| (self.def_path)(def_id).into())
+            .clone()
+    }
+
+    /// Hashes the key and the occurrence into an id that is not used yet. `is_predicted` tells
+    /// which ids were predicted, but not registered yet.
+    fn hash_position_id(
+        &self,
+        key: &str,
+        occurrence: u64,
+        def_id: ProcedureDefId,
+        is_predicted: impl Fn(u64) -> bool,
+    ) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        occurrence.hash(&mut hasher);
+        let mask = (1 << POSITION_ID_BITS) - 1;
+        let mut pos_id = hasher.finish() & mask;
+        let same_procedure = self.procedure_positions.get(&def_id);
+        // Resolve the (unlikely) collisions by probing; the id 0 is reserved for the default
+        // position. The ids of the same procedure are probed first, so that the resolution of
+        // the collisions within a procedure does not depend on the other procedures.
+        loop {
+            while pos_id == 0
+                || is_predicted(pos_id)
+                || same_procedure.map_or(false, |ids| ids.contains(&pos_id))
+            {
+                pos_id = (pos_id + 1) & mask;
+            }
+            if !self.source_span.contains_key(&pos_id) {
+                return pos_id;
+            }
+            pos_id = (pos_id + 1) & mask;
+        }
+    }
+
+    /// Returns the ids that calling `duplicate` on the given positions in this order would
+    /// assign, without registering any position. The positions may also be duplicates created
+    /// by earlier entries of `positions`. Returns `None` if some position is not registered.
+    pub fn predict_duplicates(&self, positions: &[Position]) -> Option<Vec<u64>> {
+        let mut occurrences: FxHashMap<String, u64> = FxHashMap::default();
+        let mut predicted: FxHashMap<u64, (&MultiSpan, ProcedureDefId)> = FxHashMap::default();
+        let mut ids = Vec::with_capacity(positions.len());
+        for pos in positions {
+            if pos.is_default() {
+                return None;
+            }
+            let (span, def_id) = match predicted.get(&pos.id()) {
+                Some(predicted) => *predicted,
+                None => (self.get_span(*pos)?, self.get_def_id(*pos)?),
+            };
+            let key = self.position_key(def_id, span, &format!("duplicate of {}", pos.id()));
+            let occurrence = occurrences
+                .entry(key.clone())
+                .or_insert_with(|| self.occurrences.get(&key).copied().unwrap_or(0));
+            *occurrence += 1;
+            let pos_id = self.hash_position_id(&key, *occurrence, def_id, |pos_id| {
+                predicted.contains_key(&pos_id)
+            });
+            predicted.insert(pos_id, (span, def_id));
+            ids.push(pos_id);
+        }
+        Some(ids)
+    }
+
+    /// Starts recording the registered positions so that they can be exported with
+    /// `export_position`.
+    pub fn start_recording(&mut self) {
+        self.recorded = Some(Vec::new());
+    }
+
+    /// Stops recording and returns the ids and the occurrence keys of the positions registered
+    /// since the last call of `start_recording`.
+    pub fn stop_recording(&mut self) -> Vec<(u64, String)> {
+        self.recorded.take().unwrap_or_default()
+    }
+
+    /// Exports a recorded position. Returns `None` if the def_id of the position is not in
+    /// `def_ids`. Spans produced by macro expansions are exported as the span of the macro call.
+    pub fn export_position(
+        &self,
+        pos_id: u64,
+        occurrence_key: String,
+        def_ids: &[ProcedureDefId],
+    ) -> Option<ExportedPosition> {
+        let def_id = self.def_id.get(&pos_id)?;
+        let def_id_index = def_ids.iter().position(|candidate| candidate == def_id)?;
+        let spans = self.source_span[&pos_id]
+            .primary_spans()
+            .iter()
+            .map(|span| {
+                let span = span.source_callsite();
+                let start = self.codemap.lookup_byte_offset(span.lo());
+                let end = self.codemap.lookup_byte_offset(span.hi());
+                (start.sf.name.prefer_local().to_string(), start.pos.0, end.pos.0)
+            })
+            .collect();
+        Some(ExportedPosition { id: pos_id, occurrence_key, def_id_index, spans })
+    }
+
+    /// Checks whether the exported positions can be registered again, which requires that
+    /// their ids are not used and that their files are loaded.
+    pub fn can_import_positions(&self, positions: &[ExportedPosition], def_ids: &[ProcedureDefId]) -> bool {
+        positions.iter().all(|position| {
+            !self.source_span.contains_key(&position.id)
+                && position.def_id_index < def_ids.len()
+                && position.spans.iter().all(|(file, _, _)| self.find_file_start(file).is_some())
+        })
+    }
+
+    /// Registers the exported positions with their original ids. The caller has to check that
+    /// this is possible with `can_import_positions`.
+    pub fn import_position(&mut self, position: &ExportedPosition, def_ids: &[ProcedureDefId]) {
+        let spans = position
+            .spans
+            .iter()
+            .map(|(file, lo, hi)| {
+                let start = self.find_file_start(file).unwrap();
+                Span::with_root_ctxt(start + BytePos(*lo), start + BytePos(*hi))
+            })
+            .collect();
+        let def_id = def_ids[position.def_id_index];
+        *self.occurrences.entry(position.occurrence_key.clone()).or_insert(0) += 1;
+        self.def_id.insert(position.id, def_id);
+        self.source_span.insert(position.id, MultiSpan::from_spans(spans));
+        self.procedure_positions.entry(def_id).or_default().insert(position.id);
+    }
+
+    fn find_file_start(&self, file_name: &str) -> Option<BytePos> {
+        self.codemap
+            .files()
+            .iter()
+            .find(|file| file.name.prefer_local().to_string() == file_name)
+            .map(|file| file.start_pos)
+    }
+
     pub fn get_def_id(&self, pos: Position) -> Option<ProcedureDefId> {
         self.def_id.get(&pos.id()).copied()
     }
@@ -86,4 +312,125 @@ impl<'tcx> PositionManager<'tcx>
     pub fn get_span(&self, pos: Position) -> Option<&MultiSpan> {
         self.source_span.get(&pos.id())
     }
+
+    /// The location of the primary span of the position with the given id. For spans produced by
+    /// macro expansions, this is the location of the macro call.
+    pub fn get_source_location(&self, pos_id: u64) -> Option<SourceLocation> {
+        let span = self.source_span.get(&pos_id)?.primary_span()?.source_callsite();
+        let start = self.codemap.lookup_char_pos(span.lo());
+        let end = self.codemap.lookup_char_pos(span.hi());
+        Some(SourceLocation {
+            file: start.file.name.prefer_local().to_string(),
+            line: start.line,
+            column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prusti_rustc_interface::{
+        hir::def_id::{DefId, DefIndex, CRATE_DEF_ID, LOCAL_CRATE},
+        span::{create_default_session_globals_then, source_map::FilePathMapping, FileName},
+    };
+
+    /// Calls `f` with a source map containing a file and two different spans
+    /// in it.
+    fn with_spans(f: impl FnOnce(&SourceMap, Span, Span)) {
+        create_default_session_globals_then(|| {
+            let codemap = SourceMap::new(FilePathMapping::empty());
+            let file = codemap.new_source_file(
+                FileName::Custom("test.rs".to_string()),
+                "fn f() {}\nfn g() {}\n".to_string(),
+            );
+            let first = Span::with_root_ctxt(file.start_pos, file.start_pos + BytePos(9));
+            let second =
+                Span::with_root_ctxt(file.start_pos + BytePos(10), file.start_pos + BytePos(19));
+            f(&codemap, first, second);
+        });
+    }
+
+    fn new_manager(codemap: &SourceMap) -> PositionManager<'_> {
+        PositionManager::new(codemap, |def_id| format!("{:?}", def_id))
+    }
+
+    #[test]
+    fn test_ids_do_not_depend_on_order() {
+        with_spans(|codemap, first, second| {
+            let def_id = CRATE_DEF_ID.to_def_id();
+            let mut manager = new_manager(codemap);
+            let first_pos = manager.register_span(def_id, first);
+            let second_pos = manager.register_span(def_id, second);
+            let mut other_manager = new_manager(codemap);
+            assert_eq!(other_manager.register_span(def_id, second).id(), second_pos.id());
+            assert_eq!(other_manager.register_span(def_id, first).id(), first_pos.id());
+            assert_ne!(first_pos.id(), second_pos.id());
+        });
+    }
+
+    #[test]
+    fn test_ids_of_shared_spans_do_not_depend_on_order() {
+        with_spans(|codemap, first, _| {
+            let def_id = CRATE_DEF_ID.to_def_id();
+            let other_def_id = DefId {
+                krate: LOCAL_CRATE,
+                index: DefIndex::from_u32(1),
+            };
+            let mut manager = new_manager(codemap);
+            let pos = manager.register_span(def_id, first);
+            let other_pos = manager.register_span(other_def_id, first);
+            let mut other_manager = new_manager(codemap);
+            assert_eq!(other_manager.register_span(other_def_id, first).id(), other_pos.id());
+            assert_eq!(other_manager.register_span(def_id, first).id(), pos.id());
+            assert_ne!(pos.id(), other_pos.id());
+        });
+    }
+
+    #[test]
+    fn test_ids_of_repeated_spans_and_roles() {
+        with_spans(|codemap, first, _| {
+            let def_id = CRATE_DEF_ID.to_def_id();
+            let mut manager = new_manager(codemap);
+            let pos = manager.register_span(def_id, first);
+            let again = manager.register_span(def_id, first);
+            let with_role = manager.register_span_with_role(def_id, first, "role");
+            assert_ne!(pos.id(), again.id());
+            assert_ne!(pos.id(), with_role.id());
+            assert_ne!(again.id(), with_role.id());
+            let mut other_manager = new_manager(codemap);
+            assert_eq!(
+                other_manager.register_span_with_role(def_id, first, "role").id(),
+                with_role.id()
+            );
+            assert_eq!(other_manager.register_span(def_id, first).id(), pos.id());
+            assert_eq!(other_manager.register_span(def_id, first).id(), again.id());
+        });
+    }
+
+    #[test]
+    fn test_predict_duplicates() {
+        with_spans(|codemap, first, second| {
+            let def_id = CRATE_DEF_ID.to_def_id();
+            let mut manager = new_manager(codemap);
+            let first_pos = manager.register_span(def_id, first);
+            let second_pos = manager.register_span(def_id, second);
+            let predicted = manager
+                .predict_duplicates(&[first_pos, second_pos, first_pos])
+                .unwrap();
+            let mut duplicates = vec![
+                manager.duplicate(first_pos),
+                manager.duplicate(second_pos),
+                manager.duplicate(first_pos),
+            ];
+            let duplicate_of_duplicate = manager.predict_duplicates(&[duplicates[0]]).unwrap();
+            duplicates.push(manager.duplicate(duplicates[0]));
+            let ids: Vec<_> = duplicates.iter().map(|pos| pos.id()).collect();
+            assert_eq!(ids[..3], predicted[..]);
+            assert_eq!(ids[3], duplicate_of_duplicate[0]);
+            assert!(manager.predict_duplicates(&[Position::default()]).is_none());
+        });
+    }
 }
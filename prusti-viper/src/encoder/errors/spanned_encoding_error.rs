@@ -4,7 +4,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use prusti_rustc_interface::errors::MultiSpan;
+use prusti_rustc_interface::{errors::MultiSpan, span::Span};
 use log::{debug, error};
 use prusti_interface::PrustiError;
 
@@ -18,6 +18,7 @@ pub struct SpannedEncodingError {
     span: Box<MultiSpan>,
     help: Option<String>,
     notes: Vec<(String, Option<MultiSpan>)>,
+    suggestion: Option<(String, Span, String)>,
 }
 
 pub type SpannedEncodingResult<T> = Result<T, SpannedEncodingError>;
@@ -41,6 +42,9 @@ impl From<SpannedEncodingError> for PrustiError {
         for (message, span) in other.notes {
             error.add_note_mut(message, span);
         }
+        if let Some((message, span, replacement)) = other.suggestion {
+            error = error.set_suggestion(message, span, replacement);
+        }
         error
     }
 }
@@ -52,6 +56,7 @@ impl SpannedEncodingError {
             span: Box::new(span.into()),
             help: None,
             notes: Vec::new(),
+            suggestion: None,
         }
     }
 
@@ -105,4 +110,14 @@ impl SpannedEncodingError {
     pub fn set_help<S: ToString>(&mut self, message: S) {
         self.help = Some(message.to_string());
     }
+
+    /// Suggests a machine-applicable change that fixes the error.
+    pub fn set_suggestion<S: ToString, R: ToString>(
+        &mut self,
+        message: S,
+        span: Span,
+        replacement: R,
+    ) {
+        self.suggestion = Some((message.to_string(), span, replacement.to_string()));
+    }
 }
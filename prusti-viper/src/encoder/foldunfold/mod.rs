@@ -15,6 +15,7 @@ use super::errors::SpannedEncodingError;
 use crate::encoder::high::types::HighTypeEncoderInterface;
 use prusti_common::{config, report, utils::to_string::ToString, vir::ToGraphViz, Stopwatch};
 use prusti_rustc_interface::middle::mir;
+use prusti_server::{BlockStates, FoldUnfoldState, MethodStates};
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::{self, fmt, ops::Deref};
 use vir_crate::{
@@ -262,6 +263,8 @@ struct FoldUnfold<'p, 'v: 'p, 'tcx: 'v> {
     initial_pctxt: PathCtxt<'p>,
     pctxt_at_label: FxHashMap<String, PathCtxt<'p>>,
     dump_debug_info: bool,
+    /// Dump the fold-unfold states of the blocks for `prusti-server debug`.
+    dump_debug_session: bool,
     /// Used for debugging the dump
     foldunfold_state_filter: String,
     /// Generate additional assertions to check that the state of the fold-unfold algorithm
@@ -288,6 +291,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> FoldUnfold<'p, 'v, 'tcx> {
             initial_pctxt,
             pctxt_at_label: FxHashMap::default(),
             dump_debug_info: config::dump_debug_info_during_fold(),
+            dump_debug_session: config::dump_debug_session(),
             check_foldunfold_state: config::check_foldunfold_state(),
             foldunfold_state_filter: config::foldunfold_state_filter(),
             cfg,
@@ -426,6 +430,49 @@ impl<'p, 'v: 'p, 'tcx: 'v> FoldUnfold<'p, 'v, 'tcx> {
     }
 }
 
+/// Dumps the fold-unfold states at the entry and at the exit of each block. The
+/// CFG is dumped repeatedly while the algorithm runs, so the last dump has the
+/// final states.
+fn dump_method_states(
+    encoder: &Encoder,
+    cfg: &vir::CfgMethod,
+    initial_pctxt: &[Option<PathCtxt>],
+    final_pctxt: &[Option<PathCtxt>],
+) {
+    let blocks = cfg
+        .basic_blocks_labels()
+        .iter()
+        .enumerate()
+        .map(|(index, label)| {
+            let states = BlockStates {
+                entry: fold_unfold_state(initial_pctxt.get(index)),
+                exit: fold_unfold_state(final_pctxt.get(index)),
+            };
+            (label.clone(), states)
+        })
+        .collect();
+    let source_path = encoder.env().name.source_path();
+    let source_filename = source_path.file_name().unwrap().to_str().unwrap();
+    MethodStates {
+        method: cfg.name(),
+        blocks,
+    }
+    .dump(source_filename);
+}
+
+fn fold_unfold_state(pctxt: Option<&Option<PathCtxt>>) -> Option<FoldUnfoldState> {
+    let lines = |text: String| {
+        text.lines()
+            .map(|line| line.trim().trim_end_matches(',').to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    };
+    pctxt.and_then(Option::as_ref).map(|pctxt| FoldUnfoldState {
+        acc: lines(pctxt.state().display_acc()),
+        pred: lines(pctxt.state().display_pred()),
+    })
+}
+
 #[derive(Debug)]
 pub(super) struct ActionVec(pub Vec<Action>);
 
@@ -452,8 +499,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> vir::CfgReplacer<PathCtxt<'p>, ActionVec> for FoldUnf
         &self,
         new_cfg: &vir::CfgMethod,
         initial_pctxt: &[Option<PathCtxt>],
-        _final_pctxt: &[Option<PathCtxt>],
+        final_pctxt: &[Option<PathCtxt>],
     ) {
+        if self.dump_debug_session {
+            dump_method_states(self.encoder, new_cfg, initial_pctxt, final_pctxt);
+        }
         if self.dump_debug_info {
             let source_path = self.encoder.env().name.source_path();
             let source_filename = source_path.file_name().unwrap().to_str().unwrap();
@@ -236,6 +236,11 @@ impl<'v, 'tcx: 'v> HighBuiltinFunctionEncoderInterface<'tcx>
             BuiltinFunctionKind::SliceLen { elem_pred_type, .. } => {
                 ("Slice$len".to_string(), vec![elem_pred_type.clone()])
             }
+            BuiltinFunctionKind::Constant {
+                name,
+                type_arguments,
+                ..
+            } => (format!("builtin$const${}", name), type_arguments.clone()),
         }
     }
 }
@@ -0,0 +1,250 @@
+//! An on-disk cache of the `vir_mid` encodings of procedures.
+//!
+//! If the `encoding_cache_path` flag is set, the result of encoding a
+//! procedure for the core proof is stored on disk under a key computed from
+//! the def path of the procedure, its MIR, the declarations of the types it
+//! uses, the specifications of the procedure and of the procedures it calls,
+//! and the Prusti build. A later Prusti run that computes
+//! the same key reuses the stored procedure, which skips both the MIR encoding
+//! and the fold-unfold inference. The positions registered while encoding are
+//! stored together with the procedure so that the verification errors can be
+//! reported at the right spans.
+
+use super::inference::build_fingerprint;
+use crate::encoder::{
+    errors::ExportedErrorPosition, mir::specifications::SpecificationsInterface, Encoder,
+};
+use log::{debug, warn};
+use prusti_common::config;
+use prusti_interface::specs::typed;
+use prusti_rustc_interface::{
+    hir::def_id::DefId,
+    middle::{
+        mir,
+        ty::{self, TyCtxt},
+    },
+};
+use rustc_hash::FxHashSet;
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+use vir_crate::{common::check_mode::CheckMode, middle as vir_mid};
+
+pub(super) struct EncodingCacheKey {
+    key: u64,
+    /// The procedure followed by the procedures it calls. The positions
+    /// stored with the encoding refer to their def_ids by the index in this
+    /// list, because def_ids are not stable across runs.
+    def_ids: Vec<DefId>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CachedEncoding {
+    procedure: vir_mid::ProcedureDecl,
+    positions: Vec<ExportedErrorPosition>,
+}
+
+/// Computes the key under which the encoding of the procedure is cached.
+/// Returns `None` for procedures without a local MIR body and if the build of
+/// Prusti cannot be identified.
+///
+/// As for the fold-unfold inference cache, the `Debug` representations are
+/// hashed because they contain the spans, which end up in the positions of
+/// the encoding.
+pub(super) fn encoding_cache_key(
+    encoder: &Encoder<'_, '_>,
+    proc_def_id: DefId,
+    check_mode: CheckMode,
+) -> Option<EncodingCacheKey> {
+    let env = encoder.env();
+    let body = env.body.get_impure_fn_body_identity(proc_def_id.as_local()?);
+    let mut hasher = DefaultHasher::new();
+    build_fingerprint()?.hash(&mut hasher);
+    config::dump().hash(&mut hasher);
+    env.name.get_item_def_path(proc_def_id).hash(&mut hasher);
+    format!("{:?}", check_mode).hash(&mut hasher);
+    format!("{:?}", *body).hash(&mut hasher);
+    // The MIR refers to the types only by their names, but the inferred folds and unfolds
+    // depend on their declarations.
+    hash_type_decls(
+        env.tcx(),
+        body.local_decls.iter().map(|local| local.ty),
+        &mut FxHashSet::default(),
+        &mut hasher,
+    );
+    let substs = env.query.identity_substs(proc_def_id);
+    let specs = encoder.get_procedure_specs(proc_def_id, substs);
+    hash_specs(encoder, proc_def_id, specs, &mut hasher);
+    let mut def_ids = vec![proc_def_id];
+    let calls = body
+        .basic_blocks
+        .iter()
+        .filter_map(|block| block.terminator.as_ref())
+        .filter_map(|terminator| match &terminator.kind {
+            mir::TerminatorKind::Call { func, .. } => Some(func.ty(&*body, env.tcx())),
+            _ => None,
+        });
+    for func_ty in calls {
+        if let ty::TyKind::FnDef(called_def_id, call_substs) = func_ty.kind() {
+            if !def_ids.contains(called_def_id) {
+                def_ids.push(*called_def_id);
+            }
+            env.name.get_item_def_path(*called_def_id).hash(&mut hasher);
+            let specs =
+                encoder.get_procedure_specs_for_call(*called_def_id, proc_def_id, call_substs);
+            hash_specs(encoder, proc_def_id, specs, &mut hasher);
+        }
+    }
+    Some(EncodingCacheKey {
+        key: hasher.finish(),
+        def_ids,
+    })
+}
+
+/// Hashes the declarations of the ADTs that occur in the given types, including the ADTs of
+/// their fields. The ADTs in `visited` are skipped.
+fn hash_type_decls<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    types: impl IntoIterator<Item = ty::Ty<'tcx>>,
+    visited: &mut FxHashSet<ty::Ty<'tcx>>,
+    hasher: &mut DefaultHasher,
+) {
+    let mut pending: Vec<_> = types.into_iter().collect();
+    pending.reverse();
+    while let Some(ty) = pending.pop() {
+        for arg in ty.walk() {
+            let ty::subst::GenericArgKind::Type(ty) = arg.unpack() else {
+                continue;
+            };
+            let ty::TyKind::Adt(adt_def, substs) = ty.kind() else {
+                continue;
+            };
+            if !visited.insert(ty) {
+                continue;
+            }
+            format!("{:?}", ty).hash(hasher);
+            for variant in adt_def.variants() {
+                variant.name.as_str().hash(hasher);
+                format!("{:?}", variant.discr).hash(hasher);
+                for field in &variant.fields {
+                    let field_ty = field.ty(tcx, substs);
+                    field.name.as_str().hash(hasher);
+                    format!("{:?}", field_ty).hash(hasher);
+                    pending.push(field_ty);
+                }
+            }
+        }
+    }
+}
+
+/// Hashes the kind of the procedure and the bodies of its specifications.
+fn hash_specs(
+    encoder: &Encoder<'_, '_>,
+    caller_def_id: DefId,
+    specs: Option<typed::ProcedureSpecification>,
+    hasher: &mut DefaultHasher,
+) {
+    specs.is_some().hash(hasher);
+    let Some(specs) = specs else {
+        return;
+    };
+    let env = encoder.env();
+    format!("{:?}", specs.kind.extract_with_selective_replacement()).hash(hasher);
+    specs.trusted.extract_with_selective_replacement().hash(hasher);
+    let pres = specs.pres.extract_with_selective_replacement();
+    let posts = specs.posts.extract_with_selective_replacement();
+    let pledges = specs.pledges.extract_with_selective_replacement();
+    let spec_functions = pres
+        .into_iter()
+        .chain(posts)
+        .flatten()
+        .copied()
+        .chain(
+            pledges
+                .into_iter()
+                .flatten()
+                .flat_map(|pledge| pledge.lhs.into_iter().chain([pledge.rhs])),
+        );
+    let mut visited = FxHashSet::default();
+    for spec_def_id in spec_functions {
+        let substs = env.query.identity_substs(spec_def_id);
+        let body = env.body.get_spec_body(spec_def_id, substs, caller_def_id);
+        format!("{:?}", *body).hash(hasher);
+        hash_type_decls(
+            env.tcx(),
+            body.local_decls.iter().map(|local| local.ty),
+            &mut visited,
+            hasher,
+        );
+    }
+}
+
+fn encoding_cache_file(cache_dir: &Path, key: u64) -> PathBuf {
+    cache_dir.join(format!("{:016x}.bin", key))
+}
+
+/// Loads the encoding of the procedure from the disk and registers its
+/// positions. The encoding cannot be reused if some of its position ids are
+/// already used in this run.
+pub(super) fn load_cached_encoding(
+    encoder: &mut Encoder<'_, '_>,
+    cache_dir: &Path,
+    key: &EncodingCacheKey,
+) -> Option<vir_mid::ProcedureDecl> {
+    let file = fs::File::open(encoding_cache_file(cache_dir, key.key)).ok()?;
+    let cached: CachedEncoding = match bincode::deserialize_from(io::BufReader::new(file)) {
+        Ok(cached) => cached,
+        Err(error) => {
+            warn!("Failed to read the cached encoding: {error}");
+            return None;
+        }
+    };
+    if !encoder
+        .error_manager()
+        .import_positions(&cached.positions, &key.def_ids)
+    {
+        debug!("the positions of {} cannot be registered", cached.procedure.name);
+        return None;
+    }
+    Some(cached.procedure)
+}
+
+/// Stores the encoding of the procedure on the disk. Must be called right
+/// after encoding the procedure with the positions being recorded. The
+/// encoding is written to a temporary file that is then renamed, so that
+/// concurrent Prusti runs never read a partially written encoding.
+pub(super) fn store_cached_encoding(
+    encoder: &mut Encoder<'_, '_>,
+    cache_dir: &Path,
+    key: &EncodingCacheKey,
+    procedure: &vir_mid::ProcedureDecl,
+) {
+    let Some(positions) = encoder.error_manager().export_recorded_positions(&key.def_ids) else {
+        debug!("the encoding of {} refers to positions of other procedures", procedure.name);
+        return;
+    };
+    let cached = CachedEncoding {
+        procedure: procedure.clone(),
+        positions,
+    };
+    let file = encoding_cache_file(cache_dir, key.key);
+    let temporary_file = file.with_extension(format!("bin.{}.tmp", process::id()));
+    let result = fs::create_dir_all(cache_dir)
+        .and_then(|()| fs::File::create(&temporary_file))
+        .map_err(|error| error.to_string())
+        .and_then(|temporary| {
+            let mut writer = io::BufWriter::new(temporary);
+            bincode::serialize_into(&mut writer, &cached).map_err(|error| error.to_string())?;
+            writer.flush().map_err(|error| error.to_string())
+        })
+        .and_then(|()| fs::rename(&temporary_file, &file).map_err(|error| error.to_string()));
+    if let Err(error) = result {
+        warn!("Failed to cache the encoding: {error}");
+        let _ = fs::remove_file(&temporary_file);
+    }
+}
@@ -0,0 +1,457 @@
+//! Caches of the fold-unfold inference results.
+//!
+//! The same procedure is typically inferred several times (for example, once
+//! for each check mode) and most of its blocks do not change between the
+//! runs. If a block has the same statements and is entered with the same
+//! fold-unfold state as in a previous run, the result of the previous run can
+//! be reused instead of inferring it again. This cache is kept in memory
+//! during a single Prusti run (see the `enable_fold_unfold_block_cache` flag).
+//!
+//! In addition, if the `fold_unfold_cache_path` flag is set, the inferred
+//! procedures are stored on disk so that unchanged procedures do not need to
+//! be inferred again by later Prusti runs.
+
+use super::state::FoldUnfoldState;
+use crate::encoder::{
+    errors::ErrorCtxt, high::to_typed::types::HighToTypedTypeEncoderInterface,
+    mir::errors::ErrorInterface, Encoder,
+};
+use log::{debug, warn};
+use prusti_common::config;
+use rustc_hash::FxHashMap;
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use vir_crate::{middle as vir_mid, typed as vir_typed};
+
+/// The maximum number of blocks in the in-memory cache. When the cache is
+/// full, it is cleared.
+const MAX_CACHED_BLOCKS: usize = 10_000;
+
+#[derive(Default)]
+pub(crate) struct FoldUnfoldInferenceState {
+    blocks: FxHashMap<BlockCacheKey, CachedBlock>,
+    hits: usize,
+    misses: usize,
+}
+
+pub(super) struct CachedBlock {
+    pub(super) statements: Vec<vir_mid::Statement>,
+    pub(super) exit_state: FoldUnfoldState,
+}
+
+impl FoldUnfoldInferenceState {
+    pub(super) fn get_block(&mut self, key: &BlockCacheKey) -> Option<&CachedBlock> {
+        let block = self.blocks.get(key);
+        if block.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        block
+    }
+
+    pub(super) fn insert_block(&mut self, key: BlockCacheKey, block: CachedBlock) {
+        if self.blocks.len() >= MAX_CACHED_BLOCKS {
+            debug!("the fold-unfold inference cache is full, clearing it");
+            self.blocks.clear();
+        }
+        self.blocks.insert(key, block);
+    }
+
+    /// Returns the number of cache hits and misses.
+    pub(super) fn statistics(&self) -> (usize, usize) {
+        (self.hits, self.misses)
+    }
+}
+
+/// The key under which the inference result of a block is cached.
+///
+/// The `Hash` implementations of VIR ignore positions, but the positions have
+/// to be part of the key because they end up in the inferred statements.
+/// Therefore, the key consists of the `Debug` representations, which contain
+/// them. The whole representations are compared on lookup, so that a hash
+/// collision cannot lead to reusing the result of a different block.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(super) struct BlockCacheKey {
+    statements: String,
+    entry_state: String,
+}
+
+pub(super) fn block_cache_key(
+    statements: &[vir_typed::Statement],
+    entry_state: &FoldUnfoldState,
+) -> BlockCacheKey {
+    BlockCacheKey {
+        statements: format!("{:?}", statements),
+        entry_state: format!("{:?}", entry_state),
+    }
+}
+
+/// The parts of the encoder state on which the inference result of a
+/// procedure depends.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub(super) struct InferenceDependencies {
+    /// The positions created by the inference in the order of their creation.
+    positions: Vec<CreatedPosition>,
+    /// The fingerprints of the type declarations used by the inference.
+    type_decls: Vec<(vir_typed::Type, u64)>,
+    /// Set if some blocks were taken from the in-memory cache. The positions
+    /// created for them were registered by an earlier inference, so the
+    /// result cannot be reproduced by replaying `positions`.
+    incomplete: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CreatedPosition {
+    original: vir_typed::Position,
+    error_ctxt: ErrorCtxt,
+    created: vir_typed::Position,
+}
+
+impl InferenceDependencies {
+    pub(super) fn record_position(
+        &mut self,
+        original: vir_typed::Position,
+        error_ctxt: ErrorCtxt,
+        created: vir_typed::Position,
+    ) {
+        self.positions.push(CreatedPosition {
+            original,
+            error_ctxt,
+            created,
+        });
+    }
+
+    pub(super) fn record_type_decl(
+        &mut self,
+        ty: &vir_typed::Type,
+        type_decl: &vir_typed::TypeDecl,
+    ) {
+        if !self.type_decls.iter().any(|(recorded, _)| recorded == ty) {
+            self.type_decls.push((ty.clone(), type_decl_fingerprint(type_decl)));
+        }
+    }
+
+    pub(super) fn mark_incomplete(&mut self) {
+        self.incomplete = true;
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CachedProcedure {
+    key: ProcedureCacheKey,
+    procedure: vir_mid::ProcedureDecl,
+    dependencies: InferenceDependencies,
+}
+
+fn type_decl_fingerprint(type_decl: &vir_typed::TypeDecl) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", type_decl).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Identifies the build of Prusti. Results stored by a different build are
+/// not reused because the inference or the VIR might have changed. Returns
+/// `None` if the build cannot be identified.
+pub(in super::super) fn build_fingerprint() -> Option<u64> {
+    let metadata = std::env::current_exe().ok()?.metadata().ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    modified.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// The key under which the inference result of a procedure is stored on
+/// disk. As for blocks, the key includes the positions. The whole key is
+/// stored together with the result and compared on load, so that a
+/// collision of the file names cannot lead to reusing the result of a
+/// different procedure.
+#[derive(PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(super) struct ProcedureCacheKey {
+    build: u64,
+    join_optimization: bool,
+    procedure: String,
+}
+
+impl ProcedureCacheKey {
+    fn file_name(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.build.hash(&mut hasher);
+        self.join_optimization.hash(&mut hasher);
+        self.procedure.hash(&mut hasher);
+        format!("{:016x}.bin", hasher.finish())
+    }
+}
+
+pub(super) fn procedure_cache_key(
+    procedure: &vir_typed::ProcedureDecl,
+) -> Option<ProcedureCacheKey> {
+    Some(ProcedureCacheKey {
+        build: build_fingerprint()?,
+        join_optimization: config::enable_fold_unfold_join_optimization(),
+        procedure: format!("{:?}", procedure),
+    })
+}
+
+fn procedure_cache_file(cache_dir: &Path, key: &ProcedureCacheKey) -> PathBuf {
+    cache_dir.join(key.file_name())
+}
+
+fn read_cached_procedure(cache_dir: &Path, key: &ProcedureCacheKey) -> Option<CachedProcedure> {
+    let file = fs::File::open(procedure_cache_file(cache_dir, key)).ok()?;
+    let cached: CachedProcedure = match bincode::deserialize_from(io::BufReader::new(file)) {
+        Ok(cached) => cached,
+        Err(error) => {
+            warn!("Failed to read the cached fold-unfold inference result: {error}");
+            return None;
+        }
+    };
+    if cached.key != *key {
+        debug!("the cached fold-unfold inference result belongs to a different procedure");
+        return None;
+    }
+    Some(cached)
+}
+
+/// Loads the inference result of the procedure from the disk.
+///
+/// The result can be reused only if the type declarations used by the
+/// inference did not change and the positions created by the inference get
+/// the same identifiers as in the run that stored the result. The
+/// identifiers are checked before any position is created, so that a result
+/// that cannot be reused leaves the error manager unchanged.
+pub(super) fn load_cached_procedure(
+    encoder: &mut Encoder<'_, '_>,
+    cache_dir: &Path,
+    key: &ProcedureCacheKey,
+) -> Option<vir_mid::ProcedureDecl> {
+    let cached = read_cached_procedure(cache_dir, key)?;
+    if cached.dependencies.incomplete {
+        return None;
+    }
+    for (ty, fingerprint) in &cached.dependencies.type_decls {
+        match encoder.encode_type_def_typed(ty) {
+            Ok(type_decl) if type_decl_fingerprint(&type_decl) == *fingerprint => {}
+            _ => {
+                debug!("the declaration of {} changed", ty);
+                return None;
+            }
+        }
+    }
+    let originals: Vec<_> = cached
+        .dependencies
+        .positions
+        .iter()
+        .map(|position| position.original.into())
+        .collect();
+    let predicted = encoder
+        .error_manager()
+        .position_manager()
+        .predict_duplicates(&originals);
+    let expected = cached
+        .dependencies
+        .positions
+        .iter()
+        .map(|position| position.created.id());
+    if !predicted.map_or(false, |predicted| predicted.into_iter().eq(expected)) {
+        debug!("the positions created by the cached inference result changed");
+        return None;
+    }
+    for position in cached.dependencies.positions {
+        let created = encoder.change_error_context(position.original, position.error_ctxt);
+        debug_assert_eq!(created.id(), position.created.id());
+    }
+    Some(cached.procedure)
+}
+
+/// Stores the inference result of the procedure on the disk.
+pub(super) fn store_cached_procedure(
+    cache_dir: &Path,
+    key: ProcedureCacheKey,
+    procedure: &vir_mid::ProcedureDecl,
+    dependencies: InferenceDependencies,
+) {
+    if dependencies.incomplete {
+        return;
+    }
+    let file = procedure_cache_file(cache_dir, &key);
+    let cached = CachedProcedure {
+        key,
+        procedure: procedure.clone(),
+        dependencies,
+    };
+    let result = fs::create_dir_all(cache_dir)
+        .and_then(|()| fs::File::create(file))
+        .map_err(|error| error.to_string())
+        .and_then(|file| {
+            bincode::serialize_into(io::BufWriter::new(file), &cached)
+                .map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Failed to cache the fold-unfold inference result: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vir_crate::common::check_mode::CheckMode;
+
+    fn cached_block() -> CachedBlock {
+        CachedBlock {
+            statements: Vec::new(),
+            exit_state: FoldUnfoldState::new(),
+        }
+    }
+
+    fn comment(text: &str) -> vir_typed::Statement {
+        vir_typed::Statement::comment(text.to_string())
+    }
+
+    #[test]
+    fn test_block_cache_key() {
+        let state = FoldUnfoldState::new();
+        assert_eq!(
+            block_cache_key(&[comment("a")], &state),
+            block_cache_key(&[comment("a")], &state)
+        );
+        assert_ne!(
+            block_cache_key(&[comment("a")], &state),
+            block_cache_key(&[comment("b")], &state)
+        );
+        assert_ne!(
+            block_cache_key(&[comment("a")], &state),
+            block_cache_key(&[comment("a"), comment("a")], &state)
+        );
+    }
+
+    #[test]
+    fn test_block_cache_lookup() {
+        let state = FoldUnfoldState::new();
+        let mut cache = FoldUnfoldInferenceState::default();
+        cache.insert_block(block_cache_key(&[comment("a")], &state), cached_block());
+        assert!(cache
+            .get_block(&block_cache_key(&[comment("a")], &state))
+            .is_some());
+        assert!(cache
+            .get_block(&block_cache_key(&[comment("b")], &state))
+            .is_none());
+        assert_eq!(cache.statistics(), (1, 1));
+    }
+
+    #[test]
+    fn test_block_cache_eviction() {
+        let state = FoldUnfoldState::new();
+        let mut cache = FoldUnfoldInferenceState::default();
+        for i in 0..MAX_CACHED_BLOCKS {
+            cache.insert_block(
+                block_cache_key(&[comment(&i.to_string())], &state),
+                cached_block(),
+            );
+        }
+        assert_eq!(cache.blocks.len(), MAX_CACHED_BLOCKS);
+        cache.insert_block(block_cache_key(&[comment("new")], &state), cached_block());
+        assert_eq!(cache.blocks.len(), 1);
+        assert!(cache
+            .get_block(&block_cache_key(&[comment("new")], &state))
+            .is_some());
+    }
+
+    fn procedure_key(procedure: &str) -> ProcedureCacheKey {
+        ProcedureCacheKey {
+            build: 1,
+            join_optimization: true,
+            procedure: procedure.to_string(),
+        }
+    }
+
+    fn procedure_decl(name: &str) -> vir_mid::ProcedureDecl {
+        let entry = vir_mid::BasicBlockId::new("entry".to_string());
+        let mut basic_blocks = std::collections::BTreeMap::new();
+        basic_blocks.insert(
+            entry.clone(),
+            vir_mid::BasicBlock {
+                statements: Vec::new(),
+                successor: vir_mid::Successor::Exit,
+            },
+        );
+        vir_mid::ProcedureDecl {
+            name: name.to_string(),
+            check_mode: CheckMode::CoreProof,
+            entry: entry.clone(),
+            exit: entry,
+            basic_blocks,
+        }
+    }
+
+    fn cache_dir(test: &str) -> PathBuf {
+        let cache_dir = std::env::temp_dir().join(format!(
+            "prusti-fold-unfold-cache-{}-{}",
+            test,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&cache_dir);
+        cache_dir
+    }
+
+    #[test]
+    fn test_procedure_cache_round_trip() {
+        let cache_dir = cache_dir("round-trip");
+        let key = procedure_key("procedure");
+        assert!(read_cached_procedure(&cache_dir, &key).is_none());
+        store_cached_procedure(
+            &cache_dir,
+            procedure_key("procedure"),
+            &procedure_decl("f"),
+            InferenceDependencies::default(),
+        );
+        let cached = read_cached_procedure(&cache_dir, &key).unwrap();
+        assert_eq!(cached.procedure, procedure_decl("f"));
+        assert!(cached.dependencies.positions.is_empty());
+        assert!(read_cached_procedure(&cache_dir, &procedure_key("other")).is_none());
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_procedure_cache_compares_full_key() {
+        let cache_dir = cache_dir("full-key");
+        let key = procedure_key("procedure");
+        let other_key = procedure_key("other");
+        store_cached_procedure(
+            &cache_dir,
+            procedure_key("procedure"),
+            &procedure_decl("f"),
+            InferenceDependencies::default(),
+        );
+        // Simulate a collision of the file names.
+        fs::rename(
+            procedure_cache_file(&cache_dir, &key),
+            procedure_cache_file(&cache_dir, &other_key),
+        )
+        .unwrap();
+        assert!(read_cached_procedure(&cache_dir, &other_key).is_none());
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_procedure_cache_skips_incomplete() {
+        let cache_dir = cache_dir("incomplete");
+        let mut dependencies = InferenceDependencies::default();
+        dependencies.mark_incomplete();
+        store_cached_procedure(
+            &cache_dir,
+            procedure_key("procedure"),
+            &procedure_decl("f"),
+            dependencies,
+        );
+        assert!(read_cached_procedure(&cache_dir, &procedure_key("procedure")).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     action::Action,
-    permission::{Permission, PermissionKind},
+    permission::{HeapBlock, Permission, PermissionKind},
     state::PredicateStateOnPath,
     FoldUnfoldState,
 };
@@ -33,6 +33,8 @@ pub(in super::super) trait Context {
         guiding_place: &vir_typed::Expression,
     ) -> SpannedEncodingResult<Vec<(ExpandedPermissionKind, vir_typed::Expression)>>;
     fn get_span(&mut self, position: vir_typed::Position) -> Option<MultiSpan>;
+    /// The span of the statement whose requirements are being ensured.
+    fn get_current_statement_span(&mut self) -> Option<MultiSpan>;
     fn change_error_context(
         &mut self,
         position: vir_typed::Position,
@@ -139,6 +141,10 @@ pub(in super::super) fn ensure_required_permission(
         Permission::MemoryBlock(place) => (place, PermissionKind::MemoryBlock),
         Permission::Owned(place) => (place, PermissionKind::Owned),
         Permission::MutBorrowed(borrow) => unreachable!("requiring a borrow: {}", borrow),
+        Permission::MemoryBlockHeap(block) => {
+            ensure_heap_block(state, &block);
+            return Ok(());
+        }
     };
 
     let base = place.get_base().erase_lifetime();
@@ -158,13 +164,34 @@ pub(in super::super) fn ensure_required_permission(
         PredicateState::Conditional(conditional_predicate_states) => {
             for (condition, conditional_predicate_state) in conditional_predicate_states {
                 let mut conditional_actions = Vec::new();
-                let to_drop = ensure_permission_in_state(
+                let to_drop = if can_place_be_ensured_in(
                     context,
-                    conditional_predicate_state,
-                    place.clone(),
+                    &place,
                     permission_kind,
-                    &mut conditional_actions,
-                )?;
+                    conditional_predicate_state,
+                )? {
+                    ensure_permission_in_state(
+                        context,
+                        conditional_predicate_state,
+                        place.clone(),
+                        permission_kind,
+                        &mut conditional_actions,
+                    )?
+                } else {
+                    // The permission is available only on some of the incoming
+                    // paths (for example, the place is initialized only in one
+                    // branch), so the paths on which it is missing have to be
+                    // unreachable. Instead of reporting an error, we assert
+                    // that and drop the state of these paths.
+                    debug!(
+                        "  unobtainable on {}: {:?} {}",
+                        condition, permission_kind, place
+                    );
+                    let position = context
+                        .change_error_context(place.position(), ErrorCtxt::UnreachableFoldingState);
+                    conditional_actions.push(Action::unreachable(position));
+                    true
+                };
                 // Even if the state is unreachable, we add the actions because
                 // one of them should be the marker that the state is
                 // unreachable.
@@ -185,6 +212,18 @@ pub(in super::super) fn ensure_required_permission(
     Ok(())
 }
 
+/// Heap memory blocks are never folded or unfolded. The inference identifies
+/// them by the expression of their address, so it cannot relate blocks whose
+/// addresses are given by different, but equal, pointers (for example, a copy
+/// of the pointer returned by `alloc`). Therefore, a required block that is
+/// not in the state is not an error here: whether the memory is available is
+/// checked by the verifier.
+fn ensure_heap_block(state: &FoldUnfoldState, block: &HeapBlock) {
+    if !state.contains_heap_block(block) {
+        debug!("leaving the check of {} to the verifier", block);
+    }
+}
+
 fn check_can_place_be_ensured_in(
     context: &mut impl Context,
     place: &vir_typed::Expression,
@@ -203,6 +242,13 @@ fn check_can_place_be_ensured_in(
     let by_folding_discriminant = predicate_state
         .contains_discriminant_with_prefix(place)
         .is_some();
+    // The requirement can be satisfied by converting the address of a
+    // reference (see `check_contains_place`).
+    let by_reference_address = permission_kind == PermissionKind::MemoryBlock && {
+        let address_place = reference_address_place(place);
+        predicate_state.contains(PermissionKind::MemoryBlock, &address_place)
+            || predicate_state.contains(PermissionKind::Owned, &address_place)
+    };
     // The requirement can be satisfied by restoring a mutable borrow.
     let by_restoring_blocked = predicate_state.contains_blocked(place)?.is_some();
     // The requirement can be satisfied by converting into Memory Block.
@@ -227,42 +273,19 @@ fn check_can_place_be_ensured_in(
             predicate_state,
             false,
         )?;
+    // The requirement can be satisfied by switching the active variant of an
+    // enum or union.
+    let by_switching_variant =
+        find_conflicting_variant_base(predicate_state, permission_kind, place).is_some();
     let can = already_satisfied
+        || by_reference_address
         || by_unfolding
         || by_folding
         || by_folding_discriminant
         || by_restoring_blocked
+        || by_switching_variant
         || by_into_memory_block
         || by_into_owned;
-    if !can {
-        // Check whether required_permission conflicts with state (has a
-        // different variant) and report an error to the user suggesting that
-        // they should fold.
-        for prefix in place.iter_prefixes() {
-            if let vir_typed::Expression::Variant(variant) = prefix {
-                for prefixed in predicate_state.get_all_with_prefix(permission_kind, &variant.base)
-                {
-                    if !prefixed.has_prefix(prefix) && !prefixed.is_discriminant_field() {
-                        let place_span = context.get_span(place.position()).unwrap();
-                        let prefixed_span = context.get_span(prefixed.position()).unwrap();
-                        let mut error = SpannedEncodingError::unsupported(
-                            "failed to obtain the required capability because a conflicting \
-                                    capability is present",
-                            place_span,
-                        );
-                        error.add_note(
-                            "this typically happens when trying to read from different union fields \
-                            because Prusti does not yet support reinterpreting memory",
-                            None,
-                        );
-                        error.add_note("the conflicting capability", Some(prefixed_span));
-                        error.set_help("try manually packaging the union capability");
-                        return Err(error);
-                    }
-                }
-            }
-        }
-    }
     Ok(can)
 }
 
@@ -275,6 +298,42 @@ fn can_place_be_ensured_in(
     check_can_place_be_ensured_in(context, place, permission_kind, predicate_state, true)
 }
 
+/// If `place` goes through a variant of an enum or union while the state
+/// contains capabilities of another variant of the same value, returns the
+/// place of the value. Since the fields of different variants occupy the same
+/// memory, the capabilities of the other variant have to be folded back into
+/// the value before the required variant can be unfolded.
+fn find_conflicting_variant_base(
+    predicate_state: &PredicateStateOnPath,
+    permission_kind: PermissionKind,
+    place: &vir_typed::Expression,
+) -> Option<vir_typed::Expression> {
+    for prefix in place.iter_prefixes() {
+        if let vir_typed::Expression::Variant(variant) = prefix {
+            let has_conflict = predicate_state
+                .get_all_with_prefix(permission_kind, &variant.base)
+                .any(|prefixed| !prefixed.has_prefix(prefix) && !prefixed.is_discriminant_field());
+            if has_conflict {
+                return Some((*variant.base).clone());
+            }
+        }
+    }
+    None
+}
+
+/// The place of the address of the reference `place`.
+fn reference_address_place(place: &vir_typed::Expression) -> vir_typed::Expression {
+    vir_typed::Expression::field(
+        place.clone(),
+        vir_typed::FieldDecl::new(
+            "address$",
+            0usize,
+            vir_typed::Type::Int(vir_typed::ty::Int::Usize),
+        ),
+        place.position(),
+    )
+}
+
 fn check_contains_place(
     predicate_state: &mut PredicateStateOnPath,
     place: &vir_typed::Expression,
@@ -283,15 +342,7 @@ fn check_contains_place(
     let contains = if predicate_state.contains(permission_kind, place) {
         true
     } else if permission_kind == PermissionKind::MemoryBlock {
-        let address_place = vir_typed::Expression::field(
-            place.clone(),
-            vir_typed::FieldDecl::new(
-                "address$",
-                0usize,
-                vir_typed::Type::Int(vir_typed::ty::Int::Usize),
-            ),
-            place.position(),
-        );
+        let address_place = reference_address_place(place);
         if predicate_state.contains(PermissionKind::MemoryBlock, &address_place) {
             predicate_state.remove(PermissionKind::MemoryBlock, &address_place)?;
             predicate_state.insert(PermissionKind::MemoryBlock, place.clone())?;
@@ -402,6 +453,17 @@ fn ensure_permission_in_state(
         actions.push(Action::fold(permission_kind, place.clone(), enum_variant));
         predicate_state.insert(permission_kind, place)?;
         false
+    } else if let Some(base) =
+        find_conflicting_variant_base(predicate_state, permission_kind, &place)
+    {
+        // The requirement can be satisfied by switching the active variant:
+        // fold the currently active variant into the value and then unfold
+        // the required one.
+        debug!("  switching the active variant of {}", base);
+        if ensure_permission_in_state(context, predicate_state, base, permission_kind, actions)? {
+            return Ok(true);
+        }
+        ensure_permission_in_state(context, predicate_state, place, permission_kind, actions)?
     } else if let Some((prefix, lifetime)) = predicate_state.contains_blocked(&place)? {
         let prefix = prefix.clone();
         let lifetime = lifetime.clone();
@@ -507,11 +569,53 @@ fn ensure_permission_in_state(
         }
     } else {
         // The requirement cannot be satisfied.
-        unreachable!(
-            "place={} permission_kind={:?} predicate_state={}",
-            place, permission_kind, predicate_state
-        );
+        return Err(missing_permission_error(context, &place, permission_kind, predicate_state));
     };
     predicate_state.check_consistency();
     Ok(to_drop)
 }
+
+/// Builds the error reported to the user when the required permission cannot
+/// be obtained from the current state. It points to the offending statement,
+/// because the failure is usually caused by the user's code, for example by a
+/// use of a moved value through a raw pointer.
+fn missing_permission_error(
+    context: &mut impl Context,
+    place: &vir_typed::Expression,
+    permission_kind: PermissionKind,
+    predicate_state: &PredicateStateOnPath,
+) -> SpannedEncodingError {
+    debug!(
+        "failed to obtain: place={} permission_kind={:?} predicate_state={}",
+        place, permission_kind, predicate_state
+    );
+    let statement_span = context.get_current_statement_span();
+    let place_span = context
+        .get_span(place.position())
+        .or_else(|| statement_span.clone())
+        .unwrap_or_else(MultiSpan::new);
+    let mut error = SpannedEncodingError::unsupported(
+        format!(
+            "failed to obtain the {} capability for the place `{}`",
+            match permission_kind {
+                PermissionKind::Owned => "ownership",
+                PermissionKind::MemoryBlock => "memory block",
+            },
+            place
+        ),
+        place_span,
+    );
+    error.add_note(
+        format!("the capabilities held at this point are:\n{}", predicate_state),
+        None,
+    );
+    if statement_span.is_some() {
+        error.add_note("the capability is required by this statement", statement_span);
+    }
+    error.set_help(
+        "this typically happens when a value is used after it was moved out or \
+        deallocated (for example, in unsafe code), or when a field of a \
+        partially moved value is accessed",
+    );
+    error
+}
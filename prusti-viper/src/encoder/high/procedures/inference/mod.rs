@@ -9,17 +9,26 @@
 //!
 //! The data structures used for tracking the state are in the `state` module.
 
-use self::{state::FoldUnfoldState, visitor::Visitor};
+pub(crate) use self::cache::FoldUnfoldInferenceState;
+pub(super) use self::cache::build_fingerprint;
+use self::{
+    cache::{load_cached_procedure, procedure_cache_key, store_cached_procedure},
+    state::FoldUnfoldState,
+    visitor::Visitor,
+};
 use crate::encoder::{errors::SpannedEncodingResult, Encoder};
+use log::debug;
 use prusti_common::config;
+use prusti_interface::PrustiError;
 use prusti_rustc_interface::hir::def_id::DefId;
 use vir_crate::{
-    common::graphviz::ToGraphviz,
+    common::graphviz::{Graph, ToGraphviz},
     middle::{self as vir_mid},
     typed::{self as vir_typed},
 };
 
 mod action;
+mod cache;
 mod ensurer;
 mod permission;
 mod semantics;
@@ -33,23 +42,99 @@ pub(super) fn infer_shape_operations<'v, 'tcx: 'v>(
 ) -> SpannedEncodingResult<vir_mid::ProcedureDecl> {
     if config::dump_debug_info() {
         let source_filename = encoder.env().name.source_file_name();
-        prusti_common::report::log::report_with_writer(
+        report_graph(
             "graphviz_method_before_foldunfold",
-            format!("{}.{}.dot", source_filename, procedure.name),
-            |writer| procedure.to_graphviz(writer).unwrap(),
+            format!("{}.{}", source_filename, procedure.name),
+            &procedure.to_graph(),
+        );
+    }
+    let cache_dir = config::fold_unfold_cache_path();
+    let cache_key = if cache_dir.as_os_str().is_empty() {
+        None
+    } else {
+        procedure_cache_key(&procedure)
+    };
+    let cached_procedure = cache_key
+        .as_ref()
+        .and_then(|key| load_cached_procedure(encoder, &cache_dir, key));
+    let shaped_procedure = if let Some(shaped_procedure) = cached_procedure {
+        debug!(
+            "reusing the cached fold-unfold inference result of {}",
+            shaped_procedure.name
         );
+        shaped_procedure
+    } else {
+        let mut visitor = Visitor::new(encoder, proc_def_id);
+        let initial_state = FoldUnfoldState::new();
+        let shaped_procedure = visitor.infer_procedure(procedure, initial_state)?;
+        let dependencies = visitor.take_dependencies();
+        visitor.cancel_crash_graphviz();
+        if let Some(key) = cache_key {
+            store_cached_procedure(&cache_dir, key, &shaped_procedure, dependencies);
+        }
+        shaped_procedure
+    };
+    if config::report_fold_unfold_statistics() {
+        let (operations, conditional_operations) = count_fold_unfold_operations(&shaped_procedure);
+        PrustiError::warning(
+            format!(
+                "the fold-unfold inference added {} operations, {} of them conditional",
+                operations, conditional_operations
+            ),
+            encoder.env().query.get_def_span(proc_def_id).into(),
+        )
+        .emit(&encoder.env().diagnostic);
     }
-    let mut visitor = Visitor::new(encoder, proc_def_id);
-    let initial_state = FoldUnfoldState::new();
-    let shaped_procedure = visitor.infer_procedure(procedure, initial_state)?;
-    visitor.cancel_crash_graphviz();
     if config::dump_debug_info() {
         let source_filename = encoder.env().name.source_file_name();
-        prusti_common::report::log::report_with_writer(
+        report_graph(
             "graphviz_method_after_foldunfold",
-            format!("{}.{}.dot", source_filename, shaped_procedure.name),
-            |writer| shaped_procedure.to_graphviz(writer).unwrap(),
+            format!("{}.{}", source_filename, shaped_procedure.name),
+            &shaped_procedure.to_graph(),
         );
     }
     Ok(shaped_procedure)
 }
+
+/// Returns the number of `fold`, `unfold`, `join` and `split` operations of
+/// the procedure and how many of them are conditional.
+fn count_fold_unfold_operations(procedure: &vir_mid::ProcedureDecl) -> (usize, usize) {
+    let mut operations = 0;
+    let mut conditional_operations = 0;
+    for block in procedure.basic_blocks.values() {
+        for statement in &block.statements {
+            let condition = match statement {
+                vir_mid::Statement::FoldOwned(statement) => &statement.condition,
+                vir_mid::Statement::UnfoldOwned(statement) => &statement.condition,
+                vir_mid::Statement::FoldRef(statement) => &statement.condition,
+                vir_mid::Statement::UnfoldRef(statement) => &statement.condition,
+                vir_mid::Statement::JoinBlock(statement) => &statement.condition,
+                vir_mid::Statement::SplitBlock(statement) => &statement.condition,
+                _ => continue,
+            };
+            operations += 1;
+            if condition.is_some() {
+                conditional_operations += 1;
+            }
+        }
+    }
+    (operations, conditional_operations)
+}
+
+/// Dumps the graph as a `.dot` file or, if `dump_graphs_as_html` is set, as an
+/// HTML page.
+fn report_graph(namespace: &str, name: String, graph: &Graph) {
+    if config::dump_graphs_as_html() {
+        prusti_common::report::log::report_with_writer(
+            namespace,
+            format!("{}.html", name),
+            |writer| graph.write_html(writer).unwrap(),
+        );
+    } else {
+        prusti_common::report::log::report_with_writer(
+            namespace,
+            format!("{}.dot", name),
+            |writer| graph.write(writer).unwrap(),
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::encoder::errors::{SpannedEncodingError, SpannedEncodingResult};
+use prusti_rustc_interface::errors::MultiSpan;
 use vir_crate::typed as vir_typed;
 
 #[derive(Debug, Clone, derive_more::Display)]
@@ -10,6 +12,11 @@ pub(in super::super) enum Permission {
     Owned(vir_typed::Expression),
     /// TODO: Rename MutBorrowed into `Blocked`.
     MutBorrowed(MutBorrowed),
+    /// Raw memory identified by its address (for example, obtained from
+    /// `alloc` or through a raw pointer). Unlike the other permissions, it is
+    /// not rooted in a local variable and, therefore, is never folded or
+    /// unfolded.
+    MemoryBlockHeap(HeapBlock),
 }
 
 impl Permission {
@@ -23,11 +30,15 @@ impl Permission {
         }
     }
 
-    pub(in super::super) fn get_place(&self) -> &vir_typed::Expression {
+    pub(in super::super) fn get_place(&self) -> SpannedEncodingResult<&vir_typed::Expression> {
         match self {
-            Self::MemoryBlock(place) => place,
-            Self::Owned(place) => place,
-            Self::MutBorrowed(MutBorrowed { place, .. }) => place,
+            Self::MemoryBlock(place) => Ok(place),
+            Self::Owned(place) => Ok(place),
+            Self::MutBorrowed(MutBorrowed { place, .. }) => Ok(place),
+            Self::MemoryBlockHeap(block) => Err(SpannedEncodingError::internal(
+                format!("heap memory blocks are not rooted in places: {}", block),
+                MultiSpan::new(),
+            )),
         }
     }
 }
@@ -39,6 +50,13 @@ pub(in super::super) struct MutBorrowed {
     pub(in super::super) place: vir_typed::Expression,
 }
 
+#[derive(Debug, Clone, derive_more::Display, PartialEq, Eq, PartialOrd, Ord)]
+#[display(fmt = "MemoryBlockHeap({}, {})", address, size)]
+pub(in super::super) struct HeapBlock {
+    pub(in super::super) address: vir_typed::Expression,
+    pub(in super::super) size: vir_typed::Expression,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(in super::super) enum PermissionKind {
     MemoryBlock,
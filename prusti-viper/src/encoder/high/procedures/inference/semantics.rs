@@ -1,4 +1,4 @@
-use super::permission::{MutBorrowed, Permission};
+use super::permission::{HeapBlock, MutBorrowed, Permission};
 use crate::encoder::{
     errors::SpannedEncodingResult, high::to_typed::types::HighToTypedTypeEncoderInterface, Encoder,
 };
@@ -190,9 +190,14 @@ fn extract_managed_predicate_place(
         vir_typed::Predicate::OwnedNonAliased(predicate) => {
             Ok(Some(Permission::Owned(predicate.place.clone())))
         }
+        vir_typed::Predicate::MemoryBlockHeap(predicate) => {
+            Ok(Some(Permission::MemoryBlockHeap(HeapBlock {
+                address: predicate.address.clone(),
+                size: predicate.size.clone(),
+            })))
+        }
         vir_typed::Predicate::MemoryBlockStackDrop(_)
         | vir_typed::Predicate::LifetimeToken(_)
-        | vir_typed::Predicate::MemoryBlockHeap(_)
         | vir_typed::Predicate::MemoryBlockHeapDrop(_) => {
             // Unmanaged predicates.
             Ok(None)
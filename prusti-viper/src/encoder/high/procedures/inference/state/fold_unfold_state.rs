@@ -1,5 +1,6 @@
 use crate::encoder::{
-    errors::SpannedEncodingResult, high::procedures::inference::permission::Permission,
+    errors::SpannedEncodingResult,
+    high::procedures::inference::permission::{HeapBlock, Permission},
 };
 use log::debug;
 use std::collections::{BTreeMap, BTreeSet};
@@ -10,7 +11,7 @@ use vir_crate::{
 
 use super::PredicateState;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub(in super::super::super) struct FoldUnfoldState {
     /// If this state is a merge of multiple incoming states, then
     /// `incoming_labels` contains the list of basic blocks from where the
@@ -20,6 +21,10 @@ pub(in super::super::super) struct FoldUnfoldState {
     /// support only stack allocations. They can be uniquely identified by
     /// `VariableDecl` of their base.
     predicates: BTreeMap<vir_typed::VariableDecl, PredicateState>,
+    /// Memory blocks that are identified by their address instead of a place.
+    /// Since they are never folded or unfolded, we track only which of them
+    /// are available on all incoming paths.
+    heap_blocks: BTreeSet<HeapBlock>,
 }
 
 impl std::fmt::Display for FoldUnfoldState {
@@ -32,6 +37,12 @@ impl std::fmt::Display for FoldUnfoldState {
         for (variable, predicate) in &self.predicates {
             writeln!(f, "{}:\n{}", variable, predicate)?;
         }
+        if !self.heap_blocks.is_empty() {
+            writeln!(f, "heap blocks:")?;
+            for block in &self.heap_blocks {
+                writeln!(f, "  {}", block)?;
+            }
+        }
         Ok(())
     }
 }
@@ -41,6 +52,7 @@ impl FoldUnfoldState {
         Self {
             incoming_labels: Vec::new(),
             predicates: Default::default(),
+            heap_blocks: Default::default(),
         }
     }
 
@@ -238,11 +250,59 @@ impl FoldUnfoldState {
             };
             self.predicates.insert(root, merged_state);
         }
+        // A heap block is available after the merge only if it is available
+        // on all incoming paths; the others are leaked.
+        self.heap_blocks
+            .retain(|block| incoming_state.heap_blocks.contains(block));
         self.incoming_labels.push(incoming_label);
         self.check_no_default_position();
         Ok(())
     }
 
+    pub(in super::super) fn contains_heap_block(&self, block: &HeapBlock) -> bool {
+        self.heap_blocks.contains(block)
+    }
+
+    /// Returns the roots whose state is unconditional in both `self` and
+    /// `target` but different, together with the permissions held in
+    /// `target`. Roots with mutably borrowed places are skipped.
+    pub(in super::super) fn collect_misaligned_roots(
+        &self,
+        target: &Self,
+    ) -> Vec<(vir_typed::VariableDecl, Vec<Permission>)> {
+        let mut misaligned_roots = Vec::new();
+        for (root, target_predicate) in &target.predicates {
+            if let (
+                Some(PredicateState::Unconditional(self_state)),
+                PredicateState::Unconditional(target_state),
+            ) = (self.predicates.get(root), target_predicate)
+            {
+                if self_state != target_state {
+                    if let Some(permissions) = target_state.collect_permissions() {
+                        misaligned_roots.push((root.clone(), permissions));
+                    }
+                }
+            }
+        }
+        misaligned_roots
+    }
+
+    /// Returns true if the state of `root` is the same unconditional state in
+    /// both `self` and `other`.
+    pub(in super::super) fn is_aligned_on(
+        &self,
+        other: &Self,
+        root: &vir_typed::VariableDecl,
+    ) -> bool {
+        matches!(
+            (self.predicates.get(root), other.predicates.get(root)),
+            (
+                Some(PredicateState::Unconditional(self_state)),
+                Some(PredicateState::Unconditional(other_state)),
+            ) if self_state == other_state
+        )
+    }
+
     pub(in super::super) fn insert_permissions(
         &mut self,
         permissions: Vec<Permission>,
@@ -259,7 +319,13 @@ impl FoldUnfoldState {
         permission: Permission,
     ) -> SpannedEncodingResult<()> {
         self.check_no_default_position();
-        let place = permission.get_place();
+        if let Permission::MemoryBlockHeap(block) = permission {
+            // The same address expression can denote different blocks, for
+            // example, when memory is allocated in a loop.
+            self.heap_blocks.insert(block);
+            return Ok(());
+        }
+        let place = permission.get_place()?;
         if let Some(state) = self.try_get_predicates_state(place)? {
             state.insert_permission(permission)?;
         } else {
@@ -289,7 +355,11 @@ impl FoldUnfoldState {
         permission: &Permission,
     ) -> SpannedEncodingResult<()> {
         self.check_no_default_position();
-        let place = permission.get_place();
+        if let Permission::MemoryBlockHeap(block) = permission {
+            self.heap_blocks.remove(block);
+            return Ok(());
+        }
+        let place = permission.get_place()?;
         if let Some(state) = self.try_get_predicates_state(place)? {
             state.remove_permission(permission)?;
             if state.is_empty() {
@@ -345,6 +415,7 @@ impl FoldUnfoldState {
     pub(in super::super) fn clear(&mut self) -> SpannedEncodingResult<()> {
         self.check_no_default_position();
         self.predicates.clear();
+        self.heap_blocks.clear();
         Ok(())
     }
 
@@ -132,9 +132,29 @@ impl PredicateStateOnPath {
             Permission::MutBorrowed(MutBorrowed { lifetime, place }) => {
                 assert!(self.mut_borrowed.insert(place, lifetime).is_none());
             }
+            Permission::MemoryBlockHeap(_) => {
+                unreachable!("heap memory blocks are tracked by FoldUnfoldState")
+            }
         }
     }
 
+    /// Returns the permissions held in this state, or `None` if some places
+    /// are mutably borrowed.
+    pub(super) fn collect_permissions(&self) -> Option<Vec<Permission>> {
+        if !self.mut_borrowed.is_empty() {
+            return None;
+        }
+        let owned = self
+            .owned_non_aliased
+            .iter()
+            .map(|place| Permission::Owned(place.clone()));
+        let memory_block = self
+            .memory_block_stack
+            .iter()
+            .map(|place| Permission::MemoryBlock(place.clone()));
+        Some(owned.chain(memory_block).collect())
+    }
+
     pub(super) fn remove_permission(&mut self, permission: &Permission) {
         match permission {
             Permission::MemoryBlock(place) => {
@@ -143,7 +163,7 @@ impl PredicateStateOnPath {
             Permission::Owned(place) => {
                 assert!(self.owned_non_aliased.remove(place));
             }
-            Permission::MutBorrowed(_) => {
+            Permission::MutBorrowed(_) | Permission::MemoryBlockHeap(_) => {
                 unreachable!()
             }
         }
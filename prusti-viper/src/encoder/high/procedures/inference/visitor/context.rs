@@ -23,6 +23,7 @@ impl<'p, 'v, 'tcx> super::super::ensurer::Context for Visitor<'p, 'v, 'tcx> {
         // means one more refactoring…
         let normalized_type = ty.normalize_type();
         let type_decl = self.encoder.encode_type_def_typed(&normalized_type)?;
+        self.dependencies.record_type_decl(&normalized_type, &type_decl);
         fn expand_fields<'a>(
             place: &vir_typed::Expression,
             fields: impl Iterator<Item = &'a vir_typed::FieldDecl>,
@@ -124,11 +125,17 @@ impl<'p, 'v, 'tcx> super::super::ensurer::Context for Visitor<'p, 'v, 'tcx> {
             .get_span(position.into())
             .cloned()
     }
+    fn get_current_statement_span(&mut self) -> Option<MultiSpan> {
+        let position = self.current_statement_position?;
+        self.get_span(position)
+    }
     fn change_error_context(
         &mut self,
         position: vir_typed::Position,
         error_ctxt: ErrorCtxt,
     ) -> vir_typed::Position {
-        self.encoder.change_error_context(position, error_ctxt)
+        let new_position = self.encoder.change_error_context(position, error_ctxt.clone());
+        self.dependencies.record_position(position, error_ctxt, new_position);
+        new_position
     }
 }
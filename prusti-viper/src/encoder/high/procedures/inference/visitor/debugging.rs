@@ -1,7 +1,11 @@
 use super::Visitor;
+use prusti_common::config;
 use rustc_hash::FxHashMap;
 use vir_crate::{
-    common::graphviz::{escape_html, Graph, NodeBuilder},
+    common::{
+        display::cjoin,
+        graphviz::{escape_html, Graph, NodeBuilder},
+    },
     middle::{self as vir_mid},
 };
 
@@ -14,15 +18,10 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
         let graph = self.render_crash_state(label_markers);
         let source_filename = self.encoder.env().name.source_file_name();
         let procedure_name = self.procedure_name.clone().unwrap();
-        // TODO: Include all relevant information:
-        // 1. Fold-unfold state.
-        // 2. Mark which nodes were successfully visited.
-        // 3. Mark which edges were successfully visited.
-        // 4. Mark where the crash happened.
-        prusti_common::report::log::report_with_writer(
+        super::super::report_graph(
             namespace,
-            format!("{}.{}.dot", source_filename, procedure_name),
-            |writer| graph.write(writer).unwrap(),
+            format!("{}.{}", source_filename, procedure_name),
+            &graph,
         );
     }
     fn render_state(
@@ -34,11 +33,18 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
             if let Some(should_display) = label_markers.get(&label.name) {
                 return *should_display;
             }
+            false
+        } else {
+            // When crashing, render the state of all blocks.
+            true
         }
-        false
     }
     fn render_crash_state(&self, label_markers: Option<&FxHashMap<String, bool>>) -> Graph {
-        let mut graph = Graph::with_columns(&["statement"]);
+        let mut graph = if config::dump_lifetimes_in_graphs() {
+            Graph::with_columns(&["statement", "lifetimes"])
+        } else {
+            Graph::with_columns(&["statement"])
+        };
         for (label, block) in &self.basic_blocks {
             let mut node_builder = self.create_node_builder(label, &mut graph);
             if self.render_state(label, label_markers) {
@@ -85,18 +91,39 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
                 vir_mid::Statement::Comment(statement) => {
                     format!("<font color=\"orange\">{}</font>", escape_html(statement))
                 }
+                vir_mid::Statement::OpenMutRef(_)
+                | vir_mid::Statement::OpenFracRef(_)
+                | vir_mid::Statement::CloseMutRef(_)
+                | vir_mid::Statement::CloseFracRef(_)
+                    if config::dump_lifetimes_in_graphs() =>
+                {
+                    format!("<font color=\"blue\">{}</font>", escape_html(statement))
+                }
                 _ => escape_html(statement.to_string()),
             };
-            node_builder.add_row_sequence(vec![statement_string]);
+            self.add_statement_row(node_builder, statement, statement_string);
         }
         if self.is_crash_label(label) {
             for statement in &self.current_statements {
                 let statement_string =
                     format!("<font color=\"red\">{}</font>", escape_html(statement));
-                node_builder.add_row_sequence(vec![statement_string]);
+                self.add_statement_row(node_builder, statement, statement_string);
             }
         }
     }
+    fn add_statement_row(
+        &self,
+        node_builder: &mut NodeBuilder,
+        statement: &vir_mid::Statement,
+        statement_string: String,
+    ) {
+        if config::dump_lifetimes_in_graphs() {
+            let lifetimes = describe_lifetimes(statement).map(escape_html).unwrap_or_default();
+            node_builder.add_row_sequence(vec![statement_string, lifetimes]);
+        } else {
+            node_builder.add_row_sequence(vec![statement_string]);
+        }
+    }
     fn render_state_at_entry(&self, label: &vir_mid::BasicBlockId, node_builder: &mut NodeBuilder) {
         if let Some(state) = self.state_at_entry.get(label) {
             for line in state.to_string().split('\n') {
@@ -123,7 +150,26 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
     }
     fn render_state_at_crash(&self, label: &vir_mid::BasicBlockId, node_builder: &mut NodeBuilder) {
         if self.is_crash_label(label) {
-            node_builder.add_row_single("<font color=\"red\">no state object</font>".to_string());
+            if let Some((statement, state)) = &self.crash_context {
+                node_builder.add_row_single(format!(
+                    "<font color=\"red\"><b>crashed at: {}</b></font>",
+                    escape_html(statement)
+                ));
+                for line in state.to_string().split('\n') {
+                    if !line.is_empty() {
+                        node_builder.add_row_single(format!(
+                            "<font color=\"red\">{}</font>",
+                            escape_html(line)
+                        ));
+                    }
+                }
+            } else {
+                node_builder.add_row_single(
+                    "<font color=\"red\"><b>crashed while propagating the state to the \
+                    successors</b></font>"
+                        .to_string(),
+                );
+            }
         }
     }
     fn render_successor(
@@ -132,36 +178,105 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
         successor: &vir_mid::Successor,
         graph: &mut Graph,
     ) {
+        // The state is propagated along the outgoing edges only after the
+        // block was successfully processed.
+        let is_processed = self.successfully_processed_blocks.contains(label);
+        let mut add_edge = |target: &vir_mid::BasicBlockId, annotation: Option<String>| {
+            if is_processed {
+                graph.add_highlighted_edge(label.to_string(), target.to_string(), annotation);
+            } else if let Some(annotation) = annotation {
+                graph.add_regular_annotated_edge(label.to_string(), target.to_string(), annotation);
+            } else {
+                graph.add_regular_edge(label.to_string(), target.to_string());
+            }
+        };
         match successor {
             vir_mid::Successor::Exit => graph.add_exit_edge(label.to_string(), "exit".to_string()),
-            vir_mid::Successor::Goto(target) => {
-                graph.add_regular_edge(label.to_string(), target.to_string())
-            }
+            vir_mid::Successor::Goto(target) => add_edge(target, None),
             vir_mid::Successor::GotoSwitch(targets) => {
                 for (condition, target) in targets {
-                    graph.add_regular_annotated_edge(
-                        label.to_string(),
-                        target.to_string(),
-                        condition.to_string(),
-                    );
+                    add_edge(target, Some(condition.to_string()));
                 }
             }
             vir_mid::Successor::NonDetChoice(first, second) => {
-                graph.add_regular_annotated_edge(
-                    label.to_string(),
-                    first.to_string(),
-                    "*".to_string(),
-                );
-                graph.add_regular_annotated_edge(
-                    label.to_string(),
-                    second.to_string(),
-                    "*".to_string(),
-                );
+                add_edge(first, Some("*".to_string()));
+                add_edge(second, Some("*".to_string()));
             }
         }
     }
 }
 
+/// Describes the lifetimes and borrows created, used, or ended by the
+/// statement.
+fn describe_lifetimes(statement: &vir_mid::Statement) -> Option<String> {
+    let description = match statement {
+        vir_mid::Statement::FoldRef(statement) => {
+            format!("{} borrow in {}", statement.uniqueness, statement.lifetime)
+        }
+        vir_mid::Statement::UnfoldRef(statement) => {
+            format!("{} borrow in {}", statement.uniqueness, statement.lifetime)
+        }
+        vir_mid::Statement::RestoreMutBorrowed(statement) => format!(
+            "{} no longer borrowed in {}",
+            statement.place, statement.lifetime
+        ),
+        vir_mid::Statement::Assign(vir_mid::statement::Assign {
+            value: vir_mid::Rvalue::Ref(value),
+            ..
+        }) => format!(
+            "new {} borrow of {} in {}",
+            value.uniqueness, value.place, value.new_borrow_lifetime
+        ),
+        vir_mid::Statement::Assign(vir_mid::statement::Assign {
+            value: vir_mid::Rvalue::Reborrow(value),
+            ..
+        }) => format!(
+            "new {} reborrow of {} in {} (outlived by {})",
+            value.uniqueness, value.deref_place, value.new_borrow_lifetime, value.deref_lifetime
+        ),
+        vir_mid::Statement::NewLft(statement) => format!("new lifetime {}", statement.target),
+        vir_mid::Statement::EndLft(statement) => format!("end of {}", statement.lifetime),
+        vir_mid::Statement::DeadReference(statement) => {
+            format!("reference {} is dead", statement.target)
+        }
+        vir_mid::Statement::DeadLifetime(statement) => {
+            format!("{} is dead in {}", statement.lifetime, statement.target)
+        }
+        vir_mid::Statement::DeadInclusion(statement) => format!(
+            "{} no longer includes {}",
+            statement.target, statement.value
+        ),
+        vir_mid::Statement::LifetimeTake(statement) => format!(
+            "{} is the intersection of {}",
+            statement.target,
+            cjoin(&statement.value)
+        ),
+        vir_mid::Statement::LifetimeReturn(statement) => format!(
+            "{} is returned to {}",
+            statement.target,
+            cjoin(&statement.value)
+        ),
+        vir_mid::Statement::OpenMutRef(statement) => {
+            format!("open region {} of {}", statement.lifetime, statement.place)
+        }
+        vir_mid::Statement::OpenFracRef(statement) => {
+            format!("open region {} of {}", statement.lifetime, statement.place)
+        }
+        vir_mid::Statement::CloseMutRef(statement) => {
+            format!("close region {} of {}", statement.lifetime, statement.place)
+        }
+        vir_mid::Statement::CloseFracRef(statement) => {
+            format!("close region {} of {}", statement.lifetime, statement.place)
+        }
+        vir_mid::Statement::BorShorten(statement) => format!(
+            "{} shortened to {}",
+            statement.old_lifetime, statement.lifetime
+        ),
+        _ => return None,
+    };
+    Some(description)
+}
+
 impl<'p, 'v, 'tcx> Drop for Visitor<'p, 'v, 'tcx> {
     fn drop(&mut self) {
         if self.graphviz_on_crash {
@@ -1,4 +1,5 @@
 use super::{
+    cache::{block_cache_key, CachedBlock, InferenceDependencies},
     ensurer::{
         ensure_required_permission, ensure_required_permissions,
         try_ensure_enum_discriminant_by_unfolding,
@@ -43,11 +44,23 @@ pub(super) struct Visitor<'p, 'v, 'tcx> {
     successfully_processed_blocks: FxHashSet<vir_mid::BasicBlockId>,
     current_label: Option<vir_mid::BasicBlockId>,
     current_statements: Vec<vir_mid::Statement>,
+    /// The position of the statement that is currently being lowered. Used
+    /// for error reporting.
+    current_statement_position: Option<vir_typed::Position>,
     path_disambiguators: Option<
         BTreeMap<(vir_mid::BasicBlockId, vir_mid::BasicBlockId), Vec<vir_mid::BasicBlockId>>,
     >,
     /// Should we dump a Graphviz plot in case we crash during inference?
     graphviz_on_crash: bool,
+    /// The statement that is currently being processed and the fold-unfold
+    /// state before it. Tracked only when `graphviz_on_crash` is set.
+    crash_context: Option<(String, FoldUnfoldState)>,
+    /// The number of roots whose incoming state was aligned with the state at
+    /// a join point. Used for measuring the effect of the optimization.
+    aligned_join_roots: usize,
+    /// The parts of the encoder state used by the inference. Used for caching
+    /// the result on disk.
+    dependencies: InferenceDependencies,
 }
 
 impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
@@ -63,8 +76,12 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
             successfully_processed_blocks: Default::default(),
             current_label: None,
             current_statements: Default::default(),
+            current_statement_position: None,
             path_disambiguators: Default::default(),
             graphviz_on_crash: config::dump_debug_info(),
+            crash_context: None,
+            aligned_join_roots: 0,
+            dependencies: Default::default(),
         }
     }
 
@@ -113,6 +130,16 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
                 serde_json::from_reader(std::fs::File::open(path).unwrap()).unwrap();
             self.render_crash_graphviz(Some(&label_markers));
         }
+        debug!(
+            "join-point optimization aligned {} root states in {}",
+            self.aligned_join_roots,
+            self.procedure_name.as_ref().unwrap()
+        );
+        let (hits, misses) = self.encoder.fold_unfold_inference_state.statistics();
+        debug!(
+            "fold-unfold inference cache: {} hits, {} misses in total",
+            hits, misses
+        );
         let check_mode = procedure.check_mode;
         let new_procedure = vir_mid::ProcedureDecl {
             name: self.procedure_name.take().unwrap(),
@@ -166,8 +193,41 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
                 .remove(self.current_label.as_ref().unwrap())
                 .unwrap()
         };
-        for statement in old_block.statements {
-            self.lower_statement(statement, &mut state)?;
+        let cache_key = if config::enable_fold_unfold_block_cache() {
+            Some(block_cache_key(&old_block.statements, &state))
+        } else {
+            None
+        };
+        let label = self.current_label.as_ref().unwrap();
+        let cached_block = cache_key
+            .as_ref()
+            .and_then(|key| self.encoder.fold_unfold_inference_state.get_block(key));
+        if let Some(cached_block) = cached_block {
+            debug!("reusing the inferred fold-unfold operations of {}", label);
+            let new_block = self.basic_blocks.get_mut(label).unwrap();
+            new_block
+                .statements
+                .extend(cached_block.statements.iter().cloned());
+            state = cached_block.exit_state.clone();
+            self.dependencies.mark_incomplete();
+        } else {
+            for statement in old_block.statements {
+                if self.graphviz_on_crash {
+                    self.crash_context = Some((statement.to_string(), state.clone()));
+                }
+                self.lower_statement(statement, &mut state)?;
+            }
+            self.crash_context = None;
+            if let Some(cache_key) = cache_key {
+                let new_block = &self.basic_blocks[self.current_label.as_ref().unwrap()];
+                self.encoder.fold_unfold_inference_state.insert_block(
+                    cache_key,
+                    CachedBlock {
+                        statements: new_block.statements.clone(),
+                        exit_state: state.clone(),
+                    },
+                );
+            }
         }
         let successor_blocks = self.current_successors()?;
         assert!(
@@ -194,6 +254,7 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
             "Statement has default position: {}",
             statement
         );
+        self.current_statement_position = Some(statement.position());
         if let vir_typed::Statement::DeadLifetime(dead_lifetime) = statement {
             self.process_dead_lifetime(dead_lifetime, state)?;
             return Ok(());
@@ -494,6 +555,9 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
         to_label: vir_mid::BasicBlockId,
         mut state: FoldUnfoldState,
     ) -> SpannedEncodingResult<()> {
+        if config::enable_fold_unfold_join_optimization() {
+            self.align_with_state_at_entry(&to_label, &mut state)?;
+        }
         let from_label = self.current_label.as_ref().unwrap();
         match self.state_at_entry.entry(to_label.clone()) {
             Entry::Vacant(entry) => {
@@ -519,6 +583,61 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
         Ok(())
     }
 
+    /// Merging two different unconditional states of a root makes the state
+    /// conditional, which leads to conditional fold-unfold operations in all
+    /// blocks that use the root afterwards. To avoid this, we try to bring the
+    /// incoming state into the shape of the state already at the entry of
+    /// `to_label` by folding and unfolding at the end of the current block.
+    /// This is possible only if the current block has a single successor
+    /// because otherwise the operations would affect also the other
+    /// successors.
+    fn align_with_state_at_entry(
+        &mut self,
+        to_label: &vir_mid::BasicBlockId,
+        state: &mut FoldUnfoldState,
+    ) -> SpannedEncodingResult<()> {
+        let Some(entry_state) = self.state_at_entry.get(to_label) else {
+            return Ok(());
+        };
+        if self.current_successors()?.len() != 1 {
+            return Ok(());
+        }
+        for (root, permissions) in state.collect_misaligned_roots(entry_state) {
+            let mut aligned_state = state.clone();
+            let mut actions = Vec::new();
+            let mut failed = false;
+            for permission in permissions {
+                if ensure_required_permission(self, &mut aligned_state, permission, &mut actions)
+                    .is_err()
+                {
+                    failed = true;
+                    break;
+                }
+            }
+            // Operations that mark the state as unreachable must not be
+            // introduced by an optimization.
+            failed = failed
+                || actions
+                    .iter()
+                    .any(|action| matches!(action, Action::Unreachable(_)));
+            let entry_state = self.state_at_entry.get(to_label).unwrap();
+            if !failed && aligned_state.is_aligned_on(entry_state, &root) {
+                debug!("aligned {} at the join point {}", root, to_label);
+                self.process_actions(actions)?;
+                *state = aligned_state;
+                self.aligned_join_roots += 1;
+            }
+        }
+        let new_block = self
+            .basic_blocks
+            .get_mut(self.current_label.as_ref().unwrap())
+            .unwrap();
+        new_block
+            .statements
+            .extend(std::mem::take(&mut self.current_statements));
+        Ok(())
+    }
+
     fn current_successors(&self) -> SpannedEncodingResult<Vec<vir_mid::BasicBlockId>> {
         let current_block = self
             .basic_blocks
@@ -536,6 +655,10 @@ impl<'p, 'v, 'tcx> Visitor<'p, 'v, 'tcx> {
         self.to_crashing_graphviz("graphviz_method_custom_foldunfold", label_markers);
     }
 
+    pub(super) fn take_dependencies(&mut self) -> InferenceDependencies {
+        std::mem::take(&mut self.dependencies)
+    }
+
     pub(super) fn cancel_crash_graphviz(mut self) {
         self.graphviz_on_crash = false;
     }
@@ -1,9 +1,11 @@
+use super::cache::{encoding_cache_key, load_cached_encoding, store_cached_encoding};
 use crate::encoder::{
     errors::SpannedEncodingResult,
     high::to_middle::HighToMiddle,
     mir::{procedures::MirProcedureEncoderInterface, types::MirTypeEncoderInterface},
 };
 use log::debug;
+use prusti_common::config;
 use prusti_rustc_interface::{hir::def_id::DefId, middle::ty};
 use std::collections::BTreeMap;
 use vir_crate::{
@@ -99,6 +101,15 @@ pub(crate) trait HighProcedureEncoderInterface<'tcx> {
         proc_def_id: DefId,
         check_mode: CheckMode,
     ) -> SpannedEncodingResult<vir_mid::ProcedureDecl>;
+    /// Lowers a `vir_high` procedure of the core proof to `vir_mid`, which
+    /// includes the fold-unfold inference. If `validate` is true, the
+    /// procedure is checked for well-formedness after each step.
+    fn lower_procedure_core_proof(
+        &mut self,
+        proc_def_id: DefId,
+        procedure_high: vir_high::ProcedureDecl,
+        validate: bool,
+    ) -> SpannedEncodingResult<vir_mid::ProcedureDecl>;
     fn encode_type_core_proof(
         &mut self,
         ty: ty::Ty<'tcx>,
@@ -112,12 +123,62 @@ impl<'v, 'tcx: 'v> HighProcedureEncoderInterface<'tcx> for super::super::super::
         proc_def_id: DefId,
         check_mode: CheckMode,
     ) -> SpannedEncodingResult<vir_mid::ProcedureDecl> {
-        let procedure_high = self.encode_procedure_core_proof_high(proc_def_id, check_mode)?;
+        // Validating the procedure after each lowering is expensive, so we do
+        // it only when debugging.
+        let validate = config::dump_debug_info();
+        let cache_dir = config::encoding_cache_path();
+        let cache_key = if cache_dir.as_os_str().is_empty() {
+            None
+        } else {
+            encoding_cache_key(self, proc_def_id, check_mode)
+        };
+        if let Some(key) = &cache_key {
+            if let Some(procedure) = load_cached_encoding(self, &cache_dir, key) {
+                debug!("reusing the cached encoding of {}", procedure.name);
+                return Ok(procedure);
+            }
+            self.error_manager().start_recording_positions();
+        }
+        let result = self
+            .encode_procedure_core_proof_high(proc_def_id, check_mode)
+            .and_then(|procedure_high| {
+                self.lower_procedure_core_proof(proc_def_id, procedure_high, validate)
+            });
+        if let Some(key) = &cache_key {
+            match &result {
+                Ok(procedure) => store_cached_encoding(self, &cache_dir, key, procedure),
+                Err(_) => self.error_manager().stop_recording_positions(),
+            }
+        }
+        result
+    }
+
+    fn lower_procedure_core_proof(
+        &mut self,
+        proc_def_id: DefId,
+        procedure_high: vir_high::ProcedureDecl,
+        validate: bool,
+    ) -> SpannedEncodingResult<vir_mid::ProcedureDecl> {
         debug!("procedure_high:\n{}", procedure_high);
+        if validate {
+            procedure_high.assert_well_formed();
+        }
         let procedure_typed = self.procedure_high_to_typed(procedure_high)?;
         debug!("procedure_typed:\n{}", procedure_typed);
-        let procedure =
+        if validate {
+            procedure_typed.assert_well_formed();
+        }
+        let mut procedure =
             super::inference::infer_shape_operations(self, proc_def_id, procedure_typed)?;
+        if validate {
+            procedure.assert_well_formed();
+        }
+        if config::enable_core_proof_optimizations() {
+            procedure = super::passes::run_passes(self, procedure)?;
+            if validate {
+                procedure.assert_well_formed();
+            }
+        }
         Ok(procedure)
     }
 
@@ -1,4 +1,8 @@
+mod cache;
 mod inference;
 mod interface;
+mod passes;
 
-pub(crate) use self::interface::HighProcedureEncoderInterface;
+pub(crate) use self::{
+    inference::FoldUnfoldInferenceState, interface::HighProcedureEncoderInterface,
+};
@@ -0,0 +1,152 @@
+use super::helpers::collect_referenced_blocks;
+use std::collections::BTreeSet;
+use vir_crate::{
+    common::{cfg::Cfg, expression::SyntacticEvaluation},
+    middle::{self as vir_mid},
+};
+
+/// Removes the switch targets whose conditions are known to be false, turns
+/// switches with a single possible target into gotos, and removes the blocks
+/// that become unreachable.
+///
+/// The targets of a switch are tried in order, therefore, all targets after
+/// the first one whose condition is known to be true are removed too.
+pub(super) fn simplify_branches(mut procedure: vir_mid::ProcedureDecl) -> vir_mid::ProcedureDecl {
+    for block in procedure.basic_blocks.values_mut() {
+        let successor = std::mem::replace(&mut block.successor, vir_mid::Successor::Exit);
+        block.successor = simplify_successor(successor);
+    }
+    remove_unreachable_blocks(&mut procedure);
+    procedure
+}
+
+fn simplify_successor(successor: vir_mid::Successor) -> vir_mid::Successor {
+    match successor {
+        vir_mid::Successor::GotoSwitch(targets)
+            if !targets.iter().all(|(test, _)| test.is_false()) =>
+        {
+            let mut new_targets = Vec::new();
+            for (test, target) in targets {
+                if test.is_false() {
+                    continue;
+                }
+                let is_last = test.is_true();
+                new_targets.push((test, target));
+                if is_last {
+                    break;
+                }
+            }
+            let is_exhaustive = new_targets.last().map_or(false, |(test, _)| test.is_true());
+            let has_single_target = new_targets
+                .iter()
+                .all(|(_, target)| target == &new_targets[0].1);
+            if is_exhaustive && has_single_target {
+                let (_, target) = new_targets.swap_remove(0);
+                vir_mid::Successor::Goto(target)
+            } else {
+                vir_mid::Successor::GotoSwitch(new_targets)
+            }
+        }
+        vir_mid::Successor::NonDetChoice(first, second) if first == second => {
+            vir_mid::Successor::Goto(first)
+        }
+        successor => successor,
+    }
+}
+
+/// Removes the blocks that are not reachable from the entry unless they are
+/// referenced by block marker conditions.
+fn remove_unreachable_blocks(procedure: &mut vir_mid::ProcedureDecl) {
+    let mut reachable = BTreeSet::new();
+    let mut work_list = vec![procedure.entry.clone()];
+    while let Some(label) = work_list.pop() {
+        if reachable.insert(label.clone()) {
+            work_list.extend(procedure.successors(&label).into_iter().cloned());
+        }
+    }
+    let referenced = collect_referenced_blocks(procedure);
+    let exit = procedure.exit.clone();
+    procedure.basic_blocks.retain(|label, _| {
+        reachable.contains(label) || referenced.contains(label) || label == &exit
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::helpers::assert_pass_result, simplify_branches};
+
+    #[test]
+    fn test_simplify_branches() {
+        assert_pass_result(
+            simplify_branches,
+            r#"procedure CoreProof "test" {
+                var b: bool;
+                entry bb0;
+                exit bb4;
+                bb0: {
+                    switch {
+                        false => bb1,
+                        b => bb2,
+                        true => bb3,
+                        b => bb1,
+                    }
+                }
+                bb1: {
+                    goto bb4;
+                }
+                bb2: {
+                    nondet bb4, bb4;
+                }
+                bb3: {
+                    switch {
+                        true => bb4,
+                        b => bb1,
+                    }
+                }
+                bb4: {
+                    return;
+                }
+            }"#,
+            r#"procedure CoreProof "test" {
+                var b: bool;
+                entry bb0;
+                exit bb4;
+                bb0: {
+                    switch {
+                        b => bb2,
+                        true => bb3,
+                    }
+                }
+                bb2: {
+                    goto bb4;
+                }
+                bb3: {
+                    goto bb4;
+                }
+                bb4: {
+                    return;
+                }
+            }"#,
+        );
+    }
+
+    #[test]
+    fn test_keep_referenced_blocks() {
+        let procedure = r#"procedure CoreProof "test" {
+            var b: bool;
+            entry bb0;
+            exit bb2;
+            bb0: {
+                goto bb2;
+            }
+            bb1: {
+                goto bb2;
+            }
+            bb2: {
+                assert<!bb1> b;
+                return;
+            }
+        }"#;
+        assert_pass_result(simplify_branches, procedure, procedure);
+    }
+}
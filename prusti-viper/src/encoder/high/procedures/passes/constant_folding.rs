@@ -0,0 +1,248 @@
+use vir_crate::{
+    common::expression::SyntacticEvaluation,
+    middle::{
+        self as vir_mid,
+        ast::expression::visitors::{
+            default_fold_binary_op, default_fold_conditional, default_fold_unary_op,
+            ExpressionFolder,
+        },
+        expression::ConstantValue,
+        BinaryOpKind,
+    },
+};
+
+/// Evaluates the operations on constants in the expressions of assertions,
+/// assumptions, ghost assignments, and switch conditions.
+///
+/// Arithmetic operations are folded only if the result fits into the type of
+/// the operands so that overflows are still reported by the verifier.
+pub(super) fn fold_constants(mut procedure: vir_mid::ProcedureDecl) -> vir_mid::ProcedureDecl {
+    for block in procedure.basic_blocks.values_mut() {
+        for statement in &mut block.statements {
+            match statement {
+                vir_mid::Statement::Assume(statement) => {
+                    statement.expression = fold(statement.expression.clone());
+                }
+                vir_mid::Statement::Assert(statement) => {
+                    statement.expression = fold(statement.expression.clone());
+                }
+                vir_mid::Statement::GhostAssign(statement) => {
+                    statement.value = fold(statement.value.clone());
+                }
+                _ => {}
+            }
+        }
+        if let vir_mid::Successor::GotoSwitch(targets) = &mut block.successor {
+            for (test, _) in targets {
+                *test = fold(test.clone());
+            }
+        }
+    }
+    procedure
+}
+
+fn fold(expression: vir_mid::Expression) -> vir_mid::Expression {
+    ConstantFolder.fold_expression(expression)
+}
+
+struct ConstantFolder;
+
+impl ExpressionFolder for ConstantFolder {
+    fn fold_unary_op_enum(&mut self, unary_op: vir_mid::UnaryOp) -> vir_mid::Expression {
+        let unary_op = default_fold_unary_op(self, unary_op);
+        match (unary_op.op_kind, as_bool(&unary_op.argument)) {
+            (vir_mid::UnaryOpKind::Not, Some(value)) => bool_constant(!value, unary_op.position),
+            _ => vir_mid::Expression::UnaryOp(unary_op),
+        }
+    }
+    fn fold_binary_op_enum(&mut self, binary_op: vir_mid::BinaryOp) -> vir_mid::Expression {
+        let binary_op = default_fold_binary_op(self, binary_op);
+        fold_binary_op(binary_op)
+    }
+    fn fold_conditional_enum(&mut self, conditional: vir_mid::Conditional) -> vir_mid::Expression {
+        let conditional = default_fold_conditional(self, conditional);
+        if conditional.guard.is_true() {
+            *conditional.then_expr
+        } else if conditional.guard.is_false() {
+            *conditional.else_expr
+        } else {
+            vir_mid::Expression::Conditional(conditional)
+        }
+    }
+}
+
+fn fold_binary_op(binary_op: vir_mid::BinaryOp) -> vir_mid::Expression {
+    let position = binary_op.position;
+    let left = as_bool(&binary_op.left);
+    let right = as_bool(&binary_op.right);
+    // Operands on the left of a short-circuiting operator can be dropped only
+    // if they are constants because the right operand may be well-defined
+    // only if the left one holds.
+    match (binary_op.op_kind, left, right) {
+        (BinaryOpKind::And, Some(false), _) => return bool_constant(false, position),
+        (BinaryOpKind::And, Some(true), _) => return *binary_op.right,
+        (BinaryOpKind::And, _, Some(true)) => return *binary_op.left,
+        (BinaryOpKind::Or, Some(true), _) => return bool_constant(true, position),
+        (BinaryOpKind::Or, Some(false), _) => return *binary_op.right,
+        (BinaryOpKind::Or, _, Some(false)) => return *binary_op.left,
+        (BinaryOpKind::Implies, Some(false), _) => return bool_constant(true, position),
+        (BinaryOpKind::Implies, Some(true), _) => return *binary_op.right,
+        (BinaryOpKind::EqCmp, Some(left), Some(right)) => {
+            return bool_constant(left == right, position)
+        }
+        (BinaryOpKind::NeCmp, Some(left), Some(right)) => {
+            return bool_constant(left != right, position)
+        }
+        _ => {}
+    }
+    if let (Some((left, ty)), Some((right, right_ty))) =
+        (as_int(&binary_op.left), as_int(&binary_op.right))
+    {
+        if ty == right_ty {
+            let folded = match binary_op.op_kind {
+                BinaryOpKind::EqCmp => Some(bool_constant(left == right, position)),
+                BinaryOpKind::NeCmp => Some(bool_constant(left != right, position)),
+                BinaryOpKind::GtCmp => Some(bool_constant(left > right, position)),
+                BinaryOpKind::GeCmp => Some(bool_constant(left >= right, position)),
+                BinaryOpKind::LtCmp => Some(bool_constant(left < right, position)),
+                BinaryOpKind::LeCmp => Some(bool_constant(left <= right, position)),
+                BinaryOpKind::Add => int_constant(left.checked_add(right), ty, position),
+                BinaryOpKind::Sub => int_constant(left.checked_sub(right), ty, position),
+                BinaryOpKind::Mul => int_constant(left.checked_mul(right), ty, position),
+                _ => None,
+            };
+            if let Some(folded) = folded {
+                return folded;
+            }
+        }
+    }
+    vir_mid::Expression::BinaryOp(binary_op)
+}
+
+fn as_bool(expression: &vir_mid::Expression) -> Option<bool> {
+    match expression {
+        vir_mid::Expression::Constant(vir_mid::Constant {
+            value: ConstantValue::Bool(value),
+            ..
+        }) => Some(*value),
+        _ => None,
+    }
+}
+
+fn as_int(expression: &vir_mid::Expression) -> Option<(i64, &vir_mid::Type)> {
+    match expression {
+        vir_mid::Expression::Constant(vir_mid::Constant {
+            value: ConstantValue::Int(value),
+            ty: ty @ vir_mid::Type::Int(_),
+            ..
+        }) => Some((*value, ty)),
+        _ => None,
+    }
+}
+
+fn bool_constant(value: bool, position: vir_mid::Position) -> vir_mid::Expression {
+    vir_mid::Expression::constant(ConstantValue::Bool(value), vir_mid::Type::Bool, position)
+}
+
+/// Creates an integer constant if the value was computed without an overflow
+/// and fits into the given type.
+fn int_constant(
+    value: Option<i64>,
+    ty: &vir_mid::Type,
+    position: vir_mid::Position,
+) -> Option<vir_mid::Expression> {
+    let value = value?;
+    let (min, max) = match ty {
+        vir_mid::Type::Int(vir_mid::ty::Int::I8) => (i8::MIN.into(), i8::MAX.into()),
+        vir_mid::Type::Int(vir_mid::ty::Int::I16) => (i16::MIN.into(), i16::MAX.into()),
+        vir_mid::Type::Int(vir_mid::ty::Int::I32) => (i32::MIN.into(), i32::MAX.into()),
+        vir_mid::Type::Int(vir_mid::ty::Int::I64 | vir_mid::ty::Int::Unbounded) => {
+            (i64::MIN, i64::MAX)
+        }
+        vir_mid::Type::Int(vir_mid::ty::Int::U8) => (0, u8::MAX.into()),
+        vir_mid::Type::Int(vir_mid::ty::Int::U16) => (0, u16::MAX.into()),
+        vir_mid::Type::Int(vir_mid::ty::Int::U32) => (0, u32::MAX.into()),
+        _ => return None,
+    };
+    if min <= value && value <= max {
+        Some(vir_mid::Expression::constant(
+            ConstantValue::Int(value),
+            ty.clone(),
+            position,
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::helpers::assert_pass_result, fold_constants};
+
+    #[test]
+    fn test_fold_constants() {
+        assert_pass_result(
+            fold_constants,
+            r#"procedure CoreProof "test" {
+                var b: bool;
+                var x: i32;
+                entry bb0;
+                exit bb1;
+                bb0: {
+                    assume ((1i32 + 2i32) == 3i32);
+                    assert (x > (100i32 * 2i32));
+                    ghost_assign b := (true && (x > 0i32));
+                    ghost_assign b := ((x > 0i32) && true);
+                    ghost_assign b := (!(true) || (x > 0i32));
+                    ghost_assign b := ((1i32 < 2i32) ? (x > 0i32) : false);
+                    assume (false ==> (x > 0i32));
+                    switch {
+                        (false || b) => bb1,
+                        true => bb1,
+                    }
+                }
+                bb1: {
+                    return;
+                }
+            }"#,
+            r#"procedure CoreProof "test" {
+                var b: bool;
+                var x: i32;
+                entry bb0;
+                exit bb1;
+                bb0: {
+                    assume true;
+                    assert (x > 200i32);
+                    ghost_assign b := (x > 0i32);
+                    ghost_assign b := (x > 0i32);
+                    ghost_assign b := (x > 0i32);
+                    ghost_assign b := (x > 0i32);
+                    assume true;
+                    switch {
+                        b => bb1,
+                        true => bb1,
+                    }
+                }
+                bb1: {
+                    return;
+                }
+            }"#,
+        );
+    }
+
+    #[test]
+    fn test_keep_overflowing_operations() {
+        let procedure = r#"procedure CoreProof "test" {
+            var x: i8;
+            entry bb0;
+            exit bb0;
+            bb0: {
+                ghost_assign x := (100i8 + 100i8);
+                ghost_assign x := (0u8 - 1u8);
+                assume (x > (1i8 + 1i32));
+                return;
+            }
+        }"#;
+        assert_pass_result(fold_constants, procedure, procedure);
+    }
+}
@@ -0,0 +1,225 @@
+use super::helpers::{collect_ghost_variables, ghost_target, is_read_only, statement_variables};
+use rustc_hash::{FxHashMap, FxHashSet};
+use vir_crate::middle::{
+    self as vir_mid,
+    ast::expression::visitors::{default_fold_let_expr, default_fold_quantifier, ExpressionFolder},
+};
+
+/// Replaces the uses of ghost variables that were assigned a constant or
+/// another ghost variable in the same basic block with the assigned value.
+///
+/// Only the expressions of assertions, assumptions, ghost assignments, and
+/// switch conditions are rewritten because the other statements operate on
+/// places and, therefore, on permissions.
+pub(super) fn propagate_copies(mut procedure: vir_mid::ProcedureDecl) -> vir_mid::ProcedureDecl {
+    let ghost_variables = collect_ghost_variables(&procedure);
+    if ghost_variables.is_empty() {
+        return procedure;
+    }
+    for block in procedure.basic_blocks.values_mut() {
+        let mut copies = FxHashMap::default();
+        let statements = std::mem::take(&mut block.statements);
+        for statement in statements {
+            let statement = match statement {
+                vir_mid::Statement::Assume(mut statement) => {
+                    statement.expression = substitute(&copies, statement.expression);
+                    vir_mid::Statement::Assume(statement)
+                }
+                vir_mid::Statement::Assert(mut statement) => {
+                    statement.expression = substitute(&copies, statement.expression);
+                    vir_mid::Statement::Assert(statement)
+                }
+                vir_mid::Statement::GhostAssign(mut statement) => {
+                    statement.value = substitute(&copies, statement.value);
+                    vir_mid::Statement::GhostAssign(statement)
+                }
+                statement => statement,
+            };
+            if let Some(target) = ghost_target(&statement) {
+                invalidate(&mut copies, target);
+                if let vir_mid::Statement::GhostAssign(vir_mid::statement::GhostAssign {
+                    value,
+                    ..
+                }) = &statement
+                {
+                    if ghost_variables.contains(target)
+                        && is_copy(value, target, &ghost_variables)
+                    {
+                        copies.insert(target.to_string(), value.clone());
+                    }
+                }
+            } else if !is_read_only(&statement) {
+                for variable in statement_variables(&statement) {
+                    invalidate(&mut copies, &variable);
+                }
+            }
+            block.statements.push(statement);
+        }
+        if let vir_mid::Successor::GotoSwitch(targets) = &mut block.successor {
+            for (test, _) in targets {
+                *test = substitute(&copies, test.clone());
+            }
+        }
+    }
+    procedure
+}
+
+/// Checks whether `value` is a constant or a ghost variable other than
+/// `target`.
+fn is_copy(value: &vir_mid::Expression, target: &str, ghost_variables: &FxHashSet<String>) -> bool {
+    match value {
+        vir_mid::Expression::Constant(_) => true,
+        vir_mid::Expression::Local(local) => {
+            local.variable.name != target && ghost_variables.contains(&local.variable.name)
+        }
+        _ => false,
+    }
+}
+
+/// Forgets the copies that are invalidated by changing `variable`.
+fn invalidate(copies: &mut FxHashMap<String, vir_mid::Expression>, variable: &str) {
+    copies.remove(variable);
+    copies.retain(|_, value| {
+        !matches!(value, vir_mid::Expression::Local(local) if local.variable.name == variable)
+    });
+}
+
+fn substitute(
+    copies: &FxHashMap<String, vir_mid::Expression>,
+    expression: vir_mid::Expression,
+) -> vir_mid::Expression {
+    struct Substitutor<'a> {
+        copies: &'a FxHashMap<String, vir_mid::Expression>,
+    }
+    impl<'a> Substitutor<'a> {
+        fn is_shadowed(&self, variables: &[vir_mid::VariableDecl]) -> bool {
+            variables
+                .iter()
+                .any(|variable| self.copies.contains_key(&variable.name))
+        }
+    }
+    impl<'a> ExpressionFolder for Substitutor<'a> {
+        fn fold_local_enum(&mut self, local: vir_mid::Local) -> vir_mid::Expression {
+            if let Some(value) = self.copies.get(&local.variable.name) {
+                value.clone().replace_position(local.position)
+            } else {
+                vir_mid::Expression::Local(local)
+            }
+        }
+        fn fold_labelled_old_enum(
+            &mut self,
+            labelled_old: vir_mid::LabelledOld,
+        ) -> vir_mid::Expression {
+            // The copies hold only in the current state.
+            vir_mid::Expression::LabelledOld(labelled_old)
+        }
+        fn fold_quantifier_enum(&mut self, quantifier: vir_mid::Quantifier) -> vir_mid::Expression {
+            if self.is_shadowed(&quantifier.variables) {
+                vir_mid::Expression::Quantifier(quantifier)
+            } else {
+                vir_mid::Expression::Quantifier(default_fold_quantifier(self, quantifier))
+            }
+        }
+        fn fold_let_expr_enum(&mut self, let_expr: vir_mid::LetExpr) -> vir_mid::Expression {
+            if self.is_shadowed(std::slice::from_ref(&let_expr.variable)) {
+                vir_mid::Expression::LetExpr(let_expr)
+            } else {
+                vir_mid::Expression::LetExpr(default_fold_let_expr(self, let_expr))
+            }
+        }
+    }
+    if copies.is_empty() {
+        expression
+    } else {
+        Substitutor { copies }.fold_expression(expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::helpers::assert_pass_result, propagate_copies};
+
+    #[test]
+    fn test_propagate_within_block() {
+        assert_pass_result(
+            propagate_copies,
+            r#"procedure CoreProof "test" {
+                var a: i32;
+                var b: i32;
+                var c: bool;
+                entry bb0;
+                exit bb1;
+                bb0: {
+                    ghost_havoc a;
+                    ghost_assign b := a;
+                    assert (b > 0i32);
+                    ghost_assign c := (b == 5i32);
+                    goto bb1;
+                }
+                bb1: {
+                    assert (b > 0i32);
+                    return;
+                }
+            }"#,
+            r#"procedure CoreProof "test" {
+                var a: i32;
+                var b: i32;
+                var c: bool;
+                entry bb0;
+                exit bb1;
+                bb0: {
+                    ghost_havoc a;
+                    ghost_assign b := a;
+                    assert (a > 0i32);
+                    ghost_assign c := (a == 5i32);
+                    goto bb1;
+                }
+                bb1: {
+                    assert (b > 0i32);
+                    return;
+                }
+            }"#,
+        );
+    }
+
+    #[test]
+    fn test_invalidate_copies() {
+        assert_pass_result(
+            propagate_copies,
+            r#"procedure CoreProof "test" {
+                var a: i32;
+                var b: i32;
+                entry bb0;
+                exit bb0;
+                bb0: {
+                    ghost_assign b := 1i32;
+                    old_label l;
+                    assert (old[l](b) == b);
+                    ghost_havoc b;
+                    assert (b > 0i32);
+                    ghost_assign b := a;
+                    ghost_havoc a;
+                    assert (b > 0i32);
+                    return;
+                }
+            }"#,
+            r#"procedure CoreProof "test" {
+                var a: i32;
+                var b: i32;
+                entry bb0;
+                exit bb0;
+                bb0: {
+                    ghost_assign b := 1i32;
+                    old_label l;
+                    assert (old[l](b) == 1i32);
+                    ghost_havoc b;
+                    assert (b > 0i32);
+                    ghost_assign b := a;
+                    ghost_havoc a;
+                    assert (b > 0i32);
+                    return;
+                }
+            }"#,
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use super::helpers::{collect_ghost_variables, expression_variables, statement_variables};
+use rustc_hash::FxHashSet;
+use vir_crate::{
+    common::expression::SyntacticEvaluation,
+    middle::{self as vir_mid},
+};
+
+/// Removes assertions and assumptions of `true` as well as assignments to and
+/// havocs of ghost variables that are never read.
+pub(super) fn eliminate_dead_statements(
+    mut procedure: vir_mid::ProcedureDecl,
+) -> vir_mid::ProcedureDecl {
+    let ghost_variables = collect_ghost_variables(&procedure);
+    let read_variables = collect_read_variables(&procedure);
+    let is_dead_ghost_variable = |variable: &vir_mid::VariableDecl| {
+        ghost_variables.contains(&variable.name) && !read_variables.contains(&variable.name)
+    };
+    for block in procedure.basic_blocks.values_mut() {
+        block.statements.retain(|statement| match statement {
+            vir_mid::Statement::Assume(statement) => !statement.expression.is_true(),
+            vir_mid::Statement::Assert(statement) => !statement.expression.is_true(),
+            vir_mid::Statement::GhostAssign(vir_mid::statement::GhostAssign {
+                target: vir_mid::Expression::Local(local),
+                ..
+            }) => !is_dead_ghost_variable(&local.variable),
+            vir_mid::Statement::GhostHavoc(statement) => {
+                !is_dead_ghost_variable(&statement.variable)
+            }
+            _ => true,
+        });
+    }
+    procedure
+}
+
+/// Collects the variables that are read by some statement or switch. The
+/// targets of ghost assignments and havocs are not considered to be read.
+fn collect_read_variables(procedure: &vir_mid::ProcedureDecl) -> FxHashSet<String> {
+    let mut variables = FxHashSet::default();
+    for block in procedure.basic_blocks.values() {
+        for statement in &block.statements {
+            match statement {
+                vir_mid::Statement::GhostAssign(vir_mid::statement::GhostAssign {
+                    target: vir_mid::Expression::Local(_),
+                    value,
+                    ..
+                }) => variables.extend(expression_variables(value)),
+                vir_mid::Statement::GhostHavoc(_) => {}
+                _ => variables.extend(statement_variables(statement)),
+            }
+        }
+        if let vir_mid::Successor::GotoSwitch(targets) = &block.successor {
+            for (test, _) in targets {
+                variables.extend(expression_variables(test));
+            }
+        }
+    }
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::helpers::assert_pass_result, eliminate_dead_statements};
+
+    #[test]
+    fn test_eliminate_dead_statements() {
+        assert_pass_result(
+            eliminate_dead_statements,
+            r#"procedure CoreProof "test" {
+                var a: i32;
+                var b: i32;
+                var c: i32;
+                var d: i32;
+                entry bb0;
+                exit bb1;
+                bb0: {
+                    // kept
+                    assume true;
+                    assert true;
+                    ghost_havoc a;
+                    ghost_assign b := a;
+                    ghost_assign c := 1i32;
+                    ghost_havoc d;
+                    assert (c > 0i32);
+                    goto bb1;
+                }
+                bb1: {
+                    assume (d > 0i32);
+                    return;
+                }
+            }"#,
+            r#"procedure CoreProof "test" {
+                var a: i32;
+                var b: i32;
+                var c: i32;
+                var d: i32;
+                entry bb0;
+                exit bb1;
+                bb0: {
+                    // kept
+                    ghost_havoc a;
+                    ghost_assign c := 1i32;
+                    ghost_havoc d;
+                    assert (c > 0i32);
+                    goto bb1;
+                }
+                bb1: {
+                    assume (d > 0i32);
+                    return;
+                }
+            }"#,
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use rustc_hash::FxHashSet;
+use vir_crate::middle::{
+    self as vir_mid,
+    ast::{
+        expression::visitors::ExpressionWalker, predicate::visitors::PredicateWalker,
+        rvalue::visitors::RvalueWalker, statement::visitors::StatementWalker,
+    },
+};
+
+/// Collects the names of all variables mentioned in a statement, including
+/// the variables mentioned in its predicates and rvalues.
+#[derive(Default)]
+struct VariableCollector {
+    variables: FxHashSet<String>,
+}
+
+impl ExpressionWalker for VariableCollector {
+    fn walk_variable_decl(&mut self, variable: &vir_mid::VariableDecl) {
+        self.variables.insert(variable.name.clone());
+    }
+}
+
+impl PredicateWalker for VariableCollector {
+    fn walk_expression(&mut self, expression: &vir_mid::Expression) {
+        ExpressionWalker::walk_expression(self, expression);
+    }
+}
+
+impl RvalueWalker for VariableCollector {
+    fn walk_expression(&mut self, expression: &vir_mid::Expression) {
+        ExpressionWalker::walk_expression(self, expression);
+    }
+    fn walk_operand(&mut self, operand: &vir_mid::Operand) {
+        ExpressionWalker::walk_expression(self, &operand.expression);
+    }
+}
+
+impl StatementWalker for VariableCollector {
+    fn walk_expression(&mut self, expression: &vir_mid::Expression) {
+        ExpressionWalker::walk_expression(self, expression);
+    }
+    fn walk_predicate(&mut self, predicate: &vir_mid::Predicate) {
+        PredicateWalker::walk_predicate(self, predicate);
+    }
+    fn walk_rvalue(&mut self, rvalue: &vir_mid::Rvalue) {
+        RvalueWalker::walk_rvalue(self, rvalue);
+    }
+    fn walk_operand(&mut self, operand: &vir_mid::Operand) {
+        ExpressionWalker::walk_expression(self, &operand.expression);
+    }
+    fn walk_variable_decl(&mut self, variable: &vir_mid::VariableDecl) {
+        self.variables.insert(variable.name.clone());
+    }
+}
+
+pub(super) fn statement_variables(statement: &vir_mid::Statement) -> FxHashSet<String> {
+    let mut collector = VariableCollector::default();
+    StatementWalker::walk_statement(&mut collector, statement);
+    collector.variables
+}
+
+pub(super) fn expression_variables(expression: &vir_mid::Expression) -> FxHashSet<String> {
+    let mut collector = VariableCollector::default();
+    ExpressionWalker::walk_expression(&mut collector, expression);
+    collector.variables
+}
+
+/// Returns the name of the variable assigned by a `GhostAssign` or havocked by
+/// a `GhostHavoc` statement.
+pub(super) fn ghost_target(statement: &vir_mid::Statement) -> Option<&str> {
+    match statement {
+        vir_mid::Statement::GhostAssign(vir_mid::statement::GhostAssign {
+            target: vir_mid::Expression::Local(local),
+            ..
+        }) => Some(&local.variable.name),
+        vir_mid::Statement::GhostHavoc(statement) => Some(&statement.variable.name),
+        _ => None,
+    }
+}
+
+/// Returns true if the statement only reads the variables it mentions.
+pub(super) fn is_read_only(statement: &vir_mid::Statement) -> bool {
+    matches!(
+        statement,
+        vir_mid::Statement::Comment(_)
+            | vir_mid::Statement::OldLabel(_)
+            | vir_mid::Statement::Assume(_)
+            | vir_mid::Statement::Assert(_)
+    )
+}
+
+/// Ghost variables are variables that are changed only by `GhostAssign` and
+/// `GhostHavoc` statements and otherwise only read by assertions,
+/// assumptions, and the values of ghost assignments. Since they have no
+/// permissions associated with them, their values can be freely propagated.
+pub(super) fn collect_ghost_variables(procedure: &vir_mid::ProcedureDecl) -> FxHashSet<String> {
+    let mut candidates = FxHashSet::default();
+    let mut non_ghost = FxHashSet::default();
+    for block in procedure.basic_blocks.values() {
+        for statement in &block.statements {
+            if let Some(target) = ghost_target(statement) {
+                candidates.insert(target.to_string());
+            } else if let vir_mid::Statement::GhostAssign(statement) = statement {
+                // The target is not a variable, but a place.
+                non_ghost.extend(expression_variables(&statement.target));
+            } else if !is_read_only(statement) {
+                non_ghost.extend(statement_variables(statement));
+            }
+        }
+    }
+    candidates.retain(|variable| !non_ghost.contains(variable));
+    candidates
+}
+
+/// The blocks referred to by the block marker conditions of the statements.
+/// Such blocks must be kept even if they become unreachable because their
+/// markers are still used.
+pub(super) fn collect_referenced_blocks(
+    procedure: &vir_mid::ProcedureDecl,
+) -> FxHashSet<vir_mid::BasicBlockId> {
+    let mut blocks = FxHashSet::default();
+    for block in procedure.basic_blocks.values() {
+        for statement in &block.statements {
+            if let Some(condition) = block_marker_condition(statement) {
+                for element in &condition.elements {
+                    blocks.insert(element.basic_block_id.clone());
+                }
+            }
+        }
+    }
+    blocks
+}
+
+fn block_marker_condition(
+    statement: &vir_mid::Statement,
+) -> Option<&vir_mid::BlockMarkerCondition> {
+    match statement {
+        vir_mid::Statement::Assert(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::FoldOwned(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::UnfoldOwned(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::FoldRef(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::UnfoldRef(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::JoinBlock(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::SplitBlock(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::ConvertOwnedIntoMemoryBlock(statement) => {
+            statement.condition.as_ref()
+        }
+        vir_mid::Statement::RestoreMutBorrowed(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::DeadReference(statement) => statement.condition.as_ref(),
+        vir_mid::Statement::DeadLifetime(statement) => statement.condition.as_ref(),
+        _ => None,
+    }
+}
+
+/// Checks that the pass turns the procedure `input` into the procedure
+/// `expected`, both written in the textual syntax of VIR.
+#[cfg(test)]
+pub(super) fn assert_pass_result(
+    pass: fn(vir_mid::ProcedureDecl) -> vir_mid::ProcedureDecl,
+    input: &str,
+    expected: &str,
+) {
+    let input = vir_mid::ProcedureDecl::from_text(input).unwrap();
+    let expected = vir_mid::ProcedureDecl::from_text(expected).unwrap();
+    assert_eq!(pass(input).to_text().unwrap(), expected.to_text().unwrap());
+}
@@ -0,0 +1,81 @@
+//! Optimization passes that are run on `vir_mid` procedures after the
+//! fold-unfold inference and before lowering them to `vir_low`. The passes
+//! only simplify the procedure to reduce the size of the program Silicon has
+//! to verify and must not change its verification result.
+
+use crate::encoder::{errors::SpannedEncodingResult, Encoder};
+use prusti_common::config;
+use std::io::Write;
+use vir_crate::{
+    common::graphviz::ToGraphviz,
+    middle::{self as vir_mid},
+};
+
+mod branch_simplification;
+mod constant_folding;
+mod copy_propagation;
+mod dead_statement_elimination;
+mod helpers;
+
+use self::{
+    branch_simplification::simplify_branches, constant_folding::fold_constants,
+    copy_propagation::propagate_copies, dead_statement_elimination::eliminate_dead_statements,
+};
+
+fn log_pass<'v, 'tcx: 'v>(
+    encoder: &mut Encoder<'v, 'tcx>,
+    procedure: &vir_mid::ProcedureDecl,
+    pass_name: &str,
+    is_before: bool,
+) {
+    if config::dump_debug_info() {
+        let source_filename = encoder.env().name.source_file_name();
+        let is_before = if is_before { "before" } else { "after" };
+        prusti_common::report::log::report_with_writer(
+            &format!("graphviz_method_mid_pass_{}_{}", pass_name, is_before),
+            format!("{}.{}.dot", source_filename, procedure.name),
+            |writer| procedure.to_graphviz(writer).unwrap(),
+        );
+    }
+}
+
+/// Writes the difference between the procedure before and after the pass if
+/// the pass was selected with `dump_vir_diff`.
+fn log_diff<'v, 'tcx: 'v>(
+    encoder: &mut Encoder<'v, 'tcx>,
+    before: Option<vir_mid::ProcedureDecl>,
+    after: &vir_mid::ProcedureDecl,
+    pass_name: &str,
+) {
+    if let Some(before) = before {
+        let source_filename = encoder.env().name.source_file_name();
+        prusti_common::report::log::report_with_writer(
+            &format!("vir_diff_{}", pass_name),
+            format!("{}.{}.diff", source_filename, after.name),
+            |writer| write!(writer, "{}", before.diff(after)).unwrap(),
+        );
+    }
+}
+
+macro run_pass($pass:ident( $encoder:ident, $procedure:ident )) {
+    log_pass($encoder, &$procedure, stringify!($pass), true);
+    let before = if config::dump_vir_diff().as_deref() == Some(stringify!($pass)) {
+        Some($procedure.clone())
+    } else {
+        None
+    };
+    $procedure = $pass($procedure);
+    log_pass($encoder, &$procedure, stringify!($pass), false);
+    log_diff($encoder, before, &$procedure, stringify!($pass));
+}
+
+pub(super) fn run_passes<'v, 'tcx: 'v>(
+    encoder: &mut Encoder<'v, 'tcx>,
+    mut procedure: vir_mid::ProcedureDecl,
+) -> SpannedEncodingResult<vir_mid::ProcedureDecl> {
+    run_pass!(propagate_copies(encoder, procedure));
+    run_pass!(fold_constants(encoder, procedure));
+    run_pass!(simplify_branches(encoder, procedure));
+    run_pass!(eliminate_dead_statements(encoder, procedure));
+    Ok(procedure)
+}
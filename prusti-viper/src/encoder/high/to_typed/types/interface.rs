@@ -9,6 +9,7 @@ use vir_crate::{
 pub(crate) struct HighToTypedTypeEncoderState {
     encoded_types: BTreeSet<vir_high::Type>,
     encoded_types_inverse: BTreeMap<vir_typed::Type, vir_high::Type>,
+    encoded_type_decls: BTreeMap<vir_typed::Type, vir_typed::TypeDecl>,
 }
 
 pub(crate) trait HighToTypedTypeEncoderInterface {
@@ -34,9 +35,16 @@ impl<'v, 'tcx: 'v> HighToTypedTypeEncoderInterface
         &mut self,
         ty: &vir_typed::Type,
     ) -> SpannedEncodingResult<vir_typed::TypeDecl> {
+        if let Some(type_decl) = self.typed_type_encoder_state.encoded_type_decls.get(ty) {
+            return Ok(type_decl.clone());
+        }
         let high_type = &self.typed_type_encoder_state.encoded_types_inverse[ty];
         let type_decl_high = self.encode_type_def_high(high_type)?;
-        type_decl_high.high_to_typed_type_decl(self)
+        let type_decl = type_decl_high.high_to_typed_type_decl(self)?;
+        self.typed_type_encoder_state
+            .encoded_type_decls
+            .insert(ty.clone(), type_decl.clone());
+        Ok(type_decl)
     }
 
     fn type_from_high_to_typed(
@@ -13,6 +13,7 @@ use prusti_rustc_interface::{errors::MultiSpan, middle::ty};
 use rustc_hash::FxHashMap;
 use std::cell::RefCell;
 use vir_crate::{
+    common::identifier::WithIdentifier,
     high as vir_high,
     middle::{self as vir_mid},
     polymorphic as vir_poly,
@@ -30,6 +31,9 @@ pub(crate) struct HighTypeEncoderState<'tcx> {
 
     // viper_predicate_descriptions: RefCell<FxHashMap<String, ViperPredicateDescription>>,
     viper_predicates: RefCell<FxHashMap<vir_poly::Type, vir_poly::Predicate>>,
+    /// The `vir::middle` declarations of the types, keyed by the identifiers
+    /// of the types with erased lifetimes and const generics.
+    type_decls_mid: RefCell<FxHashMap<String, vir_mid::TypeDecl>>,
 }
 
 // /// All necessary information for encoding a Viper predicate.
@@ -286,9 +290,23 @@ impl<'v, 'tcx: 'v> HighTypeEncoderInterface<'tcx> for super::super::super::Encod
         &mut self,
         ty: &vir_mid::Type,
     ) -> SpannedEncodingResult<vir_mid::TypeDecl> {
-        let high_type =
-            self.decode_type_mid_into_high(ty.erase_lifetimes().erase_const_generics())?;
+        let ty = ty.erase_lifetimes().erase_const_generics();
+        let identifier = ty.get_identifier();
+        if let Some(type_decl) = self
+            .high_type_encoder_state
+            .type_decls_mid
+            .borrow()
+            .get(&identifier)
+        {
+            return Ok(type_decl.clone());
+        }
+        let high_type = self.decode_type_mid_into_high(ty)?;
         let high_type_decl = self.encode_type_def_high(&high_type)?;
-        high_type_decl.high_to_middle(self)
+        let type_decl = high_type_decl.high_to_middle(self)?;
+        self.high_type_encoder_state
+            .type_decls_mid
+            .borrow_mut()
+            .insert(identifier, type_decl.clone());
+        Ok(type_decl)
     }
 }
@@ -0,0 +1,67 @@
+//! A harness that runs randomly generated `vir_high` procedures through the
+//! fold-unfold inference and the lowering to `vir_low` to find crashes of the
+//! core proof encoder that are not covered by the test suite.
+
+use super::lowerer::lower_procedure;
+use crate::encoder::{
+    errors::ErrorCtxt, high::procedures::HighProcedureEncoderInterface,
+    mir::errors::ErrorInterface, Encoder,
+};
+use log::info;
+use prusti_common::report::log::report;
+use prusti_rustc_interface::hir::def_id::DefId;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use vir_crate::high::fuzzing::ProcedureGenerator;
+
+/// A generated procedure that made the encoder panic or fail.
+pub(crate) struct FuzzingFailure {
+    pub(crate) seed: u64,
+    pub(crate) message: String,
+}
+
+/// Generates `iterations` procedures with the seeds `seed`,
+/// `seed + 1`, and so on, and lowers them to `vir_low`, validating them after
+/// each step. `proc_def_id` is the procedure whose span is used for the
+/// positions of the generated statements.
+///
+/// The failing procedures are dumped in the textual format (see
+/// `vir_high::ProcedureDecl::from_text`) to the `vir_fuzzing` log directory.
+/// Note that a panic may leave the encoder in an inconsistent state, so the
+/// failures after the first one may be spurious.
+pub(super) fn fuzz_core_proof<'v, 'tcx: 'v>(
+    encoder: &mut Encoder<'v, 'tcx>,
+    proc_def_id: DefId,
+    seed: u64,
+    iterations: u64,
+) -> Vec<FuzzingFailure> {
+    let span = encoder.env().query.get_def_span(proc_def_id);
+    let mut failures = Vec::new();
+    for seed in seed..seed + iterations {
+        info!("Fuzzing the core proof encoder with seed {}", seed);
+        let position = encoder.register_error(span, ErrorCtxt::Unexpected, proc_def_id);
+        let procedure =
+            ProcedureGenerator::new(seed, position).generate(format!("fuzzing_{}", seed));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let procedure =
+                encoder.lower_procedure_core_proof(proc_def_id, procedure.clone(), true)?;
+            lower_procedure(encoder, proc_def_id, procedure)
+        }));
+        let message = match result {
+            Ok(Ok(_)) => continue,
+            Ok(Err(error)) => format!("encoding error: {:?}", error.kind()),
+            Err(payload) => {
+                if let Some(message) = payload.downcast_ref::<&str>() {
+                    format!("panic: {}", message)
+                } else if let Some(message) = payload.downcast_ref::<String>() {
+                    format!("panic: {}", message)
+                } else {
+                    "panic".to_string()
+                }
+            }
+        };
+        let text = procedure.to_text().unwrap_or_else(|_| procedure.to_string());
+        report("vir_fuzzing", format!("seed_{}.vir", seed), text);
+        failures.push(FuzzingFailure { seed, message });
+    }
+    failures
+}
@@ -5,6 +5,7 @@ use crate::encoder::{
 use log::debug;
 use prusti_common::config;
 use prusti_rustc_interface::{hir::def_id::DefId, middle::ty};
+use rustc_hash::FxHashSet;
 use vir_crate::{
     common::{check_mode::CheckMode, identifier::WithIdentifier},
     low::{self as vir_low},
@@ -13,6 +14,10 @@ use vir_crate::{
 #[derive(Default)]
 pub(crate) struct MidCoreProofEncoderState {
     encoded_programs: Vec<vir_low::Program>,
+    /// The identifiers of the types for which a program was already encoded.
+    /// Different Rust types (for example, ones that differ only in lifetimes)
+    /// can have the same core proof encoding.
+    encoded_types: FxHashSet<(String, CheckMode)>,
 }
 
 pub(crate) trait MidCoreProofEncoderInterface<'tcx> {
@@ -27,6 +32,14 @@ pub(crate) trait MidCoreProofEncoderInterface<'tcx> {
         check_mode: CheckMode,
     ) -> SpannedEncodingResult<()>;
     fn take_core_proof_programs(&mut self) -> Vec<vir_low::Program>;
+    /// Runs the fuzzing harness of the core proof encoder (see
+    /// `config::fuzz_core_proof`).
+    fn fuzz_core_proof(
+        &mut self,
+        proc_def_id: DefId,
+        seed: u64,
+        iterations: u64,
+    ) -> Vec<super::FuzzingFailure>;
 }
 
 impl<'v, 'tcx: 'v> MidCoreProofEncoderInterface<'tcx> for super::super::super::Encoder<'v, 'tcx> {
@@ -62,6 +75,11 @@ impl<'v, 'tcx: 'v> MidCoreProofEncoderInterface<'tcx> for super::super::super::E
         if config::inline_caller_for() {
             super::transformations::inline_functions::inline_caller_for(&mut program);
         }
+        if config::remove_unused_definitions() {
+            super::transformations::remove_unused_definitions::remove_unused_definitions(
+                &mut program,
+            );
+        }
         self.mid_core_proof_encoder_state
             .encoded_programs
             .push(program);
@@ -93,6 +111,14 @@ impl<'v, 'tcx: 'v> MidCoreProofEncoderInterface<'tcx> for super::super::super::E
         };
         let ty = self.encode_type_core_proof(ty, check_mode)?;
         let name = ty.get_identifier();
+        if !self
+            .mid_core_proof_encoder_state
+            .encoded_types
+            .insert((name.clone(), check_mode))
+        {
+            debug!("The core proof for type {} is already encoded", name);
+            return Ok(());
+        }
         let super::lowerer::LoweringResult {
             procedures,
             domains,
@@ -113,6 +139,11 @@ impl<'v, 'tcx: 'v> MidCoreProofEncoderInterface<'tcx> for super::super::super::E
         if config::inline_caller_for() {
             super::transformations::inline_functions::inline_caller_for(&mut program);
         }
+        if config::remove_unused_definitions() {
+            super::transformations::remove_unused_definitions::remove_unused_definitions(
+                &mut program,
+            );
+        }
         self.mid_core_proof_encoder_state
             .encoded_programs
             .push(program);
@@ -122,4 +153,13 @@ impl<'v, 'tcx: 'v> MidCoreProofEncoderInterface<'tcx> for super::super::super::E
     fn take_core_proof_programs(&mut self) -> Vec<vir_low::Program> {
         std::mem::take(&mut self.mid_core_proof_encoder_state.encoded_programs)
     }
+
+    fn fuzz_core_proof(
+        &mut self,
+        proc_def_id: DefId,
+        seed: u64,
+        iterations: u64,
+    ) -> Vec<super::FuzzingFailure> {
+        super::fuzzing::fuzz_core_proof(self, proc_def_id, seed, iterations)
+    }
 }
@@ -985,10 +985,25 @@ impl IntoLow for vir_mid::Predicate {
                 lowerer.encode_memory_block_stack_drop_acc(place, size, predicate.position)?
             }
             Predicate::MemoryBlockHeap(predicate) => {
-                unimplemented!("predicate: {}", predicate);
+                lowerer.encode_memory_block_predicate()?;
+                let pointer = predicate.address.to_procedure_snapshot(lowerer)?;
+                let address = lowerer.obtain_constant_value(
+                    predicate.address.get_type(),
+                    pointer,
+                    predicate.position,
+                )?;
+                let size = predicate.size.to_procedure_snapshot(lowerer)?;
+                expr! { acc(MemoryBlock([address], [size]))}.set_default_position(predicate.position)
             }
             Predicate::MemoryBlockHeapDrop(predicate) => {
-                unimplemented!("predicate: {}", predicate);
+                let pointer = predicate.address.to_procedure_snapshot(lowerer)?;
+                let address = lowerer.obtain_constant_value(
+                    predicate.address.get_type(),
+                    pointer,
+                    predicate.position,
+                )?;
+                let size = predicate.size.to_procedure_snapshot(lowerer)?;
+                lowerer.encode_memory_block_heap_drop_acc(address, size, predicate.position)?
             }
             Predicate::OwnedNonAliased(predicate) => {
                 let place = lowerer.encode_expression_as_place(&predicate.place)?;
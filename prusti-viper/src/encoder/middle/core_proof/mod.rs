@@ -6,6 +6,7 @@ mod compute_address;
 mod const_generics;
 mod errors;
 mod function_gas;
+mod fuzzing;
 mod interface;
 mod into_low;
 mod lifetimes;
@@ -19,4 +20,7 @@ mod type_layouts;
 mod types;
 mod utils;
 
-pub(crate) use self::interface::{MidCoreProofEncoderInterface, MidCoreProofEncoderState};
+pub(crate) use self::{
+    fuzzing::FuzzingFailure,
+    interface::{MidCoreProofEncoderInterface, MidCoreProofEncoderState},
+};
@@ -85,6 +85,12 @@ pub(in super::super::super) trait PredicatesMemoryBlockInterface {
         size: vir_low::Expression,
         position: vir_low::Position,
     ) -> SpannedEncodingResult<vir_low::Expression>;
+    fn encode_memory_block_heap_drop_acc(
+        &mut self,
+        address: vir_low::Expression,
+        size: vir_low::Expression,
+        position: vir_low::Position,
+    ) -> SpannedEncodingResult<vir_low::Expression>;
     fn encode_memory_block_bytes_function_name(&mut self) -> SpannedEncodingResult<String>;
     fn encode_memory_block_bytes_expression(
         &mut self,
@@ -108,6 +114,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> PredicatesMemoryBlockInterface for Lowerer<'p, 'v, 't
     ) -> SpannedEncodingResult<vir_low::Expression> {
         self.encode_generic_memory_block_acc("MemoryBlockStackDrop", place, size, position)
     }
+    fn encode_memory_block_heap_drop_acc(
+        &mut self,
+        address: vir_low::Expression,
+        size: vir_low::Expression,
+        position: vir_low::Position,
+    ) -> SpannedEncodingResult<vir_low::Expression> {
+        self.encode_generic_memory_block_acc("MemoryBlockHeapDrop", address, size, position)
+    }
     fn encode_memory_block_bytes_function_name(&mut self) -> SpannedEncodingResult<String> {
         Ok("MemoryBlock$bytes".to_string())
     }
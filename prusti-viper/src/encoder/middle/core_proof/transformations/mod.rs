@@ -1,3 +1,4 @@
 pub(super) mod inline_functions;
 pub(super) mod remove_predicates;
+pub(super) mod remove_unused_definitions;
 pub(super) mod remove_unvisited_blocks;
@@ -0,0 +1,216 @@
+//! Removes the functions, predicates, methods, and domains that are not
+//! (transitively) used by the procedures and the methods with bodies of a
+//! program, so that the program sent to the verifier contains only what is
+//! needed to verify them.
+//!
+//! The definitions are still encoded and only removed afterwards: the encoding
+//! is not demand-driven. A domain whose axioms mention a used function or a
+//! function of a used domain is kept, because its axioms may be needed to
+//! reason about the used definitions.
+
+use rustc_hash::FxHashSet;
+use vir_crate::low::{
+    self as vir_low,
+    ast::statement::visitors::{default_walk_statement, StatementWalker},
+    expression::visitors::{default_walk_expression, ExpressionWalker},
+};
+
+pub(crate) fn remove_unused_definitions(program: &mut vir_low::Program) {
+    let mut collector = UsedDefinitions::default();
+    for procedure in &program.procedures {
+        collector.walk_variables(&procedure.locals);
+        for block in &procedure.basic_blocks {
+            collector.walk_statements(&block.statements);
+            if let vir_low::Successor::GotoSwitch(targets) = &block.successor {
+                for (test, _) in targets {
+                    ExpressionWalker::walk_expression(&mut collector, test);
+                }
+            }
+        }
+    }
+    for method in &program.methods {
+        if method.body.is_some() {
+            collector.methods.insert(method.name.clone());
+        }
+    }
+    // The definitions can refer to each other, so we iterate until no new
+    // definition is found.
+    let mut visited = UsedDefinitions::default();
+    loop {
+        let mut changed = false;
+        for function in &program.functions {
+            if collector.functions.contains(&function.name)
+                && visited.functions.insert(function.name.clone())
+            {
+                changed = true;
+                collector.walk_variables(&function.parameters);
+                collector.collect_type(&function.return_type);
+                collector.walk_expressions(function.pres.iter().chain(&function.posts));
+                collector.walk_expressions(&function.body);
+            }
+        }
+        for predicate in &program.predicates {
+            if collector.predicates.contains(&predicate.name)
+                && visited.predicates.insert(predicate.name.clone())
+            {
+                changed = true;
+                collector.walk_variables(&predicate.parameters);
+                collector.walk_expressions(&predicate.body);
+            }
+        }
+        for method in &program.methods {
+            if collector.methods.contains(&method.name)
+                && visited.methods.insert(method.name.clone())
+            {
+                changed = true;
+                collector.walk_variables(method.parameters.iter().chain(&method.targets));
+                collector.walk_expressions(method.pres.iter().chain(&method.posts));
+                if let Some(body) = &method.body {
+                    collector.walk_statements(body);
+                }
+            }
+        }
+        for domain in &program.domains {
+            if (collector.domains.contains(&domain.name)
+                || collector.is_mentioned_by_axioms(domain))
+                && visited.domains.insert(domain.name.clone())
+            {
+                collector.domains.insert(domain.name.clone());
+                changed = true;
+                for function in &domain.functions {
+                    collector.walk_variables(&function.parameters);
+                    collector.collect_type(&function.return_type);
+                }
+                collector.walk_expressions(domain.axioms.iter().map(|axiom| &axiom.body));
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    program
+        .functions
+        .retain(|function| visited.functions.contains(&function.name));
+    program
+        .predicates
+        .retain(|predicate| visited.predicates.contains(&predicate.name));
+    program
+        .methods
+        .retain(|method| visited.methods.contains(&method.name));
+    program
+        .domains
+        .retain(|domain| visited.domains.contains(&domain.name));
+}
+
+#[derive(Default)]
+struct UsedDefinitions {
+    functions: FxHashSet<String>,
+    predicates: FxHashSet<String>,
+    methods: FxHashSet<String>,
+    domains: FxHashSet<String>,
+}
+
+impl UsedDefinitions {
+    /// Whether an axiom of the domain mentions a used function or a function of
+    /// another used domain.
+    fn is_mentioned_by_axioms(&self, domain: &vir_low::DomainDecl) -> bool {
+        let mut mentioned = UsedDefinitions::default();
+        mentioned.walk_expressions(domain.axioms.iter().map(|axiom| &axiom.body));
+        mentioned
+            .functions
+            .iter()
+            .any(|function| self.functions.contains(function))
+            || mentioned
+                .domains
+                .iter()
+                .any(|name| name != &domain.name && self.domains.contains(name))
+    }
+
+    fn walk_statements(&mut self, statements: &[vir_low::Statement]) {
+        for statement in statements {
+            StatementWalker::walk_statement(self, statement);
+        }
+    }
+
+    fn walk_expressions<'a>(
+        &mut self,
+        expressions: impl IntoIterator<Item = &'a vir_low::Expression>,
+    ) {
+        for expression in expressions {
+            ExpressionWalker::walk_expression(self, expression);
+        }
+    }
+
+    fn walk_variables<'a>(
+        &mut self,
+        variables: impl IntoIterator<Item = &'a vir_low::VariableDecl>,
+    ) {
+        for variable in variables {
+            self.collect_type(&variable.ty);
+        }
+    }
+
+    fn collect_type(&mut self, ty: &vir_low::Type) {
+        match ty {
+            vir_low::Type::Domain(domain) => {
+                self.domains.insert(domain.name.clone());
+            }
+            vir_low::Type::Seq(vir_low::ty::Seq { element_type })
+            | vir_low::Type::Set(vir_low::ty::Set { element_type })
+            | vir_low::Type::MultiSet(vir_low::ty::MultiSet { element_type }) => {
+                self.collect_type(element_type);
+            }
+            vir_low::Type::Map(map) => {
+                self.collect_type(&map.key_type);
+                self.collect_type(&map.val_type);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ExpressionWalker for UsedDefinitions {
+    fn walk_expression(&mut self, expression: &vir_low::Expression) {
+        match expression {
+            vir_low::Expression::FuncApp(app) => {
+                self.functions.insert(app.function_name.clone());
+            }
+            vir_low::Expression::DomainFuncApp(app) => {
+                self.domains.insert(app.domain_name.clone());
+            }
+            vir_low::Expression::PredicateAccessPredicate(predicate) => {
+                self.predicates.insert(predicate.name.clone());
+            }
+            vir_low::Expression::Unfolding(unfolding) => {
+                self.predicates.insert(unfolding.predicate.clone());
+            }
+            _ => {}
+        }
+        default_walk_expression(self, expression);
+    }
+
+    fn walk_variable_decl(&mut self, variable: &vir_low::VariableDecl) {
+        self.collect_type(&variable.ty);
+    }
+
+    fn walk_type(&mut self, ty: &vir_low::Type) {
+        self.collect_type(ty);
+    }
+}
+
+impl StatementWalker for UsedDefinitions {
+    fn walk_statement(&mut self, statement: &vir_low::Statement) {
+        if let vir_low::Statement::MethodCall(call) = statement {
+            self.methods.insert(call.method_name.clone());
+        }
+        default_walk_statement(self, statement);
+    }
+
+    fn walk_expression(&mut self, expression: &vir_low::Expression) {
+        ExpressionWalker::walk_expression(self, expression);
+    }
+
+    fn walk_variable_decl(&mut self, variable: &vir_low::VariableDecl) {
+        self.collect_type(&variable.ty);
+    }
+}
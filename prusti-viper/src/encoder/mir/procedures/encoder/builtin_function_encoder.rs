@@ -145,6 +145,109 @@ impl<'p, 'v, 'tcx> BuiltinFuncAppEncoder<'p, 'v, 'tcx> for super::ProcedureEncod
             Ok(())
         };
 
+        // The contents of the heap memory are not modelled, so the value
+        // returned by the call is left unconstrained. The `required` blocks
+        // have to be available, the `consumed` blocks are taken away, and the
+        // `produced` blocks are obtained after the call.
+        let make_heap_memory_call = |encoder: &mut Self,
+                                     block_builder: &mut BasicBlockBuilder,
+                                     required: Vec<vir_high::Predicate>,
+                                     consumed: Vec<vir_high::Predicate>,
+                                     produced: Vec<vir_high::Predicate>|
+         -> SpannedEncodingResult<()> {
+            for predicate in required.iter().cloned().chain(consumed) {
+                block_builder.add_statement(encoder.encoder.set_statement_error_ctxt(
+                    vir_high::Statement::exhale_no_pos(predicate),
+                    span,
+                    ErrorCtxt::HeapMemoryAccess,
+                    encoder.def_id,
+                )?);
+            }
+            for predicate in required {
+                block_builder.add_statement(encoder.encoder.set_statement_error_ctxt(
+                    vir_high::Statement::inhale_no_pos(predicate),
+                    span,
+                    ErrorCtxt::ProcedureCall,
+                    encoder.def_id,
+                )?);
+            }
+            for arg in args {
+                let encoded_arg = encoder.encode_statement_operand(location, arg)?;
+                block_builder.add_statement(encoder.encoder.set_statement_error_ctxt(
+                    vir_high::Statement::consume_no_pos(encoded_arg),
+                    span,
+                    ErrorCtxt::ProcedureCall,
+                    encoder.def_id,
+                )?);
+            }
+            let target_place_local = if let Some(target_place_local) = destination.as_local() {
+                target_place_local
+            } else {
+                return Err(SpannedEncodingError::unsupported(
+                    "the result of a heap memory operation must be stored in a local variable",
+                    span,
+                ));
+            };
+            let encoded_target_place = encoder
+                .encoder
+                .encode_place_high(encoder.mir, destination, None)?;
+            let size = encoder.encoder.encode_type_size_expression(
+                encoder
+                    .encoder
+                    .get_local_type(encoder.mir, target_place_local)?,
+            )?;
+            block_builder.add_statement(encoder.encoder.set_statement_error_ctxt(
+                vir_high::Statement::exhale_no_pos(vir_high::Predicate::memory_block_stack_no_pos(
+                    encoded_target_place.clone(),
+                    size,
+                )),
+                span,
+                ErrorCtxt::ProcedureCall,
+                encoder.def_id,
+            )?);
+            block_builder.add_statement(encoder.encoder.set_statement_error_ctxt(
+                vir_high::Statement::inhale_no_pos(
+                    vir_high::Predicate::owned_non_aliased_no_pos(encoded_target_place),
+                ),
+                span,
+                ErrorCtxt::ProcedureCall,
+                encoder.def_id,
+            )?);
+            for predicate in produced {
+                block_builder.add_statement(encoder.encoder.set_statement_error_ctxt(
+                    vir_high::Statement::inhale_no_pos(predicate),
+                    span,
+                    ErrorCtxt::ProcedureCall,
+                    encoder.def_id,
+                )?);
+            }
+            let target_block = target.unwrap();
+            encoder.encode_lft_for_block(target_block, location, block_builder)?;
+            let target_label = encoder.encode_basic_block_label(target_block);
+            block_builder.set_successor_jump(vir_high::Successor::Goto(target_label));
+            Ok(())
+        };
+
+        // The size of the memory described by a layout is known only if the
+        // layout was created by `Layout::new`.
+        let layout_size = |encoder: &mut Self,
+                           layout: &mir::Operand<'tcx>|
+         -> SpannedEncodingResult<vir_high::Expression> {
+            let layout_ty = layout
+                .place()
+                .and_then(|place| place.as_local())
+                .and_then(|local| encoder.layouts.get(&local).copied());
+            if let Some(layout_ty) = layout_ty {
+                encoder.encoder.encode_type_size_expression(layout_ty)
+            } else {
+                Err(SpannedEncodingError::unsupported(
+                    "only layouts created by `Layout::new` directly in the call \
+                    are supported when allocating memory",
+                    span,
+                ))
+            }
+        };
+
         if let Some(op_name) = full_called_function_name
             .as_str()
             .strip_prefix("std::ops::")
@@ -196,7 +299,8 @@ impl<'p, 'v, 'tcx> BuiltinFuncAppEncoder<'p, 'v, 'tcx> for super::ProcedureEncod
                         vir_high::Successor::Goto(self.encode_basic_block_label(*cleanup));
                     block_builder.set_successor_jump(successor);
                 } else {
-                    unimplemented!();
+                    // The panic aborts the program.
+                    block_builder.set_successor(SuccessorBuilder::exit_resume_panic());
                 }
             }
             "prusti_contracts::Int::new" => {
@@ -241,6 +345,51 @@ impl<'p, 'v, 'tcx> BuiltinFuncAppEncoder<'p, 'v, 'tcx> for super::ProcedureEncod
             "prusti_contracts::snapshot_equality" => {
                 unreachable!();
             }
+            "std::alloc::Layout::new" | "core::alloc::Layout::new" => {
+                if let Some(local) = destination.as_local() {
+                    // A local that is assigned more than once may hold a
+                    // different layout when the memory is allocated.
+                    if is_assigned_once(self.mir, local) {
+                        self.layouts.insert(local, call_substs.type_at(0));
+                    } else {
+                        self.layouts.remove(&local);
+                    }
+                }
+                return Ok(false);
+            }
+            "std::alloc::alloc" | "alloc::alloc::alloc" => {
+                // Allocation failures are not modelled.
+                let size = layout_size(self, &args[0])?;
+                let address = self
+                    .encoder
+                    .encode_place_high(self.mir, destination, None)?;
+                let produced = vec![
+                    vir_high::Predicate::memory_block_heap_no_pos(address.clone(), size.clone()),
+                    vir_high::Predicate::memory_block_heap_drop_no_pos(address, size),
+                ];
+                make_heap_memory_call(self, block_builder, Vec::new(), Vec::new(), produced)?
+            }
+            "std::alloc::dealloc" | "alloc::alloc::dealloc" => {
+                let size = layout_size(self, &args[1])?;
+                let address = self
+                    .encode_statement_operand(location, &args[0])?
+                    .expression;
+                let consumed = vec![
+                    vir_high::Predicate::memory_block_heap_no_pos(address.clone(), size.clone()),
+                    vir_high::Predicate::memory_block_heap_drop_no_pos(address, size),
+                ];
+                make_heap_memory_call(self, block_builder, Vec::new(), consumed, Vec::new())?
+            }
+            "std::ptr::read" | "core::ptr::read" | "std::ptr::write" | "core::ptr::write" => {
+                let size = self
+                    .encoder
+                    .encode_type_size_expression(call_substs.type_at(0))?;
+                let address = self
+                    .encode_statement_operand(location, &args[0])?
+                    .expression;
+                let required = vec![vir_high::Predicate::memory_block_heap_no_pos(address, size)];
+                make_heap_memory_call(self, block_builder, required, Vec::new(), Vec::new())?
+            }
             "std::ops::Index::index" | "core::ops::Index::index" => {
                 let lhs = self
                     .encode_statement_operand(location, &args[0])?
@@ -285,3 +434,28 @@ impl<'p, 'v, 'tcx> BuiltinFuncAppEncoder<'p, 'v, 'tcx> for super::ProcedureEncod
         Ok(true)
     }
 }
+
+/// Checks whether `local` is the target of exactly one assignment or call in
+/// `body`.
+fn is_assigned_once(body: &mir::Body, local: mir::Local) -> bool {
+    let mut count = 0;
+    for block in body.basic_blocks.iter() {
+        for statement in &block.statements {
+            if let mir::StatementKind::Assign(box (target, _)) = &statement.kind {
+                if target.local == local {
+                    count += 1;
+                }
+            }
+        }
+        if let Some(mir::Terminator {
+            kind: mir::TerminatorKind::Call { destination, .. },
+            ..
+        }) = &block.terminator
+        {
+            if destination.local == local {
+                count += 1;
+            }
+        }
+    }
+    count == 1
+}
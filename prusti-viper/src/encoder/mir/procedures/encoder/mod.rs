@@ -105,7 +105,10 @@ pub(super) fn encode_procedure<'v, 'tcx: 'v>(
         specification_blocks,
         specification_block_encoding: Default::default(),
         loop_invariant_encoding: Default::default(),
-        check_panics: config::check_panics() && check_mode != CheckMode::CoreProof,
+        // A panic during const evaluation is a compilation error.
+        check_panics: (config::check_panics()
+            || config::check_const_fns() && encoder.env().tcx().is_const_fn_raw(def_id))
+            && check_mode != CheckMode::CoreProof,
         locals_without_explicit_allocation,
         used_locals: Default::default(),
         fresh_id_generator: 0,
@@ -118,6 +121,7 @@ pub(super) fn encode_procedure<'v, 'tcx: 'v>(
         reborrow_lifetimes_to_remove_for_block,
         current_basic_block,
         termination_variable: None,
+        layouts: Default::default(),
     };
     procedure_encoder.encode()
 }
@@ -158,6 +162,11 @@ struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     reborrow_lifetimes_to_remove_for_block: BTreeMap<mir::BasicBlock, BTreeSet<String>>,
     current_basic_block: Option<mir::BasicBlock>,
     termination_variable: Option<vir_high::VariableDecl>,
+    /// The types whose layouts were stored in the given locals by calls to
+    /// `Layout::new`. Used for computing the sizes of heap memory blocks.
+    /// Only locals that are assigned exactly once are recorded, so that an
+    /// entry cannot be outdated by a later reassignment.
+    layouts: BTreeMap<mir::Local, ty::Ty<'tcx>>,
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
@@ -1800,12 +1809,41 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             } else {
                 unimplemented!();
             }
-        } else if let Some(_cleanup_block) = cleanup {
+        } else if let Some(cleanup_block) = cleanup {
+            // The called function returns `!`, so the only way to leave it is
+            // by unwinding.
             // TODO: add panic postconditions.
-            unimplemented!();
+            self.encode_inhale_lifetime_tokens(
+                block_builder,
+                &lifetimes_to_exhale_inhale,
+                derived_from.len(),
+            )?;
+            let function_lifetime_return = self.encoder.set_statement_error_ctxt(
+                vir_high::Statement::lifetime_return_no_pos(
+                    function_call_lifetime,
+                    derived_from.clone(),
+                    self.lifetime_token_fractional_permission(
+                        self.lifetime_count * derived_from.len(),
+                    ),
+                ),
+                self.mir.span,
+                ErrorCtxt::LifetimeInhale,
+                self.def_id,
+            )?;
+            block_builder.add_statement(function_lifetime_return);
+            self.encode_lft_for_block(*cleanup_block, location, block_builder)?;
+            let encoded_cleanup_block = self.encode_basic_block_label(*cleanup_block);
+            block_builder.set_successor_jump(vir_high::Successor::Goto(encoded_cleanup_block));
         } else {
-            // TODO: Can we always soundly assume false here?
-            unimplemented!();
+            // The called function neither returns nor unwinds, so the code
+            // after the call is unreachable.
+            block_builder.add_statement(self.encoder.set_statement_error_ctxt(
+                vir_high::Statement::assume_no_pos(false.into()),
+                span,
+                ErrorCtxt::ProcedureCall,
+                self.def_id,
+            )?);
+            block_builder.set_successor(SuccessorBuilder::exit_resume_panic());
         }
 
         Ok(())
@@ -1,5 +1,6 @@
 use crate::encoder::{errors::SpannedEncodingResult, Encoder};
 use prusti_common::config;
+use std::io::Write;
 use vir_crate::{
     common::graphviz::ToGraphviz,
     high::{self as vir_high},
@@ -7,8 +8,12 @@ use vir_crate::{
 
 mod assertions;
 mod loop_desugaring;
+mod termination_only;
 
-pub(super) use self::{assertions::propagate_assertions_back, loop_desugaring::desugar_loops};
+pub(super) use self::{
+    assertions::propagate_assertions_back, loop_desugaring::desugar_loops,
+    termination_only::assume_non_termination_assertions,
+};
 
 fn log_pass<'v, 'tcx: 'v>(
     encoder: &mut Encoder<'v, 'tcx>,
@@ -27,10 +32,34 @@ fn log_pass<'v, 'tcx: 'v>(
     }
 }
 
+/// Writes the difference between the procedure before and after the pass if
+/// the pass was selected with `dump_vir_diff`.
+fn log_diff<'v, 'tcx: 'v>(
+    encoder: &mut Encoder<'v, 'tcx>,
+    before: Option<vir_high::ProcedureDecl>,
+    after: &vir_high::ProcedureDecl,
+    pass_name: &str,
+) {
+    if let Some(before) = before {
+        let source_filename = encoder.env().name.source_file_name();
+        prusti_common::report::log::report_with_writer(
+            &format!("vir_diff_{}", pass_name),
+            format!("{}.{}.diff", source_filename, after.name),
+            |writer| write!(writer, "{}", before.diff(after)).unwrap(),
+        );
+    }
+}
+
 macro run_pass($pass:ident( $encoder:ident, $procedure:ident )) {
     log_pass($encoder, &$procedure, stringify!($pass), true);
+    let before = if config::dump_vir_diff().as_deref() == Some(stringify!($pass)) {
+        Some($procedure.clone())
+    } else {
+        None
+    };
     $procedure = $pass($encoder, $procedure)?;
     log_pass($encoder, &$procedure, stringify!($pass), false);
+    log_diff($encoder, before, &$procedure, stringify!($pass));
 }
 
 pub(super) fn run_passes<'v, 'tcx: 'v>(
@@ -39,5 +68,8 @@ pub(super) fn run_passes<'v, 'tcx: 'v>(
 ) -> SpannedEncodingResult<vir_high::ProcedureDecl> {
     run_pass!(desugar_loops(encoder, procedure));
     run_pass!(propagate_assertions_back(encoder, procedure));
+    if config::termination_only() {
+        run_pass!(assume_non_termination_assertions(encoder, procedure));
+    }
     Ok(procedure)
 }
@@ -0,0 +1,28 @@
+use crate::encoder::{errors::SpannedEncodingResult, Encoder};
+use vir_crate::high::{self as vir_high};
+
+/// Turn all assertions except the termination checks into assumptions, so
+/// that only termination is verified while the functional specifications and
+/// the absence of panics are assumed (see the `termination_only` flag).
+pub(in super::super) fn assume_non_termination_assertions<'v, 'tcx: 'v>(
+    encoder: &mut Encoder<'v, 'tcx>,
+    mut procedure: vir_high::ProcedureDecl,
+) -> SpannedEncodingResult<vir_high::ProcedureDecl> {
+    let error_manager = encoder.error_manager();
+    for basic_block in procedure.basic_blocks.values_mut() {
+        for statement in &mut basic_block.statements {
+            if let vir_high::Statement::Assert(assert) = statement {
+                let is_termination_check = error_manager
+                    .get_position_error_ctxt(assert.position.id)
+                    .map_or(false, |error_ctxt| error_ctxt.is_termination_check());
+                if !is_termination_check {
+                    *statement = vir_high::Statement::assume(
+                        assert.expression.clone(),
+                        assert.position,
+                    );
+                }
+            }
+        }
+    }
+    Ok(procedure)
+}
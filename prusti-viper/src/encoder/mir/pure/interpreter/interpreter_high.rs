@@ -18,8 +18,8 @@ use crate::encoder::{
         generics::MirGenericsEncoderInterface,
         places::PlacesEncoderInterface,
         pure::{
-            interpreter::BackwardMirInterpreter, PureEncodingContext, PureFunctionEncoderInterface,
-            SpecificationEncoderInterface,
+            interpreter::BackwardMirInterpreter, purity::explain_impure_call, PureEncodingContext,
+            PureFunctionEncoderInterface, SpecificationEncoderInterface,
         },
         specifications::SpecificationsInterface,
         types::MirTypeEncoderInterface,
@@ -475,13 +475,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ExpressionBackwardInterpreter<'p, 'v, 'tcx> {
                         substs,
                     )?
                 } else {
-                    return Err(SpannedEncodingError::incorrect(
+                    let mut error = SpannedEncodingError::incorrect(
                         format!(
                             "use of impure function {:?} in pure code is not allowed",
                             func_proc_name
                         ),
                         span,
-                    ));
+                    );
+                    explain_impure_call(self.encoder, def_id, &mut error);
+                    return Err(error);
                 }
             } else {
                 // FIXME: Refactor the common code with the procedure encoder.
@@ -676,6 +678,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> ExpressionBackwardInterpreter<'p, 'v, 'tcx> {
                 );
                 subst_with(encoded_rhs)
             }
+            "prusti_contracts::unfolding" => {
+                let encoded_rhs =
+                    vir_high::Expression::and(encoded_args[0].clone(), encoded_args[1].clone());
+                subst_with(encoded_rhs)
+            }
             "prusti_contracts::before_expiry" => {
                 // self.encode_call_before_expiry()?
                 unimplemented!();
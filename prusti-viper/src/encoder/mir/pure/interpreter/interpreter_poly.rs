@@ -9,6 +9,7 @@ use crate::encoder::{
         pure::{
             interpreter::{state_poly::ExprBackwardInterpreterState, BackwardMirInterpreter},
             pure_functions::PureFunctionEncoderInterface,
+            purity::explain_impure_call,
             specifications::SpecificationEncoderInterface,
             PureEncodingContext,
         },
@@ -20,6 +21,7 @@ use crate::encoder::{
     snapshot::interface::SnapshotEncoderInterface,
     Encoder,
 };
+use crate::utils::is_natively_indexed;
 use log::{debug, trace};
 use prusti_common::vir_local;
 use prusti_interface::environment::mir_utils::SliceOrArrayRef;
@@ -100,6 +102,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionBackwardInterpreter<'p, 'v, 'tcx> {
         &self.mir_encoder
     }
 
+    /// Whether `ty` is a `mem::Discriminant`, which is encoded as the
+    /// discriminant of its enum.
+    fn is_discriminant_ty(&self, ty: ty::Ty<'tcx>) -> bool {
+        match ty.kind() {
+            ty::TyKind::Adt(adt_def, _) => matches!(
+                &*self.encoder.env().name.get_absolute_item_name(adt_def.did()),
+                "std::mem::Discriminant" | "core::mem::Discriminant"
+            ),
+            _ => false,
+        }
+    }
+
     fn encode_place(
         &self,
         place: mir::Place<'tcx>,
@@ -475,6 +489,67 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state
                             }
 
+                            // A `mem::Discriminant` is encoded as the discriminant of the enum.
+                            "std::mem::discriminant" | "core::mem::discriminant" => {
+                                assert_eq!(args.len(), 1);
+                                let enum_ty = self.mir_encoder.get_operand_ty(&args[0]).peel_refs();
+                                let num_variants = match enum_ty.kind() {
+                                    ty::TyKind::Adt(adt_def, _) if adt_def.is_enum() => {
+                                        adt_def.variants().len()
+                                    }
+                                    _ => {
+                                        return Err(SpannedEncodingError::unsupported(
+                                            format!(
+                                                "the discriminant of type {} is not supported",
+                                                enum_ty
+                                            ),
+                                            span,
+                                        ));
+                                    }
+                                };
+                                let encoded_rhs = if num_variants == 1 {
+                                    0u32.into()
+                                } else {
+                                    let deref_field = self
+                                        .encoder
+                                        .encode_dereference_field(enum_ty)
+                                        .with_span(span)?;
+                                    encoded_args[0]
+                                        .clone()
+                                        .field(deref_field)
+                                        .field(self.encoder.encode_discriminant_field())
+                                };
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&encoded_lhs, encoded_rhs);
+                                state
+                            }
+
+                            "std::cmp::PartialEq::eq"
+                            | "core::cmp::PartialEq::eq"
+                            | "std::cmp::PartialEq::ne"
+                            | "core::cmp::PartialEq::ne"
+                                if self.is_discriminant_ty(
+                                    self.mir_encoder.get_operand_ty(&args[0]).peel_refs(),
+                                ) =>
+                            {
+                                assert_eq!(args.len(), 2);
+                                let discr_ty = self.mir_encoder.get_operand_ty(&args[0]).peel_refs();
+                                let deref_field = self
+                                    .encoder
+                                    .encode_dereference_field(discr_ty)
+                                    .with_span(span)?;
+                                let lhs = encoded_args[0].clone().field(deref_field.clone());
+                                let rhs = encoded_args[1].clone().field(deref_field);
+                                let encoded_rhs = if full_func_proc_name.ends_with("::eq") {
+                                    vir::Expr::eq_cmp(lhs, rhs)
+                                } else {
+                                    vir::Expr::ne_cmp(lhs, rhs)
+                                };
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&encoded_lhs, encoded_rhs);
+                                state
+                            }
+
                             "core::slice::<impl [T]>::len" => {
                                 assert_eq!(args.len(), 1);
                                 let slice_ty = self.mir_encoder.get_operand_ty(&args[0]);
@@ -488,7 +563,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state
                             }
 
-                            "std::ops::Index::index" | "core::ops::Index::index" => {
+                            "std::ops::Index::index" | "core::ops::Index::index"
+                                if is_natively_indexed(self.mir_encoder.get_operand_ty(&args[0])) =>
+                            {
                                 assert_eq!(args.len(), 2);
                                 trace!("slice::index(args={:?}, encoded_args={:?}, ty={:?}, encoded_lhs={:?})", args, encoded_args, ty, encoded_lhs);
 
@@ -566,6 +643,35 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state
                             }
 
+                            // `Pin<&T>` and `Pin<&mut T>` dereference to the pinned reference.
+                            "std::ops::Deref::deref" | "core::ops::Deref::deref"
+                                if crate::utils::pinned_reference_ty(
+                                    tcx,
+                                    self.mir_encoder.get_operand_ty(&args[0]).peel_refs(),
+                                )
+                                .is_some() =>
+                            {
+                                assert_eq!(args.len(), 1);
+                                let pin_ty = self.mir_encoder.get_operand_ty(&args[0]).peel_refs();
+                                let pointer_ty =
+                                    crate::utils::pinned_reference_ty(tcx, pin_ty).unwrap();
+                                let deref_field = self
+                                    .encoder
+                                    .encode_dereference_field(pin_ty)
+                                    .with_span(span)?;
+                                let pointer_field = self
+                                    .encoder
+                                    .encode_struct_field("pointer", pointer_ty)
+                                    .with_span(span)?;
+                                let encoded_rhs = encoded_args[0]
+                                    .clone()
+                                    .field(deref_field)
+                                    .field(pointer_field);
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&encoded_lhs, encoded_rhs);
+                                state
+                            }
+
                             // Prusti-specific syntax
                             // TODO: check we are in a spec function
                             "prusti_contracts::exists"
@@ -573,7 +679,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             | "prusti_contracts::specification_entailment"
                             | "prusti_contracts::call_description"
                             | "prusti_contracts::snap"
-                            | "prusti_contracts::snapshot_equality" => {
+                            | "prusti_contracts::snapshot_equality"
+                            | "prusti_contracts::unfolding" => {
                                 let expr = self.encoder.encode_prusti_operation(
                                     full_func_proc_name,
                                     span,
@@ -606,13 +713,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                         )
                                         .with_span(span)?
                                 } else {
-                                    return Err(SpannedEncodingError::incorrect(
+                                    let mut error = SpannedEncodingError::incorrect(
                                         format!(
                                             "use of impure function {:?} in pure code is not allowed",
                                             func_proc_name
                                         ),
                                         span,
-                                    ));
+                                    );
+                                    explain_impure_call(self.encoder, called_def_id, &mut error);
+                                    return Err(error);
                                 };
                                 trace!("Encoding pure function call '{}'", function_name);
 
@@ -1145,7 +1254,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
 
                     &mir::Rvalue::Ref(_, mir::BorrowKind::Unique, place)
                     | &mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, place)
-                    | &mir::Rvalue::Ref(_, mir::BorrowKind::Shared, place) => {
+                    | &mir::Rvalue::Ref(_, mir::BorrowKind::Shared, place)
+                    | &mir::Rvalue::Ref(_, mir::BorrowKind::Shallow, place) => {
                         let (encoded_place, _, _) = self.encode_place(place).with_span(span)?;
                         // TODO: Instead of generating an `AddrOf(..)` expression, here we could
                         // generate a shapshot representing a reference. If we do so, we should
@@ -1,8 +1,10 @@
 mod interpreter;
 mod pure_functions;
+mod purity;
 mod specifications;
 
 pub(crate) use self::{
     pure_functions::{PureEncodingContext, PureFunctionEncoderInterface, PureFunctionEncoderState},
+    purity::{is_transparent_deref, suggest_annotations},
     specifications::SpecificationEncoderInterface,
 };
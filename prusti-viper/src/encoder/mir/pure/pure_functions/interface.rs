@@ -315,8 +315,13 @@ impl<'v, 'tcx: 'v> PureFunctionEncoderInterface<'v, 'tcx>
 
             let maybe_identifier: SpannedEncodingResult<vir_poly::FunctionIdentifier> = (|| {
                 let proc_kind = self.get_proc_kind(proc_def_id, Some(substs));
-                let is_bodyless = self.is_trusted(proc_def_id, Some(substs))
-                    || !self.env().query.has_body(proc_def_id);
+                // The body of a predicate is its specification item, which is
+                // exported together with the specifications of its crate, so
+                // predicates of other crates are not bodyless even though their
+                // MIR is not available.
+                let has_body = matches!(proc_kind, ProcedureSpecificationKind::Predicate(_))
+                    || self.env().query.has_body(proc_def_id);
+                let is_bodyless = self.is_trusted(proc_def_id, Some(substs)) || !has_body;
                 let mut function = if is_bodyless {
                     pure_function_encoder.encode_bodyless_function()?
                 } else {
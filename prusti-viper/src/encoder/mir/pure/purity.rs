@@ -0,0 +1,411 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Explains why an impure function cannot be called from pure code: either
+//! the function could be marked as `#[pure]`, or some statement in its body
+//! prevents that. The same analysis suggests annotations for the verified
+//! functions before their verification starts.
+
+use crate::{
+    encoder::{
+        errors::SpannedEncodingError, mir::specifications::SpecificationsInterface, Encoder,
+    },
+    utils::is_reference,
+};
+use prusti_interface::{
+    data::ProcedureDefId, specs::typed::ProcedureSpecificationKind, PrustiError,
+};
+use prusti_rustc_interface::{
+    hir::def::DefKind,
+    hir::def_id::DefId,
+    middle::{
+        mir::{self, visit::Visitor},
+        ty,
+    },
+    span::{BytePos, Span},
+};
+
+/// The functions that the pure encoder handles natively, so that calling them
+/// does not prevent a function from being pure.
+const PURE_BUILTINS: &[&str] = &[
+    "std::cmp::PartialEq::eq",
+    "core::cmp::PartialEq::eq",
+    "std::cmp::PartialEq::ne",
+    "core::cmp::PartialEq::ne",
+    "core::slice::<impl [T]>::len",
+    "std::ops::Index::index",
+    "core::ops::Index::index",
+    "std::mem::discriminant",
+    "core::mem::discriminant",
+];
+
+/// A statement that prevents a function from being pure.
+struct Impurity {
+    reason: String,
+    span: Span,
+}
+
+/// Extends the error about calling the impure function `called_def_id` from
+/// pure code. If the body of the function has no side effects, the error
+/// suggests marking the function as `#[pure]`; otherwise it points at the
+/// first statement that prevents the function from being pure.
+pub(super) fn explain_impure_call(
+    encoder: &Encoder,
+    called_def_id: DefId,
+    error: &mut SpannedEncodingError,
+) {
+    let env = encoder.env();
+    let Some(local_def_id) = called_def_id.as_local() else {
+        return;
+    };
+    if !matches!(env.tcx().def_kind(called_def_id), DefKind::Fn | DefKind::AssocFn)
+        || !env.query.has_body(called_def_id)
+    {
+        return;
+    }
+    let function_name = env.name.get_item_name(called_def_id);
+    let body = env.body.get_impure_fn_body_identity(local_def_id);
+    match find_impurity(encoder, called_def_id, &body) {
+        Some(impurity) => error.add_note(
+            format!(
+                "`{}` cannot be marked as `#[pure]` because it {}",
+                function_name, impurity.reason
+            ),
+            Some(impurity.span.into()),
+        ),
+        None => {
+            let (span, replacement) = attribute_insertion(encoder, called_def_id, "#[pure]");
+            error.set_suggestion(
+                format!(
+                    "`{}` has no side effects, so it can be marked as `#[pure]`",
+                    function_name
+                ),
+                span,
+                replacement,
+            );
+        }
+    }
+}
+
+/// Whether `def_id` is an implementation of `Deref::deref` whose body only
+/// borrows a field of `self`, as the implementations of smart pointers and
+/// wrapper types usually do. Such an implementation has no side effects, so it
+/// is treated as `#[pure]` even if it is not marked as such. This way, the
+/// deref coercions through the type can be used in specifications, and the
+/// value behind the returned reference is known at the coercion sites.
+pub(crate) fn is_transparent_deref(encoder: &Encoder, def_id: DefId) -> bool {
+    let env = encoder.env();
+    let tcx = env.tcx();
+    let Some(local_def_id) = def_id.as_local() else {
+        return false;
+    };
+    let implements_deref = tcx
+        .impl_of_method(def_id)
+        .and_then(|impl_def_id| tcx.trait_id_of_impl(impl_def_id))
+        .map_or(false, |trait_def_id| {
+            tcx.lang_items().deref_trait() == Some(trait_def_id)
+        });
+    if !implements_deref || !env.query.has_body(def_id) || encoder.is_trusted(def_id, None) {
+        return false;
+    }
+    let body = env.body.get_impure_fn_body_identity(local_def_id);
+    borrows_field_of_self(&body)
+}
+
+/// Whether the body consists of a single statement `_0 = &(*_1).f.g...`.
+fn borrows_field_of_self(body: &mir::Body) -> bool {
+    if body.basic_blocks.len() != 1 {
+        return false;
+    }
+    let data = &body.basic_blocks[mir::START_BLOCK];
+    if !matches!(data.terminator().kind, mir::TerminatorKind::Return) {
+        return false;
+    }
+    let mut statements = data.statements.iter().filter(|statement| {
+        !matches!(
+            statement.kind,
+            mir::StatementKind::StorageLive(_)
+                | mir::StatementKind::StorageDead(_)
+                | mir::StatementKind::Nop
+        )
+    });
+    let (Some(statement), None) = (statements.next(), statements.next()) else {
+        return false;
+    };
+    let mir::StatementKind::Assign(box (target, ref rvalue)) = statement.kind else {
+        return false;
+    };
+    let mir::Rvalue::Ref(_, mir::BorrowKind::Shared, borrowed) = *rvalue else {
+        return false;
+    };
+    if target.as_local() != Some(mir::RETURN_PLACE) || borrowed.local.as_u32() != 1 {
+        return false;
+    }
+    match borrowed.projection.split_first() {
+        Some((mir::ProjectionElem::Deref, fields)) => {
+            !fields.is_empty()
+                && fields.iter().all(|elem| {
+                    matches!(elem, mir::ProjectionElem::Field(_, field_ty) if !is_reference(*field_ty))
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Emits a warning for each of the procedures that could be marked as
+/// `#[pure]` because its body has no side effects, and for each procedure
+/// whose body uses features that Prusti does not support, so that it will
+/// need to be marked as `#[trusted]`. The warnings carry the attribute as a
+/// structured suggestion. Returns the number of emitted suggestions.
+pub(crate) fn suggest_annotations(encoder: &Encoder, procedures: &[ProcedureDefId]) -> usize {
+    let env = encoder.env();
+    let mut suggestions = 0;
+    for &def_id in procedures {
+        let Some(local_def_id) = def_id.as_local() else {
+            continue;
+        };
+        if !matches!(env.tcx().def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+            || encoder.is_trusted(def_id, None)
+            || encoder.get_proc_kind(def_id, None) != ProcedureSpecificationKind::Impure
+            || is_transparent_deref(encoder, def_id)
+        {
+            continue;
+        }
+        let function_name = env.name.get_item_name(def_id);
+        let def_span = env.query.get_def_span(def_id);
+        let body = env.body.get_impure_fn_body_identity(local_def_id);
+        if let Some(feature) = find_unsupported_feature(encoder, &body) {
+            let (span, replacement) = attribute_insertion(encoder, def_id, "#[trusted]");
+            PrustiError::warning(
+                format!(
+                    "`{}` {}, which is not supported by Prusti",
+                    function_name, feature.reason
+                ),
+                def_span.into(),
+            )
+            .add_note("the unsupported feature is used here", Some(feature.span))
+            .set_suggestion(
+                format!(
+                    "mark `{}` as `#[trusted]` and specify its behavior with a contract",
+                    function_name
+                ),
+                span,
+                replacement,
+            )
+            .emit(&env.diagnostic);
+            suggestions += 1;
+        } else if !body.local_decls[mir::RETURN_PLACE].ty.is_unit()
+            && find_impurity(encoder, def_id, &body).is_none()
+        {
+            let (span, replacement) = attribute_insertion(encoder, def_id, "#[pure]");
+            PrustiError::warning(
+                format!(
+                    "`{}` has no side effects, but is not marked as `#[pure]`",
+                    function_name
+                ),
+                def_span.into(),
+            )
+            .set_suggestion(
+                format!(
+                    "mark `{}` as `#[pure]` to use it in specifications",
+                    function_name
+                ),
+                span,
+                replacement,
+            )
+            .emit(&env.diagnostic);
+            suggestions += 1;
+        }
+    }
+    suggestions
+}
+
+/// Finds the first statement of the body that prevents the function from
+/// being pure.
+fn find_impurity<'tcx>(
+    encoder: &Encoder<'_, 'tcx>,
+    def_id: DefId,
+    body: &mir::Body<'tcx>,
+) -> Option<Impurity> {
+    let env = encoder.env();
+    for arg in body.args_iter() {
+        let decl = &body.local_decls[arg];
+        if let ty::TyKind::Ref(_, _, mir::Mutability::Mut) = decl.ty.kind() {
+            return Some(Impurity {
+                reason: "takes a mutable reference".to_string(),
+                span: decl.source_info.span,
+            });
+        }
+    }
+    let dominators = body.basic_blocks.dominators();
+    for (bb, data) in mir::traversal::reverse_postorder(body) {
+        for statement in &data.statements {
+            if let mir::StatementKind::Assign(box (place, _)) = statement.kind {
+                if place.is_indirect() {
+                    return Some(Impurity {
+                        reason: "writes to memory through a reference".to_string(),
+                        span: statement.source_info.span,
+                    });
+                }
+            }
+        }
+        let terminator = data.terminator();
+        match &terminator.kind {
+            mir::TerminatorKind::Call {
+                func,
+                target: Some(_),
+                ..
+            } => {
+                if let ty::TyKind::FnDef(called_def_id, _) = func.ty(body, env.tcx()).kind() {
+                    let called_name = env.name.get_absolute_item_name(*called_def_id);
+                    if *called_def_id != def_id
+                        && !encoder.is_pure(*called_def_id, None)
+                        && !PURE_BUILTINS.contains(&called_name.as_str())
+                        && !called_name.starts_with("prusti_contracts::")
+                    {
+                        return Some(Impurity {
+                            reason: format!(
+                                "calls the non-pure function `{}`",
+                                env.name.get_item_name(*called_def_id)
+                            ),
+                            span: terminator.source_info.span,
+                        });
+                    }
+                }
+            }
+            mir::TerminatorKind::InlineAsm { .. } => {
+                return Some(Impurity {
+                    reason: "contains inline assembly".to_string(),
+                    span: terminator.source_info.span,
+                });
+            }
+            _ => {}
+        }
+        if terminator
+            .successors()
+            .any(|successor| dominators.is_dominated_by(bb, successor))
+        {
+            return Some(Impurity {
+                reason: "contains a loop".to_string(),
+                span: terminator.source_info.span,
+            });
+        }
+    }
+    None
+}
+
+/// Computes where to insert the attribute before the function, on its own line
+/// with the indentation of the function.
+fn attribute_insertion(encoder: &Encoder, def_id: DefId, attribute: &str) -> (Span, String) {
+    let def_span = encoder.env().query.get_def_span(def_id);
+    let location = encoder
+        .env()
+        .tcx()
+        .sess
+        .source_map()
+        .lookup_char_pos(def_span.lo());
+    let indentation = location
+        .file
+        .get_line(location.line - 1)
+        .map(|line| line.chars().take(location.col.0).collect::<String>())
+        .filter(|prefix| prefix.chars().all(char::is_whitespace));
+    match indentation {
+        Some(indentation) => {
+            let line_start = def_span.lo() - BytePos(indentation.len() as u32);
+            (
+                def_span.with_lo(line_start).shrink_to_lo(),
+                format!("{}{}\n", indentation, attribute),
+            )
+        }
+        None => (def_span.shrink_to_lo(), format!("{} ", attribute)),
+    }
+}
+
+/// Finds the first use of a feature that Prusti does not support: inline
+/// assembly, raw pointers, and calls through function pointers or trait
+/// objects.
+fn find_unsupported_feature<'tcx>(
+    encoder: &Encoder<'_, 'tcx>,
+    body: &mir::Body<'tcx>,
+) -> Option<Impurity> {
+    let mut finder = UnsupportedFeatureFinder {
+        tcx: encoder.env().tcx(),
+        body,
+        feature: None,
+    };
+    for (bb, data) in mir::traversal::reverse_postorder(body) {
+        finder.visit_basic_block_data(bb, data);
+        if finder.feature.is_some() {
+            break;
+        }
+    }
+    finder.feature
+}
+
+struct UnsupportedFeatureFinder<'a, 'tcx> {
+    tcx: ty::TyCtxt<'tcx>,
+    body: &'a mir::Body<'tcx>,
+    feature: Option<Impurity>,
+}
+
+impl<'a, 'tcx> UnsupportedFeatureFinder<'a, 'tcx> {
+    fn found(&mut self, reason: &str, location: mir::Location) {
+        if self.feature.is_none() {
+            self.feature = Some(Impurity {
+                reason: reason.to_string(),
+                span: self.body.source_info(location).span,
+            });
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for UnsupportedFeatureFinder<'a, 'tcx> {
+    fn visit_place(
+        &mut self,
+        place: &mir::Place<'tcx>,
+        context: mir::visit::PlaceContext,
+        location: mir::Location,
+    ) {
+        self.super_place(place, context, location);
+        let dereferences_raw_pointer = place.iter_projections().any(|(base, elem)| {
+            elem == mir::ProjectionElem::Deref && base.ty(self.body, self.tcx).ty.is_unsafe_ptr()
+        });
+        if dereferences_raw_pointer {
+            self.found("dereferences a raw pointer", location);
+        }
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: mir::Location) {
+        self.super_rvalue(rvalue, location);
+        if let mir::Rvalue::AddressOf(..) = rvalue {
+            self.found("creates a raw pointer", location);
+        }
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: mir::Location) {
+        self.super_terminator(terminator, location);
+        match &terminator.kind {
+            mir::TerminatorKind::Call { func, .. } => match func.ty(self.body, self.tcx).kind() {
+                ty::TyKind::FnPtr(_) => self.found("calls a function pointer", location),
+                ty::TyKind::FnDef(called_def_id, substs) => {
+                    let calls_trait_object = self.tcx.trait_of_item(*called_def_id).is_some()
+                        && substs
+                            .types()
+                            .next()
+                            .map_or(false, |self_ty| self_ty.peel_refs().is_trait());
+                    if calls_trait_object {
+                        self.found("calls a method of a trait object", location);
+                    }
+                }
+                _ => {}
+            },
+            mir::TerminatorKind::InlineAsm { .. } => {
+                self.found("contains inline assembly", location)
+            }
+            _ => {}
+        }
+    }
+}
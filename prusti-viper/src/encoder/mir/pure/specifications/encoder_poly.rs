@@ -19,6 +19,7 @@ use crate::{
     error_incorrect,
 };
 use prusti_common::config;
+use prusti_interface::PrustiError;
 use prusti_rustc_interface::{
     errors::MultiSpan,
     hir::def_id::DefId,
@@ -129,7 +130,7 @@ pub(super) fn inline_spec_item<'tcx>(
 
 pub(super) fn encode_quantifier<'tcx>(
     encoder: &Encoder<'_, 'tcx>,
-    _span: Span,
+    span: Span,
     encoded_args: Vec<vir_crate::polymorphic::Expr>,
     is_exists: bool,
     parent_def_id: DefId,
@@ -261,6 +262,10 @@ pub(super) fn encode_quantifier<'tcx>(
         .map(|set| set.replace_multiple_places(&qvar_replacements))
         .collect::<Vec<_>>();
 
+    if encoded_trigger_sets.is_empty() {
+        warn_if_no_trigger_can_be_inferred(encoder, span, &fixed_qvars, &encoded_body);
+    }
+
     let final_body = if bounds.is_empty() {
         encoded_body
     } else if is_exists {
@@ -363,3 +368,131 @@ fn check_trigger_set(
     }
     Ok(())
 }
+
+/// The maximum number of candidate trigger terms listed in the warning about a
+/// quantifier without triggers.
+const MAX_LISTED_TRIGGER_CANDIDATES: usize = 5;
+
+/// Warns about a quantifier without user-provided triggers for which the
+/// verifier cannot infer triggers, because the function applications in its
+/// body that are valid triggers do not mention all bound variables. The SMT
+/// solver might never instantiate such a quantifier.
+fn warn_if_no_trigger_can_be_inferred(
+    encoder: &Encoder,
+    span: Span,
+    bound_vars: &[vir_crate::polymorphic::LocalVar],
+    body: &vir_crate::polymorphic::Expr,
+) {
+    use vir_crate::polymorphic::{default_walk_expr, Expr, ExprWalker};
+    struct CandidateCollector {
+        bound_vars: Vec<Expr>,
+        /// The variables bound by the quantifiers nested in the body.
+        inner_vars: Vec<Expr>,
+        /// The function applications that mention a bound variable, and
+        /// whether they are valid triggers.
+        candidates: Vec<(Expr, bool)>,
+    }
+    impl CandidateCollector {
+        fn walk_quantifier(&mut self, variables: &[vir_crate::polymorphic::LocalVar], body: &Expr) {
+            let depth = self.inner_vars.len();
+            self.inner_vars
+                .extend(variables.iter().cloned().map(Expr::local));
+            self.walk(body);
+            self.inner_vars.truncate(depth);
+        }
+    }
+    impl ExprWalker for CandidateCollector {
+        fn walk(&mut self, expr: &Expr) {
+            if matches!(expr, Expr::FuncApp(..) | Expr::DomainFuncApp(..))
+                && self.bound_vars.iter().any(|var| expr.find(var))
+            {
+                let is_valid = is_valid_trigger_term(expr)
+                    && !self.inner_vars.iter().any(|var| expr.find(var));
+                self.candidates.push((expr.clone(), is_valid));
+            }
+            default_walk_expr(self, expr);
+        }
+        fn walk_forall(&mut self, expr: &vir_crate::polymorphic::ForAll) {
+            self.walk_quantifier(&expr.variables, &expr.body);
+        }
+        fn walk_exists(&mut self, expr: &vir_crate::polymorphic::Exists) {
+            self.walk_quantifier(&expr.variables, &expr.body);
+        }
+    }
+    /// Viper does not allow arithmetic or other built-in operations in
+    /// triggers.
+    fn is_valid_trigger_term(expr: &Expr) -> bool {
+        match expr {
+            Expr::Local(..) | Expr::Const(..) => true,
+            Expr::FuncApp(vir_crate::polymorphic::FuncApp { arguments, .. })
+            | Expr::DomainFuncApp(vir_crate::polymorphic::DomainFuncApp { arguments, .. }) => {
+                arguments.iter().all(is_valid_trigger_term)
+            }
+            _ => false,
+        }
+    }
+
+    let mut collector = CandidateCollector {
+        bound_vars: bound_vars.iter().cloned().map(Expr::local).collect(),
+        inner_vars: vec![],
+        candidates: vec![],
+    };
+    collector.walk(body);
+    let is_covered = |var: &Expr| {
+        collector
+            .candidates
+            .iter()
+            .any(|(candidate, is_valid)| *is_valid && candidate.find(var))
+    };
+    // The bound variables that do not occur in the body need no triggers.
+    let needs_trigger = |var: &Expr| body.find(var) && !is_covered(var);
+    if !collector.bound_vars.iter().any(&needs_trigger) {
+        return;
+    }
+    let mut warning = PrustiError::warning(
+        "no triggers can be inferred for this quantifier, so the SMT solver might never \
+        instantiate it",
+        span.into(),
+    )
+    .set_help(
+        "provide triggers that mention all bound variables, \
+        e.g. `forall(|i: usize| ..., triggers=[(f(i),)])`",
+    );
+    let uncovered: Vec<_> = bound_vars
+        .iter()
+        .zip(&collector.bound_vars)
+        .filter(|(_, var)| needs_trigger(var))
+        .map(|(var, _)| var.name.clone())
+        .collect();
+    warning = warning.add_note(
+        format!(
+            "no function application that is a valid trigger mentions the bound variables {}",
+            uncovered.join(", ")
+        ),
+        None,
+    );
+    if collector.candidates.is_empty() {
+        warning = warning.add_note(
+            "the body contains no function application that mentions a bound variable",
+            None,
+        );
+    } else {
+        let listed: Vec<_> = collector
+            .candidates
+            .iter()
+            .take(MAX_LISTED_TRIGGER_CANDIDATES)
+            .map(|(candidate, is_valid)| {
+                if *is_valid {
+                    format!("`{}`", candidate)
+                } else {
+                    format!("`{}` (contains built-in operations)", candidate)
+                }
+            })
+            .collect();
+        warning = warning.add_note(
+            format!("the candidate terms considered were: {}", listed.join(", ")),
+            None,
+        );
+    }
+    encoder.emit_warning_once(span, warning);
+}
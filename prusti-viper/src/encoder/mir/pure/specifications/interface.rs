@@ -27,6 +27,7 @@ use crate::encoder::{
     mir_encoder::{MirEncoder, PlaceEncoder, PRECONDITION_LABEL},
     snapshot::interface::SnapshotEncoderInterface,
 };
+use prusti_interface::utils::read_prusti_attr;
 use prusti_rustc_interface::{
     hir::def_id::DefId,
     middle::{mir, ty::subst::SubstsRef},
@@ -136,9 +137,9 @@ impl<'v, 'tcx: 'v> SpecificationEncoderInterface<'tcx> for crate::encoder::Encod
             parent_def_id,
             substs,
         )?;
-        let position = self
-            .error_manager()
-            .register_span(parent_def_id, self.env().query.get_def_span(assertion));
+        let span = self.env().query.get_def_span(assertion);
+        register_failure_message(self, assertion, span);
+        let position = self.error_manager().register_span(parent_def_id, span);
         Ok(encoded_assertion.set_default_position(position.into()))
     }
 
@@ -255,6 +256,13 @@ impl<'v, 'tcx: 'v> SpecificationEncoderInterface<'tcx> for crate::encoder::Encod
                 vir_poly::Expr::snap_app(encoded_args[0].clone()),
                 vir_poly::Expr::snap_app(encoded_args[1].clone()),
             )),
+            // Predicates are encoded as functions, whose definitions are
+            // available where they are called, so unfolding one amounts to
+            // requiring that it holds.
+            "prusti_contracts::unfolding" => Ok(vir_poly::Expr::and(
+                encoded_args[0].clone(),
+                encoded_args[1].clone(),
+            )),
             _ => unimplemented!(),
         }
     }
@@ -291,6 +299,7 @@ impl<'v, 'tcx: 'v> SpecificationEncoderInterface<'tcx> for crate::encoder::Encod
         }
 
         let span = self.env().query.get_def_span(assertion);
+        register_failure_message(self, *assertion, span);
         encoded_assertion = self.patch_snapshots(encoded_assertion).with_span(span)?;
 
         Ok(encoded_assertion
@@ -371,3 +380,17 @@ impl<'v, 'tcx: 'v> SpecificationEncoderInterface<'tcx> for crate::encoder::Encod
         Ok(final_invariant)
     }
 }
+
+/// Registers the custom failure message of a specification (as in
+/// `#[requires(x > 0, "message")]`), so that it is reported with the errors of
+/// the specification.
+fn register_failure_message(
+    encoder: &crate::encoder::Encoder<'_, '_>,
+    assertion: DefId,
+    span: Span,
+) {
+    let attrs = encoder.env().query.get_attributes(assertion);
+    if let Some(message) = read_prusti_attr("failure_message", attrs) {
+        encoder.error_manager().register_failure_message(span, message);
+    }
+}
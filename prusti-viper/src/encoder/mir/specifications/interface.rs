@@ -1,4 +1,4 @@
-use crate::encoder::mir::specifications::specs::Specifications;
+use crate::encoder::mir::{pure::is_transparent_deref, specifications::specs::Specifications};
 use log::trace;
 use prusti_interface::{
     specs::{
@@ -144,6 +144,10 @@ impl<'v, 'tcx: 'v> SpecificationsInterface<'tcx> for super::super::super::Encode
             pure = true;
         }
 
+        if !pure && is_transparent_deref(self, def_id) {
+            pure = true;
+        }
+
         trace!("is_pure {:?} = {}", def_id, pure);
         pure
     }
@@ -39,6 +39,11 @@ pub(super) fn extract_const_parameters_from_type<'tcx>(
         | ty::TyKind::FnDef(_, substs) => {
             extract_const_parameters_from_substs(type_encoder, substs, const_parameters)?
         }
+        ty::TyKind::Generator(_, substs, _) => {
+            for upvar_ty in substs.as_generator().upvar_tys() {
+                extract_const_parameters_from_type(type_encoder, upvar_ty, const_parameters)?;
+            }
+        }
         ty::TyKind::Ref(_, ty, _) => {
             extract_const_parameters_from_type(type_encoder, *ty, const_parameters)?
         }
@@ -76,7 +81,6 @@ pub(super) fn extract_const_parameters_from_type<'tcx>(
         ty::TyKind::Bound(_, _)
         | ty::TyKind::Placeholder(_)
         | ty::TyKind::Infer(_)
-        | ty::TyKind::Generator(..)
         | ty::TyKind::GeneratorWitness(_) => {
             return Err(SpannedEncodingError::unsupported(
                 format!(
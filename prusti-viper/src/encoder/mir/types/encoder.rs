@@ -109,6 +109,14 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 )
             }
 
+            // The type argument of `PhantomData` does not affect its value, so
+            // all its instances share the same empty type.
+            ty::TyKind::Adt(adt_def, _) if adt_def.is_phantom_data() => vir::Type::struct_(
+                encode_struct_name(self.encoder, adt_def.did()),
+                Vec::new(),
+                Vec::new(),
+            ),
+
             ty::TyKind::Adt(adt_def, substs) if adt_def.is_struct() => {
                 let type_name: &str = &self
                     .encoder
@@ -221,6 +229,12 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 // lifetimes,
             ),
 
+            // Like a closure, a generator is encoded by the variables that it
+            // captures. The state of its suspension points is not modelled.
+            ty::TyKind::Generator(def_id, _substs, _movability) => {
+                vir::Type::closure(encode_closure_name(self.encoder, *def_id))
+            }
+
             ty::TyKind::FnDef(def_id, _substs) => vir::Type::function_def(
                 encode_function_def_name(self.encoder, *def_id),
                 // FIXME: We are currently ignoring type arguments and lifetimes.
@@ -476,6 +490,15 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 let name = encode_closure_name(self.encoder, *def_id);
                 vir::TypeDecl::closure(name, arguments)
             }
+            ty::TyKind::Generator(def_id, internal_substs, _) => {
+                let arguments = internal_substs
+                    .as_generator()
+                    .upvar_tys()
+                    .filter_map(|ty| self.encoder.encode_type_high(ty).ok())
+                    .collect();
+                let name = encode_closure_name(self.encoder, *def_id);
+                vir::TypeDecl::closure(name, arguments)
+            }
             ty::TyKind::Array(elem_ty, _size) => {
                 let lifetimes = self.encoder.get_lifetimes_from_type_high(self.ty)?;
                 let const_parameters = self.encoder.get_const_parameters_from_type_high(self.ty)?;
@@ -731,6 +754,15 @@ pub(super) fn encode_adt_def<'v, 'tcx>(
             const_parameters,
             vec![field],
         ))
+    } else if adt_def.is_phantom_data() {
+        debug!("ADT {:?} is PhantomData", adt_def);
+        assert!(variant_index.is_none());
+        Ok(vir::TypeDecl::struct_(
+            encode_struct_name(encoder, adt_def.did()),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        ))
     } else if adt_def.is_struct() {
         debug!("ADT {:?} is a struct", adt_def);
         assert!(variant_index.is_none());
@@ -46,6 +46,13 @@ pub(super) fn extract_lifetimes_from_type<'tcx>(
         | ty::TyKind::FnDef(_, substs) => {
             extract_lifetimes_from_substs(type_encoder, substs, lifetimes)?
         }
+        // The substitutions of a generator also contain the witness of its
+        // suspension points, which is not encoded.
+        ty::TyKind::Generator(_, substs, _) => {
+            for upvar_ty in substs.as_generator().upvar_tys() {
+                extract_lifetimes_from_type(type_encoder, upvar_ty, lifetimes)?;
+            }
+        }
         ty::TyKind::Array(ty, _) | ty::TyKind::Slice(ty) => {
             extract_lifetimes_from_type(type_encoder, *ty, lifetimes)?
         }
@@ -85,7 +92,6 @@ pub(super) fn extract_lifetimes_from_type<'tcx>(
         ty::TyKind::Bound(_, _)
         | ty::TyKind::Placeholder(_)
         | ty::TyKind::Infer(_)
-        | ty::TyKind::Generator(..)
         | ty::TyKind::GeneratorWitness(_) => {
             return Err(SpannedEncodingError::unsupported(
                 format!("unsupported type to extract lifetimes: {:?}", ty.kind()),
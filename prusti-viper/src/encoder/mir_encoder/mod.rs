@@ -172,8 +172,14 @@ pub trait PlaceEncoder<'v, 'tcx: 'v> {
                         (encoded_projection, field_ty, None)
                     }
 
+                    // The fields of a generator are the variables that it captures.
                     ty::TyKind::Generator(..) => {
-                        error_unsupported!("generator fields are not supported");
+                        let field_ty = *proj_field_ty;
+                        let field_name = format!("closure_{}", field.index());
+                        let encoded_field = self.encoder()
+                            .encode_raw_ref_field(field_name, field_ty)?;
+                        let encoded_projection = encoded_base.field(encoded_field);
+                        (encoded_projection, field_ty, None)
                     }
 
                     x => {
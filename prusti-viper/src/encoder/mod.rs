@@ -7,9 +7,11 @@
 pub use self::encoder::Encoder;
 
 mod builtin_encoder;
+pub mod dead_specs;
+mod encoding_trace;
 #[allow(clippy::module_inception)]
 mod encoder;
-mod errors;
+pub(crate) mod errors;
 mod foldunfold;
 mod initialisation;
 mod loop_encoder;
@@ -6,6 +6,8 @@
 
 use crate::encoder::mir::spans::interface::SpanInterface;
 use crate::encoder::builtin_encoder::{BuiltinMethodKind};
+use crate::encoder::dead_specs::{self, DeadSpecKind, DeadSpecQuery};
+use crate::encoder::encoding_trace::EncodingTrace;
 use crate::encoder::errors::{
     SpannedEncodingError, ErrorCtxt, EncodingError, WithSpan,
     EncodingResult, SpannedEncodingResult
@@ -67,7 +69,7 @@ use crate::encoder::errors::EncodingErrorKind;
 use std::convert::TryInto;
 use prusti_interface::specs::typed::{Pledge, SpecificationItem};
 use vir_crate::polymorphic::Float;
-use crate::utils::is_reference;
+use crate::utils::{is_natively_indexed, is_reference};
 use crate::encoder::mir::{
     sequences::MirSequencesEncoderInterface,
     contracts::{
@@ -97,10 +99,19 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     auxiliary_local_vars: FxHashMap<String, vir::Type>,
     mir_encoder: MirEncoder<'p, 'v, 'tcx>,
     check_panics: bool,
+    /// Whether the procedure is a `const fn` that is verified under the
+    /// semantics of const evaluation (the `check_const_fns` flag).
+    check_const_eval: bool,
     check_foldunfold_state: bool,
     polonius_info: Option<PoloniusInfo<'p, 'tcx>>,
     procedure_contract: Option<ProcedureContract<'tcx>>,
     label_after_location: FxHashMap<mir::Location, String>,
+    /// Labels of the states just before a closure with an `after_expiry`
+    /// assertion captures a place, by the location of the mutable borrow.
+    closure_capture_labels: FxHashMap<mir::Location, String>,
+    /// The `after_expiry` assertions of closures, by the location of the
+    /// mutable borrow on whose expiry they are assumed.
+    closure_expiries: FxHashMap<mir::Location, vir::Expr>,
     /// Store the CFG blocks that encode a MIR block each.
     cfg_blocks_map: FxHashMap<mir::BasicBlock, FxHashSet<CfgBlockIndex>>,
     /// Contains the boolean local variables that became `true` the first time the block is executed
@@ -133,6 +144,9 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     /// Type substitutions inside this procedure. Most likely identity for the
     /// given proc_def_id.
     substs: SubstsRef<'tcx>,
+    /// The trace of the encoding of the MIR statements, if the
+    /// `trace_encoding` flag is set.
+    encoding_trace: Option<EncodingTrace>,
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
@@ -151,6 +165,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .with_default_span(procedure.get_span())?;
 
         let specification_blocks = SpecificationBlocks::build(encoder.env().query, mir, procedure, false);
+        let check_const_eval = config::check_const_fns() && tcx.is_const_fn_raw(proc_def_id);
 
         let cfg_method = vir::CfgMethod::new(
             // method name
@@ -175,11 +190,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             loop_encoder: LoopEncoder::new(procedure, tcx),
             auxiliary_local_vars: FxHashMap::default(),
             mir_encoder,
-            check_panics: config::check_panics(),
+            // A panic during const evaluation is a compilation error.
+            check_panics: config::check_panics() || check_const_eval,
+            check_const_eval,
             check_foldunfold_state: config::check_foldunfold_state(),
             polonius_info: None,
             procedure_contract: None,
             label_after_location: FxHashMap::default(),
+            closure_capture_labels: FxHashMap::default(),
+            closure_expiries: FxHashMap::default(),
             cfg_block_has_been_executed: FxHashMap::default(),
             cfg_blocks_map: FxHashMap::default(),
             magic_wand_at_location: FxHashMap::default(),
@@ -193,6 +212,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             old_ghost_vars: FxHashMap::default(),
             cached_loop_invariant_block: FxHashMap::default(),
             substs,
+            encoding_trace: config::trace_encoding().then(EncodingTrace::default),
         })
     }
 
@@ -477,6 +497,13 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         // Encode postcondition
         self.encode_postconditions(return_cfg_block, postcondition_strengthening)?;
 
+        if let Some(trace) = &self.encoding_trace {
+            trace.dump(
+                &self.encoder.env().name.source_file_name(),
+                &self.cfg_method.name(),
+            );
+        }
+
         let local_vars: Vec<_> = self
             .locals
             .iter()
@@ -491,6 +518,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
 
         self.check_vir()?;
+        let dead_spec_queries = if config::detect_dead_specs() {
+            self.encode_dead_spec_queries()?
+        } else {
+            Vec::new()
+        };
         let method_name = self.cfg_method.name();
         let source_filename = self.encoder.env().name.source_file_name();
 
@@ -556,6 +588,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             );
         }
 
+        if config::detect_dead_specs() {
+            self.encoder.register_dead_spec_queries(self.proc_def_id, dead_spec_queries);
+        }
+
         Ok(method_with_fold_unfold)
     }
 
@@ -816,7 +852,19 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let boolean_exit_blocks_before_inv: Vec<_> = loop_body[0..after_inv_block_pos]
             .iter().copied()
             .filter(|bb| loop_exit_blocks_set.contains(bb))
-            .filter(|&bb| self.procedure.successors(bb).len() == 2)
+            .filter(|&bb| {
+                // A `match` that lists all the variants of an enum, such as
+                // `match it.next() { Some(x) => .., None => break x }`, has an
+                // additional target for the other discriminants, which is unreachable.
+                self.procedure
+                    .successors(bb)
+                    .iter()
+                    .filter(|&&succ| {
+                        !matches!(self.mir[succ].terminator().kind, TerminatorKind::Unreachable)
+                    })
+                    .count()
+                    == 2
+            })
             .collect();
         // HEURISTIC: pick the last boolean exit block before the invariant.
         // An infinite loop will have no exit blocks, so we have to use an Option here
@@ -1402,7 +1450,59 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 (stmts, Some(MirSuccessor::Kill))
             }
         };
-        Ok((self.set_stmts_default_pos(stmts, span), successor))
+        let stmts = self.set_stmts_default_pos(stmts, span);
+        if self.encoding_trace.is_some() {
+            let mir = if index < bb_data.statements.len() {
+                format!("{:?}", &bb_data.statements[index])
+            } else {
+                format!("{:?}", bb_data.terminator().kind)
+            };
+            let encoder_name = self.statement_encoder_name(location);
+            if let Some(trace) = &mut self.encoding_trace {
+                trace.record(location, mir, span, encoder_name, &stmts);
+            }
+        }
+        Ok((stmts, successor))
+    }
+
+    /// The method of the procedure encoder that encodes the statement or the
+    /// terminator at the location, which is recorded in the encoding trace.
+    fn statement_encoder_name(&self, location: mir::Location) -> &'static str {
+        let bb_data = &self.mir[location.block];
+        if let Some(stmt) = bb_data.statements.get(location.statement_index) {
+            return match stmt.kind {
+                mir::StatementKind::Assign(box (_, ref rhs)) => match rhs {
+                    mir::Rvalue::Use(..) => "encode_assign_operand",
+                    mir::Rvalue::Aggregate(..) => "encode_assign_aggregate",
+                    mir::Rvalue::BinaryOp(..) => "encode_assign_binary_op",
+                    mir::Rvalue::CheckedBinaryOp(..) => "encode_assign_checked_binary_op",
+                    mir::Rvalue::UnaryOp(..) => "encode_assign_unary_op",
+                    mir::Rvalue::NullaryOp(..) => "encode_assign_nullary_op",
+                    mir::Rvalue::Discriminant(..) => "encode_assign_discriminant",
+                    mir::Rvalue::Ref(..) => "encode_assign_ref",
+                    mir::Rvalue::Cast(..) => "encode_cast",
+                    mir::Rvalue::Len(..) => "encode_assign_sequence_len",
+                    mir::Rvalue::Repeat(..) => "encode_assign_array_repeat_initializer",
+                    _ => "encode_assign",
+                },
+                _ => "encode_statement",
+            };
+        }
+        match &bb_data.terminator().kind {
+            TerminatorKind::Return => "encode_package_end_of_method",
+            TerminatorKind::Call { func, .. } => {
+                match func.ty(self.mir, self.encoder.env().tcx()).kind() {
+                    ty::TyKind::FnDef(called_def_id, call_substs)
+                        if self.encoder.is_pure(*called_def_id, Some(call_substs)) =>
+                    {
+                        "encode_pure_function_call"
+                    }
+                    ty::TyKind::FnDef(..) => "encode_impure_function_call",
+                    _ => "encode_terminator",
+                }
+            }
+            _ => "encode_terminator",
+        }
     }
 
     /// Note: it's better to call `encode_statement_at` instead of this method.
@@ -1427,6 +1527,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             | mir::StatementKind::Coverage(..)
             | mir::StatementKind::Nop => vec![],
 
+            mir::StatementKind::Assign(box (lhs, _))
+                if self.is_formatting_type(lhs.ty(self.mir, self.encoder.env().tcx()).ty) =>
+            {
+                // The values built by the expansion of the formatting macros are
+                // only passed to formatting calls, which do not use them.
+                vec![]
+            }
+
             mir::StatementKind::Assign(box (lhs, ref rhs)) => {
                 // Array access on the LHS should always be mutable (idx is always calculated
                 // before, and just a separate local variable here)
@@ -1639,11 +1747,6 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     "unsuported creation of unique borrows (implicitly created in closure bindings)",
                 )
             }
-            mir::BorrowKind::Shallow => {
-                EncodingError::unsupported(
-                    "unsupported creation of shallow borrows (implicitly created when lowering matches)",
-                )
-            }
             _ => unreachable!(),
         }
     }
@@ -1721,7 +1824,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(match loan_places.source {
             mir::Rvalue::Ref(_, mir_borrow_kind, rhs_place) => {
                 let is_mut = match mir_borrow_kind {
-                    mir::BorrowKind::Shared => false,
+                    mir::BorrowKind::Shared | mir::BorrowKind::Shallow => false,
                     mir::BorrowKind::Mut { .. } => true,
                     _ => return Err(Self::unsupported_borrow_kind(mir_borrow_kind).with_span(span)),
                 };
@@ -1834,6 +1937,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             Ok(match stmt.kind {
                 mir::StatementKind::Assign(box (_, ref rhs)) => match rhs {
                     &mir::Rvalue::Ref(_, mir::BorrowKind::Shared, _) |
+                    &mir::Rvalue::Ref(_, mir::BorrowKind::Shallow, _) |
                     &mir::Rvalue::Use(mir::Operand::Copy(_)) => false,
                     &mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, _) |
                     &mir::Rvalue::Use(mir::Operand::Move(_)) => true,
@@ -2036,6 +2140,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             ));
         }
 
+        // The place captured by a closure with an `after_expiry` assertion
+        // satisfies the assertion once the closure is gone.
+        if !is_in_package_stmt {
+            if let Some(expiry) = self.closure_expiries.get(&loan_location) {
+                stmts.push(vir::Stmt::Inhale(vir::Inhale { expr: expiry.clone() }));
+            }
+        }
+
         let conflicting_loans = self.polonius_info().get_conflicting_loans(node.loan);
         let deaf_location = if let Some(end_location) = end_location {
             end_location
@@ -2377,11 +2489,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     kill_default_target = true;
                 };
 
-                if kill_default_target {
+                let default_target = if kill_default_target {
                     // Use the last conditional target as default. We could also assume or assert
                     // that the switch is exhaustive and never hits the default.
                     let last_target = cfg_targets.pop().unwrap();
-                    (stmts, MirSuccessor::GotoSwitch(cfg_targets, last_target.1))
+                    last_target.1
                 } else {
                     // Reorder the targets such that Silicon explores branches in the order that we want
                     if guard_is_bool && cfg_targets.len() == 1 {
@@ -2400,8 +2512,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             cfg_targets.push((target_guard, target));
                         }
                     }
-                    (stmts, MirSuccessor::GotoSwitch(cfg_targets, default_target))
-                }
+                    default_target
+                };
+                let cfg_targets = self.register_branch_guards(cfg_targets, default_target);
+                (stmts, MirSuccessor::GotoSwitch(cfg_targets, default_target))
             }
 
             TerminatorKind::Unreachable => {
@@ -2610,6 +2724,31 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             }
                         }
 
+                        "std::mem::swap" | "core::mem::swap"
+                            if self.is_exact_mem_call(args) =>
+                        {
+                            debug!("Encoding call of mem::swap");
+                            stmts.extend(self.encode_mem_swap_call(args, location)?);
+                        }
+
+                        "std::mem::replace" | "core::mem::replace"
+                            if self.is_exact_mem_call(args) =>
+                        {
+                            debug!("Encoding call of mem::replace");
+                            stmts.extend(
+                                self.encode_mem_replace_call(destination, args, location, span)?
+                            );
+                        }
+
+                        "std::mem::take" | "core::mem::take"
+                            if self.is_exact_mem_call(args) =>
+                        {
+                            debug!("Encoding call of mem::take");
+                            stmts.extend(
+                                self.encode_mem_replace_call(destination, args, location, span)?
+                            );
+                        }
+
                         "core::slice::<impl [T]>::len" => {
                             debug!("Encoding call of slice::len");
                             stmts.extend(
@@ -2632,7 +2771,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
                         // TODO: use extern_spec
                         "core::ops::IndexMut::index_mut" |
-                        "std::ops::IndexMut::index_mut" => {
+                        "std::ops::IndexMut::index_mut"
+                            if is_natively_indexed(self.mir_encoder.get_operand_ty(&args[0])) =>
+                        {
                             return Err(SpannedEncodingError::unsupported(
                                 "mutably slicing is not fully supported yet",
                                 term.source_info.span,
@@ -2640,7 +2781,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         }
 
                         "core::ops::Index::index" |
-                        "std::ops::Index::index" => {
+                        "std::ops::Index::index"
+                            if is_natively_indexed(self.mir_encoder.get_operand_ty(&args[0])) =>
+                        {
                             debug!("Encoding call of array/slice index call");
                             stmts.extend(
                                 self.encode_sequence_index_call(
@@ -2652,6 +2795,13 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             );
                         }
 
+                        _ if self.is_formatting_call(destination, args) => {
+                            debug!("Encoding formatting call {}", full_func_proc_name);
+                            stmts.extend(
+                                self.encode_formatting_call(destination, args, target, location, span)?
+                            );
+                        }
+
                         _ => {
                             // The called method might be a trait method.
                             // We try to resolve it to the concrete implementation
@@ -2779,9 +2929,31 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 (stmts, MirSuccessor::Goto(target))
             }
 
+            TerminatorKind::Yield {
+                resume, resume_arg, ..
+            } => {
+                // We assume that a suspended generator is resumed exactly once,
+                // so the body continues with an arbitrary resume argument.
+                let (encoded_resume_arg, pre_stmts, _, _) = self.encode_place(
+                    resume_arg,
+                    ArrayAccessKind::Mutable(None, location),
+                    location,
+                )?;
+                stmts.extend(pre_stmts);
+                stmts.extend(
+                    self.encode_havoc_and_initialization(&encoded_resume_arg).with_span(span)?
+                );
+                (stmts, MirSuccessor::Goto(resume))
+            }
+
+            TerminatorKind::GeneratorDrop => {
+                return Err(SpannedEncodingError::unsupported(
+                    "dropping a suspended generator is not supported",
+                    term.source_info.span,
+                ));
+            }
+
             TerminatorKind::Resume
-            | TerminatorKind::Yield { .. }
-            | TerminatorKind::GeneratorDrop
             | TerminatorKind::InlineAsm { .. } => unimplemented!("{:?}", term.kind),
         };
         Ok(result)
@@ -2838,6 +3010,192 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(stmts)
     }
 
+    /// Whether `ty` is one of the types built by the expansion of the formatting
+    /// macros, such as `fmt::Arguments`, or an array, a slice or a reference of
+    /// them.
+    fn is_formatting_type(&self, ty: ty::Ty<'tcx>) -> bool {
+        match ty.kind() {
+            ty::TyKind::Adt(adt_def, _) => {
+                let type_name = self.encoder.env().name.get_absolute_item_name(adt_def.did());
+                matches!(
+                    type_name.as_str(),
+                    "std::fmt::Arguments" | "core::fmt::Arguments" |
+                    "std::fmt::ArgumentV1" | "core::fmt::ArgumentV1" |
+                    "std::fmt::rt::v1::Argument" | "core::fmt::rt::v1::Argument"
+                )
+            }
+            ty::TyKind::Array(elem_ty, _)
+            | ty::TyKind::Slice(elem_ty)
+            | ty::TyKind::Ref(_, elem_ty, _) => self.is_formatting_type(*elem_ty),
+            _ => false,
+        }
+    }
+
+    /// Whether the call builds or consumes the values of the expansion of the
+    /// formatting macros, as `fmt::Arguments::new_v1`, `io::_print`,
+    /// `fmt::format` and `write_fmt` do.
+    fn is_formatting_call(&self, destination: mir::Place<'tcx>, args: &[mir::Operand<'tcx>]) -> bool {
+        self.is_formatting_type(destination.ty(self.mir, self.encoder.env().tcx()).ty)
+            || args.iter().any(|arg| self.is_formatting_type(self.mir_encoder.get_operand_ty(arg)))
+    }
+
+    /// Encodes a formatting call as a trusted call without effects on the
+    /// formatted values. The values of the formatting types are not encoded, so
+    /// the calls that build them do nothing. The other calls return an
+    /// unknown value and havoc the places behind their mutable references,
+    /// such as the writer of `write!`.
+    fn encode_formatting_call(
+        &mut self,
+        destination: mir::Place<'tcx>,
+        args: &[mir::Operand<'tcx>],
+        target: Option<BasicBlockIndex>,
+        location: mir::Location,
+        span: Span,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let mut stmts = vec![];
+        let destination_ty = destination.ty(self.mir, self.encoder.env().tcx()).ty;
+        if self.is_formatting_type(destination_ty) {
+            return Ok(stmts);
+        }
+        for arg in args {
+            let arg_ty = self.mir_encoder.get_operand_ty(arg);
+            let (Some(place), ty::TyKind::Ref(_, _, Mutability::Mut)) = (arg.place(), arg_ty.kind()) else {
+                continue;
+            };
+            let deref_place = self.encoder.env().tcx().mk_place_deref(place);
+            let (encoded_place, pre_stmts, _, _) = self.encode_place(
+                deref_place,
+                ArrayAccessKind::Mutable(None, location),
+                location,
+            )?;
+            stmts.extend(pre_stmts);
+            stmts.extend(self.encode_havoc_and_initialization(&encoded_place).with_span(span)?);
+        }
+        if target.is_none() {
+            stmts.push(vir::Stmt::Inhale( vir::Inhale {
+                expr: false.into(),
+            }));
+        } else if !destination_ty.is_unit() {
+            let (encoded_destination, pre_stmts, _, _) = self.encode_place(
+                destination,
+                ArrayAccessKind::Mutable(None, location),
+                location,
+            )?;
+            stmts.extend(pre_stmts);
+            stmts.extend(
+                self.encode_havoc_and_initialization(&encoded_destination).with_span(span)?
+            );
+        }
+        Ok(stmts)
+    }
+
+    /// Whether the call of `mem::swap`, `mem::replace` or `mem::take` with the
+    /// given arguments can be encoded as moves between the places behind the
+    /// references, which is not the case if these places contain references.
+    fn is_exact_mem_call(&self, args: &[mir::Operand<'tcx>]) -> bool {
+        let ty::TyKind::Ref(_, pointee_ty, _) = self.mir_encoder.get_operand_ty(&args[0]).kind() else {
+            return false;
+        };
+        let references_are_places = args.iter().all(|arg| {
+            !self.mir_encoder.get_operand_ty(arg).is_ref() || arg.place().is_some()
+        });
+        references_are_places
+            && !matches!(pointee_ty.kind(), ty::TyKind::Ref(..) | ty::TyKind::RawPtr(..))
+    }
+
+    /// The place behind the reference that is passed as `arg`.
+    fn encode_mem_call_target(
+        &mut self,
+        arg: &mir::Operand<'tcx>,
+        location: mir::Location,
+    ) -> SpannedEncodingResult<(mir::Place<'tcx>, vir::Expr, Vec<vir::Stmt>)> {
+        let place = self.encoder.env().tcx().mk_place_deref(arg.place().unwrap());
+        let (encoded_place, stmts, _, _) = self.encode_place(
+            place,
+            ArrayAccessKind::Mutable(None, location),
+            location,
+        )?;
+        Ok((place, encoded_place, stmts))
+    }
+
+    /// Encodes `mem::swap(a, b)` as moves of the values, and the permissions,
+    /// of `*a` and `*b` through a temporary variable. This keeps the values
+    /// precise instead of havocking them as an opaque call would.
+    fn encode_mem_swap_call(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        location: mir::Location,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        assert_eq!(args.len(), 2, "unexpected args to mem::swap(): {:?}", args);
+        let mut stmts = vec![];
+        let (lhs_place, encoded_lhs, lhs_stmts) = self.encode_mem_call_target(&args[0], location)?;
+        let (rhs_place, encoded_rhs, rhs_stmts) = self.encode_mem_call_target(&args[1], location)?;
+        stmts.extend(lhs_stmts);
+        stmts.extend(rhs_stmts);
+
+        let tmp_ty = self.mir_encoder.get_operand_ty(&args[0]).builtin_deref(true).unwrap().ty;
+        let tmp = vir::Expr::local(self.encode_prusti_local(self.locals.get_fresh(tmp_ty)));
+        stmts.extend(self.encode_assign_operand(&tmp, &mir::Operand::Move(lhs_place), location)?);
+        stmts.extend(
+            self.encode_assign_operand(&encoded_lhs, &mir::Operand::Move(rhs_place), location)?
+        );
+        stmts.push(vir::Stmt::Assign( vir::Assign {
+            target: encoded_rhs,
+            source: tmp,
+            kind: vir::AssignKind::Move,
+        }));
+
+        let label = self.cfg_method.get_fresh_label_name();
+        self.label_after_location.insert(location, label.clone());
+        stmts.push(vir::Stmt::label(label));
+        Ok(stmts)
+    }
+
+    /// Encodes `mem::replace(dest, src)` as a move of `*dest` into the result
+    /// followed by a move of `src` into `*dest`. `mem::take(dest)` is encoded in
+    /// the same way, except that the new value of `*dest` is unknown because
+    /// `Default::default` is not specified.
+    fn encode_mem_replace_call(
+        &mut self,
+        destination: mir::Place<'tcx>,
+        args: &[mir::Operand<'tcx>],
+        location: mir::Location,
+        span: Span,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        assert!(!args.is_empty() && args.len() <= 2, "unexpected args to mem::replace(): {:?}", args);
+        let mut stmts = vec![];
+        let (dest_place, encoded_dest, dest_stmts) = self.encode_mem_call_target(&args[0], location)?;
+        stmts.extend(dest_stmts);
+
+        let (encoded_result, result_stmts, _, _) = self.encode_place(
+            destination,
+            ArrayAccessKind::Mutable(None, location),
+            location,
+        )?;
+        stmts.extend(result_stmts);
+        stmts.extend(
+            self.encode_assign_operand(&encoded_result, &mir::Operand::Move(dest_place), location)?
+        );
+
+        if let Some(src) = args.get(1) {
+            stmts.extend(self.encode_assign_operand(&encoded_dest, src, location)?);
+        } else {
+            let default_ty = self.mir_encoder.get_operand_ty(&args[0]).builtin_deref(true).unwrap().ty;
+            let default = vir::Expr::local(self.encode_prusti_local(self.locals.get_fresh(default_ty)));
+            stmts.extend(self.encode_havoc_and_initialization(&default).with_span(span)?);
+            stmts.push(vir::Stmt::Assign( vir::Assign {
+                target: encoded_dest,
+                source: default,
+                kind: vir::AssignKind::Move,
+            }));
+        }
+
+        let label = self.cfg_method.get_fresh_label_name();
+        self.label_after_location.insert(location, label.clone());
+        stmts.push(vir::Stmt::label(label));
+        Ok(stmts)
+    }
+
     fn encode_sequence_index_call(
         &mut self,
         destination: mir::Place<'tcx>,
@@ -5861,6 +6219,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             right
         );
         let span = self.mir_encoder.get_span_of_location(location);
+        // The NaN results of the arithmetic operations may differ between const evaluation and
+        // runtime. The comparisons behave the same.
+        if self.check_const_eval
+            && matches!(ty.kind(), ty::TyKind::Float(_))
+            && matches!(
+                op,
+                mir::BinOp::Add | mir::BinOp::Sub | mir::BinOp::Mul | mir::BinOp::Div | mir::BinOp::Rem
+            )
+        {
+            return Err(SpannedEncodingError::unsupported(
+                "floating-point arithmetic is not deterministic during const evaluation",
+                span,
+            ));
+        }
         let encoded_left = self.mir_encoder.encode_operand_expr(left)
             .with_span(span)?;
         let encoded_right = self.mir_encoder.encode_operand_expr(right)
@@ -6164,7 +6536,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let span = self.mir_encoder.get_span_of_location(location);
         let loan = self.polonius_info().get_loan_at_location(location);
         let (vir_assign_kind, array_encode_kind) = match mir_borrow_kind {
-            mir::BorrowKind::Shared =>
+            // The shallow borrows that the lowering of match guards creates
+            // only prevent the guards from mutating the matched place, so
+            // they are encoded like shared borrows.
+            mir::BorrowKind::Shared | mir::BorrowKind::Shallow =>
                 (vir::AssignKind::SharedBorrow(loan.index().into()), ArrayAccessKind::Shared),
             mir::BorrowKind::Mut { .. } =>
                 (vir::AssignKind::MutableBorrow(loan.index().into()),
@@ -6172,6 +6547,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             _ => return Err(Self::unsupported_borrow_kind(mir_borrow_kind).with_span(span)),
         };
         let (encoded_value, mut stmts, _, _) = self.encode_place(place, array_encode_kind, location)?;
+        if matches!(mir_borrow_kind, mir::BorrowKind::Mut { .. })
+            && self.is_captured_by_closure_with_expiry(location)
+        {
+            // `old` expressions in the `after_expiry` assertion refer to this state
+            let label = self.cfg_method.get_fresh_label_name();
+            self.closure_capture_labels.insert(location, label.clone());
+            stmts.insert(0, vir::Stmt::label(label));
+        }
         // Initialize ref_var.ref_field
         let field = self.encoder.encode_value_field(ty).with_span(span)?;
         stmts.extend(
@@ -6196,6 +6579,115 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(stmts)
     }
 
+    /// The specification id of the `after_expiry` assertion of a closure.
+    fn get_closure_expiry_spec_id(&self, cl_def_id: ProcedureDefId) -> Option<String> {
+        utils::read_prusti_attr(
+            "closure_expiry_spec_id_ref",
+            self.encoder.env().query.get_attributes(cl_def_id),
+        )
+    }
+
+    /// Whether the mutable borrow at `location` is captured by a closure with
+    /// an `after_expiry` assertion that is created later in the same block.
+    fn is_captured_by_closure_with_expiry(&self, location: mir::Location) -> bool {
+        let statements = &self.mir.basic_blocks[location.block].statements;
+        let borrow = match &statements[location.statement_index].kind {
+            mir::StatementKind::Assign(box (lhs, _)) => *lhs,
+            _ => return false,
+        };
+        statements[location.statement_index + 1..].iter().any(|stmt| {
+            if let mir::StatementKind::Assign(box (
+                _,
+                mir::Rvalue::Aggregate(box mir::AggregateKind::Closure(cl_def_id, _), operands),
+            )) = &stmt.kind
+            {
+                operands.iter().any(|operand| operand.place() == Some(borrow))
+                    && self.get_closure_expiry_spec_id(cl_def_id.to_def_id()).is_some()
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Encodes the `after_expiry` assertion of the closure created at
+    /// `location`. The assertion is checked when the closure is created, and
+    /// recorded to be assumed when the borrow of the captured place expires.
+    fn encode_closure_expiry(
+        &mut self,
+        spec_id: &str,
+        operands: &[mir::Operand<'tcx>],
+        location: mir::Location,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let span = self.mir_encoder.get_span_of_location(location);
+        let statements = &self.mir.basic_blocks[location.block].statements;
+        let borrow_locations: Vec<mir::Location> = operands
+            .iter()
+            .filter_map(|operand| operand.place())
+            .filter_map(|place| {
+                (0..location.statement_index)
+                    .rev()
+                    .find(|&statement_index| matches!(
+                        &statements[statement_index].kind,
+                        mir::StatementKind::Assign(box (
+                            lhs,
+                            mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, _),
+                        )) if *lhs == place
+                    ))
+                    .map(|statement_index| mir::Location { block: location.block, statement_index })
+            })
+            .collect();
+        let capture_label = match &borrow_locations[..] {
+            [borrow_location] => self.closure_capture_labels.get(borrow_location).cloned(),
+            _ => None,
+        };
+        let (borrow_location, capture_label) = match capture_label {
+            Some(label) => (borrow_locations[0], label),
+            None => {
+                return Err(SpannedEncodingError::unsupported(
+                    "a closure with an `after_expiry` specification must capture exactly one \
+                    place by mutable reference",
+                    span,
+                ));
+            }
+        };
+
+        // Find the spec closure of the assertion, in the block before the closure
+        let spec_closure = self.mir.basic_blocks.iter_enumerated().find_map(|(bb, block)| {
+            block.statements.iter().find_map(|stmt| match &stmt.kind {
+                mir::StatementKind::Assign(box (
+                    _,
+                    mir::Rvalue::Aggregate(box mir::AggregateKind::Closure(cl_def_id, cl_substs), _),
+                )) if utils::read_prusti_attr(
+                    "spec_id",
+                    self.encoder.env().query.get_attributes(cl_def_id.to_def_id()),
+                ).as_deref() == Some(spec_id) => Some((bb, *cl_substs)),
+                _ => None,
+            })
+        });
+        let (spec_bb, spec_substs) = spec_closure.ok_or_else(|| {
+            SpannedEncodingError::internal(
+                format!("the `after_expiry` assertion {} was not found", spec_id),
+                span,
+            )
+        })?;
+        let expiry = self.encoder
+            .encode_invariant(self.mir, spec_bb, self.proc_def_id, spec_substs)?
+            .map_old_expr_label(|label| if label == PRECONDITION_LABEL {
+                capture_label.clone()
+            } else {
+                label
+            });
+
+        // The assertion must hold if the closure is never called
+        let position = self.register_error(span, ErrorCtxt::AssertClosureExpiryOnCreation);
+        let stmts = vec![vir::Stmt::Assert(vir::Assert {
+            expr: vir::Expr::labelled_old(&capture_label, expiry.clone()),
+            position,
+        })];
+        self.closure_expiries.insert(borrow_location, expiry);
+        Ok(stmts)
+    }
+
     /// Assignment where the RHS is a cast operation
     /// [encoded_lhs] = [operand] as [dst_ty]
     fn encode_cast(
@@ -6734,8 +7226,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             }
 
             mir::AggregateKind::Closure(def_id, substs) => {
-                // TODO: might need to assert history invariants?
                 assert!(!self.encoder.is_spec_closure(def_id.to_def_id()), "spec closure: {:?}", def_id);
+                if let Some(spec_id) = self.get_closure_expiry_spec_id(def_id.to_def_id()) {
+                    stmts.extend(self.encode_closure_expiry(&spec_id, operands, location)?);
+                }
                 let cl_substs = substs.as_closure();
                 for (field_index, field_ty) in cl_substs.upvar_tys().enumerate() {
                     let operand = &operands[field_index];
@@ -6771,17 +7265,215 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 }
             }
 
-            mir::AggregateKind::Generator(..) => {
-                return Err(SpannedEncodingError::unsupported(
-                    "construction of generators is not supported",
-                    span
-                ));
+            mir::AggregateKind::Generator(_, substs, _) => {
+                let gen_substs = substs.as_generator();
+                for (field_index, field_ty) in gen_substs.upvar_tys().enumerate() {
+                    let operand = &operands[field_index];
+                    let field_name = format!("closure_{}", field_index);
+                    let encoded_field = self.encoder
+                        .encode_raw_ref_field(field_name, field_ty)
+                        .with_span(span)?;
+                    stmts.extend(self.encode_assign_operand(
+                        &dst.clone().field(encoded_field),
+                        operand,
+                        location,
+                    )?);
+                }
             }
         }
 
         Ok(stmts)
     }
 
+    /// Encodes the queries that check whether the preconditions, the guards of
+    /// the implications in the postconditions and the loop invariants are dead.
+    /// See the `dead_specs` module.
+    fn encode_dead_spec_queries(&mut self) -> SpannedEncodingResult<Vec<DeadSpecQuery>> {
+        let mut queries = vec![];
+        let contract = self.procedure_contract();
+        let (type_spec, mandatory_type_spec, invs_spec, _) =
+            self.encode_precondition_expr(contract, self.substs, FxHashMap::default())?;
+        let permissions = vec![type_spec, mandatory_type_spec.into_iter().conjoin()]
+            .into_iter()
+            .conjoin();
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        let encoded_return: vir::Expr = self.encode_prusti_local(contract.returned_value).into();
+        let mut preconditions = vec![];
+        for (assertion, assertion_substs) in
+            contract.functional_precondition(self.encoder.env(), self.substs)
+        {
+            let encoded_assertion = self.encoder.encode_assertion(
+                &assertion,
+                None,
+                &encoded_args,
+                None,
+                false,
+                self.proc_def_id,
+                assertion_substs,
+            )?;
+            preconditions.push((encoded_assertion, self.encoder.env().query.get_def_span(assertion)));
+        }
+        let pre_stmts = vec![
+            vir::Stmt::Inhale(vir::Inhale { expr: permissions.clone() }),
+            vir::Stmt::Inhale(vir::Inhale { expr: invs_spec }),
+        ];
+
+        // A precondition is dead if it is implied by the other preconditions.
+        if preconditions.len() > 1 {
+            for (index, (precondition, span)) in preconditions.iter().enumerate() {
+                let other_preconditions = preconditions
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_index, _)| *other_index != index)
+                    .map(|(_, (other_precondition, _))| other_precondition.clone())
+                    .conjoin();
+                let mut stmts = pre_stmts.clone();
+                stmts.push(vir::Stmt::Inhale(vir::Inhale { expr: other_preconditions }));
+                queries.extend(self.encode_dead_spec_query(
+                    DeadSpecKind::ImpliedPrecondition,
+                    *span,
+                    stmts,
+                    precondition.clone(),
+                    &[],
+                    queries.len(),
+                ));
+            }
+        }
+
+        // The guard of an implication in a postcondition is dead if it cannot hold in any
+        // state after a call. The permissions of the arguments are exhaled and inhaled
+        // again to forget the values that the procedure might modify.
+        if contract.borrow_infos.is_empty() {
+            let mut post_stmts = pre_stmts;
+            post_stmts.push(vir::Stmt::Inhale(vir::Inhale {
+                expr: preconditions.into_iter().map(|(precondition, _)| precondition).conjoin(),
+            }));
+            post_stmts.push(vir::Stmt::label(PRECONDITION_LABEL));
+            post_stmts.push(vir::Stmt::Exhale(vir::Exhale {
+                expr: permissions.clone(),
+                position: self.mir_encoder.register_span(self.mir.span),
+            }));
+            post_stmts.push(vir::Stmt::Inhale(vir::Inhale { expr: permissions }));
+            post_stmts.push(vir::Stmt::Inhale(vir::Inhale {
+                expr: self.encode_local_variable_permission(contract.returned_value, None)?,
+            }));
+            for (assertion, assertion_substs) in
+                contract.functional_postcondition(self.encoder.env(), self.substs)
+            {
+                let encoded_assertion = self.encoder.encode_assertion(
+                    &assertion,
+                    Some(PRECONDITION_LABEL),
+                    &encoded_args,
+                    Some(&encoded_return),
+                    false,
+                    self.proc_def_id,
+                    assertion_substs,
+                )?;
+                let encoded_assertion = self.wrap_arguments_into_old(
+                    encoded_assertion,
+                    PRECONDITION_LABEL,
+                    contract,
+                    &encoded_args,
+                )?;
+                let span = self.encoder.env().query.get_def_span(assertion);
+                for guard in dead_specs::implication_guards(&encoded_assertion) {
+                    queries.extend(self.encode_dead_spec_query(
+                        DeadSpecKind::UnsatisfiableGuard,
+                        span,
+                        post_stmts.clone(),
+                        vir::Expr::not(guard),
+                        &[PRECONDITION_LABEL],
+                        queries.len(),
+                    ));
+                }
+            }
+        }
+
+        // A loop invariant is dead if it holds with only the permissions of the loop.
+        let mut loops: Vec<_> = self.cached_loop_invariant_block
+            .iter()
+            .map(|(&loop_head, &loop_inv_block)| (loop_head, loop_inv_block))
+            .collect();
+        loops.sort();
+        for (loop_head, loop_inv_block) in loops {
+            let (invariants, invariants_span) =
+                self.encode_loop_invariant_specs(loop_head, loop_inv_block)?;
+            let spans = invariants_span.primary_spans().to_vec();
+            if invariants.is_empty() || invariants.len() != spans.len() {
+                continue;
+            }
+            let (permissions, _, invs_spec) =
+                self.encode_loop_invariant_permissions(loop_head, loop_inv_block, true)
+                    .with_span(invariants_span)?;
+            let stmts = vec![
+                vir::Stmt::Inhale(vir::Inhale { expr: permissions.into_iter().conjoin() }),
+                vir::Stmt::Inhale(vir::Inhale { expr: invs_spec.into_iter().conjoin() }),
+            ];
+            for (invariant, span) in invariants.into_iter().zip(spans) {
+                queries.extend(self.encode_dead_spec_query(
+                    DeadSpecKind::TrivialLoopInvariant,
+                    span,
+                    stmts.clone(),
+                    invariant,
+                    &[],
+                    queries.len(),
+                ));
+            }
+        }
+
+        Ok(queries)
+    }
+
+    /// Builds a method that executes `stmts` and then asserts `assertion`. The
+    /// query is skipped if the assertion refers to a label other than `labels`
+    /// or if the fold-unfold statements of the method cannot be generated.
+    fn encode_dead_spec_query(
+        &self,
+        kind: DeadSpecKind,
+        span: Span,
+        mut stmts: Vec<vir::Stmt>,
+        assertion: vir::Expr,
+        labels: &[&str],
+        index: usize,
+    ) -> Option<DeadSpecQuery> {
+        if !dead_specs::uses_only_labels(&assertion, labels) {
+            return None;
+        }
+        let position = self.mir_encoder.register_span(span);
+        stmts.push(vir::Stmt::Assert(vir::Assert {
+            expr: assertion,
+            position,
+        }));
+        let mut method = vir::CfgMethod::new(
+            format!("{}$dead_spec${}", self.cfg_method.name(), index),
+            self.cfg_method.get_formal_returns().clone(),
+            self.cfg_method.local_vars.clone(),
+            vec![],
+        );
+        let block = method.add_block("start", stmts);
+        method.set_successor(block, Successor::Return);
+        let method = self.encoder.patch_snapshots_method(method).ok()?;
+        let method = foldunfold::add_fold_unfold(
+            self.encoder,
+            method,
+            &FxHashMap::default(),
+            &FxHashMap::default(),
+            position,
+        )
+        .map_err(|error| debug!("Cannot encode the dead specification query: {}", error))
+        .ok()?;
+        Some(DeadSpecQuery {
+            kind,
+            span,
+            position,
+            method: fix_ghost_vars(method),
+        })
+    }
+
     fn check_vir(&self) -> SpannedEncodingResult<()> {
         if self.cfg_method.has_loops() {
             return Err(SpannedEncodingError::internal(
@@ -7011,6 +7703,42 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
     fn register_error<T: Into<MultiSpan>>(&self, span: T, error_ctxt: ErrorCtxt) -> vir::Position {
         self.mir_encoder.register_error(span, error_ctxt)
     }
+
+    /// Registers the guards of a switch as branches, so that the branch conditions of a
+    /// verification error can be mapped back to the branches of the procedure (see the
+    /// `report_failing_paths` flag).
+    fn register_branch_guards(
+        &self,
+        targets: Vec<(vir::Expr, BasicBlockIndex)>,
+        default_target: BasicBlockIndex,
+    ) -> Vec<(vir::Expr, BasicBlockIndex)> {
+        if !config::report_failing_paths() {
+            return targets;
+        }
+        let alternative_span = (targets.len() == 1)
+            .then(|| self.get_span_of_branch(default_target));
+        targets
+            .into_iter()
+            .map(|(guard, target)| {
+                let pos = self.encoder.error_manager().register_branch(
+                    self.proc_def_id,
+                    self.get_span_of_branch(target),
+                    alternative_span,
+                );
+                (guard.set_pos(pos), target)
+            })
+            .collect()
+    }
+
+    /// The span of the code at the start of the basic block.
+    fn get_span_of_branch(&self, bb: BasicBlockIndex) -> Span {
+        let bb_data = &self.mir[bb];
+        bb_data
+            .statements
+            .first()
+            .map(|statement| statement.source_info.span)
+            .unwrap_or(bb_data.terminator().source_info.span)
+    }
 }
 
 /// Whether to encode a shared or mutable array access
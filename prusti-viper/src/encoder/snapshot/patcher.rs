@@ -25,6 +25,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> FallibleExprFolder for SnapshotPatcher<'p, 'v, 'tcx>
         vir::SnapApp { mut base, .. }: vir::SnapApp,
     ) -> Result<vir::Expr, Self::Error> {
         base = self.fallible_fold_boxed(base)?;
+        // A value that is constructed in a specification, such as the struct
+        // or the tuple in `result == Point { x: 0, ..old(*p) }`, is already a
+        // snapshot, and so is the value behind its address.
+        if let vir::Expr::AddrOf(vir::AddrOf {
+            base: box ref inner,
+            ..
+        }) = *base
+        {
+            if inner.get_type().is_snapshot() {
+                return Ok(inner.clone());
+            }
+        }
         self.snapshot_encoder.snap_app(self.encoder, *base)
     }
 
@@ -0,0 +1,90 @@
+//! The call sites of the verified procedures, used to show the chain of calls
+//! that leads to a call whose precondition might not hold. Since procedures
+//! are verified modularly, the unsatisfied assumption often originates in one
+//! of the callers, which has to establish a stronger precondition.
+
+use prusti_interface::{data::ProcedureDefId, environment::Environment, PrustiError};
+use prusti_rustc_interface::{
+    hir::def_id::DefId,
+    middle::{mir, ty},
+    span::Span,
+};
+use rustc_hash::FxHashMap;
+
+/// The maximum number of calls reported in a call chain.
+const MAX_CALL_CHAIN_LENGTH: usize = 5;
+
+pub struct CallSites {
+    /// For each called function, the verified procedures that call it and
+    /// the spans of the calls, ordered by the span.
+    call_sites: FxHashMap<DefId, Vec<(ProcedureDefId, Span)>>,
+}
+
+impl CallSites {
+    pub fn collect(env: &Environment, procedures: &[ProcedureDefId]) -> Self {
+        let mut call_sites: FxHashMap<DefId, Vec<(ProcedureDefId, Span)>> = FxHashMap::default();
+        for &caller in procedures {
+            let Some(local_caller) = caller.as_local() else {
+                continue;
+            };
+            let body = env.body.get_impure_fn_body_identity(local_caller);
+            let calls = body
+                .basic_blocks
+                .iter()
+                .filter_map(|block| block.terminator.as_ref())
+                .filter_map(|terminator| match &terminator.kind {
+                    mir::TerminatorKind::Call { func, .. } => {
+                        Some((func.ty(&*body, env.tcx()), terminator.source_info.span))
+                    }
+                    _ => None,
+                });
+            for (func_ty, span) in calls {
+                if let ty::TyKind::FnDef(callee, _) = func_ty.kind() {
+                    call_sites.entry(*callee).or_default().push((caller, span));
+                }
+            }
+        }
+        for sites in call_sites.values_mut() {
+            sites.sort_by_key(|&(_, span)| span);
+        }
+        Self { call_sites }
+    }
+
+    /// Adds to the error a note for each call in the chain of calls that leads
+    /// to `procedure`. Of the callers of each function, only the first one is
+    /// followed.
+    #[must_use]
+    pub fn annotate_call_chain(
+        &self,
+        env: &Environment,
+        procedure: ProcedureDefId,
+        mut error: PrustiError,
+    ) -> PrustiError {
+        let mut visited = vec![procedure];
+        let mut callee = procedure;
+        for _ in 0..MAX_CALL_CHAIN_LENGTH {
+            let Some(sites) = self.call_sites.get(&callee) else {
+                break;
+            };
+            // Each entry has at least one call site.
+            let (caller, span) = sites[0];
+            let mut note = format!(
+                "`{}` is called from `{}` here",
+                env.name.get_item_name(callee),
+                env.name.get_item_name(caller)
+            );
+            match sites.len() - 1 {
+                0 => {}
+                1 => note.push_str(" (and from 1 other call site)"),
+                others => note.push_str(&format!(" (and from {} other call sites)", others)),
+            }
+            error = error.add_note(note, Some(span));
+            if visited.contains(&caller) {
+                break;
+            }
+            visited.push(caller);
+            callee = caller;
+        }
+        error
+    }
+}
@@ -7,6 +7,7 @@
 use prusti_rustc_interface::middle::ty;
 
 
+pub mod call_chain;
 pub mod to_string;
 pub mod type_visitor;
 
@@ -43,4 +44,24 @@ pub fn ty_to_string(typ: &ty::TyKind) -> String {
 
 pub fn is_reference(base_ty: ty::Ty) -> bool {
     matches!(base_ty.kind(), ty::TyKind::RawPtr(..) | ty::TyKind::Ref(..))
-}
\ No newline at end of file
+}
+
+/// Whether indexing the given type, or the type behind the given reference,
+/// is encoded natively, which is the case for arrays and slices. Other types
+/// are indexed by calling their `Index` or `IndexMut` implementation.
+pub fn is_natively_indexed(ty: ty::Ty) -> bool {
+    matches!(ty.peel_refs().kind(), ty::TyKind::Array(..) | ty::TyKind::Slice(..))
+}
+/// The type of the reference pinned by a `Pin<&T>` or a `Pin<&mut T>`.
+pub fn pinned_reference_ty<'tcx>(
+    tcx: ty::TyCtxt<'tcx>,
+    ty: ty::Ty<'tcx>,
+) -> Option<ty::Ty<'tcx>> {
+    match ty.kind() {
+        ty::TyKind::Adt(adt_def, substs) if tcx.lang_items().pin_type() == Some(adt_def.did()) => {
+            let pointer_ty = substs.type_at(0);
+            pointer_ty.is_ref().then_some(pointer_ty)
+        }
+        _ => None,
+    }
+}
@@ -4,24 +4,42 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use prusti_common::vir::{optimizations::optimize_program};
+use prusti_common::vir::optimizations::{
+    optimize_program, slicing::slice_program, splitting::split_methods,
+};
 use prusti_common::{
-    config, report::log, Stopwatch, vir::program::Program,
+    config, report::{log, user}, telemetry, Stopwatch,
+    vir::{program::Program, program_metrics::ProgramMetrics, program_normalization::NormalizationInfo},
 };
 use vir_crate::common::check_mode::CheckMode;
 use crate::encoder::Encoder;
+use crate::encoder::mir::pure::suggest_annotations;
+use crate::encoder::errors::{ErrorCtxt, PositionManager, SourceLocation};
+use crate::utils::call_chain::CallSites;
 use crate::encoder::counterexamples::counterexample_translation;
 use crate::encoder::counterexamples::counterexample_translation_refactored;
 use prusti_interface::data::VerificationResult;
 use prusti_interface::data::VerificationTask;
 use prusti_interface::environment::Environment;
-use prusti_interface::PrustiError;
-use viper::{self, PersistentCache, Viper};
+use prusti_interface::{PrustiError, deduplicate_errors};
+use prusti_interface::data::ProcedureDefId;
+use prusti_interface::utils::read_prusti_attr;
+use rustc_hash::{FxHashMap, FxHashSet};
+use viper::{self, PersistentCache, VerificationBackend, Viper};
 use prusti_interface::specs::typed;
 use ::log::{info, debug, error};
-use prusti_server::{VerificationRequest, PrustiClient, process_verification_request, spawn_server_thread, ViperBackendConfig};
+use prusti_server::{
+    VerificationRequest, PrustiClient, process_verification_request_with_cache_status,
+    spawn_server_thread, ViperBackendConfig, ReusableVerifier, jvm_memory, DebugSession,
+    CacheStatistics, ServerMessage, Artifact, termination_result, MethodResult,
+};
 use prusti_rustc_interface::span::DUMMY_SP;
 use prusti_server::tokio::runtime::Builder;
+use std::collections::{BTreeMap, BTreeSet};
+use rayon::{ThreadPoolBuilder, prelude::*};
+
+/// The maximum number of notes listing the duplicates of a reported error.
+const MAX_DUPLICATE_ERROR_NOTES: usize = 5;
 
 /// A verifier is an object for verifying a single crate, potentially
 /// many times.
@@ -57,6 +75,13 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             log::report("config", "prusti", config::dump());
         }
 
+        // The procedures by the names of their programs, which differ between the encodings.
+        let mut procedures_by_program = FxHashMap::default();
+        for &proc_id in &task.procedures {
+            procedures_by_program.insert(self.env.name.get_unique_item_name(proc_id), proc_id);
+            procedures_by_program.insert(self.env.name.get_absolute_item_name(proc_id), proc_id);
+        }
+
         for &proc_id in &task.procedures {
             let proc_name = self.env.name.get_absolute_item_name(proc_id);
             let proc_def_path = self.env.name.get_item_def_path(proc_id);
@@ -64,6 +89,10 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             info!(" - {} ({})", proc_name, proc_def_path);
             info!("   Source: {:?}", proc_span);
         }
+        if config::suggest_annotations() {
+            let suggestions = suggest_annotations(&self.encoder, &task.procedures);
+            info!("Suggested annotations for {} functions", suggestions);
+        }
         for &proc_id in task.procedures.iter().rev() {
             // FIXME: Use the loop above.
             self.encoder.queue_procedure_encoding(proc_id);
@@ -74,31 +103,72 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         }
         self.encoder.process_encoding_queue();
 
+        if config::fuzz_core_proof() > 0 {
+            if let Some(&proc_id) = task.procedures.first() {
+                stopwatch.start_next("fuzzing the core proof encoder");
+                let proc_span = self.env.query.get_def_span(proc_id);
+                for (seed, message) in self.encoder.run_core_proof_fuzzing(proc_id) {
+                    PrustiError::internal(
+                        format!("the procedure generated for fuzzing with seed {} failed: {}", seed, message),
+                        proc_span.into(),
+                    ).emit(&self.env.diagnostic);
+                }
+            }
+        }
+
         let encoding_errors_count = self.encoder.count_encoding_errors();
 
         let polymorphic_programs = self.encoder.get_viper_programs();
+        let dead_spec_programs = self.encoder.take_dead_spec_programs();
 
-        let mut programs: Vec<Program> = if config::simplify_encoding() {
+        // The encoding itself stays on this thread because it needs the type context of the
+        // compiler, which cannot be shared between threads. The encoded programs are
+        // independent of it, so they are optimized and converted in parallel.
+        let thread_pool = ThreadPoolBuilder::new()
+            .num_threads(config::encoding_threads())
+            .build()
+            .unwrap();
+        if config::simplify_encoding() {
             stopwatch.start_next("optimizing Viper program");
-            let source_file_name = self.encoder.env().name.source_file_name();
-            polymorphic_programs.into_iter().map(
-                |program| Program::Legacy(optimize_program(program, &source_file_name).into())
-            ).collect()
-        } else {
-            polymorphic_programs.into_iter().map(
-                |program| Program::Legacy(program.into())
-            ).collect()
-        };
+        }
+        let source_file_name = self.encoder.env().name.source_file_name();
+        let slice_programs = config::selective_verification().is_some();
+        let max_method_size = config::max_method_size();
+        let mut programs: Vec<Program> = thread_pool.install(|| polymorphic_programs.into_par_iter().map(
+            |mut program| {
+                if config::simplify_encoding() {
+                    program = optimize_program(program, &source_file_name);
+                }
+                if max_method_size > 0 {
+                    program.methods = split_methods(program.methods, max_method_size);
+                }
+                if slice_programs {
+                    program = slice_program(program);
+                }
+                Program::Legacy(program.into())
+            }
+        ).collect());
         programs.extend(self.encoder.get_core_proof_programs());
 
         stopwatch.start_next("verifying Viper program");
-        let verification_results = verify_programs(self.env, programs);
+        let verification_results = verify_programs(
+            self.env,
+            self.encoder.error_manager().position_manager(),
+            programs,
+            &procedures_by_program,
+        );
         stopwatch.finish();
 
+        // The results of the dead specification queries are not verification errors.
+        let (dead_spec_results, verification_results): (Vec<_>, Vec<_>) = verification_results
+            .into_iter()
+            .partition(|(program_name, _)| dead_spec_programs.contains_key(program_name));
+
         // Group verification results
         let mut verification_errors : Vec<_> = vec![];
         let mut consistency_errors : Vec<_> = vec![];
         let mut java_exceptions : Vec<_> = vec![];
+        let mut timeouts : Vec<_> = vec![];
         for (method_name, result) in verification_results.into_iter() {
             match result {
                 viper::VerificationResult::Success => {}
@@ -115,6 +185,9 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                 viper::VerificationResult::JavaException(exception) => {
                     java_exceptions.push((method_name, exception));
                 }
+                viper::VerificationResult::Timeout => {
+                    timeouts.push(method_name);
+                }
             }
         }
 
@@ -122,27 +195,50 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         let error_manager = self.encoder.error_manager();
         let mut result = VerificationResult::Success;
 
+        // Report the backend errors at the verified procedure if the verifier gives no position.
+        let procedure_span = |method: &String| {
+            procedures_by_program
+                .get(method)
+                .map_or(DUMMY_SP, |proc_id| self.env.query.get_def_span(*proc_id))
+        };
+
         for (method, error) in consistency_errors.into_iter() {
-            PrustiError::internal(
-                format!("consistency error in {}: {}", method, error), DUMMY_SP.into()
-            ).emit(&self.env.diagnostic);
+            error_manager
+                .translate_consistency_error(&method, &error, procedure_span(&method).into())
+                .emit(&self.env.diagnostic);
             result = VerificationResult::Failure;
         }
 
         for (method, exception) in java_exceptions.into_iter() {
             error!("Java exception: {}", exception.get_stack_trace());
-            PrustiError::internal(
-                format!("in {}: {}", method, exception), DUMMY_SP.into()
-            ).emit(&self.env.diagnostic);
+            error_manager
+                .translate_java_exception(&method, &exception, procedure_span(&method).into())
+                .emit(&self.env.diagnostic);
             result = VerificationResult::Failure;
         }
 
+        // A timeout does not tell whether the procedure is correct, so it is only a warning and
+        // the remaining procedures still determine the result.
+        for method in timeouts.into_iter() {
+            let proc_id = procedures_by_program.get(&method).copied();
+            let span = proc_id.map_or(DUMMY_SP, |proc_id| self.env.query.get_def_span(proc_id));
+            let timeout = verification_timeout(self.env, proc_id).unwrap_or_default();
+            PrustiError::warning("verification timed out (unknown)", span.into())
+                .add_note(format!("the time budget of {} is {} ms", method, timeout), None)
+                .emit(&self.env.diagnostic);
+        }
+
         // Report verification errors
         let mut prusti_errors: Vec<_> = vec![];
+        let mut call_sites = None;
         for (method, verification_error) in verification_errors.into_iter() {
             debug!("Verification error in {}: {:?}", method, verification_error);
             let mut prusti_error = error_manager.translate_verification_error(&verification_error);
 
+            if config::report_failing_paths() {
+                prusti_error = error_manager.annotate_failing_path(&verification_error, prusti_error);
+            }
+
             // annotate with counterexample, if requested
             if config::counterexample() {
                 if config::unsafe_core_proof(){
@@ -185,20 +281,48 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                 }
             }
 
-            prusti_errors.push(prusti_error);
+            // Show where the unsatisfied precondition of a call might originate.
+            if let Some(ErrorCtxt::ExhaleMethodPrecondition) =
+                error_manager.get_error_ctxt(&verification_error)
+            {
+                if let Some(def_id) = error_manager.get_def_id(&verification_error) {
+                    let call_sites = call_sites
+                        .get_or_insert_with(|| CallSites::collect(self.env, &task.procedures));
+                    prusti_error = call_sites.annotate_call_chain(self.env, def_id, prusti_error);
+                }
+            }
+
+            if prusti_error.is_disabled() {
+                debug!("Disabled Prusti error: {:?}", prusti_error);
+                prusti_error.cancel();
+                result = VerificationResult::Failure;
+                continue;
+            }
+            let origin = procedures_by_program
+                .get(&method)
+                .map_or(method, |proc_id| self.env.name.get_absolute_item_name(*proc_id));
+            prusti_errors.push((origin, prusti_error));
         }
+        let mut prusti_errors = if config::deduplicate_errors() {
+            deduplicate_errors(prusti_errors, MAX_DUPLICATE_ERROR_NOTES)
+        } else {
+            prusti_errors.into_iter().map(|(_, prusti_error)| prusti_error).collect()
+        };
         prusti_errors.sort();
 
         for prusti_error in prusti_errors {
             debug!("Prusti error: {:?}", prusti_error);
-            if prusti_error.is_disabled() {
-                prusti_error.cancel();
-            } else {
-                prusti_error.emit(&self.env.diagnostic);
-            }
+            prusti_error.emit(&self.env.diagnostic);
             result = VerificationResult::Failure;
         }
 
+        // Report the specifications that the queries of `detect_dead_specs` proved dead
+        for (program_name, dead_spec_result) in dead_spec_results {
+            for (kind, span) in dead_spec_programs[&program_name].dead_specs(&dead_spec_result) {
+                kind.to_warning(span).emit(&self.env.diagnostic);
+            }
+        }
+
         if encoding_errors_count != 0 {
             result = VerificationResult::Failure;
         }
@@ -209,9 +333,12 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
 
 /// Verify a list of programs.
 /// Returns a list of (program_name, verification_result) tuples.
-fn verify_programs(env: &Environment, programs: Vec<Program>)
-    -> Vec<(String, viper::VerificationResult)>
-{
+fn verify_programs(
+    env: &Environment,
+    position_manager: &PositionManager,
+    programs: Vec<Program>,
+    procedures_by_program: &FxHashMap<String, ProcedureDefId>,
+) -> Vec<(String, viper::VerificationResult)> {
     let source_path = env.name.source_path();
     let rust_program_name = source_path
         .file_name()
@@ -219,23 +346,86 @@ fn verify_programs(env: &Environment, programs: Vec<Program>)
         .to_str()
         .unwrap()
         .to_owned();
+    // The JVM is started only in the local mode, and it has to fit the largest program.
+    let program_size = if config::server_address().is_none() {
+        programs.iter().map(|program| {
+            let metrics = ProgramMetrics::compute(program);
+            metrics.statements + metrics.expressions
+        }).max()
+    } else {
+        None
+    };
     let verification_requests = programs.into_iter().map(|mut program| {
         let program_name = program.get_name().to_string();
         let check_mode = program.get_check_mode();
         // Prepend the Rust file name to the program.
         program.set_name(format!("{}_{}", rust_program_name, program_name));
+        if config::dump_viper_program() {
+            dump_source_locations(position_manager, &program);
+        }
+        if config::dump_vir_metrics() {
+            dump_metrics(&program);
+        }
+        if config::telemetry() {
+            // The name is left out to keep the telemetry anonymous.
+            let metrics = ProgramMetrics {
+                name: String::new(),
+                ..ProgramMetrics::compute(&program)
+            };
+            telemetry::record_program(&metrics);
+        }
+        let proc_id = procedures_by_program.get(&program_name).copied();
         let backend = if check_mode == CheckMode::Specifications {
             config::verify_specifications_backend()
         } else {
-            config::viper_backend()
-        }.parse().unwrap();
+            procedure_backend(env, proc_id)
+        }.parse::<VerificationBackend>().unwrap();
+        let mut backend_config = ViperBackendConfig::new(backend);
+        if let Some(timeout) = verification_timeout(env, proc_id) {
+            if backend == VerificationBackend::Silicon {
+                backend_config.verifier_args.push("--timeout".to_string());
+                // Silicon expects the timeout in seconds.
+                backend_config.verifier_args.push(((timeout + 999) / 1000).max(1).to_string());
+            }
+        }
+        let qi_bound = proc_id.and_then(|proc_id| verification_qi_bound(env, proc_id));
+        if let (Some(proc_id), Some(qi_bound)) = (proc_id, qi_bound) {
+            if config::use_smt_wrapper() {
+                backend_config.qi_bound = Some(qi_bound);
+            } else {
+                PrustiError::warning(
+                    "the quantifier instantiation budget is ignored, because it is enforced by \
+                    the SMT wrapper",
+                    env.query.get_def_span(proc_id).into(),
+                ).set_help("enable the `use_smt_wrapper` flag")
+                .emit(&env.diagnostic);
+            }
+        }
+        if let Some(proc_id) = proc_id.filter(|&proc_id| {
+            env.query.has_prusti_attribute(proc_id, "nonlinear")
+        }) {
+            if backend == VerificationBackend::Silicon {
+                backend_config.enable_nonlinear_arithmetic();
+            } else {
+                PrustiError::warning(
+                    "nonlinear arithmetic is only enabled for functions verified by Silicon",
+                    env.query.get_def_span(proc_id).into(),
+                ).emit(&env.diagnostic);
+            }
+        }
         let request = VerificationRequest {
             program,
-            backend_config: ViperBackendConfig::new(backend),
+            backend_config,
+            webhook_url: config::server_webhook_url(),
+            // A remote server cannot dump the program into the log directory of the client.
+            return_artifacts: config::dump_viper_program() && config::server_address().is_some(),
+            report_progress: config::report_verified_methods(),
+            shared_domains: vec![],
         };
         (program_name, request)
     });
-    if let Some(server_address) = config::server_address() {
+    let mut cache_statistics = CacheStatistics::default();
+    let results = if let Some(server_address) = config::server_address() {
         let server_address = if server_address == "MOCK" {
             spawn_server_thread().to_string()
         } else {
@@ -256,27 +446,239 @@ fn verify_programs(env: &Environment, programs: Vec<Program>)
             .enable_all()
             .build()
             .expect("failed to construct Tokio runtime");
-        verification_requests.map(|(program_name, request)| {
-            let remote_result = runtime.block_on(client.verify(request));
-            let result = remote_result.unwrap_or_else(|error| {
+        // The domains that the server received from the previously verified crates of the
+        // session are not sent again.
+        let session_domains: FxHashSet<u64> = if config::server_session() {
+            runtime
+                .block_on(client.session_domains())
+                .unwrap_or_else(|error| {
+                    panic!("Could not get the domains of the verification session: {:?}", error)
+                })
+                .into_iter()
+                .collect()
+        } else {
+            FxHashSet::default()
+        };
+        verification_requests.map(|(program_name, mut request)| {
+            let session_request = config::dump_debug_session().then(|| request.clone());
+            request.shared_domains = request
+                .program
+                .remove_domains(|hash| session_domains.contains(&hash));
+            // The messages are handled as soon as they arrive, so that the verified methods are
+            // reported while the server is still verifying the program.
+            let mut termination = None;
+            let remote_result = runtime.block_on(client.process_request_with(
+                request,
+                &mut |message| match message {
+                    ServerMessage::CacheStatistics(statistics) => cache_statistics += statistics,
+                    ServerMessage::Artifact(Artifact::ViperProgram { file_name, program }) => {
+                        log::report("viper_program", file_name, program);
+                    }
+                    ServerMessage::Artifact(Artifact::SmtLogDirectory(directory)) => {
+                        info!(
+                            "The SMT logs of program {} are on the server in {}",
+                            program_name,
+                            directory.display()
+                        );
+                    }
+                    ServerMessage::MethodVerified { name, result, time_ms } => {
+                        report_verified_method(&program_name, &name, result, time_ms);
+                    }
+                    message @ ServerMessage::Termination(_) => termination = Some(message),
+                },
+            ));
+            remote_result.unwrap_or_else(|error| {
                 panic!(
                     "Verification request of program {} failed: {:?}",
                     program_name,
                     error
                 )
             });
+            let result = termination_result(termination.into_iter());
+            dump_debug_session(session_request, &result);
             (program_name, result)
         }).collect()
     } else {
         let mut stopwatch = Stopwatch::start("prusti-viper", "JVM startup");
-        let viper = Viper::new_with_args(&config::viper_home(), config::extra_jvm_args());
+        let viper = Viper::new_with_memory(
+            &config::viper_home(),
+            config::extra_jvm_args(),
+            jvm_memory(program_size),
+        );
         stopwatch.start_next("attach current thread to the JVM");
         let viper_thread = viper.attach_current_thread();
         stopwatch.finish();
         let mut cache = PersistentCache::load_cache(config::cache_path());
+        let mut reusable_verifier = ReusableVerifier::default();
         verification_requests.map(|(program_name, request)| {
-            let result = process_verification_request(&viper_thread, request, &mut cache);
+            let session_request = config::dump_debug_session().then(|| request.clone());
+            let (result, cache_status) = process_verification_request_with_cache_status(
+                &viper_thread,
+                request,
+                &mut cache,
+                &mut reusable_verifier,
+                &mut |message| {
+                    if let ServerMessage::MethodVerified { name, result, time_ms } = message {
+                        report_verified_method(&program_name, &name, result, time_ms);
+                    }
+                },
+            );
+            cache_statistics.record(cache_status);
+            dump_debug_session(session_request, &result);
             (program_name, result)
         }).collect()
+    };
+    if config::enable_cache() {
+        info!("Cache statistics: {}", cache_statistics);
+        if cache_statistics.hit_rate().is_some() {
+            user::message(format!("Verification cache: {}", cache_statistics));
+        }
+    }
+    results
+}
+
+/// Tells the user that the verifier finished the given method of the Viper program, as requested
+/// by the `report_verified_methods` flag.
+fn report_verified_method(program_name: &str, name: &str, result: MethodResult, time_ms: u64) {
+    info!(
+        "Method {} of program {} verified in {} ms: {:?}",
+        name,
+        program_name,
+        time_ms,
+        result
+    );
+    let outcome = match result {
+        MethodResult::Success => "verified",
+        MethodResult::Failure => "failed",
+    };
+    user::message(format!(
+        "Viper method {} of {} {} in {} ms",
+        name,
+        program_name,
+        outcome,
+        time_ms
+    ));
+}
+
+/// Dumps the request together with its result for `prusti-server debug`, if the
+/// `dump_debug_session` flag kept the request.
+fn dump_debug_session(request: Option<VerificationRequest>, result: &viper::VerificationResult) {
+    if let Some(request) = request {
+        DebugSession {
+            request,
+            result: result.clone(),
+        }.dump();
+    }
+}
+
+/// The time budget (in milliseconds) for verifying the program of the procedure, given by its
+/// `#[verification_timeout(ms)]` or `#[verification_budget(time = "..")]` attribute or by the
+/// `verification_timeout` flag.
+fn verification_timeout(env: &Environment, proc_id: Option<ProcedureDefId>) -> Option<u64> {
+    proc_id
+        .and_then(|proc_id| {
+            read_prusti_attr("verification_timeout", env.query.get_attributes(proc_id))
+        })
+        .and_then(|timeout| timeout.parse().ok())
+        .or_else(config::verification_timeout)
+}
+
+/// The backend that verifies the program of the procedure, given by its `#[backend(..)]`
+/// attribute, by the first matching rule of the `backend_rules` flag, or by the `viper_backend`
+/// flag.
+fn procedure_backend(env: &Environment, proc_id: Option<ProcedureDefId>) -> String {
+    let Some(proc_id) = proc_id else {
+        return config::viper_backend();
+    };
+    if let Some(backend) = read_prusti_attr("backend", env.query.get_attributes(proc_id)) {
+        return backend;
+    }
+    let item_name = env.name.get_absolute_item_name(proc_id);
+    config::backend_rules()
+        .into_iter()
+        .find(|(path, _)| {
+            let path = path.strip_prefix("crate::").unwrap_or(path);
+            item_name == path
+                || item_name
+                    .strip_prefix(path)
+                    .map_or(false, |rest| rest.starts_with("::"))
+        })
+        .map(|(_, backend)| backend)
+        .unwrap_or_else(config::viper_backend)
+}
+
+/// The bound on the quantifier instantiations of the verification of the program of the
+/// procedure, given by its `#[verification_budget(qi = n)]` attribute.
+fn verification_qi_bound(env: &Environment, proc_id: ProcedureDefId) -> Option<u64> {
+    read_prusti_attr("verification_qi_bound", env.query.get_attributes(proc_id))?
+        .parse()
+        .ok()
+}
+
+/// Writes a JSON file next to the dumped Viper program that maps the position ids used in the
+/// Viper program (and in the errors reported by the verifier) to the Rust source code. The
+/// position ids are normalized by the server in the same way as here before the program is
+/// dumped.
+fn dump_source_locations(position_manager: &PositionManager, program: &Program) {
+    let mut program = program.clone();
+    let normalization_info = NormalizationInfo::normalize_program(&mut program);
+    let mut position_ids = BTreeSet::new();
+    collect_position_ids(&serde_json::to_value(&program).unwrap(), &mut position_ids);
+    let locations: BTreeMap<u64, SourceLocation> = position_ids
+        .into_iter()
+        .filter_map(|pos_id| {
+            let original_pos_id = normalization_info.denormalize_position_id(pos_id);
+            position_manager.get_source_location(original_pos_id).map(|location| (pos_id, location))
+        })
+        .collect();
+    log::report_with_writer(
+        "viper_program",
+        format!("{}.vpr.json", program.get_name_with_check_mode()),
+        |writer| serde_json::to_writer_pretty(writer, &locations).unwrap(),
+    );
+}
+
+/// Logs the size and complexity metrics of a program and writes them to the log directory.
+fn dump_metrics(program: &Program) {
+    let metrics = ProgramMetrics::compute(program);
+    info!(
+        "Metrics of {}: {} methods, {} statements, {} expressions, {} quantifiers, \
+        {} predicate instances, {} domain axioms",
+        metrics.name,
+        metrics.methods,
+        metrics.statements,
+        metrics.expressions,
+        metrics.quantifiers,
+        metrics.predicate_instances,
+        metrics.domain_axioms,
+    );
+    log::report_with_writer(
+        "vir_metrics",
+        format!("{}.json", metrics.name),
+        |writer| serde_json::to_writer_pretty(writer, &metrics).unwrap(),
+    );
+}
+
+/// Collects the ids of the serialized positions (objects with the fields `line`, `column`, and
+/// `id`) of a serialized program.
+fn collect_position_ids(value: &serde_json::Value, position_ids: &mut BTreeSet<u64>) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            if fields.len() == 3 && fields.contains_key("line") && fields.contains_key("column") {
+                if let Some(pos_id) = fields.get("id").and_then(|id| id.as_u64()) {
+                    position_ids.insert(pos_id);
+                    return;
+                }
+            }
+            for field in fields.values() {
+                collect_position_ids(field, position_ids);
+            }
+        }
+        serde_json::Value::Array(elements) => {
+            for element in elements {
+                collect_position_ids(element, position_ids);
+            }
+        }
+        _ => {}
     }
 }
@@ -1,5 +1,5 @@
 use crate::verifier::verify;
-use prusti_common::config;
+use prusti_common::{config, exit_code::ExitCode};
 use prusti_interface::{
     environment::{mir_storage, Environment},
     specs::{self, cross_crate::CrossCrateSpecs, is_spec_fn},
@@ -13,11 +13,20 @@ use prusti_rustc_interface::{
         query::{query_values::mir_borrowck, ExternProviders, Providers},
         TyCtxt,
     },
-    session::Session,
+    session::{config::OutputType, Session},
 };
 
 #[derive(Default)]
-pub struct PrustiCompilerCalls;
+pub struct PrustiCompilerCalls {
+    /// The most severe exit code of the errors reported by Prusti.
+    exit_code: ExitCode,
+}
+
+impl PrustiCompilerCalls {
+    pub fn exit_code(&self) -> ExitCode {
+        self.exit_code
+    }
+}
 
 // Running `get_body_with_borrowck_facts` can be very slow, therefore we avoid it when not
 // necessary; for crates which won't be verified or spec_fns it suffices to load just the fn body
@@ -95,6 +104,7 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
             let mut env = Environment::new(tcx, env!("CARGO_PKG_VERSION"));
             let spec_checker = specs::checker::SpecChecker::new();
             spec_checker.check(&env);
+            self.exit_code = env.diagnostic.exit_code();
             compiler.session().abort_if_errors();
 
             let hir = env.query.hir();
@@ -110,16 +120,29 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
                 }
             }
             CrossCrateSpecs::import_export_cross_crate(&mut env, &mut def_spec);
-            if !config::no_verify() {
-                verify(env, def_spec);
-            }
+            self.exit_code = if !config::no_verify() {
+                verify(env, def_spec)
+            } else {
+                env.diagnostic.exit_code()
+            };
         });
 
         compiler.session().abort_if_errors();
-        if config::full_compilation() {
+        if config::full_compilation() || emits_only_metadata(compiler.session()) {
             Compilation::Continue
         } else {
             Compilation::Stop
         }
     }
 }
+
+/// Whether the compiler is only asked for the metadata of the crate (and its dependency info),
+/// as by `cargo check`. Writing the metadata is cheap compared to the verification, and without
+/// it the crates that depend on this one cannot be checked.
+fn emits_only_metadata(session: &Session) -> bool {
+    session
+        .opts
+        .output_types
+        .keys()
+        .all(|output_type| matches!(output_type, OutputType::Metadata | OutputType::DepInfo))
+}
@@ -12,15 +12,21 @@
 
 mod arg_value;
 mod callbacks;
+mod ord_laws;
+mod postcondition_inference;
+mod repro;
+mod trusted_calls;
+mod unimplemented_stubs;
+mod verification_record;
 mod verifier;
 
 use arg_value::arg_value;
 use callbacks::PrustiCompilerCalls;
 use lazy_static::lazy_static;
-use log::info;
-use prusti_common::{config, report::user, Stopwatch};
+use log::{info, warn};
+use prusti_common::{config, exit_code::ExitCode, report::user, telemetry, Stopwatch};
 use prusti_rustc_interface::interface::interface::try_print_query_stack;
-use std::{borrow::Cow, env, panic};
+use std::{borrow::Cow, env, fs, io::Write, panic};
 
 /// Link to report Prusti bugs
 const BUG_REPORT_URL: &str = "https://github.com/viperproject/prusti-dev/issues/new";
@@ -100,6 +106,45 @@ fn report_prusti_ice(info: &panic::PanicInfo<'_>, bug_report_url: &str) {
     }
 }
 
+/// Combine the exit code returned by the compiler with the most severe error
+/// reported by Prusti.
+fn get_exit_code(rustc_exit_code: i32, prusti_exit_code: ExitCode) -> i32 {
+    if !config::differentiated_exit_codes() {
+        return rustc_exit_code;
+    }
+    let exit_code = match rustc_exit_code {
+        prusti_rustc_interface::driver::EXIT_SUCCESS => ExitCode::Success,
+        // Errors not reported by Prusti are normal compilation errors.
+        prusti_rustc_interface::driver::EXIT_FAILURE => {
+            if prusti_exit_code == ExitCode::Success {
+                ExitCode::CompilationError
+            } else {
+                prusti_exit_code
+            }
+        }
+        _ => ExitCode::InternalError,
+    };
+    exit_code.code()
+}
+
+/// Append the exit code to the file given by the `exit_code_file` flag, if any.
+fn report_exit_code(exit_code: i32) {
+    if let Some(path) = config::exit_code_file() {
+        let result = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", exit_code));
+        if let Err(error) = result {
+            warn!(
+                "Failed to write the exit code to {}: {}",
+                path.display(),
+                error
+            );
+        }
+    }
+}
+
 /// Initialize Prusti and the Rust compiler loggers.
 fn init_loggers() {
     env_logger::init_from_env(
@@ -162,7 +207,8 @@ fn main() {
         }
     }
 
-    let exit_code = prusti_rustc_interface::driver::catch_with_exit_code(move || {
+    let mut callbacks = PrustiCompilerCalls::default();
+    let rustc_exit_code = prusti_rustc_interface::driver::catch_with_exit_code(|| {
         user::message(format!(
             "{}\n{}\n{}\n",
             r"  __          __        __  ___             ",
@@ -177,6 +223,8 @@ fn main() {
         rustc_args.push("-Zcrate-attr=feature(stmt_expr_attributes)".to_owned());
         rustc_args.push("-Zcrate-attr=feature(register_tool)".to_owned());
         rustc_args.push("-Zcrate-attr=register_tool(prusti)".to_owned());
+        // Items gated with `#[cfg(prusti)]` are compiled only when verifying.
+        rustc_args.push("--cfg=prusti".to_owned());
 
         if config::check_overflows() {
             // Some crates might have a `overflow-checks = false` in their `Cargo.toml` to
@@ -209,11 +257,12 @@ fn main() {
             ));
         }
 
-        let mut callbacks = PrustiCompilerCalls::default();
-
         prusti_rustc_interface::driver::RunCompiler::new(&rustc_args, &mut callbacks).run()
     });
+    let exit_code = get_exit_code(rustc_exit_code, callbacks.exit_code());
+    report_exit_code(exit_code);
     let duration = stopwatch.finish();
+    telemetry::write_report();
     if let Some(deadline) = config::verification_deadline() {
         // Check that we met the deadline.
         assert!(
@@ -0,0 +1,141 @@
+//! The selection of the `Ord` implementations whose laws are checked (the
+//! `check_ord_laws` flag). The specifications of functions that require `Ord`,
+//! such as sorting and `BTreeMap` operations, are sound only if the
+//! implementation is a total order. For an implementation annotated with
+//! `#[refine_trait_spec]` and a `#[pure]` `cmp`, `prusti-specs` generates a
+//! method whose postconditions are the laws. The method is verified only if
+//! the type is used by a verified function through an `Ord` bound.
+
+use prusti_common::{config, report::user};
+use prusti_interface::{data::ProcedureDefId, environment::Environment, PrustiError};
+use prusti_rustc_interface::{
+    data_structures::fx::{FxHashMap, FxHashSet},
+    hir::def_id::DefId,
+    middle::{mir, ty},
+    span::{sym, Span},
+};
+
+/// Removes from `procedures` the methods that check the laws of an `Ord`
+/// implementation, except those of the types that the other procedures use
+/// through an `Ord` bound if the `check_ord_laws` flag is enabled.
+pub fn select_ord_law_checks(env: &Environment<'_>, procedures: &mut Vec<ProcedureDefId>) {
+    let (law_checks, verified): (Vec<_>, Vec<_>) = procedures
+        .iter()
+        .copied()
+        .partition(|&procedure| env.query.has_prusti_attribute(procedure, "ord_laws"));
+    if !config::check_ord_laws() {
+        *procedures = verified;
+        return;
+    }
+    let Some(ord_trait) = env.tcx().get_diagnostic_item(sym::Ord) else {
+        *procedures = verified;
+        return;
+    };
+
+    let used_types = collect_ord_uses(env, ord_trait, &verified);
+    let mut checked_types = FxHashSet::default();
+    procedures.retain(|&procedure| {
+        if !law_checks.contains(&procedure) {
+            return true;
+        }
+        // Each implementation generates a single method.
+        self_type_adt(env, procedure)
+            .map_or(false, |adt| used_types.contains_key(&adt) && checked_types.insert(adt))
+    });
+
+    // The laws of an implementation whose `cmp` is not pure cannot be stated.
+    for (adt, &(caller, ty, span)) in &used_types {
+        if checked_types.contains(adt) {
+            continue;
+        }
+        let Some(cmp) = env
+            .tcx()
+            .associated_item_def_ids(ord_trait)
+            .iter()
+            .copied()
+            .find(|&item| env.tcx().item_name(item) == sym::cmp)
+        else {
+            continue;
+        };
+        let substs = env.tcx().mk_substs_trait(ty, &[]);
+        let (cmp_impl, _) = env.query.resolve_method_call(caller, cmp, substs);
+        if cmp_impl == cmp || !cmp_impl.is_local() {
+            continue;
+        }
+        PrustiError::warning(
+            format!(
+                "the laws of the `Ord` implementation of `{}` are not checked",
+                env.name.get_item_name(*adt)
+            ),
+            env.query.get_def_span(cmp_impl).into(),
+        )
+        .add_note("the implementation is used through an `Ord` bound here", Some(span))
+        .set_help(
+            "mark `cmp` as `#[pure]` and the implementation as `#[refine_trait_spec]` \
+                to check that it is a total order",
+        )
+        .emit(&env.diagnostic);
+    }
+
+    user::message(format!(
+        "Checking the laws of {} `Ord` implementations",
+        checked_types.len()
+    ));
+}
+
+/// The local types that the `procedures` use through an `Ord` bound of a
+/// called function, with one of the callers, the type and the call site.
+fn collect_ord_uses<'tcx>(
+    env: &Environment<'tcx>,
+    ord_trait: DefId,
+    procedures: &[ProcedureDefId],
+) -> FxHashMap<DefId, (ProcedureDefId, ty::Ty<'tcx>, Span)> {
+    let tcx = env.tcx();
+    let mut used_types = FxHashMap::default();
+    for &caller in procedures {
+        let Some(local_caller) = caller.as_local() else {
+            continue;
+        };
+        let body = env.body.get_impure_fn_body_identity(local_caller);
+        for block in body.basic_blocks.iter() {
+            let terminator = block.terminator();
+            let mir::TerminatorKind::Call { func, .. } = &terminator.kind else {
+                continue;
+            };
+            let func_ty = func.ty(&*body, tcx);
+            let ty::TyKind::FnDef(called_def_id, call_substs) = func_ty.kind() else {
+                continue;
+            };
+            let predicates = tcx
+                .predicates_of(*called_def_id)
+                .instantiate(tcx, call_substs);
+            for predicate in predicates.predicates {
+                let Some(trait_predicate) = predicate.to_opt_poly_trait_pred() else {
+                    continue;
+                };
+                let trait_predicate = trait_predicate.skip_binder();
+                if trait_predicate.def_id() != ord_trait {
+                    continue;
+                }
+                let self_ty = trait_predicate.self_ty();
+                if let ty::TyKind::Adt(adt_def, _) = self_ty.kind() {
+                    if adt_def.did().is_local() {
+                        used_types
+                            .entry(adt_def.did())
+                            .or_insert((caller, self_ty, terminator.source_info.span));
+                    }
+                }
+            }
+        }
+    }
+    used_types
+}
+
+/// The type whose `Ord` laws are checked by the given method.
+fn self_type_adt(env: &Environment<'_>, law_check: ProcedureDefId) -> Option<DefId> {
+    let impl_def_id = env.tcx().impl_of_method(law_check)?;
+    env.tcx()
+        .type_of(impl_def_id)
+        .ty_adt_def()
+        .map(|adt_def| adt_def.did())
+}
@@ -0,0 +1,408 @@
+//! The inference of candidate postconditions for simple functions (the
+//! `infer_postconditions` flag). The body of each small function without a
+//! contract is executed symbolically. If the function computes its result
+//! from the arguments without branching or calling other functions, for
+//! example by projecting fields, by arithmetic, or by wrapping the arguments
+//! in a constructor, the equality between `result` and the computed value is
+//! suggested as an `#[ensures]` clause.
+
+use prusti_common::report::user;
+use prusti_interface::{
+    data::ProcedureDefId,
+    environment::Environment,
+    specs::typed::{self, ProcedureSpecification},
+    PrustiError,
+};
+use prusti_rustc_interface::{
+    data_structures::fx::{FxHashMap, FxHashSet},
+    middle::{
+        mir,
+        ty::{self, layout::IntegerExt},
+    },
+    span::{BytePos, Span},
+    target::abi::Integer,
+};
+
+/// The largest number of basic blocks of a function whose postcondition is
+/// inferred.
+const MAX_BASIC_BLOCKS: usize = 16;
+
+/// Reports a warning for each of the `procedures` for which a postcondition
+/// could be inferred, with the postcondition as a structured suggestion.
+pub fn infer_postconditions(
+    env: &Environment<'_>,
+    def_spec: &typed::DefSpecificationMap,
+    procedures: &[ProcedureDefId],
+) {
+    let mut inferred = 0;
+    for &def_id in procedures {
+        let Some(local_def_id) = def_id.as_local() else {
+            continue;
+        };
+        if !procedure_spec(def_spec, def_id).map_or(true, has_no_contract) {
+            continue;
+        }
+        let body = env.body.get_impure_fn_body_identity(local_def_id);
+        if body.basic_blocks.len() > MAX_BASIC_BLOCKS
+            || body.local_decls[mir::RETURN_PLACE].ty.is_unit()
+        {
+            continue;
+        }
+        let Some(result) = SymbolicExecution::new(env, def_id, &body).run() else {
+            continue;
+        };
+        let function_name = env.name.get_item_name(def_id);
+        let postcondition = format!("#[ensures(result == {})]", result.text);
+        let (span, replacement) = attribute_insertion(env, def_id, &postcondition);
+        PrustiError::warning(
+            format!("inferred a postcondition for `{}`", function_name),
+            env.query.get_def_span(def_id).into(),
+        )
+        .set_suggestion(
+            format!(
+                "the body of `{}` satisfies `{}`, which can be a starting point for its contract",
+                function_name, postcondition
+            ),
+            span,
+            replacement,
+        )
+        .emit(&env.diagnostic);
+        inferred += 1;
+    }
+    user::message(format!("Inferred postconditions for {} functions", inferred));
+}
+
+fn procedure_spec(
+    def_spec: &typed::DefSpecificationMap,
+    def_id: ProcedureDefId,
+) -> Option<&ProcedureSpecification> {
+    def_spec
+        .get_proc_spec(&def_id)
+        .map(|spec_graph| &spec_graph.base_spec)
+}
+
+/// Whether the function is an ordinary function whose contract is left blank.
+fn has_no_contract(spec: &ProcedureSpecification) -> bool {
+    spec.posts.is_empty()
+        && spec.pledges.is_empty()
+        && !spec.trusted.extract_inherit().unwrap_or(false)
+        && spec.kind.is_impure().unwrap_or(false)
+}
+
+/// A symbolic value, written as a Rust expression over the arguments.
+#[derive(Clone)]
+struct Value {
+    text: String,
+    /// Whether the expression needs parentheses to be used as an operand.
+    compound: bool,
+}
+
+impl Value {
+    fn atom(text: String) -> Self {
+        Value {
+            text,
+            compound: false,
+        }
+    }
+
+    fn compound(text: String) -> Self {
+        Value {
+            text,
+            compound: true,
+        }
+    }
+
+    fn operand(&self) -> String {
+        if self.compound {
+            format!("({})", self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+/// Executes the single path of a function body, tracking the values of the
+/// locals as expressions over the arguments.
+struct SymbolicExecution<'a, 'tcx> {
+    env: &'a Environment<'tcx>,
+    def_id: ProcedureDefId,
+    body: &'a mir::Body<'tcx>,
+    locals: FxHashMap<mir::Local, Value>,
+    /// The locals that hold the result of a checked arithmetic operation,
+    /// whose first field is the value of the operation.
+    checked: FxHashSet<mir::Local>,
+}
+
+impl<'a, 'tcx> SymbolicExecution<'a, 'tcx> {
+    fn new(
+        env: &'a Environment<'tcx>,
+        def_id: ProcedureDefId,
+        body: &'a mir::Body<'tcx>,
+    ) -> Self {
+        SymbolicExecution {
+            env,
+            def_id,
+            body,
+            locals: FxHashMap::default(),
+            checked: FxHashSet::default(),
+        }
+    }
+
+    /// Returns the value of the result, or `None` if the body branches, calls
+    /// other functions, mutates memory, or uses unsupported operations.
+    fn run(mut self) -> Option<Value> {
+        for arg in self.body.args_iter() {
+            let arg_ty = self.body.local_decls[arg].ty;
+            if let ty::TyKind::Ref(_, _, mir::Mutability::Mut) = arg_ty.kind() {
+                return None;
+            }
+            let name = self.argument_name(arg)?;
+            self.locals.insert(arg, Value::atom(name));
+        }
+        let mut block = mir::START_BLOCK;
+        let mut visited = FxHashSet::default();
+        while visited.insert(block) {
+            let data = &self.body.basic_blocks[block];
+            for statement in &data.statements {
+                self.execute_statement(statement)?;
+            }
+            block = match &data.terminator().kind {
+                mir::TerminatorKind::Goto { target }
+                | mir::TerminatorKind::Assert { target, .. }
+                | mir::TerminatorKind::Drop { target, .. } => *target,
+                mir::TerminatorKind::Return => {
+                    return self.locals.remove(&mir::RETURN_PLACE);
+                }
+                _ => return None,
+            };
+        }
+        None
+    }
+
+    fn argument_name(&self, arg: mir::Local) -> Option<String> {
+        self.body.var_debug_info.iter().find_map(|info| match info.value {
+            mir::VarDebugInfoContents::Place(place) if place.as_local() == Some(arg) => {
+                Some(info.name.to_ident_string())
+            }
+            _ => None,
+        })
+    }
+
+    fn execute_statement(&mut self, statement: &mir::Statement<'tcx>) -> Option<()> {
+        match &statement.kind {
+            mir::StatementKind::Assign(assignment) => {
+                let (place, rvalue) = &**assignment;
+                let local = place.as_local()?;
+                self.checked.remove(&local);
+                // A value that cannot be expressed is only a problem if it is used.
+                match self.evaluate_rvalue(rvalue) {
+                    Some(value) => {
+                        if let mir::Rvalue::CheckedBinaryOp(..) = rvalue {
+                            self.checked.insert(local);
+                        }
+                        self.locals.insert(local, value);
+                    }
+                    None => {
+                        self.locals.remove(&local);
+                    }
+                }
+                Some(())
+            }
+            mir::StatementKind::StorageLive(_)
+            | mir::StatementKind::StorageDead(_)
+            | mir::StatementKind::FakeRead(_)
+            | mir::StatementKind::AscribeUserType(..)
+            | mir::StatementKind::Retag(..)
+            | mir::StatementKind::Nop => Some(()),
+            _ => None,
+        }
+    }
+
+    fn evaluate_rvalue(&self, rvalue: &mir::Rvalue<'tcx>) -> Option<Value> {
+        match rvalue {
+            mir::Rvalue::Use(operand) => self.evaluate_operand(operand),
+            mir::Rvalue::BinaryOp(op, operands) | mir::Rvalue::CheckedBinaryOp(op, operands) => {
+                let (left, right) = &**operands;
+                let left = self.evaluate_operand(left)?;
+                let right = self.evaluate_operand(right)?;
+                Some(Value::compound(format!(
+                    "{} {} {}",
+                    left.operand(),
+                    binary_operator(*op)?,
+                    right.operand()
+                )))
+            }
+            mir::Rvalue::UnaryOp(op, operand) => {
+                let operand = self.evaluate_operand(operand)?;
+                let operator = match op {
+                    mir::UnOp::Not => "!",
+                    mir::UnOp::Neg => "-",
+                };
+                Some(Value::compound(format!("{}{}", operator, operand.operand())))
+            }
+            mir::Rvalue::Cast(mir::CastKind::IntToInt, operand, ty) => {
+                let operand = self.evaluate_operand(operand)?;
+                Some(Value::compound(format!("{} as {}", operand.operand(), ty)))
+            }
+            mir::Rvalue::Aggregate(kind, operands) => self.evaluate_aggregate(kind, operands),
+            _ => None,
+        }
+    }
+
+    fn evaluate_aggregate(
+        &self,
+        kind: &mir::AggregateKind<'tcx>,
+        operands: &[mir::Operand<'tcx>],
+    ) -> Option<Value> {
+        let values = operands
+            .iter()
+            .map(|operand| self.evaluate_operand(operand).map(|value| value.text))
+            .collect::<Option<Vec<_>>>()?;
+        match kind {
+            mir::AggregateKind::Tuple if values.len() == 1 => {
+                Some(Value::atom(format!("({},)", values[0])))
+            }
+            mir::AggregateKind::Tuple => Some(Value::atom(format!("({})", values.join(", ")))),
+            mir::AggregateKind::Adt(adt_did, variant_index, _, _, None) => {
+                let tcx = self.env.tcx();
+                let variant = tcx.adt_def(*adt_did).variant(*variant_index);
+                let path = tcx.def_path_str(variant.def_id);
+                let field_names = variant
+                    .fields
+                    .iter()
+                    .map(|field| field.name.to_ident_string())
+                    .collect::<Vec<_>>();
+                if field_names.is_empty() {
+                    Some(Value::atom(path))
+                } else if field_names.iter().all(|name| name.parse::<usize>().is_ok()) {
+                    Some(Value::atom(format!("{}({})", path, values.join(", "))))
+                } else {
+                    let fields = field_names
+                        .iter()
+                        .zip(values)
+                        .map(|(name, value)| format!("{}: {}", name, value))
+                        .collect::<Vec<_>>();
+                    Some(Value::compound(format!("{} {{ {} }}", path, fields.join(", "))))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn evaluate_operand(&self, operand: &mir::Operand<'tcx>) -> Option<Value> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => self.evaluate_place(*place),
+            mir::Operand::Constant(constant) => self.evaluate_constant(constant),
+        }
+    }
+
+    fn evaluate_place(&self, place: mir::Place<'tcx>) -> Option<Value> {
+        let mut value = self.locals.get(&place.local)?.clone();
+        let mut checked = self.checked.contains(&place.local);
+        let mut derefs = 0;
+        for (base, elem) in place.iter_projections() {
+            match elem {
+                // Fields are accessed through references by auto-dereferencing.
+                mir::ProjectionElem::Deref => derefs += 1,
+                mir::ProjectionElem::Field(field, _) if checked => {
+                    if field.index() != 0 {
+                        return None;
+                    }
+                    checked = false;
+                }
+                mir::ProjectionElem::Field(field, _) => {
+                    let name = match base.ty(self.body, self.env.tcx()).ty.kind() {
+                        ty::TyKind::Adt(adt_def, _) if adt_def.is_struct() => {
+                            let field = &adt_def.non_enum_variant().fields[field.index()];
+                            field.name.to_ident_string()
+                        }
+                        ty::TyKind::Tuple(_) => field.index().to_string(),
+                        _ => return None,
+                    };
+                    value = Value::atom(format!("{}.{}", value.operand(), name));
+                    derefs = 0;
+                }
+                _ => return None,
+            }
+        }
+        if checked {
+            return None;
+        }
+        if derefs > 0 {
+            value = Value::compound(format!("{}{}", "*".repeat(derefs), value.operand()));
+        }
+        Some(value)
+    }
+
+    fn evaluate_constant(&self, constant: &mir::Constant<'tcx>) -> Option<Value> {
+        let tcx = self.env.tcx();
+        let param_env = tcx.param_env(self.def_id);
+        let ty = constant.ty();
+        let text = match ty.kind() {
+            ty::TyKind::Bool => constant.literal.try_eval_bool(tcx, param_env)?.to_string(),
+            ty::TyKind::Int(int_ty) => {
+                let bits = constant.literal.try_eval_bits(tcx, param_env, ty)?;
+                let size = Integer::from_int_ty(&tcx, *int_ty).size();
+                (size.sign_extend(bits) as i128).to_string()
+            }
+            ty::TyKind::Uint(_) => constant
+                .literal
+                .try_eval_bits(tcx, param_env, ty)?
+                .to_string(),
+            _ => return None,
+        };
+        if text.starts_with('-') {
+            Some(Value::compound(text))
+        } else {
+            Some(Value::atom(text))
+        }
+    }
+}
+
+fn binary_operator(op: mir::BinOp) -> Option<&'static str> {
+    Some(match op {
+        mir::BinOp::Add => "+",
+        mir::BinOp::Sub => "-",
+        mir::BinOp::Mul => "*",
+        mir::BinOp::Div => "/",
+        mir::BinOp::Rem => "%",
+        mir::BinOp::BitXor => "^",
+        mir::BinOp::BitAnd => "&",
+        mir::BinOp::BitOr => "|",
+        mir::BinOp::Shl => "<<",
+        mir::BinOp::Shr => ">>",
+        mir::BinOp::Eq => "==",
+        mir::BinOp::Lt => "<",
+        mir::BinOp::Le => "<=",
+        mir::BinOp::Ne => "!=",
+        mir::BinOp::Ge => ">=",
+        mir::BinOp::Gt => ">",
+        mir::BinOp::Offset => return None,
+    })
+}
+
+/// Computes where to insert the attribute before the function, on its own line
+/// with the indentation of the function.
+fn attribute_insertion(
+    env: &Environment<'_>,
+    def_id: ProcedureDefId,
+    attribute: &str,
+) -> (Span, String) {
+    let def_span = env.query.get_def_span(def_id);
+    let location = env.tcx().sess.source_map().lookup_char_pos(def_span.lo());
+    let indentation = location
+        .file
+        .get_line(location.line - 1)
+        .map(|line| line.chars().take(location.col.0).collect::<String>())
+        .filter(|prefix| prefix.chars().all(char::is_whitespace));
+    match indentation {
+        Some(indentation) => {
+            let line_start = def_span.lo() - BytePos(indentation.len() as u32);
+            (
+                def_span.with_lo(line_start).shrink_to_lo(),
+                format!("{}{}\n", indentation, attribute),
+            )
+        }
+        None => (def_span.shrink_to_lo(), format!("{} ", attribute)),
+    }
+}
@@ -0,0 +1,253 @@
+//! A module that writes a reproduction bundle for bug reports (see the
+//! `repro_dir` flag).
+
+use log::warn;
+use prusti_common::config;
+use prusti_interface::{data::ProcedureDefId, environment::Environment, utils};
+use prusti_rustc_interface::{
+    data_structures::fx::FxHashSet,
+    hir,
+    hir::{
+        def_id::{DefId, LocalDefId, CRATE_DEF_ID},
+        intravisit::{walk_expr, walk_path, Visitor},
+    },
+    middle::{
+        hir::{map::Map, nested_filter::OnlyBodies},
+        ty::TyCtxt,
+    },
+    span::Span,
+};
+use std::{fmt::Write as _, fs, io, path::Path};
+
+/// Writes the reproduction bundle of the current crate to the `repro_dir`
+/// directory, if the flag is set.
+pub fn write_repro_bundle(env: &Environment<'_>, procedures: &[ProcedureDefId]) {
+    if let Some(repro_dir) = config::repro_dir() {
+        let crate_dir = repro_dir.join(env.name.local_crate_name());
+        if let Err(error) = write_crate_bundle(env, procedures, &crate_dir) {
+            warn!(
+                "Failed to write the reproduction bundle to {}: {}",
+                crate_dir.display(),
+                error
+            );
+        }
+    }
+}
+
+fn write_crate_bundle(
+    env: &Environment<'_>,
+    procedures: &[ProcedureDefId],
+    crate_dir: &Path,
+) -> io::Result<()> {
+    fs::create_dir_all(crate_dir)?;
+    fs::write(crate_dir.join("config.txt"), config::dump())?;
+    fs::write(
+        crate_dir.join("version.txt"),
+        format!("Prusti version: {}\n", crate::get_prusti_version_info()),
+    )?;
+    fs::write(
+        crate_dir.join("failing_functions.rs"),
+        failing_functions_source(env, procedures),
+    )?;
+    fs::write(crate_dir.join("extern_specs.rs"), extern_specs_source(env))?;
+    Ok(())
+}
+
+/// The minimized source code of the procedures that contain the primary span
+/// of an error: the top-level items that contain the failing procedures and the
+/// local items they transitively refer to, including their (specification)
+/// attributes, preceded by the `use` declarations of the crate root. All other
+/// items of the crate are dropped.
+fn failing_functions_source(env: &Environment<'_>, procedures: &[ProcedureDefId]) -> String {
+    let error_spans: Vec<Span> = env
+        .diagnostic
+        .error_spans()
+        .into_iter()
+        .map(|span| span.source_callsite())
+        .collect();
+    let mut pending: Vec<LocalDefId> = procedures
+        .iter()
+        .filter_map(|procedure| procedure.as_local())
+        .filter(|&local_def_id| {
+            let span = item_span(env, local_def_id);
+            error_spans.iter().any(|error_span| span.contains(*error_span))
+        })
+        .map(|local_def_id| top_level_item(env.tcx(), local_def_id))
+        .collect();
+    if pending.is_empty() {
+        return String::new();
+    }
+    let mut items = FxHashSet::default();
+    while let Some(item) = pending.pop() {
+        if !items.insert(item) {
+            continue;
+        }
+        let span = item_span(env, item);
+        let mut collector = ReferencedItemsCollector::new(env.tcx());
+        // Also visit the specification items generated from the attributes.
+        for body_owner in env.query.hir().body_owners() {
+            if span.contains(item_span(env, body_owner)) {
+                collector.visit_body_owner(body_owner);
+            }
+        }
+        pending.extend(
+            collector
+                .referenced
+                .into_iter()
+                .map(|local_def_id| top_level_item(env.tcx(), local_def_id))
+                .filter(|item| !items.contains(item)),
+        );
+    }
+    let mut spans: Vec<Span> = items
+        .into_iter()
+        .map(|item| item_span(env, item))
+        .collect();
+    spans.sort();
+    spans.dedup();
+    let mut source = String::new();
+    for item_id in env.query.hir().root_module().item_ids {
+        let item = env.query.hir().item(*item_id);
+        if matches!(item.kind, hir::ItemKind::Use(..)) && !item.span.from_expansion() {
+            append_snippet(env, &mut source, item.span);
+        }
+    }
+    for &span in &spans {
+        append_snippet(env, &mut source, span);
+    }
+    let has_main = env
+        .tcx()
+        .entry_fn(())
+        .and_then(|(def_id, _)| def_id.as_local())
+        .map_or(false, |main| {
+            let main_span = item_span(env, main);
+            spans.iter().any(|span| span.contains(main_span))
+        });
+    if !has_main {
+        source.push_str("fn main() {}\n");
+    }
+    source
+}
+
+/// The span of the given item, extended with the span of its attributes.
+fn item_span(env: &Environment<'_>, local_def_id: LocalDefId) -> Span {
+    let hir_id = env.query.as_hir_id(local_def_id);
+    let mut span = env.query.hir().span_with_body(hir_id).source_callsite();
+    let codemap = env.query.codemap();
+    for attr in env.query.get_local_attributes(local_def_id) {
+        let attr_span = attr.span.source_callsite();
+        if attr_span.lo() < span.lo()
+            && codemap.span_to_filename(attr_span) == codemap.span_to_filename(span)
+        {
+            span = attr_span.to(span);
+        }
+    }
+    span
+}
+
+/// The item of the crate root that contains the given definition. Keeping
+/// whole top-level items keeps the paths between the kept items valid.
+fn top_level_item(tcx: TyCtxt<'_>, mut local_def_id: LocalDefId) -> LocalDefId {
+    while let Some(parent) = tcx.opt_parent(local_def_id.to_def_id()) {
+        let parent = parent.expect_local();
+        if parent == CRATE_DEF_ID {
+            break;
+        }
+        local_def_id = parent;
+    }
+    local_def_id
+}
+
+/// Collects the local definitions referred to by paths and method calls.
+struct ReferencedItemsCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    referenced: FxHashSet<LocalDefId>,
+}
+
+impl<'tcx> ReferencedItemsCollector<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            referenced: FxHashSet::default(),
+        }
+    }
+
+    fn visit_body_owner(&mut self, local_def_id: LocalDefId) {
+        match self.tcx.hir().get_by_def_id(local_def_id) {
+            hir::Node::Item(item) => self.visit_item(item),
+            hir::Node::ImplItem(impl_item) => self.visit_impl_item(impl_item),
+            hir::Node::TraitItem(trait_item) => self.visit_trait_item(trait_item),
+            _ => {
+                let body_id = self.tcx.hir().body_owned_by(local_def_id);
+                self.visit_nested_body(body_id);
+            }
+        }
+    }
+
+    fn add(&mut self, def_id: DefId) {
+        if let Some(local_def_id) = def_id.as_local() {
+            if local_def_id != CRATE_DEF_ID {
+                self.referenced.insert(local_def_id);
+            }
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for ReferencedItemsCollector<'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_path(&mut self, path: &'tcx hir::Path<'tcx>, _id: hir::HirId) {
+        if let hir::def::Res::Def(_, def_id) = path.res {
+            self.add(def_id);
+        }
+        walk_path(self, path);
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        let owner = expr.hir_id.owner.def_id;
+        if self.tcx.has_typeck_results(owner.to_def_id()) {
+            if let Some(def_id) = self.tcx.typeck(owner).type_dependent_def_id(expr.hir_id) {
+                self.add(def_id);
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// The source code of the external specifications declared in the crate.
+fn extern_specs_source(env: &Environment<'_>) -> String {
+    let mut spans: Vec<Span> = env
+        .query
+        .hir()
+        .body_owners()
+        .filter(|&local_def_id| {
+            utils::has_extern_spec_attr(env.query.get_local_attributes(local_def_id))
+        })
+        .map(|local_def_id| {
+            let hir_id = env.query.as_hir_id(local_def_id);
+            env.query.hir().span_with_body(hir_id).source_callsite()
+        })
+        .collect();
+    spans.sort();
+    spans.dedup();
+    let mut source = String::new();
+    for span in spans {
+        append_snippet(env, &mut source, span);
+    }
+    source
+}
+
+fn append_snippet(env: &Environment<'_>, source: &mut String, span: Span) {
+    let codemap = env.query.codemap();
+    match codemap.span_to_snippet(span) {
+        Ok(snippet) => {
+            writeln!(source, "// {}", codemap.span_to_diagnostic_string(span)).unwrap();
+            writeln!(source, "{}\n", snippet).unwrap();
+        }
+        Err(error) => warn!("Failed to get the source code of {:?}: {:?}", span, error),
+    }
+}
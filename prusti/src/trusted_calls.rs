@@ -0,0 +1,109 @@
+//! The report of the calls from verified functions into trusted ones (the
+//! `report_trusted_calls` flag). The contracts of the trusted functions are
+//! assumed without being checked against their bodies, so these calls are the
+//! boundary of the trusted computing base of the proof.
+
+use prusti_common::report::user;
+use prusti_interface::{
+    data::ProcedureDefId,
+    environment::Environment,
+    specs::typed::{self, ProcedureSpecification},
+    PrustiError,
+};
+use prusti_rustc_interface::{
+    data_structures::fx::FxHashSet,
+    middle::{mir, ty},
+};
+
+/// Reports a warning for each call from one of the `procedures` into a
+/// function whose contract is trusted: a `#[trusted]` function, a function
+/// specified with `#[extern_spec]`, or one of the `stubs` of unimplemented
+/// functions. The warnings point to the contract that the call relies upon.
+pub fn report_trusted_calls(
+    env: &Environment<'_>,
+    def_spec: &typed::DefSpecificationMap,
+    procedures: &[ProcedureDefId],
+    stubs: &FxHashSet<ProcedureDefId>,
+) {
+    let mut trusted_functions = FxHashSet::default();
+    let mut calls = 0;
+    for &caller in procedures {
+        if procedure_spec(def_spec, caller).map_or(false, is_trusted) {
+            continue;
+        }
+        let Some(local_caller) = caller.as_local() else {
+            continue;
+        };
+        let body = env.body.get_impure_fn_body_identity(local_caller);
+        for block in body.basic_blocks.iter() {
+            let terminator = block.terminator();
+            let mir::TerminatorKind::Call { func, .. } = &terminator.kind else {
+                continue;
+            };
+            let func_ty = func.ty(&*body, env.tcx());
+            let ty::TyKind::FnDef(called_def_id, call_substs) = func_ty.kind() else {
+                continue;
+            };
+            let (called_def_id, _) =
+                env.query.resolve_method_call(caller, *called_def_id, call_substs);
+            let Some(spec) = procedure_spec(def_spec, called_def_id) else {
+                continue;
+            };
+            let kind = if stubs.contains(&called_def_id) {
+                "unimplemented function"
+            } else if is_trusted(spec) {
+                "trusted function"
+            } else if spec.source != called_def_id {
+                "function with an external specification"
+            } else {
+                continue;
+            };
+            calls += 1;
+            trusted_functions.insert(called_def_id);
+            let mut warning = PrustiError::warning(
+                format!(
+                    "`{}` calls the {} `{}`, whose contract is not verified",
+                    env.name.get_item_name(caller),
+                    kind,
+                    env.name.get_item_name(called_def_id)
+                ),
+                terminator.source_info.span.into(),
+            )
+            .add_note(
+                "the contract is specified here",
+                Some(env.query.get_def_span(spec.source)),
+            );
+            for precondition in spec.pres.extract_with_selective_replacement_iter() {
+                warning = warning.add_note(
+                    "the call relies on this precondition",
+                    Some(env.query.get_def_span(*precondition)),
+                );
+            }
+            for postcondition in spec.posts.extract_with_selective_replacement_iter() {
+                warning = warning.add_note(
+                    "the call relies on this postcondition",
+                    Some(env.query.get_def_span(*postcondition)),
+                );
+            }
+            warning.emit(&env.diagnostic);
+        }
+    }
+    user::message(format!(
+        "Found {} calls into {} functions with trusted contracts",
+        calls,
+        trusted_functions.len()
+    ));
+}
+
+fn procedure_spec(
+    def_spec: &typed::DefSpecificationMap,
+    def_id: ProcedureDefId,
+) -> Option<&ProcedureSpecification> {
+    def_spec
+        .get_proc_spec(&def_id)
+        .map(|spec_graph| &spec_graph.base_spec)
+}
+
+fn is_trusted(spec: &ProcedureSpecification) -> bool {
+    spec.trusted.extract_inherit().unwrap_or(false)
+}
@@ -0,0 +1,78 @@
+//! The stubs of unimplemented functions (the `stub_unimplemented_functions`
+//! flag). A function whose body is only a `todo!()` or `unimplemented!()` call
+//! is treated as trusted: its contract is assumed at its call sites instead of
+//! being verified against its body. This allows to verify the callers of the
+//! interface of a module before the module is implemented.
+//!
+//! The contract of a stub is the one written on the function itself. Callees
+//! without a body (foreign functions and trait methods without a default
+//! implementation) are not stubbed, and contracts are not generated from
+//! separate interface annotations.
+
+use prusti_common::{config, report::user};
+use prusti_interface::{
+    data::ProcedureDefId,
+    environment::Environment,
+    specs::typed::{self, ProcedureSpecification, SpecGraph, SpecificationItem},
+};
+use prusti_rustc_interface::{
+    data_structures::fx::FxHashSet,
+    hir::{self, def::DefKind},
+};
+
+/// Marks the unimplemented functions among the `procedures` as trusted if the
+/// `stub_unimplemented_functions` flag is enabled, and returns them.
+pub fn stub_unimplemented_functions(
+    env: &Environment<'_>,
+    def_spec: &mut typed::DefSpecificationMap,
+    procedures: &[ProcedureDefId],
+) -> FxHashSet<ProcedureDefId> {
+    if !config::stub_unimplemented_functions() {
+        return FxHashSet::default();
+    }
+    let stubs: FxHashSet<_> = procedures
+        .iter()
+        .copied()
+        .filter(|&procedure| is_unimplemented(env, procedure))
+        .collect();
+    for &stub in &stubs {
+        let spec_graph = def_spec
+            .proc_specs
+            .entry(stub)
+            .or_insert_with(|| SpecGraph::new(ProcedureSpecification::empty(stub)));
+        spec_graph.base_spec.trusted = SpecificationItem::Inherent(true);
+        for spec in spec_graph.specs_with_constraints.values_mut() {
+            spec.trusted = SpecificationItem::Inherent(true);
+        }
+    }
+    if !stubs.is_empty() {
+        user::message(format!(
+            "Assuming the contracts of {} unimplemented functions",
+            stubs.len()
+        ));
+    }
+    stubs
+}
+
+/// Whether the procedure is a function whose body is only a call to `todo!`
+/// or `unimplemented!`.
+fn is_unimplemented(env: &Environment<'_>, procedure: ProcedureDefId) -> bool {
+    let Some(local_procedure) = procedure.as_local() else {
+        return false;
+    };
+    if !matches!(env.tcx().def_kind(procedure), DefKind::Fn | DefKind::AssocFn) {
+        return false;
+    }
+    let hir = env.tcx().hir();
+    let body = hir.body(hir.body_owned_by(local_procedure));
+    let hir::ExprKind::Block(hir::Block { stmts: [], expr: Some(expr), .. }, _) = body.value.kind
+    else {
+        return false;
+    };
+    expr.span.macro_backtrace().any(|expansion| {
+        expansion.macro_def_id.map_or(false, |macro_def_id| {
+            let name = env.name.get_absolute_item_name(macro_def_id);
+            name.ends_with("::todo") || name.ends_with("::unimplemented")
+        })
+    })
+}
@@ -0,0 +1,122 @@
+//! A module that records the successful verification of a crate in a sidecar
+//! file (see the `verification_record_dir` flag), so that downstream builds
+//! and auditors can check that a crate was verified in its current state. The
+//! record is signed only if a key is given with the `verification_record_key`
+//! flag; otherwise anybody can change it without being noticed.
+
+use log::{info, warn};
+use prusti_common::config;
+use prusti_interface::{data::ProcedureDefId, environment::Environment};
+use prusti_rustc_interface::span::{FileName, RealFileName};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{fs, io, path::Path};
+
+/// The record of a successful verification. Each hash is the hex-encoded
+/// SHA-256 hash of the described content.
+#[derive(Serialize)]
+struct VerificationRecord {
+    crate_name: String,
+    prusti_version: String,
+    /// The hash of the effective configuration, as dumped by `config::dump`.
+    config_hash: String,
+    /// The absolute names of the verified functions, sorted.
+    functions: Vec<String>,
+    /// The source files of the crate, sorted by their path.
+    sources: Vec<SourceFile>,
+    /// The hex-encoded HMAC-SHA256 of the compact JSON serialization of the
+    /// record with this field set to `null`, with the key read from the
+    /// `verification_record_key` file. `None` without a key.
+    signature: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SourceFile {
+    path: String,
+    hash: String,
+}
+
+/// Writes the record of the successful verification of the current crate to
+/// the `verification_record_dir` directory, if the flag is set.
+pub fn write_verification_record(env: &Environment<'_>, procedures: &[ProcedureDefId]) {
+    if let Some(record_dir) = config::verification_record_dir() {
+        let record_path = record_dir.join(format!("{}.prusti.json", env.name.local_crate_name()));
+        match write_record(env, procedures, &record_path) {
+            Ok(()) => info!("Wrote the verification record to {}", record_path.display()),
+            Err(error) => warn!(
+                "Failed to write the verification record to {}: {}",
+                record_path.display(),
+                error
+            ),
+        }
+    }
+}
+
+fn write_record(
+    env: &Environment<'_>,
+    procedures: &[ProcedureDefId],
+    record_path: &Path,
+) -> io::Result<()> {
+    let mut functions: Vec<String> = procedures
+        .iter()
+        .map(|&procedure| env.name.get_absolute_item_name(procedure))
+        .collect();
+    functions.sort();
+    functions.dedup();
+    let mut record = VerificationRecord {
+        crate_name: env.name.local_crate_name(),
+        prusti_version: crate::get_prusti_version_info(),
+        config_hash: hash(config::dump().as_bytes()),
+        functions,
+        sources: source_files(env),
+        signature: None,
+    };
+    if let Some(key_path) = config::verification_record_key() {
+        let key = fs::read(key_path)?;
+        record.signature = Some(sign(&key, &serde_json::to_vec(&record)?));
+    }
+    if let Some(record_dir) = record_path.parent() {
+        fs::create_dir_all(record_dir)?;
+    }
+    fs::write(record_path, serde_json::to_string_pretty(&record)?)
+}
+
+/// The local source files of the crate that were loaded by the compiler.
+fn source_files(env: &Environment<'_>) -> Vec<SourceFile> {
+    let mut sources: Vec<SourceFile> = env
+        .tcx()
+        .sess
+        .source_map()
+        .files()
+        .iter()
+        .filter(|file| !file.is_imported())
+        .filter_map(|file| {
+            let FileName::Real(RealFileName::LocalPath(path)) = &file.name else {
+                return None;
+            };
+            // The file is read again because the compiler normalizes the line endings.
+            let content = fs::read(path).ok()?;
+            Some(SourceFile {
+                path: path.display().to_string(),
+                hash: hash(&content),
+            })
+        })
+        .collect();
+    sources.sort_by(|left, right| left.path.cmp(&right.path));
+    sources
+}
+
+fn hash(content: &[u8]) -> String {
+    to_hex(&Sha256::digest(content))
+}
+
+fn sign(key: &[u8], content: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(content);
+    to_hex(&mac.finalize().into_bytes())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
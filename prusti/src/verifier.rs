@@ -1,7 +1,13 @@
 //! A module that invokes the verifier `prusti-viper`
 
+use crate::{
+    ord_laws::select_ord_law_checks, postcondition_inference::infer_postconditions,
+    repro::write_repro_bundle, trusted_calls::report_trusted_calls,
+    unimplemented_stubs::stub_unimplemented_functions,
+    verification_record::write_verification_record,
+};
 use log::{debug, trace, warn};
-use prusti_common::{config, report::user};
+use prusti_common::{config, exit_code::ExitCode, report::user};
 use prusti_interface::{
     data::{VerificationResult, VerificationTask},
     environment::Environment,
@@ -9,7 +15,9 @@ use prusti_interface::{
 };
 use prusti_viper::verifier::Verifier;
 
-pub fn verify(env: Environment<'_>, def_spec: typed::DefSpecificationMap) {
+/// Verifies the crate and returns the most severe exit code of the errors
+/// reported while doing so.
+pub fn verify(env: Environment<'_>, mut def_spec: typed::DefSpecificationMap) -> ExitCode {
     trace!("[verify] enter");
 
     if env.diagnostic.has_errors() {
@@ -18,7 +26,27 @@ pub fn verify(env: Environment<'_>, def_spec: typed::DefSpecificationMap) {
         debug!("Prepare verification task...");
         // TODO: can we replace `get_annotated_procedures` with information
         // that is already in `def_spec`?
-        let (annotated_procedures, types) = env.get_annotated_procedures_and_types();
+        let (mut annotated_procedures, types) = env.get_annotated_procedures_and_types();
+        select_ord_law_checks(&env, &mut annotated_procedures);
+        let no_verify_modules = config::no_verify_modules();
+        if !no_verify_modules.is_empty() {
+            annotated_procedures.retain(|&procedure| {
+                let item_name = env.name.get_absolute_item_name(procedure);
+                !no_verify_modules
+                    .iter()
+                    .any(|module| is_in_module(&item_name, module))
+            });
+        }
+        let types = if let Some(target) = config::selective_verification() {
+            let target = target.strip_prefix("crate::").unwrap_or(&target);
+            annotated_procedures
+                .retain(|&procedure| env.name.get_absolute_item_name(procedure) == target);
+            // Only the selected function is verified.
+            vec![]
+        } else {
+            types
+        };
+        let stubs = stub_unimplemented_functions(&env, &mut def_spec, &annotated_procedures);
         let verification_task = VerificationTask {
             procedures: annotated_procedures,
             types,
@@ -44,6 +72,14 @@ pub fn verify(env: Environment<'_>, def_spec: typed::DefSpecificationMap) {
             }
         }
 
+        if config::report_trusted_calls() {
+            report_trusted_calls(&env, &def_spec, &verification_task.procedures, &stubs);
+        }
+
+        if config::infer_postconditions() {
+            infer_postconditions(&env, &def_spec, &verification_task.procedures);
+        }
+
         let verification_result =
             if verification_task.procedures.is_empty() && verification_task.types.is_empty() {
                 VerificationResult::Success
@@ -70,6 +106,7 @@ pub fn verify(env: Environment<'_>, def_spec: typed::DefSpecificationMap) {
                         "Successful verification of {} items",
                         verification_task.procedures.len()
                     ));
+                    write_verification_record(&env, &verification_task.procedures);
                 }
             }
             VerificationResult::Failure => {
@@ -82,7 +119,19 @@ pub fn verify(env: Environment<'_>, def_spec: typed::DefSpecificationMap) {
                 );
             }
         };
+
+        write_repro_bundle(&env, &verification_task.procedures);
     }
 
     trace!("[verify] exit");
+    env.diagnostic.exit_code()
+}
+
+/// Checks whether the item with the given (crate-relative) name is defined in
+/// the given module or in one of its submodules.
+fn is_in_module(item_name: &str, module: &str) -> bool {
+    let module = module.strip_prefix("crate::").unwrap_or(module);
+    item_name
+        .strip_prefix(module)
+        .map_or(false, |rest| rest.starts_with("::"))
 }
@@ -517,6 +517,7 @@ fn main() {
             ]),
             java_class!("viper.silver.ast.Not", vec![
                 constructor!(),
+                method!("exp"),
             ]),
             java_class!("viper.silver.ast.NotOp$", vec![
                 object_getter!(),
@@ -731,6 +732,9 @@ fn main() {
             java_class!("viper.silicon.interfaces.SiliconMappedCounterexample", vec![
                 method!("converter")
             ]),
+            java_class!("viper.silicon.interfaces.SiliconFailureContext", vec![
+                method!("branchConditions")
+            ]),
             java_class!("viper.silver.verifier.ErrorReason", vec![
                 method!("id"),
                 method!("pos"),
@@ -738,6 +742,8 @@ fn main() {
             ]),
             java_class!("viper.silver.verifier.ConsistencyError", vec![
                 constructor!(),
+                method!("message"),
+                method!("pos"),
             ]),
             java_class!("viper.silicon.reporting.Converter", vec![
                 method!("extractedHeap"),
@@ -9,10 +9,13 @@ use log::{error, info, warn};
 use crate::verification_result::VerificationResult;
 use std::{
     collections::HashMap,
+    ffi::OsString,
     fs, io,
     ops::DerefMut,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
 };
 
 pub trait Cache {
@@ -20,14 +23,21 @@ pub trait Cache {
     fn insert(self, request: u64, result: VerificationResult) -> Option<VerificationResult>;
 }
 // We can change the representation here (e.g. adding fields):
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct PersistentCache {
     updated: bool,
-    load_loc: PathBuf,
     data: HashMap<u64, VerificationResult>,
+    /// Persists the cache in the background. `None` if the cache path is empty.
+    writer: Option<CacheWriter>,
 }
 
-const RESULT_CACHE_VERSION: u64 = 2;
+const RESULT_CACHE_VERSION: u64 = 3;
+
+/// How long the writer waits for further results before writing them to disk.
+const WRITE_BATCH_DELAY: Duration = Duration::from_millis(500);
+/// The maximum time a new result waits before being written to disk, even if
+/// further results keep arriving.
+const MAX_WRITE_DELAY: Duration = Duration::from_secs(10);
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct ResultCache {
@@ -39,20 +49,16 @@ impl From<(PathBuf, ResultCache)> for PersistentCache {
     /// Used when loading cache from disk
     fn from((load_loc, rc): (PathBuf, ResultCache)) -> Self {
         assert_eq!(rc.version, RESULT_CACHE_VERSION);
-        let data = rc.data;
+        let data = rc.data.clone();
+        let writer = if load_loc.as_os_str().is_empty() {
+            None
+        } else {
+            Some(CacheWriter::spawn(load_loc, rc))
+        };
         PersistentCache {
             updated: false,
-            load_loc,
             data,
-        }
-    }
-}
-impl From<&PersistentCache> for ResultCache {
-    /// Used when saving cache to disk
-    fn from(cache: &PersistentCache) -> Self {
-        ResultCache {
-            data: cache.data.clone(),
-            version: RESULT_CACHE_VERSION,
+            writer,
         }
     }
 }
@@ -92,28 +98,47 @@ impl PersistentCache {
             }),
         ))
     }
-    pub fn save_cache(&self, cache_loc: &Path) {
-        match fs::File::create(cache_loc) {
-            Ok(f) => {
-                info!("Saving cache to \"{}\"", cache_loc.display());
-                bincode::serialize_into(&mut io::BufWriter::new(f), &ResultCache::from(self))
-                    .unwrap_or_else(|e| error!("Failed to write cache: {e}"));
+    /// Writes all results of the cache to `writer`, in the format of the cache
+    /// file, e.g. to keep them as an artifact of a CI run.
+    pub fn export(&self, writer: impl io::Write) -> Result<(), String> {
+        let cache = ResultCache {
+            data: self.data.clone(),
+            version: RESULT_CACHE_VERSION,
+        };
+        bincode::serialize_into(writer, &cache)
+            .map_err(|e| format!("Failed to export cache: {e}"))
+    }
+
+    /// Adds the results exported with `export` that are not yet in the cache.
+    /// Returns the number of added results.
+    pub fn import(&mut self, reader: impl io::Read) -> Result<usize, String> {
+        let cache = bincode::deserialize_from::<_, ResultCache>(reader)
+            .map_err(|e| format!("Failed to import cache: {e}"))?;
+        if cache.version != RESULT_CACHE_VERSION {
+            return Err(format!(
+                "Cache version mismatch when importing: expected={} actual={}",
+                RESULT_CACHE_VERSION, cache.version
+            ));
+        }
+        let mut imported = 0;
+        for (request, result) in cache.data {
+            if !self.data.contains_key(&request) {
+                Cache::insert(&mut *self, request, result);
+                imported += 1;
             }
-            Err(e) => error!("Failed to create cache file: {e}"),
         }
+        info!("Imported {imported} results into the cache");
+        Ok(imported)
     }
+
+    /// Blocks until all results inserted so far are written to disk.
     pub fn save(&mut self) {
         // Save cache to disk, if changed and save path is valid
         if self.updated {
-            if self.load_loc.as_os_str().is_empty() {
-                warn!("Cannot save cache, because the cache path is empty.")
+            if let Some(writer) = &self.writer {
+                writer.flush();
             } else {
-                let mut save_dir = self.load_loc.clone();
-                save_dir.pop();
-                match fs::create_dir_all(&save_dir) {
-                    Ok(()) => self.save_cache(&self.load_loc),
-                    Err(e) => error!("Failed to create cache dir: {e}"),
-                }
+                warn!("Cannot save cache, because the cache path is empty.")
             }
         }
     }
@@ -125,12 +150,143 @@ impl Drop for PersistentCache {
     }
 }
 
+enum WriterMessage {
+    Insert(u64, VerificationResult),
+    /// Write the pending results to disk and then notify the sender.
+    Flush(mpsc::Sender<()>),
+    Shutdown,
+}
+
+/// A background thread that writes the cache to disk, so that the
+/// verification threads don't wait for the (potentially large) cache to be
+/// serialized. The results are written in batches, once no new result arrived
+/// for `WRITE_BATCH_DELAY`.
+#[derive(Debug)]
+struct CacheWriter {
+    sender: mpsc::Sender<WriterMessage>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl CacheWriter {
+    fn spawn(cache_loc: PathBuf, cache: ResultCache) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("cache writer".to_string())
+            .spawn(move || run_cache_writer(&cache_loc, cache, receiver))
+            .expect("failed to spawn the cache writer thread");
+        CacheWriter {
+            sender,
+            thread: Some(thread),
+        }
+    }
+
+    fn insert(&self, request: u64, result: VerificationResult) {
+        if self.sender.send(WriterMessage::Insert(request, result)).is_err() {
+            error!("The cache writer thread stopped unexpectedly");
+        }
+    }
+
+    fn flush(&self) {
+        let (done_sender, done_receiver) = mpsc::channel();
+        if self.sender.send(WriterMessage::Flush(done_sender)).is_ok() {
+            let _ = done_receiver.recv();
+        }
+    }
+}
+
+impl Drop for CacheWriter {
+    fn drop(&mut self) {
+        let _ = self.sender.send(WriterMessage::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("The cache writer thread panicked");
+            }
+        }
+    }
+}
+
+fn run_cache_writer(
+    cache_loc: &Path,
+    mut cache: ResultCache,
+    receiver: mpsc::Receiver<WriterMessage>,
+) {
+    // The time at which the oldest result that is not yet on disk arrived.
+    let mut pending_since: Option<Instant> = None;
+    loop {
+        let message = match pending_since {
+            Some(since) if since.elapsed() >= MAX_WRITE_DELAY => None,
+            Some(_) => match receiver.recv_timeout(WRITE_BATCH_DELAY) {
+                Ok(message) => Some(message),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => Some(WriterMessage::Shutdown),
+            },
+            None => Some(receiver.recv().unwrap_or(WriterMessage::Shutdown)),
+        };
+        match message {
+            Some(WriterMessage::Insert(request, result)) => {
+                cache.data.insert(request, result);
+                pending_since.get_or_insert_with(Instant::now);
+            }
+            Some(WriterMessage::Flush(done)) => {
+                if pending_since.take().is_some() {
+                    write_cache(cache_loc, &cache);
+                }
+                let _ = done.send(());
+            }
+            Some(WriterMessage::Shutdown) => break,
+            None => {
+                pending_since = None;
+                write_cache(cache_loc, &cache);
+            }
+        }
+    }
+    if pending_since.is_some() {
+        write_cache(cache_loc, &cache);
+    }
+}
+
+/// Writes the cache to a temporary file that then atomically replaces the
+/// cache file, so that a crash while writing never leaves a corrupted cache.
+fn write_cache(cache_loc: &Path, cache: &ResultCache) {
+    if let Some(cache_dir) = cache_loc.parent() {
+        if let Err(e) = fs::create_dir_all(cache_dir) {
+            error!("Failed to create cache dir: {e}");
+            return;
+        }
+    }
+    let mut tmp_loc = OsString::from(cache_loc.as_os_str());
+    tmp_loc.push(".tmp");
+    let tmp_loc = PathBuf::from(tmp_loc);
+    info!("Saving cache to \"{}\"", cache_loc.display());
+    let result = fs::File::create(&tmp_loc)
+        .map_err(|e| format!("Failed to create cache file: {e}"))
+        .and_then(|f| {
+            let mut writer = io::BufWriter::new(f);
+            bincode::serialize_into(&mut writer, cache)
+                .map_err(|e| format!("Failed to write cache: {e}"))?;
+            let f = writer
+                .into_inner()
+                .map_err(|e| format!("Failed to write cache: {e}"))?;
+            f.sync_all().map_err(|e| format!("Failed to write cache: {e}"))
+        })
+        .and_then(|()| {
+            fs::rename(&tmp_loc, cache_loc).map_err(|e| format!("Failed to replace cache: {e}"))
+        });
+    if let Err(message) = result {
+        error!("{message}");
+        let _ = fs::remove_file(&tmp_loc);
+    }
+}
+
 impl Cache for &mut PersistentCache {
     fn get(&self, request: u64) -> Option<VerificationResult> {
         self.data.get(&request).cloned()
     }
     fn insert(self, request: u64, result: VerificationResult) -> Option<VerificationResult> {
         self.updated = true;
+        if let Some(writer) = &self.writer {
+            writer.insert(request, result.clone());
+        }
         self.data.insert(request, result)
     }
 }
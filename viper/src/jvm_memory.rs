@@ -0,0 +1,164 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{env, fs};
+
+/// The heap size (in MB) used when nothing is known about the machine, which
+/// is also the smallest heap size that is chosen automatically.
+const DEFAULT_MAX_HEAP_SIZE: u64 = 512;
+/// The stack size (in MB) used when nothing is known about the programs, which
+/// is also the smallest stack size that is chosen automatically.
+const DEFAULT_STACK_SIZE: u64 = 512;
+/// The number of VIR nodes (statements and expressions) for which one
+/// additional MB of heap is reserved.
+const NODES_PER_HEAP_MB: u64 = 500;
+/// The number of VIR nodes for which one additional MB of stack is reserved.
+/// The stack is used by the recursive traversals of deeply nested expressions.
+const NODES_PER_STACK_MB: u64 = 2_000;
+
+/// The memory limits of the JVM that runs the verifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JvmMemory {
+    /// The maximum heap size in MB (`-Xmx`).
+    pub max_heap_size: u64,
+    /// The stack size of the threads in MB (`-Xss`).
+    pub stack_size: u64,
+}
+
+impl Default for JvmMemory {
+    /// The historical limits: a heap of `JAVA_HEAP_SIZE` (or 512) MB and a
+    /// stack of 512 MB.
+    fn default() -> Self {
+        JvmMemory {
+            max_heap_size: heap_size_from_env().unwrap_or(DEFAULT_MAX_HEAP_SIZE),
+            stack_size: DEFAULT_STACK_SIZE,
+        }
+    }
+}
+
+impl JvmMemory {
+    /// Chooses the limits based on the memory available on the machine and on
+    /// the size of the largest program that will be verified, measured in VIR
+    /// nodes. If the size of the programs is not known (e.g. in the server),
+    /// half of the available memory is used for the heap.
+    ///
+    /// The limits are never below the historical 512 MB. Above that, the heap
+    /// never exceeds three quarters of the available memory, so that the
+    /// machine is not pushed into swapping. The `JAVA_HEAP_SIZE` environment
+    /// variable still takes precedence over the computed heap size.
+    pub fn for_program_size(program_size: Option<usize>) -> Self {
+        Self::compute(available_memory(), heap_size_from_env(), program_size)
+    }
+
+    fn compute(
+        available: Option<u64>,
+        heap_size_from_env: Option<u64>,
+        program_size: Option<usize>,
+    ) -> Self {
+        let max_heap_size = heap_size_from_env.unwrap_or_else(|| {
+            let Some(available) = available else {
+                return DEFAULT_MAX_HEAP_SIZE;
+            };
+            let limit = available / 4 * 3;
+            let heap_size = match program_size {
+                Some(size) => DEFAULT_MAX_HEAP_SIZE + size as u64 / NODES_PER_HEAP_MB,
+                None => available / 2,
+            };
+            heap_size.min(limit).max(DEFAULT_MAX_HEAP_SIZE)
+        });
+        let stack_size = DEFAULT_STACK_SIZE
+            + program_size.map_or(0, |size| size as u64 / NODES_PER_STACK_MB);
+        JvmMemory {
+            max_heap_size,
+            stack_size,
+        }
+    }
+}
+
+fn heap_size_from_env() -> Option<u64> {
+    env::var("JAVA_HEAP_SIZE").ok()?.parse().ok()
+}
+
+/// The memory (in MB) that is available for starting new applications without
+/// swapping, as reported by `/proc/meminfo`. Returns `None` on systems without
+/// `/proc/meminfo`.
+pub fn available_memory() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kilobytes: u64 = line
+        .trim_start_matches("MemAvailable:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kilobytes / 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_machine() {
+        assert_eq!(
+            JvmMemory::compute(None, None, Some(1_000)),
+            JvmMemory {
+                max_heap_size: DEFAULT_MAX_HEAP_SIZE,
+                stack_size: DEFAULT_STACK_SIZE,
+            }
+        );
+        assert_eq!(
+            JvmMemory::compute(None, None, None).stack_size,
+            DEFAULT_STACK_SIZE
+        );
+    }
+
+    #[test]
+    fn grows_with_program_size() {
+        let small = JvmMemory::compute(Some(16_000), None, Some(1_000));
+        let large = JvmMemory::compute(Some(16_000), None, Some(1_000_000));
+        assert_eq!(small.max_heap_size, 514);
+        assert_eq!(large.max_heap_size, 2_512);
+        assert_eq!(small.stack_size, DEFAULT_STACK_SIZE);
+        assert_eq!(large.stack_size, 1_012);
+    }
+
+    #[test]
+    fn heap_is_limited_by_available_memory() {
+        let memory = JvmMemory::compute(Some(1_000), None, Some(10_000_000));
+        assert_eq!(memory.max_heap_size, 750);
+    }
+
+    #[test]
+    fn never_below_the_historical_limits() {
+        let memory = JvmMemory::compute(Some(100), None, None);
+        assert_eq!(memory.max_heap_size, DEFAULT_MAX_HEAP_SIZE);
+        assert_eq!(memory.stack_size, DEFAULT_STACK_SIZE);
+        let memory = JvmMemory::compute(Some(100), None, Some(1_000_000));
+        assert_eq!(memory.max_heap_size, DEFAULT_MAX_HEAP_SIZE);
+    }
+
+    #[test]
+    fn unknown_program_size_uses_half_of_the_memory() {
+        assert_eq!(
+            JvmMemory::compute(Some(8_000), None, None).max_heap_size,
+            4_000
+        );
+        assert_eq!(
+            JvmMemory::compute(Some(800), None, None).max_heap_size,
+            512
+        );
+    }
+
+    #[test]
+    fn environment_takes_precedence() {
+        let memory = JvmMemory::compute(Some(1_000), Some(4_096), Some(10_000_000));
+        assert_eq!(memory.max_heap_size, 4_096);
+    }
+}
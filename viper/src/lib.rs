@@ -14,16 +14,18 @@ mod jni_utils;
 pub mod utils;
 mod cache;
 mod java_exception;
+mod jvm_memory;
 pub mod silicon_counterexample;
 pub mod smt_manager;
 mod verification_backend;
 mod verification_context;
 mod verification_result;
 mod verifier;
+mod verifier_progress;
 mod viper;
 
 pub use crate::{
-    ast_factory::*, ast_utils::*, cache::*, java_exception::*, silicon_counterexample::*,
+    ast_factory::*, ast_utils::*, cache::*, java_exception::*, jvm_memory::*, silicon_counterexample::*,
     verification_backend::*, verification_context::*, verification_result::*, verifier::*,
-    viper::*,
+    verifier_progress::*, viper::*,
 };
@@ -51,17 +51,23 @@ impl<'a> VerificationContext<'a> {
             backend,
             extra_args,
             None,
+            false,
             z3_exe,
             boogie_exe,
             SmtManager::default(),
         )
     }
 
+    /// Creates and starts a verifier. If `report_path` is given, the messages of
+    /// the verifier are written to it as CSV. If `report_progress` is set, the
+    /// verified members can be taken from `Verifier::progress`.
+    #[allow(clippy::too_many_arguments)]
     pub fn new_verifier(
         &self,
         backend: VerificationBackend,
         extra_args: Vec<String>,
         report_path: Option<PathBuf>,
+        report_progress: bool,
         z3_exe: String,
         boogie_exe: Option<String>,
         smt_manager: SmtManager,
@@ -96,7 +102,7 @@ impl<'a> VerificationContext<'a> {
 
         debug!("Verifier arguments: '{}'", verifier_args.to_vec().join(" "));
 
-        Verifier::new(&self.env, backend, report_path, smt_manager)
+        Verifier::new(&self.env, backend, report_path, report_progress, smt_manager)
             .parse_command_line(&verifier_args)
             .start()
     }
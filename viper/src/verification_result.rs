@@ -14,9 +14,12 @@ pub enum VerificationResult {
     /// The program did not verify.
     Failure(Vec<VerificationError>),
     /// The program has consistency errors.
-    ConsistencyErrors(Vec<String>),
+    ConsistencyErrors(Vec<ProgramConsistencyError>),
     /// The verification raised a Java exception.
     JavaException(JavaException),
+    /// The verification did not finish within the time budget given by the
+    /// `--timeout` option of the verifier.
+    Timeout,
 }
 
 impl VerificationResult {
@@ -36,6 +39,11 @@ pub struct VerificationError {
     pub reason_pos_id: Option<String>,
     pub message: String,
     pub counterexample: Option<SiliconCounterexample>,
+    /// The branch conditions of the path on which the error occurs, in the
+    /// order in which the path takes the branches. Only Silicon reports them,
+    /// and only with its `--enableBranchconditionReporting` option.
+    #[serde(default)]
+    pub branch_conditions: Vec<BranchCondition>,
 }
 
 impl VerificationError {
@@ -46,6 +54,7 @@ impl VerificationError {
         reason_pos_id: Option<String>,
         message: String,
         counterexample: Option<SiliconCounterexample>,
+        branch_conditions: Vec<BranchCondition>,
     ) -> Self {
         VerificationError {
             full_id,
@@ -54,10 +63,34 @@ impl VerificationError {
             reason_pos_id,
             message,
             counterexample,
+            branch_conditions,
         }
     }
 }
 
+/// A branch condition of the path on which a verification error occurs.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BranchCondition {
+    /// The identifier of the position of the condition, if it has one.
+    pub pos_id: Option<String>,
+    /// Whether the path takes the branch on which the condition does not hold.
+    pub negated: bool,
+}
+
+/// A consistency error of a Viper program, found before verifying it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProgramConsistencyError {
+    pub message: String,
+    /// The identifier of the position of the inconsistent node, if it has one.
+    pub pos_id: Option<String>,
+}
+
+impl std::fmt::Display for ProgramConsistencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
 /// The consistency error reported by the verifier.
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct ConsistencyError {
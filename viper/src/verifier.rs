@@ -10,8 +10,12 @@ use crate::{
     jni_utils::JniUtils,
     silicon_counterexample::SiliconCounterexample,
     smt_manager::SmtManager,
+    java_exception::JavaException,
     verification_backend::VerificationBackend,
-    verification_result::{VerificationError, VerificationResult},
+    verification_result::{
+        BranchCondition, ProgramConsistencyError, VerificationError, VerificationResult,
+    },
+    verifier_progress::VerifierProgress,
 };
 use jni::{objects::JObject, JNIEnv};
 use log::{debug, error, info};
@@ -25,6 +29,7 @@ pub struct Verifier<'a> {
     jni: JniUtils<'a>,
     ast_utils: AstUtils<'a>,
     smt_manager: SmtManager,
+    progress: Option<VerifierProgress>,
 }
 
 impl<'a> Verifier<'a> {
@@ -32,13 +37,15 @@ impl<'a> Verifier<'a> {
         env: &'a JNIEnv,
         backend: VerificationBackend,
         report_path: Option<PathBuf>,
+        report_progress: bool,
         smt_manager: SmtManager,
     ) -> Self {
         let jni = JniUtils::new(env);
         let ast_utils = AstUtils::new(env);
         let verifier_wrapper = silver::verifier::Verifier::with(env);
+        let mut progress = None;
         let verifier_instance = jni.unwrap_result(env.with_local_frame(16, || {
-            let reporter = if let Some(real_report_path) = report_path {
+            let mut reporter = if let Some(real_report_path) = report_path {
                 jni.unwrap_result(silver::reporter::CSVReporter::with(env).new(
                     jni.new_string("csv_reporter"),
                     jni.new_string(real_report_path.to_str().unwrap()),
@@ -46,6 +53,14 @@ impl<'a> Verifier<'a> {
             } else {
                 jni.unwrap_result(silver::reporter::NoopReporter_object::with(env).singleton())
             };
+            if report_progress {
+                if let Some((verifier_progress, polling_reporter)) =
+                    VerifierProgress::wrap_reporter(env, reporter)
+                {
+                    progress = Some(verifier_progress);
+                    reporter = polling_reporter;
+                }
+            }
 
             let debug_info = jni.new_seq(&[]);
             match backend {
@@ -74,9 +89,16 @@ impl<'a> Verifier<'a> {
             jni,
             ast_utils,
             smt_manager,
+            progress,
         }
     }
 
+    /// The members that the verifier reports as verified, if it was created with
+    /// `report_progress`.
+    pub fn progress(&self) -> Option<VerifierProgress> {
+        self.progress.clone()
+    }
+
     #[must_use]
     pub fn parse_command_line(self, args: &[String]) -> Self {
         self.ast_utils.with_local_frame(16, || {
@@ -103,6 +125,18 @@ impl<'a> Verifier<'a> {
         self
     }
 
+    /// Stops and restarts the verifier, so that the state of the previous verification (e.g. the
+    /// running SMT solver) does not leak into the next one.
+    pub fn reset(&mut self) -> Result<(), JavaException> {
+        self.ast_utils.with_local_frame(16, || {
+            self.jni
+                .unwrap_or_exception(self.verifier_wrapper.call_stop(self.verifier_instance))?;
+            self.jni
+                .unwrap_or_exception(self.verifier_wrapper.call_start(self.verifier_instance))?;
+            Ok(())
+        })
+    }
+
     pub fn verify(&mut self, program: Program) -> VerificationResult {
         self.ast_utils.with_local_frame(16, || {
             debug!(
@@ -124,10 +158,30 @@ impl<'a> Verifier<'a> {
                     "The provided Viper program has {} consistency errors.",
                     consistency_errors.len()
                 );
+                let consistency_error_wrapper = silver::verifier::ConsistencyError::with(self.env);
+                let has_identifier_wrapper = silver::ast::HasIdentifier::with(self.env);
                 return VerificationResult::ConsistencyErrors(
                     consistency_errors
                         .into_iter()
-                        .map(|e| self.jni.to_string(e))
+                        .map(|error| {
+                            let message = self.jni.get_string(
+                                self.jni.unwrap_result(consistency_error_wrapper.call_message(error)),
+                            );
+                            let pos = self
+                                .jni
+                                .unwrap_result(consistency_error_wrapper.call_pos(error));
+                            let pos_id = if self
+                                .jni
+                                .is_instance_of(pos, "viper/silver/ast/HasIdentifier")
+                            {
+                                Some(self.jni.get_string(
+                                    self.jni.unwrap_result(has_identifier_wrapper.call_id(pos)),
+                                ))
+                            } else {
+                                None
+                            };
+                            ProgramConsistencyError { message, pos_id }
+                        })
                         .collect(),
                 );
             }
@@ -172,6 +226,16 @@ impl<'a> Verifier<'a> {
                         .is_instance_of(viper_error, "viper/silver/verifier/VerificationError");
 
                     if !is_verification_error {
+                        if self
+                            .jni
+                            .is_instance_of(viper_error, "viper/silver/verifier/TimeoutOccurred")
+                        {
+                            debug!(
+                                "The verification timed out: {}",
+                                self.jni.to_string(viper_error)
+                            );
+                            return VerificationResult::Timeout;
+                        }
                         let is_aborted_exceptionally = self
                             .jni
                             .is_instance_of(viper_error, "viper/silver/verifier/AbortedExceptionally");
@@ -187,6 +251,10 @@ impl<'a> Verifier<'a> {
                                 "The verification aborted due to the following exception: {}",
                                 stack_trace
                             );
+                            return VerificationResult::JavaException(JavaException::new(
+                                self.jni.to_string(exception),
+                                stack_trace,
+                            ));
                         } else {
                             error!(
                                 "The verifier returned an unhandled error of type {}: {}",
@@ -204,6 +272,18 @@ impl<'a> Verifier<'a> {
                     .jni
                     .unwrap_result(verification_error_wrapper.call_failureContexts(viper_error)));
 
+                    let branch_conditions = match failure_contexts.last() {
+                        Some(&failure_context)
+                            if self.jni.is_instance_of(
+                                failure_context,
+                                "viper/silicon/interfaces/SiliconFailureContext",
+                            ) =>
+                        {
+                            self.branch_conditions(failure_context)
+                        }
+                        _ => vec![],
+                    };
+
                     let counterexample: Option<SiliconCounterexample> = {
                         if let Some(failure_context) = failure_contexts.pop() {
                             let option_original_counterexample = self
@@ -324,6 +404,7 @@ impl<'a> Verifier<'a> {
                         reason_pos_id,
                         message,
                         counterexample,
+                        branch_conditions,
                     ))
                 }
 
@@ -335,6 +416,45 @@ impl<'a> Verifier<'a> {
     }
 }
 
+impl<'a> Verifier<'a> {
+    /// The branch conditions of a failure context of Silicon.
+    fn branch_conditions(&self, failure_context: JObject<'a>) -> Vec<BranchCondition> {
+        let positioned_wrapper = silver::ast::Positioned::with(self.env);
+        let has_identifier_wrapper = silver::ast::HasIdentifier::with(self.env);
+        let not_wrapper = silver::ast::Not::with(self.env);
+        let pos_id = |exp: JObject<'a>| {
+            let pos = self.jni.unwrap_result(positioned_wrapper.call_pos(exp));
+            if self.jni.is_instance_of(pos, "viper/silver/ast/HasIdentifier") {
+                Some(self.jni.get_string(
+                    self.jni.unwrap_result(has_identifier_wrapper.call_id(pos)),
+                ))
+            } else {
+                None
+            }
+        };
+        let conditions = self.jni.seq_to_vec(self.jni.unwrap_result(
+            silicon::interfaces::SiliconFailureContext::with(self.env)
+                .call_branchConditions(failure_context),
+        ));
+        conditions
+            .into_iter()
+            .map(|condition| {
+                let condition_pos_id = pos_id(condition);
+                // Silicon negates the condition of a branch that is not taken,
+                // and gives the negation the position of the condition.
+                let negated = condition_pos_id.is_some()
+                    && self.jni.is_instance_of(condition, "viper/silver/ast/Not")
+                    && pos_id(self.jni.unwrap_result(not_wrapper.call_exp(condition)))
+                        == condition_pos_id;
+                BranchCondition {
+                    pos_id: condition_pos_id,
+                    negated,
+                }
+            })
+            .collect()
+    }
+}
+
 impl<'a> Drop for Verifier<'a> {
     fn drop(&mut self) {
         // Tell the verifier to stop its threads.
@@ -0,0 +1,115 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::jni_utils::JniUtils;
+use jni::{
+    objects::{GlobalRef, JObject, JValue},
+    JNIEnv, JavaVM,
+};
+use log::warn;
+use std::sync::Arc;
+
+/// The reporter of Silver that keeps the messages that it receives until they are taken.
+const POLLING_REPORTER_CLASS: &str = "viper/silver/reporter/PollingReporter";
+
+/// A member of the program (e.g. a method) whose verification finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedMember {
+    pub name: String,
+    pub success: bool,
+    /// How long the verification of the member took, in milliseconds.
+    pub time_ms: u64,
+}
+
+/// The `EntitySuccessMessage`s and `EntityFailureMessage`s that the verifier
+/// sends to its reporter while it verifies a program. Only Silicon reports the
+/// verified members. The messages can be taken from any thread.
+#[derive(Clone)]
+pub struct VerifierProgress {
+    vm: Arc<JavaVM>,
+    reporter: GlobalRef,
+}
+
+impl VerifierProgress {
+    /// Wraps the reporter into a `PollingReporter`, which passes all messages
+    /// on to it. Returns `None` if the used version of Silver has no
+    /// `PollingReporter`.
+    pub(crate) fn wrap_reporter<'a>(
+        env: &'a JNIEnv<'a>,
+        reporter: JObject<'a>,
+    ) -> Option<(Self, JObject<'a>)> {
+        let jni = JniUtils::new(env);
+        if env.find_class(POLLING_REPORTER_CLASS).is_err() {
+            jni.unwrap_result(env.exception_clear());
+            warn!("The verified methods are not reported, because Silver has no PollingReporter");
+            return None;
+        }
+        let polling_reporter = jni.unwrap_result(env.new_object(
+            POLLING_REPORTER_CLASS,
+            "(Ljava/lang/String;Lviper/silver/reporter/Reporter;)V",
+            &[
+                JValue::Object(jni.new_string("polling_reporter")),
+                JValue::Object(reporter),
+            ],
+        ));
+        let progress = VerifierProgress {
+            vm: Arc::new(jni.unwrap_result(env.get_java_vm())),
+            reporter: jni.unwrap_result(env.new_global_ref(polling_reporter)),
+        };
+        Some((progress, polling_reporter))
+    }
+
+    /// Takes the members that were reported as verified since the last call.
+    pub fn take_verified_members(&self) -> Vec<VerifiedMember> {
+        let env = self
+            .vm
+            .attach_current_thread()
+            .expect("failed to attach the thread to the JVM");
+        let jni = JniUtils::new(&env);
+        let reporter = self.reporter.as_obj();
+        let mut members = vec![];
+        while jni.unwrap_result(
+            env.call_method(reporter, "hasNewMessage", "()Z", &[])
+                .and_then(|value| value.z()),
+        ) {
+            // Each message gets its own frame, so that the references to the messages are
+            // released while the verifier is running.
+            jni.unwrap_result(env.push_local_frame(16));
+            let message = jni.unwrap_result(
+                env.call_method(
+                    reporter,
+                    "getNewMessage",
+                    "()Lviper/silver/reporter/Message;",
+                    &[],
+                )
+                .and_then(|value| value.l()),
+            );
+            let success = jni.is_instance_of(message, "viper/silver/reporter/EntitySuccessMessage");
+            if success || jni.is_instance_of(message, "viper/silver/reporter/EntityFailureMessage")
+            {
+                let member = jni.unwrap_result(
+                    env.call_method(message, "concerning", "()Lviper/silver/ast/Member;", &[])
+                        .and_then(|value| value.l()),
+                );
+                let name = jni.unwrap_result(
+                    env.call_method(member, "name", "()Ljava/lang/String;", &[])
+                        .and_then(|value| value.l()),
+                );
+                let time_ms = jni.unwrap_result(
+                    env.call_method(message, "verificationTime", "()J", &[])
+                        .and_then(|value| value.j()),
+                );
+                members.push(VerifiedMember {
+                    name: jni.get_string(name),
+                    success,
+                    time_ms: time_ms as u64,
+                });
+            }
+            jni.unwrap_result(env.pop_local_frame(JObject::null()));
+        }
+        members
+    }
+}
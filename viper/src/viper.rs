@@ -4,7 +4,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{jni_utils::JniUtils, verification_context::*};
+use crate::{jni_utils::JniUtils, jvm_memory::JvmMemory, verification_context::*};
 use jni::*;
 use log::{debug, info};
 use std::{env, fs, path::Path};
@@ -26,8 +26,14 @@ impl Viper {
     }
 
     pub fn new_with_args(viper_home: &str, java_args: Vec<String>) -> Self {
-        let heap_size = env::var("JAVA_HEAP_SIZE").unwrap_or_else(|_| "512".to_string());
+        Self::new_with_memory(viper_home, java_args, JvmMemory::default())
+    }
 
+    pub fn new_with_memory(viper_home: &str, java_args: Vec<String>, memory: JvmMemory) -> Self {
+        info!(
+            "Starting the JVM with a maximum heap size of {} MB and a stack size of {} MB",
+            memory.max_heap_size, memory.stack_size
+        );
         debug!("Using Viper home: '{}'", &viper_home);
         assert!(
             Path::new(&viper_home).is_dir(),
@@ -50,9 +56,9 @@ impl Viper {
                 jar_paths.join(classpath_separator)
             ))
             // maximum heap size
-            .option(&format!("-Xmx{}m", heap_size))
+            .option(&format!("-Xmx{}m", memory.max_heap_size))
             // stack size
-            .option("-Xss512m");
+            .option(&format!("-Xss{}m", memory.stack_size));
         //.option("-Xdebug")
         //.option("-verbose:gc")
         //.option("-Xcheck:jni")
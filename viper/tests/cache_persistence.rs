@@ -0,0 +1,69 @@
+use std::{env, fs, path::PathBuf};
+use viper::{Cache, PersistentCache, VerificationResult};
+
+fn cache_path(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("prusti-cache-persistence-{}", std::process::id()));
+    let path = dir.join(name).join("cache.bin");
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+    path
+}
+
+#[test]
+fn save_and_load() {
+    let path = cache_path("save_and_load");
+    let mut cache = PersistentCache::load_cache(path.clone());
+    (&mut cache).insert(1, VerificationResult::Success);
+    (&mut cache).insert(2, VerificationResult::Failure(vec![]));
+    cache.save();
+    assert!(path.exists());
+    assert!(!path.with_extension("bin.tmp").exists());
+
+    let mut loaded = PersistentCache::load_cache(path.clone());
+    assert_eq!((&mut loaded).get(1), Some(VerificationResult::Success));
+    assert_eq!((&mut loaded).get(2), Some(VerificationResult::Failure(vec![])));
+    assert_eq!((&mut loaded).get(3), None);
+}
+
+#[test]
+fn drop_writes_pending_results() {
+    let path = cache_path("drop_writes_pending_results");
+    {
+        let mut cache = PersistentCache::load_cache(path.clone());
+        for request in 0..100 {
+            (&mut cache).insert(request, VerificationResult::Success);
+        }
+    }
+    let mut loaded = PersistentCache::load_cache(path);
+    for request in 0..100 {
+        assert_eq!((&mut loaded).get(request), Some(VerificationResult::Success));
+    }
+}
+
+#[test]
+fn later_saves_keep_earlier_results() {
+    let path = cache_path("later_saves_keep_earlier_results");
+    let mut cache = PersistentCache::load_cache(path.clone());
+    (&mut cache).insert(1, VerificationResult::Success);
+    cache.save();
+    (&mut cache).insert(2, VerificationResult::Timeout);
+    cache.save();
+    drop(cache);
+
+    let mut loaded = PersistentCache::load_cache(path);
+    assert_eq!((&mut loaded).get(1), Some(VerificationResult::Success));
+    assert_eq!((&mut loaded).get(2), Some(VerificationResult::Timeout));
+}
+
+#[test]
+fn corrupted_cache_is_replaced() {
+    let path = cache_path("corrupted_cache_is_replaced");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, b"not a cache").unwrap();
+    let mut cache = PersistentCache::load_cache(path.clone());
+    assert_eq!((&mut cache).get(1), None);
+    (&mut cache).insert(1, VerificationResult::Success);
+    drop(cache);
+
+    let mut loaded = PersistentCache::load_cache(path);
+    assert_eq!((&mut loaded).get(1), Some(VerificationResult::Success));
+}
@@ -0,0 +1,17 @@
+use std::path::PathBuf;
+use viper::{Cache, PersistentCache, VerificationResult};
+
+#[test]
+fn export_and_import() {
+    let mut exported_cache = PersistentCache::load_cache(PathBuf::new());
+    (&mut exported_cache).insert(1, VerificationResult::Success);
+    (&mut exported_cache).insert(2, VerificationResult::Failure(vec![]));
+    let mut exported = vec![];
+    exported_cache.export(&mut exported).unwrap();
+
+    let mut imported_cache = PersistentCache::load_cache(PathBuf::new());
+    (&mut imported_cache).insert(2, VerificationResult::Failure(vec![]));
+    assert_eq!(imported_cache.import(exported.as_slice()), Ok(1));
+    assert_eq!((&mut imported_cache).get(1), Some(VerificationResult::Success));
+    assert!(imported_cache.import(&b"not a cache"[..]).is_err());
+}
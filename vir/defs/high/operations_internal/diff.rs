@@ -0,0 +1,125 @@
+use super::super::{
+    ast::statement::Statement,
+    cfg::procedure::{BasicBlock, ProcedureDecl, Successor},
+};
+use crate::common::position::Positioned;
+use std::{collections::BTreeSet, fmt::Write};
+
+impl ProcedureDecl {
+    /// Computes a structural diff between `self` (the procedure before a
+    /// transformation) and `after`. Blocks are matched by their labels and
+    /// the statements of matched blocks are compared with a longest common
+    /// subsequence, so only the removed (`-`) and added (`+`) statements are
+    /// printed together with their positions. Returns an empty string if the
+    /// procedures are equal.
+    pub fn diff(&self, after: &Self) -> String {
+        let mut text = String::new();
+        if self.entry != after.entry {
+            writeln!(text, "entry: {} -> {}", self.entry, after.entry).unwrap();
+        }
+        if self.exit != after.exit {
+            writeln!(text, "exit: {} -> {}", self.exit, after.exit).unwrap();
+        }
+        let labels: BTreeSet<_> = self
+            .basic_blocks
+            .keys()
+            .chain(after.basic_blocks.keys())
+            .collect();
+        for label in labels {
+            match (self.basic_blocks.get(label), after.basic_blocks.get(label)) {
+                (Some(before_block), Some(after_block)) => {
+                    if before_block != after_block {
+                        writeln!(text, "block {}:", label).unwrap();
+                        diff_blocks(&mut text, before_block, after_block);
+                    }
+                }
+                (Some(before_block), None) => {
+                    writeln!(text, "block {} (removed):", label).unwrap();
+                    for statement in &before_block.statements {
+                        write_statement(&mut text, '-', statement);
+                    }
+                }
+                (None, Some(after_block)) => {
+                    writeln!(text, "block {} (added):", label).unwrap();
+                    for statement in &after_block.statements {
+                        write_statement(&mut text, '+', statement);
+                    }
+                    let successor = describe_successor(&after_block.successor);
+                    writeln!(text, "  successor: {}", successor).unwrap();
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+        text
+    }
+}
+
+fn diff_blocks(text: &mut String, before: &BasicBlock, after: &BasicBlock) {
+    let before_statements = &before.statements;
+    let after_statements = &after.statements;
+    // `lengths[i][j]` is the length of the longest common subsequence of
+    // `before_statements[i..]` and `after_statements[j..]`.
+    let mut lengths = vec![vec![0usize; after_statements.len() + 1]; before_statements.len() + 1];
+    for i in (0..before_statements.len()).rev() {
+        for j in (0..after_statements.len()).rev() {
+            lengths[i][j] = if before_statements[i] == after_statements[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < before_statements.len() || j < after_statements.len() {
+        if i < before_statements.len()
+            && j < after_statements.len()
+            && before_statements[i] == after_statements[j]
+        {
+            i += 1;
+            j += 1;
+        } else if j == after_statements.len()
+            || (i < before_statements.len() && lengths[i + 1][j] >= lengths[i][j + 1])
+        {
+            write_statement(text, '-', &before_statements[i]);
+            i += 1;
+        } else {
+            write_statement(text, '+', &after_statements[j]);
+            j += 1;
+        }
+    }
+    if before.successor != after.successor {
+        writeln!(
+            text,
+            "  successor: {} -> {}",
+            describe_successor(&before.successor),
+            describe_successor(&after.successor)
+        )
+        .unwrap();
+    }
+}
+
+fn write_statement(text: &mut String, marker: char, statement: &Statement) {
+    let position = statement.position();
+    // Multi-line statements such as comments are printed on a single line.
+    let statement = statement.to_string().replace('\n', " ");
+    if position.is_default() {
+        writeln!(text, "  {} {}", marker, statement).unwrap();
+    } else {
+        writeln!(text, "  {} {}#{} {}", marker, position, position.id, statement).unwrap();
+    }
+}
+
+fn describe_successor(successor: &Successor) -> String {
+    match successor {
+        Successor::Exit => "return".to_string(),
+        Successor::Goto(target) => format!("goto {}", target),
+        Successor::GotoSwitch(targets) => {
+            let targets: Vec<_> = targets
+                .iter()
+                .map(|(test, target)| format!("{} => {}", test, target))
+                .collect();
+            format!("switch {{ {} }}", targets.join(", "))
+        }
+        Successor::NonDetChoice(first, second) => format!("nondet {}, {}", first, second),
+    }
+}
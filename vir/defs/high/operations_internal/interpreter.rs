@@ -0,0 +1,277 @@
+use super::{
+    super::{
+        ast::{
+            expression::{BinaryOpKind, ConstantValue, Expression, UnaryOpKind},
+            statement::Statement,
+            ty::{Int, Type},
+        },
+        cfg::procedure::{ProcedureDecl, Successor},
+    },
+    ty::Typed,
+};
+use crate::common::interpreter::{
+    unsupported, Environment, InterpreterError, InterpreterResult, Value,
+};
+use std::collections::BTreeMap;
+
+impl Expression {
+    /// Evaluates the expression in the given environment. Old expressions
+    /// are not supported because the environment has no history; use
+    /// `ProcedureDecl::interpret` for them.
+    pub fn evaluate(&self, environment: &Environment) -> InterpreterResult<Value> {
+        State {
+            environment: environment.clone(),
+            old_environments: BTreeMap::new(),
+        }
+        .evaluate(self)
+    }
+}
+
+impl ProcedureDecl {
+    /// Executes the procedure starting with the given values of the
+    /// variables and returns the values at the exit. Each executed basic
+    /// block counts as one step.
+    ///
+    /// The supported statements are comments, old labels, assumptions,
+    /// assertions, and ghost assignments and havocs of local variables. A
+    /// havocked variable becomes uninitialized. Integer arithmetic follows
+    /// Rust: overflows are errors and division rounds towards zero.
+    pub fn interpret(
+        &self,
+        inputs: Environment,
+        max_steps: usize,
+    ) -> InterpreterResult<Environment> {
+        let mut state = State {
+            environment: inputs,
+            old_environments: BTreeMap::new(),
+        };
+        let mut current = &self.entry;
+        for _ in 0..max_steps {
+            let Some(block) = self.basic_blocks.get(current) else {
+                return unsupported(format!("missing block {}", current));
+            };
+            for statement in &block.statements {
+                state.execute(statement)?;
+            }
+            current = match &block.successor {
+                Successor::Exit => return Ok(state.environment),
+                Successor::Goto(target) => target,
+                Successor::GotoSwitch(targets) => {
+                    let mut selected = None;
+                    for (test, target) in targets {
+                        if state.evaluate_bool(test)? {
+                            selected = Some(target);
+                            break;
+                        }
+                    }
+                    let Some(target) = selected else {
+                        return Err(InterpreterError::NoApplicableTarget(current.to_string()));
+                    };
+                    target
+                }
+                Successor::NonDetChoice(..) => {
+                    return unsupported(format!("non-deterministic choice in {}", current));
+                }
+            };
+        }
+        Err(InterpreterError::StepLimitExceeded)
+    }
+}
+
+struct State {
+    environment: Environment,
+    /// The environments at the old labels.
+    old_environments: BTreeMap<String, Environment>,
+}
+
+impl State {
+    fn execute(&mut self, statement: &Statement) -> InterpreterResult<()> {
+        match statement {
+            Statement::Comment(_) => {}
+            Statement::OldLabel(statement) => {
+                self.old_environments.insert(statement.name.clone(), self.environment.clone());
+            }
+            Statement::Assume(statement) => {
+                if !self.evaluate_bool(&statement.expression)? {
+                    return Err(InterpreterError::AssumptionFailed(
+                        statement.expression.to_string(),
+                    ));
+                }
+            }
+            Statement::Assert(statement) => {
+                if !self.evaluate_bool(&statement.expression)? {
+                    return Err(InterpreterError::AssertionFailed(
+                        statement.expression.to_string(),
+                    ));
+                }
+            }
+            Statement::GhostHavoc(statement) => {
+                self.environment.remove(&statement.variable.name);
+            }
+            Statement::GhostAssign(statement) => {
+                let Expression::Local(target) = &statement.target else {
+                    return unsupported(statement);
+                };
+                let value = self.evaluate(&statement.value)?;
+                self.environment.insert(target.variable.name.clone(), value);
+            }
+            _ => return unsupported(statement),
+        }
+        Ok(())
+    }
+
+    fn evaluate_bool(&self, expression: &Expression) -> InterpreterResult<bool> {
+        match self.evaluate(expression)? {
+            Value::Bool(value) => Ok(value),
+            Value::Int(_) => Err(InterpreterError::TypeMismatch(expression.to_string())),
+        }
+    }
+
+    fn evaluate_int(&self, expression: &Expression) -> InterpreterResult<i128> {
+        match self.evaluate(expression)? {
+            Value::Int(value) => Ok(value),
+            Value::Bool(_) => Err(InterpreterError::TypeMismatch(expression.to_string())),
+        }
+    }
+
+    fn evaluate(&self, expression: &Expression) -> InterpreterResult<Value> {
+        match expression {
+            Expression::Local(local) => self
+                .environment
+                .get(&local.variable.name)
+                .copied()
+                .ok_or_else(|| InterpreterError::UninitializedVariable(local.to_string())),
+            Expression::Constant(constant) => match &constant.value {
+                ConstantValue::Bool(value) => Ok(Value::Bool(*value)),
+                ConstantValue::Int(value) => Ok(Value::Int((*value).into())),
+                ConstantValue::BigInt(value) => match value.parse() {
+                    Ok(value) => Ok(Value::Int(value)),
+                    Err(_) => unsupported(expression),
+                },
+                _ => unsupported(expression),
+            },
+            Expression::UnaryOp(unary_op) => match unary_op.op_kind {
+                UnaryOpKind::Not => Ok(Value::Bool(!self.evaluate_bool(&unary_op.argument)?)),
+                UnaryOpKind::Minus => {
+                    let value = self.evaluate_int(&unary_op.argument)?;
+                    check_range(value.checked_neg(), unary_op.argument.get_type(), expression)
+                }
+            },
+            Expression::BinaryOp(binary_op) => {
+                let left = &binary_op.left;
+                let right = &binary_op.right;
+                match binary_op.op_kind {
+                    // The logical operators are short-circuiting because the
+                    // right operand may be well-defined only if the left one
+                    // determines the result.
+                    BinaryOpKind::And => Ok(Value::Bool(
+                        self.evaluate_bool(left)? && self.evaluate_bool(right)?,
+                    )),
+                    BinaryOpKind::Or => Ok(Value::Bool(
+                        self.evaluate_bool(left)? || self.evaluate_bool(right)?,
+                    )),
+                    BinaryOpKind::Implies => Ok(Value::Bool(
+                        !self.evaluate_bool(left)? || self.evaluate_bool(right)?,
+                    )),
+                    BinaryOpKind::EqCmp | BinaryOpKind::NeCmp => {
+                        let left_value = self.evaluate(left)?;
+                        let right_value = self.evaluate(right)?;
+                        if std::mem::discriminant(&left_value)
+                            != std::mem::discriminant(&right_value)
+                        {
+                            return Err(InterpreterError::TypeMismatch(expression.to_string()));
+                        }
+                        let equal = left_value == right_value;
+                        Ok(Value::Bool(if binary_op.op_kind == BinaryOpKind::EqCmp {
+                            equal
+                        } else {
+                            !equal
+                        }))
+                    }
+                    BinaryOpKind::LifetimeIntersection => unsupported(expression),
+                    op_kind => {
+                        let left_value = self.evaluate_int(left)?;
+                        let right_value = self.evaluate_int(right)?;
+                        let ty = left.get_type();
+                        match op_kind {
+                            BinaryOpKind::GtCmp => Ok(Value::Bool(left_value > right_value)),
+                            BinaryOpKind::GeCmp => Ok(Value::Bool(left_value >= right_value)),
+                            BinaryOpKind::LtCmp => Ok(Value::Bool(left_value < right_value)),
+                            BinaryOpKind::LeCmp => Ok(Value::Bool(left_value <= right_value)),
+                            BinaryOpKind::Add => {
+                                check_range(left_value.checked_add(right_value), ty, expression)
+                            }
+                            BinaryOpKind::Sub => {
+                                check_range(left_value.checked_sub(right_value), ty, expression)
+                            }
+                            BinaryOpKind::Mul => {
+                                check_range(left_value.checked_mul(right_value), ty, expression)
+                            }
+                            BinaryOpKind::Div | BinaryOpKind::Mod if right_value == 0 => {
+                                Err(InterpreterError::DivisionByZero(expression.to_string()))
+                            }
+                            BinaryOpKind::Div => {
+                                check_range(left_value.checked_div(right_value), ty, expression)
+                            }
+                            BinaryOpKind::Mod => {
+                                check_range(left_value.checked_rem(right_value), ty, expression)
+                            }
+                            _ => unreachable!(),
+                        }
+                    }
+                }
+            }
+            Expression::Conditional(conditional) => {
+                if self.evaluate_bool(&conditional.guard)? {
+                    self.evaluate(&conditional.then_expr)
+                } else {
+                    self.evaluate(&conditional.else_expr)
+                }
+            }
+            Expression::LabelledOld(labelled_old) => {
+                let Some(environment) = self.old_environments.get(&labelled_old.label) else {
+                    return unsupported(format!("unknown old label {}", labelled_old.label));
+                };
+                State {
+                    environment: environment.clone(),
+                    old_environments: self.old_environments.clone(),
+                }
+                .evaluate(&labelled_old.base)
+            }
+            _ => unsupported(expression),
+        }
+    }
+}
+
+/// Checks that the result of an arithmetic operation on values of the given
+/// type fits into the type.
+fn check_range(
+    value: Option<i128>,
+    ty: &Type,
+    expression: &Expression,
+) -> InterpreterResult<Value> {
+    let Type::Int(int) = ty else {
+        return Err(InterpreterError::TypeMismatch(expression.to_string()));
+    };
+    let (min, max): (i128, i128) = match int {
+        Int::I8 => (i8::MIN.into(), i8::MAX.into()),
+        Int::I16 => (i16::MIN.into(), i16::MAX.into()),
+        Int::I32 => (i32::MIN.into(), i32::MAX.into()),
+        Int::I64 | Int::Isize => (i64::MIN.into(), i64::MAX.into()),
+        Int::I128 => (i128::MIN, i128::MAX),
+        Int::U8 => (0, u8::MAX.into()),
+        Int::U16 => (0, u16::MAX.into()),
+        Int::U32 => (0, u32::MAX.into()),
+        Int::U64 | Int::Usize => (0, u64::MAX.into()),
+        Int::Char => (0, char::MAX as i128),
+        Int::U128 => (0, i128::MAX),
+        Int::Unbounded => (i128::MIN, i128::MAX),
+    };
+    match value {
+        Some(value) if min <= value && value <= max => Ok(Value::Int(value)),
+        Some(_) => Err(InterpreterError::Overflow(expression.to_string())),
+        // The result does not fit into the representation of the interpreter.
+        None if *int == Int::I128 => Err(InterpreterError::Overflow(expression.to_string())),
+        None => unsupported(expression),
+    }
+}
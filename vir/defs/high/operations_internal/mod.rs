@@ -1,9 +1,11 @@
 pub mod const_generics;
 pub mod constants;
+pub mod diff;
 pub mod expression;
 pub mod function;
 pub mod graphviz;
 pub mod helpers;
+pub mod interpreter;
 pub mod identifier;
 pub mod lifetimes;
 pub mod position;
@@ -13,6 +15,8 @@ pub mod rvalue;
 pub mod special_variables;
 pub mod statement;
 pub mod successor;
+pub mod text;
 pub mod ty;
 pub mod type_decl;
+pub mod validation;
 pub mod variable;
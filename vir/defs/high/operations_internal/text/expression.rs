@@ -0,0 +1,347 @@
+use super::super::super::ast::{
+    expression::{
+        BinaryOp, BinaryOpKind, Conditional, Constant, ConstantValue, Expression, LabelledOld,
+        Local, UnaryOp, UnaryOpKind,
+    },
+    position::Position,
+    ty::{Int, Type},
+    variable::VariableDecl,
+};
+use crate::common::text::{unsupported, TextResult, Token, TokenStream};
+use std::collections::BTreeMap;
+
+const INT_TYPES: &[(&str, Int)] = &[
+    ("i8", Int::I8),
+    ("i16", Int::I16),
+    ("i32", Int::I32),
+    ("i64", Int::I64),
+    ("i128", Int::I128),
+    ("isize", Int::Isize),
+    ("u8", Int::U8),
+    ("u16", Int::U16),
+    ("u32", Int::U32),
+    ("u64", Int::U64),
+    ("u128", Int::U128),
+    ("usize", Int::Usize),
+    ("char", Int::Char),
+    ("int", Int::Unbounded),
+];
+
+const OTHER_TYPES: &[(&str, Type)] = &[
+    ("bool", Type::Bool),
+    ("mbool", Type::MBool),
+    ("mint", Type::MInt),
+    ("mperm", Type::MPerm),
+    ("lifetime", Type::Lifetime),
+];
+
+const BINARY_OPERATORS: &[(&str, BinaryOpKind)] = &[
+    ("==", BinaryOpKind::EqCmp),
+    ("!=", BinaryOpKind::NeCmp),
+    (">", BinaryOpKind::GtCmp),
+    (">=", BinaryOpKind::GeCmp),
+    ("<", BinaryOpKind::LtCmp),
+    ("<=", BinaryOpKind::LeCmp),
+    ("+", BinaryOpKind::Add),
+    ("-", BinaryOpKind::Sub),
+    ("*", BinaryOpKind::Mul),
+    ("/", BinaryOpKind::Div),
+    ("%", BinaryOpKind::Mod),
+    ("&&", BinaryOpKind::And),
+    ("||", BinaryOpKind::Or),
+    ("==>", BinaryOpKind::Implies),
+];
+
+/// The variables declared by the procedure.
+pub(super) type Variables = BTreeMap<String, Type>;
+
+fn int_name(int: Int) -> &'static str {
+    INT_TYPES
+        .iter()
+        .find(|(_, candidate)| *candidate == int)
+        .map(|(name, _)| *name)
+        .unwrap()
+}
+
+/// Whether the constants of the given type are stored as `BigInt` (this
+/// matches the `From` implementations in `constants.rs`).
+fn uses_big_int(int: Int) -> bool {
+    matches!(
+        int,
+        Int::I128 | Int::Isize | Int::U64 | Int::U128 | Int::Usize | Int::Char
+    )
+}
+
+pub(super) fn is_identifier(value: &str) -> bool {
+    value != "true"
+        && value != "false"
+        && value.chars().next().map_or(false, |c| !c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+pub(super) fn print_identifier(value: &str) -> TextResult<String> {
+    if is_identifier(value) {
+        Ok(value.to_string())
+    } else {
+        unsupported(format!("identifier `{}`", value))
+    }
+}
+
+pub(super) fn print_type(ty: &Type) -> TextResult<String> {
+    if let Type::Int(int) = ty {
+        return Ok(int_name(*int).to_string());
+    }
+    if let Some((name, _)) = OTHER_TYPES.iter().find(|(_, candidate)| candidate == ty) {
+        return Ok(name.to_string());
+    }
+    unsupported(format!("type {}", ty))
+}
+
+pub(super) fn parse_type(tokens: &mut TokenStream) -> TextResult<Type> {
+    let name = tokens.expect_identifier()?;
+    if let Some((_, int)) = INT_TYPES.iter().find(|(candidate, _)| *candidate == name) {
+        return Ok(Type::Int(*int));
+    }
+    if let Some((_, ty)) = OTHER_TYPES.iter().find(|(candidate, _)| *candidate == name) {
+        return Ok(ty.clone());
+    }
+    tokens.error(format!("unknown type `{}`", name))
+}
+
+pub(super) fn print_expression(expression: &Expression) -> TextResult<String> {
+    let text = match expression {
+        Expression::Local(local) => print_identifier(&local.variable.name)?,
+        Expression::Constant(constant) => print_constant(constant)?,
+        Expression::UnaryOp(unary_op) => {
+            let operator = match unary_op.op_kind {
+                UnaryOpKind::Not => "!",
+                UnaryOpKind::Minus => "-",
+            };
+            let argument = print_expression(&unary_op.argument)?;
+            if unary_op.argument.is_constant() {
+                // Otherwise, `-(5)` would be read back as the constant `-5`.
+                format!("{}({})", operator, argument)
+            } else {
+                format!("{}{}", operator, argument)
+            }
+        }
+        Expression::BinaryOp(binary_op) => {
+            let Some((operator, _)) = BINARY_OPERATORS
+                .iter()
+                .find(|(_, kind)| *kind == binary_op.op_kind) else {
+                return unsupported(expression);
+            };
+            format!(
+                "({} {} {})",
+                print_expression(&binary_op.left)?,
+                operator,
+                print_expression(&binary_op.right)?
+            )
+        }
+        Expression::Conditional(conditional) => format!(
+            "({} ? {} : {})",
+            print_expression(&conditional.guard)?,
+            print_expression(&conditional.then_expr)?,
+            print_expression(&conditional.else_expr)?
+        ),
+        Expression::LabelledOld(labelled_old) => format!(
+            "old[{}]({})",
+            print_identifier(&labelled_old.label)?,
+            print_expression(&labelled_old.base)?
+        ),
+        _ => return unsupported(expression),
+    };
+    Ok(text)
+}
+
+fn print_constant(constant: &Constant) -> TextResult<String> {
+    match (&constant.value, &constant.ty) {
+        (ConstantValue::Bool(value), Type::Bool) => Ok(value.to_string()),
+        (ConstantValue::Int(value), Type::Int(int)) if !uses_big_int(*int) => {
+            Ok(format!("{}{}", value, int_name(*int)))
+        }
+        (ConstantValue::BigInt(value), Type::Int(int))
+            if uses_big_int(*int)
+                || (*int == Int::Unbounded && value.parse::<i64>().is_err()) =>
+        {
+            Ok(format!("{}{}", value, int_name(*int)))
+        }
+        _ => unsupported(format!("constant {} of type {}", constant, constant.ty)),
+    }
+}
+
+pub(super) fn parse_expression(
+    tokens: &mut TokenStream,
+    variables: &Variables,
+) -> TextResult<Expression> {
+    let left = parse_binary(tokens, variables, 0)?;
+    if tokens.accept("==>") {
+        // Implication is right-associative.
+        let right = parse_expression(tokens, variables)?;
+        Ok(binary_op(BinaryOpKind::Implies, left, right))
+    } else {
+        Ok(left)
+    }
+}
+
+/// The binary operators (except implication) grouped by their precedence
+/// starting with the lowest.
+const PRECEDENCE_LEVELS: &[&[&str]] = &[
+    &["||"],
+    &["&&"],
+    &["==", "!=", "<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+fn parse_binary(
+    tokens: &mut TokenStream,
+    variables: &Variables,
+    level: usize,
+) -> TextResult<Expression> {
+    if level == PRECEDENCE_LEVELS.len() {
+        return parse_unary(tokens, variables);
+    }
+    let mut left = parse_binary(tokens, variables, level + 1)?;
+    loop {
+        let Some(&operator) = PRECEDENCE_LEVELS[level]
+            .iter()
+            .find(|operator| tokens.peek().map_or(false, |token| token.is(operator))) else {
+            return Ok(left);
+        };
+        tokens.next()?;
+        let kind = BINARY_OPERATORS
+            .iter()
+            .find(|(candidate, _)| *candidate == operator)
+            .unwrap()
+            .1;
+        let right = parse_binary(tokens, variables, level + 1)?;
+        left = binary_op(kind, left, right);
+    }
+}
+
+fn parse_unary(tokens: &mut TokenStream, variables: &Variables) -> TextResult<Expression> {
+    if tokens.accept("!") {
+        let argument = parse_unary(tokens, variables)?;
+        Ok(unary_op(UnaryOpKind::Not, argument))
+    } else if tokens.accept("-") {
+        if let Some(Token::Integer { .. }) = tokens.peek() {
+            parse_integer(tokens, true)
+        } else {
+            let argument = parse_unary(tokens, variables)?;
+            Ok(unary_op(UnaryOpKind::Minus, argument))
+        }
+    } else {
+        parse_primary(tokens, variables)
+    }
+}
+
+fn parse_primary(tokens: &mut TokenStream, variables: &Variables) -> TextResult<Expression> {
+    if tokens.accept("(") {
+        let expression = parse_expression(tokens, variables)?;
+        if tokens.accept("?") {
+            let then_expr = parse_expression(tokens, variables)?;
+            tokens.expect(":")?;
+            let else_expr = parse_expression(tokens, variables)?;
+            tokens.expect(")")?;
+            return Ok(Expression::Conditional(Conditional {
+                guard: Box::new(expression),
+                then_expr: Box::new(then_expr),
+                else_expr: Box::new(else_expr),
+                position: Position::default(),
+            }));
+        }
+        tokens.expect(")")?;
+        return Ok(expression);
+    }
+    if let Some(Token::Integer { .. }) = tokens.peek() {
+        return parse_integer(tokens, false);
+    }
+    if tokens.peek().map_or(false, |token| token.is("old"))
+        && tokens.peek_nth(1).map_or(false, |token| token.is("["))
+    {
+        tokens.next()?;
+        tokens.expect("[")?;
+        let label = tokens.expect_identifier()?;
+        tokens.expect("]")?;
+        tokens.expect("(")?;
+        let base = parse_expression(tokens, variables)?;
+        tokens.expect(")")?;
+        return Ok(Expression::LabelledOld(LabelledOld {
+            label,
+            base: Box::new(base),
+            position: Position::default(),
+        }));
+    }
+    let name = tokens.expect_identifier()?;
+    match name.as_str() {
+        "true" | "false" => Ok(Expression::Constant(Constant {
+            value: ConstantValue::Bool(name == "true"),
+            ty: Type::Bool,
+            position: Position::default(),
+        })),
+        _ => {
+            let Some(ty) = variables.get(&name) else {
+                return tokens.error(format!("undeclared variable `{}`", name));
+            };
+            Ok(Expression::Local(Local {
+                variable: VariableDecl {
+                    name,
+                    ty: ty.clone(),
+                },
+                position: Position::default(),
+            }))
+        }
+    }
+}
+
+fn parse_integer(tokens: &mut TokenStream, is_negative: bool) -> TextResult<Expression> {
+    let Token::Integer { value, suffix } = tokens.next()? else {
+        unreachable!();
+    };
+    let value = if is_negative {
+        format!("-{}", value)
+    } else {
+        value
+    };
+    let int = match suffix {
+        Some(suffix) => {
+            let Some((_, int)) = INT_TYPES.iter().find(|(name, _)| *name == suffix) else {
+                return tokens.error(format!("unknown integer type `{}`", suffix));
+            };
+            *int
+        }
+        None => Int::Unbounded,
+    };
+    let value = match value.parse::<i64>() {
+        Ok(value) if !uses_big_int(int) => ConstantValue::Int(value),
+        Err(_) if !uses_big_int(int) && int != Int::Unbounded => {
+            return tokens.error(format!("the constant {} does not fit into i64", value));
+        }
+        _ => ConstantValue::BigInt(value),
+    };
+    Ok(Expression::Constant(Constant {
+        value,
+        ty: Type::Int(int),
+        position: Position::default(),
+    }))
+}
+
+fn unary_op(op_kind: UnaryOpKind, argument: Expression) -> Expression {
+    Expression::UnaryOp(UnaryOp {
+        op_kind,
+        argument: Box::new(argument),
+        position: Position::default(),
+    })
+}
+
+fn binary_op(op_kind: BinaryOpKind, left: Expression, right: Expression) -> Expression {
+    Expression::BinaryOp(BinaryOp {
+        op_kind,
+        left: Box::new(left),
+        right: Box::new(right),
+        position: Position::default(),
+    })
+}
@@ -0,0 +1,6 @@
+//! Printing and parsing of procedures in the textual format described in
+//! `crate::common::text`.
+
+pub mod expression;
+pub mod procedure;
+mod statement;
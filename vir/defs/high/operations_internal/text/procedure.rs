@@ -0,0 +1,209 @@
+use super::{
+    super::super::{
+        ast::{
+            expression::visitors::ExpressionWalker,
+            statement::Statement,
+            ty::Type,
+            variable::VariableDecl,
+        },
+        cfg::procedure::{BasicBlock, BasicBlockId, ProcedureDecl, Successor},
+    },
+    expression::{parse_expression, parse_type, print_expression, print_identifier, print_type},
+    statement::{parse_statement, print_statement},
+};
+use crate::common::{
+    check_mode::CheckMode,
+    text::{quote, unsupported, TextError, TextResult, TokenStream},
+};
+use std::{collections::BTreeMap, fmt::Write};
+
+const SUCCESSOR_KEYWORDS: &[&str] = &["goto", "switch", "nondet", "return"];
+
+impl ProcedureDecl {
+    /// Prints the procedure in the textual format described in
+    /// `crate::common::text`. Fails if the procedure uses a construct that is
+    /// not supported by the format.
+    pub fn to_text(&self) -> TextResult<String> {
+        let mut text = String::new();
+        writeln!(text, "procedure {} {} {{", self.check_mode, quote(&self.name)).unwrap();
+        for (name, ty) in collect_variables(self)? {
+            writeln!(text, "    var {}: {};", print_identifier(&name)?, print_type(&ty)?).unwrap();
+        }
+        writeln!(text, "    entry {};", print_label(&self.entry)?).unwrap();
+        writeln!(text, "    exit {};", print_label(&self.exit)?).unwrap();
+        for (label, block) in &self.basic_blocks {
+            writeln!(text, "    {}: {{", print_label(label)?).unwrap();
+            for statement in &block.statements {
+                writeln!(text, "        {}", print_statement(statement)?).unwrap();
+            }
+            match &block.successor {
+                Successor::Exit => writeln!(text, "        return;").unwrap(),
+                Successor::Goto(target) => {
+                    writeln!(text, "        goto {};", print_label(target)?).unwrap()
+                }
+                Successor::GotoSwitch(targets) => {
+                    writeln!(text, "        switch {{").unwrap();
+                    for (test, target) in targets {
+                        writeln!(
+                            text,
+                            "            {} => {},",
+                            print_expression(test)?,
+                            print_label(target)?
+                        )
+                        .unwrap();
+                    }
+                    writeln!(text, "        }}").unwrap();
+                }
+                Successor::NonDetChoice(first, second) => writeln!(
+                    text,
+                    "        nondet {}, {};",
+                    print_label(first)?,
+                    print_label(second)?
+                )
+                .unwrap(),
+            }
+            writeln!(text, "    }}").unwrap();
+        }
+        writeln!(text, "}}").unwrap();
+        Ok(text)
+    }
+
+    /// Parses a procedure printed by `to_text` or written by hand. All
+    /// positions of the parsed procedure are default positions.
+    pub fn from_text(text: &str) -> TextResult<Self> {
+        let mut tokens = TokenStream::new(text)?;
+        tokens.expect("procedure")?;
+        let check_mode = match tokens.expect_identifier()?.as_str() {
+            "CoreProof" => CheckMode::CoreProof,
+            "Specifications" => CheckMode::Specifications,
+            "Both" => CheckMode::Both,
+            check_mode => return tokens.error(format!("unknown check mode `{}`", check_mode)),
+        };
+        let name = tokens.expect_string()?;
+        tokens.expect("{")?;
+        let mut variables = BTreeMap::new();
+        while tokens.accept("var") {
+            let name = tokens.expect_identifier()?;
+            tokens.expect(":")?;
+            let ty = parse_type(&mut tokens)?;
+            tokens.expect(";")?;
+            if variables.insert(name.clone(), ty).is_some() {
+                return tokens.error(format!("variable `{}` is declared twice", name));
+            }
+        }
+        tokens.expect("entry")?;
+        let entry = BasicBlockId::new(tokens.expect_identifier()?);
+        tokens.expect(";")?;
+        tokens.expect("exit")?;
+        let exit = BasicBlockId::new(tokens.expect_identifier()?);
+        tokens.expect(";")?;
+        let mut basic_blocks = BTreeMap::new();
+        while !tokens.accept("}") {
+            let label = BasicBlockId::new(tokens.expect_identifier()?);
+            tokens.expect(":")?;
+            tokens.expect("{")?;
+            let mut statements = Vec::new();
+            while !SUCCESSOR_KEYWORDS
+                .iter()
+                .any(|keyword| tokens.peek().map_or(false, |token| token.is(keyword)))
+            {
+                statements.push(parse_statement(&mut tokens, &variables)?);
+            }
+            let successor = match tokens.expect_identifier()?.as_str() {
+                "return" => Successor::Exit,
+                "goto" => Successor::Goto(BasicBlockId::new(tokens.expect_identifier()?)),
+                "nondet" => {
+                    let first = BasicBlockId::new(tokens.expect_identifier()?);
+                    tokens.expect(",")?;
+                    let second = BasicBlockId::new(tokens.expect_identifier()?);
+                    Successor::NonDetChoice(first, second)
+                }
+                "switch" => {
+                    tokens.expect("{")?;
+                    let mut targets = Vec::new();
+                    while !tokens.accept("}") {
+                        let test = parse_expression(&mut tokens, &variables)?;
+                        tokens.expect("=>")?;
+                        let target = BasicBlockId::new(tokens.expect_identifier()?);
+                        targets.push((test, target));
+                        if !tokens.accept(",") {
+                            tokens.expect("}")?;
+                            break;
+                        }
+                    }
+                    Successor::GotoSwitch(targets)
+                }
+                _ => unreachable!(),
+            };
+            if !matches!(successor, Successor::GotoSwitch(_)) {
+                tokens.expect(";")?;
+            }
+            tokens.expect("}")?;
+            let block = BasicBlock {
+                statements,
+                successor,
+            };
+            if basic_blocks.insert(label.clone(), block).is_some() {
+                return tokens.error(format!("block `{}` is defined twice", label));
+            }
+        }
+        if !tokens.is_at_end() {
+            return tokens.error("expected the end of input");
+        }
+        Ok(ProcedureDecl {
+            name,
+            check_mode,
+            entry,
+            exit,
+            basic_blocks,
+        })
+    }
+}
+
+fn print_label(label: &BasicBlockId) -> TextResult<String> {
+    print_identifier(&label.name)
+}
+
+/// Collects the variables used by the procedure and checks that all their
+/// uses agree on the type.
+fn collect_variables(procedure: &ProcedureDecl) -> TextResult<BTreeMap<String, Type>> {
+    #[derive(Default)]
+    struct Collector {
+        variables: BTreeMap<String, Type>,
+        error: Option<TextError>,
+    }
+    impl Collector {
+        fn add(&mut self, variable: &VariableDecl) {
+            if let Some(ty) = self.variables.get(&variable.name) {
+                if ty != &variable.ty && self.error.is_none() {
+                    self.error = unsupported::<()>(format!(
+                        "variable `{}` used with types {} and {}",
+                        variable.name, ty, variable.ty
+                    ))
+                    .err();
+                }
+            } else {
+                self.variables.insert(variable.name.clone(), variable.ty.clone());
+            }
+        }
+    }
+    impl ExpressionWalker for Collector {
+        fn walk_variable_decl(&mut self, variable: &VariableDecl) {
+            self.add(variable);
+        }
+    }
+    let mut collector = Collector::default();
+    procedure.walk_expressions(&mut collector);
+    for block in procedure.basic_blocks.values() {
+        for statement in &block.statements {
+            if let Statement::GhostHavoc(statement) = statement {
+                collector.add(&statement.variable);
+            }
+        }
+    }
+    if let Some(error) = collector.error {
+        Err(error)
+    } else {
+        Ok(collector.variables)
+    }
+}
@@ -0,0 +1,88 @@
+use super::{
+    super::super::ast::{
+        position::Position,
+        statement::{Assert, Assume, Comment, GhostAssign, GhostHavoc, OldLabel, Statement},
+        variable::VariableDecl,
+    },
+    expression::{parse_expression, print_expression, print_identifier, Variables},
+};
+use crate::common::text::{unsupported, TextResult, Token, TokenStream};
+
+pub(super) fn print_statement(statement: &Statement) -> TextResult<String> {
+    let text = match statement {
+        Statement::Comment(statement) if !statement.comment.contains('\n') => {
+            format!("// {}", statement.comment)
+        }
+        Statement::OldLabel(statement) => {
+            format!("old_label {};", print_identifier(&statement.name)?)
+        }
+        Statement::Assume(statement) => {
+            format!("assume {};", print_expression(&statement.expression)?)
+        }
+        Statement::Assert(statement) => {
+            format!("assert {};", print_expression(&statement.expression)?)
+        }
+        Statement::GhostHavoc(statement) => {
+            format!("ghost_havoc {};", print_identifier(&statement.variable.name)?)
+        }
+        Statement::GhostAssign(statement) => format!(
+            "ghost_assign {} := {};",
+            print_expression(&statement.target)?,
+            print_expression(&statement.value)?
+        ),
+        _ => return unsupported(format!("statement `{}`", statement)),
+    };
+    Ok(text)
+}
+
+pub(super) fn parse_statement(
+    tokens: &mut TokenStream,
+    variables: &Variables,
+) -> TextResult<Statement> {
+    if let Some(Token::Comment(comment)) = tokens.peek() {
+        let comment = comment.clone();
+        tokens.next()?;
+        return Ok(Statement::Comment(Comment { comment }));
+    }
+    let keyword = tokens.expect_identifier()?;
+    let position = Position::default();
+    let statement = match keyword.as_str() {
+        "old_label" => Statement::OldLabel(OldLabel {
+            name: tokens.expect_identifier()?,
+            position,
+        }),
+        "assume" => Statement::Assume(Assume {
+            expression: parse_expression(tokens, variables)?,
+            position,
+        }),
+        "assert" => Statement::Assert(Assert {
+            expression: parse_expression(tokens, variables)?,
+            position,
+        }),
+        "ghost_havoc" => {
+            let name = tokens.expect_identifier()?;
+            let Some(ty) = variables.get(&name) else {
+                return tokens.error(format!("undeclared variable `{}`", name));
+            };
+            Statement::GhostHavoc(GhostHavoc {
+                variable: VariableDecl {
+                    name,
+                    ty: ty.clone(),
+                },
+                position,
+            })
+        }
+        "ghost_assign" => {
+            let target = parse_expression(tokens, variables)?;
+            tokens.expect(":=")?;
+            Statement::GhostAssign(GhostAssign {
+                target,
+                value: parse_expression(tokens, variables)?,
+                position,
+            })
+        }
+        _ => return tokens.error(format!("unknown statement `{}`", keyword)),
+    };
+    tokens.expect(";")?;
+    Ok(statement)
+}
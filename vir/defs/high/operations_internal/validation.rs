@@ -0,0 +1,189 @@
+use super::{
+    super::{
+        ast::{
+            expression::{
+                visitors::{default_walk_expression, ExpressionWalker},
+                BinaryOpKind, Expression, LetExpr, Quantifier,
+            },
+            predicate::{visitors::PredicateWalker, Predicate},
+            statement::{visitors::StatementWalker, Statement},
+            ty::Type,
+            variable::VariableDecl,
+        },
+        cfg::procedure::{BasicBlockId, ProcedureDecl, Successor},
+    },
+    ty::Typed,
+};
+use crate::common::position::Positioned;
+use std::collections::{BTreeMap, BTreeSet};
+
+impl ProcedureDecl {
+    /// Checks that the procedure is well-formed and panics with a description
+    /// of the problem and its location otherwise. The following properties
+    /// are checked:
+    ///
+    /// 1.  All labels used by successors refer to existing blocks.
+    /// 2.  All statements (except comments and similar) have positions.
+    /// 3.  The operands of comparisons and of the conditional expression have
+    ///     the same types and the operands of logical operators are booleans.
+    /// 4.  All uses of a variable (that is not bound by a quantifier or a let
+    ///     expression) agree on its type.
+    ///
+    /// The check is meant for catching bugs in the lowerings early; it is not
+    /// a full type checker.
+    pub fn assert_well_formed(&self) {
+        let mut errors = Vec::new();
+        let mut check_label = |label: &BasicBlockId, location: &str| {
+            if !self.basic_blocks.contains_key(label) {
+                errors.push(format!("{}: unknown label {}", location, label));
+            }
+        };
+        check_label(&self.entry, "entry");
+        check_label(&self.exit, "exit");
+        for (label, block) in &self.basic_blocks {
+            for target in block.successor.get_following() {
+                check_label(target, &format!("successor of {}", label));
+            }
+        }
+        let mut validator = Validator::default();
+        for (label, block) in &self.basic_blocks {
+            for (index, statement) in block.statements.iter().enumerate() {
+                validator.location = format!("{}[{}] `{}`", label, index, statement);
+                if !statement.may_have_default_position() && statement.position().is_default() {
+                    validator.report("the statement has no position".to_string());
+                }
+                validator.walk_statement(statement);
+            }
+            if let Successor::GotoSwitch(targets) = &block.successor {
+                validator.location = format!("successor of {}", label);
+                for (test, _) in targets {
+                    ExpressionWalker::walk_expression(&mut validator, test);
+                }
+            }
+        }
+        errors.extend(validator.errors);
+        assert!(
+            errors.is_empty(),
+            "the procedure {} is not well-formed:\n  {}\n{}",
+            self.name,
+            errors.join("\n  "),
+            self
+        );
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    /// The description of the currently checked statement.
+    location: String,
+    /// The types of the free variables seen so far.
+    variables: BTreeMap<String, Type>,
+    bound_variables: Vec<BTreeSet<String>>,
+    errors: Vec<String>,
+}
+
+impl Validator {
+    fn report(&mut self, message: String) {
+        self.errors.push(format!("{}: {}", self.location, message));
+    }
+    fn check_same_types(&mut self, expression: &Expression, left: &Expression, right: &Expression) {
+        if left.get_type().erase_lifetimes() != right.get_type().erase_lifetimes() {
+            self.report(format!(
+                "the operands of `{}` have different types: {} and {}",
+                expression,
+                left.get_type(),
+                right.get_type()
+            ));
+        }
+    }
+    fn check_bool(&mut self, expression: &Expression, operand: &Expression) {
+        if !operand.get_type().is_bool() {
+            self.report(format!(
+                "the operand `{}` of `{}` has type {} instead of Bool",
+                operand,
+                expression,
+                operand.get_type()
+            ));
+        }
+    }
+}
+
+impl ExpressionWalker for Validator {
+    fn walk_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::BinaryOp(binary_op) => match binary_op.op_kind {
+                BinaryOpKind::EqCmp
+                | BinaryOpKind::NeCmp
+                | BinaryOpKind::GtCmp
+                | BinaryOpKind::GeCmp
+                | BinaryOpKind::LtCmp
+                | BinaryOpKind::LeCmp => {
+                    self.check_same_types(expression, &binary_op.left, &binary_op.right);
+                }
+                BinaryOpKind::And | BinaryOpKind::Or | BinaryOpKind::Implies => {
+                    self.check_bool(expression, &binary_op.left);
+                    self.check_bool(expression, &binary_op.right);
+                }
+                _ => {}
+            },
+            Expression::Conditional(conditional) => {
+                self.check_bool(expression, &conditional.guard);
+                self.check_same_types(expression, &conditional.then_expr, &conditional.else_expr);
+            }
+            _ => {}
+        }
+        default_walk_expression(self, expression)
+    }
+    fn walk_variable_decl(&mut self, variable: &VariableDecl) {
+        if self.bound_variables.iter().any(|set| set.contains(&variable.name)) {
+            return;
+        }
+        let ty = variable.ty.erase_lifetimes();
+        if let Some(known_ty) = self.variables.get(&variable.name) {
+            if known_ty != &ty {
+                let message = format!(
+                    "the variable {} is used with types {} and {}",
+                    variable.name, known_ty, ty
+                );
+                self.report(message);
+            }
+        } else {
+            self.variables.insert(variable.name.clone(), ty);
+        }
+    }
+    fn walk_quantifier(&mut self, quantifier: &Quantifier) {
+        self.bound_variables.push(
+            quantifier
+                .variables
+                .iter()
+                .map(|variable| variable.name.clone())
+                .collect(),
+        );
+        for trigger in &quantifier.triggers {
+            ExpressionWalker::walk_trigger(self, trigger);
+        }
+        ExpressionWalker::walk_expression(self, &quantifier.body);
+        self.bound_variables.pop();
+    }
+    fn walk_let_expr(&mut self, let_expr: &LetExpr) {
+        ExpressionWalker::walk_expression(self, &let_expr.def);
+        self.bound_variables.push(std::iter::once(let_expr.variable.name.clone()).collect());
+        ExpressionWalker::walk_expression(self, &let_expr.body);
+        self.bound_variables.pop();
+    }
+}
+
+impl PredicateWalker for Validator {
+    fn walk_expression(&mut self, expression: &Expression) {
+        ExpressionWalker::walk_expression(self, expression);
+    }
+}
+
+impl StatementWalker for Validator {
+    fn walk_expression(&mut self, expression: &Expression) {
+        ExpressionWalker::walk_expression(self, expression);
+    }
+    fn walk_predicate(&mut self, predicate: &Predicate) {
+        PredicateWalker::walk_predicate(self, predicate);
+    }
+}
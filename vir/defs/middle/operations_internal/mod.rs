@@ -5,13 +5,17 @@ pub mod identifier;
 copy_module!(crate::typed::operations_internal::predicate);
 copy_module!(crate::typed::operations_internal::procedure);
 copy_module!(crate::typed::operations_internal::successor);
+pub mod text;
 copy_module!(crate::typed::operations_internal::ty);
 copy_module!(crate::typed::operations_internal::lifetimes);
 copy_module!(crate::typed::operations_internal::const_generics);
 copy_module!(crate::typed::operations_internal::type_decl);
+copy_module!(crate::typed::operations_internal::validation);
 copy_module!(crate::typed::operations_internal::special_variables);
 copy_module!(crate::typed::operations_internal::rvalue);
 copy_module!(crate::typed::operations_internal::function);
 copy_module!(crate::typed::operations_internal::constants);
+copy_module!(crate::typed::operations_internal::diff);
 copy_module!(crate::typed::operations_internal::helpers);
+copy_module!(crate::typed::operations_internal::interpreter);
 pub mod position;
@@ -1,6 +1,14 @@
 use super::super::super::ast::statement::*;
 use crate::common::position::Positioned;
 
+impl Statement {
+    /// Comments are not related to any source code and, therefore, do not need
+    /// to have a position.
+    pub fn may_have_default_position(&self) -> bool {
+        self.is_comment()
+    }
+}
+
 impl Positioned for Statement {
     fn position(&self) -> Position {
         match self {
@@ -0,0 +1,3 @@
+copy_module!(crate::typed::operations_internal::text::expression);
+copy_module!(crate::typed::operations_internal::text::procedure);
+mod statement;
@@ -0,0 +1,134 @@
+use super::{
+    super::super::{
+        ast::{
+            position::Position,
+            statement::{
+                Assert, Assume, BlockMarkerCondition, BlockMarkerConditionElement, Comment,
+                GhostAssign, GhostHavoc, OldLabel, Statement,
+            },
+            variable::VariableDecl,
+        },
+        cfg::procedure::BasicBlockId,
+    },
+    expression::{parse_expression, print_expression, print_identifier, Variables},
+};
+use crate::common::text::{unsupported, TextResult, Token, TokenStream};
+
+pub(super) fn print_statement(statement: &Statement) -> TextResult<String> {
+    let text = match statement {
+        Statement::Comment(statement) if !statement.comment.contains('\n') => {
+            format!("// {}", statement.comment)
+        }
+        Statement::OldLabel(statement) => {
+            format!("old_label {};", print_identifier(&statement.name)?)
+        }
+        Statement::Assume(statement) => {
+            format!("assume {};", print_expression(&statement.expression)?)
+        }
+        Statement::Assert(statement) => format!(
+            "assert{} {};",
+            print_condition(&statement.condition)?,
+            print_expression(&statement.expression)?
+        ),
+        Statement::GhostHavoc(statement) => {
+            format!("ghost_havoc {};", print_identifier(&statement.variable.name)?)
+        }
+        Statement::GhostAssign(statement) => format!(
+            "ghost_assign {} := {};",
+            print_expression(&statement.target)?,
+            print_expression(&statement.value)?
+        ),
+        _ => return unsupported(format!("statement `{}`", statement)),
+    };
+    Ok(text)
+}
+
+pub(super) fn parse_statement(
+    tokens: &mut TokenStream,
+    variables: &Variables,
+) -> TextResult<Statement> {
+    if let Some(Token::Comment(comment)) = tokens.peek() {
+        let comment = comment.clone();
+        tokens.next()?;
+        return Ok(Statement::Comment(Comment { comment }));
+    }
+    let keyword = tokens.expect_identifier()?;
+    let position = Position::default();
+    let statement = match keyword.as_str() {
+        "old_label" => Statement::OldLabel(OldLabel {
+            name: tokens.expect_identifier()?,
+            position,
+        }),
+        "assume" => Statement::Assume(Assume {
+            expression: parse_expression(tokens, variables)?,
+            position,
+        }),
+        "assert" => Statement::Assert(Assert {
+            condition: parse_condition(tokens)?,
+            expression: parse_expression(tokens, variables)?,
+            position,
+        }),
+        "ghost_havoc" => {
+            let name = tokens.expect_identifier()?;
+            let Some(ty) = variables.get(&name) else {
+                return tokens.error(format!("undeclared variable `{}`", name));
+            };
+            Statement::GhostHavoc(GhostHavoc {
+                variable: VariableDecl {
+                    name,
+                    ty: ty.clone(),
+                },
+                position,
+            })
+        }
+        "ghost_assign" => {
+            let target = parse_expression(tokens, variables)?;
+            tokens.expect(":=")?;
+            Statement::GhostAssign(GhostAssign {
+                target,
+                value: parse_expression(tokens, variables)?,
+                position,
+            })
+        }
+        _ => return tokens.error(format!("unknown statement `{}`", keyword)),
+    };
+    tokens.expect(";")?;
+    Ok(statement)
+}
+
+/// Prints the block marker condition as `<bb1, !bb2>`, where `!` marks the
+/// blocks that must not be visited.
+fn print_condition(condition: &Option<BlockMarkerCondition>) -> TextResult<String> {
+    let Some(condition) = condition else {
+        return Ok(String::new());
+    };
+    let mut elements = Vec::new();
+    for element in &condition.elements {
+        let negation = if element.visited { "" } else { "!" };
+        elements.push(format!(
+            "{}{}",
+            negation,
+            print_identifier(&element.basic_block_id.name)?
+        ));
+    }
+    Ok(format!("<{}>", elements.join(", ")))
+}
+
+fn parse_condition(tokens: &mut TokenStream) -> TextResult<Option<BlockMarkerCondition>> {
+    if !tokens.accept("<") {
+        return Ok(None);
+    }
+    let mut elements = Vec::new();
+    while !tokens.accept(">") {
+        let visited = !tokens.accept("!");
+        elements.push(BlockMarkerConditionElement {
+            basic_block_id: BasicBlockId::new(tokens.expect_identifier()?),
+            visited,
+        });
+        if !tokens.accept(",") {
+            tokens.expect(">")?;
+            break;
+        }
+    }
+    Ok(Some(BlockMarkerCondition { elements }))
+}
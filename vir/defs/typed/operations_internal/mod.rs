@@ -1,8 +1,10 @@
 copy_module!(crate::high::operations_internal::constants);
+copy_module!(crate::high::operations_internal::diff);
 copy_module!(crate::high::operations_internal::expression);
 copy_module!(crate::high::operations_internal::function);
 copy_module!(crate::high::operations_internal::graphviz);
 copy_module!(crate::high::operations_internal::helpers);
+copy_module!(crate::high::operations_internal::interpreter);
 pub mod const_generics;
 pub mod identifier;
 pub mod lifetimes;
@@ -13,6 +15,8 @@ copy_module!(crate::high::operations_internal::rvalue);
 copy_module!(crate::high::operations_internal::special_variables);
 copy_module!(crate::high::operations_internal::statement);
 copy_module!(crate::high::operations_internal::successor);
+copy_module!(crate::high::operations_internal::text);
 pub mod ty;
 pub mod type_decl;
+copy_module!(crate::high::operations_internal::validation);
 copy_module!(crate::high::operations_internal::variable);
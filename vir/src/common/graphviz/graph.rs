@@ -23,6 +23,9 @@ pub(super) enum EdgeKind {
     Normal,
     Unwind,
     Imaginary,
+    /// A normal edge that is drawn more prominently than the others (for
+    /// example, to mark the edges already processed by an algorithm).
+    Highlighted,
 }
 
 pub(super) struct Edge {
@@ -112,6 +115,19 @@ impl Graph {
             kind: EdgeKind::Normal,
         })
     }
+    pub fn add_highlighted_edge(
+        &mut self,
+        source: String,
+        target: String,
+        annotation: Option<String>,
+    ) {
+        self.edges.push(Edge {
+            source,
+            target,
+            annotation,
+            kind: EdgeKind::Highlighted,
+        })
+    }
     pub fn add_unwind_edge(&mut self, source: String, target: String) {
         self.edges.push(Edge {
             source,
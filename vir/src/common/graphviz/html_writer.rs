@@ -0,0 +1,305 @@
+//! An alternative to the `.dot` output that renders the graph as a standalone
+//! HTML page. Graphs of large methods are hard to navigate in the standard
+//! graphviz viewers, therefore, the page lists the nodes as collapsible blocks
+//! linked with their predecessors and successors, and provides searching
+//! statements and toggling columns.
+
+use super::{
+    graph::{Edge, EdgeKind, Graph, Node, Row, Table},
+    helpers::escape_html,
+};
+use std::{collections::BTreeMap, io::Write};
+
+const STYLE: &str = r#"
+body { font-family: monospace; margin: 0; }
+#toolbar {
+  position: sticky; top: 0; z-index: 1;
+  background: #eee; padding: 6px; border-bottom: 1px solid #999;
+}
+#toolbar > * { margin-right: 12px; }
+.node, .table { margin: 8px; border: 1px solid #999; }
+.node > summary { padding: 4px; cursor: pointer; }
+.node > summary > .id { font-weight: bold; }
+.node.exit { padding: 4px; font-weight: bold; }
+.edges { margin-left: 12px; }
+.edge-unwind { color: red; }
+.edge-imaginary { font-style: italic; }
+.edge-highlighted { color: green; font-weight: bold; }
+table { border-collapse: collapse; margin: 4px; }
+th, td { text-align: left; vertical-align: top; padding: 1px 6px; }
+tr.match { background: #ff8; }
+.hidden { display: none; }
+"#;
+
+const SCRIPT: &str = r#"
+function setAllOpen(open) {
+  for (const node of document.querySelectorAll("details.node")) {
+    node.open = open;
+  }
+}
+function toggleColumn(checkbox) {
+  const column = checkbox.dataset.column;
+  for (const cell of document.querySelectorAll(".col" + column)) {
+    cell.classList.toggle("hidden", !checkbox.checked);
+  }
+}
+function search(query) {
+  query = query.toLowerCase();
+  for (const node of document.querySelectorAll("details.node")) {
+    let found = false;
+    for (const row of node.querySelectorAll("tr.statement")) {
+      const matches = query !== "" && row.textContent.toLowerCase().includes(query);
+      row.classList.toggle("match", matches);
+      found = found || matches;
+    }
+    node.classList.toggle("hidden", query !== "" && !found);
+    if (found) {
+      node.open = true;
+    }
+  }
+}
+"#;
+
+/// The escaping done by `escape_html` targets graphviz labels, which require
+/// escaping braces.
+fn unescape_braces(value: &str) -> String {
+    value.replace("\\{", "{").replace("\\}", "}")
+}
+
+fn node_anchor(node_id: &str) -> String {
+    format!("node{}", node_id)
+}
+
+/// Converts the graphviz attributes used for styling node identifiers into
+/// CSS. Only the background color is supported.
+fn node_id_style_to_css(style: &str) -> String {
+    style
+        .split_whitespace()
+        .filter_map(|attribute| attribute.strip_prefix("bgcolor="))
+        .map(|color| format!("background-color: {};", color.trim_matches('"')))
+        .collect()
+}
+
+impl Graph {
+    pub fn write_html(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        let mut successors: BTreeMap<&str, Vec<&Edge>> = BTreeMap::new();
+        let mut predecessors: BTreeMap<&str, Vec<&Edge>> = BTreeMap::new();
+        for edge in &self.edges {
+            successors.entry(&edge.source).or_default().push(edge);
+            predecessors.entry(&edge.target).or_default().push(edge);
+        }
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html>")?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, "<meta charset=\"utf-8\">")?;
+        writeln!(writer, "<title>CFG</title>")?;
+        writeln!(writer, "<style>{}</style>", STYLE)?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, "<div id=\"toolbar\">")?;
+        writeln!(
+            writer,
+            "<input type=\"search\" placeholder=\"Search statements\" \
+             oninput=\"search(this.value)\">"
+        )?;
+        writeln!(writer, "<button onclick=\"setAllOpen(true)\">Expand all</button>")?;
+        writeln!(writer, "<button onclick=\"setAllOpen(false)\">Collapse all</button>")?;
+        write!(writer, "<span>Columns:")?;
+        for (i, name) in self.column_names.iter().enumerate() {
+            write!(
+                writer,
+                " <label><input type=\"checkbox\" checked data-column=\"{}\" \
+                 onchange=\"toggleColumn(this)\">{}</label>",
+                i, name
+            )?;
+        }
+        writeln!(writer, "</span>")?;
+        writeln!(writer, "</div>")?;
+        for table in &self.tables {
+            table.write_html(writer)?;
+        }
+        for node in &self.nodes {
+            node.write_html(
+                &self.column_names,
+                predecessors.get(node.node_id.as_str()),
+                successors.get(node.node_id.as_str()),
+                writer,
+            )?;
+        }
+        for exit_target in &self.exit_targets {
+            writeln!(
+                writer,
+                "<div class=\"node exit\" id=\"{}\">{}</div>",
+                node_anchor(exit_target),
+                exit_target
+            )?;
+        }
+        writeln!(writer, "<script>{}</script>", SCRIPT)?;
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")?;
+        Ok(())
+    }
+}
+
+impl Table {
+    fn write_html(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(writer, "<details class=\"table\" open>")?;
+        writeln!(writer, "<summary>{}</summary>", self.table_name)?;
+        writeln!(writer, "<table>")?;
+        write!(writer, "<tr>")?;
+        for name in &self.header.column_names {
+            write!(writer, "<th>{}</th>", name)?;
+        }
+        writeln!(writer, "</tr>")?;
+        for row in &self.rows {
+            row.write_html(&self.header.column_names, writer)?;
+        }
+        writeln!(writer, "</table>")?;
+        writeln!(writer, "</details>")?;
+        Ok(())
+    }
+}
+
+impl Node {
+    fn write_html(
+        &self,
+        column_names: &[String],
+        predecessors: Option<&Vec<&Edge>>,
+        successors: Option<&Vec<&Edge>>,
+        writer: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        writeln!(writer, "<details class=\"node\" id=\"{}\" open>", node_anchor(&self.node_id))?;
+        write!(
+            writer,
+            "<summary><span class=\"id\" style=\"{}\">{}</span>",
+            node_id_style_to_css(&self.node_id_style),
+            self.node_id
+        )?;
+        if let Some(predecessors) = predecessors {
+            write!(writer, "<span class=\"edges\">from:")?;
+            for edge in predecessors {
+                edge.write_html(&edge.source, writer)?;
+            }
+            write!(writer, "</span>")?;
+        }
+        if let Some(successors) = successors {
+            write!(writer, "<span class=\"edges\">to:")?;
+            for edge in successors {
+                edge.write_html(&edge.target, writer)?;
+            }
+            write!(writer, "</span>")?;
+        }
+        writeln!(writer, "</summary>")?;
+        writeln!(writer, "<table>")?;
+        write!(writer, "<tr>")?;
+        for (i, name) in column_names.iter().enumerate() {
+            write!(writer, "<th class=\"col{}\">{}</th>", i, name)?;
+        }
+        writeln!(writer, "</tr>")?;
+        for row in &self.rows {
+            row.write_html(column_names, writer)?;
+        }
+        writeln!(writer, "</table>")?;
+        writeln!(writer, "</details>")?;
+        Ok(())
+    }
+}
+
+impl Row {
+    fn write_html(&self, column_names: &[String], writer: &mut dyn Write) -> std::io::Result<()> {
+        write!(writer, "<tr class=\"statement\">")?;
+        match self {
+            Row::Single(value) => {
+                write!(
+                    writer,
+                    "<td colspan=\"{}\">{}</td>",
+                    column_names.len(),
+                    unescape_braces(value)
+                )?;
+            }
+            Row::Seq(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    write!(writer, "<td class=\"col{}\">{}</td>", i, unescape_braces(value))?;
+                }
+            }
+            Row::Map(values) => {
+                for (i, column) in column_names.iter().enumerate() {
+                    let value = values.get(column).map(String::as_str).unwrap_or("n/a");
+                    write!(writer, "<td class=\"col{}\">{}</td>", i, unescape_braces(value))?;
+                }
+            }
+        }
+        writeln!(writer, "</tr>")?;
+        Ok(())
+    }
+}
+
+impl Edge {
+    fn write_html(&self, node_id: &str, writer: &mut dyn Write) -> std::io::Result<()> {
+        let class = match self.kind {
+            EdgeKind::Normal => "edge",
+            EdgeKind::Unwind => "edge edge-unwind",
+            EdgeKind::Imaginary => "edge edge-imaginary",
+            EdgeKind::Highlighted => "edge edge-highlighted",
+        };
+        write!(
+            writer,
+            " <a class=\"{}\" href=\"#{}\">{}</a>",
+            class,
+            node_anchor(node_id),
+            node_id
+        )?;
+        if let Some(annotation) = &self.annotation {
+            write!(writer, " ({})", unescape_braces(&escape_html(annotation)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(graph: &Graph) -> String {
+        let mut buffer = Vec::new();
+        graph.write_html(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn nodes_and_edges() {
+        let mut graph = Graph::with_columns(&["statement", "state"]);
+        let mut node = graph.create_node("bb0".to_string());
+        node.add_row_sequence(vec!["x := 1".to_string(), "\\{x\\}".to_string()]);
+        node.build();
+        graph.create_node("bb1".to_string()).build();
+        graph.add_regular_annotated_edge("bb0".to_string(), "bb1".to_string(), "b".to_string());
+        graph.add_unwind_edge("bb0".to_string(), "bb2".to_string());
+        graph.add_exit_edge("bb1".to_string(), "exit".to_string());
+        let html = render(&graph);
+        assert!(html.contains("<details class=\"node\" id=\"nodebb0\" open>"));
+        assert!(html.contains("<details class=\"node\" id=\"nodebb1\" open>"));
+        assert!(html.contains("<div class=\"node exit\" id=\"nodeexit\">exit</div>"));
+        assert!(html.contains(" <a class=\"edge\" href=\"#nodebb1\">bb1</a> (b)"));
+        assert!(html.contains(" <a class=\"edge edge-unwind\" href=\"#nodebb2\">bb2</a>"));
+        assert!(html.contains("<td class=\"col0\">x := 1</td><td class=\"col1\">{x}</td>"));
+        assert!(html.contains("data-column=\"1\" onchange=\"toggleColumn(this)\">state</label>"));
+    }
+
+    #[test]
+    fn map_rows_and_tables() {
+        let mut graph = Graph::with_columns(&["statement", "state"]);
+        let mut node = graph.create_node("bb0".to_string());
+        let mut row = node.create_row();
+        row.set("state", "s".to_string());
+        row.build();
+        node.build();
+        let mut table = graph.create_table("locals", &["name", "type"]);
+        table.add_row(vec!["x".to_string(), "i32".to_string()]);
+        table.build();
+        let html = render(&graph);
+        assert!(html.contains("<td class=\"col0\">n/a</td><td class=\"col1\">s</td>"));
+        assert!(html.contains("<summary>locals</summary>"));
+        assert!(html.contains("<tr><th>name</th><th>type</th></tr>"));
+    }
+}
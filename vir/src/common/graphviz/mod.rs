@@ -3,6 +3,7 @@ use std::io::Write;
 mod builders;
 mod graph;
 mod helpers;
+mod html_writer;
 mod writer;
 
 pub use self::{
@@ -16,5 +17,9 @@ pub trait ToGraphviz {
         let graph = self.to_graph();
         graph.write(writer)
     }
+    fn to_html(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        let graph = self.to_graph();
+        graph.write_html(writer)
+    }
     fn to_graph(&self) -> Graph;
 }
@@ -154,6 +154,13 @@ impl Edge {
                     source, target, annotation
                 )?;
             }
+            EdgeKind::Highlighted => {
+                writeln!(
+                    writer,
+                    "\"{}\" -> \"{}\" [color=green penwidth=2 {}]",
+                    source, target, annotation
+                )?;
+            }
         };
         Ok(())
     }
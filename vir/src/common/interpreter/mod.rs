@@ -0,0 +1,198 @@
+//! The layer-independent part of the reference interpreter for the pure
+//! fragment of VIR (see `Expression::evaluate` and `ProcedureDecl::interpret`
+//! in `vir_high`, `vir_typed`, and `vir_mid`).
+//!
+//! The interpreter executes procedures on concrete inputs. It is meant for
+//! differential testing: the result of interpreting the encoding of a Rust
+//! function is compared with the result of running the compiled function,
+//! which catches bugs in the semantics of the encoding that the verifier
+//! would silently accept.
+
+use std::collections::BTreeMap;
+
+/// A concrete value. All integers are represented as `i128`; values that do
+/// not fit (for example, large `u128` constants) are not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Value {
+    Bool(bool),
+    Int(i128),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Int(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// The values of the variables, indexed by their names.
+pub type Environment = BTreeMap<String, Value>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterpreterError {
+    /// An assertion evaluated to false.
+    AssertionFailed(String),
+    /// An assumption evaluated to false, that is, the inputs do not satisfy
+    /// the precondition of the procedure.
+    AssumptionFailed(String),
+    /// An arithmetic operation produced a value outside of the range of its
+    /// type.
+    Overflow(String),
+    DivisionByZero(String),
+    /// A variable was read before it was assigned (or after it was havocked).
+    UninitializedVariable(String),
+    /// A value has a different kind than required by the operation; this
+    /// indicates an ill-typed procedure.
+    TypeMismatch(String),
+    /// None of the targets of a switch applies.
+    NoApplicableTarget(String),
+    /// The procedure did not terminate within the given number of steps.
+    StepLimitExceeded,
+    /// The construct is not part of the fragment supported by the
+    /// interpreter.
+    Unsupported(String),
+}
+
+impl std::fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InterpreterError::AssertionFailed(assertion) => {
+                write!(f, "assertion failed: {}", assertion)
+            }
+            InterpreterError::AssumptionFailed(assumption) => {
+                write!(f, "assumption failed: {}", assumption)
+            }
+            InterpreterError::Overflow(expression) => write!(f, "overflow in {}", expression),
+            InterpreterError::DivisionByZero(expression) => {
+                write!(f, "division by zero in {}", expression)
+            }
+            InterpreterError::UninitializedVariable(variable) => {
+                write!(f, "variable {} is not initialized", variable)
+            }
+            InterpreterError::TypeMismatch(expression) => {
+                write!(f, "type mismatch in {}", expression)
+            }
+            InterpreterError::NoApplicableTarget(block) => {
+                write!(f, "no target of the switch in {} applies", block)
+            }
+            InterpreterError::StepLimitExceeded => write!(f, "step limit exceeded"),
+            InterpreterError::Unsupported(construct) => {
+                write!(f, "not supported by the interpreter: {}", construct)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InterpreterError {}
+
+pub type InterpreterResult<T> = Result<T, InterpreterError>;
+
+pub fn unsupported<T>(construct: impl std::fmt::Display) -> InterpreterResult<T> {
+    Err(InterpreterError::Unsupported(construct.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Environment, InterpreterError, Value};
+    use crate::{high as vir_high, middle as vir_mid};
+
+    const CLAMP: &str = r#"procedure CoreProof "clamp" {
+    var result: i32;
+    var x: i32;
+    entry bb0;
+    exit bb3;
+    bb0: {
+        switch {
+            (x > 10i32) => bb1,
+            true => bb2,
+        }
+    }
+    bb1: {
+        ghost_assign result := 10i32;
+        goto bb3;
+    }
+    bb2: {
+        ghost_assign result := x;
+        goto bb3;
+    }
+    bb3: {
+        return;
+    }
+}
+"#;
+
+    const DOUBLE: &str = r#"procedure CoreProof "double" {
+    var result: i8;
+    var x: i8;
+    entry bb0;
+    exit bb1;
+    bb0: {
+        old_label pre;
+        ghost_assign result := (x * 2i8);
+        assert ((result / 2i8) == old[pre](x));
+        goto bb1;
+    }
+    bb1: {
+        return;
+    }
+}
+"#;
+
+    fn inputs(x: i128) -> Environment {
+        let mut environment = Environment::new();
+        environment.insert("x".to_string(), Value::Int(x));
+        environment
+    }
+
+    #[test]
+    fn clamp_agrees_with_rust() {
+        fn clamp(x: i32) -> i32 {
+            if x > 10 {
+                10
+            } else {
+                x
+            }
+        }
+        let procedure = vir_high::ProcedureDecl::from_text(CLAMP).unwrap();
+        for x in -20..20 {
+            let environment = procedure.interpret(inputs(x.into()), 100).unwrap();
+            assert_eq!(environment["result"], Value::Int(clamp(x).into()));
+        }
+    }
+
+    #[test]
+    fn double_agrees_with_rust() {
+        let procedure = vir_mid::ProcedureDecl::from_text(DOUBLE).unwrap();
+        for x in i8::MIN..=i8::MAX {
+            let result = procedure.interpret(inputs(x.into()), 100);
+            match x.checked_mul(2) {
+                Some(expected) => {
+                    assert_eq!(result.unwrap()["result"], Value::Int(expected.into()))
+                }
+                None => assert!(matches!(result, Err(InterpreterError::Overflow(_)))),
+            }
+        }
+    }
+
+    #[test]
+    fn failing_assertion() {
+        let text = DOUBLE.replace("(result / 2i8)", "(result / 3i8)");
+        let procedure = vir_high::ProcedureDecl::from_text(&text).unwrap();
+        assert!(procedure.interpret(inputs(0), 100).is_ok());
+        assert!(matches!(
+            procedure.interpret(inputs(3), 100),
+            Err(InterpreterError::AssertionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn uninitialized_variable() {
+        let procedure = vir_high::ProcedureDecl::from_text(CLAMP).unwrap();
+        assert!(matches!(
+            procedure.interpret(Environment::new(), 100),
+            Err(InterpreterError::UninitializedVariable(_))
+        ));
+    }
+}
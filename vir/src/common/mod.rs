@@ -6,5 +6,7 @@ pub mod display;
 pub mod expression;
 pub mod graphviz;
 pub mod identifier;
+pub mod interpreter;
 pub mod position;
 pub mod positions;
+pub mod text;
@@ -0,0 +1,130 @@
+use super::{TextError, TextResult};
+
+/// Symbols ordered so that longer symbols are matched first.
+const SYMBOLS: &[&str] = &[
+    "==>", "==", "!=", "<=", ">=", "&&", "||", ":=", "=>", "(", ")", "{", "}", "[", "]", ",", ":",
+    ";", "<", ">", "+", "-", "*", "/", "%", "!", "?", ".",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// An identifier or a keyword.
+    Identifier(String),
+    /// An integer literal with an optional type suffix such as `i32`.
+    Integer {
+        value: String,
+        suffix: Option<String>,
+    },
+    String(String),
+    /// A line comment without the leading `//`.
+    Comment(String),
+    Symbol(&'static str),
+}
+
+impl Token {
+    /// Checks whether the token is the given symbol or keyword.
+    pub fn is(&self, expected: &str) -> bool {
+        match self {
+            Token::Identifier(identifier) => identifier == expected,
+            Token::Symbol(symbol) => *symbol == expected,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Identifier(identifier) => write!(f, "{}", identifier),
+            Token::Integer { value, suffix } => {
+                write!(f, "{}{}", value, suffix.as_deref().unwrap_or(""))
+            }
+            Token::String(value) => write!(f, "{}", super::quote(value)),
+            Token::Comment(comment) => write!(f, "//{}", comment),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+pub(super) struct LocatedToken {
+    pub(super) token: Token,
+    pub(super) line: usize,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+pub(super) fn tokenize(text: &str) -> TextResult<Vec<LocatedToken>> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let token = if c == '\n' {
+            line += 1;
+            rest = &rest[1..];
+            continue;
+        } else if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        } else if let Some(comment) = rest.strip_prefix("//") {
+            let end = comment.find('\n').unwrap_or(comment.len());
+            let token = Token::Comment(comment[..end].trim().to_string());
+            rest = &comment[end..];
+            token
+        } else if c.is_ascii_digit() {
+            let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let value = rest[..end].to_string();
+            rest = &rest[end..];
+            let end = rest.find(|c: char| !is_identifier_char(c)).unwrap_or(rest.len());
+            let suffix = if end == 0 {
+                None
+            } else {
+                Some(rest[..end].to_string())
+            };
+            rest = &rest[end..];
+            Token::Integer { value, suffix }
+        } else if is_identifier_char(c) {
+            let end = rest.find(|c: char| !is_identifier_char(c)).unwrap_or(rest.len());
+            let token = Token::Identifier(rest[..end].to_string());
+            rest = &rest[end..];
+            token
+        } else if c == '"' {
+            let mut value = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                        _ => {
+                            return Err(TextError {
+                                line,
+                                message: "invalid escape sequence".to_string(),
+                            })
+                        }
+                    },
+                    Some((_, '\n')) | None => {
+                        return Err(TextError {
+                            line,
+                            message: "unterminated string".to_string(),
+                        })
+                    }
+                    Some((_, c)) => value.push(c),
+                }
+            };
+            rest = &rest[end..];
+            Token::String(value)
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            rest = &rest[symbol.len()..];
+            Token::Symbol(*symbol)
+        } else {
+            return Err(TextError {
+                line,
+                message: format!("unexpected character `{}`", c),
+            });
+        };
+        tokens.push(LocatedToken { token, line });
+    }
+    Ok(tokens)
+}
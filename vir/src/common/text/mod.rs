@@ -0,0 +1,234 @@
+//! The layer-independent part of the textual format of VIR procedures: the
+//! lexer and the helpers used by the printers and parsers of `vir_high`,
+//! `vir_typed`, and `vir_mid` (see `ProcedureDecl::to_text` and
+//! `ProcedureDecl::from_text`).
+//!
+//! The format is meant to be written by hand. For example:
+//!
+//! ```text
+//! procedure CoreProof "test" {
+//!     var x: i32;
+//!     entry bb0;
+//!     exit bb1;
+//!     bb0: {
+//!         // x is positive
+//!         assume (x > 0i32);
+//!         assert ((x + 1i32) > 1i32);
+//!         goto bb1;
+//!     }
+//!     bb1: {
+//!         return;
+//!     }
+//! }
+//! ```
+
+mod lexer;
+
+pub use self::lexer::Token;
+use self::lexer::{tokenize, LocatedToken};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextError {
+    /// The line on which the error occurred; 0 if the error is not related
+    /// to a specific line.
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for TextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+pub type TextResult<T> = Result<T, TextError>;
+
+/// An error of the printer: the construct has no textual representation.
+pub fn unsupported<T>(construct: impl std::fmt::Display) -> TextResult<T> {
+    Err(TextError {
+        line: 0,
+        message: format!("not supported by the textual format: {}", construct),
+    })
+}
+
+/// Quotes a string so that it can be read back by `TokenStream::expect_string`.
+pub fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub struct TokenStream {
+    tokens: Vec<LocatedToken>,
+    index: usize,
+}
+
+impl TokenStream {
+    pub fn new(text: &str) -> TextResult<Self> {
+        Ok(Self {
+            tokens: tokenize(text)?,
+            index: 0,
+        })
+    }
+
+    pub fn peek(&self) -> Option<&Token> {
+        self.peek_nth(0)
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.index + n).map(|token| &token.token)
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.index == self.tokens.len()
+    }
+
+    pub fn next(&mut self) -> TextResult<Token> {
+        if let Some(token) = self.tokens.get(self.index) {
+            self.index += 1;
+            Ok(token.token.clone())
+        } else {
+            self.error("unexpected end of input")
+        }
+    }
+
+    /// Consumes the next token if it is the given symbol or keyword.
+    pub fn accept(&mut self, expected: &str) -> bool {
+        if self.peek().map_or(false, |token| token.is(expected)) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, expected: &str) -> TextResult<()> {
+        if self.accept(expected) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", expected))
+        }
+    }
+
+    pub fn expect_identifier(&mut self) -> TextResult<String> {
+        match self.peek() {
+            Some(Token::Identifier(identifier)) => {
+                let identifier = identifier.clone();
+                self.index += 1;
+                Ok(identifier)
+            }
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    pub fn expect_string(&mut self) -> TextResult<String> {
+        match self.peek() {
+            Some(Token::String(value)) => {
+                let value = value.clone();
+                self.index += 1;
+                Ok(value)
+            }
+            _ => self.error("expected a string"),
+        }
+    }
+
+    /// Reports an error at the current token.
+    pub fn error<T>(&self, message: impl Into<String>) -> TextResult<T> {
+        let (line, found) = if let Some(token) = self.tokens.get(self.index) {
+            (token.line, format!("`{}`", token.token))
+        } else {
+            (
+                self.tokens.last().map_or(0, |token| token.line),
+                "end of input".to_string(),
+            )
+        };
+        Err(TextError {
+            line,
+            message: format!("{}, found {}", message.into(), found),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{high as vir_high, middle as vir_mid};
+
+    const HIGH_PROCEDURE: &str = r#"procedure CoreProof "test" {
+    var b: bool;
+    var x: i32;
+    entry bb0;
+    exit bb2;
+    bb0: {
+        // x is positive
+        assume (x > 0i32);
+        ghost_assign b := ((x + 1i32) > -1i32);
+        switch {
+            b => bb1,
+            true => bb2,
+        }
+    }
+    bb1: {
+        old_label l;
+        assert ((x == old[l](x)) ==> !(false));
+        goto bb2;
+    }
+    bb2: {
+        return;
+    }
+}
+"#;
+
+    const MIDDLE_PROCEDURE: &str = r#"procedure Both "test" {
+    var x: u64;
+    entry bb0;
+    exit bb1;
+    bb0: {
+        ghost_havoc x;
+        assert<bb0, !bb1> (x != 18446744073709551615u64);
+        nondet bb1, bb1;
+    }
+    bb1: {
+        return;
+    }
+}
+"#;
+
+    #[test]
+    fn high_round_trip() {
+        let procedure = vir_high::ProcedureDecl::from_text(HIGH_PROCEDURE).unwrap();
+        let text = procedure.to_text().unwrap();
+        assert_eq!(text, HIGH_PROCEDURE);
+        assert_eq!(vir_high::ProcedureDecl::from_text(&text).unwrap(), procedure);
+    }
+
+    #[test]
+    fn middle_round_trip() {
+        let procedure = vir_mid::ProcedureDecl::from_text(MIDDLE_PROCEDURE).unwrap();
+        let text = procedure.to_text().unwrap();
+        assert_eq!(text, MIDDLE_PROCEDURE);
+        assert_eq!(vir_mid::ProcedureDecl::from_text(&text).unwrap(), procedure);
+    }
+
+    #[test]
+    fn precedence() {
+        let text = HIGH_PROCEDURE.replace(
+            "assume (x > 0i32);",
+            "assume x > 0i32 && x + 2i32 * x < 10i32 ==> b;",
+        );
+        let procedure = vir_high::ProcedureDecl::from_text(&text).unwrap();
+        let text = procedure.to_text().unwrap();
+        assert!(text.contains("assume (((x > 0i32) && ((x + (2i32 * x)) < 10i32)) ==> b);"));
+    }
+
+    #[test]
+    fn undeclared_variable() {
+        let text = HIGH_PROCEDURE.replace("    var x: i32;\n", "");
+        let error = vir_high::ProcedureDecl::from_text(&text).unwrap_err();
+        assert_eq!(error.line, 7);
+        assert!(error.message.starts_with("undeclared variable `x`"));
+    }
+}
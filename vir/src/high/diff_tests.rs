@@ -0,0 +1,63 @@
+//! Tests of `ProcedureDecl::diff`.
+
+use crate::high as vir_high;
+
+const PROCEDURE: &str = r#"procedure CoreProof "test" {
+    var b: bool;
+    var x: i32;
+    entry bb0;
+    exit bb2;
+    bb0: {
+        assume (x > 0i32);
+        ghost_assign b := (x > 1i32);
+        goto bb1;
+    }
+    bb1: {
+        assert (x > 0i32);
+        goto bb2;
+    }
+    bb2: {
+        return;
+    }
+}
+"#;
+
+fn diff(before: &str, after: &str) -> String {
+    let before = vir_high::ProcedureDecl::from_text(before).unwrap();
+    let after = vir_high::ProcedureDecl::from_text(after).unwrap();
+    before.diff(&after)
+}
+
+#[test]
+fn equal_procedures() {
+    assert_eq!(diff(PROCEDURE, PROCEDURE), "");
+}
+
+#[test]
+fn changed_statement() {
+    let after = PROCEDURE.replace("assume (x > 0i32);", "assume (x > 5i32);");
+    let diff = diff(PROCEDURE, &after);
+    let lines: Vec<_> = diff.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", diff);
+    assert_eq!(lines[0], "block bb0:");
+    assert!(lines[1].starts_with("  - "), "{}", diff);
+    assert!(lines[2].starts_with("  + "), "{}", diff);
+}
+
+#[test]
+fn added_and_removed_blocks() {
+    let after = PROCEDURE
+        .replace("goto bb1;", "goto bb3;")
+        .replace("    bb1: {", "    bb3: {");
+    let diff = diff(PROCEDURE, &after);
+    assert!(diff.contains("block bb0:\n  successor: goto bb1 -> goto bb3\n"), "{}", diff);
+    assert!(diff.contains("block bb1 (removed):\n  - "), "{}", diff);
+    assert!(diff.contains("block bb3 (added):\n  + "), "{}", diff);
+    assert!(diff.ends_with("  successor: goto bb2\n"), "{}", diff);
+}
+
+#[test]
+fn changed_exit() {
+    let after = PROCEDURE.replace("exit bb2;", "exit bb1;");
+    assert_eq!(diff(PROCEDURE, &after), "exit: bb2 -> bb1\n");
+}
@@ -0,0 +1,292 @@
+//! A generator of random well-typed procedures used for fuzzing the lowerings
+//! of the core proof.
+//!
+//! The generated procedures use only ghost variables of primitive types and
+//! have an acyclic control flow graph, so every generated procedure should be
+//! accepted by the fold-unfold inference and the lowering to `vir_low`. The
+//! generator is deterministic: the same seed always produces the same
+//! procedure.
+
+use crate::{
+    common::{check_mode::CheckMode, position::Position},
+    high::{
+        self as vir_high, expression::ConstantValue, ty::Int, Assert, Assume, BinaryOp,
+        BinaryOpKind, Comment, Conditional, Constant, GhostAssign, GhostHavoc, LabelledOld, Local,
+        OldLabel, UnaryOp, UnaryOpKind,
+    },
+};
+use std::collections::BTreeMap;
+
+/// The types of the generated variables.
+const TYPES: &[vir_high::Type] = &[
+    vir_high::Type::Bool,
+    vir_high::Type::Int(Int::I32),
+    vir_high::Type::Int(Int::U32),
+    vir_high::Type::Int(Int::Usize),
+];
+
+/// The maximum depth of the generated expressions.
+const MAX_DEPTH: u32 = 3;
+
+pub struct ProcedureGenerator {
+    /// The state of the pseudo-random number generator (SplitMix64).
+    state: u64,
+    /// The position of all generated statements and expressions.
+    position: Position,
+    variables: Vec<vir_high::VariableDecl>,
+    /// The old labels defined in the current block before the current
+    /// statement.
+    old_labels: Vec<String>,
+    old_label_counter: usize,
+}
+
+impl ProcedureGenerator {
+    pub fn new(seed: u64, position: Position) -> Self {
+        Self {
+            state: seed,
+            position,
+            variables: Vec::new(),
+            old_labels: Vec::new(),
+            old_label_counter: 0,
+        }
+    }
+
+    /// Generates a procedure with between 2 and 8 blocks. The last block is
+    /// the exit block and the successors of each other block are blocks with
+    /// larger indices.
+    pub fn generate(&mut self, name: String) -> vir_high::ProcedureDecl {
+        let variable_count = self.below(6) + 1;
+        let variables = (0..variable_count)
+            .map(|index| vir_high::VariableDecl {
+                name: format!("v{}", index),
+                ty: self.choose(TYPES).clone(),
+            })
+            .collect();
+        self.variables = variables;
+        let block_count = self.below(7) + 2;
+        let label = |index: usize| vir_high::BasicBlockId::new(format!("bb{}", index));
+        let mut basic_blocks = BTreeMap::new();
+        for index in 0..block_count {
+            self.old_labels.clear();
+            let statements = (0..self.below(6))
+                .map(|_| self.generate_statement())
+                .collect();
+            let successor = if index + 1 == block_count {
+                vir_high::Successor::Exit
+            } else {
+                let first = label(index + 1 + self.below(block_count - index - 1));
+                let second = label(index + 1 + self.below(block_count - index - 1));
+                match self.below(3) {
+                    0 => vir_high::Successor::Goto(first),
+                    1 => vir_high::Successor::GotoSwitch(vec![
+                        (self.generate_expression(&vir_high::Type::Bool, 0), first),
+                        (self.constant(ConstantValue::Bool(true), vir_high::Type::Bool), second),
+                    ]),
+                    _ => vir_high::Successor::NonDetChoice(first, second),
+                }
+            };
+            basic_blocks.insert(
+                label(index),
+                vir_high::BasicBlock {
+                    statements,
+                    successor,
+                },
+            );
+        }
+        vir_high::ProcedureDecl {
+            name,
+            check_mode: CheckMode::CoreProof,
+            entry: label(0),
+            exit: label(block_count - 1),
+            basic_blocks,
+        }
+    }
+
+    fn generate_statement(&mut self) -> vir_high::Statement {
+        let position = self.position;
+        match self.below(6) {
+            0 => vir_high::Statement::Comment(Comment {
+                comment: "generated".to_string(),
+            }),
+            1 => {
+                let name = format!("l{}", self.old_label_counter);
+                self.old_label_counter += 1;
+                self.old_labels.push(name.clone());
+                vir_high::Statement::OldLabel(OldLabel { name, position })
+            }
+            2 => vir_high::Statement::Assume(Assume {
+                expression: self.generate_expression(&vir_high::Type::Bool, 0),
+                position,
+            }),
+            3 => vir_high::Statement::Assert(Assert {
+                expression: self.generate_expression(&vir_high::Type::Bool, 0),
+                position,
+            }),
+            4 => vir_high::Statement::GhostHavoc(GhostHavoc {
+                variable: self.choose_variable(),
+                position,
+            }),
+            _ => {
+                let variable = self.choose_variable();
+                let value = self.generate_expression(&variable.ty, 0);
+                vir_high::Statement::GhostAssign(GhostAssign {
+                    target: self.local(variable),
+                    value,
+                    position,
+                })
+            }
+        }
+    }
+
+    fn generate_expression(&mut self, ty: &vir_high::Type, depth: u32) -> vir_high::Expression {
+        let candidates: Vec<_> = self
+            .variables
+            .iter()
+            .filter(|variable| &variable.ty == ty)
+            .cloned()
+            .collect();
+        let position = self.position;
+        let choice = if depth == MAX_DEPTH {
+            self.below(2)
+        } else {
+            self.below(6)
+        };
+        match choice {
+            0 => self.generate_constant(ty),
+            1 if !candidates.is_empty() => {
+                let variable = self.choose(&candidates).clone();
+                self.local(variable)
+            }
+            2 if !self.old_labels.is_empty() => {
+                let label = self.old_labels[self.below(self.old_labels.len())].clone();
+                vir_high::Expression::LabelledOld(LabelledOld {
+                    label,
+                    base: Box::new(self.generate_expression(ty, depth + 1)),
+                    position,
+                })
+            }
+            3 => vir_high::Expression::Conditional(Conditional {
+                guard: Box::new(self.generate_expression(&vir_high::Type::Bool, depth + 1)),
+                then_expr: Box::new(self.generate_expression(ty, depth + 1)),
+                else_expr: Box::new(self.generate_expression(ty, depth + 1)),
+                position,
+            }),
+            4 | 5 if ty == &vir_high::Type::Bool => {
+                if self.below(4) == 0 {
+                    return vir_high::Expression::UnaryOp(UnaryOp {
+                        op_kind: UnaryOpKind::Not,
+                        argument: Box::new(self.generate_expression(ty, depth + 1)),
+                        position,
+                    });
+                }
+                let (op_kind, operand_ty) = if choice == 4 {
+                    let op_kind = *self.choose(&[
+                        BinaryOpKind::And,
+                        BinaryOpKind::Or,
+                        BinaryOpKind::Implies,
+                        BinaryOpKind::EqCmp,
+                    ]);
+                    (op_kind, vir_high::Type::Bool)
+                } else {
+                    let op_kind = *self.choose(&[
+                        BinaryOpKind::EqCmp,
+                        BinaryOpKind::NeCmp,
+                        BinaryOpKind::GtCmp,
+                        BinaryOpKind::GeCmp,
+                        BinaryOpKind::LtCmp,
+                        BinaryOpKind::LeCmp,
+                    ]);
+                    (op_kind, self.choose(&TYPES[1..]).clone())
+                };
+                self.binary_op(op_kind, &operand_ty, depth)
+            }
+            4 | 5 => {
+                let op_kind =
+                    *self.choose(&[BinaryOpKind::Add, BinaryOpKind::Sub, BinaryOpKind::Mul]);
+                self.binary_op(op_kind, ty, depth)
+            }
+            _ => self.generate_constant(ty),
+        }
+    }
+
+    fn binary_op(
+        &mut self,
+        op_kind: BinaryOpKind,
+        operand_ty: &vir_high::Type,
+        depth: u32,
+    ) -> vir_high::Expression {
+        vir_high::Expression::BinaryOp(BinaryOp {
+            op_kind,
+            left: Box::new(self.generate_expression(operand_ty, depth + 1)),
+            right: Box::new(self.generate_expression(operand_ty, depth + 1)),
+            position: self.position,
+        })
+    }
+
+    fn generate_constant(&mut self, ty: &vir_high::Type) -> vir_high::Expression {
+        // Small values are more likely to trigger corner cases.
+        let value = self.below(5) as i64;
+        let value = match ty {
+            vir_high::Type::Bool => ConstantValue::Bool(value % 2 == 0),
+            vir_high::Type::Int(Int::Usize) => ConstantValue::BigInt(value.to_string()),
+            vir_high::Type::Int(Int::I32) if self.below(2) == 0 => ConstantValue::Int(-value),
+            _ => ConstantValue::Int(value),
+        };
+        self.constant(value, ty.clone())
+    }
+
+    fn constant(&self, value: ConstantValue, ty: vir_high::Type) -> vir_high::Expression {
+        vir_high::Expression::Constant(Constant {
+            value,
+            ty,
+            position: self.position,
+        })
+    }
+
+    fn local(&self, variable: vir_high::VariableDecl) -> vir_high::Expression {
+        vir_high::Expression::Local(Local {
+            variable,
+            position: self.position,
+        })
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn choose<'a, T>(&mut self, elements: &'a [T]) -> &'a T {
+        &elements[self.below(elements.len())]
+    }
+
+    fn choose_variable(&mut self) -> vir_high::VariableDecl {
+        self.variables[self.below(self.variables.len())].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProcedureGenerator;
+    use crate::common::position::Position;
+
+    #[test]
+    fn generated_procedures_are_well_formed() {
+        let position = Position::new(1, 1, 1);
+        for seed in 0..100 {
+            let procedure =
+                ProcedureGenerator::new(seed, position).generate(format!("fuzzing_{}", seed));
+            procedure.assert_well_formed();
+            let again =
+                ProcedureGenerator::new(seed, position).generate(format!("fuzzing_{}", seed));
+            assert_eq!(procedure, again);
+        }
+    }
+}
@@ -1,5 +1,11 @@
 pub mod builders;
+pub mod fuzzing;
 pub mod operations;
 
 pub use self::operations::ty::Generic;
 pub use crate::gen::high::*;
+
+#[cfg(test)]
+mod diff_tests;
+#[cfg(test)]
+mod validation_tests;
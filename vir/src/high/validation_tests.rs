@@ -0,0 +1,104 @@
+//! Tests of `ProcedureDecl::assert_well_formed`.
+
+use crate::{
+    common::position::Position,
+    high::{self as vir_high, GhostHavoc},
+};
+
+const PROCEDURE: &str = r#"procedure CoreProof "test" {
+    var b: bool;
+    var x: i32;
+    entry bb0;
+    exit bb2;
+    bb0: {
+        // x is positive
+        assume (x > 0i32);
+        ghost_assign b := ((x + 1i32) > -1i32);
+        switch {
+            b => bb1,
+            true => bb2,
+        }
+    }
+    bb1: {
+        old_label l;
+        assert ((x == old[l](x)) ==> !(false));
+        goto bb2;
+    }
+    bb2: {
+        return;
+    }
+}
+"#;
+
+/// Parses the procedure and gives positions to all its statements and
+/// expressions, which the parser leaves default.
+fn parse(text: &str) -> vir_high::ProcedureDecl {
+    let position = Position::new(1, 1, 1);
+    let mut procedure = vir_high::ProcedureDecl::from_text(text).unwrap();
+    for block in procedure.basic_blocks.values_mut() {
+        block.statements = std::mem::take(&mut block.statements)
+            .into_iter()
+            .map(|statement| statement.set_default_position(position))
+            .collect();
+        if let vir_high::Successor::GotoSwitch(targets) = &mut block.successor {
+            for (test, _) in targets {
+                *test = test.clone().set_default_position(position);
+            }
+        }
+    }
+    procedure
+}
+
+#[test]
+fn well_formed() {
+    parse(PROCEDURE).assert_well_formed();
+}
+
+#[test]
+#[should_panic(expected = "the statement has no position")]
+fn missing_position() {
+    vir_high::ProcedureDecl::from_text(PROCEDURE)
+        .unwrap()
+        .assert_well_formed();
+}
+
+#[test]
+#[should_panic(expected = "successor of bb1: unknown label bb3")]
+fn unknown_successor() {
+    parse(&PROCEDURE.replace("goto bb2;", "goto bb3;")).assert_well_formed();
+}
+
+#[test]
+#[should_panic(expected = "exit: unknown label bb3")]
+fn unknown_exit() {
+    parse(&PROCEDURE.replace("exit bb2;", "exit bb3;")).assert_well_formed();
+}
+
+#[test]
+#[should_panic(expected = "have different types")]
+fn comparison_of_different_types() {
+    parse(&PROCEDURE.replace("assume (x > 0i32);", "assume (x == b);")).assert_well_formed();
+}
+
+#[test]
+#[should_panic(expected = "instead of Bool")]
+fn non_boolean_operand() {
+    parse(&PROCEDURE.replace("assume (x > 0i32);", "assume (x && b);")).assert_well_formed();
+}
+
+#[test]
+#[should_panic(expected = "the variable x is used with types")]
+fn variable_with_different_types() {
+    let mut procedure = parse(PROCEDURE);
+    let block = procedure
+        .basic_blocks
+        .get_mut(&vir_high::BasicBlockId::new("bb1".to_string()))
+        .unwrap();
+    block
+        .statements
+        .push(vir_high::Statement::GhostHavoc(GhostHavoc {
+            variable: vir_high::VariableDecl::new("x", vir_high::Type::Bool),
+            position: Position::new(1, 1, 1),
+        }));
+    procedure.assert_well_formed();
+}